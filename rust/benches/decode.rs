@@ -0,0 +1,173 @@
+//! Decode throughput benchmarks.
+//!
+//! Run with `cargo bench --bench decode` (add `--features parallel` to
+//! include the parallel batch decoder).
+//!
+//! To check a change for regressions, save a baseline on the base branch
+//! and compare against it on the change:
+//!
+//! ```bash
+//! cargo bench --bench decode -- --save-baseline main
+//! cargo bench --bench decode -- --baseline main
+//! ```
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use k256_sdk::ws::{decode_message, decode_message_borrowed, encode_fee_market, DecodedMessageRef, Decoder};
+use k256_sdk::{AccountFee, FeeMarket, MessageType, NetworkState, Pubkey, Slot, TimestampMs};
+
+/// Pool update batch sizes, from a quiet slot to a snapshot-sized burst.
+const BATCH_SIZES: [u16; 4] = [10, 100, 500, 1000];
+
+/// Fee market account counts.
+const FEE_ACCOUNTS: [usize; 2] = [100, 1000];
+
+/// Binary pool update payload (without the type byte) with a 2-token pool
+/// and `state_len` bytes of serialized state.
+fn pool_update_payload(slot: u64, state_len: usize) -> Vec<u8> {
+    let mut out = Vec::new();
+    out.extend_from_slice(&(state_len as u64).to_le_bytes());
+    out.extend(std::iter::repeat(0xAB).take(state_len));
+    out.extend_from_slice(&slot.to_le_bytes()); // sequence
+    out.extend_from_slice(&slot.to_le_bytes()); // slot
+    out.extend_from_slice(&1u64.to_le_bytes()); // write_version
+    let name = b"Whirlpool";
+    out.extend_from_slice(&(name.len() as u64).to_le_bytes());
+    out.extend_from_slice(name);
+    out.extend_from_slice(&[slot as u8; 32]); // pool_address
+    out.extend_from_slice(&2u64.to_le_bytes());
+    out.extend_from_slice(&[1; 32]);
+    out.extend_from_slice(&[2; 32]);
+    out.extend_from_slice(&2u64.to_le_bytes());
+    out.extend_from_slice(&1_000_000u64.to_le_bytes());
+    out.extend_from_slice(&2_000_000u64.to_le_bytes());
+    out.extend_from_slice(&2u64.to_le_bytes());
+    out.extend_from_slice(&9i32.to_le_bytes());
+    out.extend_from_slice(&6i32.to_le_bytes());
+    out.push(0); // best_bid
+    out.push(0); // best_ask
+    out
+}
+
+fn batch_payload(count: u16, state_len: usize) -> Vec<u8> {
+    let mut out = count.to_le_bytes().to_vec();
+    for slot in 0..count as u64 {
+        let entry = pool_update_payload(slot, state_len);
+        out.extend_from_slice(&(entry.len() as u32).to_le_bytes());
+        out.extend_from_slice(&entry);
+    }
+    out
+}
+
+/// Binary fee market payload (without the type byte) with `accounts`
+/// per-account entries.
+fn fee_market_payload(accounts: usize) -> Vec<u8> {
+    let fees = FeeMarket {
+        slot: Slot(300_000_000),
+        timestamp_ms: TimestampMs(1_700_000_000_000),
+        recommended: 50_000,
+        state: NetworkState::Normal,
+        is_stale: false,
+        block_utilization_pct: 72.5,
+        blocks_in_window: 50,
+        accounts: (0..accounts)
+            .map(|i| AccountFee {
+                pubkey: Pubkey::new([i as u8; 32]),
+                total_txs: 120,
+                active_slots: 40,
+                cu_consumed: 12_000_000,
+                utilization_pct: 31.0,
+                p25: 1_000,
+                p50: 5_000,
+                p75: 20_000,
+                p90: 80_000,
+                min_nonzero_price: 100,
+            })
+            .collect(),
+    };
+    encode_fee_market(&fees)[1..].to_vec()
+}
+
+fn price_batch_payload(count: u16) -> Vec<u8> {
+    let mut out = count.to_le_bytes().to_vec();
+    for i in 0..count as u64 {
+        out.extend_from_slice(&[i as u8; 32]);
+        out.extend_from_slice(&(150 * 1_000_000_000_000u64).to_le_bytes());
+        out.extend_from_slice(&i.to_le_bytes());
+        out.extend_from_slice(&i.to_le_bytes());
+    }
+    out
+}
+
+fn bench_pool_update(c: &mut Criterion) {
+    let payload = pool_update_payload(1, 653);
+    let msg_type = MessageType::PoolUpdate as u8;
+    let mut group = c.benchmark_group("pool_update");
+    group.throughput(Throughput::Bytes(payload.len() as u64));
+    group.bench_function("owned", |b| b.iter(|| decode_message(msg_type, black_box(&payload))));
+    group.bench_function("borrowed", |b| {
+        b.iter(|| decode_message_borrowed(msg_type, black_box(&payload)))
+    });
+    group.finish();
+}
+
+fn bench_pool_update_batch(c: &mut Criterion) {
+    let msg_type = MessageType::PoolUpdateBatch as u8;
+    let mut group = c.benchmark_group("pool_update_batch");
+    for size in BATCH_SIZES {
+        let payload = batch_payload(size, 653);
+        group.throughput(Throughput::Elements(size as u64));
+        group.bench_with_input(BenchmarkId::new("owned", size), &payload, |b, payload| {
+            b.iter(|| decode_message(msg_type, black_box(payload)))
+        });
+        group.bench_with_input(BenchmarkId::new("borrowed", size), &payload, |b, payload| {
+            b.iter(|| {
+                let Ok(Some(DecodedMessageRef::PoolUpdateBatch(batch))) =
+                    decode_message_borrowed(msg_type, black_box(payload))
+                else {
+                    unreachable!()
+                };
+                batch.iter().map(|update| update.unwrap().slot.get()).sum::<u64>()
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("reused", size), &payload, |b, payload| {
+            let mut decoder = Decoder::new();
+            let mut message = None;
+            b.iter(|| decoder.decode_into(msg_type, black_box(payload), &mut message))
+        });
+        #[cfg(feature = "parallel")]
+        group.bench_with_input(BenchmarkId::new("parallel", size), &payload, |b, payload| {
+            b.iter(|| k256_sdk::ws::decode_message_parallel(msg_type, black_box(payload), 64, &Default::default()))
+        });
+    }
+    group.finish();
+}
+
+fn bench_fee_market(c: &mut Criterion) {
+    let msg_type = MessageType::PriorityFees as u8;
+    let mut group = c.benchmark_group("fee_market");
+    for accounts in FEE_ACCOUNTS {
+        let payload = fee_market_payload(accounts);
+        group.throughput(Throughput::Elements(accounts as u64));
+        group.bench_with_input(BenchmarkId::new("owned", accounts), &payload, |b, payload| {
+            b.iter(|| decode_message(msg_type, black_box(payload)))
+        });
+        group.bench_with_input(BenchmarkId::new("reused", accounts), &payload, |b, payload| {
+            let mut decoder = Decoder::new();
+            let mut message = None;
+            b.iter(|| decoder.decode_into(msg_type, black_box(payload), &mut message))
+        });
+    }
+    group.finish();
+}
+
+fn bench_price_batch(c: &mut Criterion) {
+    let payload = price_batch_payload(1000);
+    let msg_type = MessageType::PriceBatch as u8;
+    let mut group = c.benchmark_group("price_batch_1000");
+    group.throughput(Throughput::Elements(1000));
+    group.bench_function("owned", |b| b.iter(|| decode_message(msg_type, black_box(&payload))));
+    group.finish();
+}
+
+criterion_group!(benches, bench_pool_update, bench_pool_update_batch, bench_fee_market, bench_price_batch);
+criterion_main!(benches);
@@ -7,7 +7,7 @@ use k256_sdk::{Config, K256WebSocketClient, SubscribeRequest};
 use std::env;
 
 #[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
+async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     // Initialize logging
     tracing_subscriber::fmt::init();
 
@@ -24,7 +24,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let client = K256WebSocketClient::new(config);
 
     // Handle pool updates
-    client.on_pool_update(|update| {
+    let _pool_updates = client.on_pool_update(|update| {
         println!("[Pool Update] {} (slot {})", update.pool_address, update.slot);
         println!("  Protocol: {}", update.protocol_name);
         println!("  Tokens: {:?}", update.token_mints);
@@ -38,7 +38,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     });
 
     // Handle fee market updates
-    client.on_fee_market(|fees| {
+    let _fee_markets = client.on_fee_market(|fees| {
         println!(
             "[Fee Market] slot={}, recommended={} microlamports",
             fees.slot, fees.recommended
@@ -51,7 +51,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     });
 
     // Handle blockhash updates
-    client.on_blockhash(|bh| {
+    let _blockhashes = client.on_blockhash(|bh| {
         println!("[Blockhash] {} (slot {})", bh.blockhash, bh.slot);
         println!(
             "  Block height: {}, Last valid: {}, Stale: {}",
@@ -60,7 +60,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     });
 
     // Handle heartbeats
-    client.on_heartbeat(|hb| {
+    let _heartbeats = client.on_heartbeat(|hb| {
         println!(
             "[Heartbeat] uptime={}s, msgs_recv={}, msgs_sent={}, subs={}",
             hb.uptime_seconds, hb.messages_received, hb.messages_sent, hb.subscriptions
@@ -68,7 +68,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     });
 
     // Handle errors
-    client.on_error(|err| {
+    let _errors = client.on_error(|err| {
         eprintln!("[Error] {}", err);
     });
 
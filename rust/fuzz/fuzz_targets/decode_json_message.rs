@@ -0,0 +1,10 @@
+#![no_main]
+
+use k256_sdk::ws::decode_json_message;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(text) = std::str::from_utf8(data) {
+        let _ = decode_json_message(text);
+    }
+});
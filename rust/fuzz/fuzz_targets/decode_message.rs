@@ -0,0 +1,10 @@
+#![no_main]
+
+use k256_sdk::ws::decode_message;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    if let Some((&msg_type, payload)) = data.split_first() {
+        let _ = decode_message(msg_type, payload);
+    }
+});
@@ -0,0 +1,25 @@
+#![no_main]
+
+use k256_sdk::ws::{decode_message_borrowed, DecodedMessageRef};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let Some((&msg_type, payload)) = data.split_first() else {
+        return;
+    };
+    if let Ok(Some(decoded)) = decode_message_borrowed(msg_type, payload) {
+        // Walk every lazily decoded field
+        match &decoded {
+            DecodedMessageRef::PoolUpdate(update) => {
+                let _ = (update.token_mints().count(), update.token_balances().sum::<u64>());
+            }
+            DecodedMessageRef::PoolUpdateBatch(batch) | DecodedMessageRef::PoolSnapshot(batch) => {
+                for update in batch.iter().flatten() {
+                    let _ = update.token_decimals().count();
+                }
+            }
+            DecodedMessageRef::Owned(_) => {}
+        }
+        let _ = decoded.to_owned();
+    }
+});
@@ -0,0 +1,123 @@
+/*
+ * C ABI of the K256 Rust SDK (`ffi` feature).
+ *
+ * Build the library with `cargo rustc --release --features ffi --crate-type cdylib`
+ * (or `staticlib`). Pointers inside callback structs borrow from the message and are
+ * only valid until the callback returns. Callbacks run on SDK threads. Strings are
+ * UTF-8 pointer/length pairs without a trailing NUL, except k256_last_error().
+ *
+ * Keep in sync with src/ffi.rs.
+ */
+#ifndef K256_H
+#define K256_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#define K256_OK 0
+#define K256_ERR_ARGUMENT (-1)
+#define K256_ERR_CLIENT (-2)
+
+typedef struct K256FfiClient K256FfiClient;
+
+typedef struct K256OrderLevel {
+    uint64_t price;
+    uint64_t size;
+} K256OrderLevel;
+
+typedef struct K256PoolUpdate {
+    uint64_t sequence;
+    uint64_t slot;
+    uint64_t write_version;
+    const uint8_t *protocol_name;
+    size_t protocol_name_len;
+    uint8_t pool_address[32];
+    size_t token_count;
+    const uint8_t (*token_mints)[32];
+    const uint64_t *token_balances;
+    const int32_t *token_decimals;
+    bool has_best_bid;
+    K256OrderLevel best_bid;
+    bool has_best_ask;
+    K256OrderLevel best_ask;
+    const uint8_t *serialized_state;
+    size_t serialized_state_len;
+} K256PoolUpdate;
+
+typedef struct K256AccountFee {
+    uint8_t pubkey[32];
+    uint32_t total_txs;
+    uint32_t active_slots;
+    uint64_t cu_consumed;
+    float utilization_pct;
+    uint64_t p25;
+    uint64_t p50;
+    uint64_t p75;
+    uint64_t p90;
+    uint64_t min_nonzero_price;
+} K256AccountFee;
+
+typedef struct K256FeeMarket {
+    uint64_t slot;
+    uint64_t timestamp_ms;
+    uint64_t recommended;
+    uint8_t state; /* 0 low, 1 normal, 2 high, 3 extreme */
+    bool is_stale;
+    float block_utilization_pct;
+    uint32_t blocks_in_window;
+    const K256AccountFee *accounts;
+    size_t account_count;
+} K256FeeMarket;
+
+typedef struct K256Blockhash {
+    uint64_t slot;
+    uint64_t timestamp_ms;
+    const uint8_t *blockhash; /* base58 */
+    size_t blockhash_len;
+    uint64_t block_height;
+    uint64_t last_valid_block_height;
+    bool is_stale;
+} K256Blockhash;
+
+typedef struct K256PriceEntry {
+    uint8_t mint[32];
+    double usd_price;
+    uint64_t slot;
+    uint64_t timestamp_ms;
+} K256PriceEntry;
+
+typedef void (*K256PoolUpdateCallback)(const K256PoolUpdate *update, void *user_data);
+typedef void (*K256FeeMarketCallback)(const K256FeeMarket *fees, void *user_data);
+typedef void (*K256BlockhashCallback)(const K256Blockhash *blockhash, void *user_data);
+typedef void (*K256PricesCallback)(const K256PriceEntry *entries, size_t count, void *user_data);
+typedef void (*K256ErrorCallback)(const uint8_t *message, size_t len, void *user_data);
+
+/* Last error on the calling thread, NUL-terminated, or NULL. */
+const char *k256_last_error(void);
+
+/* Returns NULL on error. `endpoint` may be NULL for the default gateway. */
+K256FfiClient *k256_client_new(const char *api_key, const char *endpoint);
+void k256_client_free(K256FfiClient *client);
+
+int32_t k256_client_connect(const K256FfiClient *client);
+/* `request_json` overrides fields of the default subscription; NULL for the default. */
+int32_t k256_client_subscribe(const K256FfiClient *client, const char *request_json);
+int32_t k256_client_unsubscribe(const K256FfiClient *client);
+
+/* Callbacks run on SDK threads; a NULL callback returns K256_ERR_ARGUMENT. */
+int32_t k256_client_on_pool_update(const K256FfiClient *client, K256PoolUpdateCallback callback, void *user_data);
+int32_t k256_client_on_fee_market(const K256FfiClient *client, K256FeeMarketCallback callback, void *user_data);
+int32_t k256_client_on_blockhash(const K256FfiClient *client, K256BlockhashCallback callback, void *user_data);
+int32_t k256_client_on_prices(const K256FfiClient *client, K256PricesCallback callback, void *user_data);
+int32_t k256_client_on_error(const K256FfiClient *client, K256ErrorCallback callback, void *user_data);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* K256_H */
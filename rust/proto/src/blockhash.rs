@@ -0,0 +1,50 @@
+//! Recent blockhashes.
+
+use crate::error::Error;
+use crate::read::{read_bytes, read_u64, read_u8};
+
+/// Payload length.
+const LEN: usize = 65;
+
+/// Blockhash payload (`MessageType::Blockhash`), borrowed from the frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Blockhash<'a> {
+    /// Solana slot of the blockhash
+    pub slot: u64,
+    /// Unix timestamp in milliseconds
+    pub timestamp_ms: u64,
+    /// Raw blockhash
+    pub blockhash: &'a [u8; 32],
+    /// Block height
+    pub block_height: u64,
+    /// Last block height at which transactions using it are valid
+    pub last_valid_block_height: u64,
+    /// Whether the data is stale
+    pub is_stale: bool,
+}
+
+impl<'a> Blockhash<'a> {
+    /// Parse a blockhash payload.
+    pub fn parse(data: &'a [u8]) -> Result<Self, Error> {
+        if data.len() < LEN {
+            return Err(Error::PayloadTooShort {
+                expected: LEN,
+                actual: data.len(),
+            });
+        }
+        let mut offset = 0;
+        Ok(Self {
+            slot: read_u64(data, &mut offset)?,
+            timestamp_ms: read_u64(data, &mut offset)?,
+            blockhash: read_bytes(data, &mut offset)?,
+            block_height: read_u64(data, &mut offset)?,
+            last_valid_block_height: read_u64(data, &mut offset)?,
+            is_stale: read_u8(data, &mut offset)? != 0,
+        })
+    }
+
+    /// Bytes of the payload covered by the known fields.
+    pub fn encoded_len(&self) -> usize {
+        LEN
+    }
+}
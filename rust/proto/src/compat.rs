@@ -0,0 +1,21 @@
+//! Forward-compatibility mode framing.
+
+use crate::error::Error;
+use crate::message_type::MessageType;
+use crate::read::{read_slice, read_u32};
+
+/// Message body of a forward-compatibility mode payload.
+///
+/// With `forward_compat` set on the subscription, the server prefixes the
+/// body of every data message (see [`MessageType::is_data`]) with its length
+/// as a little-endian `u32`; control messages are sent unchanged. Bytes of
+/// the body past the fields a parser knows were appended by a newer protocol
+/// revision.
+pub fn compat_body(kind: MessageType, payload: &[u8]) -> Result<&[u8], Error> {
+    if !kind.is_data() {
+        return Ok(payload);
+    }
+    let mut offset = 0;
+    let len = read_u32(payload, &mut offset)? as usize;
+    read_slice(payload, &mut offset, len)
+}
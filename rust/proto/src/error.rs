@@ -0,0 +1,50 @@
+//! Wire format errors.
+
+use core::fmt;
+
+/// A frame that does not match the wire format or its [`DecodeLimits`].
+///
+/// [`DecodeLimits`]: crate::DecodeLimits
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error {
+    /// Payload is too short
+    PayloadTooShort {
+        /// Minimum number of bytes required
+        expected: usize,
+        /// Number of bytes available
+        actual: usize,
+    },
+    /// A length or count exceeds the configured limit
+    LimitExceeded {
+        /// Field or prefix that was too large
+        field: &'static str,
+        /// Configured limit
+        limit: usize,
+        /// Value found in the frame
+        actual: usize,
+    },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::PayloadTooShort { expected, actual } => {
+                write!(f, "Payload too short: expected {}, got {}", expected, actual)
+            }
+            Self::LimitExceeded { field, limit, actual } => {
+                write!(f, "{} exceeds limit: {} > {}", field, actual, limit)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+/// Fail with [`Error::LimitExceeded`] if `actual` is over `limit`.
+pub fn check_limit(field: &'static str, actual: usize, limit: usize) -> Result<(), Error> {
+    if actual > limit {
+        return Err(Error::LimitExceeded { field, limit, actual });
+    }
+    Ok(())
+}
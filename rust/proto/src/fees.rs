@@ -0,0 +1,204 @@
+//! Priority fee markets.
+
+use core::slice::ChunksExact;
+
+use crate::error::{check_limit, Error};
+use crate::limits::DecodeLimits;
+use crate::read::{read_bytes, read_len, read_u32, read_u64, read_u8};
+
+/// Bytes before the first account.
+const HEADER_LEN: usize = 42;
+/// Bytes per account.
+const ACCOUNT_LEN: usize = 92;
+
+/// Fee market payload (`MessageType::PriorityFees`), borrowed from the frame.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FeeMarket<'a> {
+    /// Solana slot number
+    pub slot: u64,
+    /// Unix timestamp in milliseconds
+    pub timestamp_ms: u64,
+    /// Recommended priority fee in microlamports per compute unit
+    pub recommended: u64,
+    /// Network congestion state: 0 low, 1 normal, 2 high, 3 extreme;
+    /// not validated here
+    pub state: u8,
+    /// Whether the data is stale
+    pub is_stale: bool,
+    /// Block utilization percentage
+    pub block_utilization_pct: f32,
+    /// Blocks in the observation window
+    pub blocks_in_window: u32,
+    accounts: &'a [u8],
+}
+
+impl<'a> FeeMarket<'a> {
+    /// Parse a fee market payload, enforcing `max_fee_accounts`.
+    pub fn parse(data: &'a [u8], limits: &DecodeLimits) -> Result<Self, Error> {
+        Self::parse_capped(data, limits, false)
+    }
+
+    /// Parse a fee market payload, keeping only the first
+    /// `max_fee_accounts` accounts instead of failing.
+    pub fn parse_truncated(data: &'a [u8], limits: &DecodeLimits) -> Result<Self, Error> {
+        Self::parse_capped(data, limits, true)
+    }
+
+    fn parse_capped(data: &'a [u8], limits: &DecodeLimits, truncate: bool) -> Result<Self, Error> {
+        if data.len() < HEADER_LEN {
+            return Err(Error::PayloadTooShort {
+                expected: HEADER_LEN,
+                actual: data.len(),
+            });
+        }
+
+        let mut offset = 0;
+        let slot = read_u64(data, &mut offset)?;
+        let timestamp_ms = read_u64(data, &mut offset)?;
+        let recommended = read_u64(data, &mut offset)?;
+        let state = read_u8(data, &mut offset)?;
+        let is_stale = read_u8(data, &mut offset)? != 0;
+        let block_utilization_pct = f32::from_le_bytes(*read_bytes(data, &mut offset)?);
+        let blocks_in_window = read_u32(data, &mut offset)?;
+        let mut account_count = read_len(data, &mut offset)?;
+        if truncate {
+            account_count = account_count.min(limits.max_fee_accounts);
+        }
+        check_limit("fee accounts", account_count, limits.max_fee_accounts)?;
+        let expected = offset.saturating_add(account_count.saturating_mul(ACCOUNT_LEN));
+        if expected > data.len() {
+            return Err(Error::PayloadTooShort {
+                expected,
+                actual: data.len(),
+            });
+        }
+
+        Ok(Self {
+            slot,
+            timestamp_ms,
+            recommended,
+            state,
+            is_stale,
+            block_utilization_pct,
+            blocks_in_window,
+            accounts: &data[offset..expected],
+        })
+    }
+
+    /// Per-account fee data.
+    pub fn accounts(&self) -> AccountFees<'a> {
+        AccountFees(self.accounts.chunks_exact(ACCOUNT_LEN))
+    }
+
+    /// Bytes of the payload covered by the header and the kept accounts.
+    pub fn encoded_len(&self) -> usize {
+        HEADER_LEN + self.accounts.len()
+    }
+}
+
+/// Per-account fee data of a [`FeeMarket`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AccountFee<'a> {
+    /// Raw account address
+    pub pubkey: &'a [u8; 32],
+    /// Total transactions touching this account
+    pub total_txs: u32,
+    /// Slots with activity on this account
+    pub active_slots: u32,
+    /// Compute units consumed
+    pub cu_consumed: u64,
+    /// Account utilization percentage
+    pub utilization_pct: f32,
+    /// 25th percentile fee
+    pub p25: u64,
+    /// 50th percentile fee
+    pub p50: u64,
+    /// 75th percentile fee
+    pub p75: u64,
+    /// 90th percentile fee
+    pub p90: u64,
+    /// Lowest non-zero fee
+    pub min_nonzero_price: u64,
+}
+
+impl<'a> AccountFee<'a> {
+    fn parse(data: &'a [u8]) -> Result<Self, Error> {
+        let mut offset = 0;
+        Ok(Self {
+            pubkey: read_bytes(data, &mut offset)?,
+            total_txs: read_u32(data, &mut offset)?,
+            active_slots: read_u32(data, &mut offset)?,
+            cu_consumed: read_u64(data, &mut offset)?,
+            utilization_pct: f32::from_le_bytes(*read_bytes(data, &mut offset)?),
+            p25: read_u64(data, &mut offset)?,
+            p50: read_u64(data, &mut offset)?,
+            p75: read_u64(data, &mut offset)?,
+            p90: read_u64(data, &mut offset)?,
+            min_nonzero_price: read_u64(data, &mut offset)?,
+        })
+    }
+}
+
+/// Accounts of a [`FeeMarket`].
+#[derive(Debug, Clone)]
+pub struct AccountFees<'a>(ChunksExact<'a, u8>);
+
+impl<'a> Iterator for AccountFees<'a> {
+    type Item = AccountFee<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // Every chunk holds exactly one account
+        self.0.next().map(|chunk| AccountFee::parse(chunk).unwrap())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl ExactSizeIterator for AccountFees<'_> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn payload(accounts: u64, present: usize) -> [u8; HEADER_LEN + 2 * ACCOUNT_LEN] {
+        let mut data = [0; HEADER_LEN + 2 * ACCOUNT_LEN];
+        data[..8].copy_from_slice(&5u64.to_le_bytes());
+        data[16..24].copy_from_slice(&1_000u64.to_le_bytes());
+        data[24] = 2;
+        data[26..30].copy_from_slice(&50f32.to_le_bytes());
+        data[34..42].copy_from_slice(&accounts.to_le_bytes());
+        for i in 0..present {
+            let account = HEADER_LEN + i * ACCOUNT_LEN;
+            data[account..account + 32].copy_from_slice(&[i as u8 + 1; 32]);
+            data[account + 76..account + 84].copy_from_slice(&(100 * (i as u64 + 1)).to_le_bytes());
+        }
+        data
+    }
+
+    #[test]
+    fn test_parse_fee_market() {
+        let data = payload(2, 2);
+        let limits = DecodeLimits::default();
+        let fees = FeeMarket::parse(&data, &limits).unwrap();
+        assert_eq!((fees.slot, fees.recommended, fees.state), (5, 1_000, 2));
+        assert_eq!(fees.block_utilization_pct, 50.0);
+        assert_eq!(fees.encoded_len(), data.len());
+        let accounts: [_; 2] = core::array::from_fn(|i| fees.accounts().nth(i).unwrap());
+        assert_eq!(accounts[1].pubkey, &[2; 32]);
+        assert_eq!(accounts[1].p90, 200);
+
+        assert!(FeeMarket::parse(&data[..41], &limits).is_err());
+        assert!(FeeMarket::parse(&payload(3, 2), &limits).is_err());
+
+        let one = DecodeLimits {
+            max_fee_accounts: 1,
+            ..limits
+        };
+        assert!(FeeMarket::parse(&data, &one).is_err());
+        let truncated = FeeMarket::parse_truncated(&data, &one).unwrap();
+        assert_eq!(truncated.accounts().len(), 1);
+        assert_eq!(truncated.encoded_len(), HEADER_LEN + ACCOUNT_LEN);
+    }
+}
@@ -0,0 +1,52 @@
+//! Wire format parsing for the K256 WebSocket protocol.
+//!
+//! The pure binary layer of [`k256-sdk`](https://docs.rs/k256-sdk): message
+//! type bytes, [`DecodeLimits`], and borrowed views over server frame
+//! payloads. Nothing here allocates or depends on `std`, so embedded and
+//! hardware-adjacent consumers parse frames exactly as the SDK does; the SDK
+//! builds its owned types on top of these views and re-exports this crate as
+//! `k256_sdk::proto`.
+//!
+//! Every parser takes the payload without the leading type byte. Integers
+//! are little-endian; addresses and hashes stay raw 32-byte arrays.
+//!
+//! ```rust
+//! use k256_proto::{DecodeLimits, MessageType, PoolUpdateBatch};
+//!
+//! fn pools_in_batch(frame: &[u8]) -> Result<usize, k256_proto::Error> {
+//!     let Some((&msg_type, payload)) = frame.split_first() else { return Ok(0) };
+//!     if MessageType::try_from(msg_type) != Ok(MessageType::PoolUpdateBatch) {
+//!         return Ok(0);
+//!     }
+//!     let limits = DecodeLimits::default();
+//!     let mut pools = 0;
+//!     for update in PoolUpdateBatch::parse(payload, &limits)?.updates(&limits) {
+//!         let _slot = update?.slot;
+//!         pools += 1;
+//!     }
+//!     Ok(pools)
+//! }
+//! ```
+
+#![cfg_attr(not(feature = "std"), no_std)]
+#![deny(missing_docs)]
+
+mod blockhash;
+mod compat;
+mod error;
+mod fees;
+mod limits;
+mod message_type;
+mod pool;
+mod price;
+mod read;
+
+pub use blockhash::Blockhash;
+pub use compat::compat_body;
+pub use error::{check_limit, Error};
+pub use fees::{AccountFee, AccountFees, FeeMarket};
+pub use limits::DecodeLimits;
+pub use message_type::MessageType;
+pub use pool::{BatchEntries, BatchUpdates, OrderLevel, PoolUpdate, PoolUpdateBatch};
+pub use price::{price_entries, PriceEntries, PriceEntry, PRICE_SCALE};
+pub use read::{read_array, read_len, read_slice, read_u16, read_u32, read_u64, read_u8};
@@ -0,0 +1,37 @@
+//! Size limits for binary frames.
+
+/// Upper bounds enforced while decoding binary frames.
+///
+/// Length prefixes are always checked against the remaining payload before
+/// anything is sliced, so a truncated or corrupt frame fails with
+/// [`Error::PayloadTooShort`](crate::Error::PayloadTooShort). These limits
+/// additionally cap what a well-formed frame may contain.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DecodeLimits {
+    /// Maximum payload size in bytes
+    pub max_payload_len: usize,
+    /// Maximum `serialized_state` length per pool update
+    pub max_state_len: usize,
+    /// Maximum protocol name length per pool update
+    pub max_protocol_name_len: usize,
+    /// Maximum token mints (and balances/decimals) per pool update
+    pub max_token_mints: usize,
+    /// Maximum updates per batch or snapshot
+    pub max_batch_len: usize,
+    /// Maximum accounts per fee market message
+    pub max_fee_accounts: usize,
+}
+
+impl Default for DecodeLimits {
+    fn default() -> Self {
+        Self {
+            max_payload_len: 64 * 1024 * 1024,
+            // Largest Solana account size
+            max_state_len: 10 * 1024 * 1024,
+            max_protocol_name_len: 256,
+            max_token_mints: 64,
+            max_batch_len: u16::MAX as usize,
+            max_fee_accounts: 65_536,
+        }
+    }
+}
@@ -1,4 +1,4 @@
-//! WebSocket message type constants.
+//! Message type bytes.
 
 /// WebSocket binary message type identifiers.
 ///
@@ -46,10 +46,33 @@ pub enum MessageType {
     PriceSnapshot = 0x13,
     /// Client → Server: Unsubscribe from price feed
     UnsubscribePrice = 0x14,
+    /// Server → Client: Initial pool state snapshot (same layout as `PoolUpdateBatch`)
+    PoolSnapshot = 0x15,
     /// Server → Client: Error message (UTF-8)
     Error = 0xFF,
 }
 
+impl MessageType {
+    /// Whether this is a server data message with a fixed binary layout:
+    /// pool updates, fee markets, blockhashes and prices.
+    ///
+    /// Their bodies are length-prefixed in forward-compatibility mode (see
+    /// [`compat_body`](crate::compat_body)).
+    pub fn is_data(&self) -> bool {
+        matches!(
+            self,
+            Self::PoolUpdate
+                | Self::PoolUpdateBatch
+                | Self::PoolSnapshot
+                | Self::PriorityFees
+                | Self::Blockhash
+                | Self::PriceUpdate
+                | Self::PriceBatch
+                | Self::PriceSnapshot
+        )
+    }
+}
+
 impl TryFrom<u8> for MessageType {
     type Error = u8;
 
@@ -75,6 +98,7 @@ impl TryFrom<u8> for MessageType {
             0x12 => Ok(Self::PriceBatch),
             0x13 => Ok(Self::PriceSnapshot),
             0x14 => Ok(Self::UnsubscribePrice),
+            0x15 => Ok(Self::PoolSnapshot),
             0xFF => Ok(Self::Error),
             other => Err(other),
         }
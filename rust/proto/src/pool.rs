@@ -0,0 +1,329 @@
+//! Pool updates, batches and snapshots.
+
+use crate::error::{check_limit, Error};
+use crate::limits::DecodeLimits;
+use crate::read::{read_array, read_bytes, read_len, read_slice, read_u16, read_u32, read_u64, read_u8};
+
+/// Order book level with price and size.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OrderLevel {
+    /// Price in base units
+    pub price: u64,
+    /// Size in base units
+    pub size: u64,
+}
+
+impl OrderLevel {
+    /// Optional level at `offset`: a presence byte, then price and size.
+    fn read_optional(data: &[u8], offset: &mut usize) -> Result<Option<Self>, Error> {
+        if read_u8(data, offset)? == 0 {
+            return Ok(None);
+        }
+        let price = read_u64(data, offset)?;
+        let size = read_u64(data, offset)?;
+        Ok(Some(Self { price, size }))
+    }
+}
+
+/// Pool update payload (`MessageType::PoolUpdate`), borrowed from the frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PoolUpdate<'a> {
+    /// Global sequence number for ordering
+    pub sequence: u64,
+    /// Solana slot number
+    pub slot: u64,
+    /// Write version within slot
+    pub write_version: u64,
+    /// DEX protocol name, UTF-8 by convention but not validated here
+    pub protocol_name: &'a [u8],
+    /// Raw pool address
+    pub pool_address: &'a [u8; 32],
+    /// Best bid order level, if available
+    pub best_bid: Option<OrderLevel>,
+    /// Best ask order level, if available
+    pub best_ask: Option<OrderLevel>,
+    /// Opaque pool state bytes
+    pub serialized_state: &'a [u8],
+    token_mints: &'a [u8],
+    token_balances: &'a [u8],
+    token_decimals: &'a [u8],
+}
+
+impl<'a> PoolUpdate<'a> {
+    /// Parse a pool update payload, enforcing `limits`.
+    pub fn parse(data: &'a [u8], limits: &DecodeLimits) -> Result<Self, Error> {
+        Self::parse_prefix(data, limits).map(|(update, _)| update)
+    }
+
+    /// Parse the known fields from the front of `data`, returning the update
+    /// and the number of bytes they cover; anything after them was appended
+    /// by a newer protocol revision.
+    pub fn parse_prefix(data: &'a [u8], limits: &DecodeLimits) -> Result<(Self, usize), Error> {
+        let mut offset = 0;
+
+        let state_len = read_len(data, &mut offset)?;
+        check_limit("serialized_state", state_len, limits.max_state_len)?;
+        let serialized_state = read_slice(data, &mut offset, state_len)?;
+        let sequence = read_u64(data, &mut offset)?;
+        let slot = read_u64(data, &mut offset)?;
+        let write_version = read_u64(data, &mut offset)?;
+
+        let name_len = read_len(data, &mut offset)?;
+        check_limit("protocol_name", name_len, limits.max_protocol_name_len)?;
+        let protocol_name = read_slice(data, &mut offset, name_len)?;
+
+        let pool_address = read_bytes(data, &mut offset)?;
+        let token_mints = read_array(data, &mut offset, 32, "token_mints", limits.max_token_mints)?;
+        let token_balances = read_array(data, &mut offset, 8, "token_balances", limits.max_token_mints)?;
+        let token_decimals = read_array(data, &mut offset, 4, "token_decimals", limits.max_token_mints)?;
+
+        let best_bid = OrderLevel::read_optional(data, &mut offset)?;
+        let best_ask = OrderLevel::read_optional(data, &mut offset)?;
+
+        let update = Self {
+            sequence,
+            slot,
+            write_version,
+            protocol_name,
+            pool_address,
+            best_bid,
+            best_ask,
+            serialized_state,
+            token_mints,
+            token_balances,
+            token_decimals,
+        };
+        Ok((update, offset))
+    }
+
+    /// Raw token mint addresses.
+    pub fn token_mints(&self) -> impl ExactSizeIterator<Item = &'a [u8; 32]> + 'a {
+        self.token_mints.chunks_exact(32).map(|chunk| chunk.try_into().unwrap())
+    }
+
+    /// Token balances (same order as mints).
+    pub fn token_balances(&self) -> impl ExactSizeIterator<Item = u64> + 'a {
+        self.token_balances
+            .chunks_exact(8)
+            .map(|chunk| u64::from_le_bytes(chunk.try_into().unwrap()))
+    }
+
+    /// Token decimals (same order as mints).
+    pub fn token_decimals(&self) -> impl ExactSizeIterator<Item = i32> + 'a {
+        self.token_decimals
+            .chunks_exact(4)
+            .map(|chunk| i32::from_le_bytes(chunk.try_into().unwrap()))
+    }
+}
+
+/// Pool update batch or snapshot payload (`MessageType::PoolUpdateBatch`,
+/// `MessageType::PoolSnapshot`): a `u16` count, then `u32` length-prefixed
+/// pool update payloads.
+///
+/// Entries are parsed lazily while iterating.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PoolUpdateBatch<'a> {
+    count: u16,
+    entries: &'a [u8],
+}
+
+impl<'a> PoolUpdateBatch<'a> {
+    /// Parse the batch header, checking its count against `max_batch_len`.
+    pub fn parse(data: &'a [u8], limits: &DecodeLimits) -> Result<Self, Error> {
+        let mut offset = 0;
+        let count = read_u16(data, &mut offset)?;
+        check_limit("batch", count as usize, limits.max_batch_len)?;
+        Ok(Self {
+            count,
+            entries: &data[offset..],
+        })
+    }
+
+    /// Number of updates announced by the batch header.
+    pub fn len(&self) -> usize {
+        self.count as usize
+    }
+
+    /// Whether the batch is empty.
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// Upper bound on the updates the payload can actually hold, so a bogus
+    /// count can't drive a large allocation.
+    pub fn capacity(&self) -> usize {
+        self.len().min(self.entries.len() / 4)
+    }
+
+    /// Iterate over the raw update payloads, stopping after the first error.
+    pub fn entries(&self) -> BatchEntries<'a> {
+        BatchEntries {
+            remaining: self.count,
+            data: self.entries,
+            offset: 0,
+        }
+    }
+
+    /// Iterate over the parsed updates, stopping after the first error.
+    pub fn updates(&self, limits: &DecodeLimits) -> BatchUpdates<'a> {
+        BatchUpdates {
+            entries: self.entries(),
+            limits: *limits,
+        }
+    }
+
+    /// Bytes of the batch payload, header included, covered by its
+    /// announced updates.
+    pub fn encoded_len(&self) -> Result<usize, Error> {
+        let mut entries = self.entries();
+        for entry in entries.by_ref() {
+            entry?;
+        }
+        Ok(2 + entries.offset)
+    }
+}
+
+/// Raw update payloads of a [`PoolUpdateBatch`].
+#[derive(Debug, Clone)]
+pub struct BatchEntries<'a> {
+    remaining: u16,
+    data: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Iterator for BatchEntries<'a> {
+    type Item = Result<&'a [u8], Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        let entry = read_u32(self.data, &mut self.offset)
+            .and_then(|length| read_slice(self.data, &mut self.offset, length as usize));
+        if entry.is_err() {
+            self.remaining = 0;
+        }
+        Some(entry)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.remaining as usize))
+    }
+}
+
+/// Parsed updates of a [`PoolUpdateBatch`].
+#[derive(Debug, Clone)]
+pub struct BatchUpdates<'a> {
+    entries: BatchEntries<'a>,
+    limits: DecodeLimits,
+}
+
+impl<'a> Iterator for BatchUpdates<'a> {
+    type Item = Result<PoolUpdate<'a>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let update = self.entries.next()?.and_then(|entry| PoolUpdate::parse(entry, &self.limits));
+        if update.is_err() {
+            self.entries.remaining = 0;
+        }
+        Some(update)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.entries.size_hint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Pool update payload with two tokens and a best ask.
+    fn payload(slot: u8) -> [u8; 207] {
+        let mut data = [0; 207];
+        let fields: [&[u8]; 15] = [
+            &2u64.to_le_bytes(),
+            &[0xAB; 2],
+            &7u64.to_le_bytes(),
+            &[slot, 0, 0, 0, 0, 0, 0, 0],
+            &1u64.to_le_bytes(),
+            &3u64.to_le_bytes(),
+            b"Ray",
+            &[9; 32],
+            &2u64.to_le_bytes(),
+            &[1; 64],
+            &2u64.to_le_bytes(),
+            &[0xE8, 3, 0, 0, 0, 0, 0, 0, 0xD0, 7, 0, 0, 0, 0, 0, 0],
+            &2u64.to_le_bytes(),
+            &[9, 0, 0, 0, 6, 0, 0, 0],
+            &[0, 1, 10, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0],
+        ];
+        let mut offset = 0;
+        for field in fields {
+            data[offset..offset + field.len()].copy_from_slice(field);
+            offset += field.len();
+        }
+        assert_eq!(offset, data.len());
+        data
+    }
+
+    #[test]
+    fn test_parse_pool_update() {
+        let data = payload(5);
+        let limits = DecodeLimits::default();
+        let (update, len) = PoolUpdate::parse_prefix(&data, &limits).unwrap();
+        assert_eq!(len, data.len());
+        assert_eq!((update.sequence, update.slot, update.write_version), (7, 5, 1));
+        assert_eq!(update.protocol_name, b"Ray");
+        assert_eq!(update.pool_address, &[9; 32]);
+        assert_eq!(update.serialized_state, [0xAB; 2]);
+        assert_eq!(update.token_mints().len(), 2);
+        assert!(update.token_balances().eq([1_000, 2_000]));
+        assert!(update.token_decimals().eq([9, 6]));
+        assert_eq!(update.best_bid, None);
+        assert_eq!(update.best_ask, Some(OrderLevel { price: 10, size: 1 }));
+
+        assert!(PoolUpdate::parse(&data[..data.len() - 1], &limits).is_err());
+        let limits = DecodeLimits {
+            max_token_mints: 1,
+            ..limits
+        };
+        assert_eq!(
+            PoolUpdate::parse(&data, &limits),
+            Err(Error::LimitExceeded { field: "token_mints", limit: 1, actual: 2 })
+        );
+    }
+
+    #[test]
+    fn test_batch_entries() {
+        let mut data = [0; 2 + 2 * (4 + 207) + 4];
+        data[..2].copy_from_slice(&3u16.to_le_bytes());
+        let mut offset = 2;
+        for slot in [1, 2] {
+            data[offset..offset + 4].copy_from_slice(&207u32.to_le_bytes());
+            data[offset + 4..offset + 4 + 207].copy_from_slice(&payload(slot));
+            offset += 4 + 207;
+        }
+        // Third entry announces more bytes than remain
+        data[offset..].copy_from_slice(&100u32.to_le_bytes());
+
+        let limits = DecodeLimits::default();
+        let batch = PoolUpdateBatch::parse(&data, &limits).unwrap();
+        assert_eq!((batch.len(), batch.capacity()), (3, 3));
+        let mut updates = batch.updates(&limits);
+        assert_eq!(updates.next().unwrap().unwrap().slot, 1);
+        assert_eq!(updates.next().unwrap().unwrap().slot, 2);
+        assert!(updates.next().unwrap().is_err());
+        assert!(updates.next().is_none());
+        assert!(batch.encoded_len().is_err());
+
+        let two = PoolUpdateBatch::parse(&[2, 0], &limits).unwrap();
+        assert_eq!(two.capacity(), 0);
+        let limits = DecodeLimits {
+            max_batch_len: 2,
+            ..limits
+        };
+        assert!(PoolUpdateBatch::parse(&data, &limits).is_err());
+    }
+}
@@ -0,0 +1,131 @@
+//! Price feed entries.
+
+use core::slice::ChunksExact;
+
+use crate::error::Error;
+use crate::read::{read_bytes, read_u16, read_u64};
+
+/// Raw USD prices are fixed-point with this many units per dollar.
+pub const PRICE_SCALE: u64 = 1_000_000_000_000;
+
+/// Bytes per entry.
+const ENTRY_LEN: usize = 56;
+
+/// Price entry (`MessageType::PriceUpdate`, and the entries of
+/// `PriceBatch` and `PriceSnapshot`), borrowed from the frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PriceEntry<'a> {
+    /// Raw token mint address
+    pub mint: &'a [u8; 32],
+    /// USD price in units of 1 / [`PRICE_SCALE`] dollars
+    pub usd_price_raw: u64,
+    /// Solana slot of the price
+    pub slot: u64,
+    /// Unix timestamp in milliseconds
+    pub timestamp_ms: u64,
+}
+
+impl<'a> PriceEntry<'a> {
+    /// Parse a single price update payload.
+    pub fn parse(data: &'a [u8]) -> Result<Self, Error> {
+        if data.len() < ENTRY_LEN {
+            return Err(Error::PayloadTooShort {
+                expected: ENTRY_LEN,
+                actual: data.len(),
+            });
+        }
+        let mut offset = 0;
+        Ok(Self {
+            mint: read_bytes(data, &mut offset)?,
+            usd_price_raw: read_u64(data, &mut offset)?,
+            slot: read_u64(data, &mut offset)?,
+            timestamp_ms: read_u64(data, &mut offset)?,
+        })
+    }
+
+    /// USD price in dollars.
+    pub fn usd_price(&self) -> f64 {
+        self.usd_price_raw as f64 / PRICE_SCALE as f64
+    }
+
+    /// Bytes of the payload covered by the known fields.
+    pub fn encoded_len(&self) -> usize {
+        ENTRY_LEN
+    }
+}
+
+/// Entries of a price batch or snapshot payload: a `u16` count, then the
+/// entries.
+///
+/// Iteration stops early, without an error, at an entry the payload is too
+/// short to hold.
+pub fn price_entries(data: &[u8]) -> Result<PriceEntries<'_>, Error> {
+    let mut offset = 0;
+    let count = read_u16(data, &mut offset)?;
+    Ok(PriceEntries {
+        count,
+        remaining: count as usize,
+        chunks: data[offset..].chunks_exact(ENTRY_LEN),
+    })
+}
+
+/// Entries of a price batch or snapshot, from [`price_entries`].
+#[derive(Debug, Clone)]
+pub struct PriceEntries<'a> {
+    count: u16,
+    remaining: usize,
+    chunks: ChunksExact<'a, u8>,
+}
+
+impl PriceEntries<'_> {
+    /// Bytes of the payload, count included, covered by the announced
+    /// entries.
+    pub fn encoded_len(&self) -> usize {
+        2 + ENTRY_LEN * self.count as usize
+    }
+}
+
+impl<'a> Iterator for PriceEntries<'a> {
+    type Item = PriceEntry<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.remaining = self.remaining.checked_sub(1)?;
+        // Every chunk holds exactly one entry
+        self.chunks.next().map(|chunk| PriceEntry::parse(chunk).unwrap())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.remaining.min(self.chunks.len());
+        (len, Some(len))
+    }
+}
+
+impl ExactSizeIterator for PriceEntries<'_> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_price_entries() {
+        let mut data = [0; 2 + 2 * ENTRY_LEN + 10];
+        data[..2].copy_from_slice(&3u16.to_le_bytes());
+        for i in 0..2 {
+            let entry = 2 + i * ENTRY_LEN;
+            data[entry..entry + 32].copy_from_slice(&[i as u8 + 1; 32]);
+            data[entry + 32..entry + 40].copy_from_slice(&(150 * PRICE_SCALE).to_le_bytes());
+        }
+
+        let entries = price_entries(&data).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries.encoded_len(), 2 + 3 * ENTRY_LEN);
+        let mut entries = entries;
+        assert_eq!(entries.next().unwrap().usd_price(), 150.0);
+        assert_eq!(entries.next().unwrap().mint, &[2; 32]);
+        assert!(entries.next().is_none());
+
+        assert!(PriceEntry::parse(&data[2..2 + ENTRY_LEN]).is_ok());
+        assert!(PriceEntry::parse(&data[2..ENTRY_LEN]).is_err());
+        assert!(price_entries(&[1]).is_err());
+    }
+}
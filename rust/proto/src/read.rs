@@ -0,0 +1,105 @@
+//! Bounds-checked little-endian readers.
+//!
+//! Each reader takes the value at `*offset` and advances the offset past it,
+//! or fails with [`Error::PayloadTooShort`] without moving it.
+
+use crate::error::{check_limit, Error};
+
+/// `len` bytes at `offset`.
+pub fn read_slice<'a>(data: &'a [u8], offset: &mut usize, len: usize) -> Result<&'a [u8], Error> {
+    if len > data.len().saturating_sub(*offset) {
+        return Err(Error::PayloadTooShort {
+            expected: offset.saturating_add(len),
+            actual: data.len(),
+        });
+    }
+    let slice = &data[*offset..*offset + len];
+    *offset += len;
+    Ok(slice)
+}
+
+/// `N` bytes at `offset`, as an array reference.
+pub(crate) fn read_bytes<'a, const N: usize>(data: &'a [u8], offset: &mut usize) -> Result<&'a [u8; N], Error> {
+    Ok(read_slice(data, offset, N)?.try_into().unwrap())
+}
+
+/// Byte at `offset`.
+pub fn read_u8(data: &[u8], offset: &mut usize) -> Result<u8, Error> {
+    Ok(read_bytes::<1>(data, offset)?[0])
+}
+
+/// Little-endian `u16` at `offset`.
+pub fn read_u16(data: &[u8], offset: &mut usize) -> Result<u16, Error> {
+    read_bytes(data, offset).map(|bytes| u16::from_le_bytes(*bytes))
+}
+
+/// Little-endian `u32` at `offset`.
+pub fn read_u32(data: &[u8], offset: &mut usize) -> Result<u32, Error> {
+    read_bytes(data, offset).map(|bytes| u32::from_le_bytes(*bytes))
+}
+
+/// Little-endian `u64` at `offset`.
+pub fn read_u64(data: &[u8], offset: &mut usize) -> Result<u64, Error> {
+    read_bytes(data, offset).map(|bytes| u64::from_le_bytes(*bytes))
+}
+
+/// Little-endian `u64` length or count at `offset`, saturated to
+/// `usize::MAX` where it does not fit so it fails limit checks instead of
+/// wrapping on 32-bit targets.
+pub fn read_len(data: &[u8], offset: &mut usize) -> Result<usize, Error> {
+    read_u64(data, offset).map(|len| usize::try_from(len).unwrap_or(usize::MAX))
+}
+
+/// `u64` count-prefixed array of `width`-byte elements, as raw bytes, with
+/// the count checked against `limit`.
+pub fn read_array<'a>(
+    data: &'a [u8],
+    offset: &mut usize,
+    width: usize,
+    field: &'static str,
+    limit: usize,
+) -> Result<&'a [u8], Error> {
+    let count = read_len(data, offset)?;
+    check_limit(field, count, limit)?;
+    read_slice(data, offset, count.saturating_mul(width))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_readers_check_bounds() {
+        let data = [1, 0, 2, 0, 0, 0, 3, 0, 0, 0, 0, 0, 0, 0];
+        let mut offset = 0;
+        assert_eq!(read_u16(&data, &mut offset), Ok(1));
+        assert_eq!(read_u32(&data, &mut offset), Ok(2));
+        assert_eq!(read_u64(&data, &mut offset), Ok(3));
+        assert_eq!(offset, data.len());
+        assert_eq!(
+            read_u8(&data, &mut offset),
+            Err(Error::PayloadTooShort { expected: 15, actual: 14 })
+        );
+        assert_eq!(offset, data.len());
+
+        // A huge length neither overflows nor panics
+        let mut offset = 2;
+        assert!(read_slice(&data, &mut offset, usize::MAX).is_err());
+        assert_eq!(read_array(&[5, 0, 0, 0, 0, 0, 0, 0], &mut 0, 8, "items", 4).unwrap_err(), Error::LimitExceeded {
+            field: "items",
+            limit: 4,
+            actual: 5
+        });
+    }
+
+    #[test]
+    fn test_lengths_do_not_wrap() {
+        // 2^32 + 1 wraps to 1 when cast to a 32-bit `usize`
+        let data = (1u64 << 32 | 1).to_le_bytes();
+        assert!(read_len(&data, &mut 0).unwrap() > 1);
+        assert!(matches!(
+            read_array(&data, &mut 0, 1, "items", 4),
+            Err(Error::LimitExceeded { field: "items", limit: 4, .. })
+        ));
+    }
+}
@@ -0,0 +1,45 @@
+//! K256 REST API client.
+
+mod pools;
+mod tokens;
+
+pub use pools::{ApiClient, ApiConfig, PoolFilter};
+pub use tokens::{TokenRegistry, TokenRegistryConfig};
+
+use reqwest::{RequestBuilder, StatusCode};
+use thiserror::Error;
+
+use crate::connector::AuthMethod;
+
+/// REST API errors.
+#[derive(Debug, Error)]
+pub enum ApiError {
+    /// Request failed or the response body could not be parsed
+    #[error("HTTP error: {0}")]
+    Http(#[from] reqwest::Error),
+
+    /// Server answered with a non-success status
+    #[error("API error {status}: {body}")]
+    Status {
+        /// HTTP status code
+        status: u16,
+        /// Response body
+        body: String,
+    },
+}
+
+/// Attach `api_key` to `request` as `auth` specifies.
+fn authenticated(request: RequestBuilder, api_key: &str, auth: AuthMethod) -> RequestBuilder {
+    match auth {
+        AuthMethod::QueryParam => request.query(&[("apiKey", api_key)]),
+        AuthMethod::Bearer => request.bearer_auth(api_key),
+        AuthMethod::ApiKeyHeader => request.header("x-api-key", api_key),
+    }
+}
+
+async fn status_error(status: StatusCode, response: reqwest::Response) -> ApiError {
+    ApiError::Status {
+        status: status.as_u16(),
+        body: response.text().await.unwrap_or_default(),
+    }
+}
@@ -0,0 +1,246 @@
+//! Pool discovery via the `/v1/pools` endpoint.
+//!
+//! [`ApiClient::list_pools`] returns [`Pool`] metadata (vaults, fee rate)
+//! for every pool matching a [`PoolFilter`], e.g. to pick the pools to
+//! subscribe to at startup.
+
+use std::time::Duration;
+
+use reqwest::RequestBuilder;
+
+use super::{status_error, ApiError};
+use crate::connector::AuthMethod;
+use crate::types::{Pool, Protocol, Pubkey};
+
+/// Configuration for [`ApiClient`].
+#[derive(Debug, Clone)]
+pub struct ApiConfig {
+    /// K256 API key
+    pub api_key: String,
+    /// REST API base URL
+    pub base_url: String,
+    /// How the API key is sent
+    pub auth: AuthMethod,
+    /// Per-request timeout
+    pub timeout: Duration,
+}
+
+impl Default for ApiConfig {
+    fn default() -> Self {
+        Self {
+            api_key: String::new(),
+            base_url: "https://gateway.k256.xyz".to_string(),
+            auth: AuthMethod::QueryParam,
+            timeout: Duration::from_secs(10),
+        }
+    }
+}
+
+/// Which pools [`ApiClient::list_pools`] returns.
+///
+/// Empty fields do not filter; the default filter matches every pool.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PoolFilter {
+    /// Only pools holding all of these mints
+    pub mints: Vec<Pubkey>,
+    /// Only pools of one of these protocols
+    pub protocols: Vec<Protocol>,
+    /// Maximum number of pools returned
+    pub limit: Option<u32>,
+}
+
+impl PoolFilter {
+    /// Pools trading `mint_a` against `mint_b`, in either order.
+    pub fn pair(mint_a: Pubkey, mint_b: Pubkey) -> Self {
+        Self {
+            mints: vec![mint_a, mint_b],
+            ..Self::default()
+        }
+    }
+
+    /// Pools holding `mint`.
+    pub fn token(mint: Pubkey) -> Self {
+        Self {
+            mints: vec![mint],
+            ..Self::default()
+        }
+    }
+
+    /// Pools of `protocol`.
+    pub fn protocol(protocol: Protocol) -> Self {
+        Self {
+            protocols: vec![protocol],
+            ..Self::default()
+        }
+    }
+
+    /// Also allow pools of `protocol`.
+    pub fn with_protocol(mut self, protocol: Protocol) -> Self {
+        self.protocols.push(protocol);
+        self
+    }
+
+    /// Return at most `limit` pools.
+    pub fn with_limit(mut self, limit: u32) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Whether `pool` passes the mint and protocol filters.
+    pub fn matches(&self, pool: &Pool) -> bool {
+        let holds = |mint: &Pubkey| pool.token_a_mint == *mint || pool.token_b_mint == *mint;
+        self.mints.iter().all(holds) && (self.protocols.is_empty() || self.protocols.contains(&pool.protocol))
+    }
+
+    fn query(&self) -> Vec<(&'static str, String)> {
+        let mut query = Vec::new();
+        if !self.mints.is_empty() {
+            let mints = self.mints.iter().map(Pubkey::to_string).collect::<Vec<_>>();
+            query.push(("mints", mints.join(",")));
+        }
+        if !self.protocols.is_empty() {
+            let protocols = self.protocols.iter().map(Protocol::as_str).collect::<Vec<_>>();
+            query.push(("protocols", protocols.join(",")));
+        }
+        if let Some(limit) = self.limit {
+            query.push(("limit", limit.to_string()));
+        }
+        query
+    }
+}
+
+/// K256 REST API client.
+///
+/// Cheap to clone; clones share the connection pool.
+///
+/// ```rust,no_run
+/// use k256_sdk::api::{ApiClient, ApiConfig, PoolFilter};
+/// use k256_sdk::{Protocol, Pubkey, SubscribeRequest};
+/// # async fn example(sol: Pubkey, usdc: Pubkey) -> Result<(), Box<dyn std::error::Error>> {
+/// let api = ApiClient::new(ApiConfig {
+///     api_key: "your-api-key".to_string(),
+///     ..Default::default()
+/// })?;
+///
+/// let filter = PoolFilter::pair(sol, usdc).with_protocol(Protocol::Whirlpool);
+/// let pools = api.list_pools(filter).await?;
+/// let request = SubscribeRequest::builder()
+///     .pools()
+///     .pool_addresses(pools.iter().map(|pool| pool.address.to_string()))
+///     .build()?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct ApiClient {
+    config: ApiConfig,
+    http: reqwest::Client,
+}
+
+impl ApiClient {
+    /// Create a client.
+    pub fn new(config: ApiConfig) -> Result<Self, ApiError> {
+        let http = reqwest::Client::builder().timeout(config.timeout).build()?;
+        Ok(Self { config, http })
+    }
+
+    /// Metadata of every pool matching `filter`.
+    pub async fn list_pools(&self, filter: PoolFilter) -> Result<Vec<Pool>, ApiError> {
+        let url = format!("{}/v1/pools", self.config.base_url.trim_end_matches('/'));
+        let request = self.http.get(url).query(&filter.query());
+        let response = self.authenticated(request).send().await?;
+        if !response.status().is_success() {
+            return Err(status_error(response.status(), response).await);
+        }
+        Ok(response.json::<Vec<Pool>>().await?)
+    }
+
+    fn authenticated(&self, request: RequestBuilder) -> RequestBuilder {
+        super::authenticated(request, &self.config.api_key, self.config.auth)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    fn pool(key: u8, protocol: Protocol) -> Pool {
+        Pool {
+            address: Pubkey::new([key; 32]),
+            protocol,
+            token_a_mint: Pubkey::new([1; 32]),
+            token_b_mint: Pubkey::new([2; 32]),
+            token_a_vault: Pubkey::new([key + 10; 32]),
+            token_b_vault: Pubkey::new([key + 20; 32]),
+            fee_rate: 25,
+        }
+    }
+
+    /// Serve `/v1/pools` with two pools, recording request paths.
+    async fn serve(paths: Arc<Mutex<Vec<String>>>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            loop {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut buf = vec![0u8; 4096];
+                let n = stream.read(&mut buf).await.unwrap();
+                let head = String::from_utf8_lossy(&buf[..n]).to_string();
+                let path = head.split_whitespace().nth(1).unwrap_or_default().to_string();
+                let (status, body) = if path.starts_with("/v1/pools") {
+                    let pools = vec![pool(3, Protocol::Whirlpool), pool(4, Protocol::RaydiumClmm)];
+                    ("200 OK", serde_json::to_string(&pools).unwrap())
+                } else {
+                    ("404 Not Found", String::new())
+                };
+                paths.lock().unwrap().push(path);
+                let response = format!(
+                    "HTTP/1.1 {}\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                );
+                stream.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+        format!("http://{}", addr)
+    }
+
+    #[tokio::test]
+    async fn test_list_pools_sends_filter() {
+        let paths = Arc::new(Mutex::new(Vec::new()));
+        let api = ApiClient::new(ApiConfig {
+            base_url: serve(paths.clone()).await,
+            auth: AuthMethod::Bearer,
+            ..Default::default()
+        })
+        .unwrap();
+
+        let filter = PoolFilter::pair(Pubkey::new([1; 32]), Pubkey::new([2; 32]))
+            .with_protocol(Protocol::Whirlpool)
+            .with_protocol(Protocol::RaydiumClmm)
+            .with_limit(10);
+        let pools = api.list_pools(filter.clone()).await.unwrap();
+        assert_eq!(pools, [pool(3, Protocol::Whirlpool), pool(4, Protocol::RaydiumClmm)]);
+        assert!(pools.iter().all(|pool| filter.matches(pool)));
+
+        let path = paths.lock().unwrap()[0].clone();
+        let mints = format!("mints={}%2C{}", Pubkey::new([1; 32]), Pubkey::new([2; 32]));
+        assert!(path.contains(&mints), "{}", path);
+        assert!(path.contains("protocols=Whirlpool%2CRaydiumClmm"), "{}", path);
+        assert!(path.contains("limit=10"), "{}", path);
+    }
+
+    #[test]
+    fn test_filter_matches() {
+        let whirlpool = pool(3, Protocol::Whirlpool);
+        assert!(PoolFilter::default().matches(&whirlpool));
+        assert!(PoolFilter::token(Pubkey::new([2; 32])).matches(&whirlpool));
+        assert!(!PoolFilter::pair(Pubkey::new([1; 32]), Pubkey::new([9; 32])).matches(&whirlpool));
+        assert!(PoolFilter::protocol(Protocol::Whirlpool).matches(&whirlpool));
+        assert!(!PoolFilter::protocol(Protocol::Phoenix).matches(&whirlpool));
+    }
+}
@@ -0,0 +1,278 @@
+//! Token metadata lookups with a TTL cache.
+//!
+//! [`TokenRegistry`] fetches [`Token`] metadata from the `/v1/tokens`
+//! endpoint on first use and caches it, including mints the endpoint does
+//! not know, so rendering pool updates does not hit the API per message.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use reqwest::{RequestBuilder, StatusCode};
+
+use super::{status_error, ApiError};
+use crate::connector::AuthMethod;
+use crate::types::{Pubkey, Token};
+
+/// Mints per `/v1/tokens?mints=` request in [`TokenRegistry::resolve_many`].
+const MAX_MINTS_PER_REQUEST: usize = 100;
+
+/// Configuration for [`TokenRegistry`].
+#[derive(Debug, Clone)]
+pub struct TokenRegistryConfig {
+    /// K256 API key
+    pub api_key: String,
+    /// REST API base URL
+    pub base_url: String,
+    /// How the API key is sent
+    pub auth: AuthMethod,
+    /// How long fetched metadata (and unknown mints) stay cached
+    pub ttl: Duration,
+    /// Per-request timeout
+    pub timeout: Duration,
+}
+
+impl Default for TokenRegistryConfig {
+    fn default() -> Self {
+        Self {
+            api_key: String::new(),
+            base_url: "https://gateway.k256.xyz".to_string(),
+            auth: AuthMethod::QueryParam,
+            ttl: Duration::from_secs(3600),
+            timeout: Duration::from_secs(10),
+        }
+    }
+}
+
+/// Cached lookup result; `None` records a mint the endpoint does not know.
+#[derive(Debug, Clone)]
+struct CacheEntry {
+    token: Option<Token>,
+    fetched_at: Instant,
+}
+
+/// Lazily fetched, TTL-cached token metadata.
+///
+/// Cheap to clone; clones share the same cache.
+///
+/// ```rust,no_run
+/// use k256_sdk::api::{TokenRegistry, TokenRegistryConfig};
+/// # async fn example(update: k256_sdk::PoolUpdate) -> Result<(), k256_sdk::api::ApiError> {
+/// let registry = TokenRegistry::new(TokenRegistryConfig {
+///     api_key: "your-api-key".to_string(),
+///     ..Default::default()
+/// })?;
+///
+/// let tokens = registry.resolve_many(&update.token_mints).await?;
+/// let symbols: Vec<&str> = update
+///     .token_mints
+///     .iter()
+///     .map(|mint| tokens.get(mint).map_or("?", |t| t.symbol.as_str()))
+///     .collect();
+/// println!("{} {}", update.protocol_name, symbols.join("/"));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct TokenRegistry {
+    config: Arc<TokenRegistryConfig>,
+    http: reqwest::Client,
+    cache: Arc<Mutex<HashMap<Pubkey, CacheEntry>>>,
+}
+
+impl TokenRegistry {
+    /// Create a registry with an empty cache.
+    pub fn new(config: TokenRegistryConfig) -> Result<Self, ApiError> {
+        let http = reqwest::Client::builder().timeout(config.timeout).build()?;
+        Ok(Self {
+            config: Arc::new(config),
+            http,
+            cache: Arc::new(Mutex::new(HashMap::new())),
+        })
+    }
+
+    /// Metadata for `mint`, fetched unless cached.
+    ///
+    /// Returns `None` if the endpoint does not know the mint.
+    pub async fn resolve(&self, mint: &Pubkey) -> Result<Option<Token>, ApiError> {
+        if let Some(token) = self.cached_entry(mint) {
+            return Ok(token);
+        }
+
+        let url = format!("{}/v1/tokens/{}", self.config.base_url.trim_end_matches('/'), mint);
+        let response = self.authenticated(self.http.get(url)).send().await?;
+        let token = match response.status() {
+            StatusCode::NOT_FOUND => None,
+            status if status.is_success() => Some(response.json::<Token>().await?),
+            status => return Err(status_error(status, response).await),
+        };
+        self.insert(*mint, token.clone());
+        Ok(token)
+    }
+
+    /// Metadata for every known mint in `mints`, fetching only cache misses.
+    ///
+    /// Mints the endpoint does not know are missing from the result.
+    pub async fn resolve_many(&self, mints: &[Pubkey]) -> Result<HashMap<Pubkey, Token>, ApiError> {
+        let mut resolved = HashMap::with_capacity(mints.len());
+        let mut missing = Vec::new();
+        for mint in mints {
+            match self.cached_entry(mint) {
+                Some(Some(token)) => {
+                    resolved.insert(*mint, token);
+                }
+                Some(None) => {}
+                None if !missing.contains(mint) => missing.push(*mint),
+                None => {}
+            }
+        }
+
+        let url = format!("{}/v1/tokens", self.config.base_url.trim_end_matches('/'));
+        for chunk in missing.chunks(MAX_MINTS_PER_REQUEST) {
+            let mints = chunk.iter().map(Pubkey::to_string).collect::<Vec<_>>().join(",");
+            let request = self.http.get(&url).query(&[("mints", mints)]);
+            let response = self.authenticated(request).send().await?;
+            if !response.status().is_success() {
+                return Err(status_error(response.status(), response).await);
+            }
+            let tokens = response.json::<Vec<Token>>().await?;
+
+            let mut found: HashMap<Pubkey, Token> = tokens.into_iter().map(|t| (t.address, t)).collect();
+            for mint in chunk {
+                let token = found.remove(mint);
+                self.insert(*mint, token.clone());
+                if let Some(token) = token {
+                    resolved.insert(*mint, token);
+                }
+            }
+        }
+        Ok(resolved)
+    }
+
+    /// Cached metadata for `mint`, without fetching.
+    ///
+    /// Useful from synchronous callbacks; pair with
+    /// [`resolve_many`](Self::resolve_many) to warm the cache.
+    pub fn get_cached(&self, mint: &Pubkey) -> Option<Token> {
+        self.cached_entry(mint).flatten()
+    }
+
+    /// Drop all cached entries.
+    pub fn clear(&self) {
+        self.cache.lock().unwrap().clear();
+    }
+
+    /// Fresh cache entry for `mint`: `Some(None)` for a cached unknown mint.
+    fn cached_entry(&self, mint: &Pubkey) -> Option<Option<Token>> {
+        let cache = self.cache.lock().unwrap();
+        cache
+            .get(mint)
+            .filter(|entry| entry.fetched_at.elapsed() < self.config.ttl)
+            .map(|entry| entry.token.clone())
+    }
+
+    fn insert(&self, mint: Pubkey, token: Option<Token>) {
+        let entry = CacheEntry {
+            token,
+            fetched_at: Instant::now(),
+        };
+        self.cache.lock().unwrap().insert(mint, entry);
+    }
+
+    fn authenticated(&self, request: RequestBuilder) -> RequestBuilder {
+        super::authenticated(request, &self.config.api_key, self.config.auth)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    fn token(key: u8, symbol: &str) -> Token {
+        Token {
+            address: Pubkey::new([key; 32]),
+            symbol: symbol.to_string(),
+            name: symbol.to_string(),
+            decimals: 6,
+            logo_uri: None,
+            tags: None,
+            extensions: None,
+        }
+    }
+
+    /// Serve `/v1/tokens` for mint `[1; 32]` only, counting requests.
+    async fn serve(requests: Arc<AtomicUsize>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            loop {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                requests.fetch_add(1, Ordering::SeqCst);
+                let mut buf = vec![0u8; 4096];
+                let n = stream.read(&mut buf).await.unwrap();
+                let head = String::from_utf8_lossy(&buf[..n]).to_string();
+                let path = head.split_whitespace().nth(1).unwrap_or_default().to_string();
+                let known = token(1, "USDC");
+                let (status, body) = if path.starts_with("/v1/tokens?") {
+                    ("200 OK", serde_json::to_string(&vec![known]).unwrap())
+                } else if path.starts_with(&format!("/v1/tokens/{}", known.address)) {
+                    ("200 OK", serde_json::to_string(&known).unwrap())
+                } else {
+                    ("404 Not Found", String::new())
+                };
+                let response = format!(
+                    "HTTP/1.1 {}\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                );
+                stream.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+        format!("http://{}", addr)
+    }
+
+    #[tokio::test]
+    async fn test_resolve_caches_hits_and_misses() {
+        let requests = Arc::new(AtomicUsize::new(0));
+        let registry = TokenRegistry::new(TokenRegistryConfig {
+            base_url: serve(requests.clone()).await,
+            ..Default::default()
+        })
+        .unwrap();
+
+        let known = Pubkey::new([1; 32]);
+        let unknown = Pubkey::new([2; 32]);
+        assert_eq!(registry.resolve(&known).await.unwrap().unwrap().symbol, "USDC");
+        assert!(registry.resolve(&unknown).await.unwrap().is_none());
+        assert!(registry.resolve(&known).await.unwrap().is_some());
+        assert!(registry.resolve(&unknown).await.unwrap().is_none());
+        assert_eq!(requests.load(Ordering::SeqCst), 2);
+        assert_eq!(registry.get_cached(&known).unwrap().symbol, "USDC");
+    }
+
+    #[tokio::test]
+    async fn test_resolve_many_fetches_misses_once() {
+        let requests = Arc::new(AtomicUsize::new(0));
+        let registry = TokenRegistry::new(TokenRegistryConfig {
+            base_url: serve(requests.clone()).await,
+            ..Default::default()
+        })
+        .unwrap();
+
+        let mints = [Pubkey::new([1; 32]), Pubkey::new([2; 32]), Pubkey::new([1; 32])];
+        let tokens = registry.resolve_many(&mints).await.unwrap();
+        assert_eq!(tokens.len(), 1);
+        assert_eq!(tokens[&mints[0]].symbol, "USDC");
+
+        registry.resolve_many(&mints).await.unwrap();
+        assert!(registry.resolve(&mints[1]).await.unwrap().is_none());
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+
+        registry.clear();
+        assert!(registry.get_cached(&mints[0]).is_none());
+    }
+}
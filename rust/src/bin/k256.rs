@@ -0,0 +1,293 @@
+//! `k256` command-line tool for tapping and inspecting the K256 feeds.
+//!
+//! Usage:
+//!   K256_API_KEY=your-key k256 tail pools --protocol RaydiumClmm
+//!   k256 fees --account <pubkey>
+//!   k256 blockhash --watch
+//!   k256 leaders --next 10
+//!
+//! Add `--json` for one JSON object per line instead of pretty output.
+
+use std::process::ExitCode;
+use std::sync::{Arc, Mutex};
+
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use k256_sdk::leader_ws::{
+    LeaderConfig, LeaderTracker, LeaderWebSocketClient, CHANNEL_LEADER_SCHEDULE, CHANNEL_SLOTS,
+};
+use k256_sdk::{Blockhash, Config, FeeMarket, K256WebSocketClient, PoolUpdate, SubscribeRequest};
+use tokio::sync::mpsc;
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+fn command() -> Command {
+    Command::new("k256")
+        .about("Tap and inspect the K256 feeds")
+        .subcommand_required(true)
+        .arg(
+            Arg::new("api-key")
+                .long("api-key")
+                .value_name("KEY")
+                .global(true)
+                .help("K256 API key (defaults to $K256_API_KEY)"),
+        )
+        .arg(
+            Arg::new("endpoint")
+                .long("endpoint")
+                .value_name("URL")
+                .global(true)
+                .help("WebSocket endpoint URL of the feed"),
+        )
+        .arg(
+            Arg::new("json")
+                .long("json")
+                .global(true)
+                .action(ArgAction::SetTrue)
+                .help("Print one JSON object per line"),
+        )
+        .subcommand(
+            Command::new("tail")
+                .about("Stream messages of a channel")
+                .subcommand_required(true)
+                .subcommand(
+                    Command::new("pools")
+                        .about("Stream pool updates")
+                        .arg(
+                            Arg::new("protocol")
+                                .long("protocol")
+                                .value_name("PROTOCOL")
+                                .action(ArgAction::Append)
+                                .help("Only pools of this DEX protocol (repeatable)"),
+                        )
+                        .arg(
+                            Arg::new("pool")
+                                .long("pool")
+                                .value_name("ADDRESS")
+                                .action(ArgAction::Append)
+                                .help("Only this pool address (repeatable)"),
+                        ),
+                ),
+        )
+        .subcommand(
+            Command::new("fees").about("Stream fee market updates").arg(
+                Arg::new("account")
+                    .long("account")
+                    .value_name("PUBKEY")
+                    .action(ArgAction::Append)
+                    .help("Only fees of this writable account (repeatable)"),
+            ),
+        )
+        .subcommand(
+            Command::new("blockhash").about("Print the recent blockhash").arg(
+                Arg::new("watch")
+                    .long("watch")
+                    .action(ArgAction::SetTrue)
+                    .help("Keep printing new blockhashes"),
+            ),
+        )
+        .subcommand(
+            Command::new("leaders").about("Print upcoming slot leaders").arg(
+                Arg::new("next")
+                    .long("next")
+                    .value_name("N")
+                    .value_parser(clap::value_parser!(usize))
+                    .default_value("10")
+                    .help("Number of upcoming slots to show"),
+            ),
+        )
+}
+
+/// Output settings shared by all subcommands.
+struct Output {
+    json: bool,
+}
+
+impl Output {
+    fn pool_update(&self, update: &PoolUpdate) -> String {
+        if self.json {
+            return to_json(update);
+        }
+        format!(
+            "[Pool Update] {} (slot {}) {} balances={:?}",
+            update.pool_address, update.slot, update.protocol_name, update.token_balances
+        )
+    }
+
+    fn fee_market(&self, fees: &FeeMarket) -> String {
+        if self.json {
+            return to_json(fees);
+        }
+        let mut line = format!(
+            "[Fee Market] slot={} recommended={} state={} stale={}",
+            fees.slot, fees.recommended, fees.state, fees.is_stale
+        );
+        for account in &fees.accounts {
+            line.push_str(&format!("\n  {}: p75={} util={:.1}%", account.pubkey, account.p75, account.utilization_pct));
+        }
+        line
+    }
+
+    fn blockhash(&self, bh: &Blockhash) -> String {
+        if self.json {
+            return to_json(bh);
+        }
+        format!(
+            "[Blockhash] {} (slot {}) last_valid_block_height={}",
+            bh.blockhash, bh.slot, bh.last_valid_block_height
+        )
+    }
+
+    fn leaders(&self, leaders: &[(u64, &str)]) -> String {
+        if self.json {
+            let rows: Vec<_> = leaders
+                .iter()
+                .map(|(slot, leader)| serde_json::json!({ "slot": slot, "leader": leader }))
+                .collect();
+            return serde_json::Value::Array(rows).to_string();
+        }
+        let rows: Vec<String> = leaders
+            .iter()
+            .map(|(slot, leader)| format!("{:>12}  {}", slot, leader))
+            .collect();
+        rows.join("\n")
+    }
+}
+
+fn to_json<T: serde::Serialize>(value: &T) -> String {
+    serde_json::to_string(value).expect("feed types serialize to JSON")
+}
+
+fn api_key(matches: &ArgMatches) -> Result<String, BoxError> {
+    match matches.get_one::<String>("api-key") {
+        Some(key) => Ok(key.clone()),
+        None => std::env::var("K256_API_KEY").map_err(|_| "set --api-key or K256_API_KEY".into()),
+    }
+}
+
+fn values(matches: &ArgMatches, id: &str) -> Vec<String> {
+    matches.get_many::<String>(id).into_iter().flatten().cloned().collect()
+}
+
+/// Subscribe with `request` and print each line the callbacks produce.
+///
+/// Stops after the first line when `once` is set.
+async fn stream(
+    config: Config,
+    request: SubscribeRequest,
+    once: bool,
+    register: impl FnOnce(&K256WebSocketClient, mpsc::UnboundedSender<String>),
+) -> Result<(), BoxError> {
+    let client = K256WebSocketClient::new(config);
+    let (tx, mut lines) = mpsc::unbounded_channel();
+    register(&client, tx);
+    let errors = client.on_error(|message| eprintln!("server error: {}", message));
+
+    client.connect().await?;
+    client.subscribe(request).await?;
+    while let Some(line) = lines.recv().await {
+        println!("{}", line);
+        if once {
+            break;
+        }
+    }
+    errors.remove();
+    Ok(())
+}
+
+async fn run(matches: ArgMatches) -> Result<(), BoxError> {
+    let output = Arc::new(Output {
+        json: matches.get_flag("json"),
+    });
+    let endpoint = matches.get_one::<String>("endpoint").cloned();
+    let mut config = Config {
+        api_key: api_key(&matches)?,
+        ..Config::default()
+    };
+    if let Some(endpoint) = &endpoint {
+        config.endpoint = endpoint.clone();
+    }
+
+    match matches.subcommand() {
+        Some(("tail", tail)) => {
+            let Some(("pools", pools)) = tail.subcommand() else {
+                unreachable!("subcommand is required");
+            };
+            let request = SubscribeRequest::builder()
+                .pools()
+                .protocols(values(pools, "protocol"))
+                .pool_addresses(values(pools, "pool"))
+                .build()?;
+            stream(config, request, false, |client, tx| {
+                client
+                    .on_pool_update(move |update| {
+                        let _ = tx.send(output.pool_update(&update));
+                    })
+                    .detach();
+            })
+            .await
+        }
+        Some(("fees", fees)) => {
+            let request = SubscribeRequest::builder()
+                .priority_fees()
+                .fee_accounts(values(fees, "account"))
+                .build()?;
+            stream(config, request, false, |client, tx| {
+                client
+                    .on_fee_market(move |fees| {
+                        let _ = tx.send(output.fee_market(&fees));
+                    })
+                    .detach();
+            })
+            .await
+        }
+        Some(("blockhash", blockhash)) => {
+            let request = SubscribeRequest::builder().blockhash().build()?;
+            let once = !blockhash.get_flag("watch");
+            stream(config, request, once, |client, tx| {
+                client
+                    .on_blockhash(move |bh| {
+                        let _ = tx.send(output.blockhash(&bh));
+                    })
+                    .detach();
+            })
+            .await
+        }
+        Some(("leaders", leaders)) => {
+            let next = *leaders.get_one::<usize>("next").expect("has a default");
+            let mut config = LeaderConfig {
+                api_key: config.api_key,
+                channels: vec![CHANNEL_LEADER_SCHEDULE.to_string(), CHANNEL_SLOTS.to_string()],
+                ..LeaderConfig::default()
+            };
+            if let Some(endpoint) = endpoint {
+                config.url = endpoint;
+            }
+            tokio::task::spawn_blocking(move || print_leaders(config, next, output)).await?
+        }
+        _ => unreachable!("subcommand is required"),
+    }
+}
+
+/// Print the next `n` leaders on every slot update.
+fn print_leaders(config: LeaderConfig, n: usize, output: Arc<Output>) -> Result<(), BoxError> {
+    let tracker = Mutex::new(LeaderTracker::new());
+    let client = LeaderWebSocketClient::new(config, move |msg| {
+        let mut tracker = tracker.lock().unwrap();
+        if tracker.apply(&msg).unwrap_or(false) && msg.msg_type == "slot_update" && tracker.has_schedule() {
+            println!("{}", output.leaders(&tracker.upcoming_leaders(n)));
+        }
+    });
+    client.connect_blocking()
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let matches = command().get_matches();
+    match run(matches).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
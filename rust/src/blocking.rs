@@ -0,0 +1,273 @@
+//! Blocking (synchronous) WebSocket client.
+//!
+//! Wraps [`K256WebSocketClient`] in an internal tokio runtime for
+//! applications that do not run an async executor of their own.
+//!
+//! # Example
+//! ```rust,no_run
+//! use k256_sdk::blocking::K256BlockingClient;
+//! use k256_sdk::{Config, DecodedMessage, SubscribeRequest};
+//!
+//! let client = K256BlockingClient::new(Config {
+//!     api_key: "your-api-key".to_string(),
+//!     ..Config::default()
+//! })?;
+//! client.connect()?;
+//! client.subscribe(SubscribeRequest::default())?;
+//!
+//! for msg in client.messages() {
+//!     if let DecodedMessage::PoolUpdate(update) = msg {
+//!         println!("{} @ slot {}", update.pool_address, update.slot);
+//!     }
+//! }
+//! # Ok::<(), Box<dyn std::error::Error + Send + Sync>>(())
+//! ```
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tokio::runtime::{Builder, Runtime};
+
+use crate::types::ConnectionState;
+use crate::ws::{Channel, Config, DecodedMessage, K256WebSocketClient, SubscribeRequest};
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Messages queued by [`K256BlockingClient::new`].
+pub const DEFAULT_QUEUE_CAPACITY: usize = 1024;
+
+/// Synchronous K256 WebSocket client.
+///
+/// Messages are queued by a background runtime thread and consumed with
+/// [`next_message`](Self::next_message) or [`messages`](Self::messages).
+/// The queue is bounded: while it is full, new messages are dropped and
+/// counted in [`dropped_messages`](Self::dropped_messages), so a client used
+/// only through callbacks on [`as_async`](Self::as_async) holds at most
+/// `capacity` messages.
+pub struct K256BlockingClient {
+    client: K256WebSocketClient,
+    queue: Arc<MessageQueue>,
+    runtime: Runtime,
+}
+
+/// Bounded queue between the client's callbacks and `next_message`.
+struct MessageQueue {
+    capacity: usize,
+    /// Taken when the connection closes for good, ending `next_message`
+    tx: Mutex<Option<SyncSender<DecodedMessage>>>,
+    rx: Mutex<Receiver<DecodedMessage>>,
+    dropped: AtomicU64,
+}
+
+impl MessageQueue {
+    fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        let (tx, rx) = mpsc::sync_channel(capacity);
+        Self {
+            capacity,
+            tx: Mutex::new(Some(tx)),
+            rx: Mutex::new(rx),
+            dropped: AtomicU64::new(0),
+        }
+    }
+
+    fn push(&self, msg: DecodedMessage) {
+        if let Some(tx) = self.tx.lock().unwrap().as_ref() {
+            if let Err(TrySendError::Full(_)) = tx.try_send(msg) {
+                self.dropped.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+
+    fn close(&self) {
+        self.tx.lock().unwrap().take();
+    }
+
+    /// Start a fresh queue if the previous connection closed it.
+    fn reopen(&self) {
+        let mut tx = self.tx.lock().unwrap();
+        if tx.is_none() {
+            let (new_tx, rx) = mpsc::sync_channel(self.capacity);
+            *self.rx.lock().unwrap() = rx;
+            *tx = Some(new_tx);
+        }
+    }
+}
+
+impl K256BlockingClient {
+    /// Create a new blocking client queueing up to
+    /// [`DEFAULT_QUEUE_CAPACITY`] messages. Does not connect yet.
+    pub fn new(config: Config) -> std::io::Result<Self> {
+        Self::with_queue_capacity(config, DEFAULT_QUEUE_CAPACITY)
+    }
+
+    /// Create a new blocking client queueing up to `capacity` messages.
+    /// Does not connect yet.
+    pub fn with_queue_capacity(config: Config, capacity: usize) -> std::io::Result<Self> {
+        Self::from_client(K256WebSocketClient::new(config), capacity)
+    }
+
+    fn from_client(client: K256WebSocketClient, capacity: usize) -> std::io::Result<Self> {
+        let runtime = Builder::new_multi_thread()
+            .worker_threads(1)
+            .thread_name("k256-ws")
+            .enable_all()
+            .build()?;
+
+        let queue = Arc::new(MessageQueue::new(capacity));
+        let sink = queue.clone();
+        client.on_message(move |msg| sink.push(msg)).detach();
+        let sink = queue.clone();
+        client
+            .on_connection_state(move |state| {
+                if matches!(state, ConnectionState::Closed { .. }) {
+                    sink.close();
+                }
+            })
+            .detach();
+
+        Ok(Self { client, queue, runtime })
+    }
+
+    /// Connect to the K256 WebSocket, blocking until the handshake completes.
+    pub fn connect(&self) -> Result<(), BoxError> {
+        self.queue.reopen();
+        self.runtime.block_on(self.client.connect())
+    }
+
+    /// Block until the next message arrives.
+    ///
+    /// Returns `None` once the connection closed for good (it failed and
+    /// `Config::reconnect` is off) and the queued messages were consumed.
+    pub fn next_message(&self) -> Option<DecodedMessage> {
+        self.queue.rx.lock().unwrap().recv().ok()
+    }
+
+    /// Wait up to `timeout` for the next message.
+    pub fn next_message_timeout(&self, timeout: Duration) -> Option<DecodedMessage> {
+        self.queue.rx.lock().unwrap().recv_timeout(timeout).ok()
+    }
+
+    /// Return the next queued message without blocking.
+    pub fn try_next_message(&self) -> Option<DecodedMessage> {
+        self.queue.rx.lock().unwrap().try_recv().ok()
+    }
+
+    /// Messages dropped because the queue was full.
+    pub fn dropped_messages(&self) -> u64 {
+        self.queue.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Blocking iterator over incoming messages.
+    pub fn messages(&self) -> impl Iterator<Item = DecodedMessage> + '_ {
+        std::iter::from_fn(move || self.next_message())
+    }
+
+    /// Subscribe to channels. See [`K256WebSocketClient::subscribe`].
+    pub fn subscribe(&self, request: SubscribeRequest) -> Result<(), BoxError> {
+        self.runtime.block_on(self.client.subscribe(request))
+    }
+
+    /// Unsubscribe from all channels.
+    pub fn unsubscribe(&self) -> Result<(), BoxError> {
+        self.runtime.block_on(self.client.unsubscribe())
+    }
+
+    /// Unsubscribe from some channels.
+    /// See [`K256WebSocketClient::unsubscribe_channels`].
+    pub fn unsubscribe_channels(&self, channels: &[Channel]) -> Result<(), BoxError> {
+        self.runtime.block_on(self.client.unsubscribe_channels(channels))
+    }
+
+    /// Stop receiving updates for some pools.
+    /// See [`K256WebSocketClient::unsubscribe_pools`].
+    pub fn unsubscribe_pools(&self, pools: &[&str]) -> Result<(), BoxError> {
+        self.runtime.block_on(self.client.unsubscribe_pools(pools))
+    }
+
+    /// Current effective subscription.
+    pub fn subscription(&self) -> Option<SubscribeRequest> {
+        self.client.subscription()
+    }
+
+    /// Add pool addresses to the pool filter.
+    pub fn add_pools(&self, pools: &[&str]) -> Result<(), BoxError> {
+        self.runtime.block_on(self.client.add_pools(pools))
+    }
+
+    /// Remove pool addresses from the pool filter.
+    pub fn remove_pools(&self, pools: &[&str]) -> Result<(), BoxError> {
+        self.runtime.block_on(self.client.remove_pools(pools))
+    }
+
+    /// Add DEX protocols to the protocol filter.
+    pub fn add_protocols(&self, protocols: &[&str]) -> Result<(), BoxError> {
+        self.runtime.block_on(self.client.add_protocols(protocols))
+    }
+
+    /// Remove DEX protocols from the protocol filter.
+    pub fn remove_protocols(&self, protocols: &[&str]) -> Result<(), BoxError> {
+        self.runtime.block_on(self.client.remove_protocols(protocols))
+    }
+
+    /// Send a keepalive ping immediately.
+    pub fn ping(&self) -> Result<(), BoxError> {
+        self.runtime.block_on(self.client.ping())
+    }
+
+    /// Last measured ping round-trip time.
+    pub fn latency(&self) -> Option<Duration> {
+        self.client.latency()
+    }
+
+    /// Underlying async client, e.g. to register typed callbacks.
+    pub fn as_async(&self) -> &K256WebSocketClient {
+        &self.client
+    }
+}
+
+#[cfg(all(test, feature = "testing"))]
+mod tests {
+    use super::*;
+    use crate::testing::{frames, MockGateway};
+    use crate::types::{Blockhash, Slot};
+
+    #[test]
+    fn test_queue_is_bounded_and_ends_on_close() {
+        let gateway = MockGateway::new();
+        let config = Config {
+            endpoint: "mock://gateway".to_string(),
+            reconnect: false,
+            ping_interval: Duration::ZERO,
+            ..Config::default()
+        };
+        let client = K256WebSocketClient::with_transport(config, gateway.transport());
+        let client = K256BlockingClient::from_client(client, 2).unwrap();
+        client.connect().unwrap();
+        let session = client.runtime.block_on(gateway.accept());
+        for slot in 1..=3 {
+            session.send(frames::blockhash(&Blockhash {
+                slot: Slot(slot),
+                blockhash: bs58::encode([1u8; 32]).into_string(),
+                ..Blockhash::default()
+            }));
+        }
+        // The third message finds the queue full
+        while client.dropped_messages() == 0 {
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        session.close();
+
+        let slots: Vec<_> = client
+            .messages()
+            .map(|msg| match msg {
+                DecodedMessage::Blockhash(bh) => bh.slot.get(),
+                other => panic!("unexpected {:?}", other),
+            })
+            .collect();
+        assert_eq!(slots, [1, 2]);
+        assert_eq!(client.dropped_messages(), 1);
+    }
+}
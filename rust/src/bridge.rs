@@ -0,0 +1,714 @@
+//! Republish decoded messages to a message bus.
+//!
+//! A [`Bridge`] listens to a [`K256WebSocketClient`] and forwards every
+//! decoded message to a [`Publisher`], on a topic chosen by a [`TopicMap`]
+//! and encoded as JSON or bincode. Publishing runs on its own task behind a
+//! bounded queue, so a slow broker never stalls the connection; messages
+//! arriving while the queue is full are dropped and counted.
+//!
+//! [`RedisStreams`] publishes to Redis streams (`XADD`) without extra
+//! dependencies. Other brokers plug in by implementing [`Publisher`], e.g.
+//! over an rdkafka `FutureProducer`.
+//!
+//! # Example
+//! ```rust,no_run
+//! use k256_sdk::bridge::{Bridge, BridgeConfig, RedisStreams, TopicMap};
+//! use k256_sdk::{Config, K256WebSocketClient, SubscribeRequest};
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+//! let client = K256WebSocketClient::new(Config::default());
+//! let redis = RedisStreams::connect("127.0.0.1:6379").await?.with_max_len(100_000);
+//! let config = BridgeConfig {
+//!     topics: TopicMap::new("k256").route("pool_update", "k256.pools").skip("heartbeat"),
+//!     ..BridgeConfig::default()
+//! };
+//! let _bridge = Bridge::attach(&client, config, redis);
+//!
+//! client.connect().await?;
+//! client.subscribe(SubscribeRequest::default()).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+use tokio::sync::mpsc;
+use tracing::{error, warn};
+
+use crate::transport::BoxFuture;
+use crate::types::{
+    AccountFee, Blockhash, FeeMarket, Heartbeat, NetworkState, PoolUpdate, PriceEntry, Protocol, Pubkey, Quote,
+    ReplayComplete, RoutePlanStep, ServerInfo, Slot, TimestampMs,
+};
+use crate::ws::{CallbackHandle, DecodedMessage, K256WebSocketClient};
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Message bus the bridge publishes to.
+pub trait Publisher: Send + 'static {
+    /// Publish one encoded message to `topic`.
+    fn publish<'a>(&'a mut self, topic: &'a str, payload: &'a [u8]) -> BoxFuture<'a, Result<(), BoxError>>;
+}
+
+/// Payload encoding of published messages.
+///
+/// Consumers read payloads back with [`Encoding::decode`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Encoding {
+    /// [`DecodedMessage`] as JSON (`{"type": ..., "data": ...}`)
+    #[default]
+    Json,
+    /// [`DecodedMessage`] as bincode, externally tagged with every field
+    /// present and keys as raw bytes
+    Bincode,
+}
+
+impl Encoding {
+    /// Encode `msg`.
+    pub fn encode(&self, msg: &DecodedMessage) -> Result<Vec<u8>, BoxError> {
+        match self {
+            Self::Json => Ok(serde_json::to_vec(msg)?),
+            Self::Bincode => Ok(bincode::serialize(&WireMessage::from(msg))?),
+        }
+    }
+
+    /// Decode a payload produced by [`Encoding::encode`].
+    pub fn decode(&self, payload: &[u8]) -> Result<DecodedMessage, BoxError> {
+        match self {
+            Self::Json => Ok(serde_json::from_slice(payload)?),
+            Self::Bincode => bincode::deserialize::<WireMessage>(payload)?.try_into(),
+        }
+    }
+}
+
+/// Bincode form of [`DecodedMessage`].
+///
+/// `DecodedMessage` is adjacently tagged, and quotes, fee markets and the
+/// server info skip absent fields or accept several JSON shapes; bincode
+/// can read none of these back, so payloads that need it are mirrored here
+/// field for field.
+#[derive(Serialize, Deserialize)]
+enum WireMessage {
+    PoolUpdate(PoolUpdate),
+    PoolUpdateBatch(Vec<PoolUpdate>),
+    PoolSnapshot(Vec<PoolUpdate>),
+    FeeMarket(WireFeeMarket),
+    Blockhash(Blockhash),
+    Quote(WireQuote),
+    Heartbeat(Heartbeat),
+    PriceUpdate(PriceEntry),
+    PriceBatch(Vec<PriceEntry>),
+    PriceSnapshot(Vec<PriceEntry>),
+    Error(String),
+    Pong { timestamp_ms: Option<TimestampMs> },
+    Subscribed { channels: Vec<String>, server: WireServerInfo },
+    ReplayComplete(ReplayComplete),
+}
+
+#[derive(Serialize, Deserialize)]
+struct WireFeeMarket {
+    slot: Slot,
+    timestamp_ms: TimestampMs,
+    recommended: u64,
+    /// [`NetworkState`] wire code
+    state: u8,
+    is_stale: bool,
+    block_utilization_pct: f32,
+    blocks_in_window: u32,
+    accounts: Vec<AccountFee>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct WireQuote {
+    input_mint: [u8; 32],
+    output_mint: [u8; 32],
+    in_amount: u64,
+    out_amount: u64,
+    price_impact_pct: f64,
+    slot: Slot,
+    timestamp_ms: TimestampMs,
+    route_plan: Vec<WireRoutePlanStep>,
+    other_amount_threshold: Option<u64>,
+    swap_mode: String,
+    request_id: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct WireRoutePlanStep {
+    venue: Protocol,
+    pool: [u8; 32],
+    in_mint: [u8; 32],
+    out_mint: [u8; 32],
+    in_amount: u64,
+    out_amount: u64,
+    fee_amount: u64,
+    fee_mint: Option<[u8; 32]>,
+    fee_bps: Option<u16>,
+    percent: Option<u8>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct WireServerInfo {
+    protocol_version: Option<u32>,
+    server_version: Option<String>,
+    features: Vec<String>,
+}
+
+impl From<&DecodedMessage> for WireMessage {
+    fn from(msg: &DecodedMessage) -> Self {
+        match msg {
+            DecodedMessage::PoolUpdate(update) => Self::PoolUpdate(update.clone()),
+            DecodedMessage::PoolUpdateBatch(updates) => Self::PoolUpdateBatch(updates.clone()),
+            DecodedMessage::PoolSnapshot(updates) => Self::PoolSnapshot(updates.clone()),
+            DecodedMessage::FeeMarket(fees) => Self::FeeMarket(WireFeeMarket {
+                slot: fees.slot,
+                timestamp_ms: fees.timestamp_ms,
+                recommended: fees.recommended,
+                state: fees.state as u8,
+                is_stale: fees.is_stale,
+                block_utilization_pct: fees.block_utilization_pct,
+                blocks_in_window: fees.blocks_in_window,
+                accounts: fees.accounts.clone(),
+            }),
+            DecodedMessage::Blockhash(blockhash) => Self::Blockhash(blockhash.clone()),
+            DecodedMessage::Quote(quote) => Self::Quote(WireQuote {
+                input_mint: quote.input_mint.to_bytes(),
+                output_mint: quote.output_mint.to_bytes(),
+                in_amount: quote.in_amount,
+                out_amount: quote.out_amount,
+                price_impact_pct: quote.price_impact_pct,
+                slot: quote.slot,
+                timestamp_ms: quote.timestamp_ms,
+                route_plan: quote
+                    .route_plan
+                    .iter()
+                    .map(|step| WireRoutePlanStep {
+                        venue: step.venue.clone(),
+                        pool: step.pool.to_bytes(),
+                        in_mint: step.in_mint.to_bytes(),
+                        out_mint: step.out_mint.to_bytes(),
+                        in_amount: step.in_amount,
+                        out_amount: step.out_amount,
+                        fee_amount: step.fee_amount,
+                        fee_mint: step.fee_mint.map(Pubkey::to_bytes),
+                        fee_bps: step.fee_bps,
+                        percent: step.percent,
+                    })
+                    .collect(),
+                other_amount_threshold: quote.other_amount_threshold,
+                swap_mode: quote.swap_mode.clone(),
+                request_id: quote.request_id.clone(),
+            }),
+            DecodedMessage::Heartbeat(heartbeat) => Self::Heartbeat(*heartbeat),
+            DecodedMessage::PriceUpdate(entry) => Self::PriceUpdate(entry.clone()),
+            DecodedMessage::PriceBatch(entries) => Self::PriceBatch(entries.clone()),
+            DecodedMessage::PriceSnapshot(entries) => Self::PriceSnapshot(entries.clone()),
+            DecodedMessage::Error(message) => Self::Error(message.clone()),
+            DecodedMessage::Pong { timestamp_ms } => Self::Pong { timestamp_ms: *timestamp_ms },
+            DecodedMessage::Subscribed { channels, server } => Self::Subscribed {
+                channels: channels.clone(),
+                server: WireServerInfo {
+                    protocol_version: server.protocol_version,
+                    server_version: server.server_version.clone(),
+                    features: server.features.clone(),
+                },
+            },
+            DecodedMessage::ReplayComplete(complete) => Self::ReplayComplete(*complete),
+        }
+    }
+}
+
+impl TryFrom<WireMessage> for DecodedMessage {
+    type Error = BoxError;
+
+    fn try_from(msg: WireMessage) -> Result<Self, BoxError> {
+        Ok(match msg {
+            WireMessage::PoolUpdate(update) => Self::PoolUpdate(update),
+            WireMessage::PoolUpdateBatch(updates) => Self::PoolUpdateBatch(updates),
+            WireMessage::PoolSnapshot(updates) => Self::PoolSnapshot(updates),
+            WireMessage::FeeMarket(fees) => Self::FeeMarket(FeeMarket {
+                slot: fees.slot,
+                timestamp_ms: fees.timestamp_ms,
+                recommended: fees.recommended,
+                state: NetworkState::try_from(fees.state)
+                    .map_err(|code| format!("invalid network state: {}", code))?,
+                is_stale: fees.is_stale,
+                block_utilization_pct: fees.block_utilization_pct,
+                blocks_in_window: fees.blocks_in_window,
+                accounts: fees.accounts,
+            }),
+            WireMessage::Blockhash(blockhash) => Self::Blockhash(blockhash),
+            WireMessage::Quote(quote) => Self::Quote(Quote {
+                input_mint: Pubkey::new(quote.input_mint),
+                output_mint: Pubkey::new(quote.output_mint),
+                in_amount: quote.in_amount,
+                out_amount: quote.out_amount,
+                price_impact_pct: quote.price_impact_pct,
+                slot: quote.slot,
+                timestamp_ms: quote.timestamp_ms,
+                route_plan: quote
+                    .route_plan
+                    .into_iter()
+                    .map(|step| RoutePlanStep {
+                        venue: step.venue,
+                        pool: Pubkey::new(step.pool),
+                        in_mint: Pubkey::new(step.in_mint),
+                        out_mint: Pubkey::new(step.out_mint),
+                        in_amount: step.in_amount,
+                        out_amount: step.out_amount,
+                        fee_amount: step.fee_amount,
+                        fee_mint: step.fee_mint.map(Pubkey::new),
+                        fee_bps: step.fee_bps,
+                        percent: step.percent,
+                    })
+                    .collect(),
+                other_amount_threshold: quote.other_amount_threshold,
+                swap_mode: quote.swap_mode,
+                request_id: quote.request_id,
+            }),
+            WireMessage::Heartbeat(heartbeat) => Self::Heartbeat(heartbeat),
+            WireMessage::PriceUpdate(entry) => Self::PriceUpdate(entry),
+            WireMessage::PriceBatch(entries) => Self::PriceBatch(entries),
+            WireMessage::PriceSnapshot(entries) => Self::PriceSnapshot(entries),
+            WireMessage::Error(message) => Self::Error(message),
+            WireMessage::Pong { timestamp_ms } => Self::Pong { timestamp_ms },
+            WireMessage::Subscribed { channels, server } => Self::Subscribed {
+                channels,
+                server: ServerInfo {
+                    protocol_version: server.protocol_version,
+                    server_version: server.server_version,
+                    features: server.features,
+                },
+            },
+            WireMessage::ReplayComplete(complete) => Self::ReplayComplete(complete),
+        })
+    }
+}
+
+/// Topic per message type.
+///
+/// Message types are the snake_case names used as the JSON `type` tag
+/// (`"pool_update"`, `"fee_market"`, ...). Unrouted types go to
+/// `<prefix>.<type>`.
+#[derive(Debug, Clone)]
+pub struct TopicMap {
+    prefix: String,
+    routes: HashMap<String, String>,
+    skipped: HashSet<String>,
+}
+
+impl Default for TopicMap {
+    fn default() -> Self {
+        Self::new("k256")
+    }
+}
+
+impl TopicMap {
+    /// Publish every message type to `<prefix>.<type>`.
+    pub fn new(prefix: impl Into<String>) -> Self {
+        Self {
+            prefix: prefix.into(),
+            routes: HashMap::new(),
+            skipped: HashSet::new(),
+        }
+    }
+
+    /// Publish `msg_type` to `topic`.
+    pub fn route(mut self, msg_type: impl Into<String>, topic: impl Into<String>) -> Self {
+        self.routes.insert(msg_type.into(), topic.into());
+        self
+    }
+
+    /// Do not publish `msg_type`.
+    pub fn skip(mut self, msg_type: impl Into<String>) -> Self {
+        self.skipped.insert(msg_type.into());
+        self
+    }
+
+    /// Topic for `msg_type`, or `None` if it is skipped.
+    pub fn topic(&self, msg_type: &str) -> Option<String> {
+        if self.skipped.contains(msg_type) {
+            return None;
+        }
+        Some(match self.routes.get(msg_type) {
+            Some(topic) => topic.clone(),
+            None => format!("{}.{}", self.prefix, msg_type),
+        })
+    }
+}
+
+/// Configuration for a [`Bridge`].
+#[derive(Debug, Clone)]
+pub struct BridgeConfig {
+    /// Topic per message type
+    pub topics: TopicMap,
+    /// Payload encoding
+    pub encoding: Encoding,
+    /// Messages waiting to be published before new ones are dropped
+    pub queue_capacity: usize,
+}
+
+impl Default for BridgeConfig {
+    fn default() -> Self {
+        Self {
+            topics: TopicMap::default(),
+            encoding: Encoding::Json,
+            queue_capacity: 10_000,
+        }
+    }
+}
+
+/// Counters of a [`Bridge`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BridgeStats {
+    /// Messages published
+    pub published: u64,
+    /// Messages dropped because the queue was full
+    pub dropped: u64,
+    /// Messages that failed to encode or publish
+    pub failed: u64,
+}
+
+#[derive(Default)]
+struct Counters {
+    published: AtomicU64,
+    dropped: AtomicU64,
+    failed: AtomicU64,
+}
+
+/// Forwards a client's decoded messages to a [`Publisher`].
+///
+/// Dropping the bridge unregisters it from the client; queued messages are
+/// still published.
+pub struct Bridge {
+    counters: Arc<Counters>,
+    _registration: CallbackHandle,
+}
+
+impl Bridge {
+    /// Start forwarding messages of `client` to `publisher`.
+    ///
+    /// Must be called within a Tokio runtime.
+    pub fn attach(client: &K256WebSocketClient, config: BridgeConfig, publisher: impl Publisher) -> Self {
+        let (tx, rx) = mpsc::channel(config.queue_capacity.max(1));
+        let counters = Arc::new(Counters::default());
+        tokio::spawn(run_bridge(rx, config, publisher, counters.clone()));
+
+        let dropped = counters.clone();
+        let registration = client.on_message(move |msg| {
+            if tx.try_send(msg).is_err() {
+                dropped.dropped.fetch_add(1, Ordering::Relaxed);
+            }
+        });
+        Self {
+            counters,
+            _registration: registration,
+        }
+    }
+
+    /// Published, dropped and failed messages so far.
+    pub fn stats(&self) -> BridgeStats {
+        BridgeStats {
+            published: self.counters.published.load(Ordering::Relaxed),
+            dropped: self.counters.dropped.load(Ordering::Relaxed),
+            failed: self.counters.failed.load(Ordering::Relaxed),
+        }
+    }
+}
+
+async fn run_bridge(
+    mut rx: mpsc::Receiver<DecodedMessage>,
+    config: BridgeConfig,
+    mut publisher: impl Publisher,
+    counters: Arc<Counters>,
+) {
+    while let Some(msg) = rx.recv().await {
+        let Some(topic) = config.topics.topic(msg.kind()) else {
+            continue;
+        };
+        let result = match config.encoding.encode(&msg) {
+            Ok(payload) => publisher.publish(&topic, &payload).await,
+            Err(e) => Err(e),
+        };
+        match result {
+            Ok(()) => counters.published.fetch_add(1, Ordering::Relaxed),
+            Err(e) => {
+                error!("Failed to publish {} to {}: {}", msg.kind(), topic, e);
+                counters.failed.fetch_add(1, Ordering::Relaxed)
+            }
+        };
+    }
+}
+
+/// Publishes to Redis streams with `XADD <topic> * data <payload>`.
+pub struct RedisStreams {
+    stream: BufReader<TcpStream>,
+    max_len: Option<usize>,
+}
+
+impl RedisStreams {
+    /// Connect to the Redis server at `address` (`host:port`).
+    pub async fn connect(address: &str) -> Result<Self, BoxError> {
+        let stream = TcpStream::connect(address).await?;
+        Ok(Self {
+            stream: BufReader::new(stream),
+            max_len: None,
+        })
+    }
+
+    /// Authenticate with `AUTH <password>`.
+    pub async fn auth(mut self, password: &str) -> Result<Self, BoxError> {
+        self.command(&[b"AUTH", password.as_bytes()]).await?;
+        Ok(self)
+    }
+
+    /// Cap each stream at roughly `max_len` entries (`MAXLEN ~`).
+    pub fn with_max_len(mut self, max_len: usize) -> Self {
+        self.max_len = Some(max_len);
+        self
+    }
+
+    /// Send a command and read its reply, failing on error replies.
+    async fn command(&mut self, args: &[&[u8]]) -> Result<Vec<u8>, BoxError> {
+        let mut request = format!("*{}\r\n", args.len()).into_bytes();
+        for arg in args {
+            request.extend_from_slice(format!("${}\r\n", arg.len()).as_bytes());
+            request.extend_from_slice(arg);
+            request.extend_from_slice(b"\r\n");
+        }
+        self.stream.get_mut().write_all(&request).await?;
+        self.read_reply().await
+    }
+
+    /// Read a simple string, error or bulk string reply.
+    async fn read_reply(&mut self) -> Result<Vec<u8>, BoxError> {
+        let mut line = String::new();
+        if self.stream.read_line(&mut line).await? == 0 {
+            return Err("connection closed by redis".into());
+        }
+        let line = line.trim_end();
+        match line.split_at(line.len().min(1)) {
+            ("+", status) => Ok(status.as_bytes().to_vec()),
+            ("-", message) => Err(format!("redis error: {}", message).into()),
+            ("$", len) => {
+                let Ok(len) = len.parse::<usize>() else {
+                    // Null bulk string
+                    return Ok(Vec::new());
+                };
+                let mut data = vec![0; len + 2];
+                self.stream.read_exact(&mut data).await?;
+                data.truncate(len);
+                Ok(data)
+            }
+            _ => {
+                warn!("Unexpected redis reply: {}", line);
+                Err(format!("unexpected redis reply: {}", line).into())
+            }
+        }
+    }
+}
+
+impl Publisher for RedisStreams {
+    fn publish<'a>(&'a mut self, topic: &'a str, payload: &'a [u8]) -> BoxFuture<'a, Result<(), BoxError>> {
+        Box::pin(async move {
+            let max_len = self.max_len.map(|n| n.to_string());
+            let mut args: Vec<&[u8]> = vec![b"XADD", topic.as_bytes()];
+            if let Some(max_len) = &max_len {
+                args.extend([b"MAXLEN".as_slice(), b"~", max_len.as_bytes()]);
+            }
+            args.extend([b"*".as_slice(), b"data", payload]);
+            self.command(&args).await?;
+            Ok(())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::replay::FrameKind;
+    use crate::ws::{encode_pool_update, Config};
+    use tokio::net::TcpListener;
+
+    /// Publisher recording `(topic, payload)` pairs.
+    struct Recording(mpsc::UnboundedSender<(String, Vec<u8>)>);
+
+    impl Publisher for Recording {
+        fn publish<'a>(&'a mut self, topic: &'a str, payload: &'a [u8]) -> BoxFuture<'a, Result<(), BoxError>> {
+            let _ = self.0.send((topic.to_string(), payload.to_vec()));
+            Box::pin(async { Ok(()) })
+        }
+    }
+
+    #[test]
+    fn test_topic_map() {
+        let topics = TopicMap::new("feed").route("pool_update", "pools").skip("heartbeat");
+        assert_eq!(topics.topic("pool_update").as_deref(), Some("pools"));
+        assert_eq!(topics.topic("fee_market").as_deref(), Some("feed.fee_market"));
+        assert_eq!(topics.topic("heartbeat"), None);
+    }
+
+    #[tokio::test]
+    async fn test_bridge_publishes_decoded_messages() {
+        let client = K256WebSocketClient::new(Config::default());
+        let (tx, mut published) = mpsc::unbounded_channel();
+        let config = BridgeConfig {
+            topics: TopicMap::new("k256").skip("heartbeat"),
+            ..BridgeConfig::default()
+        };
+        let bridge = Bridge::attach(&client, config, Recording(tx));
+
+        client.handle_frame(FrameKind::Text, br#"{"type":"heartbeat","timestamp_ms":1}"#).await;
+        let update = PoolUpdate { slot: Slot(9), ..PoolUpdate::default() };
+        client.handle_frame(FrameKind::Binary, &encode_pool_update(&update)).await;
+
+        let (topic, payload) = published.recv().await.unwrap();
+        assert_eq!(topic, "k256.pool_update");
+        let msg: DecodedMessage = serde_json::from_slice(&payload).unwrap();
+        assert!(matches!(&msg, DecodedMessage::PoolUpdate(u) if *u == update));
+        while bridge.stats().published == 0 {
+            tokio::task::yield_now().await;
+        }
+        assert_eq!(bridge.stats(), BridgeStats { published: 1, dropped: 0, failed: 0 });
+
+        drop(bridge);
+        client.handle_frame(FrameKind::Binary, &encode_pool_update(&update)).await;
+        tokio::task::yield_now().await;
+        assert!(published.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_payload_roundtrip() {
+        let quote = Quote {
+            input_mint: Pubkey::new([1; 32]),
+            output_mint: Pubkey::new([2; 32]),
+            in_amount: 10,
+            out_amount: 20,
+            price_impact_pct: 0.5,
+            slot: Slot(4),
+            timestamp_ms: TimestampMs(5),
+            route_plan: vec![RoutePlanStep {
+                venue: Protocol::RaydiumClmm,
+                pool: Pubkey::new([3; 32]),
+                in_mint: Pubkey::new([1; 32]),
+                out_mint: Pubkey::new([2; 32]),
+                in_amount: 10,
+                out_amount: 20,
+                fee_amount: 1,
+                fee_mint: Some(Pubkey::new([1; 32])),
+                fee_bps: None,
+                percent: Some(100),
+            }],
+            other_amount_threshold: None,
+            swap_mode: "ExactIn".to_string(),
+            request_id: Some("q-1".to_string()),
+        };
+        let fees = FeeMarket {
+            slot: Slot(6),
+            timestamp_ms: TimestampMs(7),
+            recommended: 1000,
+            state: NetworkState::Extreme,
+            is_stale: false,
+            block_utilization_pct: 50.0,
+            blocks_in_window: 10,
+            accounts: vec![AccountFee {
+                pubkey: Pubkey::new([4; 32]),
+                total_txs: 3,
+                active_slots: 2,
+                cu_consumed: 600_000,
+                utilization_pct: 5.0,
+                p25: 1,
+                p50: 2,
+                p75: 3,
+                p90: 4,
+                min_nonzero_price: 1,
+            }],
+        };
+        let price = PriceEntry {
+            mint: Pubkey::new([5; 32]),
+            usd_price: 1.5,
+            slot: Slot(3),
+            timestamp_ms: TimestampMs(8),
+        };
+        let update = PoolUpdate {
+            slot: Slot(1),
+            serialized_state: vec![1, 2, 3],
+            ..PoolUpdate::default()
+        };
+        let messages = [
+            DecodedMessage::PoolUpdate(update.clone()),
+            DecodedMessage::PoolUpdateBatch(vec![update.clone(), update.clone()]),
+            DecodedMessage::PoolSnapshot(vec![update]),
+            DecodedMessage::FeeMarket(fees),
+            DecodedMessage::Blockhash(Blockhash { slot: Slot(2), ..Blockhash::default() }),
+            DecodedMessage::Quote(quote),
+            DecodedMessage::Heartbeat(Heartbeat::default()),
+            DecodedMessage::PriceUpdate(price.clone()),
+            DecodedMessage::PriceBatch(vec![price.clone()]),
+            DecodedMessage::PriceSnapshot(vec![price]),
+            DecodedMessage::Error("rejected".to_string()),
+            DecodedMessage::Pong { timestamp_ms: None },
+            DecodedMessage::Subscribed {
+                channels: vec!["pools".to_string()],
+                server: ServerInfo { protocol_version: Some(1), ..ServerInfo::default() },
+            },
+            DecodedMessage::ReplayComplete(ReplayComplete::default()),
+        ];
+        for encoding in [Encoding::Json, Encoding::Bincode] {
+            for msg in &messages {
+                let payload = encoding.encode(msg).unwrap();
+                let read = encoding.decode(&payload).unwrap();
+                assert_eq!(serde_json::to_value(&read).unwrap(), serde_json::to_value(msg).unwrap());
+            }
+        }
+    }
+
+    #[test]
+    fn test_bincode_rejects_invalid_network_state() {
+        let fees = FeeMarket {
+            slot: Slot(6),
+            timestamp_ms: TimestampMs(7),
+            recommended: 1000,
+            state: NetworkState::Low,
+            is_stale: false,
+            block_utilization_pct: 50.0,
+            blocks_in_window: 10,
+            accounts: Vec::new(),
+        };
+        let mut payload = Encoding::Bincode.encode(&DecodedMessage::FeeMarket(fees)).unwrap();
+        // Variant index (4) and three u64s precede the state code
+        payload[4 + 24] = 9;
+        assert!(Encoding::Bincode.decode(&payload).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_redis_streams_xadd() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let server = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut stream = BufReader::new(stream);
+            let mut request = Vec::new();
+            // `*8` header, then a length and a value line per argument
+            for _ in 0..17 {
+                stream.read_until(b'\n', &mut request).await.unwrap();
+            }
+            stream.get_mut().write_all(b"$3\r\n1-0\r\n").await.unwrap();
+            String::from_utf8(request).unwrap()
+        });
+
+        let mut redis = RedisStreams::connect(&address).await.unwrap().with_max_len(100);
+        redis.publish("k256.pools", b"{}").await.unwrap();
+        let request = server.await.unwrap();
+        assert_eq!(
+            request,
+            "*8\r\n$4\r\nXADD\r\n$10\r\nk256.pools\r\n$6\r\nMAXLEN\r\n$1\r\n~\r\n$3\r\n100\r\n\
+             $1\r\n*\r\n$4\r\ndata\r\n$2\r\n{}\r\n"
+        );
+    }
+}
@@ -0,0 +1,66 @@
+//! SDK error types.
+
+use thiserror::Error;
+
+use crate::ws::QuotaError;
+
+/// K256 SDK error types.
+#[derive(Debug, Error)]
+pub enum K256Error {
+    /// Address is not a valid base58-encoded 32-byte public key
+    #[error("Invalid pubkey: {0}")]
+    InvalidPubkey(String),
+
+    /// Subscription request does not name any channel
+    #[error("Subscription request has no channels")]
+    NoChannels,
+
+    /// Quote swap mode is neither `ExactIn` nor `ExactOut`
+    #[error("Invalid swap mode: {0}")]
+    InvalidSwapMode(String),
+
+    /// `other_amount_threshold` is above the output (ExactIn) or below the
+    /// input (ExactOut) amount
+    #[error("{swap_mode} quote threshold {threshold} is inconsistent with amount {amount}")]
+    InconsistentThreshold {
+        /// Quote swap mode
+        swap_mode: String,
+        /// The quote's `other_amount_threshold`
+        threshold: u64,
+        /// The amount the threshold bounds
+        amount: u64,
+    },
+
+    /// A quote's route plan does not chain from its input to its output
+    #[error("Invalid route at hop {hop}: {reason}")]
+    InvalidRoute {
+        /// Index of the first offending hop
+        hop: usize,
+        /// What is inconsistent
+        reason: String,
+    },
+
+    /// Quotes with different mints or swap modes cannot be compared
+    #[error("Quotes are not comparable: {0}")]
+    IncomparableQuotes(&'static str),
+
+    /// The server rejected a subscription or left requested channels out
+    #[error("Subscription rejected: {0}")]
+    SubscriptionRejected(String),
+
+    /// The server answered a quote request with an error
+    #[error("Quote request rejected: {0}")]
+    QuoteRejected(String),
+
+    /// No answer arrived in time
+    #[error("Timed out waiting for {0}")]
+    Timeout(&'static str),
+
+    /// The server reported an exceeded rate limit or quota
+    #[error("Quota exceeded: {0}")]
+    QuotaExceeded(QuotaError),
+
+    /// The client was closed with `K256WebSocketClient::disconnect`
+    #[error("Client is closed")]
+    Closed,
+}
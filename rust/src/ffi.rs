@@ -0,0 +1,667 @@
+//! C ABI for bindings in other languages.
+//!
+//! Exposes the WebSocket client as `extern "C"` functions so Python (ctypes,
+//! cffi), C and C++ bindings can reuse this SDK instead of reimplementing the
+//! binary protocol. The declarations are in `include/k256.h`; build a shared
+//! or static library with
+//!
+//! ```text
+//! cargo rustc --release --features ffi --crate-type cdylib
+//! ```
+//!
+//! Each client runs a [`K256WebSocketClient`] on its own internal runtime,
+//! which [`k256_client_free`] shuts down along with the connection. Decoded
+//! messages are passed to function-pointer callbacks as flat `#[repr(C)]`
+//! structs; their pointers borrow from the message and are only valid until
+//! the callback returns, so copy anything kept. Callbacks run on SDK threads,
+//! so `user_data` must be safe to use from another thread. Strings are UTF-8
+//! pointer and length pairs without a trailing NUL.
+//!
+//! Functions returning `int32_t` return [`K256_OK`] or a negative error code;
+//! [`k256_last_error`] then describes the failure. Null client, string or
+//! callback arguments are reported as [`K256_ERR_ARGUMENT`].
+//!
+//! ```c
+//! static void on_pool(const K256PoolUpdate *update, void *user_data) {
+//!     printf("%.*s slot %llu\n", (int)update->protocol_name_len, update->protocol_name, update->slot);
+//! }
+//!
+//! K256FfiClient *client = k256_client_new(getenv("K256_API_KEY"), NULL);
+//! k256_client_on_pool_update(client, on_pool, NULL);
+//! if (k256_client_connect(client) != K256_OK
+//!     || k256_client_subscribe(client, "{\"channels\":[\"pools\"]}") != K256_OK) {
+//!     fprintf(stderr, "%s\n", k256_last_error());
+//! }
+//! ```
+
+use std::cell::RefCell;
+use std::ffi::{c_char, c_void, CStr, CString};
+use std::fmt::Display;
+use std::ptr;
+
+use tokio::runtime::{Builder, Runtime};
+
+use crate::types::{Blockhash, FeeMarket, PoolUpdate, PriceEntry};
+use crate::ws::{Config, DecodedMessage, K256WebSocketClient, SubscribeRequest};
+
+/// Success.
+pub const K256_OK: i32 = 0;
+/// A pointer was null, a string was not UTF-8, or a request was malformed.
+pub const K256_ERR_ARGUMENT: i32 = -1;
+/// The client failed, e.g. to connect or to send a request.
+pub const K256_ERR_CLIENT: i32 = -2;
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn set_last_error(err: impl Display) {
+    let message = CString::new(err.to_string().replace('\0', " ")).unwrap_or_default();
+    LAST_ERROR.with(|last| *last.borrow_mut() = Some(message));
+}
+
+/// Message of the last error on the calling thread, NUL-terminated, or null
+/// if there was none.
+///
+/// The string is valid until the next failing call on the same thread.
+#[no_mangle]
+pub extern "C" fn k256_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| last.borrow().as_ref().map_or(ptr::null(), |message| message.as_ptr()))
+}
+
+/// Opaque client handle.
+pub struct K256FfiClient {
+    client: K256WebSocketClient,
+    runtime: Runtime,
+}
+
+/// Order book level.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct K256OrderLevel {
+    /// Price in base units
+    pub price: u64,
+    /// Size in base units
+    pub size: u64,
+}
+
+/// Pool update.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct K256PoolUpdate {
+    /// Global sequence number for ordering
+    pub sequence: u64,
+    /// Solana slot number
+    pub slot: u64,
+    /// Write version within slot
+    pub write_version: u64,
+    /// DEX protocol name
+    pub protocol_name: *const u8,
+    /// Length of `protocol_name` in bytes
+    pub protocol_name_len: usize,
+    /// Raw pool address
+    pub pool_address: [u8; 32],
+    /// Number of entries in `token_mints`, `token_balances` and `token_decimals`
+    pub token_count: usize,
+    /// Raw token mint addresses
+    pub token_mints: *const [u8; 32],
+    /// Token balances in base units
+    pub token_balances: *const u64,
+    /// Token decimals
+    pub token_decimals: *const i32,
+    /// Whether `best_bid` is set
+    pub has_best_bid: bool,
+    /// Best bid order level
+    pub best_bid: K256OrderLevel,
+    /// Whether `best_ask` is set
+    pub has_best_ask: bool,
+    /// Best ask order level
+    pub best_ask: K256OrderLevel,
+    /// Opaque pool state bytes
+    pub serialized_state: *const u8,
+    /// Length of `serialized_state` in bytes
+    pub serialized_state_len: usize,
+}
+
+impl K256PoolUpdate {
+    fn new(update: &PoolUpdate) -> Self {
+        let level = |level: Option<crate::types::OrderLevel>| {
+            level.map_or_else(K256OrderLevel::default, |level| K256OrderLevel {
+                price: level.price,
+                size: level.size,
+            })
+        };
+        let protocol_name = update.protocol_name.as_str();
+        Self {
+            sequence: update.sequence,
+            slot: update.slot.0,
+            write_version: update.write_version,
+            protocol_name: protocol_name.as_ptr(),
+            protocol_name_len: protocol_name.len(),
+            pool_address: update.pool_address.to_bytes(),
+            token_count: update
+                .token_mints
+                .len()
+                .min(update.token_balances.len())
+                .min(update.token_decimals.len()),
+            // `Pubkey` is a transparent wrapper around its bytes
+            token_mints: update.token_mints.as_ptr().cast(),
+            token_balances: update.token_balances.as_ptr(),
+            token_decimals: update.token_decimals.as_ptr(),
+            has_best_bid: update.best_bid.is_some(),
+            best_bid: level(update.best_bid),
+            has_best_ask: update.best_ask.is_some(),
+            best_ask: level(update.best_ask),
+            serialized_state: update.serialized_state.as_ptr(),
+            serialized_state_len: update.serialized_state.len(),
+        }
+    }
+}
+
+/// Per-account fee data.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct K256AccountFee {
+    /// Raw account address
+    pub pubkey: [u8; 32],
+    /// Total transactions touching this account
+    pub total_txs: u32,
+    /// Slots with activity on this account
+    pub active_slots: u32,
+    /// Compute units consumed
+    pub cu_consumed: u64,
+    /// Account utilization percentage
+    pub utilization_pct: f32,
+    /// 25th percentile fee
+    pub p25: u64,
+    /// 50th percentile fee
+    pub p50: u64,
+    /// 75th percentile fee
+    pub p75: u64,
+    /// 90th percentile fee
+    pub p90: u64,
+    /// Lowest non-zero fee
+    pub min_nonzero_price: u64,
+}
+
+/// Fee market update.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct K256FeeMarket {
+    /// Solana slot number
+    pub slot: u64,
+    /// Unix timestamp in milliseconds
+    pub timestamp_ms: u64,
+    /// Recommended priority fee in microlamports per compute unit
+    pub recommended: u64,
+    /// Network congestion: 0 low, 1 normal, 2 high, 3 extreme
+    pub state: u8,
+    /// Whether the data is stale
+    pub is_stale: bool,
+    /// Block utilization percentage
+    pub block_utilization_pct: f32,
+    /// Blocks in the observation window
+    pub blocks_in_window: u32,
+    /// Per-account fee data
+    pub accounts: *const K256AccountFee,
+    /// Number of entries in `accounts`
+    pub account_count: usize,
+}
+
+/// Recent blockhash.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct K256Blockhash {
+    /// Solana slot of the blockhash
+    pub slot: u64,
+    /// Unix timestamp in milliseconds
+    pub timestamp_ms: u64,
+    /// Base58-encoded blockhash
+    pub blockhash: *const u8,
+    /// Length of `blockhash` in bytes
+    pub blockhash_len: usize,
+    /// Block height
+    pub block_height: u64,
+    /// Last valid block height for transactions
+    pub last_valid_block_height: u64,
+    /// Whether the data is stale
+    pub is_stale: bool,
+}
+
+impl K256Blockhash {
+    fn new(bh: &Blockhash) -> Self {
+        Self {
+            slot: bh.slot.0,
+            timestamp_ms: bh.timestamp_ms.0,
+            blockhash: bh.blockhash.as_ptr(),
+            blockhash_len: bh.blockhash.len(),
+            block_height: bh.block_height,
+            last_valid_block_height: bh.last_valid_block_height,
+            is_stale: bh.is_stale,
+        }
+    }
+}
+
+/// Token USD price.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct K256PriceEntry {
+    /// Raw token mint address
+    pub mint: [u8; 32],
+    /// USD price
+    pub usd_price: f64,
+    /// Solana slot of the price
+    pub slot: u64,
+    /// Unix timestamp in milliseconds
+    pub timestamp_ms: u64,
+}
+
+impl K256PriceEntry {
+    fn new(entry: &PriceEntry) -> Self {
+        Self {
+            mint: entry.mint.to_bytes(),
+            usd_price: entry.usd_price,
+            slot: entry.slot.0,
+            timestamp_ms: entry.timestamp_ms.0,
+        }
+    }
+}
+
+/// Callback receiving a pool update.
+pub type K256PoolUpdateCallback = extern "C" fn(update: *const K256PoolUpdate, user_data: *mut c_void);
+/// Callback receiving a fee market update.
+pub type K256FeeMarketCallback = extern "C" fn(fees: *const K256FeeMarket, user_data: *mut c_void);
+/// Callback receiving a blockhash.
+pub type K256BlockhashCallback = extern "C" fn(blockhash: *const K256Blockhash, user_data: *mut c_void);
+/// Callback receiving the entries of a price update, batch or snapshot.
+pub type K256PricesCallback = extern "C" fn(entries: *const K256PriceEntry, count: usize, user_data: *mut c_void);
+/// Callback receiving an error message.
+pub type K256ErrorCallback = extern "C" fn(message: *const u8, len: usize, user_data: *mut c_void);
+
+/// Caller-owned callback context.
+#[derive(Clone, Copy)]
+struct UserData(*mut c_void);
+
+// The caller guarantees `user_data` may be used from SDK threads.
+unsafe impl Send for UserData {}
+unsafe impl Sync for UserData {}
+
+impl UserData {
+    fn get(&self) -> *mut c_void {
+        self.0
+    }
+}
+
+/// `callback`, or an argument error for null.
+fn callback_arg<F>(callback: Option<F>) -> Result<F, i32> {
+    callback.ok_or_else(|| {
+        set_last_error("callback is null");
+        K256_ERR_ARGUMENT
+    })
+}
+
+/// Client for `client`, or an argument error for null.
+unsafe fn client_ref<'a>(client: *const K256FfiClient) -> Result<&'a K256FfiClient, i32> {
+    client.as_ref().ok_or_else(|| {
+        set_last_error("client is null");
+        K256_ERR_ARGUMENT
+    })
+}
+
+/// UTF-8 string at `s`, or `None` for null.
+unsafe fn optional_str<'a>(s: *const c_char, name: &str) -> Result<Option<&'a str>, i32> {
+    if s.is_null() {
+        return Ok(None);
+    }
+    CStr::from_ptr(s).to_str().map(Some).map_err(|err| {
+        set_last_error(format_args!("{} is not UTF-8: {}", name, err));
+        K256_ERR_ARGUMENT
+    })
+}
+
+fn status<E: Display>(result: Result<(), E>) -> i32 {
+    match result {
+        Ok(()) => K256_OK,
+        Err(err) => {
+            set_last_error(err);
+            K256_ERR_CLIENT
+        }
+    }
+}
+
+fn code(result: Result<i32, i32>) -> i32 {
+    result.unwrap_or_else(|err| err)
+}
+
+/// Create a client. Does not connect yet.
+///
+/// `endpoint` may be null for the default gateway.
+///
+/// # Returns
+///
+/// The client, to be released with [`k256_client_free`], or null on error
+///
+/// # Safety
+///
+/// `api_key` and `endpoint` must be null or NUL-terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn k256_client_new(api_key: *const c_char, endpoint: *const c_char) -> *mut K256FfiClient {
+    let (api_key, endpoint) = match (optional_str(api_key, "api_key"), optional_str(endpoint, "endpoint")) {
+        (Ok(Some(api_key)), Ok(endpoint)) => (api_key, endpoint),
+        (Ok(None), _) => {
+            set_last_error("api_key is null");
+            return ptr::null_mut();
+        }
+        (Err(_), _) | (_, Err(_)) => return ptr::null_mut(),
+    };
+    let mut config = Config {
+        api_key: api_key.to_string(),
+        ..Config::default()
+    };
+    if let Some(endpoint) = endpoint {
+        config.endpoint = endpoint.to_string();
+    }
+    let runtime = Builder::new_multi_thread()
+        .worker_threads(1)
+        .thread_name("k256-ffi")
+        .enable_all()
+        .build();
+    match runtime {
+        Ok(runtime) => {
+            let client = K256WebSocketClient::new(config);
+            Box::into_raw(Box::new(K256FfiClient { client, runtime }))
+        }
+        Err(err) => {
+            set_last_error(err);
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Disconnect and release a client. Null is ignored.
+///
+/// # Safety
+///
+/// `client` must come from [`k256_client_new`] and not be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn k256_client_free(client: *mut K256FfiClient) {
+    if !client.is_null() {
+        drop(Box::from_raw(client));
+    }
+}
+
+/// Connect, blocking until the handshake completes.
+///
+/// # Safety
+///
+/// `client` must be null or a live client from [`k256_client_new`].
+#[no_mangle]
+pub unsafe extern "C" fn k256_client_connect(client: *const K256FfiClient) -> i32 {
+    code(client_ref(client).map(|client| status(client.runtime.block_on(client.client.connect()))))
+}
+
+/// Subscribe with a JSON subscription request.
+///
+/// Fields of `request_json` override those of the default request (pools,
+/// priority fees and blockhashes), e.g. `{"channels":["pools"],"protocols":["Whirlpool"]}`;
+/// null subscribes with the default request.
+///
+/// # Safety
+///
+/// `client` must be null or a live client from [`k256_client_new`], and
+/// `request_json` null or a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn k256_client_subscribe(client: *const K256FfiClient, request_json: *const c_char) -> i32 {
+    code((|| {
+        let client = client_ref(client)?;
+        let request = parse_request(optional_str(request_json, "request_json")?).map_err(|err| {
+            set_last_error(format_args!("invalid subscription request: {}", err));
+            K256_ERR_ARGUMENT
+        })?;
+        Ok(status(client.runtime.block_on(client.client.subscribe(request))))
+    })())
+}
+
+/// Default subscription request with the fields of `json` applied.
+fn parse_request(json: Option<&str>) -> serde_json::Result<SubscribeRequest> {
+    let Some(json) = json else {
+        return Ok(SubscribeRequest::default());
+    };
+    let mut request = serde_json::to_value(SubscribeRequest::default())?;
+    let serde_json::Value::Object(fields) = serde_json::from_str(json)? else {
+        return Err(serde::de::Error::custom("expected a JSON object"));
+    };
+    request.as_object_mut().unwrap().extend(fields);
+    serde_json::from_value(request)
+}
+
+/// Unsubscribe from all channels.
+///
+/// # Safety
+///
+/// `client` must be null or a live client from [`k256_client_new`].
+#[no_mangle]
+pub unsafe extern "C" fn k256_client_unsubscribe(client: *const K256FfiClient) -> i32 {
+    code(client_ref(client).map(|client| status(client.runtime.block_on(client.client.unsubscribe()))))
+}
+
+/// Call `callback` with every pool update, snapshot entries included.
+///
+/// Callbacks stay registered until the client is freed.
+///
+/// # Safety
+///
+/// `client` must be null or a live client from [`k256_client_new`];
+/// `user_data` is passed to `callback` from SDK threads.
+#[no_mangle]
+pub unsafe extern "C" fn k256_client_on_pool_update(
+    client: *const K256FfiClient,
+    callback: Option<K256PoolUpdateCallback>,
+    user_data: *mut c_void,
+) -> i32 {
+    let user_data = UserData(user_data);
+    code((|| {
+        let (client, callback) = (client_ref(client)?, callback_arg(callback)?);
+        client
+            .client
+            .on_pool_update(move |update| callback(&K256PoolUpdate::new(&update), user_data.get()))
+            .detach();
+        Ok(K256_OK)
+    })())
+}
+
+/// Call `callback` with every fee market update.
+///
+/// # Safety
+///
+/// See [`k256_client_on_pool_update`].
+#[no_mangle]
+pub unsafe extern "C" fn k256_client_on_fee_market(
+    client: *const K256FfiClient,
+    callback: Option<K256FeeMarketCallback>,
+    user_data: *mut c_void,
+) -> i32 {
+    let user_data = UserData(user_data);
+    code((|| {
+        let (client, callback) = (client_ref(client)?, callback_arg(callback)?);
+        client
+            .client
+            .on_fee_market(move |fees| call_fee_market(&fees, callback, user_data.get()))
+            .detach();
+        Ok(K256_OK)
+    })())
+}
+
+fn call_fee_market(fees: &FeeMarket, callback: K256FeeMarketCallback, user_data: *mut c_void) {
+    let accounts: Vec<K256AccountFee> = fees
+        .accounts
+        .iter()
+        .map(|account| K256AccountFee {
+            pubkey: account.pubkey.to_bytes(),
+            total_txs: account.total_txs,
+            active_slots: account.active_slots,
+            cu_consumed: account.cu_consumed,
+            utilization_pct: account.utilization_pct,
+            p25: account.p25,
+            p50: account.p50,
+            p75: account.p75,
+            p90: account.p90,
+            min_nonzero_price: account.min_nonzero_price,
+        })
+        .collect();
+    let flat = K256FeeMarket {
+        slot: fees.slot.0,
+        timestamp_ms: fees.timestamp_ms.0,
+        recommended: fees.recommended,
+        state: fees.state as u8,
+        is_stale: fees.is_stale,
+        block_utilization_pct: fees.block_utilization_pct,
+        blocks_in_window: fees.blocks_in_window,
+        accounts: accounts.as_ptr(),
+        account_count: accounts.len(),
+    };
+    callback(&flat, user_data);
+}
+
+/// Call `callback` with every blockhash.
+///
+/// # Safety
+///
+/// See [`k256_client_on_pool_update`].
+#[no_mangle]
+pub unsafe extern "C" fn k256_client_on_blockhash(
+    client: *const K256FfiClient,
+    callback: Option<K256BlockhashCallback>,
+    user_data: *mut c_void,
+) -> i32 {
+    let user_data = UserData(user_data);
+    code((|| {
+        let (client, callback) = (client_ref(client)?, callback_arg(callback)?);
+        client
+            .client
+            .on_blockhash(move |bh| callback(&K256Blockhash::new(&bh), user_data.get()))
+            .detach();
+        Ok(K256_OK)
+    })())
+}
+
+/// Call `callback` with the entries of every price update (one entry),
+/// price batch and price snapshot.
+///
+/// # Safety
+///
+/// See [`k256_client_on_pool_update`].
+#[no_mangle]
+pub unsafe extern "C" fn k256_client_on_prices(
+    client: *const K256FfiClient,
+    callback: Option<K256PricesCallback>,
+    user_data: *mut c_void,
+) -> i32 {
+    let user_data = UserData(user_data);
+    code((|| {
+        let (client, callback) = (client_ref(client)?, callback_arg(callback)?);
+        client
+            .client
+            .on_message(move |message| match message {
+                DecodedMessage::PriceUpdate(entry) => {
+                    call_prices(std::slice::from_ref(&entry), callback, user_data.get())
+                }
+                DecodedMessage::PriceBatch(entries) | DecodedMessage::PriceSnapshot(entries) => {
+                    call_prices(&entries, callback, user_data.get())
+                }
+                _ => {}
+            })
+            .detach();
+        Ok(K256_OK)
+    })())
+}
+
+fn call_prices(entries: &[PriceEntry], callback: K256PricesCallback, user_data: *mut c_void) {
+    let flat: Vec<K256PriceEntry> = entries.iter().map(K256PriceEntry::new).collect();
+    callback(flat.as_ptr(), flat.len(), user_data);
+}
+
+/// Call `callback` with server errors and client-side guardrail reports.
+///
+/// # Safety
+///
+/// See [`k256_client_on_pool_update`].
+#[no_mangle]
+pub unsafe extern "C" fn k256_client_on_error(
+    client: *const K256FfiClient,
+    callback: Option<K256ErrorCallback>,
+    user_data: *mut c_void,
+) -> i32 {
+    let user_data = UserData(user_data);
+    code((|| {
+        let (client, callback) = (client_ref(client)?, callback_arg(callback)?);
+        client
+            .client
+            .on_error(move |message| callback(message.as_ptr(), message.len(), user_data.get()))
+            .detach();
+        Ok(K256_OK)
+    })())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{OrderLevel, Protocol, Pubkey, Slot};
+
+    extern "C" fn copy_update(update: *const K256PoolUpdate, user_data: *mut c_void) {
+        let (update, out) = unsafe { (&*update, &mut *(user_data as *mut Vec<u64>)) };
+        let name = unsafe { std::slice::from_raw_parts(update.protocol_name, update.protocol_name_len) };
+        let mints = unsafe { std::slice::from_raw_parts(update.token_mints, update.token_count) };
+        let balances = unsafe { std::slice::from_raw_parts(update.token_balances, update.token_count) };
+        assert_eq!(name, b"Whirlpool");
+        assert_eq!(mints, [[1; 32], [2; 32]]);
+        assert!(!update.has_best_bid && update.has_best_ask);
+        out.extend([update.slot, update.best_ask.price]);
+        out.extend(balances);
+    }
+
+    #[test]
+    fn test_flat_pool_update() {
+        let update = PoolUpdate {
+            slot: Slot(42),
+            protocol_name: Protocol::Whirlpool,
+            token_mints: vec![Pubkey::new([1; 32]), Pubkey::new([2; 32])],
+            token_balances: vec![1_000, 2_000],
+            token_decimals: vec![9, 6],
+            best_ask: Some(OrderLevel { price: 10, size: 1 }),
+            ..PoolUpdate::default()
+        };
+        let mut out: Vec<u64> = Vec::new();
+        copy_update(&K256PoolUpdate::new(&update), &mut out as *mut Vec<u64> as *mut c_void);
+        assert_eq!(out, [42, 10, 1_000, 2_000]);
+    }
+
+    #[test]
+    fn test_arguments() {
+        unsafe {
+            assert!(k256_client_new(ptr::null(), ptr::null()).is_null());
+            assert_eq!(CStr::from_ptr(k256_last_error()).to_str().unwrap(), "api_key is null");
+            assert_eq!(k256_client_connect(ptr::null()), K256_ERR_ARGUMENT);
+            k256_client_free(ptr::null_mut());
+
+            let (key, endpoint) = (CString::new("key").unwrap(), CString::new("ws://127.0.0.1:1/v1/ws").unwrap());
+            let client = k256_client_new(key.as_ptr(), endpoint.as_ptr());
+            assert!(!client.is_null());
+            let request = CString::new("[1]").unwrap();
+            assert_eq!(k256_client_subscribe(client, request.as_ptr()), K256_ERR_ARGUMENT);
+            let error = CStr::from_ptr(k256_last_error()).to_str().unwrap();
+            assert!(error.starts_with("invalid subscription request"), "{}", error);
+            assert_eq!(k256_client_on_pool_update(client, None, ptr::null_mut()), K256_ERR_ARGUMENT);
+            assert_eq!(CStr::from_ptr(k256_last_error()).to_str().unwrap(), "callback is null");
+            assert_eq!(k256_client_on_prices(client, None, ptr::null_mut()), K256_ERR_ARGUMENT);
+            assert_eq!(k256_client_on_pool_update(client, Some(copy_update), ptr::null_mut()), K256_OK);
+            k256_client_free(client);
+        }
+
+        let request = parse_request(Some(r#"{"channels":["pools"],"snapshot":true}"#)).unwrap();
+        assert_eq!(request.channels, ["pools"]);
+        assert_eq!(request.snapshot, Some(true));
+        assert_eq!(request.request_type, "subscribe");
+        assert!(parse_request(Some(r#"{"channels":"pools"}"#)).is_err());
+    }
+}
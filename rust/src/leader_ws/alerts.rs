@@ -0,0 +1,303 @@
+//! Typed alerts from the `alerts` channel.
+//!
+//! [`AlertFilter`] turns `skip_event`, `ip_change` and `routing_health`
+//! messages into [`Alert`]s with a [`AlertSeverity`], and drops alerts below
+//! a minimum severity so only actionable ones reach paging.
+//!
+//! # Example
+//! ```rust,no_run
+//! use k256_sdk::leader_ws::{AlertFilter, AlertSeverity, LeaderMessage};
+//!
+//! let filter = AlertFilter::new().min_severity(AlertSeverity::Critical);
+//! # let msg: LeaderMessage = unimplemented!();
+//! for alert in filter.apply(&msg)? {
+//!     println!("[{}] {}", alert.severity, alert.kind);
+//! }
+//! # Ok::<(), serde_json::Error>(())
+//! ```
+
+use std::fmt;
+
+use serde::Deserialize;
+
+use super::types::{IpChangeData, LeaderMessage, RoutingHealthData, SkipEventData};
+
+/// Skip event message type.
+const MSG_SKIP_EVENT: &str = "skip_event";
+/// IP change message type.
+const MSG_IP_CHANGE: &str = "ip_change";
+/// Routing health message type.
+const MSG_ROUTING_HEALTH: &str = "routing_health";
+
+/// Alert severity, ordered from least to most severe.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum AlertSeverity {
+    /// Informational
+    Info,
+    /// Degraded, worth a look
+    Warning,
+    /// Transactions are likely to be lost
+    Critical,
+}
+
+impl fmt::Display for AlertSeverity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Info => "info",
+            Self::Warning => "warning",
+            Self::Critical => "critical",
+        };
+        f.write_str(name)
+    }
+}
+
+/// What an alert is about.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AlertKind {
+    /// Upcoming leaders are delinquent
+    Delinquency {
+        /// Delinquent leader identities
+        leaders: Vec<String>,
+    },
+    /// A leader skipped slots
+    SkipRate {
+        /// The skip event
+        event: SkipEventData,
+        /// Share of the leader's assigned slots it skipped (0-1)
+        skip_rate: f64,
+    },
+    /// A validator changed its IP address
+    IpChange(IpChangeData),
+    /// Fewer upcoming leaders are reachable through gossip
+    CoverageDegraded {
+        /// Upcoming leaders visible in gossip, in percent
+        coverage_pct: f64,
+        /// Upcoming leaders missing from gossip
+        leaders_missing_gossip: Vec<String>,
+        /// Upcoming leaders without a TPU QUIC address
+        leaders_without_tpu_quic: Vec<String>,
+    },
+}
+
+impl fmt::Display for AlertKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Delinquency { leaders } => write!(f, "{} upcoming leaders delinquent", leaders.len()),
+            Self::SkipRate { event, skip_rate } => write!(
+                f,
+                "leader {} skipped slot {} ({:.1}% skip rate)",
+                event.leader,
+                event.slot,
+                skip_rate * 100.0
+            ),
+            Self::IpChange(change) => {
+                write!(f, "{} moved from {} to {}", change.identity, change.old_ip, change.new_ip)
+            }
+            Self::CoverageDegraded { coverage_pct, .. } => {
+                write!(f, "gossip coverage of upcoming leaders at {:.1}%", coverage_pct)
+            }
+        }
+    }
+}
+
+/// A classified alert.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Alert {
+    /// How severe the alert is
+    pub severity: AlertSeverity,
+    /// What the alert is about
+    pub kind: AlertKind,
+}
+
+/// Classifies alert-channel messages and filters them by severity.
+#[derive(Debug, Clone)]
+pub struct AlertFilter {
+    min_severity: AlertSeverity,
+    skip_rate_warning: f64,
+    skip_rate_critical: f64,
+    coverage_warning_pct: f64,
+    coverage_critical_pct: f64,
+}
+
+impl Default for AlertFilter {
+    fn default() -> Self {
+        Self {
+            min_severity: AlertSeverity::Info,
+            skip_rate_warning: 0.1,
+            skip_rate_critical: 0.25,
+            coverage_warning_pct: 95.0,
+            coverage_critical_pct: 90.0,
+        }
+    }
+}
+
+impl AlertFilter {
+    /// Create a filter passing every alert.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Drop alerts below `severity`.
+    pub fn min_severity(mut self, severity: AlertSeverity) -> Self {
+        self.min_severity = severity;
+        self
+    }
+
+    /// Skip rates (0-1) from which skip events are warnings or critical
+    /// (defaults 0.1 and 0.25).
+    pub fn skip_rate_thresholds(mut self, warning: f64, critical: f64) -> Self {
+        self.skip_rate_warning = warning;
+        self.skip_rate_critical = critical;
+        self
+    }
+
+    /// Gossip coverage percentages below which coverage is a warning or
+    /// critical (defaults 95 and 90).
+    pub fn coverage_thresholds(mut self, warning_pct: f64, critical_pct: f64) -> Self {
+        self.coverage_warning_pct = warning_pct;
+        self.coverage_critical_pct = critical_pct;
+        self
+    }
+
+    /// Classify an alert-channel message.
+    ///
+    /// # Returns
+    ///
+    /// Alerts at or above the minimum severity; empty for other message types
+    pub fn apply(&self, msg: &LeaderMessage) -> Result<Vec<Alert>, serde_json::Error> {
+        let alerts = match msg.msg_type.as_str() {
+            MSG_SKIP_EVENT => vec![self.skip_alert(SkipEventData::deserialize(&msg.data)?)],
+            MSG_IP_CHANGE => vec![Alert {
+                severity: AlertSeverity::Warning,
+                kind: AlertKind::IpChange(IpChangeData::deserialize(&msg.data)?),
+            }],
+            MSG_ROUTING_HEALTH => self.routing_alerts(RoutingHealthData::deserialize(&msg.data)?),
+            _ => Vec::new(),
+        };
+        Ok(alerts.into_iter().filter(|alert| alert.severity >= self.min_severity).collect())
+    }
+
+    fn skip_alert(&self, event: SkipEventData) -> Alert {
+        let skip_rate = if event.assigned > 0 {
+            event.assigned.saturating_sub(event.produced) as f64 / event.assigned as f64
+        } else {
+            0.0
+        };
+        let severity = if skip_rate >= self.skip_rate_critical {
+            AlertSeverity::Critical
+        } else if skip_rate >= self.skip_rate_warning {
+            AlertSeverity::Warning
+        } else {
+            AlertSeverity::Info
+        };
+        Alert {
+            severity,
+            kind: AlertKind::SkipRate { event, skip_rate },
+        }
+    }
+
+    fn routing_alerts(&self, health: RoutingHealthData) -> Vec<Alert> {
+        let coverage_pct = coverage_pct(&health);
+        let mut alerts = Vec::new();
+        if !health.leaders_delinquent.is_empty() {
+            alerts.push(Alert {
+                severity: AlertSeverity::Critical,
+                kind: AlertKind::Delinquency {
+                    leaders: health.leaders_delinquent,
+                },
+            });
+        }
+
+        let severity = if coverage_pct < self.coverage_critical_pct {
+            Some(AlertSeverity::Critical)
+        } else if coverage_pct < self.coverage_warning_pct {
+            Some(AlertSeverity::Warning)
+        } else {
+            None
+        };
+        if let Some(severity) = severity {
+            alerts.push(Alert {
+                severity,
+                kind: AlertKind::CoverageDegraded {
+                    coverage_pct,
+                    leaders_missing_gossip: health.leaders_missing_gossip,
+                    leaders_without_tpu_quic: health.leaders_without_tpu_quic,
+                },
+            });
+        }
+        alerts
+    }
+}
+
+/// Coverage from the server's summary (e.g. "98.5%"), or from the counts.
+pub(super) fn coverage_pct(health: &RoutingHealthData) -> f64 {
+    if let Ok(pct) = health.coverage.trim().trim_end_matches('%').parse() {
+        return pct;
+    }
+    if health.leaders_total == 0 {
+        return 100.0;
+    }
+    health.leaders_in_gossip as f64 * 100.0 / health.leaders_total as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn message(msg_type: &str, data: serde_json::Value) -> LeaderMessage {
+        serde_json::from_value(json!({ "type": msg_type, "data": data })).unwrap()
+    }
+
+    fn routing_health(coverage: &str, delinquent: &[&str]) -> LeaderMessage {
+        message(
+            MSG_ROUTING_HEALTH,
+            json!({
+                "leadersTotal": 20,
+                "leadersInGossip": 17,
+                "leadersMissingGossip": ["m1", "m2", "m3"],
+                "leadersWithoutTpuQuic": [],
+                "leadersDelinquent": delinquent,
+                "coverage": coverage,
+            }),
+        )
+    }
+
+    #[test]
+    fn test_classifies_alerts() {
+        let filter = AlertFilter::new();
+
+        let skip = message(MSG_SKIP_EVENT, json!({ "slot": 9, "leader": "v", "assigned": 4, "produced": 3 }));
+        let alerts = filter.apply(&skip).unwrap();
+        assert_eq!(alerts[0].severity, AlertSeverity::Critical);
+        assert!(matches!(alerts[0].kind, AlertKind::SkipRate { skip_rate, .. } if skip_rate == 0.25));
+
+        let ip = message(
+            MSG_IP_CHANGE,
+            json!({ "identity": "v", "oldIp": "1.1.1.1", "newIp": "2.2.2.2", "timestampMs": 1 }),
+        );
+        assert_eq!(filter.apply(&ip).unwrap()[0].severity, AlertSeverity::Warning);
+
+        let alerts = filter.apply(&routing_health("", &["d"])).unwrap();
+        assert_eq!(alerts.len(), 2);
+        assert_eq!(alerts[0].kind, AlertKind::Delinquency { leaders: vec!["d".to_string()] });
+        assert!(matches!(
+            alerts[1],
+            Alert { severity: AlertSeverity::Critical, kind: AlertKind::CoverageDegraded { coverage_pct, .. } }
+                if coverage_pct == 85.0
+        ));
+
+        assert!(filter.apply(&routing_health("99.0%", &[])).unwrap().is_empty());
+        assert!(filter.apply(&message("slot_update", json!({}))).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_min_severity() {
+        let filter = AlertFilter::new().min_severity(AlertSeverity::Critical);
+        assert!(filter.apply(&routing_health("93.5%", &[])).unwrap().is_empty());
+        assert_eq!(filter.apply(&routing_health("89%", &[])).unwrap().len(), 1);
+
+        let skip = message(MSG_SKIP_EVENT, json!({ "slot": 9, "leader": "v", "assigned": 100, "produced": 99 }));
+        assert!(filter.apply(&skip).unwrap().is_empty());
+    }
+}
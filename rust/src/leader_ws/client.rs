@@ -17,15 +17,25 @@
 //! // client.connect() in an async runtime
 //! ```
 
-use super::types::{LeaderMessage, ALL_CHANNELS};
+use super::alerts::{Alert, AlertFilter};
+use super::health::{CoverageEvent, RoutingHealthTracker};
+use super::ip_history::{IpFlap, IpHistory};
+use super::router::MessageRouter;
+use super::types::{EpochRollover, LeaderMessage, ALL_CHANNELS};
+use crate::connector::{AuthMethod, ProxyConfig, TlsConfig};
+use crate::types::ConnectionState;
 use serde_json::json;
+use std::sync::{Arc, Mutex};
 
 /// Configuration for the leader-schedule WebSocket client.
+#[derive(Debug, Clone)]
 pub struct LeaderConfig {
     /// K256 API key
     pub api_key: String,
     /// WebSocket endpoint URL
     pub url: String,
+    /// How the API key is sent on the handshake
+    pub auth: AuthMethod,
     /// Channels to subscribe to
     pub channels: Vec<String>,
     /// Enable automatic reconnection
@@ -34,6 +44,10 @@ pub struct LeaderConfig {
     pub reconnect_delay_secs: f64,
     /// Maximum reconnect delay in seconds
     pub max_reconnect_delay_secs: f64,
+    /// Custom TLS roots and client certificate
+    pub tls: Option<TlsConfig>,
+    /// HTTP or SOCKS5 proxy
+    pub proxy: Option<ProxyConfig>,
 }
 
 impl Default for LeaderConfig {
@@ -41,27 +55,180 @@ impl Default for LeaderConfig {
         Self {
             api_key: String::new(),
             url: "wss://gateway.k256.xyz/v1/leader-ws".to_string(),
+            auth: AuthMethod::QueryParam,
             channels: ALL_CHANNELS.iter().map(|s| s.to_string()).collect(),
             auto_reconnect: true,
             reconnect_delay_secs: 1.0,
             max_reconnect_delay_secs: 60.0,
+            tls: None,
+            proxy: None,
         }
     }
 }
 
+/// Ends the sessions of a [`LeaderWebSocketClient`] from another thread.
+///
+/// Cheap to clone; clones close the same client.
+#[derive(Debug, Clone, Default)]
+pub struct LeaderCloseHandle {
+    state: Arc<Mutex<CloseState>>,
+}
+
+#[derive(Debug, Default)]
+struct CloseState {
+    closed: bool,
+    /// Clone of the live session's TCP stream, shut down to unblock reads
+    stream: Option<std::net::TcpStream>,
+}
+
+impl LeaderCloseHandle {
+    /// End the current session, if any, and make later
+    /// [`connect_blocking`](LeaderWebSocketClient::connect_blocking) calls
+    /// return immediately.
+    pub fn close(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.closed = true;
+        if let Some(stream) = state.stream.take() {
+            let _ = stream.shutdown(std::net::Shutdown::Both);
+        }
+    }
+
+    /// Whether [`close`](Self::close) was called.
+    pub fn is_closed(&self) -> bool {
+        self.state.lock().unwrap().closed
+    }
+
+    /// Track the live session's stream (`None` once it ended); `false` if
+    /// the client is closed.
+    #[cfg(feature = "tungstenite")]
+    fn attach(&self, stream: Option<std::net::TcpStream>) -> bool {
+        let mut state = self.state.lock().unwrap();
+        state.stream = stream;
+        !state.closed
+    }
+}
+
+type Handler<T> = Box<dyn Fn(T) + Send + 'static>;
+type Callback<T> = Option<Handler<T>>;
+
 /// Leader Schedule WebSocket client (JSON mode).
 ///
 /// Uses tungstenite for WebSocket connections. Parses JSON text frames
 /// and dispatches to the provided handler callback.
 pub struct LeaderWebSocketClient<F: Fn(LeaderMessage) + Send + 'static> {
     config: LeaderConfig,
+    #[cfg_attr(not(feature = "tungstenite"), allow(dead_code))]
     handler: F,
+    #[cfg_attr(not(feature = "tungstenite"), allow(dead_code))]
+    on_connection_state: Callback<ConnectionState>,
+    #[cfg_attr(not(feature = "tungstenite"), allow(dead_code))]
+    on_epoch_rollover: Callback<EpochRollover>,
+    #[cfg_attr(not(feature = "tungstenite"), allow(dead_code))]
+    on_alert: Option<(AlertFilter, Handler<Alert>)>,
+    #[cfg_attr(not(feature = "tungstenite"), allow(dead_code))]
+    on_ip_flap: Option<(IpHistory, Handler<IpFlap>)>,
+    #[cfg_attr(not(feature = "tungstenite"), allow(dead_code))]
+    on_coverage: Option<(RoutingHealthTracker, Handler<CoverageEvent>)>,
+    router: MessageRouter,
+    close_handle: LeaderCloseHandle,
 }
 
 impl<F: Fn(LeaderMessage) + Send + 'static> LeaderWebSocketClient<F> {
     /// Create a new client with the given config and message handler.
     pub fn new(config: LeaderConfig, handler: F) -> Self {
-        Self { config, handler }
+        Self {
+            config,
+            handler,
+            on_connection_state: None,
+            on_epoch_rollover: None,
+            on_alert: None,
+            on_ip_flap: None,
+            on_coverage: None,
+            router: MessageRouter::new(),
+            close_handle: LeaderCloseHandle::default(),
+        }
+    }
+
+    /// Register a callback fired when slot updates cross into a new epoch.
+    ///
+    /// If the new epoch's schedule has not arrived yet, the subscription is
+    /// re-sent so the server delivers the current `leader_schedule` snapshot.
+    pub fn on_epoch_rollover<S>(&mut self, callback: S)
+    where
+        S: Fn(EpochRollover) + Send + 'static,
+    {
+        self.on_epoch_rollover = Some(Box::new(callback));
+    }
+
+    /// Register a callback fired on every connection lifecycle change.
+    pub fn on_connection_state<S>(&mut self, callback: S)
+    where
+        S: Fn(ConnectionState) + Send + 'static,
+    {
+        self.on_connection_state = Some(Box::new(callback));
+    }
+
+    /// Register a callback receiving alerts that pass `filter`.
+    ///
+    /// Requires the `alerts` channel in [`LeaderConfig::channels`].
+    pub fn on_alert<S>(&mut self, filter: AlertFilter, callback: S)
+    where
+        S: Fn(Alert) + Send + 'static,
+    {
+        self.on_alert = Some((filter, Box::new(callback)));
+    }
+
+    /// Register a callback fired when a validator's IP changes at least as
+    /// often as the thresholds of `history` allow.
+    ///
+    /// Each connection starts tracking from a copy of `history`. Requires the
+    /// `alerts` channel in [`LeaderConfig::channels`].
+    pub fn on_ip_flap<S>(&mut self, history: IpHistory, callback: S)
+    where
+        S: Fn(IpFlap) + Send + 'static,
+    {
+        self.on_ip_flap = Some((history, Box::new(callback)));
+    }
+
+    /// Register a callback fired when routing coverage of the upcoming
+    /// leaders crosses a threshold of `tracker`.
+    ///
+    /// Each connection starts from a copy of `tracker`. Requires the
+    /// `alerts` channel in [`LeaderConfig::channels`].
+    pub fn on_coverage<S>(&mut self, tracker: RoutingHealthTracker, callback: S)
+    where
+        S: Fn(CoverageEvent) + Send + 'static,
+    {
+        self.on_coverage = Some((tracker, Box::new(callback)));
+    }
+
+    /// Register a handler for messages of `msg_type` keyed by `key`, e.g.
+    /// `ip_change` messages of one validator identity.
+    ///
+    /// Runs before the main handler. See [`MessageRouter`] for type-wide
+    /// and typed handlers, available through [`router_mut`](Self::router_mut).
+    pub fn on_keyed<S>(&mut self, msg_type: impl Into<String>, key: impl Into<String>, handler: S)
+    where
+        S: Fn(&LeaderMessage) + Send + 'static,
+    {
+        self.router.on_keyed(msg_type, key, handler);
+    }
+
+    /// Handle ending this client's sessions from another thread.
+    pub fn close_handle(&self) -> LeaderCloseHandle {
+        self.close_handle.clone()
+    }
+
+    /// Router dispatching messages to keyed and per-type handlers.
+    pub fn router_mut(&mut self) -> &mut MessageRouter {
+        &mut self.router
+    }
+
+    #[cfg(feature = "tungstenite")]
+    fn set_state(&self, state: ConnectionState) {
+        if let Some(cb) = &self.on_connection_state {
+            cb(state);
+        }
     }
 
     /// Build the subscribe message for JSON mode.
@@ -74,7 +241,7 @@ impl<F: Fn(LeaderMessage) + Send + 'static> LeaderWebSocketClient<F> {
         .to_string()
     }
 
-    /// Get the full WebSocket URL with API key.
+    /// Get the full WebSocket URL with API key as a query parameter.
     pub fn ws_url(&self) -> String {
         format!(
             "{}?apiKey={}",
@@ -87,21 +254,92 @@ impl<F: Fn(LeaderMessage) + Send + 'static> LeaderWebSocketClient<F> {
     ///
     /// Uses tungstenite for the WebSocket connection.
     /// Call this from an async runtime or dedicated thread.
+    /// Returns `Ok(())` when the server closes the connection or the
+    /// session is ended through [`close_handle`](Self::close_handle).
+    /// Lifecycle changes are reported through
+    /// [`on_connection_state`](Self::on_connection_state).
     #[cfg(feature = "tungstenite")]
-    pub fn connect_blocking(&self) -> Result<(), Box<dyn std::error::Error>> {
-        use tungstenite::{connect, Message};
+    pub fn connect_blocking(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        if self.close_handle.is_closed() {
+            return Ok(());
+        }
+        self.set_state(ConnectionState::Connecting);
+        let result = self.run_blocking();
+        // Release the stream clone, which would otherwise keep the socket open
+        let closed = !self.close_handle.attach(None);
+        let result = if closed { Ok(()) } else { result };
+        let reason = match &result {
+            Ok(()) if closed => "closed by client".to_string(),
+            Ok(()) => "closed by server".to_string(),
+            Err(e) => e.to_string(),
+        };
+        self.set_state(ConnectionState::Closed { reason });
+        result
+    }
 
-        let (mut socket, _) = connect(&self.ws_url())?;
+    #[cfg(feature = "tungstenite")]
+    fn run_blocking(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        use super::tracker::LeaderTracker;
+        use super::types::CHANNEL_LEADER_SCHEDULE;
+        use tungstenite::Message;
+
+        let request = crate::connector::authenticated_request(&self.config.url, &self.config.api_key, self.config.auth)?;
+        let mut socket =
+            crate::connector::connect_blocking(request, self.config.tls.as_ref(), self.config.proxy.as_ref())?;
+        let stream = crate::connector::blocking_tcp_stream(&socket).and_then(|stream| stream.try_clone().ok());
+        if !self.close_handle.attach(stream) {
+            return Ok(());
+        }
+        self.set_state(ConnectionState::Connected {
+            peer: crate::connector::blocking_peer_addr(&socket),
+        });
 
         // Subscribe with JSON mode
         socket.send(Message::Text(self.subscribe_message()))?;
 
+        let mut subscribed = false;
+        let mut tracker = LeaderTracker::new();
+        let mut ip_history = self.on_ip_flap.as_ref().map(|(history, _)| history.clone());
+        let mut health = self.on_coverage.as_ref().map(|(tracker, _)| tracker.clone());
         loop {
-            let msg = socket.read()?;
-            if let Message::Text(text) = msg {
-                if let Ok(leader_msg) = serde_json::from_str::<LeaderMessage>(&text) {
-                    (self.handler)(leader_msg);
+            match socket.read() {
+                Ok(Message::Text(text)) => {
+                    if let Ok(leader_msg) = serde_json::from_str::<LeaderMessage>(&text) {
+                        if !subscribed && leader_msg.msg_type == "subscribed" {
+                            subscribed = true;
+                            self.set_state(ConnectionState::Subscribed);
+                        }
+                        let _ = tracker.apply(&leader_msg);
+                        if let Some((filter, cb)) = &self.on_alert {
+                            for alert in filter.apply(&leader_msg).unwrap_or_default() {
+                                cb(alert);
+                            }
+                        }
+                        if let (Some(history), Some((_, cb))) = (ip_history.as_mut(), &self.on_ip_flap) {
+                            if let Ok(Some(flap)) = history.apply(&leader_msg) {
+                                cb(flap);
+                            }
+                        }
+                        if let (Some(health), Some((_, cb))) = (health.as_mut(), &self.on_coverage) {
+                            for event in health.apply(&leader_msg).unwrap_or_default() {
+                                cb(event);
+                            }
+                        }
+                        self.router.dispatch(&leader_msg);
+                        (self.handler)(leader_msg);
+                        if let Some(rollover) = tracker.take_rollover() {
+                            if !rollover.schedule_ready && self.config.channels.iter().any(|c| c == CHANNEL_LEADER_SCHEDULE) {
+                                socket.send(Message::Text(self.subscribe_message()))?;
+                            }
+                            if let Some(cb) = &self.on_epoch_rollover {
+                                cb(rollover);
+                            }
+                        }
+                    }
                 }
+                Ok(Message::Close(_)) | Err(tungstenite::Error::ConnectionClosed) => return Ok(()),
+                Ok(_) => {}
+                Err(e) => return Err(e.into()),
             }
         }
     }
@@ -0,0 +1,301 @@
+//! In-memory gossip peer registry.
+//!
+//! Applies `gossip_snapshot` and `gossip_diff` messages to keep an
+//! up-to-date map of validator identity → [`GossipPeer`].
+//!
+//! # Example
+//! ```rust,no_run
+//! use k256_sdk::leader_ws::{GossipRegistry, LeaderMessage};
+//!
+//! let mut registry = GossipRegistry::new();
+//! # let msg: LeaderMessage = unimplemented!();
+//! registry.apply(&msg)?;
+//! for peer in registry.staked_peers(1_000_000_000) {
+//!     println!("{} {:?}", peer.identity, peer.tpu_quic);
+//! }
+//! # Ok::<(), serde_json::Error>(())
+//! ```
+
+use std::collections::HashMap;
+
+use serde::Deserialize;
+
+use super::types::{GossipDiffData, GossipPeer, GossipSnapshotData, LeaderMessage};
+
+/// Gossip snapshot message type.
+const MSG_GOSSIP_SNAPSHOT: &str = "gossip_snapshot";
+/// Gossip diff message type.
+const MSG_GOSSIP_DIFF: &str = "gossip_diff";
+
+/// Mean Earth radius in kilometers.
+const EARTH_RADIUS_KM: f64 = 6371.0;
+
+/// Geographic coordinates in degrees.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GeoPoint {
+    /// Latitude
+    pub latitude: f64,
+    /// Longitude
+    pub longitude: f64,
+}
+
+impl GeoPoint {
+    /// Point at `latitude`, `longitude`.
+    pub fn new(latitude: f64, longitude: f64) -> Self {
+        Self { latitude, longitude }
+    }
+
+    /// Great-circle distance to `other` in kilometers.
+    pub fn distance_km(&self, other: &GeoPoint) -> f64 {
+        let (lat1, lat2) = (self.latitude.to_radians(), other.latitude.to_radians());
+        let d_lat = lat2 - lat1;
+        let d_lon = (other.longitude - self.longitude).to_radians();
+        let a = (d_lat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (d_lon / 2.0).sin().powi(2);
+        2.0 * EARTH_RADIUS_KM * a.sqrt().asin()
+    }
+}
+
+impl GossipPeer {
+    /// Geolocated position, or `None` when the server has no location for the peer.
+    pub fn location(&self) -> Option<GeoPoint> {
+        // Unlocated peers are sent with both coordinates zeroed
+        let located = self.latitude != 0.0 || self.longitude != 0.0;
+        (located && self.latitude.is_finite() && self.longitude.is_finite())
+            .then(|| GeoPoint::new(self.latitude, self.longitude))
+    }
+}
+
+/// Registry of gossip peers keyed by validator identity.
+#[derive(Debug, Clone, Default)]
+pub struct GossipRegistry {
+    peers: HashMap<String, GossipPeer>,
+    updated_at_ms: u64,
+}
+
+impl GossipRegistry {
+    /// Create an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Apply a leader WS message if it is a gossip snapshot or diff.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the message was applied, `false` for other message types
+    pub fn apply(&mut self, msg: &LeaderMessage) -> Result<bool, serde_json::Error> {
+        match msg.msg_type.as_str() {
+            MSG_GOSSIP_SNAPSHOT => {
+                let snapshot = GossipSnapshotData::deserialize(&msg.data)?;
+                self.apply_snapshot(snapshot);
+                Ok(true)
+            }
+            MSG_GOSSIP_DIFF => {
+                let diff = GossipDiffData::deserialize(&msg.data)?;
+                self.apply_diff(diff);
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    /// Replace all known peers with a snapshot.
+    pub fn apply_snapshot(&mut self, snapshot: GossipSnapshotData) {
+        self.peers = snapshot
+            .peers
+            .into_iter()
+            .map(|peer| (peer.identity.clone(), peer))
+            .collect();
+        self.updated_at_ms = snapshot.timestamp;
+    }
+
+    /// Apply an incremental diff on top of the current state.
+    pub fn apply_diff(&mut self, diff: GossipDiffData) {
+        for identity in &diff.removed {
+            self.peers.remove(identity);
+        }
+        for peer in diff.added.into_iter().chain(diff.updated) {
+            self.peers.insert(peer.identity.clone(), peer);
+        }
+        self.updated_at_ms = diff.timestamp_ms;
+    }
+
+    /// Look up a peer by validator identity.
+    pub fn peer(&self, identity: &str) -> Option<&GossipPeer> {
+        self.peers.get(identity)
+    }
+
+    /// All known peers (unordered).
+    pub fn peers(&self) -> impl Iterator<Item = &GossipPeer> {
+        self.peers.values()
+    }
+
+    /// Peers located in a country (ISO 3166 code, case-insensitive).
+    pub fn peers_by_country(&self, code: &str) -> Vec<&GossipPeer> {
+        self.filter(|peer| peer.country_code.eq_ignore_ascii_case(code))
+    }
+
+    /// Peers located on a continent (two-letter code, case-insensitive).
+    pub fn peers_by_continent(&self, code: &str) -> Vec<&GossipPeer> {
+        self.filter(|peer| peer.continent_code.eq_ignore_ascii_case(code))
+    }
+
+    /// Up to `n` geolocated peers closest to `origin`, nearest first.
+    pub fn nearest_peers(&self, origin: GeoPoint, n: usize) -> Vec<&GossipPeer> {
+        let mut peers: Vec<(f64, &GossipPeer)> = self
+            .peers
+            .values()
+            .filter_map(|peer| Some((origin.distance_km(&peer.location()?), peer)))
+            .collect();
+        peers.sort_by(|a, b| a.0.total_cmp(&b.0));
+        peers.into_iter().take(n).map(|(_, peer)| peer).collect()
+    }
+
+    /// Peers announced from an ASN. Accepts `"AS15169"` or `"15169"`.
+    pub fn peers_by_asn(&self, asn: &str) -> Vec<&GossipPeer> {
+        let asn = strip_as_prefix(asn);
+        self.filter(|peer| strip_as_prefix(&peer.asn) == asn)
+    }
+
+    /// Peers with at least `min_stake` lamports, sorted by stake descending.
+    pub fn staked_peers(&self, min_stake: u64) -> Vec<&GossipPeer> {
+        let mut peers = self.filter(|peer| peer.stake >= min_stake);
+        peers.sort_by_key(|peer| std::cmp::Reverse(peer.stake));
+        peers
+    }
+
+    /// Number of known peers.
+    pub fn len(&self) -> usize {
+        self.peers.len()
+    }
+
+    /// Whether no peers are known.
+    pub fn is_empty(&self) -> bool {
+        self.peers.is_empty()
+    }
+
+    /// Timestamp (ms) of the last applied snapshot or diff.
+    pub fn updated_at_ms(&self) -> u64 {
+        self.updated_at_ms
+    }
+
+    fn filter(&self, predicate: impl Fn(&GossipPeer) -> bool) -> Vec<&GossipPeer> {
+        self.peers.values().filter(|peer| predicate(peer)).collect()
+    }
+}
+
+fn strip_as_prefix(asn: &str) -> &str {
+    asn.strip_prefix("AS")
+        .or_else(|| asn.strip_prefix("as"))
+        .unwrap_or(asn)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn located_peer(identity: &str, continent: &str, latitude: f64, longitude: f64) -> serde_json::Value {
+        let mut peer = peer(identity, 1, "", "");
+        peer["continentCode"] = json!(continent);
+        peer["latitude"] = json!(latitude);
+        peer["longitude"] = json!(longitude);
+        peer
+    }
+
+    fn peer(identity: &str, stake: u64, country: &str, asn: &str) -> serde_json::Value {
+        json!({
+            "identity": identity,
+            "tpuQuic": format!("1.1.1.1:{}", stake),
+            "tpuUdp": null,
+            "tpuForwardsQuic": null,
+            "tpuForwardsUdp": null,
+            "tpuVote": null,
+            "gossipAddr": null,
+            "version": "2.0.0",
+            "shredVersion": 1,
+            "stake": stake,
+            "commission": 5,
+            "isDelinquent": false,
+            "wallclock": 0,
+            "countryCode": country,
+            "asn": asn,
+        })
+    }
+
+    fn message(msg_type: &str, data: serde_json::Value) -> LeaderMessage {
+        LeaderMessage {
+            msg_type: msg_type.to_string(),
+            kind: None,
+            key: None,
+            data,
+        }
+    }
+
+    #[test]
+    fn test_snapshot_then_diff() {
+        let mut registry = GossipRegistry::new();
+        let snapshot = json!({
+            "timestamp": 1,
+            "count": 2,
+            "peers": [peer("a", 100, "US", "AS1"), peer("b", 50, "DE", "AS2")],
+        });
+        assert!(registry.apply(&message("gossip_snapshot", snapshot)).unwrap());
+        assert_eq!(registry.len(), 2);
+
+        let diff = json!({
+            "timestampMs": 2,
+            "added": [peer("c", 200, "de", "AS2")],
+            "removed": ["a"],
+            "updated": [peer("b", 75, "DE", "AS2")],
+        });
+        assert!(registry.apply(&message("gossip_diff", diff)).unwrap());
+        assert!(registry.peer("a").is_none());
+        assert_eq!(registry.peer("b").unwrap().stake, 75);
+        assert_eq!(registry.updated_at_ms(), 2);
+
+        assert_eq!(registry.peers_by_country("DE").len(), 2);
+        assert_eq!(registry.peers_by_asn("2").len(), 2);
+        let staked: Vec<_> = registry.staked_peers(60).iter().map(|p| p.identity.as_str()).collect();
+        assert_eq!(staked, ["c", "b"]);
+    }
+
+    #[test]
+    fn test_ignores_other_messages() {
+        let mut registry = GossipRegistry::new();
+        assert!(!registry.apply(&message("slot_update", json!({}))).unwrap());
+        assert!(registry.is_empty());
+    }
+
+    #[test]
+    fn test_geo_queries() {
+        let mut registry = GossipRegistry::new();
+        let snapshot = json!({
+            "timestamp": 1,
+            "count": 4,
+            "peers": [
+                located_peer("frankfurt", "EU", 50.11, 8.68),
+                located_peer("amsterdam", "eu", 52.37, 4.90),
+                located_peer("new-york", "NA", 40.71, -74.01),
+                peer("unknown", 1, "", ""),
+            ],
+        });
+        registry.apply(&message("gossip_snapshot", snapshot)).unwrap();
+
+        assert_eq!(registry.peers_by_continent("EU").len(), 2);
+        assert!(registry.peer("unknown").unwrap().location().is_none());
+
+        let paris = GeoPoint::new(48.86, 2.35);
+        let nearest: Vec<_> = registry
+            .nearest_peers(paris, 10)
+            .iter()
+            .map(|peer| peer.identity.as_str())
+            .collect();
+        assert_eq!(nearest, ["amsterdam", "frankfurt", "new-york"]);
+        assert_eq!(registry.nearest_peers(paris, 1).len(), 1);
+
+        // Paris to New York is about 5840 km
+        let distance = paris.distance_km(&GeoPoint::new(40.71, -74.01));
+        assert!((distance - 5840.0).abs() < 20.0, "{}", distance);
+    }
+}
@@ -0,0 +1,318 @@
+//! Routing health trend and coverage alerts.
+//!
+//! [`RoutingHealthTracker`] keeps the recent `routing_health` reports as
+//! numeric coverage samples and reports when coverage of the upcoming
+//! leaders drops below a threshold, naming the leaders that became
+//! unreachable since the previous report. Two coverages are tracked:
+//!
+//! - [`CoverageMetric::Gossip`]: upcoming leaders visible in gossip.
+//! - [`CoverageMetric::TpuQuic`]: upcoming leaders with a TPU QUIC address,
+//!   which is what QUIC senders actually need.
+//!
+//! # Example
+//! ```rust,no_run
+//! use k256_sdk::leader_ws::{CoverageMetric, LeaderMessage, RoutingHealthTracker};
+//!
+//! let mut health = RoutingHealthTracker::new().threshold(CoverageMetric::TpuQuic, 95.0);
+//! # let msg: LeaderMessage = unimplemented!();
+//! for event in health.apply(&msg)? {
+//!     println!("{:?} {:.1}% lost {:?}", event.metric, event.coverage_pct, event.newly_unreachable);
+//! }
+//! # Ok::<(), serde_json::Error>(())
+//! ```
+
+use std::collections::{HashSet, VecDeque};
+
+use serde::Deserialize;
+
+use super::alerts::coverage_pct;
+use super::types::{LeaderMessage, RoutingHealthData};
+
+/// Routing health message type.
+const MSG_ROUTING_HEALTH: &str = "routing_health";
+
+/// Default number of reports kept.
+pub const DEFAULT_HEALTH_HISTORY: usize = 120;
+
+/// Which coverage of the upcoming leaders a threshold applies to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CoverageMetric {
+    /// Leaders visible in gossip
+    Gossip,
+    /// Leaders with a TPU QUIC address
+    TpuQuic,
+}
+
+/// Coverage figures of one routing health report.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CoverageSample {
+    /// Number of upcoming leaders considered
+    pub leaders_total: u32,
+    /// Upcoming leaders visible in gossip, in percent
+    pub gossip_pct: f64,
+    /// Upcoming leaders with a TPU QUIC address, in percent
+    pub tpu_quic_pct: f64,
+}
+
+impl CoverageSample {
+    /// Coverage for `metric`, in percent.
+    pub fn coverage_pct(&self, metric: CoverageMetric) -> f64 {
+        match metric {
+            CoverageMetric::Gossip => self.gossip_pct,
+            CoverageMetric::TpuQuic => self.tpu_quic_pct,
+        }
+    }
+}
+
+/// Whether coverage fell below or recovered above its threshold.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CoverageChange {
+    /// Coverage dropped below the threshold
+    Dropped,
+    /// Coverage is at or above the threshold again
+    Recovered,
+}
+
+/// Coverage crossed a configured threshold.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CoverageEvent {
+    /// The coverage that crossed
+    pub metric: CoverageMetric,
+    /// Direction of the crossing
+    pub change: CoverageChange,
+    /// Coverage now, in percent
+    pub coverage_pct: f64,
+    /// Coverage in the previous report, in percent
+    pub previous_pct: Option<f64>,
+    /// The threshold crossed, in percent
+    pub threshold_pct: f64,
+    /// Leaders unreachable for `metric` now but not in the previous report
+    pub newly_unreachable: Vec<String>,
+    /// Leaders reachable again since the previous report
+    pub recovered: Vec<String>,
+}
+
+/// Per-metric threshold and whether coverage is currently below it.
+#[derive(Debug, Clone, Copy)]
+struct Threshold {
+    metric: CoverageMetric,
+    pct: f64,
+    below: bool,
+}
+
+/// Coverage history of `routing_health` reports with threshold alerts.
+#[derive(Debug, Clone)]
+pub struct RoutingHealthTracker {
+    capacity: usize,
+    samples: VecDeque<CoverageSample>,
+    thresholds: Vec<Threshold>,
+    /// Unreachable leaders of the previous report, per metric
+    unreachable_gossip: HashSet<String>,
+    unreachable_quic: HashSet<String>,
+}
+
+impl Default for RoutingHealthTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RoutingHealthTracker {
+    /// Keep [`DEFAULT_HEALTH_HISTORY`] reports; alert when either coverage
+    /// drops below 95%.
+    pub fn new() -> Self {
+        Self::with_capacity(DEFAULT_HEALTH_HISTORY)
+    }
+
+    /// Keep up to `capacity` reports.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            samples: VecDeque::new(),
+            thresholds: Vec::new(),
+            unreachable_gossip: HashSet::new(),
+            unreachable_quic: HashSet::new(),
+        }
+        .threshold(CoverageMetric::Gossip, 95.0)
+        .threshold(CoverageMetric::TpuQuic, 95.0)
+    }
+
+    /// Alert when coverage for `metric` drops below `pct` percent.
+    pub fn threshold(mut self, metric: CoverageMetric, pct: f64) -> Self {
+        self.thresholds.retain(|threshold| threshold.metric != metric);
+        self.thresholds.push(Threshold {
+            metric,
+            pct,
+            below: false,
+        });
+        self
+    }
+
+    /// Stop alerting for `metric`.
+    pub fn without_threshold(mut self, metric: CoverageMetric) -> Self {
+        self.thresholds.retain(|threshold| threshold.metric != metric);
+        self
+    }
+
+    /// Apply a leader WS message if it is a routing health report.
+    ///
+    /// # Returns
+    ///
+    /// Threshold crossings caused by the report; empty for other message types
+    pub fn apply(&mut self, msg: &LeaderMessage) -> Result<Vec<CoverageEvent>, serde_json::Error> {
+        if msg.msg_type != MSG_ROUTING_HEALTH {
+            return Ok(Vec::new());
+        }
+        Ok(self.record(&RoutingHealthData::deserialize(&msg.data)?))
+    }
+
+    /// Record a routing health report, returning threshold crossings.
+    pub fn record(&mut self, health: &RoutingHealthData) -> Vec<CoverageEvent> {
+        let unreachable_gossip: HashSet<String> = health.leaders_missing_gossip.iter().cloned().collect();
+        let mut unreachable_quic = unreachable_gossip.clone();
+        unreachable_quic.extend(health.leaders_without_tpu_quic.iter().cloned());
+
+        let sample = CoverageSample {
+            leaders_total: health.leaders_total,
+            gossip_pct: coverage_pct(health),
+            tpu_quic_pct: reachable_pct(health.leaders_total, unreachable_quic.len()),
+        };
+        let previous = self.samples.back().cloned();
+
+        let mut events = Vec::new();
+        for threshold in &mut self.thresholds {
+            let coverage = sample.coverage_pct(threshold.metric);
+            let below = coverage < threshold.pct;
+            if below == threshold.below {
+                continue;
+            }
+            threshold.below = below;
+            let (before, now) = match threshold.metric {
+                CoverageMetric::Gossip => (&self.unreachable_gossip, &unreachable_gossip),
+                CoverageMetric::TpuQuic => (&self.unreachable_quic, &unreachable_quic),
+            };
+            events.push(CoverageEvent {
+                metric: threshold.metric,
+                change: if below { CoverageChange::Dropped } else { CoverageChange::Recovered },
+                coverage_pct: coverage,
+                previous_pct: previous.as_ref().map(|sample| sample.coverage_pct(threshold.metric)),
+                threshold_pct: threshold.pct,
+                newly_unreachable: sorted_difference(now, before),
+                recovered: sorted_difference(before, now),
+            });
+        }
+
+        if self.samples.len() == self.capacity {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(sample);
+        self.unreachable_gossip = unreachable_gossip;
+        self.unreachable_quic = unreachable_quic;
+        events
+    }
+
+    /// Recorded reports, oldest first.
+    pub fn history(&self) -> impl Iterator<Item = &CoverageSample> {
+        self.samples.iter()
+    }
+
+    /// Latest report.
+    pub fn latest(&self) -> Option<&CoverageSample> {
+        self.samples.back()
+    }
+
+    /// Change in coverage for `metric` over the recorded reports, in
+    /// percentage points; negative when coverage is falling.
+    pub fn trend(&self, metric: CoverageMetric) -> Option<f64> {
+        let (first, last) = (self.samples.front()?, self.samples.back()?);
+        Some(last.coverage_pct(metric) - first.coverage_pct(metric))
+    }
+
+    /// Leaders unreachable for `metric` in the latest report, sorted.
+    pub fn unreachable(&self, metric: CoverageMetric) -> Vec<String> {
+        let leaders = match metric {
+            CoverageMetric::Gossip => &self.unreachable_gossip,
+            CoverageMetric::TpuQuic => &self.unreachable_quic,
+        };
+        sorted_difference(leaders, &HashSet::new())
+    }
+}
+
+fn reachable_pct(total: u32, unreachable: usize) -> f64 {
+    if total == 0 {
+        return 100.0;
+    }
+    let reachable = (total as usize).saturating_sub(unreachable);
+    reachable as f64 * 100.0 / total as f64
+}
+
+/// Members of `a` not in `b`, sorted.
+fn sorted_difference(a: &HashSet<String>, b: &HashSet<String>) -> Vec<String> {
+    let mut leaders: Vec<String> = a.difference(b).cloned().collect();
+    leaders.sort();
+    leaders
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn report(missing: &[&str], without_quic: &[&str]) -> LeaderMessage {
+        LeaderMessage {
+            msg_type: MSG_ROUTING_HEALTH.to_string(),
+            kind: None,
+            key: None,
+            data: json!({
+                "leadersTotal": 20,
+                "leadersInGossip": 20 - missing.len(),
+                "leadersMissingGossip": missing,
+                "leadersWithoutTpuQuic": without_quic,
+                "leadersDelinquent": [],
+                "coverage": "",
+            }),
+        }
+    }
+
+    #[test]
+    fn test_alerts_on_threshold_crossings() {
+        let mut health = RoutingHealthTracker::new().without_threshold(CoverageMetric::Gossip);
+        assert!(health.apply(&report(&[], &["q1"])).unwrap().is_empty());
+
+        let events = health.apply(&report(&["m1"], &["q1", "q2"])).unwrap();
+        assert_eq!(events.len(), 1);
+        let event = &events[0];
+        assert_eq!((event.metric, event.change), (CoverageMetric::TpuQuic, CoverageChange::Dropped));
+        assert_eq!((event.coverage_pct, event.previous_pct), (85.0, Some(95.0)));
+        assert_eq!(event.newly_unreachable, ["m1", "q2"]);
+        assert!(event.recovered.is_empty());
+
+        // Still below: no repeated alert
+        assert!(health.apply(&report(&["m1"], &["q2"])).unwrap().is_empty());
+
+        let events = health.apply(&report(&[], &[])).unwrap();
+        assert_eq!(events[0].change, CoverageChange::Recovered);
+        assert_eq!(events[0].recovered, ["m1", "q2"]);
+    }
+
+    #[test]
+    fn test_history_and_trend() {
+        let mut health = RoutingHealthTracker::with_capacity(2).threshold(CoverageMetric::Gossip, 80.0);
+        assert_eq!(health.trend(CoverageMetric::Gossip), None);
+        health.apply(&report(&[], &[])).unwrap();
+        health.apply(&report(&["m1"], &[])).unwrap();
+        let events = health.apply(&report(&["m1", "m2", "m3", "m4", "m5"], &[])).unwrap();
+        let metrics: Vec<_> = events.iter().map(|event| event.metric).collect();
+        assert_eq!(metrics, [CoverageMetric::TpuQuic, CoverageMetric::Gossip]);
+
+        assert_eq!(health.history().count(), 2);
+        assert_eq!(health.latest().unwrap().gossip_pct, 75.0);
+        assert_eq!(health.trend(CoverageMetric::Gossip), Some(-20.0));
+        assert_eq!(health.unreachable(CoverageMetric::Gossip).len(), 5);
+        let other = LeaderMessage {
+            msg_type: "slot_update".to_string(),
+            ..report(&[], &[])
+        };
+        assert!(health.apply(&other).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,218 @@
+//! Per-validator IP change history.
+//!
+//! Keeps the latest `ip_change` events of each identity in a ring buffer
+//! and flags identities whose address keeps changing, which usually means
+//! an unstable host or relay worth routing around. An identity flaps once
+//! it changes IP at least `max_changes` times within `window`, measured
+//! with the events' own timestamps.
+//!
+//! # Example
+//! ```rust,no_run
+//! use std::time::Duration;
+//! use k256_sdk::leader_ws::{IpHistory, LeaderMessage};
+//!
+//! let mut history = IpHistory::new().flap_threshold(3, Duration::from_secs(3600));
+//! # let msg: LeaderMessage = unimplemented!();
+//! if let Some(flap) = history.apply(&msg)? {
+//!     println!("{} changed IP {} times", flap.identity, flap.changes);
+//! }
+//! for change in history.ip_history("validator-identity") {
+//!     println!("{} -> {}", change.old_ip, change.new_ip);
+//! }
+//! # Ok::<(), serde_json::Error>(())
+//! ```
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::time::Duration;
+
+use serde::Deserialize;
+
+use super::types::{IpChangeData, LeaderMessage};
+
+/// IP change message type.
+const MSG_IP_CHANGE: &str = "ip_change";
+
+/// Default number of changes kept per identity.
+pub const DEFAULT_HISTORY_LEN: usize = 32;
+
+/// An identity changing IP too often.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IpFlap {
+    /// Validator identity
+    pub identity: String,
+    /// IP changes within the window, including the latest
+    pub changes: usize,
+    /// Distinct addresses seen within the window
+    pub distinct_ips: usize,
+    /// The window the changes fell in
+    pub window: Duration,
+    /// The change that crossed the threshold
+    pub latest: IpChangeData,
+}
+
+/// Ring buffers of recent IP changes, keyed by validator identity.
+#[derive(Debug, Clone)]
+pub struct IpHistory {
+    capacity: usize,
+    max_changes: usize,
+    window: Duration,
+    identities: HashMap<String, VecDeque<IpChangeData>>,
+}
+
+impl Default for IpHistory {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl IpHistory {
+    /// Keep [`DEFAULT_HISTORY_LEN`] changes per identity; flag 3 changes
+    /// within an hour.
+    pub fn new() -> Self {
+        Self::with_capacity(DEFAULT_HISTORY_LEN)
+    }
+
+    /// Keep up to `capacity` changes per identity.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            max_changes: 3,
+            window: Duration::from_secs(3600),
+            identities: HashMap::new(),
+        }
+    }
+
+    /// Flag identities with at least `max_changes` changes within `window`.
+    ///
+    /// Thresholds above the history capacity can never be reached.
+    pub fn flap_threshold(mut self, max_changes: usize, window: Duration) -> Self {
+        self.max_changes = max_changes.max(1);
+        self.window = window;
+        self
+    }
+
+    /// Apply a leader WS message if it is an IP change.
+    ///
+    /// # Returns
+    ///
+    /// The flap the change completes, if any; `None` for other message types
+    pub fn apply(&mut self, msg: &LeaderMessage) -> Result<Option<IpFlap>, serde_json::Error> {
+        if msg.msg_type != MSG_IP_CHANGE {
+            return Ok(None);
+        }
+        Ok(self.record(IpChangeData::deserialize(&msg.data)?))
+    }
+
+    /// Record an IP change, returning a flap while the identity is over the
+    /// threshold.
+    pub fn record(&mut self, change: IpChangeData) -> Option<IpFlap> {
+        let identity = change.identity.clone();
+        let history = self.identities.entry(identity.clone()).or_default();
+        if history.len() == self.capacity {
+            history.pop_front();
+        }
+        history.push_back(change);
+        self.flap(&identity)
+    }
+
+    /// Recorded changes of `identity`, oldest first.
+    pub fn ip_history(&self, identity: &str) -> impl Iterator<Item = &IpChangeData> {
+        self.identities.get(identity).into_iter().flatten()
+    }
+
+    /// Current IP of `identity`, from its latest change.
+    pub fn current_ip(&self, identity: &str) -> Option<&str> {
+        Some(self.identities.get(identity)?.back()?.new_ip.as_str())
+    }
+
+    /// Identities over the flap threshold as of their latest change, most
+    /// changes first.
+    pub fn flapping(&self) -> Vec<IpFlap> {
+        let mut flaps: Vec<IpFlap> = self.identities.keys().filter_map(|identity| self.flap(identity)).collect();
+        flaps.sort_by(|a, b| b.changes.cmp(&a.changes).then_with(|| a.identity.cmp(&b.identity)));
+        flaps
+    }
+
+    /// Number of identities with recorded changes.
+    pub fn len(&self) -> usize {
+        self.identities.len()
+    }
+
+    /// Whether no changes are recorded.
+    pub fn is_empty(&self) -> bool {
+        self.identities.is_empty()
+    }
+
+    fn flap(&self, identity: &str) -> Option<IpFlap> {
+        let history = self.identities.get(identity)?;
+        let latest = history.back()?;
+        let window_ms = u64::try_from(self.window.as_millis()).unwrap_or(u64::MAX);
+        let since = latest.timestamp_ms.saturating_sub(window_ms);
+        let recent: Vec<&IpChangeData> = history.iter().filter(|change| change.timestamp_ms >= since).collect();
+        if recent.len() < self.max_changes {
+            return None;
+        }
+        let distinct_ips: HashSet<&str> = recent
+            .iter()
+            .flat_map(|change| [change.old_ip.as_str(), change.new_ip.as_str()])
+            .collect();
+        Some(IpFlap {
+            identity: identity.to_string(),
+            changes: recent.len(),
+            distinct_ips: distinct_ips.len(),
+            window: self.window,
+            latest: latest.clone(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn change(identity: &str, old_ip: &str, new_ip: &str, timestamp_ms: u64) -> LeaderMessage {
+        LeaderMessage {
+            msg_type: MSG_IP_CHANGE.to_string(),
+            kind: None,
+            key: None,
+            data: json!({ "identity": identity, "oldIp": old_ip, "newIp": new_ip, "timestampMs": timestamp_ms }),
+        }
+    }
+
+    #[test]
+    fn test_history_ring_buffer() {
+        let mut history = IpHistory::with_capacity(2);
+        assert!(history.apply(&change("v", "1.1.1.1", "2.2.2.2", 1_000)).unwrap().is_none());
+        history.apply(&change("v", "2.2.2.2", "3.3.3.3", 2_000)).unwrap();
+        history.apply(&change("v", "3.3.3.3", "4.4.4.4", 3_000)).unwrap();
+        let other = LeaderMessage {
+            msg_type: "slot_update".to_string(),
+            ..change("v", "5.5.5.5", "6.6.6.6", 4_000)
+        };
+        assert!(history.apply(&other).unwrap().is_none());
+
+        let ips: Vec<_> = history.ip_history("v").map(|change| change.new_ip.as_str()).collect();
+        assert_eq!(ips, ["3.3.3.3", "4.4.4.4"]);
+        assert_eq!(history.current_ip("v"), Some("4.4.4.4"));
+        assert_eq!(history.ip_history("unknown").count(), 0);
+        assert_eq!(history.len(), 1);
+    }
+
+    #[test]
+    fn test_flap_detection() {
+        let mut history = IpHistory::new().flap_threshold(3, Duration::from_secs(60));
+        history.apply(&change("v", "1.1.1.1", "2.2.2.2", 0)).unwrap();
+        history.apply(&change("v", "2.2.2.2", "1.1.1.1", 50_000)).unwrap();
+        // The first change fell out of the window
+        assert!(history.apply(&change("v", "1.1.1.1", "2.2.2.2", 70_000)).unwrap().is_none());
+
+        let flap = history.apply(&change("v", "2.2.2.2", "3.3.3.3", 80_000)).unwrap().unwrap();
+        assert_eq!((flap.changes, flap.distinct_ips), (3, 3));
+        assert_eq!(flap.latest.new_ip, "3.3.3.3");
+        assert_eq!(history.flapping(), [flap]);
+
+        history.apply(&change("w", "5.5.5.5", "6.6.6.6", 80_000)).unwrap();
+        assert_eq!(history.flapping().len(), 1);
+    }
+}
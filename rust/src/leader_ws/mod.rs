@@ -3,8 +3,35 @@
 //! Real-time Solana leader schedule, gossip network, and routing data.
 //! Uses JSON mode over WebSocket — no binary decoding needed.
 
+pub mod alerts;
+#[cfg(not(target_arch = "wasm32"))]
 pub mod client;
+pub mod gossip;
+pub mod health;
+pub mod ip_history;
+pub mod persist;
+pub mod router;
+pub mod routing;
+pub mod schedule;
+pub mod skips;
+pub mod slot_clock;
+pub mod stake;
+pub mod tpu;
+pub mod tracker;
 pub mod types;
 
-pub use client::{LeaderConfig, LeaderWebSocketClient};
+pub use alerts::{Alert, AlertFilter, AlertKind, AlertSeverity};
+#[cfg(not(target_arch = "wasm32"))]
+pub use client::{LeaderCloseHandle, LeaderConfig, LeaderWebSocketClient};
+pub use gossip::{GeoPoint, GossipRegistry};
+pub use health::{CoverageChange, CoverageEvent, CoverageMetric, CoverageSample, RoutingHealthTracker};
+pub use ip_history::{IpFlap, IpHistory};
+pub use persist::LeaderStateSnapshot;
+pub use router::MessageRouter;
+pub use routing::{RoutingHint, RoutingHints};
+pub use skips::{LeaderSkipStats, SkipRateTracker, SkipWindow};
+pub use slot_clock::SlotClock;
+pub use stake::{LeaderStake, StakeDistribution, StakeShare};
+pub use tpu::{TpuResolver, TpuTarget};
+pub use tracker::LeaderTracker;
 pub use types::*;
@@ -0,0 +1,195 @@
+//! Leader and gossip state snapshots on disk.
+//!
+//! A restarting sender would otherwise have no leaders to route to until
+//! the server's `gossip_snapshot` and `leader_schedule` arrive.
+//! [`LeaderStateSnapshot`] captures a [`TpuResolver`]'s gossip peers,
+//! current-epoch schedule and slot as JSON; restoring it serves routing
+//! queries right away, with [`TpuResolver::staleness`] reporting its age
+//! until live data replaces it.
+//!
+//! # Example
+//! ```rust,no_run
+//! use k256_sdk::leader_ws::{LeaderStateSnapshot, TpuResolver};
+//!
+//! let mut resolver = TpuResolver::new();
+//! if let Ok(snapshot) = LeaderStateSnapshot::load("leader-state.json") {
+//!     resolver.restore(snapshot);
+//! }
+//! println!("warm={} stale for {:?}", resolver.is_warm(), resolver.staleness());
+//! // ... apply live messages, then periodically:
+//! LeaderStateSnapshot::capture(&resolver).save("leader-state.json")?;
+//! # Ok::<(), std::io::Error>(())
+//! ```
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::io;
+#[cfg(not(target_arch = "wasm32"))]
+use std::path::Path;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use super::tpu::TpuResolver;
+use super::types::{GossipSnapshotData, LeaderScheduleData};
+use crate::types::TimestampMs;
+
+/// Snapshot format revision written by this SDK.
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+/// Saved gossip peers, leader schedule and slot.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LeaderStateSnapshot {
+    /// Snapshot format revision
+    pub version: u32,
+    /// When the snapshot was taken (Unix ms)
+    pub saved_at_ms: u64,
+    /// Last known slot
+    pub current_slot: Option<u64>,
+    /// Gossip peers
+    pub gossip: GossipSnapshotData,
+    /// Schedule of the current epoch, if loaded
+    pub schedule: Option<LeaderScheduleData>,
+}
+
+impl LeaderStateSnapshot {
+    /// Capture the current state of `resolver`.
+    pub fn capture(resolver: &TpuResolver) -> Self {
+        let peers: Vec<_> = resolver.gossip().peers().cloned().collect();
+        Self {
+            version: SNAPSHOT_VERSION,
+            saved_at_ms: TimestampMs::now().get(),
+            current_slot: resolver.leaders().current_slot(),
+            gossip: GossipSnapshotData {
+                timestamp: resolver.gossip().updated_at_ms(),
+                count: peers.len(),
+                peers,
+            },
+            schedule: resolver.leaders().schedule(),
+        }
+    }
+
+    /// Time since the snapshot was taken.
+    pub fn age(&self) -> Duration {
+        TimestampMs(self.saved_at_ms).age()
+    }
+
+    /// Serialize as JSON.
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string(self)
+    }
+
+    /// Parse JSON, rejecting snapshots from a newer format revision.
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        let snapshot: Self = serde_json::from_str(json)?;
+        if snapshot.version > SNAPSHOT_VERSION {
+            return Err(serde::de::Error::custom(format!(
+                "snapshot version {} is newer than supported {}",
+                snapshot.version, SNAPSHOT_VERSION
+            )));
+        }
+        Ok(snapshot)
+    }
+
+    /// Write the snapshot to `path`, replacing it atomically.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path.as_ref();
+        let json = self.to_json().map_err(io::Error::other)?;
+        // Write next to the target and rename, so readers never see a partial file
+        let mut tmp = path.as_os_str().to_owned();
+        tmp.push(".tmp");
+        std::fs::write(&tmp, json)?;
+        std::fs::rename(&tmp, path)
+    }
+
+    /// Read a snapshot written by [`save`](Self::save).
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let json = std::fs::read_to_string(path)?;
+        Self::from_json(&json).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::leader_ws::types::LeaderMessage;
+    use serde_json::json;
+
+    fn message(msg_type: &str, data: serde_json::Value) -> LeaderMessage {
+        LeaderMessage {
+            msg_type: msg_type.to_string(),
+            kind: None,
+            key: None,
+            data,
+        }
+    }
+
+    fn resolver() -> TpuResolver {
+        let mut resolver = TpuResolver::new();
+        let schedule = json!({
+            "epoch": 1,
+            "slotsInEpoch": 4,
+            "validators": 2,
+            "schedule": [
+                {"identity": "a", "slots": 2, "slotIndices": [0, 1]},
+                {"identity": "b", "slots": 2, "slotIndices": [2, 3]},
+            ],
+        });
+        let peer = |identity: &str, tpu_quic: &str| {
+            json!({
+                "identity": identity,
+                "tpuQuic": tpu_quic,
+                "tpuUdp": null,
+                "tpuForwardsQuic": null,
+                "tpuForwardsUdp": null,
+                "tpuVote": null,
+                "gossipAddr": null,
+                "version": "2.0.0",
+                "shredVersion": 1,
+                "stake": 1,
+                "commission": 0,
+                "isDelinquent": false,
+                "wallclock": 0,
+            })
+        };
+        let gossip = json!({
+            "timestamp": 7,
+            "count": 2,
+            "peers": [peer("a", "10.0.0.1:8009"), peer("b", "10.0.0.2:8009")],
+        });
+        resolver.apply(&message("leader_schedule", schedule)).unwrap();
+        resolver.apply(&message("gossip_snapshot", gossip)).unwrap();
+        resolver
+            .apply(&message("slot_update", json!({"slot": 4, "leader": "a", "blockHeight": 1})))
+            .unwrap();
+        resolver
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let live = resolver();
+        let path = std::env::temp_dir().join(format!("k256-leader-state-{}.json", std::process::id()));
+        LeaderStateSnapshot::capture(&live).save(&path).unwrap();
+        let snapshot = LeaderStateSnapshot::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!((snapshot.current_slot, snapshot.gossip.count), (Some(4), 2));
+        assert!(snapshot.age() < Duration::from_secs(60));
+        let mut restored = TpuResolver::new();
+        restored.restore(snapshot);
+        assert_eq!(restored.gossip().updated_at_ms(), 7);
+        assert_eq!(restored.resolve(4), live.resolve(4));
+        assert_eq!(restored.leaders().slots_until("b"), Some(2));
+    }
+
+    #[test]
+    fn test_rejects_newer_versions() {
+        let mut snapshot = LeaderStateSnapshot::capture(&resolver());
+        snapshot.version = SNAPSHOT_VERSION + 1;
+        let json = snapshot.to_json().unwrap();
+        assert!(LeaderStateSnapshot::from_json(&json).is_err());
+        assert!(LeaderStateSnapshot::load("/nonexistent/leader-state.json").is_err());
+    }
+}
@@ -0,0 +1,166 @@
+//! Dispatch of leader WS messages by type and key.
+//!
+//! Keyed messages (e.g. `ip_change` keyed by validator identity) arrive on
+//! one shared stream. [`MessageRouter`] hands each message only to the
+//! handlers registered for its `(type, key)` pair, plus any registered for
+//! the type as a whole, so apps following a few validators need no giant
+//! filtering closure.
+//!
+//! # Example
+//! ```rust,no_run
+//! use k256_sdk::leader_ws::{IpChangeData, LeaderMessage, MessageRouter};
+//!
+//! let mut router = MessageRouter::new();
+//! router.on_keyed_data("ip_change", "validator-identity", |change: IpChangeData| {
+//!     println!("moved to {}", change.new_ip);
+//! });
+//! router.on_type("slot_update", |msg| println!("{}", msg.data));
+//! # let msg: LeaderMessage = unimplemented!();
+//! router.dispatch(&msg);
+//! ```
+
+use std::collections::HashMap;
+use std::fmt;
+
+use serde::de::DeserializeOwned;
+
+use super::types::LeaderMessage;
+
+type Handler = Box<dyn Fn(&LeaderMessage) + Send + 'static>;
+
+/// Routes leader WS messages to handlers by message type and key.
+#[derive(Default)]
+pub struct MessageRouter {
+    keyed: HashMap<(String, String), Vec<Handler>>,
+    by_type: HashMap<String, Vec<Handler>>,
+}
+
+impl fmt::Debug for MessageRouter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MessageRouter")
+            .field("keyed", &self.keyed.keys().collect::<Vec<_>>())
+            .field("by_type", &self.by_type.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl MessageRouter {
+    /// Create a router without handlers.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Call `handler` for messages of `msg_type` whose key is `key`.
+    pub fn on_keyed<H>(&mut self, msg_type: impl Into<String>, key: impl Into<String>, handler: H)
+    where
+        H: Fn(&LeaderMessage) + Send + 'static,
+    {
+        self.keyed
+            .entry((msg_type.into(), key.into()))
+            .or_default()
+            .push(Box::new(handler));
+    }
+
+    /// Like [`on_keyed`](Self::on_keyed), deserializing the message data
+    /// into `T` first. Messages whose data does not fit `T` are skipped.
+    pub fn on_keyed_data<T, H>(&mut self, msg_type: impl Into<String>, key: impl Into<String>, handler: H)
+    where
+        T: DeserializeOwned,
+        H: Fn(T) + Send + 'static,
+    {
+        self.on_keyed(msg_type, key, move |msg| {
+            if let Ok(data) = T::deserialize(&msg.data) {
+                handler(data);
+            }
+        });
+    }
+
+    /// Call `handler` for every message of `msg_type`, keyed or not.
+    pub fn on_type<H>(&mut self, msg_type: impl Into<String>, handler: H)
+    where
+        H: Fn(&LeaderMessage) + Send + 'static,
+    {
+        self.by_type.entry(msg_type.into()).or_default().push(Box::new(handler));
+    }
+
+    /// Remove the handlers of `(msg_type, key)`, returning whether any existed.
+    pub fn remove_keyed(&mut self, msg_type: &str, key: &str) -> bool {
+        self.keyed.remove(&(msg_type.to_string(), key.to_string())).is_some()
+    }
+
+    /// Hand `msg` to its keyed handlers, then its type handlers.
+    ///
+    /// # Returns
+    ///
+    /// Number of handlers called
+    pub fn dispatch(&self, msg: &LeaderMessage) -> usize {
+        let keyed = msg
+            .key
+            .as_ref()
+            .and_then(|key| self.keyed.get(&(msg.msg_type.clone(), key.clone())));
+        let handlers: Vec<&Handler> = keyed
+            .into_iter()
+            .flatten()
+            .chain(self.by_type.get(&msg.msg_type).into_iter().flatten())
+            .collect();
+        for handler in &handlers {
+            handler(msg);
+        }
+        handlers.len()
+    }
+
+    /// Whether no handlers are registered.
+    pub fn is_empty(&self) -> bool {
+        self.keyed.is_empty() && self.by_type.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::leader_ws::types::IpChangeData;
+    use serde_json::json;
+    use std::sync::mpsc;
+
+    fn ip_change(identity: &str, new_ip: &str) -> LeaderMessage {
+        LeaderMessage {
+            msg_type: "ip_change".to_string(),
+            kind: None,
+            key: Some(identity.to_string()),
+            data: json!({ "identity": identity, "oldIp": "0.0.0.0", "newIp": new_ip, "timestampMs": 1 }),
+        }
+    }
+
+    #[test]
+    fn test_routes_by_type_and_key() {
+        let mut router = MessageRouter::new();
+        assert!(router.is_empty());
+        let (tx, rx) = mpsc::channel();
+        let keyed = tx.clone();
+        router.on_keyed_data("ip_change", "a", move |change: IpChangeData| {
+            keyed.send(format!("a:{}", change.new_ip)).unwrap()
+        });
+        router.on_type("ip_change", move |msg| tx.send(format!("any:{}", msg.key.as_deref().unwrap())).unwrap());
+
+        assert_eq!(router.dispatch(&ip_change("a", "1.1.1.1")), 2);
+        assert_eq!(router.dispatch(&ip_change("b", "2.2.2.2")), 1);
+        let slot = LeaderMessage {
+            msg_type: "slot_update".to_string(),
+            ..ip_change("a", "")
+        };
+        assert_eq!(router.dispatch(&slot), 0);
+        assert_eq!(rx.try_iter().collect::<Vec<_>>(), ["a:1.1.1.1", "any:a", "any:b"]);
+
+        assert!(router.remove_keyed("ip_change", "a"));
+        assert_eq!(router.dispatch(&ip_change("a", "3.3.3.3")), 1);
+    }
+
+    #[test]
+    fn test_skips_mismatched_data() {
+        let mut router = MessageRouter::new();
+        router.on_keyed_data("ip_change", "a", |_: IpChangeData| panic!("data does not fit"));
+        let mut msg = ip_change("a", "1.1.1.1");
+        msg.data = json!({ "unexpected": true });
+        assert_eq!(router.dispatch(&msg), 1);
+    }
+}
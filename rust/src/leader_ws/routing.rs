@@ -0,0 +1,208 @@
+//! Geography-aware routing hints.
+//!
+//! Combines [`TpuResolver`] targets with gossip geolocation so a sender can
+//! prefer upcoming leaders, and relay peers, close to where it runs.
+//!
+//! # Example
+//! ```rust,no_run
+//! use k256_sdk::leader_ws::{GeoPoint, RoutingHints, TpuResolver};
+//!
+//! # let resolver = TpuResolver::new();
+//! let hints = RoutingHints::new("EU").with_location(GeoPoint::new(50.11, 8.68));
+//! for hint in hints.preferred(&resolver, 8) {
+//!     println!("{} slot {} local={} {:?}", hint.target.identity, hint.target.slot, hint.local, hint.distance_km);
+//! }
+//! ```
+
+use super::gossip::{GeoPoint, GossipRegistry};
+use super::tpu::{TpuResolver, TpuTarget};
+use super::types::GossipPeer;
+
+/// Upcoming leader annotated with its location relative to the sender.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RoutingHint {
+    /// Resolved TPU endpoints
+    pub target: TpuTarget,
+    /// Continent code of the leader (empty when unknown)
+    pub continent_code: String,
+    /// Country code of the leader (empty when unknown)
+    pub country_code: String,
+    /// Distance from the sender in kilometers, when both are located
+    pub distance_km: Option<f64>,
+    /// Whether the leader is on the sender's continent
+    pub local: bool,
+}
+
+/// Where the sender runs, for ranking leaders and relays.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RoutingHints {
+    continent: String,
+    location: Option<GeoPoint>,
+}
+
+impl RoutingHints {
+    /// Sender running on `continent` (two-letter code, e.g. `"EU"`).
+    pub fn new(continent: impl Into<String>) -> Self {
+        Self {
+            continent: continent.into(),
+            location: None,
+        }
+    }
+
+    /// Also rank by distance from `location`.
+    pub fn with_location(mut self, location: GeoPoint) -> Self {
+        self.location = Some(location);
+        self
+    }
+
+    /// Leaders of the next `slots` slots, in slot order, with geo annotations.
+    pub fn hints(&self, resolver: &TpuResolver, slots: usize) -> Vec<RoutingHint> {
+        resolver
+            .resolve(slots)
+            .into_iter()
+            .map(|target| self.hint(resolver.gossip().peer(&target.identity), target))
+            .collect()
+    }
+
+    /// Like [`hints`](Self::hints), ordered local leaders first, then by
+    /// distance; ties and unlocated leaders keep slot order.
+    pub fn preferred(&self, resolver: &TpuResolver, slots: usize) -> Vec<RoutingHint> {
+        let mut hints = self.hints(resolver, slots);
+        hints.sort_by(|a, b| {
+            let distance = |hint: &RoutingHint| hint.distance_km.unwrap_or(f64::INFINITY);
+            b.local.cmp(&a.local).then(distance(a).total_cmp(&distance(b)))
+        });
+        hints
+    }
+
+    /// Up to `n` peers on the sender's continent to use as relays, nearest
+    /// first when a location is set, otherwise by stake.
+    pub fn relays<'a>(&self, gossip: &'a GossipRegistry, n: usize) -> Vec<&'a GossipPeer> {
+        let mut peers = gossip.peers_by_continent(&self.continent);
+        match self.location {
+            Some(origin) => peers.sort_by(|a, b| {
+                let distance = |peer: &GossipPeer| {
+                    peer.location().map_or(f64::INFINITY, |point| origin.distance_km(&point))
+                };
+                distance(a).total_cmp(&distance(b))
+            }),
+            None => peers.sort_by_key(|peer| std::cmp::Reverse(peer.stake)),
+        }
+        peers.truncate(n);
+        peers
+    }
+
+    fn hint(&self, peer: Option<&GossipPeer>, target: TpuTarget) -> RoutingHint {
+        let continent_code = peer.map(|peer| peer.continent_code.clone()).unwrap_or_default();
+        let country_code = peer.map(|peer| peer.country_code.clone()).unwrap_or_default();
+        let distance_km = self
+            .location
+            .zip(peer.and_then(GossipPeer::location))
+            .map(|(origin, point)| origin.distance_km(&point));
+        RoutingHint {
+            local: !continent_code.is_empty() && continent_code.eq_ignore_ascii_case(&self.continent),
+            target,
+            continent_code,
+            country_code,
+            distance_km,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::leader_ws::LeaderMessage;
+    use serde_json::json;
+
+    fn message(msg_type: &str, data: serde_json::Value) -> LeaderMessage {
+        LeaderMessage {
+            msg_type: msg_type.to_string(),
+            kind: None,
+            key: None,
+            data,
+        }
+    }
+
+    fn peer(identity: &str, stake: u64, continent: &str, latitude: f64, longitude: f64) -> serde_json::Value {
+        json!({
+            "identity": identity,
+            "tpuQuic": "10.0.0.1:8009",
+            "tpuUdp": null,
+            "tpuForwardsQuic": null,
+            "tpuForwardsUdp": null,
+            "tpuVote": null,
+            "gossipAddr": null,
+            "version": "2.0.0",
+            "shredVersion": 1,
+            "stake": stake,
+            "commission": 0,
+            "isDelinquent": false,
+            "wallclock": 0,
+            "continentCode": continent,
+            "latitude": latitude,
+            "longitude": longitude,
+        })
+    }
+
+    fn resolver() -> TpuResolver {
+        let mut resolver = TpuResolver::new();
+        let schedule = json!({
+            "epoch": 0,
+            "slotsInEpoch": 8,
+            "validators": 4,
+            "schedule": [
+                {"identity": "new-york", "slots": 2, "slotIndices": [0, 1]},
+                {"identity": "amsterdam", "slots": 2, "slotIndices": [2, 3]},
+                {"identity": "unknown", "slots": 2, "slotIndices": [4, 5]},
+                {"identity": "frankfurt", "slots": 2, "slotIndices": [6, 7]},
+            ],
+        });
+        let snapshot = json!({
+            "timestamp": 0,
+            "count": 4,
+            "peers": [
+                peer("new-york", 5, "NA", 40.71, -74.01),
+                peer("amsterdam", 1, "EU", 52.37, 4.90),
+                peer("unknown", 1, "", 0.0, 0.0),
+                peer("frankfurt", 3, "EU", 50.11, 8.68),
+            ],
+        });
+        resolver.apply(&message("leader_schedule", schedule)).unwrap();
+        resolver.apply(&message("gossip_snapshot", snapshot)).unwrap();
+        resolver
+            .apply(&message("slot_update", json!({"slot": 0, "leader": "new-york", "blockHeight": 1})))
+            .unwrap();
+        resolver
+    }
+
+    #[test]
+    fn test_preferred_orders_local_then_nearest() {
+        let resolver = resolver();
+        let hints = RoutingHints::new("EU").with_location(GeoPoint::new(50.0, 8.5));
+
+        let in_slot_order: Vec<_> = hints.hints(&resolver, 8).into_iter().map(|h| h.target.identity).collect();
+        assert_eq!(in_slot_order, ["new-york", "amsterdam", "unknown", "frankfurt"]);
+
+        let preferred = hints.preferred(&resolver, 8);
+        let identities: Vec<_> = preferred.iter().map(|h| h.target.identity.as_str()).collect();
+        assert_eq!(identities, ["frankfurt", "amsterdam", "new-york", "unknown"]);
+        assert!(preferred[0].local && !preferred[2].local && !preferred[3].local);
+        assert!(preferred[3].distance_km.is_none());
+    }
+
+    #[test]
+    fn test_relays_on_continent() {
+        let resolver = resolver();
+        let by_stake: Vec<_> = RoutingHints::new("eu")
+            .relays(resolver.gossip(), 5)
+            .iter()
+            .map(|peer| peer.identity.as_str())
+            .collect();
+        assert_eq!(by_stake, ["frankfurt", "amsterdam"]);
+
+        let amsterdam = GeoPoint::new(52.37, 4.90);
+        let nearest = RoutingHints::new("EU").with_location(amsterdam).relays(resolver.gossip(), 1);
+        assert_eq!(nearest[0].identity, "amsterdam");
+    }
+}
@@ -0,0 +1,140 @@
+//! Leader schedule export.
+//!
+//! `leader_schedule` messages list each validator's slots as indices
+//! relative to the epoch start. These helpers convert them to absolute
+//! slots, using the same `epoch * slots_in_epoch` boundary as
+//! [`LeaderTracker`](super::LeaderTracker), and export the result as CSV
+//! or JSON.
+//!
+//! # Example
+//! ```rust,no_run
+//! use k256_sdk::leader_ws::LeaderScheduleData;
+//!
+//! # let schedule: LeaderScheduleData = unimplemented!();
+//! let slots = schedule.to_slot_map();
+//! if let Some((slot, leader)) = slots.range(schedule.first_slot()..).next() {
+//!     println!("epoch {} opens with {} at slot {}", schedule.epoch, leader, slot);
+//! }
+//! schedule.write_csv(std::io::stdout())?;
+//! # Ok::<(), std::io::Error>(())
+//! ```
+
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+
+use serde::Serialize;
+
+use super::types::LeaderScheduleData;
+
+/// One row of an exported schedule.
+#[derive(Serialize)]
+struct SlotLeader<'a> {
+    slot: u64,
+    leader: &'a str,
+}
+
+impl LeaderScheduleData {
+    /// Absolute slot of the first slot in the epoch.
+    pub fn first_slot(&self) -> u64 {
+        self.epoch * self.slots_in_epoch
+    }
+
+    /// Absolute slot of the last slot in the epoch.
+    pub fn last_slot(&self) -> u64 {
+        (self.first_slot() + self.slots_in_epoch).saturating_sub(1)
+    }
+
+    /// Absolute slot → leader identity.
+    ///
+    /// Indices outside the epoch are skipped.
+    pub fn to_slot_map(&self) -> BTreeMap<u64, String> {
+        self.slot_leaders()
+            .map(|(slot, leader)| (slot, leader.to_string()))
+            .collect()
+    }
+
+    /// Write the schedule as `slot,leader` CSV rows, in slot order, with a header.
+    pub fn write_csv<W: Write>(&self, writer: W) -> io::Result<()> {
+        let mut writer = io::BufWriter::new(writer);
+        writeln!(writer, "slot,leader")?;
+        for (slot, leader) in self.sorted() {
+            writeln!(writer, "{},{}", slot, leader)?;
+        }
+        writer.flush()
+    }
+
+    /// Schedule as CSV; see [`write_csv`](Self::write_csv).
+    pub fn to_csv(&self) -> String {
+        let mut csv = Vec::new();
+        self.write_csv(&mut csv).expect("writing to a Vec cannot fail");
+        String::from_utf8(csv).expect("identities are UTF-8")
+    }
+
+    /// Schedule as a JSON array of `{"slot", "leader"}` objects, in slot order.
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        let rows: Vec<SlotLeader<'_>> = self
+            .sorted()
+            .into_iter()
+            .map(|(slot, leader)| SlotLeader { slot, leader })
+            .collect();
+        serde_json::to_string(&rows)
+    }
+
+    fn slot_leaders(&self) -> impl Iterator<Item = (u64, &str)> + '_ {
+        let first_slot = self.first_slot();
+        self.schedule.iter().flat_map(move |validator| {
+            validator
+                .slot_indices
+                .iter()
+                .filter(|&&index| u64::from(index) < self.slots_in_epoch)
+                .map(move |&index| (first_slot + u64::from(index), validator.identity.as_str()))
+        })
+    }
+
+    fn sorted(&self) -> Vec<(u64, &str)> {
+        let mut slots: Vec<_> = self.slot_leaders().collect();
+        slots.sort_unstable_by_key(|&(slot, _)| slot);
+        slots.dedup_by_key(|&mut (slot, _)| slot);
+        slots
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn schedule() -> LeaderScheduleData {
+        serde_json::from_value(json!({
+            "epoch": 2,
+            "slotsInEpoch": 8,
+            "validators": 2,
+            "schedule": [
+                {"identity": "b", "slots": 2, "slotIndices": [4, 5]},
+                {"identity": "a", "slots": 3, "slotIndices": [0, 1, 9]},
+            ],
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_slot_map_uses_absolute_slots() {
+        let schedule = schedule();
+        assert_eq!((schedule.first_slot(), schedule.last_slot()), (16, 23));
+        let slots = schedule.to_slot_map();
+        let expected = [(16, "a"), (17, "a"), (20, "b"), (21, "b")];
+        assert_eq!(
+            slots.iter().map(|(&slot, leader)| (slot, leader.as_str())).collect::<Vec<_>>(),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_csv_and_json_export() {
+        let schedule = schedule();
+        assert_eq!(schedule.to_csv(), "slot,leader\n16,a\n17,a\n20,b\n21,b\n");
+        let rows: serde_json::Value = serde_json::from_str(&schedule.to_json().unwrap()).unwrap();
+        assert_eq!(rows[0], json!({"slot": 16, "leader": "a"}));
+        assert_eq!(rows.as_array().unwrap().len(), 4);
+    }
+}
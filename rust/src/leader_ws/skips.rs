@@ -0,0 +1,332 @@
+//! Per-leader skip rates.
+//!
+//! Aggregates `skip_event` messages so senders can avoid leaders that have
+//! been skipping slots. Each event carries the leader's epoch-to-date
+//! `assigned` / `produced` counters; the epoch rate is read from the latest
+//! event, the recent rate from the counter deltas over the last
+//! [`DEFAULT_WINDOW_SLOTS`] slots.
+//!
+//! Leaders only appear after their first skip, so a leader without stats
+//! has not skipped since the tracker started.
+//!
+//! # Example
+//! ```rust,no_run
+//! use k256_sdk::leader_ws::{LeaderMessage, SkipRateTracker, SkipWindow};
+//!
+//! let mut skips = SkipRateTracker::new();
+//! # let msg: LeaderMessage = unimplemented!();
+//! skips.apply(&msg)?;
+//! for stats in skips.worst_offenders(SkipWindow::Recent, 5) {
+//!     println!("{} skipped {:.1}%", stats.identity, stats.recent_skip_rate() * 100.0);
+//! }
+//! # Ok::<(), serde_json::Error>(())
+//! ```
+
+use std::collections::{HashMap, VecDeque};
+
+use serde::Deserialize;
+
+use super::types::{LeaderMessage, SkipEventData, SlotUpdateData};
+
+/// Skip event message type.
+const MSG_SKIP_EVENT: &str = "skip_event";
+/// Slot update message type.
+const MSG_SLOT_UPDATE: &str = "slot_update";
+
+/// Default length of the recent window, in slots.
+pub const DEFAULT_WINDOW_SLOTS: u64 = 1000;
+
+/// Period a skip rate is computed over.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SkipWindow {
+    /// Since the start of the current epoch
+    Epoch,
+    /// Over the tracker's recent slot window
+    Recent,
+}
+
+/// Skip statistics of one leader.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LeaderSkipStats {
+    /// Validator identity
+    pub identity: String,
+    /// Slots assigned this epoch, as of the latest skip event
+    pub assigned: u32,
+    /// Slots produced this epoch, as of the latest skip event
+    pub produced: u32,
+    /// Slots assigned within the recent window
+    pub recent_assigned: u32,
+    /// Slots skipped within the recent window
+    pub recent_skipped: u32,
+    /// Slot of the latest skip event
+    pub last_skip_slot: u64,
+}
+
+impl LeaderSkipStats {
+    /// Share of this epoch's assigned slots that were skipped (0-1).
+    pub fn epoch_skip_rate(&self) -> f64 {
+        rate(self.assigned.saturating_sub(self.produced), self.assigned)
+    }
+
+    /// Share of the recent window's assigned slots that were skipped (0-1).
+    pub fn recent_skip_rate(&self) -> f64 {
+        rate(self.recent_skipped, self.recent_assigned)
+    }
+
+    /// Skip rate over `window`.
+    pub fn skip_rate(&self, window: SkipWindow) -> f64 {
+        match window {
+            SkipWindow::Epoch => self.epoch_skip_rate(),
+            SkipWindow::Recent => self.recent_skip_rate(),
+        }
+    }
+}
+
+fn rate(skipped: u32, assigned: u32) -> f64 {
+    if assigned == 0 {
+        0.0
+    } else {
+        skipped.min(assigned) as f64 / assigned as f64
+    }
+}
+
+/// Epoch-to-date counters carried by one skip event.
+#[derive(Debug, Clone, Copy)]
+struct Sample {
+    slot: u64,
+    assigned: u32,
+    produced: u32,
+}
+
+/// Skip events of one leader, oldest first.
+///
+/// At most one sample older than the window is kept, as the baseline the
+/// window's deltas are taken from.
+#[derive(Debug, Clone, Default)]
+struct LeaderSamples {
+    samples: VecDeque<Sample>,
+}
+
+impl LeaderSamples {
+    fn push(&mut self, sample: Sample) {
+        // Counters going backwards mean a new epoch started
+        if self
+            .samples
+            .back()
+            .is_some_and(|last| sample.assigned < last.assigned || sample.produced < last.produced)
+        {
+            self.samples.clear();
+        }
+        self.samples.push_back(sample);
+    }
+
+    fn prune(&mut self, window_start: u64) {
+        while self.samples.len() > 1 && self.samples[1].slot < window_start {
+            self.samples.pop_front();
+        }
+    }
+
+    fn stats(&self, identity: &str, window_start: u64) -> Option<LeaderSkipStats> {
+        let latest = *self.samples.back()?;
+        let (recent_assigned, recent_skipped) = if latest.slot < window_start {
+            (0, 0)
+        } else {
+            let first = self.samples.front().copied().filter(|first| first.slot < window_start);
+            let base = first.unwrap_or(Sample {
+                slot: 0,
+                assigned: 0,
+                produced: 0,
+            });
+            let assigned = latest.assigned.saturating_sub(base.assigned);
+            let produced = latest.produced.saturating_sub(base.produced);
+            (assigned, assigned.saturating_sub(produced))
+        };
+        Some(LeaderSkipStats {
+            identity: identity.to_string(),
+            assigned: latest.assigned,
+            produced: latest.produced,
+            recent_assigned,
+            recent_skipped,
+            last_skip_slot: latest.slot,
+        })
+    }
+}
+
+/// Rolling per-leader skip rates from `skip_event` messages.
+#[derive(Debug, Clone)]
+pub struct SkipRateTracker {
+    window_slots: u64,
+    latest_slot: u64,
+    leaders: HashMap<String, LeaderSamples>,
+}
+
+impl Default for SkipRateTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SkipRateTracker {
+    /// Create an empty tracker with a [`DEFAULT_WINDOW_SLOTS`] recent window.
+    pub fn new() -> Self {
+        Self::with_window(DEFAULT_WINDOW_SLOTS)
+    }
+
+    /// Create an empty tracker with a recent window of `window_slots` slots.
+    pub fn with_window(window_slots: u64) -> Self {
+        Self {
+            window_slots: window_slots.max(1),
+            latest_slot: 0,
+            leaders: HashMap::new(),
+        }
+    }
+
+    /// Apply a leader WS message if it is a skip event or slot update.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the message was applied, `false` for other message types
+    pub fn apply(&mut self, msg: &LeaderMessage) -> Result<bool, serde_json::Error> {
+        match msg.msg_type.as_str() {
+            MSG_SKIP_EVENT => {
+                self.apply_skip(&SkipEventData::deserialize(&msg.data)?);
+                Ok(true)
+            }
+            MSG_SLOT_UPDATE => {
+                self.set_slot(SlotUpdateData::deserialize(&msg.data)?.slot);
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    /// Record a skip event.
+    pub fn apply_skip(&mut self, event: &SkipEventData) {
+        self.leaders.entry(event.leader.clone()).or_default().push(Sample {
+            slot: event.slot,
+            assigned: event.assigned,
+            produced: event.produced,
+        });
+        self.set_slot(event.slot);
+    }
+
+    /// Advance the recent window to end at `slot`.
+    pub fn set_slot(&mut self, slot: u64) {
+        if slot <= self.latest_slot {
+            return;
+        }
+        self.latest_slot = slot;
+        let window_start = self.window_start();
+        for samples in self.leaders.values_mut() {
+            samples.prune(window_start);
+        }
+    }
+
+    /// Forget all leaders, e.g. on [`EpochRollover`](super::EpochRollover).
+    pub fn reset(&mut self) {
+        self.leaders.clear();
+    }
+
+    /// Statistics of `identity`, if it has skipped since the tracker started.
+    pub fn stats(&self, identity: &str) -> Option<LeaderSkipStats> {
+        self.leaders.get(identity)?.stats(identity, self.window_start())
+    }
+
+    /// Skip rate of `identity` over `window` (0-1).
+    pub fn skip_rate(&self, identity: &str, window: SkipWindow) -> Option<f64> {
+        self.stats(identity).map(|stats| stats.skip_rate(window))
+    }
+
+    /// Up to `n` leaders with the highest skip rate over `window`, worst first.
+    ///
+    /// Leaders without skips in `window` are left out.
+    pub fn worst_offenders(&self, window: SkipWindow, n: usize) -> Vec<LeaderSkipStats> {
+        let window_start = self.window_start();
+        let mut stats: Vec<LeaderSkipStats> = self
+            .leaders
+            .iter()
+            .filter_map(|(identity, samples)| samples.stats(identity, window_start))
+            .filter(|stats| stats.skip_rate(window) > 0.0)
+            .collect();
+        stats.sort_by(|a, b| {
+            b.skip_rate(window)
+                .total_cmp(&a.skip_rate(window))
+                .then_with(|| a.identity.cmp(&b.identity))
+        });
+        stats.truncate(n);
+        stats
+    }
+
+    /// Number of leaders with recorded skips.
+    pub fn len(&self) -> usize {
+        self.leaders.len()
+    }
+
+    /// Whether no skips are recorded.
+    pub fn is_empty(&self) -> bool {
+        self.leaders.is_empty()
+    }
+
+    fn window_start(&self) -> u64 {
+        (self.latest_slot + 1).saturating_sub(self.window_slots)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn skip(slot: u64, leader: &str, assigned: u32, produced: u32) -> LeaderMessage {
+        LeaderMessage {
+            msg_type: MSG_SKIP_EVENT.to_string(),
+            kind: None,
+            key: None,
+            data: json!({ "slot": slot, "leader": leader, "assigned": assigned, "produced": produced }),
+        }
+    }
+
+    #[test]
+    fn test_epoch_and_recent_rates() {
+        let mut skips = SkipRateTracker::with_window(100);
+        skips.apply(&skip(10, "a", 8, 6)).unwrap();
+        skips.apply(&skip(150, "a", 40, 37)).unwrap();
+        skips.apply(&skip(160, "a", 44, 40)).unwrap();
+
+        let stats = skips.stats("a").unwrap();
+        assert_eq!((stats.assigned, stats.produced), (44, 40));
+        assert_eq!(skips.skip_rate("a", SkipWindow::Epoch), Some(4.0 / 44.0));
+        // Window 61..=160 measured from the slot 10 baseline: 36 assigned, 2 skipped
+        assert_eq!((stats.recent_assigned, stats.recent_skipped), (36, 2));
+        assert_eq!(skips.skip_rate("b", SkipWindow::Epoch), None);
+
+        // Slot advances past the window: no recent skips, epoch rate kept
+        skips.set_slot(400);
+        assert_eq!(skips.skip_rate("a", SkipWindow::Recent), Some(0.0));
+        assert_eq!(skips.skip_rate("a", SkipWindow::Epoch), Some(4.0 / 44.0));
+
+        // Counters reset with the new epoch
+        skips.apply(&skip(432_010, "a", 4, 3)).unwrap();
+        assert_eq!(skips.skip_rate("a", SkipWindow::Epoch), Some(0.25));
+        assert_eq!(skips.skip_rate("a", SkipWindow::Recent), Some(0.25));
+    }
+
+    #[test]
+    fn test_worst_offenders() {
+        let mut skips = SkipRateTracker::new();
+        skips.apply(&skip(1, "a", 100, 99)).unwrap();
+        skips.apply(&skip(2, "b", 4, 2)).unwrap();
+        skips.apply(&skip(3, "c", 8, 6)).unwrap();
+
+        let worst: Vec<_> = skips
+            .worst_offenders(SkipWindow::Epoch, 2)
+            .into_iter()
+            .map(|stats| stats.identity)
+            .collect();
+        assert_eq!(worst, ["b", "c"]);
+
+        skips.reset();
+        assert!(skips.is_empty());
+        assert!(skips.worst_offenders(SkipWindow::Recent, 5).is_empty());
+    }
+}
@@ -0,0 +1,303 @@
+//! Slot clock between slot updates.
+//!
+//! [`SlotClock`] anchors on the newest observed slot and extrapolates the
+//! current slot from an exponentially weighted moving average of the slot
+//! duration, so schedulers can act a fixed number of slots before a target
+//! leader without waiting for the next `slot_update`. Slots come from
+//! `slot_update` messages and, optionally, blockhash slots of the liquidity
+//! feed.
+//!
+//! `Instant::now` panics on `wasm32`, so there only the methods taking an
+//! explicit instant, such as [`SlotClock::observe_slot_at`] and
+//! [`SlotClock::slot_at`], are available.
+//!
+//! # Example
+//! ```rust,no_run
+//! use k256_sdk::leader_ws::{LeaderMessage, SlotClock};
+//!
+//! # async fn example(msg: LeaderMessage, target_slot: u64) -> Result<(), serde_json::Error> {
+//! let clock = SlotClock::new();
+//! clock.apply(&msg)?;
+//! // Two slots before the target leader
+//! clock.await_slot(target_slot - 2).await;
+//! println!("slot ~{:?}, {:?} per slot", clock.current_slot(), clock.slot_duration());
+//! # Ok(())
+//! # }
+//! ```
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+#[cfg(not(target_arch = "wasm32"))]
+use serde::Deserialize;
+use tokio::sync::watch;
+
+#[cfg(not(target_arch = "wasm32"))]
+use super::types::{LeaderMessage, SlotUpdateData};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::types::Blockhash;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::ws::{CallbackHandle, K256WebSocketClient};
+
+/// Slot update message type.
+#[cfg(not(target_arch = "wasm32"))]
+const MSG_SLOT_UPDATE: &str = "slot_update";
+
+/// Target Solana slot duration, the estimate before any observation.
+pub const DEFAULT_SLOT_DURATION: Duration = Duration::from_millis(400);
+
+/// Weight of a new slot duration sample in the moving average.
+const DEFAULT_SMOOTHING: f64 = 0.1;
+
+/// Samples this far off the current estimate (as a factor) are discarded,
+/// e.g. the gap across a reconnect.
+const OUTLIER_FACTOR: f64 = 4.0;
+
+#[derive(Debug, Clone)]
+struct ClockState {
+    /// Newest observed slot and when it was observed
+    anchor: Option<(u64, Instant)>,
+    slot_duration: Duration,
+    /// Observed minus estimated slot at the latest observation
+    drift: f64,
+    samples: u64,
+}
+
+/// Estimates the current slot between slot observations.
+///
+/// Cheap to clone; clones share the same clock.
+#[derive(Debug, Clone)]
+pub struct SlotClock {
+    state: Arc<watch::Sender<ClockState>>,
+    smoothing: f64,
+}
+
+impl Default for SlotClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SlotClock {
+    /// Clock starting from [`DEFAULT_SLOT_DURATION`].
+    pub fn new() -> Self {
+        Self::with_slot_duration(DEFAULT_SLOT_DURATION)
+    }
+
+    /// Clock starting from an initial slot duration estimate.
+    pub fn with_slot_duration(slot_duration: Duration) -> Self {
+        Self {
+            state: Arc::new(watch::Sender::new(ClockState {
+                anchor: None,
+                slot_duration: slot_duration.max(Duration::from_millis(1)),
+                drift: 0.0,
+                samples: 0,
+            })),
+            smoothing: DEFAULT_SMOOTHING,
+        }
+    }
+
+    /// Weight (0-1] of each new sample in the slot duration average
+    /// (default 0.1). Higher values follow changes faster but are noisier.
+    pub fn smoothing(mut self, smoothing: f64) -> Self {
+        self.smoothing = smoothing.clamp(f64::MIN_POSITIVE, 1.0);
+        self
+    }
+
+    /// Apply a leader WS message if it is a slot update.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the message was applied, `false` for other message types
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn apply(&self, msg: &LeaderMessage) -> Result<bool, serde_json::Error> {
+        if msg.msg_type != MSG_SLOT_UPDATE {
+            return Ok(false);
+        }
+        self.observe_slot(SlotUpdateData::deserialize(&msg.data)?.slot);
+        Ok(true)
+    }
+
+    /// Observe the slot of a blockhash message.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn observe_blockhash(&self, blockhash: &Blockhash) {
+        self.observe_slot(blockhash.slot.get());
+    }
+
+    /// Feed the clock from `client`'s blockhash messages.
+    ///
+    /// Runs alongside other [`K256WebSocketClient::on_blockhash`] callbacks
+    /// until the returned handle is dropped.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn attach(&self, client: &K256WebSocketClient) -> CallbackHandle {
+        let clock = self.clone();
+        client.on_blockhash(move |blockhash| clock.observe_blockhash(&blockhash))
+    }
+
+    /// Observe `slot` now.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn observe_slot(&self, slot: u64) {
+        self.observe_slot_at(slot, Instant::now());
+    }
+
+    /// Observe `slot` at `at`. Slots not newer than the last one are ignored.
+    pub fn observe_slot_at(&self, slot: u64, at: Instant) {
+        let smoothing = self.smoothing;
+        self.state.send_if_modified(|state| {
+            if let Some((anchor_slot, anchor_at)) = state.anchor {
+                if slot <= anchor_slot || at < anchor_at {
+                    return false;
+                }
+                state.drift = slot as f64 - estimate(state, at);
+                let sample = at.duration_since(anchor_at).as_secs_f64() / (slot - anchor_slot) as f64;
+                let current = state.slot_duration.as_secs_f64();
+                if sample > current / OUTLIER_FACTOR && sample < current * OUTLIER_FACTOR {
+                    let updated = current + smoothing * (sample - current);
+                    state.slot_duration = Duration::from_secs_f64(updated);
+                    state.samples += 1;
+                }
+            }
+            state.anchor = Some((slot, at));
+            true
+        });
+    }
+
+    /// Estimated current slot, `None` before the first observation.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn current_slot(&self) -> Option<u64> {
+        self.slot_at(Instant::now())
+    }
+
+    /// Estimated slot at `at`.
+    pub fn slot_at(&self, at: Instant) -> Option<u64> {
+        let state = self.state.borrow();
+        state.anchor?;
+        Some(estimate(&state, at) as u64)
+    }
+
+    /// Estimated start of `slot`; in the past for slots already reached.
+    pub fn slot_start(&self, slot: u64) -> Option<Instant> {
+        let state = self.state.borrow();
+        let (anchor_slot, anchor_at) = state.anchor?;
+        let duration = state.slot_duration.as_secs_f64();
+        Some(if slot >= anchor_slot {
+            anchor_at + Duration::from_secs_f64((slot - anchor_slot) as f64 * duration)
+        } else {
+            let back = Duration::from_secs_f64((anchor_slot - slot) as f64 * duration);
+            anchor_at.checked_sub(back).unwrap_or(anchor_at)
+        })
+    }
+
+    /// Estimated time until `slot` starts, zero if it already did.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn time_until(&self, slot: u64) -> Option<Duration> {
+        Some(self.slot_start(slot)?.saturating_duration_since(Instant::now()))
+    }
+
+    /// Smoothed slot duration.
+    pub fn slot_duration(&self) -> Duration {
+        self.state.borrow().slot_duration
+    }
+
+    /// How far the latest observation was ahead of the estimate, in slots;
+    /// negative when the chain ran slower than estimated.
+    pub fn drift(&self) -> f64 {
+        self.state.borrow().drift
+    }
+
+    /// Number of slot duration samples taken.
+    pub fn samples(&self) -> u64 {
+        self.state.borrow().samples
+    }
+
+    /// Wait until the estimated current slot reaches `slot`.
+    ///
+    /// Re-estimates whenever a new slot is observed, and waits for the first
+    /// observation. Wrap in [`tokio::time::timeout`] to bound the wait.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub async fn await_slot(&self, slot: u64) {
+        let mut rx = self.state.subscribe();
+        loop {
+            let start = {
+                rx.borrow_and_update();
+                self.slot_start(slot)
+            };
+            match start {
+                Some(start) if start <= Instant::now() => return,
+                Some(start) => {
+                    tokio::select! {
+                        _ = tokio::time::sleep_until(start.into()) => {}
+                        // The sender lives in `self`, so the channel cannot close here
+                        _ = rx.changed() => {}
+                    }
+                }
+                None => {
+                    let _ = rx.changed().await;
+                }
+            }
+        }
+    }
+}
+
+/// Fractional slot at `at`, extrapolated from the anchor.
+fn estimate(state: &ClockState, at: Instant) -> f64 {
+    let Some((anchor_slot, anchor_at)) = state.anchor else {
+        return 0.0;
+    };
+    let elapsed = at.saturating_duration_since(anchor_at).as_secs_f64();
+    anchor_slot as f64 + elapsed / state.slot_duration.as_secs_f64()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_estimates_between_updates() {
+        let clock = SlotClock::new().smoothing(0.5);
+        let start = Instant::now();
+        assert_eq!(clock.slot_at(start), None);
+
+        clock.observe_slot_at(100, start);
+        assert_eq!(clock.slot_at(start + Duration::from_millis(1000)), Some(102));
+        assert_eq!(clock.slot_start(105), Some(start + Duration::from_millis(2000)));
+
+        // Slots arriving every 500ms pull the estimate up and show negative drift
+        clock.observe_slot_at(102, start + Duration::from_millis(1000));
+        assert_eq!(clock.slot_duration(), Duration::from_millis(450));
+        assert_eq!(clock.drift(), -0.5);
+        assert_eq!(clock.samples(), 1);
+
+        // Stale and outlier observations
+        clock.observe_slot_at(101, start + Duration::from_millis(1200));
+        clock.observe_slot_at(103, start + Duration::from_millis(60_000));
+        assert_eq!((clock.slot_duration(), clock.samples()), (Duration::from_millis(450), 1));
+
+        let update = LeaderMessage {
+            msg_type: MSG_SLOT_UPDATE.to_string(),
+            kind: None,
+            key: None,
+            data: json!({ "slot": 200, "leader": "v", "blockHeight": 1 }),
+        };
+        let clock = SlotClock::new();
+        assert!(clock.apply(&update).unwrap());
+        assert!(clock.current_slot().unwrap() >= 200);
+    }
+
+    #[tokio::test]
+    async fn test_await_slot() {
+        let clock = SlotClock::with_slot_duration(Duration::from_millis(10));
+        let waiter = tokio::spawn({
+            let clock = clock.clone();
+            async move { clock.await_slot(53).await }
+        });
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(!waiter.is_finished(), "no slot observed yet");
+
+        clock.observe_slot(50);
+        tokio::time::timeout(Duration::from_secs(1), waiter).await.unwrap().unwrap();
+        assert!(clock.current_slot().unwrap() >= 53);
+        clock.await_slot(10).await;
+    }
+}
@@ -0,0 +1,290 @@
+//! Stake distribution of the visible validator set.
+//!
+//! Aggregates gossip peer stake into the metrics researchers usually
+//! derive from the feed: total active stake, the Nakamoto coefficient, stake
+//! by country and ASN, and the stake behind the upcoming leaders. Delinquent
+//! peers hold no active stake and are left out throughout.
+//!
+//! # Example
+//! ```rust,no_run
+//! use k256_sdk::leader_ws::TpuResolver;
+//!
+//! # let resolver = TpuResolver::new();
+//! let distribution = resolver.gossip().stake_distribution();
+//! println!(
+//!     "{} lamports active, Nakamoto coefficient {}",
+//!     distribution.total_active_stake, distribution.nakamoto_coefficient
+//! );
+//! for leader in resolver.stake_weighted_leaders(16) {
+//!     println!("{} {} slots {:.2}%", leader.identity, leader.slots, leader.stake_share * 100.0);
+//! }
+//! ```
+
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use super::gossip::GossipRegistry;
+use super::tpu::TpuResolver;
+use super::types::GossipPeer;
+
+/// Stake held by one group of peers, e.g. a country or ASN.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StakeShare {
+    /// Group key (country code or ASN; empty when unknown)
+    pub key: String,
+    /// Number of peers in the group
+    pub peers: usize,
+    /// Active stake in lamports
+    pub stake: u64,
+    /// Fraction of the total active stake (0-1)
+    pub share: f64,
+}
+
+/// Stake distribution of the visible, non-delinquent peers.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StakeDistribution {
+    /// Sum of active stake in lamports
+    pub total_active_stake: u64,
+    /// Peers with active stake
+    pub staked_peers: usize,
+    /// Fewest peers jointly holding more than a third of the active stake
+    pub nakamoto_coefficient: usize,
+    /// Stake by country code, largest first
+    pub by_country: Vec<StakeShare>,
+    /// Stake by ASN, largest first
+    pub by_asn: Vec<StakeShare>,
+}
+
+/// Upcoming leader weighted by its stake.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LeaderStake {
+    /// Validator identity
+    pub identity: String,
+    /// Slots led within the window
+    pub slots: usize,
+    /// Active stake in lamports (0 when not in gossip or delinquent)
+    pub stake: u64,
+    /// Fraction of the total active stake (0-1)
+    pub stake_share: f64,
+}
+
+impl GossipRegistry {
+    /// Sum of the stake of non-delinquent peers, in lamports.
+    pub fn total_active_stake(&self) -> u64 {
+        self.active_peers().map(|peer| peer.stake).sum()
+    }
+
+    /// Fewest peers whose combined active stake exceeds a third of the total,
+    /// enough to halt consensus. 0 without any active stake.
+    pub fn nakamoto_coefficient(&self) -> usize {
+        let mut stakes: Vec<u64> = self.active_peers().map(|peer| peer.stake).collect();
+        stakes.sort_unstable_by_key(|&stake| std::cmp::Reverse(stake));
+        let total = u128::from(stakes.iter().sum::<u64>());
+        let mut cumulative: u128 = 0;
+        for (count, stake) in stakes.iter().enumerate() {
+            cumulative += u128::from(*stake);
+            if cumulative * 3 > total {
+                return count + 1;
+            }
+        }
+        0
+    }
+
+    /// Active stake by country code (upper-cased), largest first.
+    pub fn stake_by_country(&self) -> Vec<StakeShare> {
+        self.stake_by(|peer| peer.country_code.to_ascii_uppercase())
+    }
+
+    /// Active stake by ASN, largest first.
+    pub fn stake_by_asn(&self) -> Vec<StakeShare> {
+        self.stake_by(|peer| peer.asn.clone())
+    }
+
+    /// All stake metrics at once.
+    pub fn stake_distribution(&self) -> StakeDistribution {
+        StakeDistribution {
+            total_active_stake: self.total_active_stake(),
+            staked_peers: self.active_peers().filter(|peer| peer.stake > 0).count(),
+            nakamoto_coefficient: self.nakamoto_coefficient(),
+            by_country: self.stake_by_country(),
+            by_asn: self.stake_by_asn(),
+        }
+    }
+
+    /// Fraction of the total active stake held by `identity` (0-1).
+    pub fn stake_share(&self, identity: &str) -> f64 {
+        let total = self.total_active_stake();
+        match self.peer(identity) {
+            Some(peer) if !peer.is_delinquent && total > 0 => peer.stake as f64 / total as f64,
+            _ => 0.0,
+        }
+    }
+
+    fn active_peers(&self) -> impl Iterator<Item = &GossipPeer> {
+        self.peers().filter(|peer| !peer.is_delinquent)
+    }
+
+    fn stake_by(&self, key: impl Fn(&GossipPeer) -> String) -> Vec<StakeShare> {
+        let mut groups: HashMap<String, (usize, u64)> = HashMap::new();
+        for peer in self.active_peers() {
+            let group = groups.entry(key(peer)).or_default();
+            group.0 += 1;
+            group.1 += peer.stake;
+        }
+        let total = self.total_active_stake();
+        let mut shares: Vec<StakeShare> = groups
+            .into_iter()
+            .map(|(key, (peers, stake))| StakeShare {
+                key,
+                peers,
+                stake,
+                share: if total > 0 { stake as f64 / total as f64 } else { 0.0 },
+            })
+            .collect();
+        shares.sort_by(|a, b| b.stake.cmp(&a.stake).then_with(|| a.key.cmp(&b.key)));
+        shares
+    }
+}
+
+impl TpuResolver {
+    /// Leaders of the next `next_n_slots` slots, starting at the current
+    /// slot, with their stake; largest stake first, ties in slot order.
+    ///
+    /// Unlike [`resolve`](Self::resolve), leaders without gossip contact
+    /// info or flagged delinquent are kept, with their stake counted as 0.
+    pub fn stake_weighted_leaders(&self, next_n_slots: usize) -> Vec<LeaderStake> {
+        let Some(current) = self.leaders().current_slot() else {
+            return Vec::new();
+        };
+        let gossip = self.gossip();
+        let total = gossip.total_active_stake();
+
+        let mut leaders: Vec<LeaderStake> = Vec::new();
+        for slot in current..current + next_n_slots as u64 {
+            let Some(identity) = self.leaders().leader_at_slot(slot) else {
+                break;
+            };
+            if let Some(leader) = leaders.iter_mut().find(|leader| leader.identity == identity) {
+                leader.slots += 1;
+                continue;
+            }
+            let stake = gossip
+                .peer(identity)
+                .filter(|peer| !peer.is_delinquent)
+                .map_or(0, |peer| peer.stake);
+            leaders.push(LeaderStake {
+                identity: identity.to_string(),
+                slots: 1,
+                stake,
+                stake_share: if total > 0 { stake as f64 / total as f64 } else { 0.0 },
+            });
+        }
+        leaders.sort_by_key(|leader| std::cmp::Reverse(leader.stake));
+        leaders
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::leader_ws::types::LeaderMessage;
+    use serde_json::json;
+
+    fn message(msg_type: &str, data: serde_json::Value) -> LeaderMessage {
+        LeaderMessage {
+            msg_type: msg_type.to_string(),
+            kind: None,
+            key: None,
+            data,
+        }
+    }
+
+    fn peer(identity: &str, stake: u64, country: &str, asn: &str, delinquent: bool) -> serde_json::Value {
+        json!({
+            "identity": identity,
+            "tpuQuic": null,
+            "tpuUdp": null,
+            "tpuForwardsQuic": null,
+            "tpuForwardsUdp": null,
+            "tpuVote": null,
+            "gossipAddr": null,
+            "version": "2.0.0",
+            "shredVersion": 1,
+            "stake": stake,
+            "commission": 5,
+            "isDelinquent": delinquent,
+            "wallclock": 0,
+            "countryCode": country,
+            "asn": asn,
+        })
+    }
+
+    fn snapshot() -> serde_json::Value {
+        json!({
+            "timestamp": 1,
+            "count": 5,
+            "peers": [
+                peer("a", 300, "US", "AS1", false),
+                peer("b", 250, "de", "AS2", false),
+                peer("c", 250, "DE", "AS2", false),
+                peer("d", 200, "", "AS3", false),
+                peer("e", 1000, "US", "AS1", true),
+            ],
+        })
+    }
+
+    #[test]
+    fn test_stake_distribution() {
+        let mut registry = GossipRegistry::new();
+        assert_eq!(registry.nakamoto_coefficient(), 0);
+        registry.apply(&message("gossip_snapshot", snapshot())).unwrap();
+
+        let distribution = registry.stake_distribution();
+        assert_eq!(distribution.total_active_stake, 1000);
+        assert_eq!(distribution.staked_peers, 4);
+        // 300 alone is not above a third of 1000; 300 + 250 is
+        assert_eq!(distribution.nakamoto_coefficient, 2);
+
+        let countries: Vec<_> = distribution
+            .by_country
+            .iter()
+            .map(|share| (share.key.as_str(), share.peers, share.stake))
+            .collect();
+        assert_eq!(countries, [("DE", 2, 500), ("US", 1, 300), ("", 1, 200)]);
+        assert_eq!(distribution.by_asn[0].share, 0.5);
+        assert_eq!(registry.stake_share("a"), 0.3);
+        assert_eq!(registry.stake_share("e"), 0.0);
+    }
+
+    #[test]
+    fn test_stake_weighted_leaders() {
+        let mut resolver = TpuResolver::new();
+        let schedule = json!({
+            "epoch": 0,
+            "slotsInEpoch": 8,
+            "validators": 4,
+            "schedule": [
+                {"identity": "d", "slots": 2, "slotIndices": [0, 1]},
+                {"identity": "a", "slots": 3, "slotIndices": [2, 3, 6]},
+                {"identity": "x", "slots": 2, "slotIndices": [4, 5]},
+            ],
+        });
+        resolver.apply(&message("leader_schedule", schedule)).unwrap();
+        resolver.apply(&message("gossip_snapshot", snapshot())).unwrap();
+        assert!(resolver.stake_weighted_leaders(4).is_empty());
+
+        resolver
+            .apply(&message("slot_update", json!({"slot": 1, "leader": "d", "blockHeight": 1})))
+            .unwrap();
+        let leaders: Vec<_> = resolver
+            .stake_weighted_leaders(6)
+            .into_iter()
+            .map(|leader| (leader.identity, leader.slots, leader.stake))
+            .collect();
+        assert_eq!(
+            leaders,
+            [("a".to_string(), 3, 300), ("d".to_string(), 1, 200), ("x".to_string(), 2, 0)]
+        );
+    }
+}
@@ -0,0 +1,325 @@
+//! TPU endpoint resolution for upcoming leaders.
+//!
+//! Joins the leader schedule ([`LeaderTracker`]) with gossip contact info
+//! ([`GossipRegistry`]) to answer "where do I send transactions for the next
+//! few slots?".
+//!
+//! # Example
+//! ```rust,no_run
+//! use k256_sdk::leader_ws::{LeaderMessage, TpuResolver};
+//!
+//! let mut resolver = TpuResolver::new();
+//! # let msg: LeaderMessage = unimplemented!();
+//! resolver.apply(&msg)?;
+//! for target in resolver.resolve(4) {
+//!     println!("slot {} → {} {:?}", target.slot, target.identity, target.tpu_quic);
+//! }
+//! # Ok::<(), serde_json::Error>(())
+//! ```
+
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use serde::Deserialize;
+
+use super::gossip::GossipRegistry;
+use super::persist::LeaderStateSnapshot;
+use super::tracker::LeaderTracker;
+use super::types::{LeaderMessage, RoutingHealthData};
+use crate::types::TimestampMs;
+
+/// Routing health message type.
+const MSG_ROUTING_HEALTH: &str = "routing_health";
+/// Gossip snapshot message type.
+const MSG_GOSSIP_SNAPSHOT: &str = "gossip_snapshot";
+/// Leader schedule message type.
+const MSG_LEADER_SCHEDULE: &str = "leader_schedule";
+
+/// Resolved TPU endpoints of an upcoming leader.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TpuTarget {
+    /// Validator identity
+    pub identity: String,
+    /// First slot of the leader within the requested window
+    pub slot: u64,
+    /// TPU QUIC socket address
+    pub tpu_quic: Option<SocketAddr>,
+    /// TPU UDP socket address
+    pub tpu_udp: Option<SocketAddr>,
+    /// TPU forwards QUIC socket address
+    pub tpu_forwards_quic: Option<SocketAddr>,
+}
+
+/// Resolves TPU socket addresses of the leaders for an upcoming slot window.
+#[derive(Debug, Clone, Default)]
+pub struct TpuResolver {
+    gossip: GossipRegistry,
+    leaders: LeaderTracker,
+    routing_health: Option<RoutingHealthData>,
+    include_delinquent: bool,
+    /// When the restored snapshot was saved, while part of it is still in use
+    restored_at: Option<TimestampMs>,
+    restored_gossip: bool,
+    restored_schedule: bool,
+}
+
+impl TpuResolver {
+    /// Create an empty resolver.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Also return leaders flagged as delinquent (skipped by default).
+    pub fn include_delinquent(mut self, include: bool) -> Self {
+        self.include_delinquent = include;
+        self
+    }
+
+    /// Apply a leader WS message to the underlying gossip registry,
+    /// leader tracker, or routing health state.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the message was applied, `false` for other message types
+    pub fn apply(&mut self, msg: &LeaderMessage) -> Result<bool, serde_json::Error> {
+        if msg.msg_type == MSG_ROUTING_HEALTH {
+            self.routing_health = Some(RoutingHealthData::deserialize(&msg.data)?);
+            return Ok(true);
+        }
+        let gossip = self.gossip.apply(msg)?;
+        let leaders = self.leaders.apply(msg)?;
+        match msg.msg_type.as_str() {
+            MSG_GOSSIP_SNAPSHOT => self.restored_gossip = false,
+            MSG_LEADER_SCHEDULE => self.restored_schedule = false,
+            _ => {}
+        }
+        if !self.restored_gossip && !self.restored_schedule {
+            self.restored_at = None;
+        }
+        Ok(gossip || leaders)
+    }
+
+    /// Load gossip peers, the leader schedule and the current slot from a
+    /// saved snapshot, so routing queries can be answered before the live
+    /// snapshots arrive.
+    ///
+    /// The restored parts count as stale (see [`staleness`](Self::staleness))
+    /// until a live `gossip_snapshot` and `leader_schedule` replace them.
+    pub fn restore(&mut self, snapshot: LeaderStateSnapshot) {
+        self.gossip.apply_snapshot(snapshot.gossip);
+        self.restored_gossip = true;
+        if let Some(schedule) = &snapshot.schedule {
+            self.leaders.apply_schedule(schedule);
+            self.restored_schedule = true;
+        }
+        if let Some(slot) = snapshot.current_slot {
+            self.leaders.set_slot(slot);
+        }
+        self.restored_at = Some(TimestampMs(snapshot.saved_at_ms));
+    }
+
+    /// Whether both gossip peers and a schedule for the current epoch are
+    /// loaded, live or restored, so [`resolve`](Self::resolve) can answer.
+    pub fn is_warm(&self) -> bool {
+        !self.gossip.is_empty() && self.leaders.has_schedule()
+    }
+
+    /// Age of the restored snapshot while gossip or schedule still come from
+    /// it; `None` once both were replaced by live data.
+    pub fn staleness(&self) -> Option<Duration> {
+        Some(self.restored_at?.age())
+    }
+
+    /// Resolve TPU endpoints for the leaders of the next `slots` slots,
+    /// starting at the current slot.
+    ///
+    /// Each leader appears once, at its first slot in the window. Leaders
+    /// without any parseable TPU address, and delinquent leaders (per gossip
+    /// or the latest routing health report), are omitted.
+    pub fn resolve(&self, slots: usize) -> Vec<TpuTarget> {
+        let Some(current) = self.leaders.current_slot() else {
+            return Vec::new();
+        };
+
+        let mut targets: Vec<TpuTarget> = Vec::new();
+        for slot in current..current + slots as u64 {
+            let Some(identity) = self.leaders.leader_at_slot(slot) else {
+                break;
+            };
+            if targets.iter().any(|target| target.identity == identity) {
+                continue;
+            }
+            if !self.include_delinquent && self.is_delinquent(identity) {
+                continue;
+            }
+            let Some(peer) = self.gossip.peer(identity) else {
+                continue;
+            };
+            let target = TpuTarget {
+                identity: identity.to_string(),
+                slot,
+                tpu_quic: parse_addr(&peer.tpu_quic),
+                tpu_udp: parse_addr(&peer.tpu_udp),
+                tpu_forwards_quic: parse_addr(&peer.tpu_forwards_quic),
+            };
+            if target.tpu_quic.is_some() || target.tpu_udp.is_some() {
+                targets.push(target);
+            }
+        }
+        targets
+    }
+
+    /// Upcoming-leader coverage from the latest routing health report (0-100).
+    pub fn coverage_pct(&self) -> Option<f64> {
+        let coverage = &self.routing_health.as_ref()?.coverage;
+        coverage.trim().trim_end_matches('%').parse().ok()
+    }
+
+    /// Latest routing health report.
+    pub fn routing_health(&self) -> Option<&RoutingHealthData> {
+        self.routing_health.as_ref()
+    }
+
+    /// Underlying gossip registry.
+    pub fn gossip(&self) -> &GossipRegistry {
+        &self.gossip
+    }
+
+    /// Underlying leader tracker.
+    pub fn leaders(&self) -> &LeaderTracker {
+        &self.leaders
+    }
+
+    fn is_delinquent(&self, identity: &str) -> bool {
+        let in_gossip = self
+            .gossip
+            .peer(identity)
+            .is_some_and(|peer| peer.is_delinquent);
+        let in_health = self
+            .routing_health
+            .as_ref()
+            .is_some_and(|health| health.leaders_delinquent.iter().any(|id| id == identity));
+        in_gossip || in_health
+    }
+}
+
+fn parse_addr(addr: &Option<String>) -> Option<SocketAddr> {
+    addr.as_deref()?.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn message(msg_type: &str, data: serde_json::Value) -> LeaderMessage {
+        LeaderMessage {
+            msg_type: msg_type.to_string(),
+            kind: None,
+            key: None,
+            data,
+        }
+    }
+
+    fn peer(identity: &str, tpu_quic: Option<&str>, delinquent: bool) -> serde_json::Value {
+        json!({
+            "identity": identity,
+            "tpuQuic": tpu_quic,
+            "tpuUdp": null,
+            "tpuForwardsQuic": null,
+            "tpuForwardsUdp": null,
+            "tpuVote": null,
+            "gossipAddr": null,
+            "version": "2.0.0",
+            "shredVersion": 1,
+            "stake": 1,
+            "commission": 0,
+            "isDelinquent": delinquent,
+            "wallclock": 0,
+        })
+    }
+
+    #[test]
+    fn test_resolve_window() {
+        let mut resolver = TpuResolver::new();
+        let schedule = json!({
+            "epoch": 0,
+            "slotsInEpoch": 8,
+            "validators": 4,
+            "schedule": [
+                {"identity": "a", "slots": 2, "slotIndices": [0, 1]},
+                {"identity": "b", "slots": 2, "slotIndices": [2, 3]},
+                {"identity": "c", "slots": 2, "slotIndices": [4, 5]},
+                {"identity": "d", "slots": 2, "slotIndices": [6, 7]},
+            ],
+        });
+        let snapshot = json!({
+            "timestamp": 0,
+            "count": 4,
+            "peers": [
+                peer("a", Some("10.0.0.1:8009"), false),
+                peer("b", Some("10.0.0.2:8009"), true),
+                peer("c", Some("not-an-addr"), false),
+                peer("d", Some("10.0.0.4:8009"), false),
+            ],
+        });
+        resolver.apply(&message("leader_schedule", schedule)).unwrap();
+        resolver.apply(&message("gossip_snapshot", snapshot)).unwrap();
+        resolver
+            .apply(&message("slot_update", json!({"slot": 1, "leader": "a", "blockHeight": 1})))
+            .unwrap();
+
+        let targets = resolver.resolve(7);
+        let identities: Vec<_> = targets.iter().map(|t| (t.identity.as_str(), t.slot)).collect();
+        assert_eq!(identities, [("a", 1), ("d", 6)]);
+        assert_eq!(targets[0].tpu_quic, Some("10.0.0.1:8009".parse().unwrap()));
+
+        let resolver = resolver.include_delinquent(true);
+        assert_eq!(resolver.resolve(2).len(), 2);
+    }
+
+    #[test]
+    fn test_routing_health() {
+        let mut resolver = TpuResolver::new();
+        let health = json!({
+            "leadersTotal": 10,
+            "leadersInGossip": 9,
+            "leadersMissingGossip": ["x"],
+            "leadersWithoutTpuQuic": [],
+            "leadersDelinquent": ["a"],
+            "coverage": "98.5%",
+        });
+        assert!(resolver.apply(&message("routing_health", health)).unwrap());
+        assert_eq!(resolver.coverage_pct(), Some(98.5));
+        assert!(resolver.is_delinquent("a"));
+    }
+
+    #[test]
+    fn test_staleness_after_restore() {
+        let mut live = TpuResolver::new();
+        let schedule = json!({
+            "epoch": 0,
+            "slotsInEpoch": 4,
+            "validators": 1,
+            "schedule": [{"identity": "a", "slots": 4, "slotIndices": [0, 1, 2, 3]}],
+        });
+        let gossip = json!({"timestamp": 0, "count": 1, "peers": [peer("a", Some("10.0.0.1:8009"), false)]});
+        live.apply(&message("leader_schedule", schedule.clone())).unwrap();
+        live.apply(&message("gossip_snapshot", gossip.clone())).unwrap();
+        live.apply(&message("slot_update", json!({"slot": 1, "leader": "a", "blockHeight": 1}))).unwrap();
+        assert!(live.is_warm());
+        assert_eq!(live.staleness(), None);
+
+        let mut resolver = TpuResolver::new();
+        assert!(!resolver.is_warm());
+        resolver.restore(LeaderStateSnapshot::capture(&live));
+        assert!(resolver.is_warm());
+        assert!(resolver.staleness().is_some());
+        assert_eq!(resolver.resolve(2), live.resolve(2));
+
+        resolver.apply(&message("gossip_snapshot", gossip)).unwrap();
+        assert!(resolver.staleness().is_some(), "schedule still restored");
+        resolver.apply(&message("leader_schedule", schedule)).unwrap();
+        assert_eq!(resolver.staleness(), None);
+    }
+}
@@ -0,0 +1,372 @@
+//! Upcoming-leader lookup.
+//!
+//! Combines `leader_schedule` snapshots with `slot_update` messages to answer
+//! "who is leader now, and who is next?".
+//!
+//! Epoch boundaries are derived as `epoch * slots_in_epoch`, which holds on
+//! clusters without warmup epochs (mainnet-beta, devnet, testnet).
+//!
+//! A schedule for the next epoch that arrives early is held back until the
+//! current slot crosses the boundary. Crossing the boundary without it
+//! drops the stale schedule, so lookups return `None` instead of wrong
+//! leaders until the new schedule arrives.
+
+use serde::Deserialize;
+
+use super::types::{
+    EpochRollover, LeaderMessage, LeaderScheduleData, LeaderScheduleValidator, LeaderSubscribedData, SlotUpdateData,
+};
+
+/// Leader schedule message type.
+const MSG_LEADER_SCHEDULE: &str = "leader_schedule";
+/// Slot update message type.
+const MSG_SLOT_UPDATE: &str = "slot_update";
+/// Subscribed handshake message type.
+const MSG_SUBSCRIBED: &str = "subscribed";
+
+/// Marks slot indices with no assigned leader.
+const NO_LEADER: u32 = u32::MAX;
+
+/// One epoch's leader schedule, indexed by slot.
+#[derive(Debug, Clone, Default)]
+struct EpochSchedule {
+    epoch: u64,
+    slots_in_epoch: u64,
+    identities: Vec<String>,
+    /// Slot index → position in `identities`
+    slot_leaders: Vec<u32>,
+}
+
+impl EpochSchedule {
+    fn new(schedule: &LeaderScheduleData) -> Self {
+        let mut slot_leaders = vec![NO_LEADER; schedule.slots_in_epoch as usize];
+        let mut identities = Vec::with_capacity(schedule.schedule.len());
+        for validator in &schedule.schedule {
+            let position = identities.len() as u32;
+            identities.push(validator.identity.clone());
+            for &index in &validator.slot_indices {
+                if let Some(entry) = slot_leaders.get_mut(index as usize) {
+                    *entry = position;
+                }
+            }
+        }
+        Self {
+            epoch: schedule.epoch,
+            slots_in_epoch: schedule.slots_in_epoch,
+            identities,
+            slot_leaders,
+        }
+    }
+}
+
+/// Tracks the current slot against the epoch leader schedule.
+#[derive(Debug, Clone, Default)]
+pub struct LeaderTracker {
+    epoch: u64,
+    first_slot: u64,
+    slots_in_epoch: u64,
+    identities: Vec<String>,
+    /// Slot index → position in `identities`
+    slot_leaders: Vec<u32>,
+    current_slot: Option<u64>,
+    /// Next epoch's schedule, received before the boundary
+    next: Option<EpochSchedule>,
+    /// Boundary crossed, schedule for the new epoch not yet received
+    awaiting_schedule: bool,
+    /// Rollover not yet taken by [`LeaderTracker::take_rollover`]
+    rollover: Option<EpochRollover>,
+}
+
+impl LeaderTracker {
+    /// Create an empty tracker.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Apply a leader WS message if it carries schedule or slot information.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the message was applied, `false` for other message types
+    pub fn apply(&mut self, msg: &LeaderMessage) -> Result<bool, serde_json::Error> {
+        match msg.msg_type.as_str() {
+            MSG_LEADER_SCHEDULE => {
+                self.apply_schedule(&LeaderScheduleData::deserialize(&msg.data)?);
+                Ok(true)
+            }
+            MSG_SLOT_UPDATE => {
+                self.apply_slot(&SlotUpdateData::deserialize(&msg.data)?);
+                Ok(true)
+            }
+            MSG_SUBSCRIBED => {
+                let subscribed = LeaderSubscribedData::deserialize(&msg.data)?;
+                self.set_slot(subscribed.current_slot);
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    /// Apply an epoch's schedule.
+    ///
+    /// A schedule for a later epoch than the current slot's is held until
+    /// the slot crosses into that epoch; schedules for earlier epochs than
+    /// the tracked one are ignored.
+    pub fn apply_schedule(&mut self, schedule: &LeaderScheduleData) {
+        if schedule.slots_in_epoch == 0 {
+            return;
+        }
+        if self.slots_in_epoch != 0 && schedule.epoch < self.epoch {
+            return;
+        }
+        let current_epoch = self.current_slot.map(|slot| slot / schedule.slots_in_epoch);
+        let schedule = EpochSchedule::new(schedule);
+        if current_epoch.is_some_and(|current| schedule.epoch > current) {
+            self.next = Some(schedule);
+        } else {
+            self.install(schedule);
+        }
+    }
+
+    fn install(&mut self, schedule: EpochSchedule) {
+        self.epoch = schedule.epoch;
+        self.first_slot = schedule.epoch * schedule.slots_in_epoch;
+        self.slots_in_epoch = schedule.slots_in_epoch;
+        self.identities = schedule.identities;
+        self.slot_leaders = schedule.slot_leaders;
+        self.awaiting_schedule = false;
+        if self.next.as_ref().is_some_and(|next| next.epoch <= self.epoch) {
+            self.next = None;
+        }
+    }
+
+    /// Advance the current slot from a slot update.
+    pub fn apply_slot(&mut self, update: &SlotUpdateData) {
+        self.set_slot(update.slot);
+    }
+
+    /// Set the current slot. Older slots are ignored.
+    ///
+    /// Crossing into a later epoch than the tracked schedule's records an
+    /// [`EpochRollover`] (see [`take_rollover`](Self::take_rollover)).
+    pub fn set_slot(&mut self, slot: u64) {
+        if self.current_slot.is_some_and(|current| slot <= current) {
+            return;
+        }
+        self.current_slot = Some(slot);
+
+        if self.slots_in_epoch == 0 {
+            return;
+        }
+        let new_epoch = slot / self.slots_in_epoch;
+        if new_epoch <= self.epoch {
+            return;
+        }
+        let old = self.epoch;
+        match self.next.take().filter(|next| next.epoch == new_epoch) {
+            Some(next) => self.install(next),
+            None => {
+                // The old schedule no longer describes the current epoch
+                self.epoch = new_epoch;
+                self.first_slot = new_epoch * self.slots_in_epoch;
+                self.identities.clear();
+                self.slot_leaders.clear();
+                self.awaiting_schedule = true;
+            }
+        }
+        self.rollover = Some(EpochRollover {
+            old,
+            new: new_epoch,
+            schedule_ready: !self.awaiting_schedule,
+        });
+    }
+
+    /// Take the most recent epoch rollover, if one happened since the last call.
+    pub fn take_rollover(&mut self) -> Option<EpochRollover> {
+        self.rollover.take()
+    }
+
+    /// Whether the current slot has moved into an epoch whose schedule has
+    /// not arrived yet.
+    pub fn awaiting_schedule(&self) -> bool {
+        self.awaiting_schedule
+    }
+
+    /// Last known slot.
+    pub fn current_slot(&self) -> Option<u64> {
+        self.current_slot
+    }
+
+    /// Epoch of the tracked schedule.
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    /// Whether a schedule for the current epoch is loaded.
+    pub fn has_schedule(&self) -> bool {
+        !self.slot_leaders.is_empty()
+    }
+
+    /// The tracked epoch's schedule, rebuilt in the `leader_schedule` form.
+    ///
+    /// `None` while no schedule for the current epoch is loaded.
+    pub fn schedule(&self) -> Option<LeaderScheduleData> {
+        if !self.has_schedule() {
+            return None;
+        }
+        let mut schedule: Vec<LeaderScheduleValidator> = self
+            .identities
+            .iter()
+            .map(|identity| LeaderScheduleValidator {
+                identity: identity.clone(),
+                slots: 0,
+                slot_indices: Vec::new(),
+            })
+            .collect();
+        for (index, &position) in self.slot_leaders.iter().enumerate() {
+            if let Some(validator) = schedule.get_mut(position as usize) {
+                validator.slot_indices.push(index as u32);
+                validator.slots += 1;
+            }
+        }
+        Some(LeaderScheduleData {
+            epoch: self.epoch,
+            slots_in_epoch: self.slots_in_epoch,
+            validators: schedule.len(),
+            schedule,
+        })
+    }
+
+    /// Leader of the current slot.
+    pub fn current_leader(&self) -> Option<&str> {
+        self.leader_at_slot(self.current_slot?)
+    }
+
+    /// Leader of an absolute slot, if it falls within the tracked epoch.
+    pub fn leader_at_slot(&self, slot: u64) -> Option<&str> {
+        let index = slot.checked_sub(self.first_slot)?;
+        let position = *self.slot_leaders.get(usize::try_from(index).ok()?)?;
+        self.identities.get(position as usize).map(String::as_str)
+    }
+
+    /// Leaders of the next `n` slots after the current slot, as `(slot, identity)`.
+    ///
+    /// Stops early at the end of the tracked epoch.
+    pub fn upcoming_leaders(&self, n: usize) -> Vec<(u64, &str)> {
+        let Some(current) = self.current_slot else {
+            return Vec::new();
+        };
+        (current + 1..)
+            .take(n)
+            .map_while(|slot| self.leader_at_slot(slot).map(|leader| (slot, leader)))
+            .collect()
+    }
+
+    /// Slots until `identity` is next leader (0 if it is the current leader).
+    pub fn slots_until(&self, identity: &str) -> Option<u64> {
+        let current = self.current_slot?;
+        let position = self.identities.iter().position(|id| id == identity)? as u32;
+        let start = usize::try_from(current.checked_sub(self.first_slot)?).ok()?;
+        self.slot_leaders
+            .get(start..)?
+            .iter()
+            .position(|&leader| leader == position)
+            .map(|offset| offset as u64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::leader_ws::types::LeaderScheduleValidator;
+
+    fn schedule(epoch: u64, first: &str, second: &str) -> LeaderScheduleData {
+        let validator = |identity: &str, slot_indices: Vec<u32>| LeaderScheduleValidator {
+            identity: identity.to_string(),
+            slots: slot_indices.len(),
+            slot_indices,
+        };
+        LeaderScheduleData {
+            epoch,
+            slots_in_epoch: 8,
+            validators: 2,
+            schedule: vec![validator(first, vec![0, 1, 2, 3]), validator(second, vec![4, 5, 6, 7])],
+        }
+    }
+
+    fn tracker() -> LeaderTracker {
+        let mut tracker = LeaderTracker::new();
+        tracker.apply_schedule(&schedule(2, "a", "b"));
+        tracker
+    }
+
+    #[test]
+    fn test_rollover_with_early_schedule() {
+        let mut tracker = tracker();
+        tracker.set_slot(22);
+        tracker.apply_schedule(&schedule(3, "c", "d"));
+        assert_eq!(tracker.epoch(), 2);
+        assert_eq!(tracker.current_leader(), Some("b"));
+        assert_eq!(tracker.take_rollover(), None);
+
+        tracker.set_slot(24);
+        assert_eq!(
+            tracker.take_rollover(),
+            Some(EpochRollover { old: 2, new: 3, schedule_ready: true })
+        );
+        assert_eq!(tracker.take_rollover(), None);
+        assert_eq!(tracker.epoch(), 3);
+        assert_eq!(tracker.current_leader(), Some("c"));
+        assert!(!tracker.awaiting_schedule());
+    }
+
+    #[test]
+    fn test_rollover_without_schedule() {
+        let mut tracker = tracker();
+        tracker.set_slot(23);
+        tracker.set_slot(25);
+        assert_eq!(
+            tracker.take_rollover(),
+            Some(EpochRollover { old: 2, new: 3, schedule_ready: false })
+        );
+        assert!(tracker.awaiting_schedule());
+        assert!(!tracker.has_schedule());
+        assert_eq!(tracker.current_leader(), None);
+        assert_eq!(tracker.leader_at_slot(20), None);
+
+        // A late copy of the old schedule does not resurrect it
+        tracker.apply_schedule(&schedule(2, "a", "b"));
+        assert_eq!(tracker.current_leader(), None);
+
+        tracker.apply_schedule(&schedule(3, "c", "d"));
+        assert!(!tracker.awaiting_schedule());
+        assert_eq!(tracker.current_leader(), Some("c"));
+    }
+
+    #[test]
+    fn test_leader_lookup() {
+        let mut tracker = tracker();
+        assert_eq!(tracker.current_leader(), None);
+        assert_eq!(tracker.leader_at_slot(15), None);
+        assert_eq!(tracker.leader_at_slot(16), Some("a"));
+        assert_eq!(tracker.leader_at_slot(20), Some("b"));
+        assert_eq!(tracker.leader_at_slot(24), None);
+
+        tracker.set_slot(18);
+        tracker.set_slot(17);
+        assert_eq!(tracker.current_slot(), Some(18));
+        assert_eq!(tracker.current_leader(), Some("a"));
+        assert_eq!(tracker.upcoming_leaders(3), [(19, "a"), (20, "b"), (21, "b")]);
+        assert_eq!(tracker.slots_until("a"), Some(0));
+        assert_eq!(tracker.slots_until("b"), Some(2));
+        assert_eq!(tracker.slots_until("c"), None);
+
+        tracker.set_slot(22);
+        assert_eq!(tracker.upcoming_leaders(4), [(23, "b")]);
+        assert_eq!(tracker.slots_until("a"), None);
+
+        let schedule = tracker.schedule().unwrap();
+        assert_eq!((schedule.epoch, schedule.validators), (2, 2));
+        assert_eq!(schedule.schedule[1].slot_indices, [4, 5, 6, 7]);
+    }
+}
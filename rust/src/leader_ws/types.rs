@@ -4,10 +4,15 @@
 
 use serde::{Deserialize, Serialize};
 
-/// Subscription channel constants.
+// Subscription channel constants.
+
+/// Leader schedule channel (epoch schedule snapshots).
 pub const CHANNEL_LEADER_SCHEDULE: &str = "leader_schedule";
+/// Gossip channel (peer snapshots and diffs).
 pub const CHANNEL_GOSSIP: &str = "gossip";
+/// Slot updates channel.
 pub const CHANNEL_SLOTS: &str = "slots";
+/// Alerts channel (skip events, IP changes, routing health).
 pub const CHANNEL_ALERTS: &str = "alerts";
 
 /// All available channels.
@@ -22,68 +27,97 @@ pub const ALL_CHANNELS: &[&str] = &[
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
 pub enum MessageKind {
+    /// Full state replacement
     Snapshot,
+    /// Incremental change applied to the last snapshot
     Diff,
+    /// Point-in-time event
     Event,
 }
 
 /// Generic leader-schedule WS message envelope.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LeaderMessage {
+    /// Message type (e.g. "gossip_snapshot", "slot_update")
     #[serde(rename = "type")]
     pub msg_type: String,
+    /// How to consume the message
     #[serde(skip_serializing_if = "Option::is_none")]
     pub kind: Option<MessageKind>,
+    /// Optional message key (e.g. validator identity)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub key: Option<String>,
+    /// Raw message payload
     pub data: serde_json::Value,
 }
 
 /// Protocol schema entry (from subscribed handshake).
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MessageSchemaEntry {
+    /// Message type name
     #[serde(rename = "type")]
     pub msg_type: String,
+    /// Wire tag
     pub tag: String,
+    /// Message kind ("snapshot", "diff" or "event")
     pub kind: String,
+    /// Key field name, if the message is keyed
     #[serde(skip_serializing_if = "Option::is_none")]
     pub key: Option<String>,
+    /// Human-readable description
     pub description: String,
 }
 
 /// Subscribed response data.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LeaderSubscribedData {
+    /// Subscribed channels
     pub channels: Vec<String>,
+    /// Current Solana slot
     #[serde(rename = "currentSlot")]
     pub current_slot: u64,
+    /// Current epoch
     pub epoch: u64,
+    /// Protocol schema
     pub schema: Vec<MessageSchemaEntry>,
 }
 
 /// A single gossip peer.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GossipPeer {
+    /// Validator identity pubkey (base58)
     pub identity: String,
+    /// TPU QUIC socket address
     #[serde(rename = "tpuQuic")]
     pub tpu_quic: Option<String>,
+    /// TPU UDP socket address
     #[serde(rename = "tpuUdp")]
     pub tpu_udp: Option<String>,
+    /// TPU forwards QUIC socket address
     #[serde(rename = "tpuForwardsQuic")]
     pub tpu_forwards_quic: Option<String>,
+    /// TPU forwards UDP socket address
     #[serde(rename = "tpuForwardsUdp")]
     pub tpu_forwards_udp: Option<String>,
+    /// TPU vote socket address
     #[serde(rename = "tpuVote")]
     pub tpu_vote: Option<String>,
+    /// Gossip socket address
     #[serde(rename = "gossipAddr")]
     pub gossip_addr: Option<String>,
+    /// Validator software version
     pub version: String,
+    /// Shred version
     #[serde(rename = "shredVersion")]
     pub shred_version: u16,
+    /// Activated stake in lamports
     pub stake: u64,
+    /// Vote account commission (0-100)
     pub commission: u8,
+    /// Whether the vote account is delinquent
     #[serde(rename = "isDelinquent")]
     pub is_delinquent: bool,
+    /// Gossip wallclock (ms)
     pub wallclock: u64,
     /// ISO 3166 country code (e.g. "US", "DE")
     #[serde(rename = "countryCode", default)]
@@ -117,26 +151,36 @@ pub struct GossipPeer {
 /// Gossip snapshot data.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GossipSnapshotData {
+    /// Snapshot timestamp (ms)
     pub timestamp: u64,
+    /// Number of peers
     pub count: usize,
+    /// All known peers
     pub peers: Vec<GossipPeer>,
 }
 
 /// Gossip diff data.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GossipDiffData {
+    /// Unix timestamp in milliseconds
     #[serde(rename = "timestampMs")]
     pub timestamp_ms: u64,
+    /// Newly discovered peers
     pub added: Vec<GossipPeer>,
+    /// Identities of peers that left gossip
     pub removed: Vec<String>,
+    /// Peers whose contact info changed
     pub updated: Vec<GossipPeer>,
 }
 
 /// Slot update data.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SlotUpdateData {
+    /// Solana slot
     pub slot: u64,
+    /// Leader identity for the slot
     pub leader: String,
+    /// Block height
     #[serde(rename = "blockHeight")]
     pub block_height: u64,
 }
@@ -144,36 +188,50 @@ pub struct SlotUpdateData {
 /// Routing health data.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RoutingHealthData {
+    /// Number of upcoming leaders considered
     #[serde(rename = "leadersTotal")]
     pub leaders_total: u32,
+    /// Upcoming leaders visible in gossip
     #[serde(rename = "leadersInGossip")]
     pub leaders_in_gossip: u32,
+    /// Upcoming leaders missing from gossip
     #[serde(rename = "leadersMissingGossip")]
     pub leaders_missing_gossip: Vec<String>,
+    /// Upcoming leaders without a TPU QUIC address
     #[serde(rename = "leadersWithoutTpuQuic")]
     pub leaders_without_tpu_quic: Vec<String>,
+    /// Upcoming leaders that are delinquent
     #[serde(rename = "leadersDelinquent")]
     pub leaders_delinquent: Vec<String>,
+    /// Coverage summary (e.g. "98.5%")
     pub coverage: String,
 }
 
 /// Skip event data.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct SkipEventData {
+    /// Skipped slot
     pub slot: u64,
+    /// Leader identity
     pub leader: String,
+    /// Slots assigned to the leader
     pub assigned: u32,
+    /// Slots produced by the leader
     pub produced: u32,
 }
 
 /// IP change data.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct IpChangeData {
+    /// Validator identity
     pub identity: String,
+    /// Previous IP address
     #[serde(rename = "oldIp")]
     pub old_ip: String,
+    /// New IP address
     #[serde(rename = "newIp")]
     pub new_ip: String,
+    /// Unix timestamp in milliseconds
     #[serde(rename = "timestampMs")]
     pub timestamp_ms: u64,
 }
@@ -181,12 +239,16 @@ pub struct IpChangeData {
 /// Leader heartbeat data.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LeaderHeartbeatData {
+    /// Unix timestamp in milliseconds
     #[serde(rename = "timestampMs")]
     pub timestamp_ms: u64,
+    /// Current Solana slot
     #[serde(rename = "currentSlot")]
     pub current_slot: u64,
+    /// Connected clients on the server
     #[serde(rename = "connectedClients")]
     pub connected_clients: u32,
+    /// Number of gossip peers known to the server
     #[serde(rename = "gossipPeers")]
     pub gossip_peers: u32,
 }
@@ -194,8 +256,11 @@ pub struct LeaderHeartbeatData {
 /// Leader schedule validator entry.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LeaderScheduleValidator {
+    /// Validator identity
     pub identity: String,
+    /// Number of leader slots in the epoch
     pub slots: usize,
+    /// Slot indices relative to the epoch start
     #[serde(rename = "slotIndices")]
     pub slot_indices: Vec<u32>,
 }
@@ -203,9 +268,24 @@ pub struct LeaderScheduleValidator {
 /// Leader schedule data.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LeaderScheduleData {
+    /// Epoch number
     pub epoch: u64,
+    /// Number of slots in the epoch
     #[serde(rename = "slotsInEpoch")]
     pub slots_in_epoch: u64,
+    /// Number of validators in the schedule
     pub validators: usize,
+    /// Per-validator leader slots
     pub schedule: Vec<LeaderScheduleValidator>,
 }
+
+/// The current slot crossed into a new epoch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EpochRollover {
+    /// Epoch of the previous schedule
+    pub old: u64,
+    /// Epoch the current slot is in now
+    pub new: u64,
+    /// Whether the new epoch's schedule had already arrived
+    pub schedule_ready: bool,
+}
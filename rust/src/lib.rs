@@ -10,13 +10,13 @@
 //! use k256_sdk::{K256WebSocketClient, Config};
 //!
 //! #[tokio::main]
-//! async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
 //!     let client = K256WebSocketClient::new(Config {
 //!         api_key: std::env::var("K256_API_KEY")?,
 //!         ..Default::default()
 //!     });
 //!
-//!     client.on_pool_update(|update| {
+//!     let _updates = client.on_pool_update(|update| {
 //!         println!("Pool {}: slot={}", update.pool_address, update.slot);
 //!     });
 //!
@@ -35,18 +35,84 @@
 //! ## Modules
 //!
 //! - [`ws`] - WebSocket client and binary decoder
+//! - [`proto`] - `no_std` wire format parsing the decoder is built on (the `k256-proto` crate)
+//! - [`error`] - SDK error types
+//! - [`connector`] - TLS, proxy and DNS options
 //! - [`types`] - Core type definitions
-//! - [`utils`] - Utility functions (base58, pubkey validation)
+//! - [`utils`] - Utility functions (base58, pubkey validation, amount formatting, compute budget instructions)
+//! - [`replay`] - Frame recording and replay for backtesting
+//! - [`transport`] - Pluggable WebSocket transport
+//! - `pool_state` - Typed decoders for `PoolUpdate::serialized_state` (feature `pool-state`)
+//! - `blocking` - Synchronous client wrapper (feature `blocking`)
+//! - `api` - REST API client and token metadata registry (feature `api`)
+//! - `testing` - In-process mock gateway and frame builders (feature `testing`)
+//! - `bridge` - Republish decoded messages to Redis streams or other buses (feature `bridge`)
+//! - `unified` - Liquidity and leader feeds under one client (feature `tungstenite`)
+//! - `sender` - Transaction submission to upcoming leaders' TPUs and RPC endpoints (feature `sender`)
+//! - `ffi` - C ABI for Python/C/C++ bindings (feature `ffi`)
 
 #![cfg_attr(docsrs, feature(doc_cfg))]
 #![warn(missing_docs)]
 #![warn(rustdoc::missing_crate_level_docs)]
 
+#[cfg(not(target_arch = "wasm32"))]
+pub mod connector;
+pub mod error;
 pub mod types;
 pub mod utils;
 pub mod ws;
 pub mod leader_ws;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod replay;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod transport;
+#[cfg(feature = "pool-state")]
+#[cfg_attr(docsrs, doc(cfg(feature = "pool-state")))]
+pub mod pool_state;
+#[cfg(all(feature = "blocking", not(target_arch = "wasm32")))]
+#[cfg_attr(docsrs, doc(cfg(feature = "blocking")))]
+pub mod blocking;
+#[cfg(all(feature = "api", not(target_arch = "wasm32")))]
+#[cfg_attr(docsrs, doc(cfg(feature = "api")))]
+pub mod api;
+#[cfg(all(feature = "testing", not(target_arch = "wasm32")))]
+#[cfg_attr(docsrs, doc(cfg(feature = "testing")))]
+pub mod testing;
+#[cfg(all(feature = "tungstenite", not(target_arch = "wasm32")))]
+#[cfg_attr(docsrs, doc(cfg(feature = "tungstenite")))]
+pub mod unified;
+#[cfg(all(feature = "bridge", not(target_arch = "wasm32")))]
+#[cfg_attr(docsrs, doc(cfg(feature = "bridge")))]
+pub mod bridge;
+#[cfg(all(feature = "sender", not(target_arch = "wasm32")))]
+#[cfg_attr(docsrs, doc(cfg(feature = "sender")))]
+pub mod sender;
+#[cfg(all(feature = "ffi", not(target_arch = "wasm32")))]
+#[cfg_attr(docsrs, doc(cfg(feature = "ffi")))]
+pub mod ffi;
 
 // Re-exports
+pub use k256_proto as proto;
+pub use error::K256Error;
 pub use types::*;
-pub use ws::{K256WebSocketClient, Config, SubscribeRequest};
+pub use ws::{Channel, DecodedMessage, SubscribeRequest};
+#[cfg(not(target_arch = "wasm32"))]
+pub use ws::{K256WebSocketClient, Config};
+#[cfg(all(feature = "tungstenite", not(target_arch = "wasm32")))]
+pub use unified::{K256Client, K256ClientConfig, K256Event};
+
+#[cfg(all(
+    target_arch = "wasm32",
+    any(
+        feature = "tungstenite",
+        feature = "blocking",
+        feature = "api",
+        feature = "testing",
+        feature = "bridge",
+        feature = "sender",
+        feature = "ffi"
+    )
+))]
+compile_error!(
+    "the `tungstenite`, `blocking`, `api`, `testing`, `bridge`, `sender` and `ffi` features are not available on wasm32"
+);
@@ -0,0 +1,73 @@
+//! Meteora DLMM pool state layout.
+
+use super::{AccountReader, PoolStateError};
+use crate::types::Pubkey;
+
+/// Meteora DLMM `LbPair` account.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MeteoraDlmmState {
+    /// Base fee factor
+    pub base_factor: u16,
+    /// Variable fee control parameter
+    pub variable_fee_control: u32,
+    /// Maximum volatility accumulator
+    pub max_volatility_accumulator: u32,
+    /// Lowest bin id of the pair
+    pub min_bin_id: i32,
+    /// Highest bin id of the pair
+    pub max_bin_id: i32,
+    /// Share of the fee taken by the protocol, in basis points
+    pub protocol_share: u16,
+    /// Current volatility accumulator
+    pub volatility_accumulator: u32,
+    /// Last parameter update (unix seconds)
+    pub last_update_timestamp: i64,
+    /// Pair type
+    pub pair_type: u8,
+    /// Currently active bin id
+    pub active_id: i32,
+    /// Bin step in basis points
+    pub bin_step: u16,
+    /// Pair status
+    pub status: u8,
+    /// Token X mint
+    pub token_x_mint: Pubkey,
+    /// Token Y mint
+    pub token_y_mint: Pubkey,
+    /// Token X reserve account
+    pub reserve_x: Pubkey,
+    /// Token Y reserve account
+    pub reserve_y: Pubkey,
+    /// Protocol fees owed in token X
+    pub protocol_fee_x: u64,
+    /// Protocol fees owed in token Y
+    pub protocol_fee_y: u64,
+}
+
+const DLMM_DISCRIMINATOR: [u8; 8] = [33, 11, 49, 98, 181, 101, 177, 13];
+const DLMM_MIN_LEN: usize = 232;
+
+pub(super) fn decode_dlmm(data: &[u8]) -> Result<MeteoraDlmmState, PoolStateError> {
+    let r = AccountReader::anchor(data, DLMM_MIN_LEN, DLMM_DISCRIMINATOR, "MeteoraDlmm")?;
+
+    Ok(MeteoraDlmmState {
+        base_factor: r.u16(8),
+        variable_fee_control: r.u32(16),
+        max_volatility_accumulator: r.u32(20),
+        min_bin_id: r.i32(24),
+        max_bin_id: r.i32(28),
+        protocol_share: r.u16(32),
+        volatility_accumulator: r.u32(40),
+        last_update_timestamp: r.i64(56),
+        pair_type: r.u8(75),
+        active_id: r.i32(76),
+        bin_step: r.u16(80),
+        status: r.u8(82),
+        token_x_mint: r.pubkey(88),
+        token_y_mint: r.pubkey(120),
+        reserve_x: r.pubkey(152),
+        reserve_y: r.pubkey(184),
+        protocol_fee_x: r.u64(216),
+        protocol_fee_y: r.u64(224),
+    })
+}
@@ -0,0 +1,348 @@
+//! Typed decoders for [`PoolUpdate::serialized_state`].
+//!
+//! `serialized_state` carries the raw on-chain account data of the pool.
+//! The decoders in this module parse the fields of the major protocols
+//! streamed by K256 into typed structs, selected by `protocol_name`.
+//!
+//! Enabled with the `pool-state` feature.
+//!
+//! ```rust,no_run
+//! # use k256_sdk::PoolUpdate;
+//! # use k256_sdk::pool_state::PoolState;
+//! # fn handle(update: &PoolUpdate) {
+//! match update.decode_state() {
+//!     Ok(PoolState::Whirlpool(pool)) => println!("sqrt_price={}", pool.sqrt_price),
+//!     Ok(other) => println!("{:?}", other),
+//!     Err(e) => eprintln!("{}", e),
+//! }
+//! # }
+//! ```
+
+mod meteora;
+mod phoenix;
+mod raydium;
+mod swap;
+mod whirlpool;
+
+pub use meteora::MeteoraDlmmState;
+pub use phoenix::PhoenixMarketState;
+pub use raydium::{RaydiumAmmState, RaydiumClmmState};
+pub use swap::{simulate_swap, SimResult};
+pub use whirlpool::WhirlpoolState;
+
+use thiserror::Error;
+
+use crate::types::{PoolUpdate, Protocol, Pubkey};
+
+/// Pool state decoder error types.
+#[derive(Debug, Error)]
+pub enum PoolStateError {
+    /// No decoder exists for the protocol
+    #[error("Unsupported protocol: {0}")]
+    UnsupportedProtocol(String),
+
+    /// Account data is too short for the protocol layout
+    #[error("Pool state too short: expected {expected}, got {actual}")]
+    TooShort {
+        /// Minimum number of bytes required
+        expected: usize,
+        /// Number of bytes available
+        actual: usize,
+    },
+
+    /// Anchor account discriminator does not match the protocol
+    #[error("Invalid account discriminator for {0}")]
+    InvalidDiscriminator(&'static str),
+
+    /// The pool does not trade the given mint
+    #[error("Mint {0} is not traded by the pool")]
+    MintNotInPool(Pubkey),
+
+    /// The pool has no liquidity to swap against
+    #[error("Pool has no liquidity")]
+    NoLiquidity,
+}
+
+/// Decoded pool state, one variant per supported protocol.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum PoolState {
+    /// Raydium AMM v4 (`"RaydiumAmm"`)
+    RaydiumAmm(RaydiumAmmState),
+    /// Raydium concentrated liquidity (`"RaydiumClmm"`)
+    RaydiumClmm(RaydiumClmmState),
+    /// Orca Whirlpool (`"Whirlpool"`)
+    Whirlpool(WhirlpoolState),
+    /// Meteora DLMM (`"MeteoraDlmm"`)
+    MeteoraDlmm(MeteoraDlmmState),
+    /// Phoenix order book market header (`"Phoenix"`)
+    Phoenix(PhoenixMarketState),
+}
+
+/// Decode raw pool account data for the given protocol name.
+///
+/// # Arguments
+///
+/// * `protocol_name` - DEX protocol name as sent in `PoolUpdate::protocol_name`
+/// * `data` - Raw account data
+pub fn decode_pool_state(protocol_name: &str, data: &[u8]) -> Result<PoolState, PoolStateError> {
+    decode_protocol_state(&Protocol::from(protocol_name), data)
+}
+
+fn decode_protocol_state(protocol: &Protocol, data: &[u8]) -> Result<PoolState, PoolStateError> {
+    match protocol {
+        Protocol::RaydiumAmm => raydium::decode_amm(data).map(PoolState::RaydiumAmm),
+        Protocol::RaydiumClmm => raydium::decode_clmm(data).map(PoolState::RaydiumClmm),
+        Protocol::Whirlpool => whirlpool::decode(data).map(PoolState::Whirlpool),
+        Protocol::MeteoraDlmm => meteora::decode_dlmm(data).map(PoolState::MeteoraDlmm),
+        Protocol::Phoenix => phoenix::decode_market(data).map(PoolState::Phoenix),
+        other => Err(PoolStateError::UnsupportedProtocol(other.to_string())),
+    }
+}
+
+impl PoolUpdate {
+    /// Decode `serialized_state` into a typed [`PoolState`] based on `protocol_name`.
+    pub fn decode_state(&self) -> Result<PoolState, PoolStateError> {
+        decode_protocol_state(&self.protocol_name, &self.serialized_state)
+    }
+}
+
+/// Spot price of one whole `base_mint` token in whole `quote_mint` tokens
+/// from the decoded state of a concentrated liquidity pool.
+///
+/// The pool price (token B atoms per token A atom) comes from `sqrt_price`
+/// for Whirlpool and Raydium CLMM, and from the active bin for Meteora DLMM,
+/// then is scaled by the token decimals of the update.
+pub(crate) fn spot_price(update: &PoolUpdate, base_mint: &Pubkey, quote_mint: &Pubkey) -> Option<f64> {
+    let (mint_a, mint_b, atoms_price) = match update.decode_state().ok()? {
+        PoolState::Whirlpool(pool) => (pool.token_mint_a, pool.token_mint_b, sqrt_price_x64(pool.sqrt_price)),
+        PoolState::RaydiumClmm(pool) => (pool.token_mint_0, pool.token_mint_1, sqrt_price_x64(pool.sqrt_price_x64)),
+        PoolState::MeteoraDlmm(pool) => {
+            let price = (1.0 + pool.bin_step as f64 / 10_000.0).powi(pool.active_id);
+            (pool.token_x_mint, pool.token_y_mint, price)
+        }
+        _ => return None,
+    };
+    let price = atoms_price * 10f64.powi(update.decimals_of(&mint_a)? - update.decimals_of(&mint_b)?);
+    if !price.is_finite() || price <= 0.0 {
+        return None;
+    }
+    if (*base_mint, *quote_mint) == (mint_a, mint_b) {
+        Some(price)
+    } else if (*base_mint, *quote_mint) == (mint_b, mint_a) {
+        Some(1.0 / price)
+    } else {
+        None
+    }
+}
+
+/// Price from a Q64.64 square root price.
+fn sqrt_price_x64(sqrt_price: u128) -> f64 {
+    let sqrt = sqrt_price as f64 / (1u128 << 64) as f64;
+    sqrt * sqrt
+}
+
+/// Fixed-offset reader over account data whose length was checked up front.
+struct AccountReader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> AccountReader<'a> {
+    fn new(data: &'a [u8], min_len: usize) -> Result<Self, PoolStateError> {
+        if data.len() < min_len {
+            return Err(PoolStateError::TooShort {
+                expected: min_len,
+                actual: data.len(),
+            });
+        }
+        Ok(Self { data })
+    }
+
+    fn anchor(
+        data: &'a [u8],
+        min_len: usize,
+        discriminator: [u8; 8],
+        name: &'static str,
+    ) -> Result<Self, PoolStateError> {
+        let reader = Self::new(data, min_len)?;
+        if data[..8] != discriminator {
+            return Err(PoolStateError::InvalidDiscriminator(name));
+        }
+        Ok(reader)
+    }
+
+    fn bytes<const N: usize>(&self, offset: usize) -> [u8; N] {
+        self.data[offset..offset + N].try_into().unwrap()
+    }
+
+    fn pubkey(&self, offset: usize) -> Pubkey {
+        Pubkey::new(self.bytes(offset))
+    }
+
+    fn u8(&self, offset: usize) -> u8 {
+        self.data[offset]
+    }
+
+    fn u16(&self, offset: usize) -> u16 {
+        u16::from_le_bytes(self.bytes(offset))
+    }
+
+    fn u32(&self, offset: usize) -> u32 {
+        u32::from_le_bytes(self.bytes(offset))
+    }
+
+    fn i32(&self, offset: usize) -> i32 {
+        i32::from_le_bytes(self.bytes(offset))
+    }
+
+    fn u64(&self, offset: usize) -> u64 {
+        u64::from_le_bytes(self.bytes(offset))
+    }
+
+    fn i64(&self, offset: usize) -> i64 {
+        i64::from_le_bytes(self.bytes(offset))
+    }
+
+    fn u128(&self, offset: usize) -> u128 {
+        u128::from_le_bytes(self.bytes(offset))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn put(buf: &mut [u8], offset: usize, bytes: &[u8]) {
+        buf[offset..offset + bytes.len()].copy_from_slice(bytes);
+    }
+
+    #[test]
+    fn test_decode_whirlpool() {
+        let mut data = vec![0u8; 653];
+        put(&mut data, 0, &[63, 149, 209, 12, 225, 128, 99, 9]);
+        put(&mut data, 41, &64u16.to_le_bytes());
+        put(&mut data, 45, &3000u16.to_le_bytes());
+        put(&mut data, 49, &1_000_000u128.to_le_bytes());
+        put(&mut data, 65, &(1u128 << 64).to_le_bytes());
+        put(&mut data, 81, &(-5i32).to_le_bytes());
+        put(&mut data, 101, &[1u8; 32]);
+        put(&mut data, 181, &[2u8; 32]);
+
+        let state = decode_pool_state("Whirlpool", &data).unwrap();
+        let PoolState::Whirlpool(pool) = state else {
+            panic!("expected whirlpool state");
+        };
+        assert_eq!(pool.tick_spacing, 64);
+        assert_eq!(pool.fee_rate, 3000);
+        assert_eq!(pool.liquidity, 1_000_000);
+        assert_eq!(pool.sqrt_price, 1u128 << 64);
+        assert_eq!(pool.tick_current_index, -5);
+        assert_eq!(pool.token_mint_a, Pubkey::new([1u8; 32]));
+        assert_eq!(pool.token_mint_b, Pubkey::new([2u8; 32]));
+    }
+
+    #[test]
+    fn test_decode_raydium_amm() {
+        let mut data = vec![0u8; 752];
+        put(&mut data, 32, &9u64.to_le_bytes());
+        put(&mut data, 40, &6u64.to_le_bytes());
+        put(&mut data, 144, &25u64.to_le_bytes());
+        put(&mut data, 152, &10_000u64.to_le_bytes());
+        put(&mut data, 336, &[7u8; 32]);
+
+        let PoolState::RaydiumAmm(pool) = decode_pool_state("RaydiumAmm", &data).unwrap() else {
+            panic!("expected raydium amm state");
+        };
+        assert_eq!(pool.coin_decimals, 9);
+        assert_eq!(pool.pc_decimals, 6);
+        assert_eq!(pool.trade_fee_numerator, 25);
+        assert_eq!(pool.trade_fee_denominator, 10_000);
+        assert_eq!(pool.coin_vault, Pubkey::new([7u8; 32]));
+    }
+
+    #[test]
+    fn test_spot_price_from_state() {
+        let (sol, usdc) = (Pubkey::new([1u8; 32]), Pubkey::new([2u8; 32]));
+        let mut data = vec![0u8; 653];
+        put(&mut data, 0, &[63, 149, 209, 12, 225, 128, 99, 9]);
+        // sqrt(0.15) as Q64.64: 0.15 USDC atoms per lamport
+        put(&mut data, 65, &((0.15f64.sqrt() * 2f64.powi(64)) as u128).to_le_bytes());
+        put(&mut data, 101, sol.as_bytes());
+        put(&mut data, 181, usdc.as_bytes());
+        let update = PoolUpdate {
+            protocol_name: Protocol::Whirlpool,
+            token_mints: vec![usdc, sol],
+            // Reserves imply 1 SOL = 1 USDC and must be ignored
+            token_balances: vec![1_000_000, 1_000_000_000],
+            token_decimals: vec![6, 9],
+            serialized_state: data,
+            ..PoolUpdate::default()
+        };
+        let price = update.spot_price(&sol, &usdc).unwrap();
+        assert!((price - 150.0).abs() < 1e-6, "{}", price);
+        let inverse = update.spot_price(&usdc, &sol).unwrap();
+        assert!((inverse - 1.0 / 150.0).abs() < 1e-9, "{}", inverse);
+        assert_eq!(update.spot_price(&sol, &Pubkey::new([3u8; 32])), None);
+
+        // Meteora DLMM: 1.001^693 ≈ 2 Y atoms per X atom
+        let mut data = vec![0u8; 904];
+        put(&mut data, 0, &[33, 11, 49, 98, 181, 101, 177, 13]);
+        put(&mut data, 76, &693i32.to_le_bytes());
+        put(&mut data, 80, &10u16.to_le_bytes());
+        put(&mut data, 88, &[3u8; 32]);
+        put(&mut data, 120, &[4u8; 32]);
+        let dlmm = PoolUpdate {
+            protocol_name: Protocol::MeteoraDlmm,
+            token_mints: vec![Pubkey::new([3u8; 32]), Pubkey::new([4u8; 32])],
+            token_decimals: vec![6, 6],
+            serialized_state: data,
+            ..PoolUpdate::default()
+        };
+        let price = dlmm.spot_price(&Pubkey::new([3u8; 32]), &Pubkey::new([4u8; 32])).unwrap();
+        assert!((price - 2.0).abs() < 0.01, "{}", price);
+    }
+
+    #[test]
+    fn test_decode_phoenix() {
+        let mut data = vec![0u8; 576];
+        put(&mut data, 8, &1u64.to_le_bytes());
+        put(&mut data, 40, &9u32.to_le_bytes());
+        put(&mut data, 48, &[4u8; 32]);
+        put(&mut data, 112, &1_000u64.to_le_bytes());
+        put(&mut data, 120, &6u32.to_le_bytes());
+        put(&mut data, 128, &[5u8; 32]);
+        put(&mut data, 200, &10u64.to_le_bytes());
+        put(&mut data, 312, &1u32.to_le_bytes());
+
+        let PoolState::Phoenix(market) = decode_pool_state("Phoenix", &data).unwrap() else {
+            panic!("expected phoenix state");
+        };
+        assert_eq!(market.status, 1);
+        assert_eq!((market.base_decimals, market.quote_decimals), (9, 6));
+        assert_eq!(market.base_mint, Pubkey::new([4u8; 32]));
+        assert_eq!(market.quote_mint, Pubkey::new([5u8; 32]));
+        assert_eq!(market.base_lot_size, 1_000);
+        assert_eq!(market.tick_size_in_quote_atoms_per_base_unit, 10);
+        assert_eq!(market.raw_base_units_per_base_unit, 1);
+        assert!(matches!(
+            decode_pool_state("Phoenix", &data[..575]),
+            Err(PoolStateError::TooShort { expected: 576, .. })
+        ));
+    }
+
+    #[test]
+    fn test_decode_errors() {
+        assert!(matches!(
+            decode_pool_state("Unknown", &[]),
+            Err(PoolStateError::UnsupportedProtocol(_))
+        ));
+        assert!(matches!(
+            decode_pool_state("RaydiumClmm", &[0u8; 16]),
+            Err(PoolStateError::TooShort { .. })
+        ));
+        assert!(matches!(
+            decode_pool_state("MeteoraDlmm", &[0u8; 512]),
+            Err(PoolStateError::InvalidDiscriminator(_))
+        ));
+    }
+}
@@ -0,0 +1,77 @@
+//! Phoenix order book market header layout.
+
+use super::{AccountReader, PoolStateError};
+use crate::types::Pubkey;
+
+/// Phoenix `MarketHeader`: the first 576 bytes of a market account.
+///
+/// The order book and trader seats that follow the header are not decoded;
+/// the streamed best bid and ask are in [`PoolUpdate::best_bid`] and
+/// [`PoolUpdate::best_ask`].
+///
+/// [`PoolUpdate::best_bid`]: crate::PoolUpdate::best_bid
+/// [`PoolUpdate::best_ask`]: crate::PoolUpdate::best_ask
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PhoenixMarketState {
+    /// Market status (0 uninitialized, 1 active, 2 post-only, 3 paused,
+    /// 4 closed, 5 tombstoned)
+    pub status: u64,
+    /// Bid levels the order book can hold
+    pub bids_size: u64,
+    /// Ask levels the order book can hold
+    pub asks_size: u64,
+    /// Trader seats the market can hold
+    pub num_seats: u64,
+    /// Base token decimals
+    pub base_decimals: u32,
+    /// Base token mint
+    pub base_mint: Pubkey,
+    /// Base token vault
+    pub base_vault: Pubkey,
+    /// Base atoms per base lot
+    pub base_lot_size: u64,
+    /// Quote token decimals
+    pub quote_decimals: u32,
+    /// Quote token mint
+    pub quote_mint: Pubkey,
+    /// Quote token vault
+    pub quote_vault: Pubkey,
+    /// Quote atoms per quote lot
+    pub quote_lot_size: u64,
+    /// Tick size in quote atoms per base unit
+    pub tick_size_in_quote_atoms_per_base_unit: u64,
+    /// Market authority
+    pub authority: Pubkey,
+    /// Fee recipient
+    pub fee_recipient: Pubkey,
+    /// Sequence number of the market's last event
+    pub market_sequence_number: u64,
+    /// Raw base units per base unit
+    pub raw_base_units_per_base_unit: u32,
+}
+
+const MIN_LEN: usize = 576;
+
+pub(super) fn decode_market(data: &[u8]) -> Result<PhoenixMarketState, PoolStateError> {
+    let r = AccountReader::new(data, MIN_LEN)?;
+
+    Ok(PhoenixMarketState {
+        status: r.u64(8),
+        bids_size: r.u64(16),
+        asks_size: r.u64(24),
+        num_seats: r.u64(32),
+        base_decimals: r.u32(40),
+        base_mint: r.pubkey(48),
+        base_vault: r.pubkey(80),
+        base_lot_size: r.u64(112),
+        quote_decimals: r.u32(120),
+        quote_mint: r.pubkey(128),
+        quote_vault: r.pubkey(160),
+        quote_lot_size: r.u64(192),
+        tick_size_in_quote_atoms_per_base_unit: r.u64(200),
+        authority: r.pubkey(208),
+        fee_recipient: r.pubkey(240),
+        market_sequence_number: r.u64(272),
+        raw_base_units_per_base_unit: r.u32(312),
+    })
+}
@@ -0,0 +1,147 @@
+//! Raydium AMM v4 and CLMM pool state layouts.
+
+use super::{AccountReader, PoolStateError};
+use crate::types::Pubkey;
+
+/// Raydium AMM v4 `AmmInfo` account (752 bytes, no discriminator).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RaydiumAmmState {
+    /// Pool status
+    pub status: u64,
+    /// PDA nonce
+    pub nonce: u64,
+    /// Base (coin) token decimals
+    pub coin_decimals: u64,
+    /// Quote (pc) token decimals
+    pub pc_decimals: u64,
+    /// Trade fee numerator
+    pub trade_fee_numerator: u64,
+    /// Trade fee denominator
+    pub trade_fee_denominator: u64,
+    /// Swap fee numerator
+    pub swap_fee_numerator: u64,
+    /// Swap fee denominator
+    pub swap_fee_denominator: u64,
+    /// Base (coin) PnL not yet taken, excluded from reserves
+    pub need_take_pnl_coin: u64,
+    /// Quote (pc) PnL not yet taken, excluded from reserves
+    pub need_take_pnl_pc: u64,
+    /// Unix timestamp when the pool opens for trading
+    pub pool_open_time: u64,
+    /// Base (coin) token vault
+    pub coin_vault: Pubkey,
+    /// Quote (pc) token vault
+    pub pc_vault: Pubkey,
+    /// Base (coin) token mint
+    pub coin_mint: Pubkey,
+    /// Quote (pc) token mint
+    pub pc_mint: Pubkey,
+    /// LP token mint
+    pub lp_mint: Pubkey,
+    /// OpenBook open orders account
+    pub open_orders: Pubkey,
+    /// OpenBook market
+    pub market: Pubkey,
+    /// Target orders account
+    pub target_orders: Pubkey,
+    /// LP tokens outstanding
+    pub lp_reserve: u64,
+}
+
+/// Raydium CLMM `PoolState` account.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RaydiumClmmState {
+    /// AMM config account (holds the fee tier)
+    pub amm_config: Pubkey,
+    /// Pool owner
+    pub owner: Pubkey,
+    /// Token 0 mint
+    pub token_mint_0: Pubkey,
+    /// Token 1 mint
+    pub token_mint_1: Pubkey,
+    /// Token 0 vault
+    pub token_vault_0: Pubkey,
+    /// Token 1 vault
+    pub token_vault_1: Pubkey,
+    /// Observation account
+    pub observation_key: Pubkey,
+    /// Token 0 decimals
+    pub mint_decimals_0: u8,
+    /// Token 1 decimals
+    pub mint_decimals_1: u8,
+    /// Tick spacing
+    pub tick_spacing: u16,
+    /// Active liquidity
+    pub liquidity: u128,
+    /// Square root of the price as Q64.64
+    pub sqrt_price_x64: u128,
+    /// Current tick index
+    pub tick_current: i32,
+    /// Global fee growth of token 0 as Q64.64
+    pub fee_growth_global_0_x64: u128,
+    /// Global fee growth of token 1 as Q64.64
+    pub fee_growth_global_1_x64: u128,
+    /// Protocol fees owed in token 0
+    pub protocol_fees_token_0: u64,
+    /// Protocol fees owed in token 1
+    pub protocol_fees_token_1: u64,
+    /// Pool status bitmask
+    pub status: u8,
+}
+
+const AMM_V4_LEN: usize = 752;
+
+const CLMM_DISCRIMINATOR: [u8; 8] = [247, 237, 227, 245, 215, 195, 222, 70];
+const CLMM_MIN_LEN: usize = 390;
+
+pub(super) fn decode_amm(data: &[u8]) -> Result<RaydiumAmmState, PoolStateError> {
+    let r = AccountReader::new(data, AMM_V4_LEN)?;
+
+    Ok(RaydiumAmmState {
+        status: r.u64(0),
+        nonce: r.u64(8),
+        coin_decimals: r.u64(32),
+        pc_decimals: r.u64(40),
+        trade_fee_numerator: r.u64(144),
+        trade_fee_denominator: r.u64(152),
+        swap_fee_numerator: r.u64(176),
+        swap_fee_denominator: r.u64(184),
+        need_take_pnl_coin: r.u64(192),
+        need_take_pnl_pc: r.u64(200),
+        pool_open_time: r.u64(224),
+        coin_vault: r.pubkey(336),
+        pc_vault: r.pubkey(368),
+        coin_mint: r.pubkey(400),
+        pc_mint: r.pubkey(432),
+        lp_mint: r.pubkey(464),
+        open_orders: r.pubkey(496),
+        market: r.pubkey(528),
+        target_orders: r.pubkey(592),
+        lp_reserve: r.u64(720),
+    })
+}
+
+pub(super) fn decode_clmm(data: &[u8]) -> Result<RaydiumClmmState, PoolStateError> {
+    let r = AccountReader::anchor(data, CLMM_MIN_LEN, CLMM_DISCRIMINATOR, "RaydiumClmm")?;
+
+    Ok(RaydiumClmmState {
+        amm_config: r.pubkey(9),
+        owner: r.pubkey(41),
+        token_mint_0: r.pubkey(73),
+        token_mint_1: r.pubkey(105),
+        token_vault_0: r.pubkey(137),
+        token_vault_1: r.pubkey(169),
+        observation_key: r.pubkey(201),
+        mint_decimals_0: r.u8(233),
+        mint_decimals_1: r.u8(234),
+        tick_spacing: r.u16(235),
+        liquidity: r.u128(237),
+        sqrt_price_x64: r.u128(253),
+        tick_current: r.i32(269),
+        fee_growth_global_0_x64: r.u128(277),
+        fee_growth_global_1_x64: r.u128(293),
+        protocol_fees_token_0: r.u64(309),
+        protocol_fees_token_1: r.u64(317),
+        status: r.u8(389),
+    })
+}
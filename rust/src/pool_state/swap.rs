@@ -0,0 +1,236 @@
+//! Local swap simulation on streamed pool state.
+//!
+//! [`simulate_swap`] quotes an exact-in swap against the latest
+//! [`PoolUpdate`] without a round trip to the quote API:
+//!
+//! - Raydium AMM v4 uses the constant-product formula on the vault balances
+//!   (less untaken PnL), with the pool's trade fee, matching on-chain
+//!   integer rounding.
+//! - Orca Whirlpool uses the concentrated-liquidity formula on the active
+//!   liquidity. Tick arrays are not streamed, so the simulation assumes the
+//!   active liquidity holds for the whole swap; it is accurate for swaps that
+//!   stay within the current tick range and computed in floating point.
+
+use super::{PoolState, PoolStateError, RaydiumAmmState, WhirlpoolState};
+use crate::types::{PoolUpdate, Pubkey};
+
+/// Whirlpool fee rates are in hundredths of a basis point.
+const WHIRLPOOL_FEE_DENOMINATOR: u64 = 1_000_000;
+/// Q64.64 fixed-point scale of Whirlpool square-root prices.
+const Q64: f64 = 18_446_744_073_709_551_616.0;
+
+/// Result of a simulated exact-in swap.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SimResult {
+    /// Output amount in base units
+    pub out_amount: u64,
+    /// Shortfall of the execution price against the spot price (0-1), fees excluded
+    pub price_impact: f64,
+    /// Fee charged, in input token base units
+    pub fee: u64,
+}
+
+/// Simulate swapping `amount` of `input_mint` through the pool in `update`.
+///
+/// Supports Raydium AMM v4 and Orca Whirlpool pools; other protocols fail
+/// with [`PoolStateError::UnsupportedProtocol`].
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # use k256_sdk::PoolUpdate;
+/// use k256_sdk::pool_state::simulate_swap;
+/// # fn handle(update: &PoolUpdate) -> Result<(), k256_sdk::pool_state::PoolStateError> {
+/// let sim = simulate_swap(update, &update.token_mints[0], 1_000_000)?;
+/// println!("out={} impact={:.2}%", sim.out_amount, sim.price_impact * 100.0);
+/// # Ok(())
+/// # }
+/// ```
+pub fn simulate_swap(update: &PoolUpdate, input_mint: &Pubkey, amount: u64) -> Result<SimResult, PoolStateError> {
+    match update.decode_state()? {
+        PoolState::RaydiumAmm(pool) => simulate_raydium_amm(&pool, update, input_mint, amount),
+        PoolState::Whirlpool(pool) => simulate_whirlpool(&pool, input_mint, amount),
+        _ => Err(PoolStateError::UnsupportedProtocol(update.protocol_name.to_string())),
+    }
+}
+
+fn simulate_raydium_amm(
+    pool: &RaydiumAmmState,
+    update: &PoolUpdate,
+    input_mint: &Pubkey,
+    amount: u64,
+) -> Result<SimResult, PoolStateError> {
+    let coin = balance(update, &pool.coin_mint)?.saturating_sub(pool.need_take_pnl_coin);
+    let pc = balance(update, &pool.pc_mint)?.saturating_sub(pool.need_take_pnl_pc);
+    let (reserve_in, reserve_out) = if *input_mint == pool.coin_mint {
+        (coin, pc)
+    } else if *input_mint == pool.pc_mint {
+        (pc, coin)
+    } else {
+        return Err(PoolStateError::MintNotInPool(*input_mint));
+    };
+    if reserve_in == 0 || reserve_out == 0 {
+        return Err(PoolStateError::NoLiquidity);
+    }
+
+    let fee = match pool.trade_fee_denominator {
+        0 => 0,
+        denominator => (amount as u128 * pool.trade_fee_numerator as u128).div_ceil(denominator as u128) as u64,
+    };
+    let amount_in = amount.saturating_sub(fee) as u128;
+    let out_amount = (reserve_out as u128 * amount_in / (reserve_in as u128 + amount_in)) as u64;
+
+    let spot_out = amount_in as f64 * reserve_out as f64 / reserve_in as f64;
+    Ok(SimResult {
+        out_amount,
+        price_impact: impact(out_amount, spot_out),
+        fee,
+    })
+}
+
+fn simulate_whirlpool(pool: &WhirlpoolState, input_mint: &Pubkey, amount: u64) -> Result<SimResult, PoolStateError> {
+    let a_to_b = if *input_mint == pool.token_mint_a {
+        true
+    } else if *input_mint == pool.token_mint_b {
+        false
+    } else {
+        return Err(PoolStateError::MintNotInPool(*input_mint));
+    };
+    if pool.liquidity == 0 || pool.sqrt_price == 0 {
+        return Err(PoolStateError::NoLiquidity);
+    }
+
+    let fee = (amount as u128 * pool.fee_rate as u128).div_ceil(WHIRLPOOL_FEE_DENOMINATOR as u128) as u64;
+    let amount_in = amount.saturating_sub(fee) as f64;
+    let liquidity = pool.liquidity as f64;
+    let sqrt_price = pool.sqrt_price as f64 / Q64;
+
+    // Token A is x, token B is y: x = L / sqrt(P), y = L * sqrt(P)
+    let (out, spot_out) = if a_to_b {
+        let next = liquidity * sqrt_price / (liquidity + amount_in * sqrt_price);
+        (liquidity * (sqrt_price - next), amount_in * sqrt_price * sqrt_price)
+    } else {
+        let next = sqrt_price + amount_in / liquidity;
+        (liquidity * (1.0 / sqrt_price - 1.0 / next), amount_in / (sqrt_price * sqrt_price))
+    };
+    let out_amount = out.max(0.0).floor().min(u64::MAX as f64) as u64;
+    Ok(SimResult {
+        out_amount,
+        price_impact: impact(out_amount, spot_out),
+        fee,
+    })
+}
+
+/// Vault balance of `mint` in the update.
+fn balance(update: &PoolUpdate, mint: &Pubkey) -> Result<u64, PoolStateError> {
+    update
+        .token_mints
+        .iter()
+        .position(|m| m == mint)
+        .and_then(|i| update.token_balances.get(i).copied())
+        .ok_or(PoolStateError::MintNotInPool(*mint))
+}
+
+fn impact(out_amount: u64, spot_out: f64) -> f64 {
+    if spot_out <= 0.0 {
+        return 0.0;
+    }
+    (1.0 - out_amount as f64 / spot_out).clamp(0.0, 1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Protocol;
+
+    fn put(buf: &mut [u8], offset: usize, bytes: &[u8]) {
+        buf[offset..offset + bytes.len()].copy_from_slice(bytes);
+    }
+
+    fn raydium_update(coin_balance: u64, pc_balance: u64) -> PoolUpdate {
+        let mut data = vec![0u8; 752];
+        put(&mut data, 144, &25u64.to_le_bytes());
+        put(&mut data, 152, &10_000u64.to_le_bytes());
+        put(&mut data, 192, &1_000u64.to_le_bytes());
+        put(&mut data, 400, &[1u8; 32]);
+        put(&mut data, 432, &[2u8; 32]);
+        PoolUpdate {
+            protocol_name: Protocol::RaydiumAmm,
+            token_mints: vec![Pubkey::new([1; 32]), Pubkey::new([2; 32])],
+            token_balances: vec![coin_balance, pc_balance],
+            serialized_state: data,
+            ..Default::default()
+        }
+    }
+
+    fn whirlpool_update(liquidity: u128, sqrt_price: u128) -> PoolUpdate {
+        let mut data = vec![0u8; 261];
+        put(&mut data, 0, &[63, 149, 209, 12, 225, 128, 99, 9]);
+        put(&mut data, 45, &3000u16.to_le_bytes());
+        put(&mut data, 49, &liquidity.to_le_bytes());
+        put(&mut data, 65, &sqrt_price.to_le_bytes());
+        put(&mut data, 101, &[1u8; 32]);
+        put(&mut data, 181, &[2u8; 32]);
+        PoolUpdate {
+            protocol_name: Protocol::Whirlpool,
+            serialized_state: data,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_raydium_constant_product() {
+        // Coin reserve is 1_001_000 less 1_000 untaken PnL
+        let update = raydium_update(1_001_000, 2_000_000);
+        let sim = simulate_swap(&update, &Pubkey::new([1; 32]), 10_000).unwrap();
+        assert_eq!(sim.fee, 25);
+        // 2_000_000 * 9_975 / (1_000_000 + 9_975)
+        assert_eq!(sim.out_amount, 19_752);
+        assert!((sim.price_impact - 0.009925).abs() < 1e-5);
+
+        let back = simulate_swap(&update, &Pubkey::new([2; 32]), 20_000).unwrap();
+        assert_eq!(back.out_amount, 9_876);
+    }
+
+    #[test]
+    fn test_whirlpool_within_range() {
+        // sqrt_price of 1.0: price 1 token B per token A
+        let update = whirlpool_update(1_000_000_000_000, 1u128 << 64);
+
+        let sim = simulate_swap(&update, &Pubkey::new([1; 32]), 1_000_000).unwrap();
+        assert_eq!(sim.fee, 3_000);
+        // L * (1 - L / (L + dx)) for dx = 997_000
+        assert_eq!(sim.out_amount, 996_999);
+        assert!(sim.price_impact < 1e-5);
+
+        let big = simulate_swap(&update, &Pubkey::new([2; 32]), 1_000_000_000_000).unwrap();
+        assert!((big.price_impact - 0.5).abs() < 1e-2);
+    }
+
+    #[test]
+    fn test_errors() {
+        let update = raydium_update(1_000, 1_000);
+        assert!(matches!(
+            simulate_swap(&update, &Pubkey::new([9; 32]), 1),
+            Err(PoolStateError::MintNotInPool(_))
+        ));
+        assert!(matches!(
+            simulate_swap(&raydium_update(1_000, 0), &Pubkey::new([1; 32]), 1),
+            Err(PoolStateError::NoLiquidity)
+        ));
+
+        assert!(matches!(
+            simulate_swap(&whirlpool_update(0, 1u128 << 64), &Pubkey::new([1; 32]), 1),
+            Err(PoolStateError::NoLiquidity)
+        ));
+
+        let update = PoolUpdate {
+            protocol_name: Protocol::from("Unknown"),
+            ..Default::default()
+        };
+        assert!(matches!(
+            simulate_swap(&update, &Pubkey::new([1; 32]), 1),
+            Err(PoolStateError::UnsupportedProtocol(_))
+        ));
+    }
+}
@@ -0,0 +1,64 @@
+//! Orca Whirlpool pool state layout.
+
+use super::{AccountReader, PoolStateError};
+use crate::types::Pubkey;
+
+/// Orca `Whirlpool` account.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WhirlpoolState {
+    /// Whirlpools config account
+    pub whirlpools_config: Pubkey,
+    /// Tick spacing
+    pub tick_spacing: u16,
+    /// Fee rate in hundredths of a basis point (3000 = 0.3%)
+    pub fee_rate: u16,
+    /// Share of the fee taken by the protocol, in basis points
+    pub protocol_fee_rate: u16,
+    /// Active liquidity
+    pub liquidity: u128,
+    /// Square root of the price as Q64.64
+    pub sqrt_price: u128,
+    /// Current tick index
+    pub tick_current_index: i32,
+    /// Protocol fees owed in token A
+    pub protocol_fee_owed_a: u64,
+    /// Protocol fees owed in token B
+    pub protocol_fee_owed_b: u64,
+    /// Token A mint
+    pub token_mint_a: Pubkey,
+    /// Token A vault
+    pub token_vault_a: Pubkey,
+    /// Global fee growth of token A as Q64.64
+    pub fee_growth_global_a: u128,
+    /// Token B mint
+    pub token_mint_b: Pubkey,
+    /// Token B vault
+    pub token_vault_b: Pubkey,
+    /// Global fee growth of token B as Q64.64
+    pub fee_growth_global_b: u128,
+}
+
+const DISCRIMINATOR: [u8; 8] = [63, 149, 209, 12, 225, 128, 99, 9];
+const MIN_LEN: usize = 261;
+
+pub(super) fn decode(data: &[u8]) -> Result<WhirlpoolState, PoolStateError> {
+    let r = AccountReader::anchor(data, MIN_LEN, DISCRIMINATOR, "Whirlpool")?;
+
+    Ok(WhirlpoolState {
+        whirlpools_config: r.pubkey(8),
+        tick_spacing: r.u16(41),
+        fee_rate: r.u16(45),
+        protocol_fee_rate: r.u16(47),
+        liquidity: r.u128(49),
+        sqrt_price: r.u128(65),
+        tick_current_index: r.i32(81),
+        protocol_fee_owed_a: r.u64(85),
+        protocol_fee_owed_b: r.u64(93),
+        token_mint_a: r.pubkey(101),
+        token_vault_a: r.pubkey(133),
+        fee_growth_global_a: r.u128(165),
+        token_mint_b: r.pubkey(181),
+        token_vault_b: r.pubkey(213),
+        fee_growth_global_b: r.u128(245),
+    })
+}
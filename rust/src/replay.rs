@@ -0,0 +1,357 @@
+//! Recording and replay of raw WebSocket frames.
+//!
+//! A recording is a header followed by length-prefixed frames, each stamped
+//! with its receive time. Replaying feeds the frames back through the
+//! client's decoder, so the same callbacks fire as in the live session.
+//!
+//! ```text
+//! header: b"K256REC1"
+//! frame:  [u64 LE receive time, µs since UNIX epoch][u8 kind][u32 LE len][len bytes]
+//! ```
+//!
+//! # Example
+//! ```rust,no_run
+//! use std::fs::File;
+//! use k256_sdk::replay::{Replayer, Speed};
+//! use k256_sdk::{Config, K256WebSocketClient};
+//!
+//! # async fn run() -> std::io::Result<()> {
+//! // Record a live session
+//! let client = K256WebSocketClient::new(Config::default());
+//! client.record_to(File::create("session.k256rec")?)?;
+//!
+//! // Later: replay it at 10x speed through a fresh client's callbacks
+//! let replay_client = K256WebSocketClient::new(Config::default());
+//! replay_client.on_pool_update(|update| println!("{}", update.pool_address)).detach();
+//! Replayer::open("session.k256rec")?
+//!     .speed(Speed::Multiplier(10.0))
+//!     .replay(&replay_client)
+//!     .await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::ws::K256WebSocketClient;
+
+/// File magic identifying a K256 recording.
+pub const MAGIC: &[u8; 8] = b"K256REC1";
+
+/// WebSocket frame kind.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrameKind {
+    /// Binary frame
+    Binary,
+    /// Text (JSON) frame
+    Text,
+}
+
+impl FrameKind {
+    fn to_byte(self) -> u8 {
+        match self {
+            Self::Binary => 0,
+            Self::Text => 1,
+        }
+    }
+
+    fn from_byte(byte: u8) -> io::Result<Self> {
+        match byte {
+            0 => Ok(Self::Binary),
+            1 => Ok(Self::Text),
+            other => Err(invalid_data(format!("unknown frame kind {}", other))),
+        }
+    }
+}
+
+/// A recorded WebSocket frame.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+    /// Receive time in microseconds since the UNIX epoch
+    pub timestamp_us: u64,
+    /// Frame kind
+    pub kind: FrameKind,
+    /// Raw frame payload (including the message type byte for binary frames)
+    pub data: Vec<u8>,
+}
+
+/// An inbound frame as seen by [`K256WebSocketClient::on_raw_frame`],
+/// before decoding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RawFrame<'a> {
+    /// Receive time in microseconds since the UNIX epoch
+    pub timestamp_us: u64,
+    /// Frame kind
+    pub kind: FrameKind,
+    /// Raw frame payload (including the message type byte for binary frames)
+    pub data: &'a [u8],
+}
+
+impl RawFrame<'_> {
+    /// Message type byte of a binary frame.
+    pub fn msg_type(&self) -> Option<u8> {
+        match self.kind {
+            FrameKind::Binary => self.data.first().copied(),
+            FrameKind::Text => None,
+        }
+    }
+
+    /// Frame payload without the message type byte of binary frames.
+    pub fn payload(&self) -> &[u8] {
+        match self.kind {
+            FrameKind::Binary => self.data.get(1..).unwrap_or_default(),
+            FrameKind::Text => self.data,
+        }
+    }
+
+    /// Owned copy, e.g. for archiving.
+    pub fn to_frame(&self) -> Frame {
+        Frame {
+            timestamp_us: self.timestamp_us,
+            kind: self.kind,
+            data: self.data.to_vec(),
+        }
+    }
+}
+
+/// Writes frames to a recording.
+pub struct Recorder<W: Write> {
+    writer: W,
+}
+
+impl Recorder<BufWriter<File>> {
+    /// Create (or truncate) a recording file.
+    pub fn create(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::new(BufWriter::new(File::create(path)?))
+    }
+}
+
+impl<W: Write> Recorder<W> {
+    /// Start a recording on `writer`, writing the file header.
+    pub fn new(mut writer: W) -> io::Result<Self> {
+        writer.write_all(MAGIC)?;
+        Ok(Self { writer })
+    }
+
+    /// Append a frame received now.
+    pub fn record(&mut self, kind: FrameKind, data: &[u8]) -> io::Result<()> {
+        self.write_frame(now_us(), kind, data)
+    }
+
+    /// Append a frame with an explicit receive time.
+    pub fn write_frame(&mut self, timestamp_us: u64, kind: FrameKind, data: &[u8]) -> io::Result<()> {
+        let len = u32::try_from(data.len()).map_err(|_| invalid_data("frame too large"))?;
+        self.writer.write_all(&timestamp_us.to_le_bytes())?;
+        self.writer.write_all(&[kind.to_byte()])?;
+        self.writer.write_all(&len.to_le_bytes())?;
+        self.writer.write_all(data)
+    }
+
+    /// Flush buffered frames to the underlying writer.
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
+    /// Flush and return the underlying writer.
+    pub fn into_inner(mut self) -> io::Result<W> {
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+/// Reads frames from a recording.
+pub struct FrameReader<R: Read> {
+    reader: R,
+}
+
+impl<R: Read> FrameReader<R> {
+    /// Open a recording on `reader`, validating the file header.
+    pub fn new(mut reader: R) -> io::Result<Self> {
+        let mut magic = [0u8; 8];
+        reader.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(invalid_data("not a K256 recording"));
+        }
+        Ok(Self { reader })
+    }
+
+    /// Read the next frame, or `None` at end of file.
+    pub fn read_frame(&mut self) -> io::Result<Option<Frame>> {
+        let mut header = [0u8; 13];
+        match self.reader.read_exact(&mut header) {
+            Ok(()) => {}
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+            Err(e) => return Err(e),
+        }
+        let timestamp_us = u64::from_le_bytes(header[0..8].try_into().unwrap());
+        let kind = FrameKind::from_byte(header[8])?;
+        let len = u32::from_le_bytes(header[9..13].try_into().unwrap()) as usize;
+        let mut data = vec![0u8; len];
+        self.reader.read_exact(&mut data)?;
+        Ok(Some(Frame {
+            timestamp_us,
+            kind,
+            data,
+        }))
+    }
+}
+
+impl<R: Read> Iterator for FrameReader<R> {
+    type Item = io::Result<Frame>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_frame().transpose()
+    }
+}
+
+/// Replay pacing.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Speed {
+    /// Preserve the recorded inter-frame gaps
+    RealTime,
+    /// Divide the recorded gaps by the given factor
+    Multiplier(f64),
+    /// Replay as fast as possible
+    Max,
+}
+
+/// Replays a recording through a client's decoder and callbacks.
+pub struct Replayer<R: Read> {
+    frames: FrameReader<R>,
+    speed: Speed,
+}
+
+impl Replayer<BufReader<File>> {
+    /// Open a recording file.
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::new(BufReader::new(File::open(path)?))
+    }
+}
+
+impl<R: Read> Replayer<R> {
+    /// Replay a recording from `reader` at [`Speed::Max`].
+    pub fn new(reader: R) -> io::Result<Self> {
+        Ok(Self {
+            frames: FrameReader::new(reader)?,
+            speed: Speed::Max,
+        })
+    }
+
+    /// Set the replay pacing.
+    pub fn speed(mut self, speed: Speed) -> Self {
+        self.speed = speed;
+        self
+    }
+
+    /// Feed every frame to `client`, invoking its registered callbacks.
+    ///
+    /// # Returns
+    ///
+    /// Number of frames replayed
+    pub async fn replay(mut self, client: &K256WebSocketClient) -> io::Result<usize> {
+        let mut previous: Option<u64> = None;
+        let mut count = 0;
+        while let Some(frame) = self.frames.read_frame()? {
+            if let Some(gap) = previous.map(|prev| frame.timestamp_us.saturating_sub(prev)) {
+                if let Some(delay) = self.delay(gap) {
+                    tokio::time::sleep(delay).await;
+                }
+            }
+            previous = Some(frame.timestamp_us);
+            client.handle_frame(frame.kind, &frame.data).await;
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    fn delay(&self, gap_us: u64) -> Option<Duration> {
+        let gap = Duration::from_micros(gap_us);
+        match self.speed {
+            Speed::RealTime => Some(gap),
+            Speed::Multiplier(factor) if factor > 0.0 => Some(gap.div_f64(factor)),
+            Speed::Multiplier(_) | Speed::Max => None,
+        }
+        .filter(|delay| !delay.is_zero())
+    }
+}
+
+pub(crate) fn now_us() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_micros() as u64)
+        .unwrap_or(0)
+}
+
+fn invalid_data(msg: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::MessageType;
+    use crate::ws::Config;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    #[test]
+    fn test_frame_roundtrip() {
+        let mut recorder = Recorder::new(Vec::new()).unwrap();
+        recorder.write_frame(1, FrameKind::Binary, &[1, 2, 3]).unwrap();
+        recorder.write_frame(2, FrameKind::Text, b"{}").unwrap();
+        let bytes = recorder.into_inner().unwrap();
+
+        let frames: Vec<Frame> = FrameReader::new(bytes.as_slice())
+            .unwrap()
+            .collect::<io::Result<_>>()
+            .unwrap();
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[0].data, [1, 2, 3]);
+        assert_eq!(frames[1].kind, FrameKind::Text);
+        assert!(FrameReader::new(&b"nope0000"[..]).is_err());
+    }
+
+    #[test]
+    fn test_raw_frame_parts() {
+        let binary = RawFrame { timestamp_us: 5, kind: FrameKind::Binary, data: &[0x0D, 1, 2] };
+        assert_eq!(binary.msg_type(), Some(0x0D));
+        assert_eq!(binary.payload(), [1, 2]);
+        assert_eq!(binary.to_frame().data, [0x0D, 1, 2]);
+
+        let text = RawFrame { timestamp_us: 5, kind: FrameKind::Text, data: b"{}" };
+        assert_eq!(text.msg_type(), None);
+        assert_eq!(text.payload(), b"{}");
+        assert_eq!(RawFrame { data: &[], ..binary }.payload(), b"");
+    }
+
+    #[tokio::test]
+    async fn test_replay_invokes_callbacks() {
+        let mut recorder = Recorder::new(Vec::new()).unwrap();
+        let text = br#"{"type":"heartbeat","timestamp_ms":1,"uptime_seconds":2}"#;
+        recorder.write_frame(0, FrameKind::Text, text).unwrap();
+        recorder.write_frame(10, FrameKind::Text, text).unwrap();
+        recorder
+            .write_frame(20, FrameKind::Binary, &[MessageType::Heartbeat as u8])
+            .unwrap();
+        let bytes = recorder.into_inner().unwrap();
+
+        let client = K256WebSocketClient::new(Config::default());
+        let seen = Arc::new(AtomicUsize::new(0));
+        let counter = seen.clone();
+        let _heartbeats = client.on_heartbeat(move |_| {
+            counter.fetch_add(1, Ordering::SeqCst);
+        });
+
+        let replayed = Replayer::new(bytes.as_slice())
+            .unwrap()
+            .speed(Speed::RealTime)
+            .replay(&client)
+            .await
+            .unwrap();
+        assert_eq!(replayed, 3);
+        assert_eq!(seen.load(Ordering::SeqCst), 2);
+    }
+}
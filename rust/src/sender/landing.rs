@@ -0,0 +1,300 @@
+//! Landing status of submitted transactions.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use serde::Deserialize;
+
+use super::{RpcSender, SendError};
+use crate::leader_ws::{LeaderMessage, SlotUpdateData};
+use crate::types::{Blockhash, Slot};
+use crate::ws::RecentBlockhash;
+
+/// Slot update message type.
+const MSG_SLOT_UPDATE: &str = "slot_update";
+
+/// Where a tracked transaction stands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LandingStatus {
+    /// Neither landed nor expired yet
+    Pending,
+    /// Included in a block at `slot`
+    Landed {
+        /// Slot of the block
+        slot: Slot,
+    },
+    /// The block height passed the transaction's `last_valid_block_height`
+    /// before it was seen landing
+    Expired,
+    /// Neither outcome observed within the allowed time, or not tracked
+    Unknown,
+}
+
+/// A tracked transaction reaching a final status.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LandingEvent {
+    /// Transaction signature
+    pub signature: String,
+    /// Final status; never [`LandingStatus::Pending`]
+    pub status: LandingStatus,
+    /// Latest slot seen when tracking started
+    pub tracked_slot: Option<Slot>,
+    /// Time from tracking start to this event
+    pub elapsed: Duration,
+}
+
+impl LandingEvent {
+    /// Slots from tracking start to landing, for landed transactions whose
+    /// start slot is known.
+    pub fn slots_to_land(&self) -> Option<u64> {
+        match self.status {
+            LandingStatus::Landed { slot } => Some(slot.slots_since(self.tracked_slot?)),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Tracked {
+    last_valid_block_height: u64,
+    tracked_slot: Option<Slot>,
+    tracked_at: Instant,
+}
+
+/// Follows submitted transactions until they land or their blockhash
+/// expires.
+///
+/// Block heights come from leader `slot_update` messages ([`apply`]) or
+/// blockhash messages ([`apply_blockhash`]); landings from
+/// [`poll`](Self::poll) against an RPC node or from the caller via
+/// [`landed`](Self::landed). Each method returns the events of the
+/// transactions it resolved, which are then no longer tracked.
+///
+/// Expiry is reported as soon as the block height passes, so poll for
+/// landings before applying a new height to attribute late landings
+/// correctly.
+///
+/// ```rust,no_run
+/// use std::time::Duration;
+/// use k256_sdk::sender::{LandingTracker, RpcSender};
+/// # async fn example(rpc: RpcSender, signature: String, last_valid_block_height: u64)
+/// # -> Result<(), k256_sdk::sender::SendError> {
+/// let mut tracker = LandingTracker::new();
+/// tracker.track(signature, last_valid_block_height);
+///
+/// loop {
+///     // Apply slot updates from the leader feed with `tracker.apply(&msg)`
+///     for event in tracker.poll(&rpc).await? {
+///         println!("{} {:?} after {:?} slots", event.signature, event.status, event.slots_to_land());
+///     }
+///     if tracker.is_empty() {
+///         break;
+///     }
+///     tokio::time::sleep(Duration::from_secs(1)).await;
+/// }
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`apply`]: Self::apply
+/// [`apply_blockhash`]: Self::apply_blockhash
+#[derive(Debug, Default)]
+pub struct LandingTracker {
+    pending: HashMap<String, Tracked>,
+    slot: Option<Slot>,
+    block_height: Option<u64>,
+}
+
+impl LandingTracker {
+    /// Create an empty tracker.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Track `signature`, sent with a blockhash valid through
+    /// `last_valid_block_height`.
+    pub fn track(&mut self, signature: impl Into<String>, last_valid_block_height: u64) {
+        self.pending.insert(
+            signature.into(),
+            Tracked {
+                last_valid_block_height,
+                tracked_slot: self.slot,
+                tracked_at: Instant::now(),
+            },
+        );
+    }
+
+    /// Track `signature`, sent with `blockhash`.
+    pub fn track_blockhash(&mut self, signature: impl Into<String>, blockhash: &RecentBlockhash) {
+        self.track(signature, blockhash.last_valid_block_height);
+    }
+
+    /// Apply a leader WS message; `slot_update` advances the block height.
+    pub fn apply(&mut self, msg: &LeaderMessage) -> Result<Vec<LandingEvent>, serde_json::Error> {
+        if msg.msg_type != MSG_SLOT_UPDATE {
+            return Ok(Vec::new());
+        }
+        let update = SlotUpdateData::deserialize(&msg.data)?;
+        Ok(self.set_block_height(Slot(update.slot), update.block_height))
+    }
+
+    /// Apply a blockhash message, which carries the block height too.
+    pub fn apply_blockhash(&mut self, blockhash: &Blockhash) -> Vec<LandingEvent> {
+        self.set_block_height(blockhash.slot, blockhash.block_height)
+    }
+
+    /// Record the block height at `slot` and expire transactions whose
+    /// blockhash it passed. Older heights are ignored.
+    pub fn set_block_height(&mut self, slot: Slot, block_height: u64) -> Vec<LandingEvent> {
+        self.slot = self.slot.max(Some(slot));
+        if self.block_height.is_some_and(|height| height >= block_height) {
+            return Vec::new();
+        }
+        self.block_height = Some(block_height);
+        self.resolve_where(LandingStatus::Expired, |tracked| block_height > tracked.last_valid_block_height)
+    }
+
+    /// Report that `signature` landed at `slot`.
+    ///
+    /// Returns `None` for signatures that are not tracked.
+    pub fn landed(&mut self, signature: &str, slot: Slot) -> Option<LandingEvent> {
+        let (signature, tracked) = self.pending.remove_entry(signature)?;
+        Some(event(signature, tracked, LandingStatus::Landed { slot }))
+    }
+
+    /// Give up on transactions tracked for at least `max_age`, reporting
+    /// them as [`LandingStatus::Unknown`].
+    ///
+    /// Covers gaps in the block height feed, without which expiry is never
+    /// observed.
+    pub fn expire_unknown(&mut self, max_age: Duration) -> Vec<LandingEvent> {
+        self.resolve_where(LandingStatus::Unknown, |tracked| tracked.tracked_at.elapsed() >= max_age)
+    }
+
+    /// Ask `rpc` for the status of every pending transaction and resolve
+    /// the ones that landed.
+    pub async fn poll(&mut self, rpc: &RpcSender) -> Result<Vec<LandingEvent>, SendError> {
+        let signatures: Vec<String> = self.pending.keys().cloned().collect();
+        if signatures.is_empty() {
+            return Ok(Vec::new());
+        }
+        let refs: Vec<&str> = signatures.iter().map(String::as_str).collect();
+        let slots = rpc.signature_slots(&refs).await?;
+        Ok(signatures
+            .iter()
+            .zip(slots)
+            .filter_map(|(signature, slot)| self.landed(signature, slot?))
+            .collect())
+    }
+
+    /// Status of `signature`; [`LandingStatus::Unknown`] once resolved or if
+    /// never tracked.
+    pub fn status(&self, signature: &str) -> LandingStatus {
+        if self.pending.contains_key(signature) {
+            LandingStatus::Pending
+        } else {
+            LandingStatus::Unknown
+        }
+    }
+
+    /// Signatures still pending.
+    pub fn pending(&self) -> impl Iterator<Item = &str> {
+        self.pending.keys().map(String::as_str)
+    }
+
+    /// Number of pending transactions.
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Whether no transaction is pending.
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    /// Latest block height seen.
+    pub fn block_height(&self) -> Option<u64> {
+        self.block_height
+    }
+
+    fn resolve_where(&mut self, status: LandingStatus, resolve: impl Fn(&Tracked) -> bool) -> Vec<LandingEvent> {
+        let signatures: Vec<String> = self
+            .pending
+            .iter()
+            .filter(|(_, tracked)| resolve(tracked))
+            .map(|(signature, _)| signature.clone())
+            .collect();
+        signatures
+            .into_iter()
+            .filter_map(|signature| self.pending.remove_entry(&signature))
+            .map(|(signature, tracked)| event(signature, tracked, status))
+            .collect()
+    }
+}
+
+fn event(signature: String, tracked: Tracked, status: LandingStatus) -> LandingEvent {
+    LandingEvent {
+        signature,
+        status,
+        tracked_slot: tracked.tracked_slot,
+        elapsed: tracked.tracked_at.elapsed(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn slot_update(slot: u64, block_height: u64) -> LeaderMessage {
+        LeaderMessage {
+            msg_type: "slot_update".to_string(),
+            kind: None,
+            key: None,
+            data: json!({"slot": slot, "leader": "a", "blockHeight": block_height}),
+        }
+    }
+
+    #[test]
+    fn test_landed_and_expired() {
+        let mut tracker = LandingTracker::new();
+        tracker.apply(&slot_update(1_000, 900)).unwrap();
+        tracker.track("landed", 1_050);
+        tracker.track("dropped", 1_050);
+        tracker.track("later", 1_100);
+        assert_eq!(tracker.status("landed"), LandingStatus::Pending);
+
+        let event = tracker.landed("landed", Slot(1_004)).unwrap();
+        assert_eq!(event.status, LandingStatus::Landed { slot: Slot(1_004) });
+        assert_eq!(event.slots_to_land(), Some(4));
+        assert!(tracker.landed("landed", Slot(1_005)).is_none());
+        assert_eq!(tracker.status("landed"), LandingStatus::Unknown);
+
+        assert!(tracker.apply(&slot_update(1_150, 1_050)).unwrap().is_empty());
+        let expired = tracker.apply_blockhash(&Blockhash {
+            slot: Slot(1_152),
+            block_height: 1_051,
+            ..Blockhash::default()
+        });
+        assert_eq!(expired.len(), 1);
+        assert_eq!((expired[0].signature.as_str(), expired[0].status), ("dropped", LandingStatus::Expired));
+        assert_eq!(expired[0].slots_to_land(), None);
+
+        // Heights going backwards are ignored
+        assert!(tracker.set_block_height(Slot(1_300), 1_000).is_empty());
+        assert_eq!(tracker.block_height(), Some(1_051));
+        assert_eq!(tracker.pending().collect::<Vec<_>>(), ["later"]);
+    }
+
+    #[test]
+    fn test_expire_unknown() {
+        let mut tracker = LandingTracker::new();
+        tracker.track("sig", 100);
+        assert!(tracker.expire_unknown(Duration::from_secs(60)).is_empty());
+
+        let events = tracker.expire_unknown(Duration::ZERO);
+        assert_eq!(events[0].status, LandingStatus::Unknown);
+        assert_eq!(events[0].tracked_slot, None);
+        assert!(tracker.is_empty());
+    }
+}
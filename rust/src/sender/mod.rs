@@ -0,0 +1,361 @@
+//! Transaction submission to upcoming leaders and RPC nodes.
+//!
+//! [`TpuSender`] takes a signed, serialized transaction and sends it straight
+//! to the TPU ports of the next few leaders, resolved by a [`TpuResolver`]
+//! fed from the leader feed. [`RpcSender`] submits it through
+//! `sendTransaction` on one or more RPC endpoints instead, for hosts that
+//! cannot reach the leaders directly. [`Sender`] puts both behind one
+//! interface and picks the paths by [`SendStrategy`]. [`LandingTracker`]
+//! follows sent transactions until they land or expire.
+//!
+//! Retried sends go out every [`SenderConfig::retry_interval`] (re-resolving
+//! leaders each round, so the window follows the slot) until the caller
+//! reports the transaction landed or the current block height, taken from a
+//! [`BlockhashProvider`], passes the transaction's `last_valid_block_height`.
+//!
+//! Packets go out over UDP with [`UdpTransport`]. QUIC clients plug in by
+//! implementing [`TpuTransport`].
+//!
+//! # Example
+//! ```rust,no_run
+//! use std::sync::{Arc, RwLock};
+//! use k256_sdk::leader_ws::TpuResolver;
+//! use k256_sdk::sender::{RpcConfig, RpcSender, SendStrategy, Sender, TpuSender, UdpTransport};
+//! use k256_sdk::ws::BlockhashProvider;
+//!
+//! # async fn example(wire_transaction: Vec<u8>) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+//! let resolver = Arc::new(RwLock::new(TpuResolver::new()));
+//! let blockhash = BlockhashProvider::new();
+//! // Feed `resolver` from a `LeaderWebSocketClient` and `blockhash` from a
+//! // `K256WebSocketClient`, then sign a transaction with the cached hash:
+//! let recent = blockhash.latest().unwrap();
+//!
+//! let tpu = TpuSender::new(resolver, blockhash.clone(), UdpTransport::bind().await?);
+//! let rpc = RpcSender::new(RpcConfig {
+//!     endpoints: vec!["https://api.mainnet-beta.solana.com".to_string()],
+//!     ..RpcConfig::default()
+//! })?;
+//! let sender = Sender::new(blockhash).with_tpu(tpu).with_rpc(rpc).with_strategy(SendStrategy::Both);
+//!
+//! let outcome = sender.send(&wire_transaction, recent.last_valid_block_height).await?;
+//! println!("{:?}", outcome);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`TpuResolver`]: crate::leader_ws::TpuResolver
+
+mod landing;
+mod rpc;
+mod tpu;
+
+pub use landing::{LandingEvent, LandingStatus, LandingTracker};
+pub use rpc::{RpcConfig, RpcError, RpcSendResult, RpcSender};
+pub use tpu::{TpuSendResult, TpuSender, TpuTransport, UdpTransport};
+
+use std::future::Future;
+use std::time::Duration;
+
+use thiserror::Error;
+use tokio::time::MissedTickBehavior;
+use tracing::warn;
+
+use crate::ws::BlockhashProvider;
+
+/// Transaction submission errors.
+#[derive(Debug, Error)]
+pub enum SendError {
+    /// The resolver knows no reachable leader for the upcoming slots
+    #[error("No upcoming leader with a reachable TPU address")]
+    NoLeaders,
+
+    /// The RPC sender has no endpoints
+    #[error("No RPC endpoints configured")]
+    NoEndpoints,
+
+    /// The strategy uses a path the [`Sender`] was not given
+    #[error("Sender has no {0} path")]
+    Unconfigured(&'static str),
+
+    /// Every TPU send of a round failed
+    #[error("TPU send failed: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// Every RPC endpoint of a round failed
+    #[error("RPC send failed: {0}")]
+    Rpc(#[from] RpcError),
+}
+
+/// How a retried submission ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SendOutcome {
+    /// The caller reported the transaction landed
+    Landed {
+        /// Send rounds made
+        rounds: u32,
+    },
+    /// The block height passed the transaction's `last_valid_block_height`
+    Expired {
+        /// Send rounds made
+        rounds: u32,
+    },
+    /// [`SenderConfig::max_duration`] elapsed without the block height
+    /// confirming expiry, e.g. because no blockhash message arrived
+    TimedOut {
+        /// Send rounds made
+        rounds: u32,
+    },
+}
+
+impl SendOutcome {
+    /// Send rounds made.
+    pub fn rounds(&self) -> u32 {
+        match *self {
+            Self::Landed { rounds } | Self::Expired { rounds } | Self::TimedOut { rounds } => rounds,
+        }
+    }
+}
+
+/// Retry settings shared by [`TpuSender`] and [`Sender`].
+#[derive(Debug, Clone)]
+pub struct SenderConfig {
+    /// Distinct upcoming leaders to send to each round
+    pub leaders: usize,
+    /// Slots ahead of the current slot to look for leaders in
+    pub lookahead_slots: usize,
+    /// Time between send rounds
+    pub retry_interval: Duration,
+    /// Give up after this long even if the block height never confirms
+    /// expiry
+    pub max_duration: Duration,
+}
+
+impl Default for SenderConfig {
+    fn default() -> Self {
+        Self {
+            leaders: 4,
+            lookahead_slots: 32,
+            retry_interval: Duration::from_secs(2),
+            max_duration: Duration::from_secs(90),
+        }
+    }
+}
+
+/// Which paths [`Sender`] submits over.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SendStrategy {
+    /// Leaders' TPUs only
+    TpuOnly,
+    /// RPC endpoints only
+    RpcOnly,
+    /// TPUs and RPC endpoints at once; a round succeeds if either accepts
+    #[default]
+    Both,
+}
+
+/// Per-path results of one [`Sender`] round.
+///
+/// A path is `None` when the strategy did not use it.
+#[derive(Debug, Default)]
+pub struct SendReport {
+    /// Leader sends, or why none were made
+    pub tpu: Option<Result<Vec<TpuSendResult>, SendError>>,
+    /// RPC endpoint sends, or why none were made
+    pub rpc: Option<Result<Vec<RpcSendResult>, SendError>>,
+}
+
+impl SendReport {
+    /// Whether at least one leader or endpoint accepted the transaction.
+    pub fn accepted(&self) -> bool {
+        let tpu = self.tpu_results().any(|sent| sent.result.is_ok());
+        tpu || self.rpc_results().any(|sent| sent.result.is_ok())
+    }
+
+    /// Signature returned by the first RPC endpoint that accepted the
+    /// transaction.
+    pub fn signature(&self) -> Option<&str> {
+        self.rpc_results().find_map(|sent| sent.result.as_deref().ok())
+    }
+
+    fn tpu_results(&self) -> impl Iterator<Item = &TpuSendResult> {
+        self.tpu.iter().flat_map(|tpu| tpu.iter().flatten())
+    }
+
+    fn rpc_results(&self) -> impl Iterator<Item = &RpcSendResult> {
+        self.rpc.iter().flat_map(|rpc| rpc.iter().flatten())
+    }
+
+    /// First error of a round nothing accepted, TPU before RPC.
+    fn into_error(self) -> SendError {
+        let tpu = self.tpu.map(|tpu| tpu.and_then(tpu::round_result));
+        let rpc = self.rpc.map(|rpc| rpc.and_then(rpc::round_result));
+        match (tpu, rpc) {
+            (Some(Err(e)), _) | (_, Some(Err(e))) => e,
+            _ => SendError::NoLeaders,
+        }
+    }
+}
+
+/// Submits transactions over the TPU and RPC paths per [`SendStrategy`].
+///
+/// See the [module docs](self) for an example.
+pub struct Sender {
+    tpu: Option<TpuSender<Box<dyn TpuTransport>>>,
+    rpc: Option<RpcSender>,
+    blockhash: BlockhashProvider,
+    strategy: SendStrategy,
+    config: SenderConfig,
+}
+
+impl Sender {
+    /// Sender without paths, checking expiry against the block heights seen
+    /// by `blockhash`.
+    pub fn new(blockhash: BlockhashProvider) -> Self {
+        Self {
+            tpu: None,
+            rpc: None,
+            blockhash,
+            strategy: SendStrategy::default(),
+            config: SenderConfig::default(),
+        }
+    }
+
+    /// Submit to leaders through `tpu`.
+    pub fn with_tpu<T: TpuTransport>(mut self, tpu: TpuSender<T>) -> Self {
+        self.tpu = Some(tpu.boxed());
+        self
+    }
+
+    /// Submit to RPC endpoints through `rpc`.
+    pub fn with_rpc(mut self, rpc: RpcSender) -> Self {
+        self.rpc = Some(rpc);
+        self
+    }
+
+    /// Replace the default [`SendStrategy::Both`].
+    pub fn with_strategy(mut self, strategy: SendStrategy) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    /// Replace the default retry settings. Leader selection follows the
+    /// [`TpuSender`]'s own config.
+    pub fn with_config(mut self, config: SenderConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Current strategy.
+    pub fn strategy(&self) -> SendStrategy {
+        self.strategy
+    }
+
+    /// Send `wire_transaction` once over each path of the strategy.
+    ///
+    /// Fails with [`SendError::Unconfigured`] when the strategy needs a path
+    /// this sender was not given; per-path failures are in the report.
+    pub async fn send_once(&self, wire_transaction: &[u8]) -> Result<SendReport, SendError> {
+        let (use_tpu, use_rpc) = match self.strategy {
+            SendStrategy::TpuOnly => (true, false),
+            SendStrategy::RpcOnly => (false, true),
+            SendStrategy::Both => (true, true),
+        };
+        let tpu = match (use_tpu, &self.tpu) {
+            (true, None) => return Err(SendError::Unconfigured("TPU")),
+            (true, Some(tpu)) => Some(tpu),
+            (false, _) => None,
+        };
+        let rpc = match (use_rpc, &self.rpc) {
+            (true, None) => return Err(SendError::Unconfigured("RPC")),
+            (true, Some(rpc)) => Some(rpc),
+            (false, _) => None,
+        };
+
+        let tpu_round = async {
+            match tpu {
+                Some(tpu) => Some(tpu.send_once(wire_transaction).await),
+                None => None,
+            }
+        };
+        let rpc_round = async {
+            match rpc {
+                Some(rpc) => Some(rpc.send(wire_transaction).await),
+                None => None,
+            }
+        };
+        let (tpu, rpc) = tokio::join!(tpu_round, rpc_round);
+        Ok(SendReport { tpu, rpc })
+    }
+
+    /// Send `wire_transaction` every retry interval until its blockhash
+    /// expires. See [`TpuSender::send`].
+    pub async fn send(&self, wire_transaction: &[u8], last_valid_block_height: u64) -> Result<SendOutcome, SendError> {
+        self.send_until(wire_transaction, last_valid_block_height, std::future::pending::<()>())
+            .await
+    }
+
+    /// Send `wire_transaction` every retry interval until `landed` completes
+    /// or its blockhash expires. See [`TpuSender::send_until`].
+    pub async fn send_until<F: Future>(
+        &self,
+        wire_transaction: &[u8],
+        last_valid_block_height: u64,
+        landed: F,
+    ) -> Result<SendOutcome, SendError> {
+        retry_until(&self.config, &self.blockhash, last_valid_block_height, landed, || async {
+            let report = self.send_once(wire_transaction).await?;
+            if report.accepted() {
+                Ok(())
+            } else {
+                Err(report.into_error())
+            }
+        })
+        .await
+    }
+}
+
+/// Run `round` every retry interval until `landed` completes, the block
+/// height passes `last_valid_block_height` or `max_duration` elapses.
+///
+/// The first round fails fast; later failing rounds are logged and retried.
+async fn retry_until<F, R, Fut>(
+    config: &SenderConfig,
+    blockhash: &BlockhashProvider,
+    last_valid_block_height: u64,
+    landed: F,
+    mut round: R,
+) -> Result<SendOutcome, SendError>
+where
+    F: Future,
+    R: FnMut() -> Fut,
+    Fut: Future<Output = Result<(), SendError>>,
+{
+    let deadline = tokio::time::sleep(config.max_duration);
+    tokio::pin!(landed, deadline);
+    let mut interval = tokio::time::interval(config.retry_interval);
+    interval.set_missed_tick_behavior(MissedTickBehavior::Skip);
+
+    let mut rounds = 0;
+    loop {
+        tokio::select! {
+            biased;
+            _ = &mut landed => return Ok(SendOutcome::Landed { rounds }),
+            _ = &mut deadline => return Ok(SendOutcome::TimedOut { rounds }),
+            _ = interval.tick() => {
+                let expired = blockhash
+                    .latest()
+                    .is_some_and(|recent| recent.block_height > last_valid_block_height);
+                if expired {
+                    return Ok(SendOutcome::Expired { rounds });
+                }
+                let result = round().await;
+                rounds += 1;
+                match result {
+                    Ok(()) => {}
+                    Err(e) if rounds == 1 => return Err(e),
+                    Err(e) => warn!("Send round {} failed: {}", rounds, e),
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,381 @@
+//! `sendTransaction` and `getSignatureStatuses` over JSON-RPC.
+
+use std::time::Duration;
+
+use base64::Engine;
+use futures_util::future::join_all;
+use serde::Deserialize;
+use serde_json::json;
+use thiserror::Error;
+use tracing::debug;
+
+use super::SendError;
+use crate::types::Slot;
+
+/// Most signatures a `getSignatureStatuses` request may carry.
+const MAX_STATUS_SIGNATURES: usize = 256;
+
+/// JSON-RPC failures at one endpoint.
+#[derive(Debug, Error)]
+pub enum RpcError {
+    /// Request failed or the response body could not be parsed
+    #[error("HTTP error: {0}")]
+    Http(#[from] reqwest::Error),
+
+    /// Endpoint answered with a non-success status
+    #[error("HTTP status {status}: {body}")]
+    Status {
+        /// HTTP status code
+        status: u16,
+        /// Response body
+        body: String,
+    },
+
+    /// Endpoint returned an error, e.g. on a failed preflight
+    #[error("RPC error {code}: {message}")]
+    Rejected {
+        /// JSON-RPC error code
+        code: i64,
+        /// JSON-RPC error message
+        message: String,
+    },
+}
+
+/// Configuration for [`RpcSender`].
+#[derive(Debug, Clone)]
+pub struct RpcConfig {
+    /// JSON-RPC endpoint URLs; each send goes to all of them
+    pub endpoints: Vec<String>,
+    /// Per-request timeout
+    pub timeout: Duration,
+    /// Skip the node's preflight simulation
+    pub skip_preflight: bool,
+    /// Node-side rebroadcast attempts; `None` leaves it to the node.
+    /// Defaults to 0 as the sender does its own retrying.
+    pub max_retries: Option<usize>,
+}
+
+impl Default for RpcConfig {
+    fn default() -> Self {
+        Self {
+            endpoints: Vec::new(),
+            timeout: Duration::from_secs(10),
+            skip_preflight: true,
+            max_retries: Some(0),
+        }
+    }
+}
+
+/// Result of sending to one RPC endpoint.
+#[derive(Debug)]
+pub struct RpcSendResult {
+    /// Endpoint URL
+    pub endpoint: String,
+    /// Transaction signature returned by the endpoint
+    pub result: Result<String, RpcError>,
+}
+
+/// Submits signed transactions through `sendTransaction` on RPC endpoints.
+///
+/// Cheap to clone; clones share the connection pool.
+#[derive(Clone)]
+pub struct RpcSender {
+    config: RpcConfig,
+    http: reqwest::Client,
+}
+
+#[derive(Deserialize)]
+struct RpcResponse {
+    result: Option<serde_json::Value>,
+    error: Option<RpcErrorBody>,
+}
+
+/// `getSignatureStatuses` result.
+#[derive(Deserialize)]
+struct SignatureStatuses {
+    value: Vec<Option<SignatureStatus>>,
+}
+
+#[derive(Deserialize)]
+struct SignatureStatus {
+    slot: u64,
+}
+
+#[derive(Deserialize)]
+struct RpcErrorBody {
+    code: i64,
+    message: String,
+}
+
+impl RpcSender {
+    /// Create a sender.
+    pub fn new(config: RpcConfig) -> Result<Self, SendError> {
+        let http = reqwest::Client::builder()
+            .timeout(config.timeout)
+            .build()
+            .map_err(RpcError::from)?;
+        Ok(Self { config, http })
+    }
+
+    /// Configured endpoints.
+    pub fn endpoints(&self) -> &[String] {
+        &self.config.endpoints
+    }
+
+    /// Send `wire_transaction` once to every endpoint.
+    ///
+    /// Fails with [`SendError::NoEndpoints`] when none are configured;
+    /// per-endpoint errors are reported in the results.
+    pub async fn send(&self, wire_transaction: &[u8]) -> Result<Vec<RpcSendResult>, SendError> {
+        if self.config.endpoints.is_empty() {
+            return Err(SendError::NoEndpoints);
+        }
+        let body = self.request_body(wire_transaction);
+        let results = join_all(self.config.endpoints.iter().map(|endpoint| self.send_to(endpoint, &body))).await;
+        Ok(self
+            .config
+            .endpoints
+            .iter()
+            .zip(results)
+            .map(|(endpoint, result)| RpcSendResult {
+                endpoint: endpoint.clone(),
+                result,
+            })
+            .collect())
+    }
+
+    fn request_body(&self, wire_transaction: &[u8]) -> serde_json::Value {
+        let mut options = json!({
+            "encoding": "base64",
+            "skipPreflight": self.config.skip_preflight,
+        });
+        if let Some(max_retries) = self.config.max_retries {
+            options["maxRetries"] = json!(max_retries);
+        }
+        json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "sendTransaction",
+            "params": [base64::engine::general_purpose::STANDARD.encode(wire_transaction), options],
+        })
+    }
+
+    /// Slots the given transactions landed in, `None` for signatures the
+    /// node has not seen (yet), in request order.
+    ///
+    /// Asks the endpoints in order and returns the first answer; only recent
+    /// transactions are searched.
+    pub async fn signature_slots(&self, signatures: &[&str]) -> Result<Vec<Option<Slot>>, SendError> {
+        let mut slots = Vec::with_capacity(signatures.len());
+        for chunk in signatures.chunks(MAX_STATUS_SIGNATURES) {
+            let body = json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "getSignatureStatuses",
+                "params": [chunk],
+            });
+            let mut first_error = None;
+            let mut statuses = None;
+            for endpoint in &self.config.endpoints {
+                let result = self.call(endpoint, &body).await.and_then(|result| {
+                    SignatureStatuses::deserialize(result).map_err(|e| RpcError::Rejected {
+                        code: 0,
+                        message: format!("invalid getSignatureStatuses result: {}", e),
+                    })
+                });
+                match result {
+                    Ok(result) => {
+                        statuses = Some(result.value);
+                        break;
+                    }
+                    Err(e) => {
+                        debug!("getSignatureStatuses at {} failed: {}", endpoint, e);
+                        first_error.get_or_insert(e);
+                    }
+                }
+            }
+            let statuses = statuses.ok_or_else(|| first_error.map_or(SendError::NoEndpoints, SendError::Rpc))?;
+            slots.extend(statuses.into_iter().map(|status| status.map(|status| Slot(status.slot))));
+        }
+        Ok(slots)
+    }
+
+    async fn send_to(&self, endpoint: &str, body: &serde_json::Value) -> Result<String, RpcError> {
+        match self.call(endpoint, body).await? {
+            serde_json::Value::String(signature) => Ok(signature),
+            other => Err(RpcError::Rejected {
+                code: 0,
+                message: format!("unexpected sendTransaction result: {}", other),
+            }),
+        }
+    }
+
+    /// JSON-RPC call returning the `result` member.
+    async fn call(&self, endpoint: &str, body: &serde_json::Value) -> Result<serde_json::Value, RpcError> {
+        let response = self.http.post(endpoint).json(body).send().await?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(RpcError::Status {
+                status: status.as_u16(),
+                body: response.text().await.unwrap_or_default(),
+            });
+        }
+        let response: RpcResponse = response.json().await?;
+        match (response.result, response.error) {
+            (_, Some(error)) => Err(RpcError::Rejected {
+                code: error.code,
+                message: error.message,
+            }),
+            (Some(result), None) => Ok(result),
+            (None, None) => Err(RpcError::Rejected {
+                code: 0,
+                message: "response has neither result nor error".to_string(),
+            }),
+        }
+    }
+}
+
+/// `Ok` if any endpoint accepted the transaction, else the first error.
+pub(super) fn round_result(results: Vec<RpcSendResult>) -> Result<(), SendError> {
+    let mut first_error = None;
+    for sent in results {
+        match sent.result {
+            Ok(_) => return Ok(()),
+            Err(e) => {
+                debug!("RPC send to {} failed: {}", sent.endpoint, e);
+                first_error.get_or_insert(e);
+            }
+        }
+    }
+    Err(first_error.map_or(SendError::NoEndpoints, SendError::Rpc))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex, RwLock};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::{TcpListener, UdpSocket};
+
+    use crate::leader_ws::TpuResolver;
+    use crate::sender::{LandingStatus, LandingTracker, SendStrategy, Sender, TpuSender, UdpTransport};
+    use crate::ws::BlockhashProvider;
+
+    /// Serve JSON-RPC answering every request with `reply`, recording
+    /// request bodies.
+    async fn serve(reply: serde_json::Value, bodies: Arc<Mutex<Vec<serde_json::Value>>>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            loop {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut request = Vec::new();
+                let mut buf = vec![0u8; 4096];
+                // Read until the headers and the full body have arrived
+                let body = loop {
+                    let n = stream.read(&mut buf).await.unwrap();
+                    request.extend_from_slice(&buf[..n]);
+                    let text = String::from_utf8_lossy(&request).to_string();
+                    let Some((head, body)) = text.split_once("\r\n\r\n") else {
+                        continue;
+                    };
+                    let length = head
+                        .lines()
+                        .find_map(|line| line.to_ascii_lowercase().strip_prefix("content-length:").map(str::to_string))
+                        .and_then(|len| len.trim().parse::<usize>().ok())
+                        .unwrap_or(0);
+                    if body.len() >= length {
+                        break body.to_string();
+                    }
+                };
+                bodies.lock().unwrap().push(serde_json::from_str(&body).unwrap());
+                let reply = reply.to_string();
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                    reply.len(),
+                    reply
+                );
+                stream.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+        format!("http://{}", addr)
+    }
+
+    fn rpc_sender(endpoints: Vec<String>) -> RpcSender {
+        RpcSender::new(RpcConfig {
+            endpoints,
+            ..RpcConfig::default()
+        })
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_send_reports_each_endpoint() {
+        let bodies = Arc::new(Mutex::new(Vec::new()));
+        let ok = serve(json!({"jsonrpc": "2.0", "id": 1, "result": "sig"}), bodies.clone()).await;
+        let rejected = json!({"jsonrpc": "2.0", "id": 1, "error": {"code": -32002, "message": "preflight"}});
+        let rejected = serve(rejected, bodies.clone()).await;
+
+        let results = rpc_sender(vec![ok.clone(), rejected.clone()]).send(b"signed-tx").await.unwrap();
+        assert_eq!(results[0].endpoint, ok);
+        assert_eq!(results[0].result.as_deref().unwrap(), "sig");
+        assert!(matches!(results[1].result, Err(RpcError::Rejected { code: -32002, .. })));
+        assert!(round_result(results).is_ok());
+
+        let body = bodies.lock().unwrap()[0].clone();
+        assert_eq!(body["method"], "sendTransaction");
+        assert_eq!(body["params"][0], "c2lnbmVkLXR4");
+        assert_eq!(body["params"][1], json!({"encoding": "base64", "skipPreflight": true, "maxRetries": 0}));
+
+        assert!(matches!(rpc_sender(vec![]).send(b"tx").await, Err(SendError::NoEndpoints)));
+    }
+
+    #[tokio::test]
+    async fn test_signature_slots_feed_landing_tracker() {
+        let statuses = json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "result": {
+                "context": {"slot": 110},
+                "value": [{"slot": 105, "confirmations": 2, "err": null, "confirmationStatus": "confirmed"}],
+            },
+        });
+        let bodies = Arc::new(Mutex::new(Vec::new()));
+        let rpc = rpc_sender(vec![serve(statuses, bodies.clone()).await]);
+        assert_eq!(rpc.signature_slots(&["sig"]).await.unwrap(), [Some(Slot(105))]);
+        assert_eq!(bodies.lock().unwrap()[0]["params"], json!([["sig"]]));
+
+        let mut tracker = LandingTracker::new();
+        tracker.set_block_height(Slot(100), 90);
+        tracker.track("sig", 240);
+        let events = tracker.poll(&rpc).await.unwrap();
+        assert_eq!(events[0].status, LandingStatus::Landed { slot: Slot(105) });
+        assert_eq!(events[0].slots_to_land(), Some(5));
+        assert!(tracker.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_sender_strategies() {
+        let bodies = Arc::new(Mutex::new(Vec::new()));
+        let endpoint = serve(json!({"jsonrpc": "2.0", "id": 1, "result": "sig"}), bodies).await;
+        let blockhash = BlockhashProvider::new();
+        // No leaders known, so only the RPC path can accept
+        let resolver = Arc::new(RwLock::new(TpuResolver::new()));
+        let transport = UdpTransport::from_socket(UdpSocket::bind("127.0.0.1:0").await.unwrap());
+        let tpu = TpuSender::new(resolver, blockhash.clone(), transport);
+
+        let rpc_only = Sender::new(blockhash.clone()).with_strategy(SendStrategy::RpcOnly);
+        assert!(matches!(rpc_only.send_once(b"tx").await, Err(SendError::Unconfigured("RPC"))));
+
+        let sender = Sender::new(blockhash).with_tpu(tpu).with_rpc(rpc_sender(vec![endpoint]));
+        let report = sender.send_once(b"tx").await.unwrap();
+        assert!(matches!(report.tpu, Some(Err(SendError::NoLeaders))));
+        assert!(report.accepted());
+        assert_eq!(report.signature(), Some("sig"));
+
+        let sender = sender.with_strategy(SendStrategy::TpuOnly);
+        let report = sender.send_once(b"tx").await.unwrap();
+        assert!(report.rpc.is_none() && !report.accepted());
+        assert!(matches!(sender.send(b"tx", 150).await, Err(SendError::NoLeaders)));
+    }
+}
@@ -0,0 +1,306 @@
+//! Direct-to-leader TPU sender.
+
+use std::future::Future;
+use std::io;
+use std::sync::{Arc, RwLock};
+
+use futures_util::future::join_all;
+use tokio::net::{ToSocketAddrs, UdpSocket};
+use tracing::debug;
+
+use super::{retry_until, SendError, SendOutcome, SenderConfig};
+use crate::leader_ws::{TpuResolver, TpuTarget};
+use crate::transport::BoxFuture;
+use crate::ws::BlockhashProvider;
+
+/// Delivers a serialized transaction to one leader's TPU.
+pub trait TpuTransport: Send + Sync + 'static {
+    /// Send `wire_transaction` to `target`.
+    fn send<'a>(&'a self, target: &'a TpuTarget, wire_transaction: &'a [u8]) -> BoxFuture<'a, io::Result<()>>;
+}
+
+impl TpuTransport for Box<dyn TpuTransport> {
+    fn send<'a>(&'a self, target: &'a TpuTarget, wire_transaction: &'a [u8]) -> BoxFuture<'a, io::Result<()>> {
+        (**self).send(target, wire_transaction)
+    }
+}
+
+/// Sends transactions as single datagrams to the leaders' TPU UDP ports.
+#[derive(Debug)]
+pub struct UdpTransport {
+    socket: UdpSocket,
+}
+
+impl UdpTransport {
+    /// Bind to an ephemeral port on all interfaces.
+    pub async fn bind() -> io::Result<Self> {
+        Self::bind_to("0.0.0.0:0").await
+    }
+
+    /// Bind to `addr`.
+    pub async fn bind_to(addr: impl ToSocketAddrs) -> io::Result<Self> {
+        Ok(Self::from_socket(UdpSocket::bind(addr).await?))
+    }
+
+    /// Send from an already bound socket.
+    pub fn from_socket(socket: UdpSocket) -> Self {
+        Self { socket }
+    }
+}
+
+impl TpuTransport for UdpTransport {
+    fn send<'a>(&'a self, target: &'a TpuTarget, wire_transaction: &'a [u8]) -> BoxFuture<'a, io::Result<()>> {
+        Box::pin(async move {
+            let addr = target.tpu_udp.ok_or_else(|| {
+                io::Error::new(io::ErrorKind::NotFound, format!("{} has no TPU UDP address", target.identity))
+            })?;
+            self.socket.send_to(wire_transaction, addr).await.map(|_| ())
+        })
+    }
+}
+
+/// Result of sending to one leader.
+#[derive(Debug)]
+pub struct TpuSendResult {
+    /// Validator identity
+    pub identity: String,
+    /// First slot of the leader within the lookahead window
+    pub slot: u64,
+    /// Transport result
+    pub result: io::Result<()>,
+}
+
+/// Sends signed transactions to the TPUs of the next leaders.
+///
+/// See the [module docs](super) for an example.
+pub struct TpuSender<T> {
+    resolver: Arc<RwLock<TpuResolver>>,
+    blockhash: BlockhashProvider,
+    transport: T,
+    config: SenderConfig,
+}
+
+impl<T: TpuTransport> TpuSender<T> {
+    /// Sender routing with `resolver` and checking expiry against the block
+    /// heights seen by `blockhash`.
+    pub fn new(resolver: Arc<RwLock<TpuResolver>>, blockhash: BlockhashProvider, transport: T) -> Self {
+        Self {
+            resolver,
+            blockhash,
+            transport,
+            config: SenderConfig::default(),
+        }
+    }
+
+    /// Replace the default settings.
+    pub fn with_config(mut self, config: SenderConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Shared resolver; apply leader feed messages to it.
+    pub fn resolver(&self) -> &Arc<RwLock<TpuResolver>> {
+        &self.resolver
+    }
+
+    /// Leaders the next round would send to.
+    pub fn targets(&self) -> Vec<TpuTarget> {
+        let resolver = self.resolver.read().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let mut targets = resolver.resolve(self.config.lookahead_slots);
+        targets.truncate(self.config.leaders);
+        targets
+    }
+
+    /// Send `wire_transaction` once to each of the next leaders.
+    ///
+    /// Fails with [`SendError::NoLeaders`] when no leader is known; per-leader
+    /// transport errors are reported in the results.
+    pub async fn send_once(&self, wire_transaction: &[u8]) -> Result<Vec<TpuSendResult>, SendError> {
+        let targets = self.targets();
+        if targets.is_empty() {
+            return Err(SendError::NoLeaders);
+        }
+        let results = join_all(targets.iter().map(|target| self.transport.send(target, wire_transaction))).await;
+        Ok(targets
+            .into_iter()
+            .zip(results)
+            .map(|(target, result)| TpuSendResult {
+                identity: target.identity,
+                slot: target.slot,
+                result,
+            })
+            .collect())
+    }
+
+    /// Send `wire_transaction` every retry interval until its blockhash
+    /// expires.
+    ///
+    /// Without a landing signal this always ends in
+    /// [`SendOutcome::Expired`] or [`SendOutcome::TimedOut`]; use
+    /// [`send_until`](Self::send_until) to stop once it lands.
+    pub async fn send(&self, wire_transaction: &[u8], last_valid_block_height: u64) -> Result<SendOutcome, SendError> {
+        self.send_until(wire_transaction, last_valid_block_height, std::future::pending::<()>())
+            .await
+    }
+
+    /// Send `wire_transaction` every retry interval until `landed` completes
+    /// or its blockhash expires.
+    ///
+    /// The first round fails fast on [`SendError`]; later failing rounds are
+    /// logged and retried, since leaders and their sockets change over time.
+    pub async fn send_until<F: Future>(
+        &self,
+        wire_transaction: &[u8],
+        last_valid_block_height: u64,
+        landed: F,
+    ) -> Result<SendOutcome, SendError> {
+        retry_until(&self.config, &self.blockhash, last_valid_block_height, landed, || async {
+            round_result(self.send_once(wire_transaction).await?)
+        })
+        .await
+    }
+
+    /// Same sender with a type-erased transport.
+    pub(super) fn boxed(self) -> TpuSender<Box<dyn TpuTransport>> {
+        TpuSender {
+            resolver: self.resolver,
+            blockhash: self.blockhash,
+            transport: Box::new(self.transport),
+            config: self.config,
+        }
+    }
+}
+
+/// `Ok` if any leader accepted the packet, else the first transport error.
+pub(super) fn round_result(results: Vec<TpuSendResult>) -> Result<(), SendError> {
+    let mut first_error = None;
+    for sent in results {
+        match sent.result {
+            Ok(()) => return Ok(()),
+            Err(e) => {
+                debug!("TPU send to {} (slot {}) failed: {}", sent.identity, sent.slot, e);
+                first_error.get_or_insert(e);
+            }
+        }
+    }
+    Err(first_error.map_or(SendError::NoLeaders, SendError::Io))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::leader_ws::LeaderMessage;
+    use crate::types::Blockhash;
+    use serde_json::json;
+    use std::time::Duration;
+
+    fn message(msg_type: &str, data: serde_json::Value) -> LeaderMessage {
+        LeaderMessage {
+            msg_type: msg_type.to_string(),
+            kind: None,
+            key: None,
+            data,
+        }
+    }
+
+    fn peer(identity: &str, tpu_udp: &str) -> serde_json::Value {
+        json!({
+            "identity": identity,
+            "tpuQuic": null,
+            "tpuUdp": tpu_udp,
+            "tpuForwardsQuic": null,
+            "tpuForwardsUdp": null,
+            "tpuVote": null,
+            "gossipAddr": null,
+            "version": "2.0.0",
+            "shredVersion": 1,
+            "stake": 1,
+            "commission": 0,
+            "isDelinquent": false,
+            "wallclock": 0,
+        })
+    }
+
+    /// Resolver whose next leaders are "a" then "b", at the given TPU addresses.
+    fn resolver(a: &str, b: &str) -> Arc<RwLock<TpuResolver>> {
+        let mut resolver = TpuResolver::new();
+        let schedule = json!({
+            "epoch": 0,
+            "slotsInEpoch": 4,
+            "validators": 2,
+            "schedule": [
+                {"identity": "a", "slots": 2, "slotIndices": [0, 1]},
+                {"identity": "b", "slots": 2, "slotIndices": [2, 3]},
+            ],
+        });
+        let snapshot = json!({"timestamp": 0, "count": 2, "peers": [peer("a", a), peer("b", b)]});
+        resolver.apply(&message("leader_schedule", schedule)).unwrap();
+        resolver.apply(&message("gossip_snapshot", snapshot)).unwrap();
+        resolver
+            .apply(&message("slot_update", json!({"slot": 0, "leader": "a", "blockHeight": 0})))
+            .unwrap();
+        Arc::new(RwLock::new(resolver))
+    }
+
+    async fn fake_tpu() -> (UdpSocket, String) {
+        let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let addr = socket.local_addr().unwrap().to_string();
+        (socket, addr)
+    }
+
+    #[tokio::test]
+    async fn test_send_once_reaches_leaders() {
+        let (tpu_a, addr_a) = fake_tpu().await;
+        let (tpu_b, addr_b) = fake_tpu().await;
+        let transport = UdpTransport::bind_to("127.0.0.1:0").await.unwrap();
+        let sender = TpuSender::new(resolver(&addr_a, &addr_b), BlockhashProvider::new(), transport);
+
+        let results = sender.send_once(b"signed-tx").await.unwrap();
+        let sent: Vec<_> = results.iter().map(|r| (r.identity.as_str(), r.slot, r.result.is_ok())).collect();
+        assert_eq!(sent, [("a", 0, true), ("b", 2, true)]);
+
+        let mut buf = [0u8; 64];
+        for tpu in [&tpu_a, &tpu_b] {
+            let n = tpu.recv(&mut buf).await.unwrap();
+            assert_eq!(&buf[..n], b"signed-tx");
+        }
+
+        let one_leader = sender.with_config(SenderConfig { leaders: 1, ..SenderConfig::default() });
+        assert_eq!(one_leader.targets().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_send_until_landed_or_expired() {
+        let (tpu, addr) = fake_tpu().await;
+        let blockhash = BlockhashProvider::new();
+        let transport = UdpTransport::bind_to("127.0.0.1:0").await.unwrap();
+        let sender = TpuSender::new(resolver(&addr, &addr), blockhash.clone(), transport).with_config(SenderConfig {
+            retry_interval: Duration::from_millis(10),
+            ..SenderConfig::default()
+        });
+
+        // Lands once the fake TPU has seen three packets
+        let landed = async {
+            let mut buf = [0u8; 64];
+            for _ in 0..3 {
+                tpu.recv(&mut buf).await.unwrap();
+            }
+        };
+        let outcome = sender.send_until(b"tx", 150, landed).await.unwrap();
+        assert!(matches!(outcome, SendOutcome::Landed { rounds } if rounds >= 2));
+
+        blockhash.update(&Blockhash {
+            block_height: 151,
+            ..Blockhash::default()
+        });
+        assert_eq!(sender.send(b"tx", 150).await.unwrap(), SendOutcome::Expired { rounds: 0 });
+    }
+
+    #[tokio::test]
+    async fn test_no_leaders() {
+        let transport = UdpTransport::bind_to("127.0.0.1:0").await.unwrap();
+        let resolver = Arc::new(RwLock::new(TpuResolver::new()));
+        let sender = TpuSender::new(resolver, BlockhashProvider::new(), transport);
+        assert!(matches!(sender.send(b"tx", 150).await, Err(SendError::NoLeaders)));
+    }
+}
@@ -0,0 +1,123 @@
+//! Server → client binary frames built from typed messages.
+//!
+//! Every function returns a complete frame (type byte included) that
+//! [`decode_message`](crate::ws::decode_message) decodes back into the same
+//! message, ready for [`MockSession::send`](super::MockSession::send).
+//! Single pool updates, fee markets and blockhashes come from the
+//! [`encode_*`](crate::ws::encode_pool_update) encoders.
+
+use crate::types::{Blockhash, FeeMarket, MessageType, PoolUpdate, PriceEntry};
+use crate::ws::{encode_blockhash, encode_fee_market, encode_pool_update};
+
+/// Fixed-point scale of wire USD prices.
+const USD_PRICE_SCALE: f64 = 1e12;
+
+/// Single pool update frame (`0x01`).
+pub fn pool_update(update: &PoolUpdate) -> Vec<u8> {
+    encode_pool_update(update)
+}
+
+/// Pool update batch frame (`0x0E`).
+pub fn pool_update_batch(updates: &[PoolUpdate]) -> Vec<u8> {
+    pool_batch(MessageType::PoolUpdateBatch, updates)
+}
+
+/// Pool snapshot frame (`0x15`).
+pub fn pool_snapshot(updates: &[PoolUpdate]) -> Vec<u8> {
+    pool_batch(MessageType::PoolSnapshot, updates)
+}
+
+/// Fee market frame (`0x05`).
+pub fn fee_market(fees: &FeeMarket) -> Vec<u8> {
+    encode_fee_market(fees)
+}
+
+/// Blockhash frame (`0x06`).
+///
+/// # Panics
+///
+/// If `blockhash.blockhash` is not a base58-encoded 32-byte hash.
+pub fn blockhash(blockhash: &Blockhash) -> Vec<u8> {
+    encode_blockhash(blockhash).expect("blockhash is a base58-encoded 32-byte hash")
+}
+
+/// Single price update frame (`0x11`).
+pub fn price_update(entry: &PriceEntry) -> Vec<u8> {
+    let mut frame = vec![MessageType::PriceUpdate as u8];
+    write_price_entry(&mut frame, entry);
+    frame
+}
+
+/// Price batch frame (`0x12`).
+pub fn price_batch(entries: &[PriceEntry]) -> Vec<u8> {
+    price_entries(MessageType::PriceBatch, entries)
+}
+
+/// Price snapshot frame (`0x13`).
+pub fn price_snapshot(entries: &[PriceEntry]) -> Vec<u8> {
+    price_entries(MessageType::PriceSnapshot, entries)
+}
+
+/// Error frame (`0xFF`).
+pub fn error(message: &str) -> Vec<u8> {
+    let mut frame = vec![MessageType::Error as u8];
+    frame.extend_from_slice(message.as_bytes());
+    frame
+}
+
+/// Pong frame (`0x0C`) answering a keepalive ping.
+pub fn pong(timestamp_ms: u64) -> Vec<u8> {
+    let mut frame = vec![MessageType::Pong as u8];
+    frame.extend_from_slice(&timestamp_ms.to_le_bytes());
+    frame
+}
+
+/// `frame` as sent in forward-compatibility mode, with `extra` appended to
+/// its body as fields from a newer protocol revision.
+///
+/// Data frames get their body length-prefixed (see
+/// [`decode_message_compat`](crate::ws::decode_message_compat)); control
+/// frames are returned unchanged.
+pub fn forward_compat(frame: &[u8], extra: &[u8]) -> Vec<u8> {
+    let Some((&msg_type, body)) = frame.split_first() else {
+        return frame.to_vec();
+    };
+    if !MessageType::try_from(msg_type).is_ok_and(|kind| kind.is_data()) {
+        return frame.to_vec();
+    }
+    let mut wrapped = vec![msg_type];
+    wrapped.extend_from_slice(&((body.len() + extra.len()) as u32).to_le_bytes());
+    wrapped.extend_from_slice(body);
+    wrapped.extend_from_slice(extra);
+    wrapped
+}
+
+fn pool_batch(msg_type: MessageType, updates: &[PoolUpdate]) -> Vec<u8> {
+    let count = u16::try_from(updates.len()).expect("at most 65535 updates per batch");
+    let mut frame = vec![msg_type as u8];
+    frame.extend_from_slice(&count.to_le_bytes());
+    for update in updates {
+        // Batch entries are pool update payloads without the type byte
+        let entry = &encode_pool_update(update)[1..];
+        frame.extend_from_slice(&(entry.len() as u32).to_le_bytes());
+        frame.extend_from_slice(entry);
+    }
+    frame
+}
+
+fn price_entries(msg_type: MessageType, entries: &[PriceEntry]) -> Vec<u8> {
+    let count = u16::try_from(entries.len()).expect("at most 65535 entries per batch");
+    let mut frame = vec![msg_type as u8];
+    frame.extend_from_slice(&count.to_le_bytes());
+    for entry in entries {
+        write_price_entry(&mut frame, entry);
+    }
+    frame
+}
+
+fn write_price_entry(out: &mut Vec<u8>, entry: &PriceEntry) {
+    out.extend_from_slice(entry.mint.as_bytes());
+    out.extend_from_slice(&((entry.usd_price * USD_PRICE_SCALE).round() as u64).to_le_bytes());
+    out.extend_from_slice(&entry.slot.get().to_le_bytes());
+    out.extend_from_slice(&entry.timestamp_ms.get().to_le_bytes());
+}
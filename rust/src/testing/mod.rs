@@ -0,0 +1,309 @@
+//! In-process mock gateway for integration-testing code built on the SDK.
+//!
+//! [`MockGateway`] stands in for the K256 gateway without a network: hand
+//! [`MockGateway::transport`] to [`K256WebSocketClient::with_transport`],
+//! then [`accept`](MockGateway::accept) each connection the client opens as
+//! a [`MockSession`] and script the frames it receives. Build frames from
+//! typed messages with [`frames`].
+//!
+//! The gateway answers the protocol on its own: subscriptions are confirmed
+//! with a `subscribed` message listing the requested channels, and
+//! keepalive pings get a pong. Every client request is also handed to the
+//! session, so tests can assert on what was sent.
+//!
+//! ```rust
+//! use k256_sdk::testing::{frames, MockGateway};
+//! use k256_sdk::{Config, K256WebSocketClient, PoolUpdate, Slot, SubscribeRequest};
+//!
+//! # #[tokio::main]
+//! # async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+//! let gateway = MockGateway::new();
+//! let client = K256WebSocketClient::with_transport(Config::default(), gateway.transport());
+//! let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+//! client.on_pool_update(move |update| tx.send(update.slot).unwrap()).detach();
+//!
+//! client.connect().await?;
+//! client.subscribe(SubscribeRequest::default()).await?;
+//!
+//! let mut session = gateway.accept().await;
+//! let request = session.next_subscription().await.unwrap();
+//! assert!(request.channels.contains(&"pools".to_string()));
+//!
+//! session.send(frames::pool_update(&PoolUpdate { slot: Slot(42), ..PoolUpdate::default() }));
+//! assert_eq!(rx.recv().await, Some(Slot(42)));
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`K256WebSocketClient::with_transport`]: crate::ws::K256WebSocketClient::with_transport
+
+pub mod frames;
+
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde_json::Value;
+use tokio::sync::mpsc;
+
+use crate::transport::{BoxFuture, Frame, Transport};
+use crate::types::PROTOCOL_VERSION;
+use crate::ws::{decode_client_message, ClientMessage, SubscribeRequest};
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+/// In-process stand-in for the K256 gateway.
+///
+/// Clones share the same gateway.
+#[derive(Clone)]
+pub struct MockGateway {
+    shared: Arc<Shared>,
+}
+
+struct Shared {
+    sessions_tx: mpsc::UnboundedSender<MockSession>,
+    sessions_rx: tokio::sync::Mutex<mpsc::UnboundedReceiver<MockSession>>,
+    auto_confirm: AtomicBool,
+    refuse: AtomicUsize,
+    connections: AtomicUsize,
+}
+
+impl Default for MockGateway {
+    fn default() -> Self {
+        let (sessions_tx, sessions_rx) = mpsc::unbounded_channel();
+        Self {
+            shared: Arc::new(Shared {
+                sessions_tx,
+                sessions_rx: tokio::sync::Mutex::new(sessions_rx),
+                auto_confirm: AtomicBool::new(true),
+                refuse: AtomicUsize::new(0),
+                connections: AtomicUsize::new(0),
+            }),
+        }
+    }
+}
+
+impl MockGateway {
+    /// Gateway that accepts every connection and confirms subscriptions.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A [`Transport`] connecting to this gateway.
+    pub fn transport(&self) -> MockTransport {
+        MockTransport {
+            shared: self.shared.clone(),
+            connection: None,
+        }
+    }
+
+    /// Wait for the next client connection.
+    pub async fn accept(&self) -> MockSession {
+        let mut sessions = self.shared.sessions_rx.lock().await;
+        // The gateway holds a sender, so the channel never closes
+        sessions.recv().await.expect("gateway holds a session sender")
+    }
+
+    /// Whether subscriptions are confirmed automatically (default `true`).
+    ///
+    /// Turn off to answer them from the session, e.g. with
+    /// [`frames::error`] to test a rejected subscription.
+    pub fn set_auto_confirm(&self, enabled: bool) {
+        self.shared.auto_confirm.store(enabled, Ordering::Relaxed);
+    }
+
+    /// Refuse the next `count` connection attempts.
+    pub fn refuse_connections(&self, count: usize) {
+        self.shared.refuse.store(count, Ordering::Relaxed);
+    }
+
+    /// Number of connections accepted so far.
+    pub fn connections(&self) -> usize {
+        self.shared.connections.load(Ordering::Relaxed)
+    }
+}
+
+/// One client connection to a [`MockGateway`], seen from the server side.
+///
+/// Dropping the session (or calling [`close`](Self::close)) ends the
+/// connection, as if the gateway had hung up.
+pub struct MockSession {
+    endpoint: String,
+    outbound: mpsc::UnboundedSender<Frame>,
+    requests: mpsc::UnboundedReceiver<ClientMessage>,
+    subscription: Arc<Mutex<Option<SubscribeRequest>>>,
+}
+
+impl MockSession {
+    /// Endpoint the client connected to.
+    pub fn endpoint(&self) -> &str {
+        &self.endpoint
+    }
+
+    /// Send a binary frame (see [`frames`]).
+    ///
+    /// Returns `false` once the client has disconnected.
+    pub fn send(&self, frame: Vec<u8>) -> bool {
+        self.outbound.send(Frame::Binary(frame)).is_ok()
+    }
+
+    /// Send a JSON text frame.
+    ///
+    /// Returns `false` once the client has disconnected.
+    pub fn send_text(&self, text: impl Into<String>) -> bool {
+        self.outbound.send(Frame::Text(text.into())).is_ok()
+    }
+
+    /// Next request from the client; `None` once it has disconnected.
+    pub async fn recv(&mut self) -> Option<ClientMessage> {
+        self.requests.recv().await
+    }
+
+    /// Wait for the next subscribe request, skipping other requests.
+    pub async fn next_subscription(&mut self) -> Option<SubscribeRequest> {
+        loop {
+            if let ClientMessage::Subscribe(request) = self.recv().await? {
+                return Some(request);
+            }
+        }
+    }
+
+    /// Subscription currently in effect on this connection.
+    pub fn subscription(&self) -> Option<SubscribeRequest> {
+        self.subscription.lock().unwrap().clone()
+    }
+
+    /// Close the connection with a close frame.
+    pub fn close(self) {
+        let _ = self.outbound.send(Frame::Close);
+    }
+}
+
+/// [`Transport`] connected to a [`MockGateway`].
+pub struct MockTransport {
+    shared: Arc<Shared>,
+    connection: Option<Connection>,
+}
+
+struct Connection {
+    inbound: mpsc::UnboundedReceiver<Frame>,
+    /// Protocol replies queued by the gateway itself
+    replies: VecDeque<Frame>,
+    requests: mpsc::UnboundedSender<ClientMessage>,
+    subscription: Arc<Mutex<Option<SubscribeRequest>>>,
+}
+
+impl Connection {
+    fn handle(&mut self, frame: Frame, auto_confirm: bool) {
+        let request = match frame {
+            Frame::Binary(data) => decode_client_message(&data).ok(),
+            Frame::Text(text) => parse_text_request(&text),
+            Frame::Ping(data) => {
+                self.replies.push_back(Frame::Pong(data));
+                None
+            }
+            Frame::Pong(_) | Frame::Close => None,
+        };
+        let Some(request) = request else {
+            return;
+        };
+
+        match &request {
+            ClientMessage::Subscribe(subscribe) => {
+                *self.subscription.lock().unwrap() = Some(subscribe.clone());
+                if auto_confirm {
+                    let confirmed = serde_json::json!({
+                        "type": "subscribed",
+                        "channels": subscribe.channels,
+                        "protocol_version": PROTOCOL_VERSION,
+                    });
+                    self.replies.push_back(Frame::Text(confirmed.to_string()));
+                }
+            }
+            ClientMessage::Unsubscribe => *self.subscription.lock().unwrap() = None,
+            ClientMessage::Ping => self.replies.push_back(Frame::Binary(frames::pong(now_ms()))),
+            _ => {}
+        }
+        // The session may already be gone; the client learns that on receive
+        let _ = self.requests.send(request);
+    }
+}
+
+impl Transport for MockTransport {
+    fn connect<'a>(&'a mut self, endpoint: &'a str) -> BoxFuture<'a, Result<(), BoxError>> {
+        Box::pin(async move {
+            self.connection = None;
+            let refused = self
+                .shared
+                .refuse
+                .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |n| n.checked_sub(1))
+                .is_ok();
+            if refused {
+                return Err("connection refused by mock gateway".into());
+            }
+
+            let (outbound, inbound) = mpsc::unbounded_channel();
+            let (requests_tx, requests) = mpsc::unbounded_channel();
+            let subscription = Arc::new(Mutex::new(None));
+            let session = MockSession {
+                endpoint: endpoint.to_string(),
+                outbound,
+                requests,
+                subscription: subscription.clone(),
+            };
+            self.shared.sessions_tx.send(session).map_err(|_| "mock gateway is gone")?;
+            self.shared.connections.fetch_add(1, Ordering::Relaxed);
+            self.connection = Some(Connection {
+                inbound,
+                replies: VecDeque::new(),
+                requests: requests_tx,
+                subscription,
+            });
+            Ok(())
+        })
+    }
+
+    fn send(&mut self, frame: Frame) -> BoxFuture<'_, Result<(), BoxError>> {
+        Box::pin(async move {
+            let auto_confirm = self.shared.auto_confirm.load(Ordering::Relaxed);
+            let connection = self.connection.as_mut().ok_or("not connected")?;
+            connection.handle(frame, auto_confirm);
+            Ok(())
+        })
+    }
+
+    fn receive(&mut self) -> BoxFuture<'_, Option<Result<Frame, BoxError>>> {
+        Box::pin(async move {
+            let connection = self.connection.as_mut()?;
+            if let Some(reply) = connection.replies.pop_front() {
+                return Some(Ok(reply));
+            }
+            connection.inbound.recv().await.map(Ok)
+        })
+    }
+
+    fn close(&mut self) -> BoxFuture<'_, Result<(), BoxError>> {
+        Box::pin(async move {
+            self.connection = None;
+            Ok(())
+        })
+    }
+}
+
+/// Client request carried in a JSON text frame.
+fn parse_text_request(text: &str) -> Option<ClientMessage> {
+    let value: Value = serde_json::from_str(text).ok()?;
+    match value.get("type")?.as_str()? {
+        "subscribe" => serde_json::from_value(value).ok().map(ClientMessage::Subscribe),
+        "unsubscribe" => Some(ClientMessage::Unsubscribe),
+        _ => None,
+    }
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
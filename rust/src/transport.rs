@@ -0,0 +1,363 @@
+//! Pluggable WebSocket transport for [`K256WebSocketClient`].
+//!
+//! The client talks to the gateway through a [`Transport`]: it connects to
+//! an endpoint, exchanges [`Frame`]s and closes the connection, reusing the
+//! same transport across reconnects. [`TungsteniteTransport`] is the default
+//! and honors [`Config::auth`], [`Config::tls`], [`Config::proxy`] and
+//! [`Config::dns`]. Pass
+//! your own to [`K256WebSocketClient::with_transport`] to run the client
+//! over an in-memory mock in tests, a local relay, or a unix socket.
+//!
+//! ```rust
+//! use k256_sdk::transport::{BoxFuture, Frame, Transport};
+//! use k256_sdk::{Config, K256WebSocketClient};
+//!
+//! type BoxError = Box<dyn std::error::Error + Send + Sync>;
+//!
+//! /// Replays canned frames, then reports the connection closed.
+//! struct Canned(Vec<Frame>);
+//!
+//! impl Transport for Canned {
+//!     fn connect<'a>(&'a mut self, _endpoint: &'a str) -> BoxFuture<'a, Result<(), BoxError>> {
+//!         Box::pin(async { Ok(()) })
+//!     }
+//!     fn send(&mut self, _frame: Frame) -> BoxFuture<'_, Result<(), BoxError>> {
+//!         Box::pin(async { Ok(()) })
+//!     }
+//!     fn receive(&mut self) -> BoxFuture<'_, Option<Result<Frame, BoxError>>> {
+//!         Box::pin(async { (!self.0.is_empty()).then(|| Ok(self.0.remove(0))) })
+//!     }
+//!     fn close(&mut self) -> BoxFuture<'_, Result<(), BoxError>> {
+//!         Box::pin(async { Ok(()) })
+//!     }
+//! }
+//!
+//! let client = K256WebSocketClient::with_transport(Config::default(), Canned(Vec::new()));
+//! ```
+//!
+//! [`K256WebSocketClient`]: crate::ws::K256WebSocketClient
+//! [`K256WebSocketClient::with_transport`]: crate::ws::K256WebSocketClient::with_transport
+//! [`Config::auth`]: crate::ws::Config::auth
+//! [`Config::tls`]: crate::ws::Config::tls
+//! [`Config::proxy`]: crate::ws::Config::proxy
+//! [`Config::dns`]: crate::ws::Config::dns
+
+use std::future::Future;
+use std::net::SocketAddr;
+use std::pin::Pin;
+
+use futures_util::{SinkExt, StreamExt};
+use tokio_tungstenite::tungstenite::Message;
+
+use crate::connector::{self, AuthMethod, Dialer, ProxyConfig, TlsConfig, WsStream};
+use crate::ws::Config;
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Boxed future returned by [`Transport`] methods.
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// A WebSocket frame.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Frame {
+    /// Binary data frame
+    Binary(Vec<u8>),
+    /// Text data frame
+    Text(String),
+    /// WebSocket-level ping
+    Ping(Vec<u8>),
+    /// WebSocket-level pong
+    Pong(Vec<u8>),
+    /// Close frame
+    Close,
+}
+
+/// Connection to the K256 gateway used by [`K256WebSocketClient`].
+///
+/// A transport holds at most one connection at a time. The client calls
+/// [`connect`](Self::connect) for the initial connection and every
+/// reconnect, and [`close`](Self::close) when a session ends.
+///
+/// [`receive`](Self::receive) must be cancel-safe: the client polls it
+/// alongside its timers and outgoing queue, and drops the future whenever
+/// one of those fires first.
+///
+/// [`K256WebSocketClient`]: crate::ws::K256WebSocketClient
+pub trait Transport: Send + 'static {
+    /// Open a connection to `endpoint`, replacing any current one.
+    fn connect<'a>(&'a mut self, endpoint: &'a str) -> BoxFuture<'a, Result<(), BoxError>>;
+
+    /// Send a frame on the current connection.
+    fn send(&mut self, frame: Frame) -> BoxFuture<'_, Result<(), BoxError>>;
+
+    /// Receive the next frame; `None` once the connection is closed.
+    fn receive(&mut self) -> BoxFuture<'_, Option<Result<Frame, BoxError>>>;
+
+    /// Close the current connection.
+    fn close(&mut self) -> BoxFuture<'_, Result<(), BoxError>>;
+
+    /// Remote address of the current connection, if known.
+    ///
+    /// Reported in [`ConnectionState::Connected`](crate::types::ConnectionState::Connected).
+    fn peer_addr(&self) -> Option<SocketAddr> {
+        None
+    }
+}
+
+/// Default [`Transport`] over `tokio-tungstenite`.
+pub struct TungsteniteTransport {
+    api_key: String,
+    auth: AuthMethod,
+    tls: Option<TlsConfig>,
+    proxy: Option<ProxyConfig>,
+    dialer: Dialer,
+    stream: Option<WsStream>,
+    peer: Option<SocketAddr>,
+}
+
+impl TungsteniteTransport {
+    /// Transport authenticating and connecting as `config` specifies.
+    pub fn new(config: &Config) -> Self {
+        Self {
+            api_key: config.api_key.clone(),
+            auth: config.auth,
+            tls: config.tls.clone(),
+            proxy: config.proxy.clone(),
+            dialer: Dialer::new(config.dns.clone()),
+            stream: None,
+            peer: None,
+        }
+    }
+
+    fn stream(&mut self) -> Result<&mut WsStream, BoxError> {
+        self.stream.as_mut().ok_or_else(|| "not connected".into())
+    }
+}
+
+impl Transport for TungsteniteTransport {
+    fn connect<'a>(&'a mut self, endpoint: &'a str) -> BoxFuture<'a, Result<(), BoxError>> {
+        Box::pin(async move {
+            self.stream = None;
+            self.peer = None;
+            let request = connector::authenticated_request(endpoint, &self.api_key, self.auth)?;
+            let (stream, peer) =
+                connector::connect(request, self.tls.as_ref(), self.proxy.as_ref(), &mut self.dialer).await?;
+            self.stream = Some(stream);
+            self.peer = Some(peer);
+            Ok(())
+        })
+    }
+
+    fn send(&mut self, frame: Frame) -> BoxFuture<'_, Result<(), BoxError>> {
+        Box::pin(async move {
+            let msg = match frame {
+                Frame::Binary(data) => Message::Binary(data),
+                Frame::Text(text) => Message::Text(text),
+                Frame::Ping(data) => Message::Ping(data),
+                Frame::Pong(data) => Message::Pong(data),
+                Frame::Close => Message::Close(None),
+            };
+            self.stream()?.send(msg).await?;
+            Ok(())
+        })
+    }
+
+    fn receive(&mut self) -> BoxFuture<'_, Option<Result<Frame, BoxError>>> {
+        Box::pin(async move {
+            let stream = self.stream.as_mut()?;
+            loop {
+                let frame = match stream.next().await? {
+                    Ok(Message::Binary(data)) => Frame::Binary(data),
+                    Ok(Message::Text(text)) => Frame::Text(text),
+                    Ok(Message::Ping(data)) => Frame::Ping(data),
+                    Ok(Message::Pong(data)) => Frame::Pong(data),
+                    Ok(Message::Close(_)) => Frame::Close,
+                    Ok(Message::Frame(_)) => continue,
+                    Err(e) => return Some(Err(e.into())),
+                };
+                return Some(Ok(frame));
+            }
+        })
+    }
+
+    fn close(&mut self) -> BoxFuture<'_, Result<(), BoxError>> {
+        Box::pin(async move {
+            self.peer = None;
+            match self.stream.take() {
+                Some(mut stream) => Ok(stream.close(None).await?),
+                None => Ok(()),
+            }
+        })
+    }
+
+    fn peer_addr(&self) -> Option<SocketAddr> {
+        self.peer
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    use tokio::sync::mpsc;
+
+    use super::*;
+    use crate::types::{ConnectionState, Slot};
+    use crate::ws::K256WebSocketClient;
+    use crate::SubscribeRequest;
+
+    /// In-memory transport: frames from `inbound` are received, sent frames
+    /// are recorded, and each connect takes the next inbound script.
+    struct MockTransport {
+        sessions: mpsc::UnboundedReceiver<Vec<Frame>>,
+        inbound: Vec<Frame>,
+        sent: Arc<Mutex<Vec<Frame>>>,
+        connects: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl Transport for MockTransport {
+        fn connect<'a>(&'a mut self, endpoint: &'a str) -> BoxFuture<'a, Result<(), BoxError>> {
+            Box::pin(async move {
+                self.inbound = self.sessions.try_recv().map_err(|_| "no more sessions")?;
+                self.connects.lock().unwrap().push(endpoint.to_string());
+                Ok(())
+            })
+        }
+
+        fn send(&mut self, frame: Frame) -> BoxFuture<'_, Result<(), BoxError>> {
+            Box::pin(async move {
+                self.sent.lock().unwrap().push(frame);
+                Ok(())
+            })
+        }
+
+        fn receive(&mut self) -> BoxFuture<'_, Option<Result<Frame, BoxError>>> {
+            Box::pin(async move {
+                if self.inbound.is_empty() {
+                    // Give the client time to send before closing the session
+                    tokio::time::sleep(Duration::from_millis(20)).await;
+                    return None;
+                }
+                Some(Ok(self.inbound.remove(0)))
+            })
+        }
+
+        fn close(&mut self) -> BoxFuture<'_, Result<(), BoxError>> {
+            Box::pin(async { Ok(()) })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_client_over_mock_transport() {
+        let (sessions, rx) = mpsc::unbounded_channel();
+        let subscribed = Frame::Text(r#"{"type":"subscribed","channels":["pools"]}"#.to_string());
+        sessions.send(vec![subscribed.clone(), subscribed]).unwrap();
+        sessions.send(Vec::new()).unwrap();
+
+        let sent = Arc::new(Mutex::new(Vec::new()));
+        let connects = Arc::new(Mutex::new(Vec::new()));
+        let transport = MockTransport {
+            sessions: rx,
+            inbound: Vec::new(),
+            sent: sent.clone(),
+            connects: connects.clone(),
+        };
+        let config = Config {
+            endpoint: "mock://primary".to_string(),
+            reconnect_delay_initial: Duration::from_millis(1),
+            ping_interval: Duration::ZERO,
+            ..Config::default()
+        };
+        let client = K256WebSocketClient::with_transport(config, transport);
+        let states = Arc::new(Mutex::new(Vec::new()));
+        let seen = states.clone();
+        client.on_connection_state(move |state| seen.lock().unwrap().push(state)).detach();
+
+        client.connect().await.unwrap();
+        let request = SubscribeRequest::builder().channel(crate::Channel::Pools).build().unwrap();
+        client.subscribe(request).await.unwrap();
+        // Reconnect delay is 1ms plus up to 500ms of jitter
+        tokio::time::sleep(Duration::from_millis(800)).await;
+
+        // The first session ends when its script runs out; the client
+        // reconnects and restores the subscription on the second
+        assert_eq!(*connects.lock().unwrap(), vec!["mock://primary", "mock://primary"]);
+        let subscribes = sent
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|frame| matches!(frame, Frame::Text(text) if text.contains("subscribe")))
+            .count();
+        assert_eq!(subscribes, 2);
+        assert!(states.lock().unwrap().contains(&ConnectionState::Subscribed));
+        assert!(states
+            .lock()
+            .unwrap()
+            .contains(&ConnectionState::Reconnecting { attempt: 1 }));
+    }
+
+    #[tokio::test]
+    async fn test_tungstenite_reports_peer() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (socket, _) = listener.accept().await.unwrap();
+            let _ws = tokio_tungstenite::accept_async(socket).await.unwrap();
+            std::future::pending::<()>().await;
+        });
+
+        let config = Config {
+            endpoint: format!("ws://localhost:{}/v1/ws", addr.port()),
+            dns: connector::DnsConfig {
+                ip_family: connector::IpFamily::V4,
+                ..connector::DnsConfig::default()
+            },
+            ping_interval: Duration::ZERO,
+            reconnect: false,
+            ..Config::default()
+        };
+        let client = K256WebSocketClient::new(config);
+        let (tx, mut states) = mpsc::unbounded_channel();
+        client.on_connection_state(move |state| tx.send(state).unwrap()).detach();
+        client.connect().await.unwrap();
+        assert_eq!(states.recv().await, Some(ConnectionState::Connecting));
+        assert_eq!(states.recv().await, Some(ConnectionState::Connected { peer: Some(addr) }));
+        assert_eq!(client.peer_addr(), Some(addr));
+    }
+
+    #[tokio::test]
+    async fn test_decode_queue_delivers_in_order() {
+        use crate::types::PoolUpdate;
+        use crate::ws::encode_pool_update;
+
+        let (sessions, rx) = mpsc::unbounded_channel();
+        let frames = (1..=5)
+            .map(|slot| Frame::Binary(encode_pool_update(&PoolUpdate { slot: Slot(slot), ..PoolUpdate::default() })))
+            .collect();
+        sessions.send(frames).unwrap();
+        let transport = MockTransport {
+            sessions: rx,
+            inbound: Vec::new(),
+            sent: Arc::new(Mutex::new(Vec::new())),
+            connects: Arc::new(Mutex::new(Vec::new())),
+        };
+        let config = Config {
+            endpoint: "mock://primary".to_string(),
+            reconnect: false,
+            ping_interval: Duration::ZERO,
+            decode_queue: Some(1),
+            ..Config::default()
+        };
+        let client = K256WebSocketClient::with_transport(config, transport);
+        let (tx, mut slots) = mpsc::unbounded_channel();
+        client.on_pool_update(move |update| tx.send(update.slot).unwrap()).detach();
+
+        client.connect().await.unwrap();
+        let mut received = Vec::new();
+        while received.len() < 5 {
+            received.push(slots.recv().await.unwrap());
+        }
+        assert_eq!(received, [1, 2, 3, 4, 5]);
+    }
+}
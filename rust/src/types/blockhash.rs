@@ -2,13 +2,15 @@
 
 use serde::{Deserialize, Serialize};
 
+use super::{Slot, TimestampMs};
+
 /// Recent blockhash from K256.
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Blockhash {
     /// Solana slot of the blockhash
-    pub slot: u64,
+    pub slot: Slot,
     /// Unix timestamp in milliseconds
-    pub timestamp_ms: u64,
+    pub timestamp_ms: TimestampMs,
     /// Base58-encoded recent blockhash
     pub blockhash: String,
     /// Block height
@@ -18,3 +20,13 @@ pub struct Blockhash {
     /// Whether data may be stale
     pub is_stale: bool,
 }
+
+impl Blockhash {
+    /// Blocks left before the blockhash expires at `current_height`, or 0
+    /// once it has.
+    ///
+    /// Takes a block height, not a slot: skipped slots produce no block.
+    pub fn remaining_validity(&self, current_height: u64) -> u64 {
+        self.last_valid_block_height.saturating_sub(current_height)
+    }
+}
@@ -0,0 +1,212 @@
+//! Slot and timestamp types.
+//!
+//! Messages carry slots, block heights and millisecond timestamps, all as
+//! 64-bit integers on the wire. [`Slot`] and [`TimestampMs`] keep them
+//! apart at compile time; both serialize as plain numbers.
+
+use std::fmt;
+use std::ops::{Add, Sub};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+/// Target duration of a Solana slot.
+pub const SLOT_DURATION: Duration = Duration::from_millis(400);
+
+/// Solana slot number.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Slot(pub u64);
+
+impl Slot {
+    /// Slot number as an integer.
+    pub const fn get(self) -> u64 {
+        self.0
+    }
+
+    /// Slots from `earlier` to this slot, or 0 if `earlier` is later.
+    pub const fn slots_since(self, earlier: Slot) -> u64 {
+        self.0.saturating_sub(earlier.0)
+    }
+
+    /// Estimated wall-clock time of this slot, assuming [`SLOT_DURATION`]
+    /// per slot since `genesis`, the time of slot 0.
+    ///
+    /// Real slots run somewhat slower than the target, so the estimate drifts
+    /// over an epoch; pass a recent `(slot, time)` pair via
+    /// [`estimated_time_from`](Self::estimated_time_from) for a closer one.
+    pub fn estimated_time(self, genesis: TimestampMs) -> TimestampMs {
+        self.estimated_time_from(Slot(0), genesis)
+    }
+
+    /// Estimated wall-clock time of this slot, given that `anchor` started
+    /// at `anchor_time`. Works for slots before the anchor too.
+    pub fn estimated_time_from(self, anchor: Slot, anchor_time: TimestampMs) -> TimestampMs {
+        let slot_ms = SLOT_DURATION.as_millis() as u64;
+        if self >= anchor {
+            anchor_time + Duration::from_millis(self.slots_since(anchor).saturating_mul(slot_ms))
+        } else {
+            anchor_time - Duration::from_millis(anchor.slots_since(self).saturating_mul(slot_ms))
+        }
+    }
+}
+
+impl From<u64> for Slot {
+    fn from(slot: u64) -> Self {
+        Self(slot)
+    }
+}
+
+impl From<Slot> for u64 {
+    fn from(slot: Slot) -> Self {
+        slot.0
+    }
+}
+
+impl PartialEq<u64> for Slot {
+    fn eq(&self, other: &u64) -> bool {
+        self.0 == *other
+    }
+}
+
+impl PartialOrd<u64> for Slot {
+    fn partial_cmp(&self, other: &u64) -> Option<std::cmp::Ordering> {
+        self.0.partial_cmp(other)
+    }
+}
+
+impl Add<u64> for Slot {
+    type Output = Slot;
+
+    fn add(self, slots: u64) -> Slot {
+        Slot(self.0 + slots)
+    }
+}
+
+impl fmt::Display for Slot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+/// Unix timestamp in milliseconds.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct TimestampMs(pub u64);
+
+impl TimestampMs {
+    /// Current time.
+    ///
+    /// Read from `Date.now()` on `wasm32`, where [`SystemTime::now`] panics.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn now() -> Self {
+        Self::from(SystemTime::now())
+    }
+
+    /// Current time.
+    ///
+    /// Read from `Date.now()` on `wasm32`, where [`SystemTime::now`] panics.
+    #[cfg(target_arch = "wasm32")]
+    pub fn now() -> Self {
+        Self(js_sys::Date::now() as u64)
+    }
+
+    /// Milliseconds since the Unix epoch.
+    pub const fn get(self) -> u64 {
+        self.0
+    }
+
+    /// Time elapsed since this timestamp, or zero if it is in the future.
+    pub fn age(self) -> Duration {
+        Self::now().duration_since(self)
+    }
+
+    /// Time from `earlier` to this timestamp, or zero if `earlier` is later.
+    pub fn duration_since(self, earlier: TimestampMs) -> Duration {
+        Duration::from_millis(self.0.saturating_sub(earlier.0))
+    }
+
+    /// Timestamp as a [`SystemTime`].
+    pub fn to_system_time(self) -> SystemTime {
+        UNIX_EPOCH + Duration::from_millis(self.0)
+    }
+}
+
+impl From<u64> for TimestampMs {
+    fn from(ms: u64) -> Self {
+        Self(ms)
+    }
+}
+
+impl From<TimestampMs> for u64 {
+    fn from(ts: TimestampMs) -> Self {
+        ts.0
+    }
+}
+
+impl From<SystemTime> for TimestampMs {
+    /// Times before the Unix epoch map to 0.
+    fn from(time: SystemTime) -> Self {
+        Self(time.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_millis() as u64))
+    }
+}
+
+impl PartialEq<u64> for TimestampMs {
+    fn eq(&self, other: &u64) -> bool {
+        self.0 == *other
+    }
+}
+
+impl PartialOrd<u64> for TimestampMs {
+    fn partial_cmp(&self, other: &u64) -> Option<std::cmp::Ordering> {
+        self.0.partial_cmp(other)
+    }
+}
+
+impl Add<Duration> for TimestampMs {
+    type Output = TimestampMs;
+
+    fn add(self, duration: Duration) -> TimestampMs {
+        TimestampMs(self.0.saturating_add(duration.as_millis() as u64))
+    }
+}
+
+impl Sub<Duration> for TimestampMs {
+    type Output = TimestampMs;
+
+    fn sub(self, duration: Duration) -> TimestampMs {
+        TimestampMs(self.0.saturating_sub(duration.as_millis() as u64))
+    }
+}
+
+impl fmt::Display for TimestampMs {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_slot_estimates() {
+        let genesis = TimestampMs(1_000_000);
+        assert_eq!(Slot(10).estimated_time(genesis), 1_004_000);
+
+        let anchor_time = TimestampMs(5_000_000);
+        assert_eq!(Slot(105).estimated_time_from(Slot(100), anchor_time), 5_002_000);
+        assert_eq!(Slot(95).estimated_time_from(Slot(100), anchor_time), 4_998_000);
+        assert_eq!(Slot(95).slots_since(Slot(100)), 0);
+    }
+
+    #[test]
+    fn test_timestamp_age_and_serde() {
+        let past = TimestampMs::now() - Duration::from_secs(5);
+        assert!(past.age() >= Duration::from_secs(5));
+        assert_eq!((TimestampMs::now() + Duration::from_secs(60)).age(), Duration::ZERO);
+
+        assert_eq!(serde_json::to_string(&Slot(7)).unwrap(), "7");
+        assert_eq!(serde_json::from_str::<TimestampMs>("12").unwrap(), 12);
+    }
+}
@@ -0,0 +1,86 @@
+//! Connection lifecycle types.
+
+use std::fmt;
+use std::net::SocketAddr;
+
+/// Lifecycle state of a streaming connection.
+///
+/// Reported by both [`crate::ws::K256WebSocketClient`] and
+/// [`crate::leader_ws::LeaderWebSocketClient`] whenever the state changes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConnectionState {
+    /// Opening the initial connection
+    Connecting,
+    /// Handshake complete, no subscription confirmed yet
+    Connected {
+        /// Address the connection was made to (the proxy's when connecting
+        /// through one), if the transport reports it
+        peer: Option<SocketAddr>,
+    },
+    /// The server confirmed the subscription
+    Subscribed,
+    /// Connected, but data went stale or pings go unanswered
+    Degraded,
+    /// Waiting to reconnect after the connection dropped
+    Reconnecting {
+        /// Reconnect attempt, starting at 1
+        attempt: u32,
+    },
+    /// Closed and not reconnecting
+    Closed {
+        /// Why the connection closed
+        reason: String,
+    },
+}
+
+impl ConnectionState {
+    /// Whether a connection is currently open (including degraded).
+    pub fn is_connected(&self) -> bool {
+        matches!(self, Self::Connected { .. } | Self::Subscribed | Self::Degraded)
+    }
+}
+
+impl Default for ConnectionState {
+    fn default() -> Self {
+        Self::Closed {
+            reason: "not connected".to_string(),
+        }
+    }
+}
+
+impl fmt::Display for ConnectionState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Connecting => f.write_str("connecting"),
+            Self::Connected { peer: None } => f.write_str("connected"),
+            Self::Connected { peer: Some(peer) } => write!(f, "connected to {}", peer),
+            Self::Subscribed => f.write_str("subscribed"),
+            Self::Degraded => f.write_str("degraded"),
+            Self::Reconnecting { attempt } => write!(f, "reconnecting (attempt {})", attempt),
+            Self::Closed { reason } => write!(f, "closed: {}", reason),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_connected() {
+        assert!(!ConnectionState::default().is_connected());
+        assert!(!ConnectionState::Connecting.is_connected());
+        assert!(!ConnectionState::Reconnecting { attempt: 1 }.is_connected());
+        assert!(ConnectionState::Connected { peer: None }.is_connected());
+        assert!(ConnectionState::Subscribed.is_connected());
+        assert!(ConnectionState::Degraded.is_connected());
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(ConnectionState::Reconnecting { attempt: 3 }.to_string(), "reconnecting (attempt 3)");
+        assert_eq!(ConnectionState::default().to_string(), "closed: not connected");
+        let peer = Some("[2001:db8::1]:443".parse().unwrap());
+        assert_eq!(ConnectionState::Connected { peer }.to_string(), "connected to [2001:db8::1]:443");
+    }
+}
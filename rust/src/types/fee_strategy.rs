@@ -0,0 +1,228 @@
+//! Priority fee presets driven by the fee market.
+
+use std::fmt;
+use std::sync::Arc;
+
+use super::{AccountFee, FeeMarket, NetworkState, Pubkey};
+
+/// Signature of a [`FeeStrategy::Custom`] pricing function.
+///
+/// Receives the fee market and the writable accounts of the transaction and
+/// returns a compute unit price in microlamports/CU.
+pub type FeeFn = dyn Fn(&FeeMarket, &[Pubkey]) -> u64 + Send + Sync;
+
+/// How to turn a [`FeeMarket`] into a compute unit price.
+///
+/// The presets pick a per-account fee percentile by [`NetworkState`] and
+/// price the transaction at the maximum over its writable accounts, as the
+/// scheduler does:
+///
+/// | Preset         | Low | Normal | High | Extreme |
+/// |----------------|-----|--------|------|---------|
+/// | `Conservative` | p25 | p50    | p50  | p75     |
+/// | `Balanced`     | p50 | p75    | p75  | p90     |
+/// | `Aggressive`   | p75 | p90    | p90  | p90     |
+///
+/// `Aggressive` additionally scales its price by up to 1.5x with block
+/// utilization. Writable accounts missing from the message fall back to the
+/// hottest accounts it carries, and to [`FeeMarket::recommended`] when it
+/// carries none.
+///
+/// ```rust
+/// use k256_sdk::{FeeMarket, FeeStrategy, Pubkey};
+///
+/// fn price(fees: &FeeMarket, writable: &[Pubkey]) -> u64 {
+///     FeeStrategy::Balanced.compute_unit_price(fees, writable)
+/// }
+/// ```
+#[derive(Clone, Default)]
+pub enum FeeStrategy {
+    /// Land eventually; lower percentiles
+    Conservative,
+    /// Land reliably at a fair price
+    #[default]
+    Balanced,
+    /// Land as fast as possible
+    Aggressive,
+    /// Caller-defined pricing
+    Custom(Arc<FeeFn>),
+}
+
+impl FeeStrategy {
+    /// Strategy pricing with `f`.
+    pub fn custom<F>(f: F) -> Self
+    where
+        F: Fn(&FeeMarket, &[Pubkey]) -> u64 + Send + Sync + 'static,
+    {
+        Self::Custom(Arc::new(f))
+    }
+
+    /// Compute unit price in microlamports/CU for a transaction writing to
+    /// `writable`; the value for a `SetComputeUnitPrice` instruction.
+    pub fn compute_unit_price(&self, fees: &FeeMarket, writable: &[Pubkey]) -> u64 {
+        let percentile = match self {
+            Self::Custom(f) => return f(fees, writable),
+            Self::Conservative => match fees.state {
+                NetworkState::Low => Percentile::P25,
+                NetworkState::Normal | NetworkState::High => Percentile::P50,
+                NetworkState::Extreme => Percentile::P75,
+            },
+            Self::Balanced => match fees.state {
+                NetworkState::Low => Percentile::P50,
+                NetworkState::Normal | NetworkState::High => Percentile::P75,
+                NetworkState::Extreme => Percentile::P90,
+            },
+            Self::Aggressive => match fees.state {
+                NetworkState::Low => Percentile::P75,
+                _ => Percentile::P90,
+            },
+        };
+
+        let price = account_price(fees, writable, percentile);
+        if let Self::Aggressive = self {
+            let utilization = fees.block_utilization_pct.clamp(0.0, 100.0) as f64;
+            return (price as f64 * (1.0 + utilization / 200.0)).round() as u64;
+        }
+        price
+    }
+
+    /// Full fee estimate for a transaction writing to `writable` and
+    /// requesting `compute_units`.
+    pub fn estimate(&self, fees: &FeeMarket, writable: &[Pubkey], compute_units: u32) -> FeeEstimate {
+        FeeEstimate {
+            compute_unit_price: self.compute_unit_price(fees, writable),
+            compute_unit_limit: compute_units,
+        }
+    }
+}
+
+impl fmt::Debug for FeeStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Conservative => f.write_str("Conservative"),
+            Self::Balanced => f.write_str("Balanced"),
+            Self::Aggressive => f.write_str("Aggressive"),
+            Self::Custom(_) => f.write_str("Custom(..)"),
+        }
+    }
+}
+
+/// Priority fee for one transaction.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FeeEstimate {
+    /// Compute unit price in microlamports/CU (`SetComputeUnitPrice`)
+    pub compute_unit_price: u64,
+    /// Compute units requested (`SetComputeUnitLimit`)
+    pub compute_unit_limit: u32,
+}
+
+impl FeeEstimate {
+    /// Total priority fee in microlamports.
+    pub fn priority_fee_micro_lamports(&self) -> u64 {
+        self.compute_unit_price.saturating_mul(self.compute_unit_limit as u64)
+    }
+
+    /// Total priority fee in lamports, rounded up as the runtime does.
+    pub fn priority_fee_lamports(&self) -> u64 {
+        self.priority_fee_micro_lamports().div_ceil(1_000_000)
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Percentile {
+    P25,
+    P50,
+    P75,
+    P90,
+}
+
+impl Percentile {
+    fn of(self, account: &AccountFee) -> u64 {
+        match self {
+            Self::P25 => account.p25,
+            Self::P50 => account.p50,
+            Self::P75 => account.p75,
+            Self::P90 => account.p90,
+        }
+    }
+}
+
+/// Maximum `percentile` over the writable accounts found in `fees`, falling
+/// back to every account in `fees`, then to `fees.recommended`.
+fn account_price(fees: &FeeMarket, writable: &[Pubkey], percentile: Percentile) -> u64 {
+    let written = fees
+        .accounts
+        .iter()
+        .filter(|account| writable.contains(&account.pubkey))
+        .map(|account| percentile.of(account))
+        .max();
+    written
+        .or_else(|| fees.accounts.iter().map(|account| percentile.of(account)).max())
+        .unwrap_or(fees.recommended)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{Slot, TimestampMs};
+
+    fn account(key: u8, p25: u64) -> AccountFee {
+        AccountFee {
+            pubkey: Pubkey::new([key; 32]),
+            total_txs: 10,
+            active_slots: 5,
+            cu_consumed: 1_000_000,
+            utilization_pct: 10.0,
+            p25,
+            p50: p25 * 2,
+            p75: p25 * 3,
+            p90: p25 * 4,
+            min_nonzero_price: 1,
+        }
+    }
+
+    fn market(state: NetworkState, accounts: Vec<AccountFee>) -> FeeMarket {
+        FeeMarket {
+            slot: Slot(1),
+            timestamp_ms: TimestampMs(0),
+            recommended: 7_000,
+            state,
+            is_stale: false,
+            block_utilization_pct: 50.0,
+            blocks_in_window: 10,
+            accounts,
+        }
+    }
+
+    #[test]
+    fn test_presets_follow_state_and_accounts() {
+        let fees = market(NetworkState::Normal, vec![account(1, 100), account(2, 1_000)]);
+        let cold = [Pubkey::new([1; 32])];
+        assert_eq!(FeeStrategy::Conservative.compute_unit_price(&fees, &cold), 200);
+        assert_eq!(FeeStrategy::Balanced.compute_unit_price(&fees, &cold), 300);
+        assert_eq!(FeeStrategy::Aggressive.compute_unit_price(&fees, &cold), 500);
+
+        // Max over written accounts; unknown accounts fall back to all accounts
+        let both = [Pubkey::new([1; 32]), Pubkey::new([2; 32])];
+        assert_eq!(FeeStrategy::Balanced.compute_unit_price(&fees, &both), 3_000);
+        assert_eq!(FeeStrategy::Balanced.compute_unit_price(&fees, &[Pubkey::new([9; 32])]), 3_000);
+        assert_eq!(FeeStrategy::Balanced.compute_unit_price(&market(NetworkState::Normal, vec![]), &[]), 7_000);
+
+        let extreme = market(NetworkState::Extreme, vec![account(1, 100)]);
+        assert_eq!(FeeStrategy::Balanced.compute_unit_price(&extreme, &cold), 400);
+    }
+
+    #[test]
+    fn test_custom_and_estimate() {
+        let fees = market(NetworkState::High, vec![account(1, 100)]);
+        let strategy = FeeStrategy::custom(|fees, _| fees.recommended / 2);
+        assert_eq!(format!("{:?}", strategy), "Custom(..)");
+
+        let estimate = strategy.estimate(&fees, &[], 200_000);
+        assert_eq!(estimate.compute_unit_price, 3_500);
+        assert_eq!(estimate.priority_fee_micro_lamports(), 700_000_000);
+        assert_eq!(estimate.priority_fee_lamports(), 700);
+        let estimate = FeeEstimate { compute_unit_price: 1, compute_unit_limit: 1_500_000 };
+        assert_eq!(estimate.priority_fee_lamports(), 2);
+    }
+}
@@ -1,10 +1,15 @@
 //! Fee market types (per-writable-account model).
 
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
+
+use super::{Pubkey, Slot, TimestampMs};
 
 /// Network congestion state.
+///
+/// Deserializes from either the wire code (`0`-`3`) or the state name
+/// (case-insensitive), so JSON-mode messages parse regardless of encoding.
 #[repr(u8)]
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
 pub enum NetworkState {
     /// Low congestion - minimal fees needed
     Low = 0,
@@ -30,14 +35,52 @@ impl TryFrom<u8> for NetworkState {
     }
 }
 
+impl<'de> Deserialize<'de> for NetworkState {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Repr {
+            Code(u8),
+            Name(String),
+        }
+
+        match Repr::deserialize(deserializer)? {
+            Repr::Code(code) => Self::try_from(code)
+                .map_err(|c| serde::de::Error::custom(format!("invalid network state: {}", c))),
+            Repr::Name(name) => match name.to_ascii_lowercase().as_str() {
+                "low" => Ok(Self::Low),
+                "normal" => Ok(Self::Normal),
+                "high" => Ok(Self::High),
+                "extreme" => Ok(Self::Extreme),
+                _ => Err(serde::de::Error::custom(format!("invalid network state: {}", name))),
+            },
+        }
+    }
+}
+
+impl std::fmt::Display for NetworkState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Low => "low",
+            Self::Normal => "normal",
+            Self::High => "high",
+            Self::Extreme => "extreme",
+        };
+        f.write_str(name)
+    }
+}
+
 /// Per-writable-account fee data.
 ///
 /// Solana's scheduler limits each writable account to 12M CU per block.
 /// Fee pricing is per-account: `max(p75(account) for account in writable_accounts)`.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct AccountFee {
-    /// Account public key (base58)
-    pub pubkey: String,
+    /// Account public key
+    pub pubkey: Pubkey,
     /// Total transactions touching this account in the window
     pub total_txs: u32,
     /// Number of slots where this account was active
@@ -66,9 +109,9 @@ pub struct AccountFee {
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct FeeMarket {
     /// Current Solana slot
-    pub slot: u64,
+    pub slot: Slot,
     /// Unix timestamp in milliseconds
-    pub timestamp_ms: u64,
+    pub timestamp_ms: TimestampMs,
     /// Recommended fee in microlamports/CU (max p75 across hottest accounts)
     pub recommended: u64,
     /// Network congestion state
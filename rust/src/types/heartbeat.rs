@@ -2,12 +2,16 @@
 
 use serde::{Deserialize, Serialize};
 
+use super::TimestampMs;
+
 /// Connection heartbeat with stats.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Heartbeat {
     /// Unix timestamp in milliseconds
-    pub timestamp_ms: u64,
+    pub timestamp_ms: TimestampMs,
     /// Connection uptime in seconds
+    #[serde(alias = "uptime_secs")]
     pub uptime_seconds: u64,
     /// Total messages received
     pub messages_received: u64,
@@ -1,19 +1,32 @@
 //! Core type definitions for K256 SDK.
 
 mod blockhash;
+mod clock;
+mod connection;
+mod fee_strategy;
 mod fees;
 mod heartbeat;
-mod messages;
 mod pool;
+mod protocol;
 mod price;
+mod pubkey;
 mod quote;
+mod resume;
+mod server;
 mod token;
 
 pub use blockhash::Blockhash;
+pub use clock::{Slot, TimestampMs, SLOT_DURATION};
+pub use connection::ConnectionState;
+pub use fee_strategy::{FeeEstimate, FeeFn, FeeStrategy};
 pub use fees::{AccountFee, FeeMarket, NetworkState};
 pub use heartbeat::Heartbeat;
-pub use messages::MessageType;
+pub use k256_proto::MessageType;
 pub use pool::{OrderLevel, Pool, PoolUpdate};
 pub use price::PriceEntry;
-pub use quote::Quote;
+pub use protocol::Protocol;
+pub use pubkey::{ParsePubkeyError, Pubkey, PubkeyStr};
+pub use quote::{Quote, QuoteComparison, RoutePlanStep, SwapRequest};
+pub use resume::ReplayComplete;
+pub use server::{ServerInfo, PROTOCOL_VERSION};
 pub use token::Token;
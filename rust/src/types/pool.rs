@@ -2,30 +2,44 @@
 
 use serde::{Deserialize, Serialize};
 
+use super::{Protocol, Pubkey, Slot};
+
 /// Order book level with price and size.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub struct OrderLevel {
-    /// Price in base units (u64)
+    /// Price in `token_mints[1]` atoms per `token_mints[0]` atom of the
+    /// pool, as assumed by [`PoolUpdate::mid_price`] and
+    /// [`PoolUpdate::spread_bps`]
     pub price: u64,
     /// Size in base units (u64)
     pub size: u64,
 }
 
+impl From<k256_proto::OrderLevel> for OrderLevel {
+    fn from(level: k256_proto::OrderLevel) -> Self {
+        Self {
+            price: level.price,
+            size: level.size,
+        }
+    }
+}
+
 /// Real-time pool state update from K256 WebSocket.
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub struct PoolUpdate {
     /// Global sequence number for ordering
     pub sequence: u64,
     /// Solana slot number
-    pub slot: u64,
+    pub slot: Slot,
     /// Write version within slot
     pub write_version: u64,
     /// DEX protocol name (e.g., "RaydiumClmm", "Whirlpool")
-    pub protocol_name: String,
-    /// Base58-encoded pool address
-    pub pool_address: String,
+    #[serde(alias = "protocol")]
+    pub protocol_name: Protocol,
+    /// Pool address
+    pub pool_address: Pubkey,
     /// List of token mint addresses
-    pub token_mints: Vec<String>,
+    pub token_mints: Vec<Pubkey>,
     /// List of token balances (same order as mints)
     pub token_balances: Vec<u64>,
     /// List of token decimals (same order as mints)
@@ -35,44 +49,235 @@ pub struct PoolUpdate {
     /// Best ask order level, if available
     pub best_ask: Option<OrderLevel>,
     /// Opaque pool state bytes
-    #[serde(with = "serde_bytes")]
+    #[serde(with = "serde_bytes", default)]
     pub serialized_state: Vec<u8>,
 }
 
+impl PoolUpdate {
+    /// Midpoint of `best_bid` and `best_ask`, as the price of one whole
+    /// `token_mints[0]` token in whole `token_mints[1]` tokens.
+    ///
+    /// Book prices are quote atoms per base atom and are scaled by the token
+    /// decimals. Returns `None` unless both sides of the book and the
+    /// decimals of both tokens are present, or if the decimals are out of
+    /// range.
+    pub fn mid_price(&self) -> Option<f64> {
+        let scale = 10f64.powi(self.token_decimals.first()?.checked_sub(*self.token_decimals.get(1)?)?);
+        Some(self.raw_mid_price()? * scale)
+    }
+
+    /// Bid/ask spread in basis points of the mid price.
+    pub fn spread_bps(&self) -> Option<f64> {
+        let (bid, ask) = (self.best_bid?, self.best_ask?);
+        let mid = self.raw_mid_price()?;
+        if mid <= 0.0 {
+            return None;
+        }
+        Some((ask.price as f64 - bid.price as f64) / mid * 10_000.0)
+    }
+
+    /// Price of one whole `base_mint` token in whole `quote_mint` tokens.
+    ///
+    /// Constant-product pools (Raydium AMM and CPMM) are priced from the
+    /// reserve ratio with both balances scaled by their token decimals.
+    /// Concentrated liquidity pools (Whirlpool, Raydium CLMM, Meteora DLMM)
+    /// are priced from the `sqrt_price` or active bin in `serialized_state`,
+    /// which needs the `pool-state` feature. Returns `None` for other
+    /// protocols, if either mint is not part of the pool, or if a reserve is
+    /// empty.
+    pub fn spot_price(&self, base_mint: &Pubkey, quote_mint: &Pubkey) -> Option<f64> {
+        match self.protocol_name {
+            Protocol::RaydiumAmm | Protocol::RaydiumCpmm => self.reserve_price(base_mint, quote_mint),
+            #[cfg(feature = "pool-state")]
+            Protocol::Whirlpool | Protocol::RaydiumClmm | Protocol::MeteoraDlmm => {
+                crate::pool_state::spot_price(self, base_mint, quote_mint)
+            }
+            _ => None,
+        }
+    }
+
+    /// Total value locked in the pool, denominated in `quote_mint`.
+    ///
+    /// `price_of` returns the price of a mint in `quote_mint` units (e.g. a
+    /// lookup into the price feed). The quote mint itself is valued at 1, and
+    /// tokens without an external price fall back to [`spot_price`](Self::spot_price)
+    /// against `quote_mint` when it is part of the pool. Returns `None` if any
+    /// token cannot be priced.
+    pub fn tvl_in<F>(&self, quote_mint: &Pubkey, price_of: F) -> Option<f64>
+    where
+        F: Fn(&Pubkey) -> Option<f64>,
+    {
+        let mut tvl = 0.0;
+        for (index, mint) in self.token_mints.iter().enumerate() {
+            let amount = self.ui_balance(index)?;
+            let price = if mint == quote_mint {
+                1.0
+            } else {
+                price_of(mint).or_else(|| self.spot_price(mint, quote_mint))?
+            };
+            tvl += amount * price;
+        }
+        Some(tvl)
+    }
+
+    fn raw_mid_price(&self) -> Option<f64> {
+        let (bid, ask) = (self.best_bid?, self.best_ask?);
+        Some((bid.price as f64 + ask.price as f64) / 2.0)
+    }
+
+    fn reserve_price(&self, base_mint: &Pubkey, quote_mint: &Pubkey) -> Option<f64> {
+        let base = self.ui_balance(self.mint_index(base_mint)?)?;
+        let quote = self.ui_balance(self.mint_index(quote_mint)?)?;
+        if base <= 0.0 {
+            return None;
+        }
+        Some(quote / base)
+    }
+
+    fn mint_index(&self, mint: &Pubkey) -> Option<usize> {
+        self.token_mints.iter().position(|m| m == mint)
+    }
+
+    /// Decimals of a token of the pool.
+    #[cfg_attr(not(feature = "pool-state"), allow(dead_code))]
+    pub(crate) fn decimals_of(&self, mint: &Pubkey) -> Option<i32> {
+        self.token_decimals.get(self.mint_index(mint)?).copied()
+    }
+
+    fn ui_balance(&self, index: usize) -> Option<f64> {
+        let balance = *self.token_balances.get(index)?;
+        let decimals = *self.token_decimals.get(index)?;
+        Some(crate::utils::ui_amount(balance, decimals))
+    }
+}
+
 /// DEX pool metadata.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Pool {
-    /// Base58-encoded pool address
-    pub address: String,
-    /// DEX protocol name
-    pub protocol: String,
+    /// Pool address
+    pub address: Pubkey,
+    /// DEX protocol
+    pub protocol: Protocol,
     /// First token mint address
-    pub token_a_mint: String,
+    pub token_a_mint: Pubkey,
     /// Second token mint address
-    pub token_b_mint: String,
+    pub token_b_mint: Pubkey,
     /// First token vault address
-    pub token_a_vault: String,
+    pub token_a_vault: Pubkey,
     /// Second token vault address
-    pub token_b_vault: String,
+    pub token_b_vault: Pubkey,
     /// Fee rate in basis points
     pub fee_rate: u32,
 }
 
 mod serde_bytes {
-    use serde::{Deserialize, Deserializer, Serializer};
+    use std::fmt;
 
-    pub fn serialize<S>(bytes: &Vec<u8>, serializer: S) -> Result<S::Ok, S::Error>
+    use serde::de::{SeqAccess, Visitor};
+    use serde::{Deserializer, Serializer};
+
+    pub fn serialize<S>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error>
     where
         S: Serializer,
     {
         serializer.serialize_bytes(bytes)
     }
 
+    /// Accepts raw bytes (bincode) as well as an array of integers (JSON).
     pub fn deserialize<'de, D>(deserializer: D) -> Result<Vec<u8>, D::Error>
     where
         D: Deserializer<'de>,
     {
-        let bytes: &[u8] = Deserialize::deserialize(deserializer)?;
-        Ok(bytes.to_vec())
+        struct BytesVisitor;
+
+        impl<'de> Visitor<'de> for BytesVisitor {
+            type Value = Vec<u8>;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a byte array")
+            }
+
+            fn visit_bytes<E: serde::de::Error>(self, v: &[u8]) -> Result<Vec<u8>, E> {
+                Ok(v.to_vec())
+            }
+
+            fn visit_byte_buf<E: serde::de::Error>(self, v: Vec<u8>) -> Result<Vec<u8>, E> {
+                Ok(v)
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Vec<u8>, A::Error> {
+                let mut bytes = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+                while let Some(byte) = seq.next_element()? {
+                    bytes.push(byte);
+                }
+                Ok(bytes)
+            }
+        }
+
+        deserializer.deserialize_bytes(BytesVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOL: Pubkey = Pubkey::new([1; 32]);
+    const USDC: Pubkey = Pubkey::new([2; 32]);
+    const BONK: Pubkey = Pubkey::new([3; 32]);
+
+    fn sol_usdc_update() -> PoolUpdate {
+        PoolUpdate {
+            sequence: 1,
+            slot: Slot(100),
+            write_version: 1,
+            protocol_name: Protocol::RaydiumAmm,
+            pool_address: Pubkey::default(),
+            token_mints: vec![SOL, USDC],
+            token_balances: vec![1_000 * 1_000_000_000, 150_000 * 1_000_000],
+            token_decimals: vec![9, 6],
+            best_bid: Some(OrderLevel { price: 149, size: 10 }),
+            best_ask: Some(OrderLevel { price: 151, size: 10 }),
+            serialized_state: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_spot_price_scales_decimals() {
+        let update = sol_usdc_update();
+        assert_eq!(update.spot_price(&SOL, &USDC), Some(150.0));
+        assert_eq!(update.spot_price(&USDC, &SOL), Some(1.0 / 150.0));
+        assert_eq!(update.spot_price(&SOL, &BONK), None);
+    }
+
+    #[test]
+    fn test_spot_price_needs_constant_product() {
+        let mut update = sol_usdc_update();
+        update.protocol_name = Protocol::Phoenix;
+        assert_eq!(update.spot_price(&SOL, &USDC), None);
+        // Concentrated liquidity pools are priced from their state, not reserves
+        update.protocol_name = Protocol::Whirlpool;
+        assert_eq!(update.spot_price(&SOL, &USDC), None);
+    }
+
+    #[test]
+    fn test_book_prices() {
+        let mut update = sol_usdc_update();
+        // 150 USDC atoms per lamport on average, scaled by 10^(9 - 6)
+        assert_eq!(update.mid_price(), Some(150_000.0));
+        let spread = update.spread_bps().unwrap();
+        assert!((spread - 133.333).abs() < 0.001);
+        update.token_decimals = vec![i32::MIN, 1];
+        assert_eq!(update.mid_price(), None);
+        update.token_decimals.clear();
+        assert_eq!(update.mid_price(), None);
+    }
+
+    #[test]
+    fn test_tvl_in() {
+        let update = sol_usdc_update();
+        assert_eq!(update.tvl_in(&USDC, |_| None), Some(300_000.0));
+        assert_eq!(update.tvl_in(&USDC, |_| Some(100.0)), Some(250_000.0));
+        assert_eq!(update.tvl_in(&BONK, |_| None), None);
     }
 }
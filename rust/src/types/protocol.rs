@@ -0,0 +1,161 @@
+//! DEX protocol identifiers.
+
+use std::convert::Infallible;
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// DEX protocol of a pool.
+///
+/// Travels as its name on the wire (e.g. `"Whirlpool"`). Names without a
+/// dedicated variant parse to [`Protocol::Other`] and are sent back
+/// unchanged, so new protocols stream before the SDK knows about them.
+///
+/// ```rust
+/// use k256_sdk::Protocol;
+///
+/// assert_eq!("OrcaWhirlpool".parse::<Protocol>().unwrap(), Protocol::Whirlpool);
+/// assert_eq!(Protocol::RaydiumClmm.to_string(), "RaydiumClmm");
+/// assert_eq!(Protocol::from("NewDex"), Protocol::Other("NewDex".to_string()));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Protocol {
+    /// Raydium AMM v4 (`"RaydiumAmm"`, also `"RaydiumAmmV4"`)
+    RaydiumAmm,
+    /// Raydium concentrated liquidity (`"RaydiumClmm"`)
+    RaydiumClmm,
+    /// Raydium constant product (`"RaydiumCpmm"`)
+    RaydiumCpmm,
+    /// Orca Whirlpool (`"Whirlpool"`, also `"OrcaWhirlpool"`)
+    Whirlpool,
+    /// Meteora DLMM (`"MeteoraDlmm"`)
+    MeteoraDlmm,
+    /// Phoenix order book (`"Phoenix"`)
+    Phoenix,
+    /// Lifinity (`"Lifinity"`)
+    Lifinity,
+    /// Any other protocol, by wire name
+    Other(String),
+}
+
+impl Protocol {
+    /// Wire name of the protocol.
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::RaydiumAmm => "RaydiumAmm",
+            Self::RaydiumClmm => "RaydiumClmm",
+            Self::RaydiumCpmm => "RaydiumCpmm",
+            Self::Whirlpool => "Whirlpool",
+            Self::MeteoraDlmm => "MeteoraDlmm",
+            Self::Phoenix => "Phoenix",
+            Self::Lifinity => "Lifinity",
+            Self::Other(name) => name,
+        }
+    }
+
+    fn known(name: &str) -> Option<Self> {
+        let protocol = match name {
+            "RaydiumAmm" | "RaydiumAmmV4" => Self::RaydiumAmm,
+            "RaydiumClmm" => Self::RaydiumClmm,
+            "RaydiumCpmm" => Self::RaydiumCpmm,
+            "Whirlpool" | "OrcaWhirlpool" => Self::Whirlpool,
+            "MeteoraDlmm" => Self::MeteoraDlmm,
+            "Phoenix" => Self::Phoenix,
+            "Lifinity" => Self::Lifinity,
+            _ => return None,
+        };
+        Some(protocol)
+    }
+}
+
+/// Empty [`Protocol::Other`], as in a default [`PoolUpdate`](super::PoolUpdate).
+impl Default for Protocol {
+    fn default() -> Self {
+        Self::Other(String::new())
+    }
+}
+
+impl FromStr for Protocol {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self::from(s))
+    }
+}
+
+impl From<&str> for Protocol {
+    fn from(name: &str) -> Self {
+        Self::known(name).unwrap_or_else(|| Self::Other(name.to_string()))
+    }
+}
+
+impl From<String> for Protocol {
+    fn from(name: String) -> Self {
+        Self::known(&name).unwrap_or(Self::Other(name))
+    }
+}
+
+impl fmt::Display for Protocol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl PartialEq<str> for Protocol {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl PartialEq<&str> for Protocol {
+    fn eq(&self, other: &&str) -> bool {
+        self == *other
+    }
+}
+
+impl Serialize for Protocol {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for Protocol {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(Self::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_names_roundtrip() {
+        for protocol in [
+            Protocol::RaydiumAmm,
+            Protocol::RaydiumClmm,
+            Protocol::RaydiumCpmm,
+            Protocol::Whirlpool,
+            Protocol::MeteoraDlmm,
+            Protocol::Phoenix,
+            Protocol::Lifinity,
+            Protocol::Other("NewDex".to_string()),
+        ] {
+            assert_eq!(protocol.to_string().parse::<Protocol>().unwrap(), protocol);
+        }
+        assert_eq!(Protocol::from("RaydiumAmmV4"), Protocol::RaydiumAmm);
+        assert_eq!(Protocol::from("whirlpool".to_string()), Protocol::Other("whirlpool".to_string()));
+    }
+
+    #[test]
+    fn test_serde_as_string() {
+        assert_eq!(serde_json::to_string(&Protocol::Whirlpool).unwrap(), r#""Whirlpool""#);
+        let protocols: Vec<Protocol> = serde_json::from_str(r#"["MeteoraDlmm","NewDex"]"#).unwrap();
+        assert_eq!(protocols, [Protocol::MeteoraDlmm, Protocol::Other("NewDex".to_string())]);
+
+        let bytes = bincode::serialize(&Protocol::Phoenix).unwrap();
+        assert_eq!(bincode::deserialize::<Protocol>(&bytes).unwrap(), Protocol::Phoenix);
+    }
+}
@@ -0,0 +1,231 @@
+//! Solana public key type.
+
+use std::fmt;
+use std::ops::Deref;
+use std::str::FromStr;
+
+use serde::de::{self, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use thiserror::Error;
+
+/// Maximum base58 length of a 32-byte key.
+const MAX_BASE58_LEN: usize = 44;
+
+/// 32-byte Solana public key.
+///
+/// Decoded messages carry the raw key bytes; base58 encoding happens only
+/// when a key is displayed, serialized or compared against a string. Use
+/// [`encode`](Self::encode) to get an allocation-free `&str`.
+///
+/// Serializes as a base58 string in human-readable formats (JSON) and as
+/// raw bytes otherwise. Laid out exactly as its bytes, so a `[Pubkey]` can
+/// be handed out as `[[u8; 32]]`.
+#[repr(transparent)]
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Pubkey([u8; 32]);
+
+impl Pubkey {
+    /// Key from raw bytes.
+    pub const fn new(bytes: [u8; 32]) -> Self {
+        Self(bytes)
+    }
+
+    /// Key from a 32-byte slice, or `None` for any other length.
+    pub fn try_from_slice(bytes: &[u8]) -> Option<Self> {
+        bytes.try_into().ok().map(Self)
+    }
+
+    /// Raw key bytes.
+    pub const fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+
+    /// Copy of the raw key bytes.
+    pub const fn to_bytes(self) -> [u8; 32] {
+        self.0
+    }
+
+    /// Base58 encoding on the stack.
+    pub fn encode(&self) -> PubkeyStr {
+        let mut buf = [0u8; MAX_BASE58_LEN];
+        let len = bs58::encode(&self.0)
+            .onto(&mut buf[..])
+            .expect("32 bytes always fit in 44 base58 characters");
+        PubkeyStr { buf, len }
+    }
+}
+
+impl From<[u8; 32]> for Pubkey {
+    fn from(bytes: [u8; 32]) -> Self {
+        Self(bytes)
+    }
+}
+
+impl AsRef<[u8]> for Pubkey {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl fmt::Display for Pubkey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.encode())
+    }
+}
+
+impl fmt::Debug for Pubkey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Pubkey({})", self.encode())
+    }
+}
+
+/// Error parsing a [`Pubkey`] from a string.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum ParsePubkeyError {
+    /// Not valid base58
+    #[error("Invalid base58: {0}")]
+    InvalidBase58(#[from] bs58::decode::Error),
+
+    /// Does not decode to exactly 32 bytes
+    #[error("Invalid pubkey length: expected 32 bytes")]
+    InvalidLength,
+}
+
+impl FromStr for Pubkey {
+    type Err = ParsePubkeyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // Reject overlong input before decoding it
+        if s.len() > MAX_BASE58_LEN {
+            return Err(ParsePubkeyError::InvalidLength);
+        }
+        let mut bytes = [0u8; 32];
+        match bs58::decode(s).onto(&mut bytes) {
+            Ok(32) => Ok(Self(bytes)),
+            Ok(_) | Err(bs58::decode::Error::BufferTooSmall) => Err(ParsePubkeyError::InvalidLength),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+impl PartialEq<str> for Pubkey {
+    fn eq(&self, other: &str) -> bool {
+        self.encode().as_str() == other
+    }
+}
+
+impl PartialEq<&str> for Pubkey {
+    fn eq(&self, other: &&str) -> bool {
+        self == *other
+    }
+}
+
+impl Serialize for Pubkey {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.serialize_str(&self.encode())
+        } else {
+            self.0.serialize(serializer)
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Pubkey {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if !deserializer.is_human_readable() {
+            return <[u8; 32]>::deserialize(deserializer).map(Self);
+        }
+
+        struct PubkeyVisitor;
+
+        impl<'de> Visitor<'de> for PubkeyVisitor {
+            type Value = Pubkey;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a base58-encoded public key")
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<Pubkey, E> {
+                v.parse().map_err(E::custom)
+            }
+        }
+
+        deserializer.deserialize_str(PubkeyVisitor)
+    }
+}
+
+/// Base58-encoded [`Pubkey`] held inline, without allocation.
+///
+/// Dereferences to `str`.
+#[derive(Clone, Copy)]
+pub struct PubkeyStr {
+    buf: [u8; MAX_BASE58_LEN],
+    len: usize,
+}
+
+impl PubkeyStr {
+    /// The encoded key.
+    pub fn as_str(&self) -> &str {
+        // bs58 only writes ASCII alphabet characters
+        std::str::from_utf8(&self.buf[..self.len]).expect("base58 is ASCII")
+    }
+}
+
+impl Deref for PubkeyStr {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl fmt::Display for PubkeyStr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl fmt::Debug for PubkeyStr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const USDC: &str = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+
+    #[test]
+    fn test_base58_roundtrip() {
+        let key: Pubkey = USDC.parse().unwrap();
+        assert_eq!(key.encode().as_str(), USDC);
+        assert_eq!(key.to_string(), USDC);
+        assert_eq!(key, USDC);
+        assert_eq!(Pubkey::default().to_string(), "11111111111111111111111111111111");
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(matches!("0OIl".parse::<Pubkey>(), Err(ParsePubkeyError::InvalidBase58(_))));
+        assert!(matches!("abc".parse::<Pubkey>(), Err(ParsePubkeyError::InvalidLength)));
+        assert!(matches!(
+            "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1vEP".parse::<Pubkey>(),
+            Err(ParsePubkeyError::InvalidLength)
+        ));
+        assert!(Pubkey::try_from_slice(&[0u8; 31]).is_none());
+    }
+
+    #[test]
+    fn test_serde_formats() {
+        let key: Pubkey = USDC.parse().unwrap();
+        let json = serde_json::to_string(&key).unwrap();
+        assert_eq!(json, format!("\"{}\"", USDC));
+        assert_eq!(serde_json::from_str::<Pubkey>(&json).unwrap(), key);
+
+        let bytes = bincode::serialize(&key).unwrap();
+        assert_eq!(bytes, key.to_bytes());
+        assert_eq!(bincode::deserialize::<Pubkey>(&bytes).unwrap(), key);
+    }
+}
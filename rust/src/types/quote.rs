@@ -1,14 +1,22 @@
 //! Quote types.
 
-use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+
+use serde::{Deserialize, Deserializer, Serialize};
+
+use super::{Protocol, Pubkey, Slot, TimestampMs};
+use crate::error::K256Error;
+
+/// One hundred percent in basis points.
+const BPS_DENOMINATOR: u128 = 10_000;
 
 /// Swap quote from K256.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Quote {
     /// Input token mint address
-    pub input_mint: String,
+    pub input_mint: Pubkey,
     /// Output token mint address
-    pub output_mint: String,
+    pub output_mint: Pubkey,
     /// Input amount in base units
     pub in_amount: u64,
     /// Output amount in base units
@@ -16,19 +24,504 @@ pub struct Quote {
     /// Price impact percentage
     pub price_impact_pct: f64,
     /// Solana slot of the quote
-    pub slot: u64,
+    pub slot: Slot,
     /// Unix timestamp in milliseconds
-    pub timestamp_ms: u64,
+    pub timestamp_ms: TimestampMs,
     /// List of route steps
-    pub route_plan: Vec<serde_json::Value>,
+    pub route_plan: Vec<RoutePlanStep>,
     /// Minimum output (or max input for exactOut)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub other_amount_threshold: Option<u64>,
     /// "ExactIn" or "ExactOut"
     #[serde(default = "default_swap_mode")]
     pub swap_mode: String,
+    /// Request ID of the quote request this quote answers, `None` for
+    /// streamed and REST quotes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
 }
 
 fn default_swap_mode() -> String {
     "ExactIn".to_string()
 }
+
+/// Single hop of a quote's route.
+///
+/// Hops run in order, each spending the previous hop's output. Consecutive
+/// hops between the same mints with a `percent` below 100 split one leg of
+/// the route across venues (see [`Quote::validate_route`]).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RoutePlanStep {
+    /// DEX/protocol of the pool
+    #[serde(alias = "protocol", alias = "label")]
+    pub venue: Protocol,
+    /// Pool address
+    #[serde(rename = "pool_address", alias = "poolAddress", alias = "amm_key", alias = "ammKey", alias = "pool")]
+    pub pool: Pubkey,
+    /// Input mint for this hop
+    #[serde(rename = "input_mint", alias = "inputMint", alias = "in_mint")]
+    pub in_mint: Pubkey,
+    /// Output mint for this hop
+    #[serde(rename = "output_mint", alias = "outputMint", alias = "out_mint")]
+    pub out_mint: Pubkey,
+    /// Input amount in base units
+    #[serde(alias = "inAmount", default, deserialize_with = "amount")]
+    pub in_amount: u64,
+    /// Output amount in base units
+    #[serde(alias = "outAmount", default, deserialize_with = "amount")]
+    pub out_amount: u64,
+    /// Fee charged by the venue, in base units of `fee_mint`
+    #[serde(alias = "feeAmount", alias = "fee", default, deserialize_with = "amount")]
+    pub fee_amount: u64,
+    /// Mint the fee is charged in
+    #[serde(alias = "feeMint", default, skip_serializing_if = "Option::is_none")]
+    pub fee_mint: Option<Pubkey>,
+    /// Venue fee rate in basis points
+    #[serde(alias = "feeBps", default, skip_serializing_if = "Option::is_none")]
+    pub fee_bps: Option<u16>,
+    /// Share of the input routed through this hop (0-100)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub percent: Option<u8>,
+}
+
+/// Swap payload for the K256 `/v1/swap` endpoint, built from a [`Quote`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SwapRequest {
+    /// Wallet that signs and pays for the swap
+    pub user_public_key: Pubkey,
+    /// Input token mint address
+    pub input_mint: Pubkey,
+    /// Output token mint address
+    pub output_mint: Pubkey,
+    /// Input amount in base units
+    #[serde(with = "string_amount")]
+    pub in_amount: u64,
+    /// Output amount in base units
+    #[serde(with = "string_amount")]
+    pub out_amount: u64,
+    /// Minimum output (or max input for ExactOut)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub other_amount_threshold: Option<u64>,
+    /// "ExactIn" or "ExactOut"
+    pub swap_mode: String,
+    /// Slippage tolerance in basis points
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub slippage_bps: Option<u16>,
+    /// Route to execute
+    pub route_plan: Vec<RoutePlanStep>,
+    /// Slot the quote was computed at
+    pub context_slot: Slot,
+    /// Wrap/unwrap SOL automatically
+    pub wrap_and_unwrap_sol: bool,
+    /// Compute unit price in micro-lamports
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub compute_unit_price_micro_lamports: Option<u64>,
+}
+
+impl SwapRequest {
+    /// Set the slippage tolerance in basis points.
+    pub fn with_slippage_bps(mut self, slippage_bps: u16) -> Self {
+        self.slippage_bps = Some(slippage_bps);
+        self
+    }
+
+    /// Set the compute unit price in micro-lamports.
+    pub fn with_compute_unit_price(mut self, micro_lamports: u64) -> Self {
+        self.compute_unit_price_micro_lamports = Some(micro_lamports);
+        self
+    }
+
+    /// Enable or disable automatic SOL wrapping (enabled by default).
+    pub fn with_wrap_and_unwrap_sol(mut self, wrap: bool) -> Self {
+        self.wrap_and_unwrap_sol = wrap;
+        self
+    }
+}
+
+impl Quote {
+    /// Build a swap request executing this quote for `user_public_key`.
+    pub fn to_swap_request(&self, user_public_key: Pubkey) -> SwapRequest {
+        SwapRequest {
+            user_public_key,
+            input_mint: self.input_mint,
+            output_mint: self.output_mint,
+            in_amount: self.in_amount,
+            out_amount: self.out_amount,
+            other_amount_threshold: self.other_amount_threshold,
+            swap_mode: self.swap_mode.clone(),
+            slippage_bps: None,
+            route_plan: self.route_plan.clone(),
+            context_slot: self.slot,
+            wrap_and_unwrap_sol: true,
+            compute_unit_price_micro_lamports: None,
+        }
+    }
+
+    /// Whether this is an `ExactOut` quote (the output amount is fixed).
+    pub fn is_exact_out(&self) -> bool {
+        self.swap_mode == "ExactOut"
+    }
+
+    /// Minimum acceptable output at `slippage_bps` tolerance, rounded down.
+    pub fn min_out_with_slippage(&self, slippage_bps: u16) -> u64 {
+        let keep = BPS_DENOMINATOR.saturating_sub(slippage_bps as u128);
+        (self.out_amount as u128 * keep / BPS_DENOMINATOR) as u64
+    }
+
+    /// Maximum acceptable input at `slippage_bps` tolerance, rounded up.
+    ///
+    /// The `ExactOut` counterpart of
+    /// [`min_out_with_slippage`](Self::min_out_with_slippage).
+    pub fn max_in_with_slippage(&self, slippage_bps: u16) -> u64 {
+        let scaled = self.in_amount as u128 * (BPS_DENOMINATOR + slippage_bps as u128);
+        scaled.div_ceil(BPS_DENOMINATOR).min(u64::MAX as u128) as u64
+    }
+
+    /// Output per input in base units, or `None` for a zero input.
+    ///
+    /// Use [`effective_ui_price`](Self::effective_ui_price) for a price in
+    /// whole tokens.
+    pub fn effective_price(&self) -> Option<f64> {
+        (self.in_amount != 0).then(|| self.out_amount as f64 / self.in_amount as f64)
+    }
+
+    /// Output per input in whole tokens, given both mints' decimals.
+    pub fn effective_ui_price(&self, input_decimals: i32, output_decimals: i32) -> Option<f64> {
+        Some(self.effective_price()? * 10f64.powi(input_decimals - output_decimals))
+    }
+
+    /// Check that `swap_mode` is known, `other_amount_threshold` bounds
+    /// the right amount (at most `out_amount` for `ExactIn`, at least
+    /// `in_amount` for `ExactOut`) and the route plan is consistent (see
+    /// [`validate_route`](Self::validate_route)).
+    pub fn validate(&self) -> Result<(), K256Error> {
+        let exact_out = match self.swap_mode.as_str() {
+            "ExactIn" => false,
+            "ExactOut" => true,
+            other => return Err(K256Error::InvalidSwapMode(other.to_string())),
+        };
+        if let Some(threshold) = self.other_amount_threshold {
+            let consistent = if exact_out {
+                threshold >= self.in_amount
+            } else {
+                threshold <= self.out_amount
+            };
+            if !consistent {
+                return Err(K256Error::InconsistentThreshold {
+                    swap_mode: self.swap_mode.clone(),
+                    threshold,
+                    amount: if exact_out { self.in_amount } else { self.out_amount },
+                });
+            }
+        }
+        self.validate_route()
+    }
+
+    /// Check that the route plan is structurally sound: each leg's output
+    /// mint and amount feed the next leg, and the route starts with the
+    /// quote's input and ends with its output.
+    ///
+    /// A leg is one hop, or consecutive hops between the same mints with a
+    /// `percent` below 100 whose amounts add up. An empty route plan passes.
+    pub fn validate_route(&self) -> Result<(), K256Error> {
+        let Some(first) = self.route_plan.first() else {
+            return Ok(());
+        };
+        let invalid = |hop: usize, reason: String| Err(K256Error::InvalidRoute { hop, reason });
+        if first.in_mint != self.input_mint {
+            return invalid(0, format!("starts with {} instead of input mint {}", first.in_mint, self.input_mint));
+        }
+
+        let mut hop = 0;
+        let mut previous: Option<(Pubkey, u64)> = None;
+        while hop < self.route_plan.len() {
+            let start = hop;
+            let leg = &self.route_plan[start];
+            let (mut in_amount, mut out_amount, mut percent) = (leg.in_amount, leg.out_amount, leg.percent);
+            hop += 1;
+            while leg.percent.is_some_and(|p| p < 100) && hop < self.route_plan.len() {
+                let split = &self.route_plan[hop];
+                if (split.in_mint, split.out_mint) != (leg.in_mint, leg.out_mint) || percent == Some(100) {
+                    break;
+                }
+                in_amount = in_amount.saturating_add(split.in_amount);
+                out_amount = out_amount.saturating_add(split.out_amount);
+                percent = Some(percent.unwrap_or(0).saturating_add(split.percent.unwrap_or(0)));
+                hop += 1;
+            }
+            if percent.is_some_and(|p| p != 100) {
+                return invalid(start, format!("split percentages add up to {}", percent.unwrap_or(0)));
+            }
+            if let Some((mint, amount)) = previous {
+                if leg.in_mint != mint {
+                    return invalid(start, format!("takes {} but the previous hop outputs {}", leg.in_mint, mint));
+                }
+                if in_amount != amount {
+                    return invalid(start, format!("takes {} but the previous hop outputs {}", in_amount, amount));
+                }
+            } else if in_amount != self.in_amount {
+                return invalid(start, format!("takes {} but the quote's input is {}", in_amount, self.in_amount));
+            }
+            previous = Some((leg.out_mint, out_amount));
+        }
+
+        let last = self.route_plan.len() - 1;
+        let (mint, amount) = previous.unwrap_or_default();
+        if mint != self.output_mint {
+            return invalid(last, format!("ends with {} instead of output mint {}", mint, self.output_mint));
+        }
+        if amount != self.out_amount {
+            return invalid(last, format!("outputs {} but the quote's output is {}", amount, self.out_amount));
+        }
+        Ok(())
+    }
+
+    /// Compare the rate of this quote with `other` for the same swap.
+    ///
+    /// Rates (output per input) are compared exactly, so quotes for
+    /// different amounts can be compared too.
+    pub fn compare(&self, other: &Quote) -> Result<QuoteComparison, K256Error> {
+        if self.input_mint != other.input_mint || self.output_mint != other.output_mint {
+            return Err(K256Error::IncomparableQuotes("different mints"));
+        }
+        if self.swap_mode != other.swap_mode {
+            return Err(K256Error::IncomparableQuotes("different swap modes"));
+        }
+
+        // self.out / self.in vs other.out / other.in, cross-multiplied
+        let ours = self.out_amount as u128 * other.in_amount as u128;
+        let theirs = other.out_amount as u128 * self.in_amount as u128;
+        let improvement_bps = if theirs == 0 {
+            0.0
+        } else {
+            (ours as f64 / theirs as f64 - 1.0) * BPS_DENOMINATOR as f64
+        };
+        Ok(QuoteComparison {
+            ordering: ours.cmp(&theirs),
+            improvement_bps,
+        })
+    }
+}
+
+/// Result of [`Quote::compare`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct QuoteComparison {
+    /// `Greater` if the compared quote gives the better rate
+    pub ordering: Ordering,
+    /// Rate improvement over the other quote, in basis points (negative if worse)
+    pub improvement_bps: f64,
+}
+
+impl QuoteComparison {
+    /// Whether the compared quote gives a strictly better rate.
+    pub fn is_better(&self) -> bool {
+        self.ordering == Ordering::Greater
+    }
+}
+
+/// Amounts may be sent as JSON numbers or decimal strings.
+fn amount<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Amount {
+        Number(u64),
+        String(String),
+    }
+
+    match Amount::deserialize(deserializer)? {
+        Amount::Number(n) => Ok(n),
+        Amount::String(s) => s.parse().map_err(serde::de::Error::custom),
+    }
+}
+
+/// The swap endpoint takes amounts as strings to avoid precision loss in JS clients.
+mod string_amount {
+    use serde::{Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(value: &u64, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(value)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+        super::amount(deserializer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn mint(n: u8) -> Pubkey {
+        Pubkey::new([n; 32])
+    }
+
+    fn step(in_mint: u8, out_mint: u8, in_amount: u64, out_amount: u64, percent: Option<u8>) -> RoutePlanStep {
+        RoutePlanStep {
+            venue: Protocol::Whirlpool,
+            pool: mint(100 + in_mint),
+            in_mint: mint(in_mint),
+            out_mint: mint(out_mint),
+            in_amount,
+            out_amount,
+            fee_amount: 0,
+            fee_mint: None,
+            fee_bps: Some(30),
+            percent,
+        }
+    }
+
+    #[test]
+    fn test_route_plan_step_accepts_camel_case_and_string_amounts() {
+        let step: RoutePlanStep = serde_json::from_value(json!({
+            "protocol": "Whirlpool",
+            "poolAddress": mint(9).to_string(),
+            "inputMint": mint(1).to_string(),
+            "outputMint": mint(2).to_string(),
+            "inAmount": "1000",
+            "outAmount": 990,
+            "fee": 3,
+            "feeBps": 25,
+        }))
+        .unwrap();
+        assert_eq!(step.venue, Protocol::Whirlpool);
+        assert_eq!((step.pool, step.in_mint), (mint(9), mint(1)));
+        assert_eq!(step.in_amount, 1000);
+        assert_eq!((step.fee_amount, step.fee_bps), (3, Some(25)));
+        assert_eq!(step.percent, None);
+
+        let value = serde_json::to_value(&step).unwrap();
+        assert_eq!(value["pool_address"], mint(9).to_string());
+        assert_eq!(value["venue"], "Whirlpool");
+        assert!(serde_json::from_value::<RoutePlanStep>(json!({ "venue": "Whirlpool", "pool": "pool" })).is_err());
+    }
+
+    #[test]
+    fn test_validate_route() {
+        let mut q = quote(1_000, 900);
+        q.input_mint = mint(1);
+        q.output_mint = mint(3);
+        q.route_plan = vec![step(1, 2, 1_000, 500, None), step(2, 3, 500, 900, None)];
+        assert!(q.validate().is_ok());
+
+        // Split second leg
+        q.route_plan = vec![
+            step(1, 2, 1_000, 500, Some(100)),
+            step(2, 3, 300, 540, Some(60)),
+            step(2, 3, 200, 360, Some(40)),
+        ];
+        assert!(q.validate_route().is_ok());
+
+        q.route_plan[2].percent = Some(30);
+        assert!(matches!(q.validate_route(), Err(K256Error::InvalidRoute { hop: 1, .. })));
+        q.route_plan[2].percent = Some(40);
+        q.route_plan[2].in_amount = 100;
+        assert!(matches!(q.validate_route(), Err(K256Error::InvalidRoute { hop: 1, .. })));
+
+        q.route_plan = vec![step(1, 2, 1_000, 500, None), step(4, 3, 500, 900, None)];
+        assert!(matches!(q.validate(), Err(K256Error::InvalidRoute { hop: 1, .. })));
+        q.route_plan = vec![step(1, 2, 1_000, 500, None), step(2, 3, 500, 899, None)];
+        assert!(matches!(q.validate_route(), Err(K256Error::InvalidRoute { hop: 1, .. })));
+        q.route_plan = vec![step(2, 3, 1_000, 900, None)];
+        assert!(matches!(q.validate_route(), Err(K256Error::InvalidRoute { hop: 0, .. })));
+        q.route_plan = vec![step(1, 3, 999, 900, None)];
+        assert!(matches!(q.validate_route(), Err(K256Error::InvalidRoute { hop: 0, .. })));
+    }
+
+    fn quote(in_amount: u64, out_amount: u64) -> Quote {
+        Quote {
+            input_mint: mint(1),
+            output_mint: mint(2),
+            in_amount,
+            out_amount,
+            price_impact_pct: 0.0,
+            slot: Slot(1),
+            timestamp_ms: TimestampMs(0),
+            route_plan: Vec::new(),
+            other_amount_threshold: None,
+            swap_mode: default_swap_mode(),
+            request_id: None,
+        }
+    }
+
+    #[test]
+    fn test_slippage_rounding() {
+        let q = quote(1_001, 999);
+        assert_eq!(q.min_out_with_slippage(50), 994); // 994.005 rounds down
+        assert_eq!(q.max_in_with_slippage(50), 1_007); // 1006.005 rounds up
+        assert_eq!(q.min_out_with_slippage(0), 999);
+        assert_eq!(q.min_out_with_slippage(u16::MAX), 0);
+        assert_eq!(quote(u64::MAX, u64::MAX).max_in_with_slippage(100), u64::MAX);
+        assert_eq!(quote(u64::MAX, u64::MAX).min_out_with_slippage(0), u64::MAX);
+    }
+
+    #[test]
+    fn test_effective_price() {
+        let q = quote(1_000_000_000, 150_000_000);
+        assert_eq!(q.effective_price(), Some(0.15));
+        assert!((q.effective_ui_price(9, 6).unwrap() - 150.0).abs() < 1e-9);
+        assert_eq!(quote(0, 1).effective_price(), None);
+    }
+
+    #[test]
+    fn test_validate_threshold() {
+        let mut q = quote(1_000, 990);
+        assert!(q.validate().is_ok());
+        q.other_amount_threshold = Some(980);
+        assert!(q.validate().is_ok());
+        q.other_amount_threshold = Some(995);
+        assert!(matches!(
+            q.validate(),
+            Err(K256Error::InconsistentThreshold { threshold: 995, amount: 990, .. })
+        ));
+
+        q.swap_mode = "ExactOut".to_string();
+        assert!(q.validate().is_err());
+        q.other_amount_threshold = Some(1_010);
+        assert!(q.validate().is_ok());
+
+        q.swap_mode = "exact_in".to_string();
+        assert!(matches!(q.validate(), Err(K256Error::InvalidSwapMode(_))));
+    }
+
+    #[test]
+    fn test_compare() {
+        let better = quote(1_000, 995);
+        let worse = quote(2_000, 1_980);
+        let cmp = better.compare(&worse).unwrap();
+        assert!(cmp.is_better());
+        assert!((cmp.improvement_bps - 50.505).abs() < 0.01);
+        assert_eq!(worse.compare(&better).unwrap().ordering, Ordering::Less);
+        assert_eq!(better.compare(&better).unwrap().ordering, Ordering::Equal);
+
+        let mut other = quote(1_000, 995);
+        other.output_mint = mint(3);
+        assert!(matches!(better.compare(&other), Err(K256Error::IncomparableQuotes(_))));
+    }
+
+    #[test]
+    fn test_to_swap_request() {
+        let quote = Quote {
+            input_mint: mint(1),
+            output_mint: mint(2),
+            in_amount: 1000,
+            out_amount: 990,
+            price_impact_pct: 0.1,
+            slot: Slot(42),
+            timestamp_ms: TimestampMs(0),
+            route_plan: Vec::new(),
+            other_amount_threshold: Some(980),
+            swap_mode: default_swap_mode(),
+            request_id: None,
+        };
+        let request = quote.to_swap_request(mint(7)).with_slippage_bps(50);
+        let value = serde_json::to_value(&request).unwrap();
+        assert_eq!(value["userPublicKey"], mint(7).to_string());
+        assert_eq!(value["inputMint"], mint(1).to_string());
+        assert_eq!(value["inAmount"], "1000");
+        assert_eq!(value["contextSlot"], 42);
+        assert_eq!(value["slippageBps"], 50);
+        assert!(value.get("computeUnitPriceMicroLamports").is_none());
+    }
+}
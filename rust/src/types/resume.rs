@@ -0,0 +1,34 @@
+//! Subscription resume types.
+
+use serde::{Deserialize, Serialize};
+
+/// End of the pool updates replayed after a resumed subscription.
+///
+/// Sent once the server has replayed the updates after the subscription's
+/// `resume_from` sequence; live updates follow.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReplayComplete {
+    /// Sequence the client asked to resume after
+    pub resume_from: u64,
+    /// First replayed sequence
+    pub from_sequence: u64,
+    /// Last replayed sequence
+    pub to_sequence: u64,
+    /// Number of replayed updates
+    pub replayed: u64,
+    /// Whether the server's replay buffer no longer reached back to
+    /// `resume_from`, so updates before `from_sequence` were missed
+    pub truncated: bool,
+}
+
+impl ReplayComplete {
+    /// Updates the server could not replay.
+    pub fn missed(&self) -> u64 {
+        if self.truncated {
+            self.from_sequence.saturating_sub(self.resume_from + 1)
+        } else {
+            0
+        }
+    }
+}
@@ -0,0 +1,38 @@
+//! Server handshake types.
+
+use serde::{Deserialize, Serialize};
+
+/// Binary protocol revision this SDK decodes.
+///
+/// Sent as `protocol_version` with every subscription request; the gateway
+/// reports the revision it speaks in the `subscribed` confirmation.
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// Server details reported when a subscription is confirmed.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ServerInfo {
+    /// Binary protocol revision the server speaks, if reported
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub protocol_version: Option<u32>,
+    /// Server software version, if reported
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub server_version: Option<String>,
+    /// Optional features the server supports (e.g. server-side filters)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub features: Vec<String>,
+}
+
+impl ServerInfo {
+    /// Whether the server speaks a newer protocol revision than
+    /// [`PROTOCOL_VERSION`], so some frames may carry fields or message
+    /// types this SDK does not know.
+    pub fn is_newer(&self) -> bool {
+        self.protocol_version.is_some_and(|version| version > PROTOCOL_VERSION)
+    }
+
+    /// Whether the server reported support for `feature`.
+    pub fn supports(&self, feature: &str) -> bool {
+        self.features.iter().any(|supported| supported == feature)
+    }
+}
@@ -3,11 +3,13 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+use super::Pubkey;
+
 /// Token metadata.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Token {
     /// Token mint address
-    pub address: String,
+    pub address: Pubkey,
     /// Token symbol (e.g., "SOL", "USDC")
     pub symbol: String,
     /// Token name
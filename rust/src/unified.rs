@@ -0,0 +1,463 @@
+//! One client for the liquidity and leader-schedule feeds.
+//!
+//! [`K256Client`] runs a [`K256WebSocketClient`] and a
+//! [`LeaderWebSocketClient`] from one [`K256ClientConfig`]: the API key,
+//! auth, TLS, proxy and reconnect policy are set once and applied to both.
+//! Messages from both feeds arrive on a single bounded [`K256Event`] queue;
+//! events arriving while it is full are dropped and counted.
+//!
+//! The leader feed runs on its own thread with the blocking client and is
+//! reconnected with the same exponential backoff as the liquidity feed.
+//! [`K256Client::close`], or dropping the client, disconnects both feeds.
+//!
+//! # Example
+//! ```rust,no_run
+//! use k256_sdk::unified::{K256Client, K256ClientConfig, K256Event};
+//! use k256_sdk::SubscribeRequest;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+//! let client = K256Client::new(K256ClientConfig {
+//!     api_key: "your-api-key".to_string(),
+//!     ..Default::default()
+//! });
+//! client.connect().await?;
+//! client.liquidity().subscribe(SubscribeRequest::default()).await?;
+//!
+//! while let Some(event) = client.next_event().await {
+//!     match event {
+//!         K256Event::Liquidity(msg) => println!("liquidity: {:?}", msg),
+//!         K256Event::Leader(msg) => println!("leader: {}", msg.msg_type),
+//!         K256Event::ConnectionState { feed, state } => println!("{:?}: {}", feed, state),
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tokio::sync::mpsc;
+use tracing::{error, warn};
+
+use crate::connector::{AuthMethod, ProxyConfig, TlsConfig};
+use crate::leader_ws::{LeaderCloseHandle, LeaderConfig, LeaderMessage, LeaderWebSocketClient};
+use crate::types::ConnectionState;
+use crate::ws::{Config, DecodedMessage, K256WebSocketClient};
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Default [`K256ClientConfig::event_queue_capacity`].
+pub const DEFAULT_EVENT_QUEUE_CAPACITY: usize = 1024;
+
+/// Configuration for [`K256Client`].
+///
+/// The shared fields override the matching fields of `liquidity` and
+/// `leader`, which supply the endpoints and feed-specific options.
+#[derive(Debug, Clone)]
+pub struct K256ClientConfig {
+    /// K256 API key
+    pub api_key: String,
+    /// How the API key is sent on the handshakes
+    pub auth: AuthMethod,
+    /// Custom TLS roots and client certificate
+    pub tls: Option<TlsConfig>,
+    /// HTTP or SOCKS5 proxy
+    pub proxy: Option<ProxyConfig>,
+    /// Whether to automatically reconnect either feed
+    pub reconnect: bool,
+    /// Initial reconnect delay
+    pub reconnect_delay_initial: Duration,
+    /// Maximum reconnect delay
+    pub reconnect_delay_max: Duration,
+    /// Liquidity feed options
+    pub liquidity: Config,
+    /// Leader-schedule feed options (`None` to run the liquidity feed only)
+    pub leader: Option<LeaderConfig>,
+    /// Events waiting for [`K256Client::next_event`] before new ones are
+    /// dropped (see [`K256Client::dropped_events`])
+    pub event_queue_capacity: usize,
+}
+
+impl Default for K256ClientConfig {
+    fn default() -> Self {
+        let liquidity = Config::default();
+        Self {
+            api_key: String::new(),
+            auth: liquidity.auth,
+            tls: None,
+            proxy: None,
+            reconnect: liquidity.reconnect,
+            reconnect_delay_initial: liquidity.reconnect_delay_initial,
+            reconnect_delay_max: liquidity.reconnect_delay_max,
+            liquidity,
+            leader: Some(LeaderConfig::default()),
+            event_queue_capacity: DEFAULT_EVENT_QUEUE_CAPACITY,
+        }
+    }
+}
+
+impl K256ClientConfig {
+    fn liquidity_config(&self) -> Config {
+        Config {
+            api_key: self.api_key.clone(),
+            auth: self.auth,
+            tls: self.tls.clone(),
+            proxy: self.proxy.clone(),
+            reconnect: self.reconnect,
+            reconnect_delay_initial: self.reconnect_delay_initial,
+            reconnect_delay_max: self.reconnect_delay_max,
+            ..self.liquidity.clone()
+        }
+    }
+
+    fn leader_config(&self) -> Option<LeaderConfig> {
+        let leader = self.leader.as_ref()?;
+        Some(LeaderConfig {
+            api_key: self.api_key.clone(),
+            auth: self.auth,
+            tls: self.tls.clone(),
+            proxy: self.proxy.clone(),
+            auto_reconnect: self.reconnect,
+            reconnect_delay_secs: self.reconnect_delay_initial.as_secs_f64(),
+            max_reconnect_delay_secs: self.reconnect_delay_max.as_secs_f64(),
+            ..leader.clone()
+        })
+    }
+}
+
+/// Feed an event came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Feed {
+    /// Liquidity WebSocket ([`K256WebSocketClient`])
+    Liquidity,
+    /// Leader-schedule WebSocket ([`LeaderWebSocketClient`])
+    Leader,
+}
+
+/// Event from either feed of a [`K256Client`].
+#[derive(Debug, Clone)]
+pub enum K256Event {
+    /// Decoded liquidity message
+    Liquidity(DecodedMessage),
+    /// Leader-schedule message
+    Leader(LeaderMessage),
+    /// Lifecycle change of one feed
+    ConnectionState {
+        /// Feed whose state changed
+        feed: Feed,
+        /// New state
+        state: ConnectionState,
+    },
+}
+
+/// Sender of the event queue, counting events dropped while it is full.
+#[derive(Clone)]
+struct EventSender {
+    tx: mpsc::Sender<K256Event>,
+    dropped: Arc<AtomicU64>,
+}
+
+impl EventSender {
+    fn send(&self, event: K256Event) {
+        if let Err(mpsc::error::TrySendError::Full(_)) = self.tx.try_send(event) {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    fn is_closed(&self) -> bool {
+        self.tx.is_closed()
+    }
+}
+
+/// Lifecycle of the leader feed.
+enum LeaderFeed {
+    /// Not started yet
+    Idle,
+    /// Running on its thread
+    Running(LeaderCloseHandle),
+    /// Closed before or after starting
+    Closed,
+}
+
+/// Liquidity and leader-schedule feeds under one configuration and lifecycle.
+pub struct K256Client {
+    config: K256ClientConfig,
+    liquidity: K256WebSocketClient,
+    events_tx: EventSender,
+    events: tokio::sync::Mutex<mpsc::Receiver<K256Event>>,
+    leader: Mutex<LeaderFeed>,
+}
+
+impl K256Client {
+    /// Create a client. Does not connect yet.
+    pub fn new(config: K256ClientConfig) -> Self {
+        let (tx, events) = mpsc::channel(config.event_queue_capacity.max(1));
+        let events_tx = EventSender {
+            tx,
+            dropped: Arc::new(AtomicU64::new(0)),
+        };
+        let liquidity = K256WebSocketClient::new(config.liquidity_config());
+
+        let tx = events_tx.clone();
+        liquidity.on_message(move |msg| tx.send(K256Event::Liquidity(msg))).detach();
+        let tx = events_tx.clone();
+        liquidity
+            .on_connection_state(move |state| {
+                tx.send(K256Event::ConnectionState {
+                    feed: Feed::Liquidity,
+                    state,
+                });
+            })
+            .detach();
+
+        Self {
+            config,
+            liquidity,
+            events_tx,
+            events: tokio::sync::Mutex::new(events),
+            leader: Mutex::new(LeaderFeed::Idle),
+        }
+    }
+
+    /// Connect the liquidity feed, then start the leader feed.
+    ///
+    /// Returns once the liquidity handshake succeeds; the leader feed
+    /// reports its progress through [`K256Event::ConnectionState`].
+    pub async fn connect(&self) -> Result<(), BoxError> {
+        self.liquidity.connect().await?;
+        if let Some(config) = self.config.leader_config() {
+            let mut leader = self.leader.lock().unwrap();
+            if let LeaderFeed::Idle = *leader {
+                *leader = LeaderFeed::Running(self.spawn_leader(config)?);
+            }
+        }
+        Ok(())
+    }
+
+    /// Next event from either feed, or `None` once both have stopped.
+    pub async fn next_event(&self) -> Option<K256Event> {
+        self.events.lock().await.recv().await
+    }
+
+    /// Events dropped because the queue was full.
+    pub fn dropped_events(&self) -> u64 {
+        self.events_tx.dropped.load(Ordering::Relaxed)
+    }
+
+    /// The liquidity client, for subscriptions and typed callbacks.
+    pub fn liquidity(&self) -> &K256WebSocketClient {
+        &self.liquidity
+    }
+
+    /// Disconnect the liquidity feed and end the leader session.
+    ///
+    /// Neither feed reconnects afterwards; both report
+    /// [`ConnectionState::Closed`].
+    pub fn close(&self) {
+        self.liquidity.disconnect();
+        let mut leader = self.leader.lock().unwrap();
+        if let LeaderFeed::Running(handle) = &*leader {
+            handle.close();
+        }
+        *leader = LeaderFeed::Closed;
+    }
+
+    fn spawn_leader(&self, config: LeaderConfig) -> Result<LeaderCloseHandle, BoxError> {
+        let subscribed = Arc::new(AtomicBool::new(false));
+        let tx = self.events_tx.clone();
+        let mut client = LeaderWebSocketClient::new(config.clone(), move |msg| tx.send(K256Event::Leader(msg)));
+        let (tx, seen) = (self.events_tx.clone(), subscribed.clone());
+        client.on_connection_state(move |state| {
+            if state == ConnectionState::Subscribed {
+                seen.store(true, Ordering::Relaxed);
+            }
+            tx.send(K256Event::ConnectionState {
+                feed: Feed::Leader,
+                state,
+            });
+        });
+
+        let handle = client.close_handle();
+        let events = self.events_tx.clone();
+        std::thread::Builder::new()
+            .name("k256-leader".to_string())
+            .spawn(move || run_leader(client, &config, &events, &subscribed))?;
+        Ok(handle)
+    }
+}
+
+impl Drop for K256Client {
+    fn drop(&mut self) {
+        self.close();
+    }
+}
+
+/// Run leader sessions until the feed is closed or reconnects are disabled.
+fn run_leader<F>(client: LeaderWebSocketClient<F>, config: &LeaderConfig, events: &EventSender, subscribed: &AtomicBool)
+where
+    F: Fn(LeaderMessage) + Send + 'static,
+{
+    let closed = client.close_handle();
+    let initial = Duration::from_secs_f64(config.reconnect_delay_secs.max(0.0));
+    let max = Duration::from_secs_f64(config.max_reconnect_delay_secs.max(0.0));
+    let mut delay = initial;
+    let mut attempt = 0u32;
+    loop {
+        subscribed.store(false, Ordering::Relaxed);
+        if let Err(e) = client.connect_blocking() {
+            error!("Leader feed error: {}", e);
+        }
+        if !config.auto_reconnect || closed.is_closed() || events.is_closed() {
+            break;
+        }
+        if subscribed.load(Ordering::Relaxed) {
+            delay = initial;
+            attempt = 0;
+        }
+        attempt += 1;
+        events.send(K256Event::ConnectionState {
+            feed: Feed::Leader,
+            state: ConnectionState::Reconnecting { attempt },
+        });
+        warn!("Reconnecting leader feed in {:?} (attempt {})", delay, attempt);
+        std::thread::sleep(delay);
+        delay = (delay * 2).min(max);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::{SinkExt, StreamExt};
+    use tokio::net::TcpListener;
+    use tokio_tungstenite::tungstenite::Message;
+
+    /// Serve one WebSocket session sending `messages`, then wait for the client to leave.
+    async fn serve(messages: Vec<Message>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+            for message in messages {
+                ws.send(message).await.unwrap();
+            }
+            while let Some(Ok(_)) = ws.next().await {}
+        });
+        format!("ws://{}/", addr)
+    }
+
+    #[test]
+    fn test_shared_fields_override_feeds() {
+        let config = K256ClientConfig {
+            api_key: "key".to_string(),
+            auth: AuthMethod::Bearer,
+            reconnect: false,
+            reconnect_delay_initial: Duration::from_millis(1500),
+            ..Default::default()
+        };
+        let liquidity = config.liquidity_config();
+        assert_eq!(liquidity.api_key, "key");
+        assert_eq!((liquidity.auth, liquidity.reconnect), (AuthMethod::Bearer, false));
+        let leader = config.leader_config().unwrap();
+        assert_eq!(leader.api_key, "key");
+        assert_eq!((leader.auth, leader.auto_reconnect), (AuthMethod::Bearer, false));
+        assert_eq!(leader.reconnect_delay_secs, 1.5);
+        assert_eq!(leader.url, LeaderConfig::default().url);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_events_from_both_feeds() {
+        let heartbeat = Message::Text(r#"{"type":"heartbeat","timestamp_ms":1}"#.to_string());
+        let subscribed = Message::Text(r#"{"type":"subscribed","data":{}}"#.to_string());
+        let slot = r#"{"type":"slot_update","data":{"slot":5,"leader":"v","blockHeight":1}}"#;
+        let slot = Message::Text(slot.to_string());
+        let client = K256Client::new(K256ClientConfig {
+            reconnect: false,
+            liquidity: Config {
+                endpoint: serve(vec![heartbeat]).await,
+                ping_interval: Duration::ZERO,
+                ..Config::default()
+            },
+            leader: Some(LeaderConfig {
+                url: serve(vec![subscribed, slot]).await,
+                ..LeaderConfig::default()
+            }),
+            ..Default::default()
+        });
+        client.connect().await.unwrap();
+
+        let (mut heartbeat, mut slot_update, mut leader_subscribed) = (false, false, false);
+        while !(heartbeat && slot_update && leader_subscribed) {
+            let event = tokio::time::timeout(Duration::from_secs(5), client.next_event())
+                .await
+                .unwrap()
+                .unwrap();
+            match event {
+                K256Event::Liquidity(DecodedMessage::Heartbeat(_)) => heartbeat = true,
+                K256Event::Leader(msg) if msg.msg_type == "slot_update" => slot_update = true,
+                K256Event::ConnectionState {
+                    feed: Feed::Leader,
+                    state: ConnectionState::Subscribed,
+                } => leader_subscribed = true,
+                _ => {}
+            }
+        }
+
+        client.close();
+        let (mut liquidity_closed, mut leader_closed) = (false, false);
+        while !(liquidity_closed && leader_closed) {
+            let event = tokio::time::timeout(Duration::from_secs(5), client.next_event())
+                .await
+                .unwrap()
+                .unwrap();
+            if let K256Event::ConnectionState {
+                feed,
+                state: ConnectionState::Closed { reason },
+            } = event
+            {
+                assert_eq!(reason, "closed by client");
+                match feed {
+                    Feed::Liquidity => liquidity_closed = true,
+                    Feed::Leader => leader_closed = true,
+                }
+            }
+        }
+        assert!(client.connect().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_event_queue_is_bounded() {
+        let heartbeat = Message::Text(r#"{"type":"heartbeat","timestamp_ms":1}"#.to_string());
+        let client = K256Client::new(K256ClientConfig {
+            reconnect: false,
+            liquidity: Config {
+                endpoint: serve(vec![heartbeat.clone(), heartbeat.clone(), heartbeat]).await,
+                ping_interval: Duration::ZERO,
+                ..Config::default()
+            },
+            leader: None,
+            event_queue_capacity: 1,
+            ..Default::default()
+        });
+        client.connect().await.unwrap();
+
+        // `Connecting` fills the queue; `Connected` and the heartbeats are dropped
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while client.dropped_events() < 4 {
+                tokio::time::sleep(Duration::from_millis(1)).await;
+            }
+        })
+        .await
+        .unwrap();
+        assert!(matches!(
+            client.next_event().await,
+            Some(K256Event::ConnectionState {
+                feed: Feed::Liquidity,
+                state: ConnectionState::Connecting
+            })
+        ));
+    }
+}
@@ -0,0 +1,134 @@
+//! Conversion of base-unit token amounts to human-readable amounts.
+
+/// Convert a base-unit amount to a UI amount (`raw / 10^decimals`).
+///
+/// # Example
+///
+/// ```
+/// use k256_sdk::utils::ui_amount;
+///
+/// assert_eq!(ui_amount(1_500_000, 6), 1.5);
+/// ```
+pub fn ui_amount(raw: u64, decimals: i32) -> f64 {
+    raw as f64 / 10f64.powi(decimals)
+}
+
+/// Format a base-unit amount exactly, followed by `symbol` if non-empty.
+///
+/// Trailing fractional zeros are dropped; no precision is lost.
+///
+/// # Example
+///
+/// ```
+/// use k256_sdk::utils::format_amount;
+///
+/// assert_eq!(format_amount(1_500_000, 6, "USDC"), "1.5 USDC");
+/// assert_eq!(format_amount(42, 9, ""), "0.000000042");
+/// ```
+pub fn format_amount(raw: u64, decimals: i32, symbol: &str) -> String {
+    let mut out = raw.to_string();
+    if decimals <= 0 {
+        if raw != 0 {
+            out.extend(std::iter::repeat('0').take(decimals.unsigned_abs() as usize));
+        }
+    } else {
+        let decimals = decimals as usize;
+        if out.len() <= decimals {
+            out.insert_str(0, &"0".repeat(decimals + 1 - out.len()));
+        }
+        out.insert(out.len() - decimals, '.');
+        let trimmed = out.trim_end_matches('0').trim_end_matches('.').len();
+        out.truncate(trimmed);
+    }
+    if !symbol.is_empty() {
+        out.push(' ');
+        out.push_str(symbol);
+    }
+    out
+}
+
+/// Convert a base-unit amount to an exact [`Decimal`](rust_decimal::Decimal) UI amount.
+///
+/// Returns `None` if the result does not fit a `Decimal` (more than 28
+/// decimals, or a large amount with negative decimals).
+#[cfg(feature = "rust_decimal")]
+#[cfg_attr(docsrs, doc(cfg(feature = "rust_decimal")))]
+pub fn ui_amount_decimal(raw: u64, decimals: i32) -> Option<rust_decimal::Decimal> {
+    use rust_decimal::Decimal;
+
+    if decimals >= 0 {
+        Decimal::try_from_i128_with_scale(raw as i128, decimals as u32)
+            .ok()
+            .map(|d| d.normalize())
+    } else {
+        let scale = 10u64.checked_pow(decimals.unsigned_abs())?;
+        Decimal::from(raw).checked_mul(Decimal::from(scale))
+    }
+}
+
+/// Convert an exact UI amount back to base units.
+///
+/// Returns `None` if `amount` is negative, has more fractional digits than
+/// `decimals`, or does not fit a `u64`.
+#[cfg(feature = "rust_decimal")]
+#[cfg_attr(docsrs, doc(cfg(feature = "rust_decimal")))]
+pub fn raw_amount_decimal(amount: rust_decimal::Decimal, decimals: i32) -> Option<u64> {
+    use rust_decimal::prelude::ToPrimitive;
+    use rust_decimal::Decimal;
+
+    if amount.is_sign_negative() && !amount.is_zero() {
+        return None;
+    }
+    let scale = Decimal::from(10u64.checked_pow(decimals.unsigned_abs())?);
+    let raw = if decimals >= 0 {
+        amount.checked_mul(scale)?
+    } else {
+        amount.checked_div(scale)?
+    };
+    if !raw.fract().is_zero() {
+        return None;
+    }
+    raw.to_u64()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ui_amount() {
+        assert_eq!(ui_amount(1_000_000_000, 9), 1.0);
+        assert_eq!(ui_amount(0, 6), 0.0);
+        assert_eq!(ui_amount(5, -2), 500.0);
+    }
+
+    #[test]
+    fn test_format_amount() {
+        assert_eq!(format_amount(1_000_000_000, 9, "SOL"), "1 SOL");
+        assert_eq!(format_amount(1_234_567, 6, "USDC"), "1.234567 USDC");
+        assert_eq!(format_amount(1, 6, ""), "0.000001");
+        assert_eq!(format_amount(0, 6, ""), "0");
+        assert_eq!(format_amount(u64::MAX, 0, ""), "18446744073709551615");
+        assert_eq!(format_amount(u64::MAX, 19, ""), "1.8446744073709551615");
+        assert_eq!(format_amount(12, -3, "X"), "12000 X");
+        assert_eq!(format_amount(0, -3, ""), "0");
+    }
+
+    #[cfg(feature = "rust_decimal")]
+    #[test]
+    fn test_decimal_roundtrip() {
+        use rust_decimal::Decimal;
+        use std::str::FromStr;
+
+        let amount = ui_amount_decimal(1_234_567, 6).unwrap();
+        assert_eq!(amount, Decimal::from_str("1.234567").unwrap());
+        assert_eq!(raw_amount_decimal(amount, 6), Some(1_234_567));
+        assert_eq!(ui_amount_decimal(u64::MAX, 9).unwrap().to_string(), "18446744073.709551615");
+        assert_eq!(ui_amount_decimal(12, -3), Some(Decimal::from(12_000)));
+        assert_eq!(raw_amount_decimal(Decimal::from(12_000), -3), Some(12));
+
+        assert_eq!(raw_amount_decimal(Decimal::from_str("0.0000001").unwrap(), 6), None);
+        assert_eq!(raw_amount_decimal(Decimal::from(-1), 6), None);
+        assert_eq!(ui_amount_decimal(1, 29), None);
+    }
+}
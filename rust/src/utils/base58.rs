@@ -1,5 +1,7 @@
 //! Base58 encoding/decoding utilities for Solana addresses.
 
+use crate::types::{ParsePubkeyError, Pubkey, PubkeyStr};
+
 /// Encode bytes to base58 string.
 pub fn base58_encode(data: &[u8]) -> String {
     bs58::encode(data).into_string()
@@ -10,6 +12,23 @@ pub fn base58_decode(s: &str) -> Result<Vec<u8>, bs58::decode::Error> {
     bs58::decode(s).into_vec()
 }
 
+/// Encode a 32-byte key or hash to base58 on the stack.
+///
+/// Dereferences to `str`; call `to_string()` only where an owned string is
+/// needed.
+pub fn encode_32(bytes: &[u8; 32]) -> PubkeyStr {
+    Pubkey::new(*bytes).encode()
+}
+
+/// Decode a base58 public key (or hash) to its 32 bytes.
+///
+/// Fails with [`ParsePubkeyError::InvalidLength`] unless the string
+/// decodes to exactly 32 bytes; strings over 44 characters are rejected
+/// without decoding them.
+pub fn decode_pubkey(s: &str) -> Result<[u8; 32], ParsePubkeyError> {
+    s.parse().map(Pubkey::to_bytes)
+}
+
 /// Check if a string is a valid Solana public key.
 ///
 /// # Arguments
@@ -20,15 +39,7 @@ pub fn base58_decode(s: &str) -> Result<Vec<u8>, bs58::decode::Error> {
 ///
 /// True if valid, false otherwise
 pub fn is_valid_pubkey(address: &str) -> bool {
-    // Solana pubkeys are 32 bytes, which encode to 32-44 base58 chars
-    if address.len() < 32 || address.len() > 44 {
-        return false;
-    }
-
-    match bs58::decode(address).into_vec() {
-        Ok(decoded) => decoded.len() == 32,
-        Err(_) => false,
-    }
+    decode_pubkey(address).is_ok()
 }
 
 #[cfg(test)]
@@ -54,6 +65,25 @@ mod tests {
         assert!(!is_valid_pubkey("invalid!@#$"));
     }
 
+    #[test]
+    fn test_fixed_size_roundtrip() {
+        let usdc = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+        let bytes = decode_pubkey(usdc).unwrap();
+        assert_eq!(&*encode_32(&bytes), usdc);
+        assert_eq!(encode_32(&bytes).as_str(), base58_encode(&bytes));
+        assert_eq!(&*encode_32(&[0; 32]), "11111111111111111111111111111111");
+
+        // 31 and 33 bytes, and strings outside 32-44 characters
+        assert_eq!(decode_pubkey(&base58_encode(&[7; 31])), Err(ParsePubkeyError::InvalidLength));
+        assert_eq!(decode_pubkey(&base58_encode(&[7; 33])), Err(ParsePubkeyError::InvalidLength));
+        assert_eq!(decode_pubkey(&"1".repeat(31)), Err(ParsePubkeyError::InvalidLength));
+        assert_eq!(decode_pubkey(&"z".repeat(45)), Err(ParsePubkeyError::InvalidLength));
+        assert!(matches!(
+            decode_pubkey("0PjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v"),
+            Err(ParsePubkeyError::InvalidBase58(_))
+        ));
+    }
+
     #[test]
     fn test_base58_roundtrip() {
         let original = vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 10];
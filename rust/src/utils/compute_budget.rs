@@ -0,0 +1,109 @@
+//! Compute Budget program instructions without the Solana SDK.
+//!
+//! Builds the raw data of `SetComputeUnitLimit` and `SetComputeUnitPrice`
+//! instructions, ready to wrap in whatever instruction type the caller's
+//! transaction library uses. Neither instruction takes accounts.
+//!
+//! ```rust
+//! use k256_sdk::utils::compute_budget;
+//! use k256_sdk::{FeeMarket, FeeStrategy, Pubkey};
+//!
+//! fn budget(fees: &FeeMarket, writable: &[Pubkey]) -> [compute_budget::Instruction; 2] {
+//!     let estimate = FeeStrategy::Balanced.estimate(fees, writable, 200_000);
+//!     compute_budget::instructions(&estimate)
+//! }
+//! ```
+
+use crate::types::{FeeEstimate, Pubkey};
+
+/// Compute Budget program id (`ComputeBudget111111111111111111111111111111`).
+pub const PROGRAM_ID: Pubkey = Pubkey::new([
+    3, 6, 70, 111, 229, 33, 23, 50, 255, 236, 173, 186, 114, 195, 155, 231, 188, 140, 229, 187, 197, 247, 18, 107,
+    44, 67, 155, 58, 64, 0, 0, 0,
+]);
+
+/// Most compute units a transaction may request.
+pub const MAX_COMPUTE_UNIT_LIMIT: u32 = 1_400_000;
+
+const SET_COMPUTE_UNIT_LIMIT: u8 = 2;
+const SET_COMPUTE_UNIT_PRICE: u8 = 3;
+
+/// Compute Budget instruction: program id and data, no accounts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Instruction {
+    /// Always [`PROGRAM_ID`]
+    pub program_id: Pubkey,
+    data: [u8; 9],
+    len: usize,
+}
+
+impl Instruction {
+    /// Instruction data.
+    pub fn data(&self) -> &[u8] {
+        &self.data[..self.len]
+    }
+}
+
+/// `SetComputeUnitLimit` instruction data.
+pub fn set_compute_unit_limit_data(units: u32) -> [u8; 5] {
+    let mut data = [SET_COMPUTE_UNIT_LIMIT, 0, 0, 0, 0];
+    data[1..].copy_from_slice(&units.to_le_bytes());
+    data
+}
+
+/// `SetComputeUnitPrice` instruction data, price in microlamports/CU.
+pub fn set_compute_unit_price_data(micro_lamports: u64) -> [u8; 9] {
+    let mut data = [SET_COMPUTE_UNIT_PRICE, 0, 0, 0, 0, 0, 0, 0, 0];
+    data[1..].copy_from_slice(&micro_lamports.to_le_bytes());
+    data
+}
+
+/// `SetComputeUnitLimit` instruction, capped at [`MAX_COMPUTE_UNIT_LIMIT`].
+pub fn set_compute_unit_limit(units: u32) -> Instruction {
+    let mut data = [0; 9];
+    data[..5].copy_from_slice(&set_compute_unit_limit_data(units.min(MAX_COMPUTE_UNIT_LIMIT)));
+    Instruction {
+        program_id: PROGRAM_ID,
+        data,
+        len: 5,
+    }
+}
+
+/// `SetComputeUnitPrice` instruction, price in microlamports/CU.
+pub fn set_compute_unit_price(micro_lamports: u64) -> Instruction {
+    Instruction {
+        program_id: PROGRAM_ID,
+        data: set_compute_unit_price_data(micro_lamports),
+        len: 9,
+    }
+}
+
+/// Limit and price instructions for `estimate`, in that order.
+pub fn instructions(estimate: &FeeEstimate) -> [Instruction; 2] {
+    [
+        set_compute_unit_limit(estimate.compute_unit_limit),
+        set_compute_unit_price(estimate.compute_unit_price),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_program_id() {
+        assert_eq!(PROGRAM_ID, "ComputeBudget111111111111111111111111111111");
+    }
+
+    #[test]
+    fn test_instruction_data() {
+        let [limit, price] = instructions(&FeeEstimate {
+            compute_unit_price: 50_000,
+            compute_unit_limit: 200_000,
+        });
+        assert_eq!(limit.data(), [2, 0x40, 0x0d, 0x03, 0x00]);
+        assert_eq!(price.data(), [3, 0x50, 0xc3, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(price.program_id, PROGRAM_ID);
+        assert_eq!(set_compute_unit_limit(u32::MAX).data()[1..], MAX_COMPUTE_UNIT_LIMIT.to_le_bytes());
+    }
+}
@@ -1,5 +1,10 @@
 //! Utility functions.
 
+mod amount;
 mod base58;
+pub mod compute_budget;
 
-pub use base58::{base58_decode, base58_encode, is_valid_pubkey};
+pub use amount::{format_amount, ui_amount};
+#[cfg(feature = "rust_decimal")]
+pub use amount::{raw_amount_decimal, ui_amount_decimal};
+pub use base58::{base58_decode, base58_encode, decode_pubkey, encode_32, is_valid_pubkey};
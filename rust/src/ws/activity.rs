@@ -0,0 +1,355 @@
+//! Most active pools and tokens over a sliding window.
+//!
+//! [`ActivityTracker`] counts pool updates and the balance changes between
+//! consecutive updates of each pool into one-second buckets, and ranks pools
+//! and tokens by update count or traded volume over the last `window`. Pool
+//! snapshots only reset the balances deltas are measured from, so the first
+//! update after a reconnect is not mistaken for a trade.
+//!
+//! With [`Config::activity_report`] set, the client emits an
+//! [`ActivityReport`] every interval through
+//! [`K256WebSocketClient::on_activity`].
+//!
+//! [`Config::activity_report`]: crate::ws::Config::activity_report
+//! [`K256WebSocketClient::on_activity`]: crate::ws::K256WebSocketClient::on_activity
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+use crate::types::{PoolUpdate, Protocol, Pubkey};
+use crate::ws::message::DecodedMessage;
+
+/// What pools and tokens are ranked by.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub enum ActivityRank {
+    /// Number of updates
+    #[default]
+    Updates,
+    /// Traded volume: [`PoolActivity::turnover`] for pools,
+    /// [`TokenActivity::volume`] for tokens
+    Volume,
+}
+
+/// Activity report settings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ActivityConfig {
+    /// Window the report covers
+    pub window: Duration,
+    /// How often the client emits a report
+    pub interval: Duration,
+    /// Pools and tokens listed per report
+    pub top_n: usize,
+    /// Ranking criterion
+    pub rank: ActivityRank,
+}
+
+impl ActivityConfig {
+    /// Report the 10 pools and tokens with the most updates in `window`,
+    /// every `interval`.
+    pub fn new(window: Duration, interval: Duration) -> Self {
+        Self {
+            window,
+            interval,
+            top_n: 10,
+            rank: ActivityRank::Updates,
+        }
+    }
+}
+
+/// Activity of one pool in the window.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PoolActivity {
+    /// Pool address
+    pub pool: Pubkey,
+    /// DEX protocol of the pool
+    pub protocol: Protocol,
+    /// Updates in the window
+    pub updates: u64,
+    /// Balance changes relative to the pool's balances, summed over its
+    /// tokens (1.0 = one pool's worth of reserves traded)
+    pub turnover: f64,
+}
+
+/// Activity of one token in the window, across all pools.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TokenActivity {
+    /// Token mint
+    pub mint: Pubkey,
+    /// Updates of pools holding the token
+    pub updates: u64,
+    /// Distinct pools holding the token that updated
+    pub pools: usize,
+    /// Absolute balance changes in whole tokens. Only comparable between
+    /// tokens of similar price.
+    pub volume: f64,
+}
+
+/// Most active pools and tokens over the window ending at the report.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ActivityReport {
+    /// Seconds covered, shorter than the configured window until that much
+    /// time has passed
+    pub window_secs: f64,
+    /// Ranking criterion
+    pub rank: ActivityRank,
+    /// Pool updates in the window
+    pub total_updates: u64,
+    /// Most active pools, most active first
+    pub pools: Vec<PoolActivity>,
+    /// Most active tokens, most active first
+    pub tokens: Vec<TokenActivity>,
+}
+
+#[derive(Debug, Default)]
+struct TokenTally {
+    updates: u64,
+    pools: HashSet<Pubkey>,
+    volume: f64,
+}
+
+#[derive(Debug, Default)]
+struct Bucket {
+    /// Whole seconds since the tracker was created
+    second: u64,
+    updates: u64,
+    pools: HashMap<Pubkey, (Protocol, u64, f64)>,
+    tokens: HashMap<Pubkey, TokenTally>,
+}
+
+/// Sliding-window pool and token activity.
+///
+/// ```rust
+/// use std::time::Duration;
+/// use k256_sdk::ws::{ActivityConfig, ActivityTracker};
+/// use k256_sdk::{DecodedMessage, PoolUpdate};
+///
+/// let mut activity = ActivityTracker::new(ActivityConfig::new(Duration::from_secs(60), Duration::from_secs(5)));
+/// activity.record(&DecodedMessage::PoolUpdate(PoolUpdate::default()));
+///
+/// let report = activity.report();
+/// assert_eq!(report.pools[0].updates, 1);
+/// ```
+#[derive(Debug)]
+pub struct ActivityTracker {
+    window_secs: u64,
+    top_n: usize,
+    rank: ActivityRank,
+    origin: Instant,
+    buckets: VecDeque<Bucket>,
+    /// Latest balances of each pool, the base of the next delta
+    balances: HashMap<Pubkey, Vec<u64>>,
+}
+
+impl ActivityTracker {
+    /// Tracker with `config`'s window (rounded up to whole seconds), size and
+    /// ranking.
+    pub fn new(config: ActivityConfig) -> Self {
+        Self {
+            window_secs: config.window.as_secs_f64().ceil().max(1.0) as u64,
+            top_n: config.top_n,
+            rank: config.rank,
+            origin: Instant::now(),
+            buckets: VecDeque::new(),
+            balances: HashMap::new(),
+        }
+    }
+
+    /// Record the pool updates in `msg`, received now.
+    pub fn record(&mut self, msg: &DecodedMessage) {
+        self.record_at(msg, Instant::now());
+    }
+
+    /// Record the pool updates in `msg`, received at `at`.
+    ///
+    /// Pool snapshots reset the balances deltas are measured from; other
+    /// message types are ignored.
+    pub fn record_at(&mut self, msg: &DecodedMessage, at: Instant) {
+        match msg {
+            DecodedMessage::PoolUpdate(update) => self.record_update(update, at),
+            DecodedMessage::PoolUpdateBatch(updates) => {
+                for update in updates {
+                    self.record_update(update, at);
+                }
+            }
+            DecodedMessage::PoolSnapshot(updates) => {
+                for update in updates {
+                    self.balances.insert(update.pool_address, update.token_balances.clone());
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn record_update(&mut self, update: &PoolUpdate, at: Instant) {
+        let second = at.saturating_duration_since(self.origin).as_secs();
+        self.evict(second);
+        if self.buckets.back().map_or(true, |bucket| bucket.second < second) {
+            self.buckets.push_back(Bucket {
+                second,
+                ..Bucket::default()
+            });
+        }
+        let bucket = self.buckets.back_mut().expect("bucket was just ensured");
+
+        // Deltas only make sense against the same token layout
+        let previous = self
+            .balances
+            .insert(update.pool_address, update.token_balances.clone())
+            .filter(|previous| previous.len() == update.token_balances.len());
+        let mut turnover = 0.0;
+        for (i, mint) in update.token_mints.iter().enumerate() {
+            let balance = update.token_balances.get(i).copied().unwrap_or(0);
+            let delta = previous.as_ref().map_or(0, |previous| balance.abs_diff(previous[i]));
+            let base = previous.as_ref().map_or(0, |previous| previous[i].max(balance));
+            if base > 0 {
+                turnover += delta as f64 / base as f64;
+            }
+            let decimals = update.token_decimals.get(i).copied().unwrap_or(0);
+            let token = bucket.tokens.entry(*mint).or_default();
+            token.updates += 1;
+            token.pools.insert(update.pool_address);
+            token.volume += delta as f64 / 10f64.powi(decimals);
+        }
+        bucket.updates += 1;
+        let pool = bucket
+            .pools
+            .entry(update.pool_address)
+            .or_insert_with(|| (update.protocol_name.clone(), 0, 0.0));
+        pool.1 += 1;
+        pool.2 += turnover;
+    }
+
+    /// Report for the window ending now.
+    pub fn report(&mut self) -> ActivityReport {
+        self.report_at(Instant::now())
+    }
+
+    /// Report for the window ending at `at`.
+    pub fn report_at(&mut self, at: Instant) -> ActivityReport {
+        let elapsed = at.saturating_duration_since(self.origin);
+        self.evict(elapsed.as_secs());
+
+        let mut pools: HashMap<Pubkey, PoolActivity> = HashMap::new();
+        let mut tokens: HashMap<Pubkey, (u64, HashSet<Pubkey>, f64)> = HashMap::new();
+        for bucket in &self.buckets {
+            for (address, (protocol, updates, turnover)) in &bucket.pools {
+                let pool = pools.entry(*address).or_insert_with(|| PoolActivity {
+                    pool: *address,
+                    protocol: protocol.clone(),
+                    updates: 0,
+                    turnover: 0.0,
+                });
+                pool.updates += updates;
+                pool.turnover += turnover;
+            }
+            for (mint, tally) in &bucket.tokens {
+                let token = tokens.entry(*mint).or_default();
+                token.0 += tally.updates;
+                token.1.extend(&tally.pools);
+                token.2 += tally.volume;
+            }
+        }
+
+        let mut pools: Vec<PoolActivity> = pools.into_values().collect();
+        let mut tokens: Vec<TokenActivity> = tokens
+            .into_iter()
+            .map(|(mint, (updates, pools, volume))| TokenActivity {
+                mint,
+                updates,
+                pools: pools.len(),
+                volume,
+            })
+            .collect();
+        match self.rank {
+            ActivityRank::Updates => {
+                pools.sort_by(|a, b| b.updates.cmp(&a.updates).then(a.pool.cmp(&b.pool)));
+                tokens.sort_by(|a, b| b.updates.cmp(&a.updates).then(a.mint.cmp(&b.mint)));
+            }
+            ActivityRank::Volume => {
+                pools.sort_by(|a, b| b.turnover.total_cmp(&a.turnover).then(a.pool.cmp(&b.pool)));
+                tokens.sort_by(|a, b| b.volume.total_cmp(&a.volume).then(a.mint.cmp(&b.mint)));
+            }
+        }
+        pools.truncate(self.top_n);
+        tokens.truncate(self.top_n);
+
+        ActivityReport {
+            window_secs: elapsed.as_secs_f64().clamp(1.0, self.window_secs as f64),
+            rank: self.rank,
+            total_updates: self.buckets.iter().map(|bucket| bucket.updates).sum(),
+            pools,
+            tokens,
+        }
+    }
+
+    /// Drop buckets that fell out of the window ending in `second`.
+    fn evict(&mut self, second: u64) {
+        while self
+            .buckets
+            .front()
+            .is_some_and(|bucket| bucket.second + self.window_secs <= second)
+        {
+            self.buckets.pop_front();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn update(pool: u8, balances: [u64; 2]) -> DecodedMessage {
+        DecodedMessage::PoolUpdate(PoolUpdate {
+            protocol_name: Protocol::Whirlpool,
+            pool_address: Pubkey::new([pool; 32]),
+            token_mints: vec![Pubkey::new([1; 32]), Pubkey::new([pool + 100; 32])],
+            token_balances: balances.to_vec(),
+            token_decimals: vec![0, 2],
+            ..PoolUpdate::default()
+        })
+    }
+
+    #[test]
+    fn test_ranks_by_updates_and_volume() {
+        let config = ActivityConfig::new(Duration::from_secs(10), Duration::from_secs(1));
+        let mut activity = ActivityTracker::new(config);
+        let start = activity.origin;
+        // Pool 2 updates often with small trades, pool 3 once with a large one
+        for (i, balance) in [1_000, 1_010, 1_000, 1_010].into_iter().enumerate() {
+            activity.record_at(&update(2, [balance, 1_000]), start + Duration::from_secs(i as u64));
+        }
+        activity.record_at(&DecodedMessage::PoolSnapshot(vec![]), start);
+        let DecodedMessage::PoolUpdate(snapshot) = update(3, [1_000, 1_000]) else {
+            unreachable!()
+        };
+        activity.record_at(&DecodedMessage::PoolSnapshot(vec![snapshot]), start);
+        activity.record_at(&update(3, [1_500, 700]), start + Duration::from_secs(3));
+
+        let report = activity.report_at(start + Duration::from_secs(4));
+        assert_eq!((report.window_secs, report.total_updates), (4.0, 5));
+        assert_eq!(report.pools[0].updates, 4);
+        assert_eq!(report.tokens[0].mint, Pubkey::new([1; 32]));
+        assert_eq!((report.tokens[0].updates, report.tokens[0].pools), (5, 2));
+        assert_eq!(report.tokens[0].volume, 530.0);
+
+        let mut activity = ActivityTracker::new(ActivityConfig {
+            top_n: 1,
+            rank: ActivityRank::Volume,
+            ..config
+        });
+        activity.record_at(&update(2, [1_000, 1_000]), start);
+        activity.record_at(&update(2, [1_010, 1_000]), start);
+        activity.record_at(&update(3, [1_000, 1_000]), start);
+        activity.record_at(&update(3, [1_500, 700]), start);
+        let report = activity.report_at(start);
+        assert_eq!(report.pools.len(), 1);
+        assert_eq!(report.pools[0].pool, Pubkey::new([3; 32]));
+        assert!((report.pools[0].turnover - (500.0 / 1_500.0 + 0.3)).abs() < 1e-9);
+        assert_eq!(report.tokens[0].volume, 510.0);
+
+        // Everything ages out of the window
+        assert_eq!(activity.report_at(start + Duration::from_secs(60)).total_updates, 0);
+    }
+}
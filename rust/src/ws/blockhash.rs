@@ -0,0 +1,186 @@
+//! Recent-blockhash cache for transaction building.
+//!
+//! [`BlockhashProvider`] keeps the newest [`Blockhash`] from the feed and
+//! hands it out with its `last_valid_block_height`, optionally waiting until
+//! a fresh one arrives. Age is measured from when the provider received the
+//! hash, so it is unaffected by clock skew against the server. Not
+//! available on `wasm32`, where `Instant::now` panics.
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use tokio::sync::watch;
+
+use crate::types::{Blockhash, Slot};
+use crate::ws::{CallbackHandle, K256WebSocketClient};
+
+/// A cached recent blockhash, ready to put into a transaction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecentBlockhash {
+    /// Base58-encoded blockhash
+    pub blockhash: String,
+    /// Block height of the blockhash
+    pub block_height: u64,
+    /// Last block height at which transactions using this hash are valid
+    pub last_valid_block_height: u64,
+    /// Solana slot of the blockhash
+    pub slot: Slot,
+    /// When the provider received the hash
+    pub received_at: Instant,
+}
+
+impl RecentBlockhash {
+    /// Time since the provider received the hash.
+    pub fn age(&self) -> Duration {
+        self.received_at.elapsed()
+    }
+}
+
+/// Newest hash overall and newest hash the server did not flag as stale.
+#[derive(Debug, Default)]
+struct Cached {
+    latest: Option<RecentBlockhash>,
+    latest_non_stale: Option<RecentBlockhash>,
+}
+
+/// Caches the newest blockhash from `Blockhash` messages.
+///
+/// Cheap to clone; clones share the same cache.
+///
+/// ```rust,no_run
+/// use std::time::Duration;
+/// use k256_sdk::ws::BlockhashProvider;
+/// # async fn example(client: &k256_sdk::K256WebSocketClient) {
+/// let provider = BlockhashProvider::new();
+/// provider.attach(client).detach();
+///
+/// let recent = provider.await_fresh(Duration::from_secs(2)).await;
+/// println!("{} valid until {}", recent.blockhash, recent.last_valid_block_height);
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct BlockhashProvider {
+    cache: Arc<watch::Sender<Cached>>,
+}
+
+impl Default for BlockhashProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BlockhashProvider {
+    /// Create an empty provider.
+    pub fn new() -> Self {
+        Self {
+            cache: Arc::new(watch::Sender::new(Cached::default())),
+        }
+    }
+
+    /// Feed the provider from `client`'s blockhash messages.
+    ///
+    /// Runs alongside other [`K256WebSocketClient::on_blockhash`] callbacks
+    /// until the returned handle is dropped.
+    pub fn attach(&self, client: &K256WebSocketClient) -> CallbackHandle {
+        let provider = self.clone();
+        client.on_blockhash(move |blockhash| provider.update(&blockhash))
+    }
+
+    /// Apply a blockhash message. Hashes older than the cached one are ignored.
+    pub fn update(&self, blockhash: &Blockhash) {
+        let recent = RecentBlockhash {
+            blockhash: blockhash.blockhash.clone(),
+            block_height: blockhash.block_height,
+            last_valid_block_height: blockhash.last_valid_block_height,
+            slot: blockhash.slot,
+            received_at: Instant::now(),
+        };
+        let is_stale = blockhash.is_stale;
+        self.cache.send_if_modified(|cached| {
+            if cached.latest.as_ref().is_some_and(|latest| latest.slot > recent.slot) {
+                return false;
+            }
+            if !is_stale {
+                cached.latest_non_stale = Some(recent.clone());
+            }
+            cached.latest = Some(recent);
+            true
+        });
+    }
+
+    /// Newest cached blockhash, even if the server flagged it as stale.
+    pub fn latest(&self) -> Option<RecentBlockhash> {
+        self.cache.borrow().latest.clone()
+    }
+
+    /// Newest cached blockhash the server did not flag as stale.
+    pub fn latest_non_stale(&self) -> Option<RecentBlockhash> {
+        self.cache.borrow().latest_non_stale.clone()
+    }
+
+    /// Wait for a non-stale blockhash received at most `max_age` ago.
+    ///
+    /// Returns immediately when the cached hash qualifies. Wrap in
+    /// [`tokio::time::timeout`] to bound the wait.
+    pub async fn await_fresh(&self, max_age: Duration) -> RecentBlockhash {
+        let mut rx = self.cache.subscribe();
+        loop {
+            if let Some(recent) = rx.borrow_and_update().latest_non_stale.as_ref() {
+                if recent.age() <= max_age {
+                    return recent.clone();
+                }
+            }
+            // The sender lives in `self`, so the channel cannot close here
+            let _ = rx.changed().await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn blockhash(slot: u64, is_stale: bool) -> Blockhash {
+        Blockhash {
+            slot: Slot(slot),
+            blockhash: format!("hash-{}", slot),
+            last_valid_block_height: slot + 150,
+            is_stale,
+            ..Blockhash::default()
+        }
+    }
+
+    #[test]
+    fn test_latest_and_non_stale() {
+        let provider = BlockhashProvider::new();
+        assert!(provider.latest().is_none());
+
+        provider.update(&blockhash(10, false));
+        provider.update(&blockhash(11, true));
+        provider.update(&blockhash(9, false));
+
+        let latest = provider.latest().unwrap();
+        assert_eq!((latest.blockhash.as_str(), latest.last_valid_block_height), ("hash-11", 161));
+        assert_eq!(provider.latest_non_stale().unwrap().slot, 10);
+    }
+
+    #[tokio::test]
+    async fn test_await_fresh() {
+        let provider = BlockhashProvider::new();
+        provider.update(&blockhash(10, true));
+
+        let waiter = tokio::spawn({
+            let provider = provider.clone();
+            async move { provider.await_fresh(Duration::from_secs(1)).await }
+        });
+        tokio::task::yield_now().await;
+        assert!(!waiter.is_finished());
+
+        provider.update(&blockhash(12, false));
+        let recent = tokio::time::timeout(Duration::from_secs(1), waiter).await.unwrap().unwrap();
+        assert_eq!(recent.slot, 12);
+
+        let immediate = provider.await_fresh(Duration::from_secs(1)).await;
+        assert_eq!(immediate.slot, 12);
+    }
+}
@@ -0,0 +1,435 @@
+//! Zero-copy decoding of binary messages.
+//!
+//! [`decode_message_borrowed`] returns views into the frame payload instead
+//! of owned values: addresses stay raw 32-byte slices, the protocol name and
+//! pool state are borrowed, and token vectors are read lazily. Nothing is
+//! allocated until [`to_owned`](PoolUpdateRef::to_owned) is called.
+//!
+//! ```rust
+//! use k256_sdk::ws::{decode_message_borrowed, DecodedMessageRef};
+//!
+//! fn on_frame(frame: &[u8]) {
+//!     let Some((&msg_type, payload)) = frame.split_first() else { return };
+//!     if let Ok(Some(DecodedMessageRef::PoolUpdate(update))) = decode_message_borrowed(msg_type, payload) {
+//!         if update.protocol_name == "Whirlpool" {
+//!             let owned = update.to_owned();
+//!             println!("{} @ {}", owned.pool_address, owned.slot);
+//!         }
+//!     }
+//! }
+//! ```
+
+use k256_proto::{self as proto, check_limit};
+
+use crate::types::{MessageType, OrderLevel, PoolUpdate, Pubkey, Slot};
+use crate::ws::message::DecodedMessage;
+use crate::ws::decoder::{self, DecodeError, DecodeLimits};
+
+/// Borrowed view of a decoded binary message.
+#[derive(Debug, Clone)]
+pub enum DecodedMessageRef<'a> {
+    /// Single pool update
+    PoolUpdate(PoolUpdateRef<'a>),
+    /// Batch of pool updates
+    PoolUpdateBatch(PoolUpdateBatchRef<'a>),
+    /// Current state of every subscribed pool, sent once after subscribing
+    PoolSnapshot(PoolUpdateBatchRef<'a>),
+    /// Message types without a borrowed form, decoded as usual
+    Owned(DecodedMessage),
+}
+
+impl DecodedMessageRef<'_> {
+    /// Convert to the owned [`DecodedMessage`].
+    pub fn to_owned(&self) -> Result<DecodedMessage, DecodeError> {
+        Ok(match self {
+            Self::PoolUpdate(update) => DecodedMessage::PoolUpdate(update.to_owned()),
+            Self::PoolUpdateBatch(batch) => DecodedMessage::PoolUpdateBatch(batch.to_owned()?),
+            Self::PoolSnapshot(batch) => DecodedMessage::PoolSnapshot(batch.to_owned()?),
+            Self::Owned(message) => message.clone(),
+        })
+    }
+}
+
+/// Borrowed view of a [`PoolUpdate`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PoolUpdateRef<'a> {
+    /// Global sequence number for ordering
+    pub sequence: u64,
+    /// Solana slot number
+    pub slot: Slot,
+    /// Write version within slot
+    pub write_version: u64,
+    /// DEX protocol name
+    pub protocol_name: &'a str,
+    /// Raw pool address
+    pub pool_address: &'a [u8; 32],
+    /// Best bid order level, if available
+    pub best_bid: Option<OrderLevel>,
+    /// Best ask order level, if available
+    pub best_ask: Option<OrderLevel>,
+    /// Opaque pool state bytes
+    pub serialized_state: &'a [u8],
+    wire: proto::PoolUpdate<'a>,
+}
+
+impl<'a> PoolUpdateRef<'a> {
+    /// Decode a pool update payload (without the type byte).
+    pub fn decode(data: &'a [u8]) -> Result<Self, DecodeError> {
+        Self::decode_with_limits(data, &DecodeLimits::default())
+    }
+
+    /// Decode a pool update payload (without the type byte), enforcing `limits`.
+    pub fn decode_with_limits(data: &'a [u8], limits: &DecodeLimits) -> Result<Self, DecodeError> {
+        Self::decode_prefix(data, limits).map(|(update, _)| update)
+    }
+
+    /// Decode the fields this SDK knows from the front of `data`, returning
+    /// the update and the number of bytes they cover.
+    pub(super) fn decode_prefix(data: &'a [u8], limits: &DecodeLimits) -> Result<(Self, usize), DecodeError> {
+        let (wire, len) = proto::PoolUpdate::parse_prefix(data, limits)?;
+        let protocol_name = std::str::from_utf8(wire.protocol_name)
+            .map_err(|_| String::from_utf8(wire.protocol_name.to_vec()).unwrap_err())?;
+        let update = Self {
+            sequence: wire.sequence,
+            slot: Slot(wire.slot),
+            write_version: wire.write_version,
+            protocol_name,
+            pool_address: wire.pool_address,
+            best_bid: wire.best_bid.map(OrderLevel::from),
+            best_ask: wire.best_ask.map(OrderLevel::from),
+            serialized_state: wire.serialized_state,
+            wire,
+        };
+        Ok((update, len))
+    }
+
+    /// Pool address as a [`Pubkey`].
+    pub fn pool_pubkey(&self) -> Pubkey {
+        Pubkey::new(*self.pool_address)
+    }
+
+    /// Raw token mint addresses.
+    pub fn token_mints(&self) -> impl ExactSizeIterator<Item = &'a [u8; 32]> + 'a {
+        self.wire.token_mints()
+    }
+
+    /// Token balances (same order as mints).
+    pub fn token_balances(&self) -> impl ExactSizeIterator<Item = u64> + 'a {
+        self.wire.token_balances()
+    }
+
+    /// Token decimals (same order as mints).
+    pub fn token_decimals(&self) -> impl ExactSizeIterator<Item = i32> + 'a {
+        self.wire.token_decimals()
+    }
+
+    /// Copy into an owned [`PoolUpdate`].
+    pub fn to_owned(&self) -> PoolUpdate {
+        let mut update = PoolUpdate::default();
+        self.write_to(&mut update);
+        update
+    }
+
+    /// Overwrite `update` with this view, reusing its vectors' capacity.
+    pub(crate) fn write_to(&self, update: &mut PoolUpdate) {
+        update.sequence = self.sequence;
+        update.slot = self.slot;
+        update.write_version = self.write_version;
+        update.protocol_name = self.protocol_name.into();
+        update.pool_address = self.pool_pubkey();
+        update.token_mints.clear();
+        update.token_mints.extend(self.token_mints().map(|mint| Pubkey::new(*mint)));
+        update.token_balances.clear();
+        update.token_balances.extend(self.token_balances());
+        update.token_decimals.clear();
+        update.token_decimals.extend(self.token_decimals());
+        update.best_bid = self.best_bid;
+        update.best_ask = self.best_ask;
+        update.serialized_state.clear();
+        update.serialized_state.extend_from_slice(self.serialized_state);
+    }
+}
+
+/// Borrowed view of a pool update batch.
+///
+/// Entries are decoded lazily while iterating.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PoolUpdateBatchRef<'a> {
+    wire: proto::PoolUpdateBatch<'a>,
+    limits: DecodeLimits,
+}
+
+impl<'a> PoolUpdateBatchRef<'a> {
+    /// Wrap a batch payload (without the type byte).
+    pub fn decode(data: &'a [u8]) -> Result<Self, DecodeError> {
+        Self::decode_with_limits(data, &DecodeLimits::default())
+    }
+
+    /// Wrap a batch payload (without the type byte), enforcing `limits`
+    /// on the batch and on every update.
+    pub fn decode_with_limits(data: &'a [u8], limits: &DecodeLimits) -> Result<Self, DecodeError> {
+        Ok(Self {
+            wire: proto::PoolUpdateBatch::parse(data, limits)?,
+            limits: *limits,
+        })
+    }
+
+    /// Upper bound on the updates the payload can actually hold, so a bogus
+    /// count can't drive a large allocation.
+    pub(super) fn capacity(&self) -> usize {
+        self.wire.capacity()
+    }
+
+    /// Number of updates announced by the batch header.
+    pub fn len(&self) -> usize {
+        self.wire.len()
+    }
+
+    /// Whether the batch is empty.
+    pub fn is_empty(&self) -> bool {
+        self.wire.is_empty()
+    }
+
+    /// Iterate over the updates, stopping after the first decode error.
+    pub fn iter(&self) -> PoolUpdateBatchIter<'a> {
+        PoolUpdateBatchIter {
+            entries: self.wire.entries(),
+            limits: self.limits,
+            done: false,
+        }
+    }
+
+    /// Copy every update into an owned [`PoolUpdate`].
+    pub fn to_owned(&self) -> Result<Vec<PoolUpdate>, DecodeError> {
+        let mut updates = Vec::with_capacity(self.capacity());
+        for update in self.iter() {
+            updates.push(update?.to_owned());
+        }
+        Ok(updates)
+    }
+
+    /// Bytes of the batch payload, header included, covered by its
+    /// announced updates.
+    /// Split the batch into per-update payloads without decoding them.
+    #[cfg(feature = "parallel")]
+    pub(crate) fn raw_entries(&self) -> Result<Vec<&'a [u8]>, DecodeError> {
+        let mut entries = Vec::with_capacity(self.capacity());
+        for entry in self.wire.entries() {
+            entries.push(entry?);
+        }
+        Ok(entries)
+    }
+}
+
+impl<'a> IntoIterator for PoolUpdateBatchRef<'a> {
+    type Item = Result<PoolUpdateRef<'a>, DecodeError>;
+    type IntoIter = PoolUpdateBatchIter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Iterator over the updates of a [`PoolUpdateBatchRef`].
+#[derive(Debug, Clone)]
+pub struct PoolUpdateBatchIter<'a> {
+    entries: proto::BatchEntries<'a>,
+    limits: DecodeLimits,
+    done: bool,
+}
+
+impl<'a> Iterator for PoolUpdateBatchIter<'a> {
+    type Item = Result<PoolUpdateRef<'a>, DecodeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let entry = self.entries.next()?;
+        let update = entry
+            .map_err(DecodeError::from)
+            .and_then(|entry| PoolUpdateRef::decode_with_limits(entry, &self.limits));
+        self.done = update.is_err();
+        Some(update)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        if self.done {
+            return (0, Some(0));
+        }
+        self.entries.size_hint()
+    }
+}
+
+/// Decode a binary WebSocket message without copying its payload.
+///
+/// Pool updates, batches and snapshots are returned as borrowed views;
+/// every other message type is decoded as by [`decode_message`](crate::ws::decode_message).
+///
+/// # Arguments
+///
+/// * `msg_type` - Message type byte
+/// * `payload` - Message payload (without type byte)
+///
+/// # Returns
+///
+/// Decoded message, or None for unhandled types
+pub fn decode_message_borrowed(msg_type: u8, payload: &[u8]) -> Result<Option<DecodedMessageRef<'_>>, DecodeError> {
+    decode_message_borrowed_with_limits(msg_type, payload, &DecodeLimits::default())
+}
+
+/// Like [`decode_message_borrowed`], enforcing `limits`.
+pub fn decode_message_borrowed_with_limits<'a>(
+    msg_type: u8,
+    payload: &'a [u8],
+    limits: &DecodeLimits,
+) -> Result<Option<DecodedMessageRef<'a>>, DecodeError> {
+    check_limit("payload", payload.len(), limits.max_payload_len)?;
+    let decoded = match MessageType::try_from(msg_type) {
+        Ok(MessageType::PoolUpdate) => {
+            DecodedMessageRef::PoolUpdate(PoolUpdateRef::decode_with_limits(payload, limits)?)
+        }
+        Ok(MessageType::PoolUpdateBatch) => {
+            DecodedMessageRef::PoolUpdateBatch(PoolUpdateBatchRef::decode_with_limits(payload, limits)?)
+        }
+        Ok(MessageType::PoolSnapshot) => {
+            DecodedMessageRef::PoolSnapshot(PoolUpdateBatchRef::decode_with_limits(payload, limits)?)
+        }
+        _ => return Ok(decoder::decode_message_with_limits(msg_type, payload, limits)?.map(DecodedMessageRef::Owned)),
+    };
+    Ok(Some(decoded))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{Protocol, TimestampMs};
+
+    /// Binary pool update payload (without the type byte).
+    fn encode_pool_update(update: &PoolUpdate) -> Vec<u8> {
+        crate::ws::encode_pool_update(update)[1..].to_vec()
+    }
+
+    fn sample_update(slot: u64) -> PoolUpdate {
+        PoolUpdate {
+            sequence: 7,
+            slot: Slot(slot),
+            write_version: 2,
+            protocol_name: Protocol::Whirlpool,
+            pool_address: Pubkey::new([9; 32]),
+            token_mints: vec![Pubkey::new([1; 32]), Pubkey::new([2; 32])],
+            token_balances: vec![1_000, 2_000],
+            token_decimals: vec![9, 6],
+            best_bid: None,
+            best_ask: Some(OrderLevel { price: 10, size: 1 }),
+            serialized_state: vec![0xAB; 16],
+        }
+    }
+
+    #[test]
+    fn test_borrowed_pool_update_matches_owned() {
+        let update = sample_update(100);
+        let payload = encode_pool_update(&update);
+
+        let view = PoolUpdateRef::decode(&payload).unwrap();
+        assert_eq!(view.protocol_name, "Whirlpool");
+        assert_eq!(view.pool_address, &[9; 32]);
+        assert_eq!(view.token_balances().collect::<Vec<_>>(), [1_000, 2_000]);
+        assert_eq!(view.serialized_state.as_ptr(), payload[8..].as_ptr());
+        assert_eq!(view.to_owned(), update);
+
+        let owned = decoder::decode_message(MessageType::PoolUpdate as u8, &payload).unwrap();
+        assert!(matches!(owned, Some(DecodedMessage::PoolUpdate(u)) if u == update));
+        assert!(PoolUpdateRef::decode(&payload[..payload.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn test_borrowed_batch() {
+        let mut payload = 3u16.to_le_bytes().to_vec();
+        for slot in [1, 2] {
+            let entry = encode_pool_update(&sample_update(slot));
+            payload.extend_from_slice(&(entry.len() as u32).to_le_bytes());
+            payload.extend_from_slice(&entry);
+        }
+        payload.extend_from_slice(&100u32.to_le_bytes());
+
+        let Some(DecodedMessageRef::PoolUpdateBatch(batch)) =
+            decode_message_borrowed(MessageType::PoolUpdateBatch as u8, &payload).unwrap()
+        else {
+            panic!("expected batch");
+        };
+        assert_eq!(batch.len(), 3);
+        let slots: Vec<_> = batch.iter().map(|u| u.map(|u| u.slot)).collect();
+        assert_eq!(slots.len(), 3);
+        assert_eq!(slots[1].as_ref().ok(), Some(&Slot(2)));
+        assert!(slots[2].is_err());
+        assert!(batch.to_owned().is_err());
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_parallel_batch_matches_serial() {
+        let updates: Vec<_> = (0..100).map(sample_update).collect();
+        let mut payload = (updates.len() as u16).to_le_bytes().to_vec();
+        for update in &updates {
+            let entry = encode_pool_update(update);
+            payload.extend_from_slice(&(entry.len() as u32).to_le_bytes());
+            payload.extend_from_slice(&entry);
+        }
+
+        let msg_type = MessageType::PoolSnapshot as u8;
+        for min_parallel in [1, 1000] {
+            let decoded = decoder::decode_message_parallel(msg_type, &payload, min_parallel, &DecodeLimits::default()).unwrap();
+            assert!(matches!(decoded, Some(DecodedMessage::PoolSnapshot(ref u)) if *u == updates));
+        }
+        assert!(decoder::decode_message_parallel(msg_type, &payload[..payload.len() - 1], 1, &DecodeLimits::default()).is_err());
+    }
+
+    #[test]
+    fn test_limits_reject_oversized_prefixes() {
+        let update = sample_update(1);
+        let payload = encode_pool_update(&update);
+
+        // state_len claiming far more than the payload holds
+        let mut bogus = payload.clone();
+        bogus[..8].copy_from_slice(&(u64::MAX / 2).to_le_bytes());
+        assert!(matches!(
+            PoolUpdateRef::decode(&bogus),
+            Err(DecodeError::LimitExceeded { field: "serialized_state", .. })
+        ));
+
+        let limits = DecodeLimits {
+            max_token_mints: 1,
+            ..DecodeLimits::default()
+        };
+        assert!(matches!(
+            PoolUpdateRef::decode_with_limits(&payload, &limits),
+            Err(DecodeError::LimitExceeded { field: "token_mints", limit: 1, actual: 2 })
+        ));
+
+        let limits = DecodeLimits {
+            max_payload_len: 8,
+            ..DecodeLimits::default()
+        };
+        assert!(matches!(
+            decode_message_borrowed_with_limits(MessageType::PoolUpdate as u8, &payload, &limits),
+            Err(DecodeError::LimitExceeded { field: "payload", .. })
+        ));
+
+        // A batch header announcing 65535 updates in a 2-byte payload
+        let header = u16::MAX.to_le_bytes();
+        let batch = PoolUpdateBatchRef::decode(&header).unwrap();
+        assert_eq!(batch.capacity(), 0);
+        assert!(batch.to_owned().is_err());
+    }
+
+    #[test]
+    fn test_other_types_decode_owned() {
+        let payload = 42u64.to_le_bytes();
+        let decoded = decode_message_borrowed(MessageType::Pong as u8, &payload).unwrap();
+        assert!(matches!(
+            decoded,
+            Some(DecodedMessageRef::Owned(DecodedMessage::Pong { timestamp_ms: Some(TimestampMs(42)) }))
+        ));
+    }
+}
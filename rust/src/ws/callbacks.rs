@@ -0,0 +1,308 @@
+//! Callback registries for [`K256WebSocketClient`].
+//!
+//! Every `on_*` event keeps a list of handlers, called in registration
+//! order. Registration returns a [`CallbackHandle`] that unregisters the
+//! handler when removed or dropped, so independent layers (metrics,
+//! business logic) can listen to the same event.
+//!
+//! Callbacks run on the connection task (or a dispatch queue), so slow
+//! work belongs in an [`async_callback`], which moves it onto spawned tasks.
+//!
+//! [`K256WebSocketClient`]: crate::ws::K256WebSocketClient
+
+use std::fmt;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+
+use tokio::sync::{mpsc, Semaphore};
+
+/// Handler with its registration id.
+type Entry<F> = (u64, Arc<F>);
+
+/// Registered handlers of one event.
+///
+/// Emitting works on a snapshot of the list, so handlers may register or
+/// remove callbacks without deadlocking; a handler removed mid-emit may
+/// still see that one event.
+pub(crate) struct Callbacks<F: ?Sized> {
+    next_id: AtomicU64,
+    handlers: RwLock<Arc<[Entry<F>]>>,
+}
+
+/// Handlers taking the event by value.
+pub(crate) type Handlers<T> = Callbacks<dyn Fn(T) + Send + Sync>;
+
+impl<F: ?Sized> Callbacks<F> {
+    pub(crate) fn new() -> Self {
+        Self {
+            next_id: AtomicU64::new(0),
+            handlers: RwLock::new(Arc::new([])),
+        }
+    }
+
+    /// Append `handler`, returning its id.
+    pub(crate) fn add(&self, handler: Arc<F>) -> u64 {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let mut handlers = self.handlers.write().unwrap();
+        let mut updated = handlers.to_vec();
+        updated.push((id, handler));
+        *handlers = updated.into();
+        id
+    }
+
+    /// Remove the handler with `id`. Returns whether it was registered.
+    pub(crate) fn remove(&self, id: u64) -> bool {
+        let mut handlers = self.handlers.write().unwrap();
+        if !handlers.iter().any(|(handler_id, _)| *handler_id == id) {
+            return false;
+        }
+        let updated: Vec<_> = handlers.iter().filter(|(handler_id, _)| *handler_id != id).cloned().collect();
+        *handlers = updated.into();
+        true
+    }
+
+    /// Current handlers, in registration order.
+    pub(crate) fn snapshot(&self) -> Arc<[Entry<F>]> {
+        self.handlers.read().unwrap().clone()
+    }
+}
+
+impl<T: Clone> Handlers<T> {
+    /// Call every handler with `value`, cloning it for all but the last.
+    pub(crate) fn emit(&self, value: T) {
+        call(&self.snapshot(), value);
+    }
+
+    /// Call every handler with a clone of `value`.
+    pub(crate) fn emit_cloned(&self, value: &T) {
+        let handlers = self.snapshot();
+        if !handlers.is_empty() {
+            call(&handlers, value.clone());
+        }
+    }
+
+    /// Emit each of `values` in order.
+    pub(crate) fn emit_each(&self, values: Vec<T>) {
+        let handlers = self.snapshot();
+        if handlers.is_empty() {
+            return;
+        }
+        for value in values {
+            call(&handlers, value);
+        }
+    }
+}
+
+fn call<T: Clone>(handlers: &[Entry<dyn Fn(T) + Send + Sync>], value: T) {
+    let Some(((_, last), rest)) = handlers.split_last() else {
+        return;
+    };
+    for (_, handler) in rest {
+        handler(value.clone());
+    }
+    last(value);
+}
+
+/// Adapt an async handler into a callback for any `on_*` method.
+///
+/// Each event is handed to `handler` on a task spawned with
+/// [`tokio::spawn`], with at most `max_concurrent` (at least 1) of them
+/// running at once; with [`Config::dispatch`] the tasks run on the runtime
+/// the client was connected on. Handlers start in event order; events
+/// arriving while the limit is reached wait in an unbounded queue.
+/// Unregistering the callback lets queued events finish.
+///
+/// [`Config::dispatch`]: crate::ws::Config::dispatch
+///
+/// ```rust,no_run
+/// use k256_sdk::ws::async_callback;
+/// use k256_sdk::{Config, K256WebSocketClient, PoolUpdate};
+///
+/// # async fn store(update: PoolUpdate) {}
+/// let client = K256WebSocketClient::new(Config::default());
+/// let _updates = client.on_pool_update(async_callback(8, |update: PoolUpdate| async move {
+///     store(update).await;
+/// }));
+/// ```
+pub fn async_callback<T, F, Fut>(max_concurrent: usize, handler: F) -> impl Fn(T) + Send + Sync + 'static
+where
+    T: Send + 'static,
+    F: Fn(T) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    let (tx, rx) = mpsc::unbounded_channel::<T>();
+    // The worker starts with the first event, which arrives within the runtime
+    let pending = Mutex::new(Some((rx, handler)));
+    move |value| {
+        if let Some((rx, handler)) = pending.lock().unwrap().take() {
+            tokio::spawn(run_async_callback(rx, handler, max_concurrent.max(1)));
+        }
+        let _ = tx.send(value);
+    }
+}
+
+async fn run_async_callback<T, F, Fut>(mut rx: mpsc::UnboundedReceiver<T>, handler: F, max_concurrent: usize)
+where
+    T: Send + 'static,
+    F: Fn(T) -> Fut,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    let limit = Arc::new(Semaphore::new(max_concurrent));
+    while let Some(value) = rx.recv().await {
+        let Ok(permit) = limit.clone().acquire_owned().await else {
+            return;
+        };
+        let task = handler(value);
+        tokio::spawn(async move {
+            task.await;
+            drop(permit);
+        });
+    }
+}
+
+/// Registration of a client callback.
+///
+/// Dropping the handle, or calling [`remove`](Self::remove), unregisters
+/// the callback. Call [`detach`](Self::detach) to keep it registered for
+/// the lifetime of the client instead.
+///
+/// ```rust,no_run
+/// use k256_sdk::{Config, K256WebSocketClient};
+///
+/// let client = K256WebSocketClient::new(Config::default());
+/// client.on_pool_update(|update| println!("{}", update.pool_address)).detach();
+///
+/// let metrics = client.on_pool_update(|_| { /* count updates */ });
+/// // ...
+/// metrics.remove();
+/// ```
+#[must_use = "dropping a CallbackHandle unregisters the callback; call `detach` to keep it"]
+pub struct CallbackHandle {
+    unregister: Option<Box<dyn FnOnce() + Send + Sync>>,
+}
+
+impl CallbackHandle {
+    pub(crate) fn new(unregister: impl FnOnce() + Send + Sync + 'static) -> Self {
+        Self {
+            unregister: Some(Box::new(unregister)),
+        }
+    }
+
+    /// Unregister the callback.
+    pub fn remove(self) {}
+
+    /// Keep the callback registered for the lifetime of the client.
+    pub fn detach(mut self) {
+        self.unregister = None;
+    }
+}
+
+impl Drop for CallbackHandle {
+    fn drop(&mut self) {
+        if let Some(unregister) = self.unregister.take() {
+            unregister();
+        }
+    }
+}
+
+impl fmt::Debug for CallbackHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CallbackHandle")
+            .field("registered", &self.unregister.is_some())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[test]
+    fn test_handlers_run_in_order_until_removed() {
+        let callbacks: Handlers<u32> = Callbacks::new();
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let ids: Vec<u64> = (0..3)
+            .map(|n| {
+                let seen = seen.clone();
+                callbacks.add(Arc::new(move |value: u32| seen.lock().unwrap().push((n, value))))
+            })
+            .collect();
+
+        callbacks.emit(7);
+        assert!(callbacks.remove(ids[1]));
+        assert!(!callbacks.remove(ids[1]));
+        callbacks.emit_each(vec![8, 9]);
+        assert_eq!(
+            *seen.lock().unwrap(),
+            [(0, 7), (1, 7), (2, 7), (0, 8), (2, 8), (0, 9), (2, 9)]
+        );
+    }
+
+    #[test]
+    fn test_handler_may_register_while_emitting() {
+        let callbacks: Arc<Handlers<u32>> = Arc::new(Callbacks::new());
+        let registry = callbacks.clone();
+        callbacks.add(Arc::new(move |_| {
+            registry.add(Arc::new(|_| {}));
+        }));
+        callbacks.emit(1);
+        assert_eq!(callbacks.snapshot().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_client_handles_unregister_on_drop() {
+        use crate::replay::FrameKind;
+        use crate::types::{PoolUpdate, Slot};
+        use crate::ws::{encode_pool_update, Config, K256WebSocketClient};
+
+        let client = K256WebSocketClient::new(Config::default());
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let metrics_tx = tx.clone();
+        client.on_pool_update(move |update| tx.send(("logic", update.slot.get())).unwrap()).detach();
+        let metrics = client.on_pool_update(move |update| metrics_tx.send(("metrics", update.slot.get())).unwrap());
+
+        let frame = |slot| encode_pool_update(&PoolUpdate { slot: Slot(slot), ..PoolUpdate::default() });
+        client.handle_frame(FrameKind::Binary, &frame(1)).await;
+        drop(metrics);
+        client.handle_frame(FrameKind::Binary, &frame(2)).await;
+
+        let delivered: Vec<_> = std::iter::from_fn(|| rx.try_recv().ok()).collect();
+        assert_eq!(delivered, [("logic", 1), ("metrics", 1), ("logic", 2)]);
+    }
+
+    #[tokio::test]
+    async fn test_async_callback_limits_concurrency() {
+        use std::sync::atomic::AtomicUsize;
+        use std::time::Duration;
+
+        let running = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        let (done_tx, mut done_rx) = tokio::sync::mpsc::unbounded_channel();
+        let callback = {
+            let (running, peak) = (running.clone(), peak.clone());
+            async_callback(2, move |n: u32| {
+                let (running, peak, done_tx) = (running.clone(), peak.clone(), done_tx.clone());
+                async move {
+                    let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                    peak.fetch_max(now, Ordering::SeqCst);
+                    tokio::time::sleep(Duration::from_millis(10)).await;
+                    running.fetch_sub(1, Ordering::SeqCst);
+                    done_tx.send(n).unwrap();
+                }
+            })
+        };
+
+        for n in 0..6 {
+            callback(n);
+        }
+        let mut done = Vec::new();
+        while done.len() < 6 {
+            done.push(done_rx.recv().await.unwrap());
+        }
+        done.sort();
+        assert_eq!(done, [0, 1, 2, 3, 4, 5]);
+        assert_eq!(peak.load(Ordering::SeqCst), 2);
+    }
+}
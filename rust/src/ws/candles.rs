@@ -0,0 +1,240 @@
+//! OHLC candles from the price feed.
+//!
+//! [`CandleAggregator`] buckets [`PriceEntry`] updates by their server
+//! timestamp into fixed-interval candles per mint (e.g. 1s, 15s and 1m) and
+//! hands out each candle once its interval is over. The price feed carries
+//! no traded volume, so candles count price updates instead. Intervals
+//! without updates produce no candle.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use crate::types::{PriceEntry, Pubkey};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::ws::K256WebSocketClient;
+
+/// One interval of price updates for a mint.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Candle {
+    /// Token mint
+    pub mint: Pubkey,
+    /// Candle length
+    pub interval: Duration,
+    /// Start of the interval, Unix milliseconds
+    pub open_time_ms: u64,
+    /// First USD price in the interval
+    pub open: f64,
+    /// Highest USD price in the interval
+    pub high: f64,
+    /// Lowest USD price in the interval
+    pub low: f64,
+    /// Last USD price in the interval
+    pub close: f64,
+    /// Price updates in the interval
+    pub ticks: u64,
+}
+
+impl Candle {
+    fn open_at(entry: &PriceEntry, interval: Duration, open_time_ms: u64) -> Self {
+        Self {
+            mint: entry.mint,
+            interval,
+            open_time_ms,
+            open: entry.usd_price,
+            high: entry.usd_price,
+            low: entry.usd_price,
+            close: entry.usd_price,
+            ticks: 1,
+        }
+    }
+
+    /// End of the interval (exclusive), Unix milliseconds.
+    pub fn close_time_ms(&self) -> u64 {
+        self.open_time_ms + self.interval.as_millis() as u64
+    }
+
+    fn add(&mut self, price: f64) {
+        self.high = self.high.max(price);
+        self.low = self.low.min(price);
+        self.close = price;
+        self.ticks += 1;
+    }
+}
+
+/// Buckets price updates into OHLC candles per mint and interval.
+///
+/// ```rust,no_run
+/// use std::time::Duration;
+/// use k256_sdk::ws::CandleAggregator;
+/// # async fn example(client: &k256_sdk::K256WebSocketClient) {
+/// let aggregator = CandleAggregator::new(&[Duration::from_secs(1), Duration::from_secs(60)]);
+/// let mut candles = aggregator.attach(client);
+/// while let Some(candle) = candles.recv().await {
+///     println!("{} {:?} close={}", candle.mint, candle.interval, candle.close);
+/// }
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct CandleAggregator {
+    /// Candle lengths in milliseconds
+    intervals: Vec<u64>,
+    /// Open candle per (mint, interval in milliseconds)
+    open: HashMap<(Pubkey, u64), Candle>,
+}
+
+impl CandleAggregator {
+    /// Create an aggregator producing candles of each of `intervals`.
+    ///
+    /// Intervals are truncated to whole milliseconds; zero-length ones are
+    /// ignored.
+    pub fn new(intervals: &[Duration]) -> Self {
+        let mut intervals: Vec<u64> = intervals
+            .iter()
+            .map(|interval| interval.as_millis() as u64)
+            .filter(|&ms| ms > 0)
+            .collect();
+        intervals.sort_unstable();
+        intervals.dedup();
+        Self {
+            intervals,
+            open: HashMap::new(),
+        }
+    }
+
+    /// Add a price update, returning the candles it completed.
+    ///
+    /// Updates older than the open candle of their mint and interval are
+    /// ignored.
+    pub fn push(&mut self, entry: &PriceEntry) -> Vec<Candle> {
+        let mut completed = Vec::new();
+        for &interval_ms in &self.intervals {
+            let open_time_ms = entry.timestamp_ms.get() - entry.timestamp_ms.get() % interval_ms;
+            let interval = Duration::from_millis(interval_ms);
+            match self.open.get_mut(&(entry.mint, interval_ms)) {
+                Some(candle) if candle.open_time_ms == open_time_ms => candle.add(entry.usd_price),
+                Some(candle) if candle.open_time_ms > open_time_ms => {}
+                Some(candle) => completed.push(std::mem::replace(
+                    candle,
+                    Candle::open_at(entry, interval, open_time_ms),
+                )),
+                None => {
+                    self.open
+                        .insert((entry.mint, interval_ms), Candle::open_at(entry, interval, open_time_ms));
+                }
+            }
+        }
+        completed
+    }
+
+    /// Complete every open candle whose interval ended by `now_ms`.
+    ///
+    /// Call periodically to emit candles for mints that stopped updating.
+    pub fn flush_until(&mut self, now_ms: u64) -> Vec<Candle> {
+        let mut completed = Vec::new();
+        self.open.retain(|_, candle| {
+            if candle.close_time_ms() <= now_ms {
+                completed.push(*candle);
+                false
+            } else {
+                true
+            }
+        });
+        completed.sort_by_key(|candle| (candle.open_time_ms, candle.interval));
+        completed
+    }
+
+    /// The in-progress candle for `mint` and `interval`, if any.
+    pub fn current(&self, mint: &Pubkey, interval: Duration) -> Option<&Candle> {
+        self.open.get(&(*mint, interval.as_millis() as u64))
+    }
+
+    /// Feed the aggregator from `client`'s price updates and batches.
+    ///
+    /// Returns a stream of completed candles. The aggregator stays attached
+    /// for the lifetime of the client, alongside other price callbacks; call
+    /// [`push`](Self::push) from your own callbacks to control that instead.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn attach(self, client: &K256WebSocketClient) -> tokio::sync::mpsc::UnboundedReceiver<Candle> {
+        use std::sync::{Arc, Mutex};
+
+        let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+        let shared = Arc::new(Mutex::new(self));
+
+        let (aggregator, sender) = (shared.clone(), tx.clone());
+        client.on_price_update(move |entry| {
+            for candle in aggregator.lock().unwrap().push(&entry) {
+                let _ = sender.send(candle);
+            }
+        })
+        .detach();
+        client.on_price_batch(move |entries| {
+            let mut aggregator = shared.lock().unwrap();
+            for entry in &entries {
+                for candle in aggregator.push(entry) {
+                    let _ = tx.send(candle);
+                }
+            }
+        })
+        .detach();
+        rx
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{Slot, TimestampMs};
+
+    const MINT: Pubkey = Pubkey::new([1; 32]);
+
+    fn entry(usd_price: f64, timestamp_ms: u64) -> PriceEntry {
+        PriceEntry {
+            mint: MINT,
+            usd_price,
+            slot: Slot(0),
+            timestamp_ms: TimestampMs(timestamp_ms),
+        }
+    }
+
+    #[test]
+    fn test_buckets_into_intervals() {
+        let mut aggregator = CandleAggregator::new(&[Duration::from_secs(15), Duration::from_secs(1)]);
+        assert!(aggregator.push(&entry(10.0, 1_000)).is_empty());
+        assert!(aggregator.push(&entry(12.0, 1_400)).is_empty());
+        assert!(aggregator.push(&entry(9.0, 1_900)).is_empty());
+
+        let completed = aggregator.push(&entry(11.0, 2_100));
+        assert_eq!(
+            completed,
+            vec![Candle {
+                mint: MINT,
+                interval: Duration::from_secs(1),
+                open_time_ms: 1_000,
+                open: 10.0,
+                high: 12.0,
+                low: 9.0,
+                close: 9.0,
+                ticks: 3,
+            }]
+        );
+
+        let fifteen = aggregator.current(&MINT, Duration::from_secs(15)).unwrap();
+        assert_eq!((fifteen.open_time_ms, fifteen.high, fifteen.close, fifteen.ticks), (0, 12.0, 11.0, 4));
+
+        // Late update for an already completed second is ignored
+        assert!(aggregator.push(&entry(100.0, 1_500)).is_empty());
+        assert_eq!(aggregator.current(&MINT, Duration::from_secs(1)).unwrap().high, 11.0);
+    }
+
+    #[test]
+    fn test_flush_until() {
+        let mut aggregator = CandleAggregator::new(&[Duration::from_secs(1), Duration::from_secs(60)]);
+        aggregator.push(&entry(1.0, 59_500));
+
+        let completed = aggregator.flush_until(60_000);
+        assert_eq!(completed.len(), 2);
+        assert!(completed.iter().all(|candle| candle.close_time_ms() == 60_000));
+        assert!(aggregator.flush_until(120_000).is_empty());
+        assert!(aggregator.current(&MINT, Duration::from_secs(1)).is_none());
+    }
+}
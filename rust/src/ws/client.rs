@@ -1,17 +1,52 @@
 //! K256 WebSocket client implementation.
 
-use std::sync::Arc;
-use std::time::Duration;
+use std::collections::HashSet;
+use std::io::{self, BufWriter, Write};
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, OnceLock, RwLock, Weak};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
-use futures_util::{SinkExt, StreamExt};
-use serde::{Deserialize, Serialize};
-use serde_json;
-use tokio::sync::{mpsc, RwLock};
-use tokio_tungstenite::{connect_async, tungstenite::Message};
-use tracing::{debug, error, info, warn};
+use tokio::runtime::Handle;
+use tokio::sync::{mpsc, oneshot};
+use tokio::time::Sleep;
+use tracing::{debug, error, info, warn, Instrument, Level, Span};
 
-use crate::types::{Blockhash, FeeMarket, Heartbeat, PoolUpdate, PriceEntry, Quote};
-use crate::ws::decoder::decode_message;
+use crate::connector::{self, AuthMethod, DnsConfig, ProxyConfig, TlsConfig};
+use crate::error::K256Error;
+use crate::replay::{self, FrameKind, RawFrame, Recorder};
+use crate::transport::{Frame, Transport, TungsteniteTransport};
+use crate::types::{
+    Blockhash, ConnectionState, FeeMarket, Heartbeat, MessageType, PoolUpdate, PriceEntry, Pubkey, Quote,
+    ReplayComplete, ServerInfo, PROTOCOL_VERSION,
+};
+use crate::ws::decoder::{
+    compat_body, decode_json_message, decode_message_compat, decode_message_truncated, decode_message_with_limits,
+    DecodeError, DecodeLimits, LimitPolicy, UnknownFields,
+};
+use crate::ws::activity::{ActivityConfig, ActivityReport, ActivityTracker};
+use crate::ws::callbacks::{CallbackHandle, Callbacks, Handlers};
+use crate::ws::coalesce::PoolCoalescer;
+use crate::ws::dispatch::{self, DispatchConfig, DispatchQueue, DroppedMessages, Lane};
+use crate::ws::encoder::{encode_message, ClientMessage};
+use crate::ws::fee_filter::FeeAccountFilter;
+use crate::ws::frame_rate::{Admission, FrameRateLimit};
+use crate::ws::liquidity::{LiquidityFilter, FEATURE_MIN_LIQUIDITY};
+use crate::ws::feed_stats::{FeedStats, FeedStatsSnapshot};
+use crate::ws::latency::{LatencyStats, LatencyWindow};
+use crate::ws::message::{DecodedMessage, QuoteRequest, SubscribeRequest};
+use crate::ws::metrics;
+use crate::ws::ordering::{OrderingStats, PoolOrderer};
+use crate::ws::pool_delta::{PoolDelta, PoolDeltaTracker};
+use crate::ws::predicates::MessagePredicates;
+use crate::ws::quota::QuotaError;
+use crate::ws::quote_request::PendingQuotes;
+use crate::ws::sequence::{GapInfo, SequenceTracker};
+use crate::ws::silence::{PoolSilent, SilenceTracker};
+use crate::ws::spans;
+use crate::ws::stats::{ClientStats, StatsCounters};
+use crate::ws::subscription::Channel;
 
 /// Configuration for K256 WebSocket client.
 #[derive(Debug, Clone)]
@@ -20,6 +55,19 @@ pub struct Config {
     pub api_key: String,
     /// WebSocket endpoint URL
     pub endpoint: String,
+    /// How the API key is sent on the handshake
+    pub auth: AuthMethod,
+    /// Additional endpoints (e.g. other regional gateways) used when
+    /// `endpoint` is unreachable
+    pub fallback_endpoints: Vec<String>,
+    /// How to pick among `endpoint` and `fallback_endpoints`
+    pub endpoint_selection: EndpointSelection,
+    /// Custom TLS roots and client certificate
+    pub tls: Option<TlsConfig>,
+    /// HTTP or SOCKS5 proxy
+    pub proxy: Option<ProxyConfig>,
+    /// Address family, custom resolver and re-resolution on reconnect
+    pub dns: DnsConfig,
     /// Whether to automatically reconnect
     pub reconnect: bool,
     /// Initial reconnect delay
@@ -28,6 +76,85 @@ pub struct Config {
     pub reconnect_delay_max: Duration,
     /// Ping interval (0 to disable)
     pub ping_interval: Duration,
+    /// Consecutive unanswered pings before the connection is considered dead
+    /// and a reconnect is triggered (0 to never force a reconnect)
+    pub max_missed_pings: u32,
+    /// Treat the connection as stale when no data or heartbeat frame arrives
+    /// for this long (`None` disables the watchdog)
+    pub stale_after: Option<Duration>,
+    /// Drop and reconnect a stale connection instead of only reporting it
+    pub reconnect_on_stale: bool,
+    /// Track end-to-end latency of timestamped messages over this many
+    /// recent messages (see [`K256WebSocketClient::latency_stats`])
+    pub latency_window: Option<usize>,
+    /// Track pool update rates per protocol and pool over this window
+    /// (see [`K256WebSocketClient::feed_stats`])
+    pub feed_stats_window: Option<Duration>,
+    /// Emit the most active pools and tokens periodically through
+    /// [`K256WebSocketClient::on_activity`]
+    pub activity_report: Option<ActivityConfig>,
+    /// Report jumps in pool update sequence numbers through
+    /// [`K256WebSocketClient::on_gap`]
+    pub detect_sequence_gaps: bool,
+    /// Re-send the subscription with `snapshot` set after a sequence gap
+    /// (requires `detect_sequence_gaps`)
+    pub resnapshot_on_gap: bool,
+    /// Report pools named in the subscription's pool filter that go this
+    /// long without an update through [`K256WebSocketClient::on_pool_silent`]
+    pub pool_silence_after: Option<Duration>,
+    /// When restoring the subscription after a reconnect, ask the server to
+    /// replay pool updates after the last received sequence (see
+    /// [`K256WebSocketClient::on_replay_complete`])
+    pub resume: bool,
+    /// Run callbacks from bounded per-channel queues instead of inline on
+    /// the connection task (see [`DispatchConfig`])
+    pub dispatch: Option<DispatchConfig>,
+    /// Decode binary data frames on a separate task fed by a queue of this
+    /// many frames, so decoding large batches never delays pings or close
+    /// frames on the connection task (`None` decodes inline). Socket reads
+    /// pause while the queue is full.
+    pub decode_queue: Option<usize>,
+    /// Hold pool updates for this long, then deliver them in increasing
+    /// `(slot, write_version)` order per pool, dropping updates older than
+    /// one already delivered (`None` delivers in arrival order; see
+    /// [`K256WebSocketClient::ordering_stats`])
+    pub ordered_delivery: Option<Duration>,
+    /// Deliver only the newest update per pool once per interval
+    /// (`None` delivers every update)
+    pub pool_coalesce_interval: Option<Duration>,
+    /// Size limits applied to incoming binary frames
+    pub decode_limits: DecodeLimits,
+    /// Whether frames over `decode_limits` are dropped or truncated; either
+    /// way the violation is reported through
+    /// [`K256WebSocketClient::on_error`]
+    pub limit_policy: LimitPolicy,
+    /// Accept at most this many binary data frames per second and drop the
+    /// rest, reporting the first drop of each second through
+    /// [`K256WebSocketClient::on_error`] (`None` accepts every frame).
+    /// Pongs, errors and subscription confirmations are never dropped.
+    pub max_frames_per_sec: Option<u32>,
+    /// Give up on an endpoint whose connection handshake takes longer than
+    /// this (`None` waits indefinitely)
+    pub connect_timeout: Option<Duration>,
+    /// Fail requests such as [`K256WebSocketClient::subscribe`] that are not
+    /// written to the connection within this long, e.g. while it is stalled
+    /// or reconnecting (`None` waits indefinitely)
+    pub request_timeout: Option<Duration>,
+    /// Subscribe in forward-compatibility mode and decode data messages
+    /// with [`decode_message_compat`](crate::ws::decode_message_compat):
+    /// trailing fields from a newer protocol revision are skipped and
+    /// counted in [`ClientStats::unknown_field_messages`] and passed to
+    /// [`K256WebSocketClient::on_unknown_fields`] instead of risking
+    /// misdecoding. Batches are then always decoded on the connection task.
+    pub forward_compat: bool,
+    /// Level of the `tracing` spans following each connection and message
+    /// from decoding to callbacks (see [`spans`](crate::ws::spans); `None`
+    /// creates no spans)
+    pub trace_spans: Option<Level>,
+    /// Decode pool update batches with at least this many updates on the
+    /// rayon thread pool (`None` always decodes on the connection task)
+    #[cfg(feature = "parallel")]
+    pub parallel_decode_threshold: Option<usize>,
 }
 
 impl Default for Config {
@@ -35,428 +162,2173 @@ impl Default for Config {
         Self {
             api_key: String::new(),
             endpoint: "wss://gateway.k256.xyz/v1/ws".to_string(),
+            auth: AuthMethod::QueryParam,
+            fallback_endpoints: Vec::new(),
+            endpoint_selection: EndpointSelection::Failover,
+            tls: None,
+            proxy: None,
+            dns: DnsConfig::default(),
             reconnect: true,
             reconnect_delay_initial: Duration::from_secs(1),
             reconnect_delay_max: Duration::from_secs(60),
             ping_interval: Duration::from_secs(30),
+            max_missed_pings: 3,
+            stale_after: None,
+            reconnect_on_stale: false,
+            latency_window: None,
+            feed_stats_window: None,
+            activity_report: None,
+            detect_sequence_gaps: false,
+            resnapshot_on_gap: false,
+            pool_silence_after: None,
+            resume: false,
+            dispatch: None,
+            decode_queue: None,
+            ordered_delivery: None,
+            pool_coalesce_interval: None,
+            decode_limits: DecodeLimits::default(),
+            limit_policy: LimitPolicy::Reject,
+            max_frames_per_sec: None,
+            connect_timeout: Some(Duration::from_secs(10)),
+            request_timeout: Some(Duration::from_secs(10)),
+            forward_compat: false,
+            trace_spans: Some(Level::DEBUG),
+            #[cfg(feature = "parallel")]
+            parallel_decode_threshold: None,
         }
     }
 }
 
-/// WebSocket subscription request.
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct SubscribeRequest {
-    /// Request type (always "subscribe")
-    #[serde(rename = "type")]
-    pub request_type: String,
-    /// List of channels to subscribe to
-    pub channels: Vec<String>,
-    /// Message format ("binary" or "json")
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub format: Option<String>,
-    /// Optional list of DEX protocols to filter
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub protocols: Option<Vec<String>>,
-    /// Optional list of pool addresses to filter
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub pools: Option<Vec<String>>,
-    /// Optional list of token pairs to filter
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub token_pairs: Option<Vec<(String, String)>>,
+/// Endpoint selection policy for multi-endpoint configurations.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum EndpointSelection {
+    /// Prefer `endpoint`; on each reconnect rotate to the next endpoint in
+    /// the list, skipping endpoints that fail to connect
+    #[default]
+    Failover,
+    /// Connect to the endpoint with the lowest TCP connect latency,
+    /// re-measured before every (re)connect through `proxy` and `dns` like
+    /// the connection itself
+    LeastLatency,
 }
 
-impl Default for SubscribeRequest {
-    fn default() -> Self {
+/// How long to wait for the transport to close a finished session.
+const CLOSE_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// `ConnectionState::Closed` reason after [`K256WebSocketClient::disconnect`].
+const CLOSED_BY_CLIENT: &str = "closed by client";
+
+/// Request ending every subscription on the connection.
+const UNSUBSCRIBE_ALL: &str = r#"{"type":"unsubscribe"}"#;
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+type FrameRecorder = Recorder<BufWriter<Box<dyn Write + Send>>>;
+type RawFrameTap = Callbacks<dyn Fn(&RawFrame<'_>) + Send + Sync>;
+
+/// Pending `subscribe_and_wait` call.
+struct SubscribeWaiter {
+    /// Requested channels, matched against the confirmed ones
+    channels: Vec<String>,
+    /// Confirmed channels, or the server's rejection message
+    tx: oneshot::Sender<Result<Vec<String>, String>>,
+}
+
+/// Frame queued for the connection task.
+struct Outgoing {
+    frame: Frame,
+    /// Told whether the frame was written, for a request awaiting it
+    written: Option<oneshot::Sender<Result<(), String>>>,
+}
+
+/// Keepalive bookkeeping for the active connection.
+#[derive(Debug, Default)]
+struct Keepalive {
+    /// When the outstanding ping was sent, if no pong arrived yet
+    ping_sent_at: Option<Instant>,
+    /// Consecutive pings that went unanswered
+    missed: u32,
+    /// Last measured ping round-trip time
+    latency: Option<Duration>,
+}
+
+/// Binary frame waiting for the decode task.
+struct InboundFrame {
+    data: Vec<u8>,
+    received_us: u64,
+}
+
+/// Queue to the decode task, when `Config::decode_queue` is set.
+struct DecodeStage {
+    tx: mpsc::Sender<InboundFrame>,
+    /// Taken by the decode task when the client first connects
+    rx: Mutex<Option<mpsc::Receiver<InboundFrame>>>,
+}
+
+impl DecodeStage {
+    fn new(capacity: usize) -> Self {
+        let (tx, rx) = mpsc::channel(capacity.max(1));
         Self {
-            request_type: "subscribe".to_string(),
-            channels: vec![
-                "pools".to_string(),
-                "priority_fees".to_string(),
-                "blockhash".to_string(),
-            ],
-            format: None,
-            protocols: None,
-            pools: None,
-            token_pairs: None,
+            tx,
+            rx: Mutex::new(Some(rx)),
         }
     }
 }
 
-/// Decoded WebSocket message.
-#[derive(Debug, Clone)]
-pub enum DecodedMessage {
-    /// Pool update
-    PoolUpdate(PoolUpdate),
-    /// Batch of pool updates
-    PoolUpdateBatch(Vec<PoolUpdate>),
-    /// Fee market update (per-writable-account)
-    FeeMarket(FeeMarket),
-    /// Blockhash
-    Blockhash(Blockhash),
-    /// Quote
-    Quote(Quote),
-    /// Heartbeat
-    Heartbeat(Heartbeat),
-    /// Single price update
-    PriceUpdate(PriceEntry),
-    /// Batch of price updates
-    PriceBatch(Vec<PriceEntry>),
-    /// Full price snapshot
-    PriceSnapshot(Vec<PriceEntry>),
-    /// Error message
-    Error(String),
-    /// Subscription confirmed
-    Subscribed { channels: Vec<String> },
+/// Endpoint bookkeeping for failover.
+#[derive(Debug, Default)]
+struct EndpointState {
+    /// Index of the endpoint to try first on the next connect
+    next: usize,
+    /// Endpoint of the live connection
+    active: Option<String>,
+    /// Remote address of the live connection, if the transport reports it
+    peer: Option<SocketAddr>,
 }
 
-type Callback<T> = Arc<RwLock<Option<Box<dyn Fn(T) + Send + Sync + 'static>>>>;
+/// State shared between the client handle and its connection task.
+struct Inner {
+    config: Config,
+    outbound: tokio::sync::Mutex<mpsc::Receiver<Outgoing>>,
+    /// Sender side of `outbound`, for requests made by the connection task
+    outbound_tx: mpsc::Sender<Outgoing>,
+    /// Connection to the gateway, held by the connection task while a session runs
+    transport: tokio::sync::Mutex<Box<dyn Transport>>,
+    keepalive: Mutex<Keepalive>,
+    /// Counters behind `K256WebSocketClient::stats`
+    stats: StatsCounters,
+    /// Recent message latencies, when `Config::latency_window` is set
+    latency: Option<Mutex<LatencyWindow>>,
+    /// Pool update rates, when `Config::feed_stats_window` is set
+    feed_stats: Option<Mutex<FeedStats>>,
+    /// Pool and token activity, when `Config::activity_report` is set
+    activity: Option<Mutex<ActivityTracker>>,
+    /// Data frame rate cap, when `Config::max_frames_per_sec` is set
+    frame_rate: Option<Mutex<FrameRateLimit>>,
+    /// Effective subscription, re-sent after every reconnect
+    subscription: Mutex<Option<SubscribeRequest>>,
+    /// Client-side fee account filter, from the subscription's `fee_accounts`
+    fee_filter: Mutex<Option<FeeAccountFilter>>,
+    /// Client-side pool liquidity floor, from the subscription's `min_liquidity_usd`
+    liquidity: Mutex<Option<LiquidityFilter>>,
+    /// User predicates evaluated before dispatch
+    predicates: RwLock<MessagePredicates>,
+    /// Channels whose messages are dropped before dispatch
+    paused: RwLock<HashSet<Channel>>,
+    /// Last seen pool update sequence, when `Config::detect_sequence_gaps` is set
+    sequence: Mutex<SequenceTracker>,
+    /// Last balances per pool, for `on_pool_delta`
+    pool_deltas: Mutex<PoolDeltaTracker>,
+    /// Inactivity timers of the pools in the subscription's pool filter
+    silence: Mutex<SilenceTracker>,
+    /// Set while a snapshot requested after a gap has not arrived
+    resnapshot_pending: AtomicBool,
+    /// Newest pool update sequence received, when `Config::resume` is set
+    resume_sequence: Mutex<Option<u64>>,
+    /// Active frame recording, if any
+    recorder: Mutex<Option<FrameRecorder>>,
+    /// Sees every inbound frame before decoding
+    on_raw_frame: RawFrameTap,
+    /// Decode task queue (`None` when frames are decoded inline)
+    decode_stage: Option<DecodeStage>,
+    /// Per-channel dispatch queues (empty when callbacks run inline)
+    queues: Vec<Arc<DispatchQueue>>,
+    /// Runtime the client was connected on, entered by the dispatch threads
+    runtime: OnceLock<Handle>,
+    /// Held pool updates when ordered delivery is enabled
+    orderer: Option<PoolOrderer>,
+    ordering_started: AtomicBool,
+    /// Pending pool updates when coalescing is enabled
+    coalescer: Option<PoolCoalescer>,
+    coalesce_started: AtomicBool,
+    this: Weak<Inner>,
+    endpoint: Mutex<EndpointState>,
+    /// `k256.connection` span of the live connection
+    connection_span: Mutex<Span>,
+    /// Set while the watchdog considers the connection stale
+    stale: AtomicBool,
+    /// Current lifecycle state, reported through `on_connection_state`
+    state: Mutex<ConnectionState>,
+    /// Set by `K256WebSocketClient::disconnect`, ending the connection task
+    closing: tokio::sync::watch::Sender<bool>,
+    /// Whether the server confirmed the subscription on this connection
+    session_subscribed: AtomicBool,
+    /// `subscribe_and_wait` calls awaiting the server's answer
+    subscribe_waiters: Mutex<Vec<SubscribeWaiter>>,
+    /// `request_quote` calls awaiting their answer
+    quote_requests: PendingQuotes,
+    /// Latest rate-limit or quota error, cleared by a confirmed subscription
+    quota: Mutex<Option<QuotaError>>,
+    /// Server details from the latest subscription confirmation
+    server_info: Mutex<Option<ServerInfo>>,
+    /// Whether unknown trailing fields were already logged
+    unknown_fields_seen: AtomicBool,
+    on_subscribed: Handlers<Vec<String>>,
+    on_connection_state: Handlers<ConnectionState>,
+    on_stale: Handlers<Duration>,
+    on_gap: Handlers<GapInfo>,
+    on_pool_silent: Handlers<PoolSilent>,
+    on_activity: Handlers<ActivityReport>,
+    on_pool_delta: Handlers<PoolDelta>,
+    on_replay_complete: Handlers<ReplayComplete>,
+    on_unknown_fields: Handlers<UnknownFields>,
+    on_message: Handlers<DecodedMessage>,
+    on_pool_update: Handlers<PoolUpdate>,
+    on_pool_snapshot: Handlers<Vec<PoolUpdate>>,
+    on_fee_market: Handlers<FeeMarket>,
+    on_blockhash: Handlers<Blockhash>,
+    on_quote: Handlers<Quote>,
+    on_heartbeat: Handlers<Heartbeat>,
+    on_price_update: Handlers<PriceEntry>,
+    on_price_batch: Handlers<Vec<PriceEntry>>,
+    on_price_snapshot: Handlers<Vec<PriceEntry>>,
+    on_error: Handlers<String>,
+    on_quota_warning: Handlers<QuotaError>,
+}
 
 /// K256 WebSocket client for real-time Solana liquidity data.
+///
+/// Each `on_*` method adds a callback next to those already registered for
+/// the event; they run in registration order. The returned
+/// [`CallbackHandle`] unregisters the callback when dropped, so keep it, or
+/// call [`CallbackHandle::detach`] to keep the callback for the lifetime of
+/// the client.
+///
+/// Callbacks run inline on the connection task unless
+/// [`Config::dispatch`] is set; wrap handlers that await I/O in
+/// [`async_callback`](crate::ws::async_callback) so they do not stall it.
 pub struct K256WebSocketClient {
-    config: Config,
-    tx: mpsc::Sender<Message>,
-    on_pool_update: Callback<PoolUpdate>,
-    on_fee_market: Callback<FeeMarket>,
-    on_blockhash: Callback<Blockhash>,
-    on_quote: Callback<Quote>,
-    on_heartbeat: Callback<Heartbeat>,
-    on_price_update: Callback<PriceEntry>,
-    on_price_batch: Callback<Vec<PriceEntry>>,
-    on_price_snapshot: Callback<Vec<PriceEntry>>,
-    on_error: Callback<String>,
+    inner: Arc<Inner>,
+    tx: mpsc::Sender<Outgoing>,
 }
 
 impl K256WebSocketClient {
     /// Create a new WebSocket client with the given configuration.
     pub fn new(config: Config) -> Self {
-        let (tx, _rx) = mpsc::channel(100);
-        Self {
-            config,
-            tx,
-            on_pool_update: Arc::new(RwLock::new(None)),
-            on_fee_market: Arc::new(RwLock::new(None)),
-            on_blockhash: Arc::new(RwLock::new(None)),
-            on_quote: Arc::new(RwLock::new(None)),
-            on_heartbeat: Arc::new(RwLock::new(None)),
-            on_price_update: Arc::new(RwLock::new(None)),
-            on_price_batch: Arc::new(RwLock::new(None)),
-            on_price_snapshot: Arc::new(RwLock::new(None)),
-            on_error: Arc::new(RwLock::new(None)),
-        }
+        let transport = TungsteniteTransport::new(&config);
+        Self::with_transport(config, transport)
+    }
+
+    /// Create a client that connects through `transport` instead of the
+    /// default [`TungsteniteTransport`] (see [`crate::transport`]).
+    ///
+    /// `Config::auth`, `Config::tls` and `Config::proxy` are up to the
+    /// transport; endpoints, reconnects and keepalive still follow `config`.
+    pub fn with_transport(config: Config, transport: impl Transport) -> Self {
+        let (tx, rx) = mpsc::channel(100);
+        let queues: Vec<_> = match &config.dispatch {
+            Some(dispatch) => Lane::ALL
+                .into_iter()
+                .map(|lane| DispatchQueue::new(lane, dispatch))
+                .collect(),
+            None => Vec::new(),
+        };
+        let decode_stage = config.decode_queue.map(DecodeStage::new);
+        let orderer = config.ordered_delivery.map(|_| PoolOrderer::default());
+        let coalescer = config.pool_coalesce_interval.map(|_| PoolCoalescer::default());
+        let latency = config.latency_window.map(|window| Mutex::new(LatencyWindow::new(window)));
+        let feed_stats = config.feed_stats_window.map(|window| Mutex::new(FeedStats::new(window)));
+        let activity = config.activity_report.map(|activity| Mutex::new(ActivityTracker::new(activity)));
+        let frame_rate = config
+            .max_frames_per_sec
+            .map(|max| Mutex::new(FrameRateLimit::new(max, Instant::now())));
+        let silence = Mutex::new(SilenceTracker::new(config.pool_silence_after));
+        let inner = Arc::new_cyclic(|weak: &Weak<Inner>| {
+            for queue in &queues {
+                spawn_dispatcher(queue.clone(), weak.clone());
+            }
+            Inner {
+                config,
+                outbound: tokio::sync::Mutex::new(rx),
+                outbound_tx: tx.clone(),
+                transport: tokio::sync::Mutex::new(Box::new(transport)),
+                keepalive: Mutex::new(Keepalive::default()),
+                stats: StatsCounters::default(),
+                latency,
+                feed_stats,
+                activity,
+                frame_rate,
+                subscription: Mutex::new(None),
+                fee_filter: Mutex::new(None),
+                liquidity: Mutex::new(None),
+                predicates: RwLock::new(MessagePredicates::default()),
+                paused: RwLock::new(HashSet::new()),
+                sequence: Mutex::new(SequenceTracker::default()),
+                pool_deltas: Mutex::new(PoolDeltaTracker::new()),
+                silence,
+                resnapshot_pending: AtomicBool::new(false),
+                resume_sequence: Mutex::new(None),
+                recorder: Mutex::new(None),
+                on_raw_frame: Callbacks::new(),
+                decode_stage,
+                queues,
+                runtime: OnceLock::new(),
+                orderer,
+                ordering_started: AtomicBool::new(false),
+                coalescer,
+                coalesce_started: AtomicBool::new(false),
+                this: weak.clone(),
+                endpoint: Mutex::new(EndpointState::default()),
+                connection_span: Mutex::new(Span::none()),
+                stale: AtomicBool::new(false),
+                state: Mutex::new(ConnectionState::default()),
+                closing: tokio::sync::watch::Sender::new(false),
+                session_subscribed: AtomicBool::new(false),
+                subscribe_waiters: Mutex::new(Vec::new()),
+                quote_requests: PendingQuotes::default(),
+                quota: Mutex::new(None),
+                server_info: Mutex::new(None),
+                unknown_fields_seen: AtomicBool::new(false),
+                on_subscribed: Callbacks::new(),
+                on_connection_state: Callbacks::new(),
+                on_stale: Callbacks::new(),
+                on_gap: Callbacks::new(),
+                on_pool_silent: Callbacks::new(),
+                on_activity: Callbacks::new(),
+                on_pool_delta: Callbacks::new(),
+                on_replay_complete: Callbacks::new(),
+                on_unknown_fields: Callbacks::new(),
+                on_message: Callbacks::new(),
+                on_pool_update: Callbacks::new(),
+                on_pool_snapshot: Callbacks::new(),
+                on_fee_market: Callbacks::new(),
+                on_blockhash: Callbacks::new(),
+                on_quote: Callbacks::new(),
+                on_heartbeat: Callbacks::new(),
+                on_price_update: Callbacks::new(),
+                on_price_batch: Callbacks::new(),
+                on_price_snapshot: Callbacks::new(),
+                on_error: Callbacks::new(),
+                on_quota_warning: Callbacks::new(),
+            }
+        });
+        Self { inner, tx }
+    }
+
+    fn register<F: ?Sized + 'static>(&self, select: fn(&Inner) -> &Callbacks<F>, callback: Arc<F>) -> CallbackHandle {
+        let id = select(&self.inner).add(callback);
+        let inner = Arc::downgrade(&self.inner);
+        CallbackHandle::new(move || {
+            if let Some(inner) = inner.upgrade() {
+                select(&inner).remove(id);
+            }
+        })
+    }
+
+    /// Register a callback receiving every decoded message.
+    ///
+    /// Runs before the type-specific callbacks below.
+    pub fn on_message<F>(&self, callback: F) -> CallbackHandle
+    where
+        F: Fn(DecodedMessage) + Send + Sync + 'static,
+    {
+        self.register(|inner| &inner.on_message, Arc::new(callback) as Arc<_>)
     }
 
     /// Register a callback for pool updates.
-    pub fn on_pool_update<F>(&self, callback: F)
+    pub fn on_pool_update<F>(&self, callback: F) -> CallbackHandle
     where
         F: Fn(PoolUpdate) + Send + Sync + 'static,
     {
-        let rt = tokio::runtime::Handle::current();
-        rt.block_on(async {
-            *self.on_pool_update.write().await = Some(Box::new(callback));
-        });
+        self.register(|inner| &inner.on_pool_update, Arc::new(callback) as Arc<_>)
+    }
+
+    /// Register a callback for the initial pool snapshot.
+    ///
+    /// Snapshot entries are also delivered to the pool update callback.
+    pub fn on_pool_snapshot<F>(&self, callback: F) -> CallbackHandle
+    where
+        F: Fn(Vec<PoolUpdate>) + Send + Sync + 'static,
+    {
+        self.register(|inner| &inner.on_pool_snapshot, Arc::new(callback) as Arc<_>)
     }
 
     /// Register a callback for fee market updates.
-    pub fn on_fee_market<F>(&self, callback: F)
+    pub fn on_fee_market<F>(&self, callback: F) -> CallbackHandle
     where
         F: Fn(FeeMarket) + Send + Sync + 'static,
     {
-        let rt = tokio::runtime::Handle::current();
-        rt.block_on(async {
-            *self.on_fee_market.write().await = Some(Box::new(callback));
-        });
+        self.register(|inner| &inner.on_fee_market, Arc::new(callback) as Arc<_>)
     }
 
     /// Register a callback for blockhash updates.
-    pub fn on_blockhash<F>(&self, callback: F)
+    pub fn on_blockhash<F>(&self, callback: F) -> CallbackHandle
     where
         F: Fn(Blockhash) + Send + Sync + 'static,
     {
-        let rt = tokio::runtime::Handle::current();
-        rt.block_on(async {
-            *self.on_blockhash.write().await = Some(Box::new(callback));
-        });
+        self.register(|inner| &inner.on_blockhash, Arc::new(callback) as Arc<_>)
     }
 
     /// Register a callback for quote updates.
-    pub fn on_quote<F>(&self, callback: F)
+    pub fn on_quote<F>(&self, callback: F) -> CallbackHandle
     where
         F: Fn(Quote) + Send + Sync + 'static,
     {
-        let rt = tokio::runtime::Handle::current();
-        rt.block_on(async {
-            *self.on_quote.write().await = Some(Box::new(callback));
-        });
+        self.register(|inner| &inner.on_quote, Arc::new(callback) as Arc<_>)
     }
 
     /// Register a callback for heartbeat messages.
-    pub fn on_heartbeat<F>(&self, callback: F)
+    pub fn on_heartbeat<F>(&self, callback: F) -> CallbackHandle
     where
         F: Fn(Heartbeat) + Send + Sync + 'static,
     {
-        let rt = tokio::runtime::Handle::current();
-        rt.block_on(async {
-            *self.on_heartbeat.write().await = Some(Box::new(callback));
-        });
+        self.register(|inner| &inner.on_heartbeat, Arc::new(callback) as Arc<_>)
     }
 
     /// Register a callback for price updates.
-    pub fn on_price_update<F>(&self, callback: F)
+    pub fn on_price_update<F>(&self, callback: F) -> CallbackHandle
     where
         F: Fn(PriceEntry) + Send + Sync + 'static,
     {
-        let rt = tokio::runtime::Handle::current();
-        rt.block_on(async {
-            *self.on_price_update.write().await = Some(Box::new(callback));
-        });
+        self.register(|inner| &inner.on_price_update, Arc::new(callback) as Arc<_>)
     }
 
     /// Register a callback for price batch updates.
-    pub fn on_price_batch<F>(&self, callback: F)
+    pub fn on_price_batch<F>(&self, callback: F) -> CallbackHandle
     where
         F: Fn(Vec<PriceEntry>) + Send + Sync + 'static,
     {
-        let rt = tokio::runtime::Handle::current();
-        rt.block_on(async {
-            *self.on_price_batch.write().await = Some(Box::new(callback));
-        });
+        self.register(|inner| &inner.on_price_batch, Arc::new(callback) as Arc<_>)
     }
 
     /// Register a callback for price snapshots.
-    pub fn on_price_snapshot<F>(&self, callback: F)
+    pub fn on_price_snapshot<F>(&self, callback: F) -> CallbackHandle
     where
         F: Fn(Vec<PriceEntry>) + Send + Sync + 'static,
     {
-        let rt = tokio::runtime::Handle::current();
-        rt.block_on(async {
-            *self.on_price_snapshot.write().await = Some(Box::new(callback));
-        });
+        self.register(|inner| &inner.on_price_snapshot, Arc::new(callback) as Arc<_>)
     }
 
     /// Register a callback for errors.
-    pub fn on_error<F>(&self, callback: F)
+    ///
+    /// Receives server error messages, and frames dropped or truncated by
+    /// `Config::decode_limits` and `Config::max_frames_per_sec`.
+    pub fn on_error<F>(&self, callback: F) -> CallbackHandle
     where
         F: Fn(String) + Send + Sync + 'static,
     {
-        let rt = tokio::runtime::Handle::current();
-        rt.block_on(async {
-            *self.on_error.write().await = Some(Box::new(callback));
-        });
+        self.register(|inner| &inner.on_error, Arc::new(callback) as Arc<_>)
     }
 
-    /// Connect to the K256 WebSocket.
-    pub async fn connect(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        let url = format!("{}?apiKey={}", self.config.endpoint, self.config.api_key);
+    /// Register a callback for rate-limit and quota errors.
+    ///
+    /// Fires for server errors [`QuotaError::parse`] recognizes, after the
+    /// [`on_error`](Self::on_error) callbacks; the latest one is also
+    /// available from [`quota`](Self::quota).
+    pub fn on_quota_warning<F>(&self, callback: F) -> CallbackHandle
+    where
+        F: Fn(QuotaError) + Send + Sync + 'static,
+    {
+        self.register(|inner| &inner.on_quota_warning, Arc::new(callback) as Arc<_>)
+    }
+
+    /// Register a callback fired when the connection goes stale.
+    ///
+    /// Receives the time since the last data or heartbeat frame. Fires once
+    /// per stale period, on the connection task; requires
+    /// [`Config::stale_after`].
+    pub fn on_stale<F>(&self, callback: F) -> CallbackHandle
+    where
+        F: Fn(Duration) + Send + Sync + 'static,
+    {
+        self.register(|inner| &inner.on_stale, Arc::new(callback) as Arc<_>)
+    }
+
+    /// Register a callback fired when the server confirms a subscription.
+    ///
+    /// Receives the confirmed channels, also after a subscription is
+    /// restored on reconnect.
+    pub fn on_subscribed<F>(&self, callback: F) -> CallbackHandle
+    where
+        F: Fn(Vec<String>) + Send + Sync + 'static,
+    {
+        self.register(|inner| &inner.on_subscribed, Arc::new(callback) as Arc<_>)
+    }
+
+    /// Register a callback fired when pool update sequence numbers skip ahead.
+    ///
+    /// Runs on the connection task; requires [`Config::detect_sequence_gaps`].
+    /// Gaps across reconnects are reported too. With
+    /// [`Config::resnapshot_on_gap`] a fresh pool snapshot follows.
+    pub fn on_gap<F>(&self, callback: F) -> CallbackHandle
+    where
+        F: Fn(GapInfo) + Send + Sync + 'static,
+    {
+        self.register(|inner| &inner.on_gap, Arc::new(callback) as Arc<_>)
+    }
+
+    /// Register a callback fired when a subscribed pool stops updating.
+    ///
+    /// Covers the pools named in the subscription's pool filter. Fires once
+    /// per silence, on the connection task; the pool's next update clears
+    /// it. Requires [`Config::pool_silence_after`] or a timeout from
+    /// [`set_pool_silence_after`](Self::set_pool_silence_after).
+    pub fn on_pool_silent<F>(&self, callback: F) -> CallbackHandle
+    where
+        F: Fn(PoolSilent) + Send + Sync + 'static,
+    {
+        self.register(|inner| &inner.on_pool_silent, Arc::new(callback) as Arc<_>)
+    }
+
+    /// Register a callback for the balance change of a pool between two
+    /// consecutive delivered updates (see [`PoolDelta`]).
+    ///
+    /// Pool snapshots and reconnects reset the balances compared against,
+    /// so gaps are never reported as trades. Runs before the
+    /// [`on_pool_update`](Self::on_pool_update) callbacks of the update.
+    pub fn on_pool_delta<F>(&self, callback: F) -> CallbackHandle
+    where
+        F: Fn(PoolDelta) + Send + Sync + 'static,
+    {
+        self.register(|inner| &inner.on_pool_delta, Arc::new(callback) as Arc<_>)
+    }
+
+    /// Register a callback for periodic reports of the most active pools
+    /// and tokens.
+    ///
+    /// Fires every [`ActivityConfig::interval`] on the connection task while
+    /// connected. Counts every decoded update, before client-side filters.
+    /// Requires [`Config::activity_report`].
+    pub fn on_activity<F>(&self, callback: F) -> CallbackHandle
+    where
+        F: Fn(ActivityReport) + Send + Sync + 'static,
+    {
+        self.register(|inner| &inner.on_activity, Arc::new(callback) as Arc<_>)
+    }
+
+    /// Set the inactivity timeout of `pool`, overriding
+    /// [`Config::pool_silence_after`]; `None` restores the default.
+    ///
+    /// Only applies while `pool` is in the subscription's pool filter.
+    pub fn set_pool_silence_after(&self, pool: Pubkey, after: Option<Duration>) {
+        self.inner.silence.lock().unwrap().set_timeout(pool, after);
+    }
+
+    /// Register a callback fired when the server finished replaying the
+    /// pool updates missed while disconnected.
+    ///
+    /// Requires [`Config::resume`]. Replayed updates reach the pool update
+    /// callbacks before this fires; a [`ReplayComplete::truncated`] replay
+    /// means the server could not reach back to the last received sequence.
+    pub fn on_replay_complete<F>(&self, callback: F) -> CallbackHandle
+    where
+        F: Fn(ReplayComplete) + Send + Sync + 'static,
+    {
+        self.register(|inner| &inner.on_replay_complete, Arc::new(callback) as Arc<_>)
+    }
+
+    /// Register a callback fired with the trailing fields of each message
+    /// from a newer protocol revision.
+    ///
+    /// Runs on the connection task before the message itself is delivered;
+    /// requires [`Config::forward_compat`]. Without it such fields cannot be
+    /// told apart and are not reported.
+    pub fn on_unknown_fields<F>(&self, callback: F) -> CallbackHandle
+    where
+        F: Fn(UnknownFields) + Send + Sync + 'static,
+    {
+        self.register(|inner| &inner.on_unknown_fields, Arc::new(callback) as Arc<_>)
+    }
+
+    /// Register a callback fired on every connection lifecycle change.
+    ///
+    /// Runs on the connection task; use it to gate trading logic on
+    /// connection health (see [`ConnectionState`]).
+    pub fn on_connection_state<F>(&self, callback: F) -> CallbackHandle
+    where
+        F: Fn(ConnectionState) + Send + Sync + 'static,
+    {
+        self.register(|inner| &inner.on_connection_state, Arc::new(callback) as Arc<_>)
+    }
+
+    /// Only deliver pool updates for which `predicate` returns `true`.
+    ///
+    /// Applies to single updates, batches and snapshots, after sequence gap
+    /// detection and before coalescing. Replaces any previous pool filter.
+    pub fn set_pool_filter<F>(&self, predicate: F)
+    where
+        F: Fn(&PoolUpdate) -> bool + Send + Sync + 'static,
+    {
+        self.inner.predicates.write().unwrap().pool = Some(Box::new(predicate));
+    }
+
+    /// Only deliver fee market updates for which `predicate` returns `true`.
+    ///
+    /// Sees the update after the `fee_accounts` filter of the subscription.
+    /// Replaces any previous fee market filter.
+    pub fn set_fee_market_filter<F>(&self, predicate: F)
+    where
+        F: Fn(&FeeMarket) -> bool + Send + Sync + 'static,
+    {
+        self.inner.predicates.write().unwrap().fee_market = Some(Box::new(predicate));
+    }
+
+    /// Only deliver price entries for which `predicate` returns `true`.
+    ///
+    /// Applies to single updates, batches and snapshots. Replaces any
+    /// previous price filter.
+    pub fn set_price_filter<F>(&self, predicate: F)
+    where
+        F: Fn(&PriceEntry) -> bool + Send + Sync + 'static,
+    {
+        self.inner.predicates.write().unwrap().price = Some(Box::new(predicate));
+    }
+
+    /// Remove all filters set with [`set_pool_filter`](Self::set_pool_filter),
+    /// [`set_fee_market_filter`](Self::set_fee_market_filter) and
+    /// [`set_price_filter`](Self::set_price_filter).
+    pub fn clear_filters(&self) {
+        *self.inner.predicates.write().unwrap() = MessagePredicates::default();
+    }
+
+    /// Stop delivering messages of `channel` to callbacks.
+    ///
+    /// Client-side only: the subscription, its filters and the sequence and
+    /// resume tracking are untouched, so other channels keep flowing and a
+    /// reconnect restores the same subscription. Messages arriving while
+    /// paused, snapshots included, are dropped and not replayed on resume.
+    pub fn pause_channel(&self, channel: Channel) {
+        self.inner.paused.write().unwrap().insert(channel);
+    }
+
+    /// Deliver messages of `channel` again after [`pause_channel`](Self::pause_channel).
+    pub fn resume_channel(&self, channel: Channel) {
+        self.inner.paused.write().unwrap().remove(&channel);
+    }
+
+    /// Whether `channel` is paused.
+    pub fn is_paused(&self, channel: Channel) -> bool {
+        self.inner.paused.read().unwrap().contains(&channel)
+    }
+
+    /// Register a hook receiving every inbound data frame before decoding.
+    ///
+    /// Runs on the connection task, so keep it cheap; copy the frame with
+    /// [`RawFrame::to_frame`] to keep it. Frames still reach the typed
+    /// callbacks afterwards.
+    pub fn on_raw_frame<F>(&self, callback: F) -> CallbackHandle
+    where
+        F: Fn(&RawFrame<'_>) + Send + Sync + 'static,
+    {
+        self.register(|inner| &inner.on_raw_frame, Arc::new(callback) as Arc<_>)
+    }
 
-        let (ws_stream, _) = connect_async(&url).await?;
+    /// Connect to the K256 WebSocket.
+    ///
+    /// Returns once the initial handshake succeeds, or with the error of the
+    /// last endpoint tried: [`K256Error::Timeout`] if its handshake took
+    /// longer than `Config::connect_timeout`. The connection is then
+    /// driven by a background task that sends keepalive pings and, when
+    /// `Config::reconnect` is set, reconnects with exponential backoff.
+    pub async fn connect(&self) -> Result<(), BoxError> {
+        if self.inner.is_closing() {
+            return Err(K256Error::Closed.into());
+        }
+        let _ = self.inner.runtime.set(Handle::current());
+        self.inner.set_state(ConnectionState::Connecting);
+        if let Err(e) = self.inner.open().await {
+            self.inner.set_state(ConnectionState::Closed { reason: e.to_string() });
+            return Err(e);
+        }
         info!("Connected to K256 WebSocket");
+        self.inner.set_state(self.inner.connected_state());
+        self.inner.start_decoder();
+
+        let inner = self.inner.clone();
+        tokio::spawn(async move { inner.drive().await });
+        Ok(())
+    }
+
+    /// Close the connection for good.
+    ///
+    /// The connection task closes the socket, stops reconnecting and reports
+    /// [`ConnectionState::Closed`]; [`connect`](Self::connect) then fails
+    /// with [`K256Error::Closed`].
+    pub fn disconnect(&self) {
+        self.inner.closing.send_replace(true);
+    }
+
+    /// Record every received frame to `writer` (see [`crate::replay`]).
+    ///
+    /// Writes are buffered; call [`stop_recording`](Self::stop_recording)
+    /// to flush.
+    ///
+    /// Replaces any recording already in progress.
+    pub fn record_to<W: Write + Send + 'static>(&self, writer: W) -> io::Result<()> {
+        let writer: Box<dyn Write + Send> = Box::new(writer);
+        let recorder = Recorder::new(BufWriter::new(writer))?;
+        if let Some(mut previous) = self.inner.recorder.lock().unwrap().replace(recorder) {
+            previous.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Stop recording and flush the recording.
+    pub fn stop_recording(&self) -> io::Result<()> {
+        match self.inner.recorder.lock().unwrap().take() {
+            Some(mut recorder) => recorder.flush(),
+            None => Ok(()),
+        }
+    }
+
+    /// Decode a raw frame and dispatch it to the registered callbacks.
+    pub(crate) async fn handle_frame(&self, kind: FrameKind, data: &[u8]) {
+        let span = self.inner.message_span();
+        let decoded = spans::decode(self.inner.config.trace_spans, &span).in_scope(|| match kind {
+            FrameKind::Binary => self.inner.decode_binary(data),
+            FrameKind::Text => self.inner.decode_text(&String::from_utf8_lossy(data)),
+        });
+        if let Some(decoded) = decoded {
+            spans::record(&span, &decoded);
+            self.inner.deliver(decoded).instrument(span).await;
+        }
+    }
+
+    /// Pool updates superseded by a newer update for the same pool before
+    /// delivery (zero without [`Config::pool_coalesce_interval`]).
+    pub fn coalesced_updates(&self) -> u64 {
+        self.inner.coalescer.as_ref().map_or(0, PoolCoalescer::coalesced)
+    }
+
+    /// Reordered and stale pool updates (all zero without
+    /// [`Config::ordered_delivery`]).
+    pub fn ordering_stats(&self) -> OrderingStats {
+        self.inner.orderer.as_ref().map(PoolOrderer::stats).unwrap_or_default()
+    }
+
+    /// Messages dropped by the dispatch queues (all zero without [`Config::dispatch`]).
+    pub fn dropped_messages(&self) -> DroppedMessages {
+        dispatch::dropped_messages(&self.inner.queues)
+    }
+
+    /// Endpoint of the live connection, or `None` while disconnected.
+    pub fn active_endpoint(&self) -> Option<String> {
+        self.inner.endpoint.lock().unwrap().active.clone()
+    }
+
+    /// Resolved remote address of the live connection, if the transport
+    /// reports it (the proxy's address when connecting through one).
+    pub fn peer_addr(&self) -> Option<SocketAddr> {
+        self.inner.endpoint.lock().unwrap().peer
+    }
+
+    /// Frame, message, error and reconnect counters since the client was created.
+    pub fn stats(&self) -> ClientStats {
+        self.inner.stats.snapshot(self.dropped_messages())
+    }
+
+    /// Newest pool update sequence received, sent as the resume point after
+    /// a reconnect (`None` without [`Config::resume`]).
+    pub fn last_sequence(&self) -> Option<u64> {
+        *self.inner.resume_sequence.lock().unwrap()
+    }
+
+    /// Current connection lifecycle state.
+    pub fn connection_state(&self) -> ConnectionState {
+        self.inner.state.lock().unwrap().clone()
+    }
+
+    /// Whether no data or heartbeat frame arrived within
+    /// [`Config::stale_after`] on the current connection.
+    pub fn is_stale(&self) -> bool {
+        self.inner.stale.load(Ordering::Relaxed)
+    }
+
+    /// Last measured ping round-trip time, if a pong has been received.
+    pub fn latency(&self) -> Option<Duration> {
+        self.inner.keepalive.lock().unwrap().latency
+    }
+
+    /// End-to-end latency percentiles of recent timestamped messages.
+    ///
+    /// `None` until a timestamped message arrives, or without
+    /// [`Config::latency_window`].
+    pub fn latency_stats(&self) -> Option<LatencyStats> {
+        self.inner.latency.as_ref()?.lock().unwrap().stats()
+    }
+
+    /// Pool update rates per protocol and pool and batch size distribution
+    /// over the configured window.
+    ///
+    /// Counts every decoded update, before client-side filters. `None`
+    /// unless [`Config::feed_stats_window`] is set.
+    pub fn feed_stats(&self) -> Option<FeedStatsSnapshot> {
+        Some(self.inner.feed_stats.as_ref()?.lock().unwrap().snapshot())
+    }
+
+    /// Send a keepalive ping (0x0B) immediately.
+    ///
+    /// The round-trip time is available from [`latency`](Self::latency)
+    /// once the pong arrives.
+    pub async fn ping(&self) -> Result<(), BoxError> {
+        self.request(Frame::Binary(encode_message(&ClientMessage::Ping)), "ping").await
+    }
+
+    /// Protocol revision and version the server reported when it last
+    /// confirmed a subscription.
+    ///
+    /// A newer [`ServerInfo::protocol_version`] than [`PROTOCOL_VERSION`] is
+    /// logged as a warning; frames of message types the SDK does not know
+    /// are then skipped instead of counted as decode errors.
+    pub fn server_info(&self) -> Option<ServerInfo> {
+        self.inner.server_info.lock().unwrap().clone()
+    }
+
+    /// Latest rate-limit or quota error from the server, until a later
+    /// subscription is confirmed.
+    pub fn quota(&self) -> Option<QuotaError> {
+        self.inner.quota.lock().unwrap().clone()
+    }
+
+    /// Subscribe to channels.
+    ///
+    /// The request becomes the client's effective subscription and is
+    /// restored automatically after a reconnect.
+    ///
+    /// Fails fast with [`K256Error::QuotaExceeded`], without sending, while
+    /// the latest [`quota`](Self::quota) error is exhausted and not yet reset.
+    pub async fn subscribe(&self, request: SubscribeRequest) -> Result<(), BoxError> {
+        if let Some(quota) = self.quota().filter(QuotaError::is_exhausted) {
+            return Err(K256Error::QuotaExceeded(quota).into());
+        }
+        let mut request = request;
+        self.inner.apply_forward_compat(&mut request);
+        let msg = serde_json::to_string(&request)?;
+        self.inner.sync_filters(Some(&request));
+        *self.inner.subscription.lock().unwrap() = Some(request);
+        self.request(Frame::Text(msg), "subscribe request").await
+    }
+
+    /// Subscribe to channels and wait for the server to confirm them.
+    ///
+    /// Returns the confirmed channels. A confirmation answers the oldest
+    /// pending call that requested any of its channels. Fails with
+    /// [`K256Error::SubscriptionRejected`] when the server answers with a
+    /// subscription error or leaves a requested channel out,
+    /// [`K256Error::QuotaExceeded`] when the error is a rate-limit or quota
+    /// error, and with
+    /// [`K256Error::Timeout`] when no answer arrives within `timeout`. The
+    /// request stays the effective subscription either way.
+    pub async fn subscribe_and_wait(
+        &self,
+        request: SubscribeRequest,
+        timeout: Duration,
+    ) -> Result<Vec<String>, BoxError> {
+        let requested = request.channels.clone();
+        let (tx, rx) = oneshot::channel();
+        let waiter = SubscribeWaiter {
+            channels: requested.clone(),
+            tx,
+        };
+        self.inner.subscribe_waiters.lock().unwrap().push(waiter);
+        self.subscribe(request).await?;
+
+        let confirmed = match tokio::time::timeout(timeout, rx).await {
+            Ok(Ok(Ok(channels))) => channels,
+            Ok(Ok(Err(message))) => {
+                return Err(match QuotaError::parse(&message) {
+                    Some(quota) => K256Error::QuotaExceeded(quota).into(),
+                    None => K256Error::SubscriptionRejected(message).into(),
+                })
+            }
+            Ok(Err(_)) | Err(_) => return Err(K256Error::Timeout("subscription confirmation").into()),
+        };
+        let missing: Vec<&str> = requested
+            .iter()
+            .filter(|channel| !confirmed.contains(channel))
+            .map(String::as_str)
+            .collect();
+        if !missing.is_empty() {
+            let message = format!("channels not confirmed: {}", missing.join(", "));
+            return Err(K256Error::SubscriptionRejected(message).into());
+        }
+        Ok(confirmed)
+    }
+
+    /// Request a single quote and wait for the server's answer.
+    ///
+    /// The request is tagged with a request ID the server echoes back, so
+    /// the answer is told apart from streamed quotes and is not passed to
+    /// [`on_quote`](Self::on_quote) callbacks. Fails with
+    /// [`K256Error::QuoteRejected`] when the server answers with an error
+    /// and with [`K256Error::Timeout`] when no answer arrives within
+    /// `timeout`, e.g. because the connection dropped meanwhile.
+    pub async fn request_quote(&self, request: QuoteRequest, timeout: Duration) -> Result<Quote, BoxError> {
+        if let Some(quota) = self.quota().filter(QuotaError::is_exhausted) {
+            return Err(K256Error::QuotaExceeded(quota).into());
+        }
+        let ticket = self.inner.quote_requests.register(&request)?;
+        if let Err(e) = self.request(Frame::Text(ticket.frame), "quote request").await {
+            self.inner.quote_requests.cancel(&ticket.request_id);
+            return Err(e);
+        }
+        match tokio::time::timeout(timeout, ticket.answer).await {
+            Ok(Ok(Ok(quote))) => Ok(quote),
+            Ok(Ok(Err(message))) => Err(K256Error::QuoteRejected(message).into()),
+            Ok(Err(_)) | Err(_) => {
+                self.inner.quote_requests.cancel(&ticket.request_id);
+                Err(K256Error::Timeout("quote response").into())
+            }
+        }
+    }
+
+    /// Unsubscribe from all channels.
+    pub async fn unsubscribe(&self) -> Result<(), BoxError> {
+        *self.inner.subscription.lock().unwrap() = None;
+        self.inner.sync_filters(None);
+        self.request(Frame::Text(UNSUBSCRIBE_ALL.to_string()), "unsubscribe request")
+            .await
+    }
+
+    /// Unsubscribe from `channels`, keeping the rest of the subscription.
+    ///
+    /// The gateway replaces subscriptions as a whole, so this re-sends the
+    /// subscription without those channels; dropping the last channel
+    /// unsubscribes from all. No-op while nothing is subscribed.
+    pub async fn unsubscribe_channels(&self, channels: &[Channel]) -> Result<(), BoxError> {
+        self.narrow_subscription(|request| {
+            request.channels.retain(|name| !channels.iter().any(|channel| channel.as_str() == name));
+            Ok(())
+        })
+        .await
+    }
+
+    /// Stop receiving updates for `pools`.
+    ///
+    /// Unlike [`remove_pools`](Self::remove_pools), removing the last pool
+    /// drops the `pools` channel instead of widening the filter to all
+    /// pools. Fails if the subscription has no pool filter, since "all pools
+    /// except these" cannot be expressed.
+    pub async fn unsubscribe_pools(&self, pools: &[&str]) -> Result<(), BoxError> {
+        self.narrow_subscription(|request| {
+            if request.pools.is_none() {
+                return Err("subscription has no pool filter to unsubscribe from".into());
+            }
+            remove_filters(&mut request.pools, pools);
+            if request.pools.is_none() {
+                request.channels.retain(|name| name != Channel::Pools.as_str());
+            }
+            Ok(())
+        })
+        .await
+    }
+
+    /// Current effective subscription, including filters added or removed at runtime.
+    pub fn subscription(&self) -> Option<SubscribeRequest> {
+        self.inner.subscription.lock().unwrap().clone()
+    }
+
+    /// Add pool addresses to the pool filter and re-send the subscription.
+    ///
+    /// Starts from [`SubscribeRequest::default`] if nothing is subscribed yet.
+    pub async fn add_pools(&self, pools: &[&str]) -> Result<(), BoxError> {
+        self.update_subscription(|request| add_filters(&mut request.pools, pools))
+            .await
+    }
+
+    /// Remove pool addresses from the pool filter and re-send the subscription.
+    ///
+    /// Removing the last pool clears the filter, so updates for all pools
+    /// matching the remaining filters are delivered again.
+    pub async fn remove_pools(&self, pools: &[&str]) -> Result<(), BoxError> {
+        self.update_subscription(|request| remove_filters(&mut request.pools, pools))
+            .await
+    }
+
+    /// Add DEX protocols to the protocol filter and re-send the subscription.
+    ///
+    /// Starts from [`SubscribeRequest::default`] if nothing is subscribed yet.
+    pub async fn add_protocols(&self, protocols: &[&str]) -> Result<(), BoxError> {
+        self.update_subscription(|request| add_filters(&mut request.protocols, protocols))
+            .await
+    }
+
+    /// Remove DEX protocols from the protocol filter and re-send the subscription.
+    ///
+    /// Removing the last protocol clears the filter.
+    pub async fn remove_protocols(&self, protocols: &[&str]) -> Result<(), BoxError> {
+        self.update_subscription(|request| remove_filters(&mut request.protocols, protocols))
+            .await
+    }
+
+    /// Add writable accounts to the fee account filter and re-send the subscription.
+    ///
+    /// Starts from [`SubscribeRequest::default`] if nothing is subscribed yet.
+    pub async fn add_fee_accounts(&self, accounts: &[&str]) -> Result<(), BoxError> {
+        self.update_subscription(|request| add_filters(&mut request.fee_accounts, accounts))
+            .await
+    }
+
+    /// Remove writable accounts from the fee account filter and re-send the subscription.
+    ///
+    /// Removing the last account clears the filter, so fee market updates
+    /// are delivered in full again.
+    pub async fn remove_fee_accounts(&self, accounts: &[&str]) -> Result<(), BoxError> {
+        self.update_subscription(|request| remove_filters(&mut request.fee_accounts, accounts))
+            .await
+    }
+
+    async fn update_subscription<F>(&self, update: F) -> Result<(), BoxError>
+    where
+        F: FnOnce(&mut SubscribeRequest),
+    {
+        let msg = {
+            let mut subscription = self.inner.subscription.lock().unwrap();
+            let request = subscription.get_or_insert_with(|| {
+                let mut request = SubscribeRequest::default();
+                self.inner.apply_forward_compat(&mut request);
+                request
+            });
+            update(request);
+            self.inner.sync_filters(Some(request));
+            serde_json::to_string(request)?
+        };
+        self.request(Frame::Text(msg), "subscribe request").await
+    }
+
+    /// Apply `update` to the current subscription and re-send it, or
+    /// unsubscribe from all once no channel is left.
+    async fn narrow_subscription<F>(&self, update: F) -> Result<(), BoxError>
+    where
+        F: FnOnce(&mut SubscribeRequest) -> Result<(), BoxError>,
+    {
+        let msg = {
+            let mut subscription = self.inner.subscription.lock().unwrap();
+            let Some(request) = subscription.as_mut() else {
+                return Ok(());
+            };
+            update(request)?;
+            if request.channels.is_empty() {
+                *subscription = None;
+                self.inner.sync_filters(None);
+                UNSUBSCRIBE_ALL.to_string()
+            } else {
+                self.inner.sync_filters(Some(request));
+                serde_json::to_string(request)?
+            }
+        };
+        self.request(Frame::Text(msg), "subscribe request").await
+    }
+
+    /// Send `frame` on the connection, failing with [`K256Error::Timeout`]
+    /// unless it is written within `Config::request_timeout`.
+    ///
+    /// The queue only drains while connected, so requests made while the
+    /// connection is stalled or reconnecting time out. A timed out request
+    /// stays queued and is still sent once the connection is back.
+    async fn request(&self, frame: Frame, what: &'static str) -> Result<(), BoxError> {
+        let (written, rx) = oneshot::channel();
+        let send = async {
+            let outgoing = Outgoing {
+                frame,
+                written: Some(written),
+            };
+            self.tx.send(outgoing).await.map_err(|_| BoxError::from(K256Error::Closed))?;
+            match rx.await {
+                Ok(result) => result.map_err(BoxError::from),
+                Err(_) => Err(K256Error::Closed.into()),
+            }
+        };
+        match self.inner.config.request_timeout {
+            Some(timeout) => tokio::time::timeout(timeout, send)
+                .await
+                .map_err(|_| K256Error::Timeout(what))?,
+            None => send.await,
+        }
+    }
+}
+
+impl Inner {
+    fn endpoints(&self) -> impl Iterator<Item = &String> {
+        std::iter::once(&self.config.endpoint).chain(&self.config.fallback_endpoints)
+    }
+
+    /// Connect to the first reachable endpoint in selection order.
+    async fn open(&self) -> Result<(), BoxError> {
+        let endpoints: Vec<&String> = self.endpoints().collect();
+        let order = match self.config.endpoint_selection {
+            EndpointSelection::Failover => {
+                let start = self.endpoint.lock().unwrap().next % endpoints.len();
+                (start..endpoints.len()).chain(0..start).collect()
+            }
+            EndpointSelection::LeastLatency => {
+                rank_by_latency(&endpoints, self.config.proxy.as_ref(), &self.config.dns).await
+            }
+        };
+
+        let mut transport = self.transport.lock().await;
+        let mut last_error: Option<BoxError> = None;
+        for index in order {
+            let endpoint = endpoints[index];
+            let connect = transport.connect(endpoint);
+            let connected = match self.config.connect_timeout {
+                Some(timeout) => tokio::time::timeout(timeout, connect)
+                    .await
+                    .unwrap_or_else(|_| Err(K256Error::Timeout("connection handshake").into())),
+                None => connect.await,
+            };
+            match connected {
+                Ok(()) => {
+                    let mut state = self.endpoint.lock().unwrap();
+                    state.next = index;
+                    state.active = Some(endpoint.clone());
+                    state.peer = transport.peer_addr();
+                    let span = spans::connection(self.config.trace_spans, endpoint, state.peer);
+                    span.in_scope(|| match state.peer {
+                        Some(peer) => info!("Connected to {} ({})", endpoint, peer),
+                        None => info!("Connected to {}", endpoint),
+                    });
+                    *self.connection_span.lock().unwrap() = span;
+                    return Ok(());
+                }
+                Err(e) => {
+                    warn!("Failed to connect to {}: {}", endpoint, e);
+                    last_error = Some(e);
+                }
+            }
+        }
+        Err(last_error.unwrap_or_else(|| "no endpoints configured".into()))
+    }
+
+    /// Run sessions until the connection is closed for good.
+    async fn drive(self: Arc<Self>) {
+        let mut resubscribe = false;
+        loop {
+            let reason = self.run_session(resubscribe).await;
+            resubscribe = true;
+            {
+                let mut endpoint = self.endpoint.lock().unwrap();
+                endpoint.active = None;
+                endpoint.peer = None;
+                endpoint.next += 1;
+            }
+            *self.connection_span.lock().unwrap() = Span::none();
+            if !self.config.reconnect || self.is_closing() {
+                self.set_state(ConnectionState::Closed { reason });
+                break;
+            }
+
+            let mut delay = self.config.reconnect_delay_initial;
+            let mut attempt = 0u32;
+            loop {
+                attempt += 1;
+                self.set_state(ConnectionState::Reconnecting { attempt });
+                let wait = delay + jitter();
+                self.stats.backing_off(wait);
+                warn!("Reconnecting in {:?} (attempt {})", wait, attempt);
+                if !self.sleep_unless_closed(wait).await {
+                    self.set_state(ConnectionState::Closed {
+                        reason: CLOSED_BY_CLIENT.to_string(),
+                    });
+                    return;
+                }
+
+                match self.open().await {
+                    Ok(()) => break,
+                    Err(e) => {
+                        error!("Reconnect failed: {}", e);
+                        delay = (delay * 2).min(self.config.reconnect_delay_max);
+                    }
+                }
+            }
+            info!("Reconnected to K256 WebSocket");
+            metrics::reconnect();
+            self.stats.reconnected();
+            self.set_state(self.connected_state());
+        }
+    }
+
+    fn is_closing(&self) -> bool {
+        *self.closing.borrow()
+    }
+
+    /// Sleep for `duration`, or return `false` early once the client is closed.
+    async fn sleep_unless_closed(&self, duration: Duration) -> bool {
+        let mut closing = self.closing.subscribe();
+        tokio::select! {
+            _ = tokio::time::sleep(duration) => !self.is_closing(),
+            _ = closed(&mut closing) => false,
+        }
+    }
 
-        let (mut write, mut read) = ws_stream.split();
-
-        let on_pool_update = self.on_pool_update.clone();
-        let on_fee_market = self.on_fee_market.clone();
-        let on_blockhash = self.on_blockhash.clone();
-        let on_quote = self.on_quote.clone();
-        let on_heartbeat = self.on_heartbeat.clone();
-        let on_price_update = self.on_price_update.clone();
-        let on_price_batch = self.on_price_batch.clone();
-        let on_price_snapshot = self.on_price_snapshot.clone();
-        let on_error = self.on_error.clone();
-
-        // Message receiving task
-        let recv_task = tokio::spawn(async move {
-            while let Some(msg) = read.next().await {
-                match msg {
-                    Ok(Message::Binary(data)) => {
-                        if data.is_empty() {
+    /// Run a single connection until it ends, then close it.
+    ///
+    /// Returns why the connection ended.
+    async fn run_session(&self, resubscribe: bool) -> String {
+        let mut transport = self.transport.lock().await;
+        let span = self.connection_span.lock().unwrap().clone();
+        let reason = self.pump(transport.as_mut(), resubscribe).instrument(span).await;
+        match tokio::time::timeout(CLOSE_TIMEOUT, transport.close()).await {
+            Ok(Err(e)) => debug!("Failed to close connection: {}", e),
+            Err(_) => debug!("Timed out closing connection"),
+            Ok(Ok(())) => {}
+        }
+        reason
+    }
+
+    /// Pump a single connection until it closes or stops answering pings.
+    ///
+    /// When `resubscribe` is set the effective subscription is restored first.
+    /// Returns why the connection ended.
+    async fn pump(&self, transport: &mut dyn Transport, resubscribe: bool) -> String {
+        self.session_subscribed.store(false, Ordering::Relaxed);
+        self.resnapshot_pending.store(false, Ordering::Relaxed);
+        self.pool_deltas.lock().unwrap().reset();
+        let mut outbound = self.outbound.lock().await;
+        let mut closing = self.closing.subscribe();
+        *self.keepalive.lock().unwrap() = Keepalive::default();
+
+        let restore = self.subscription.lock().unwrap().clone().filter(|_| resubscribe);
+        if let Some(mut request) = restore {
+            info!("Restoring subscription to {:?}", request.channels);
+            request.resume_from = *self.resume_sequence.lock().unwrap();
+            if let Some(sequence) = request.resume_from {
+                info!("Resuming after sequence {}", sequence);
+            }
+            let msg = serde_json::to_string(&request).expect("subscribe request serializes");
+            if let Err(e) = transport.send(Frame::Text(msg)).await {
+                error!("Failed to restore subscription: {}", e);
+                return format!("failed to restore subscription: {}", e);
+            }
+        }
+
+        let ping_enabled = !self.config.ping_interval.is_zero();
+        let period = self.config.ping_interval.max(Duration::from_millis(1));
+        let mut ping_timer = tokio::time::interval_at(tokio::time::Instant::now() + period, period);
+
+        let watchdog = tokio::time::sleep(self.config.stale_after.unwrap_or(Duration::MAX));
+        tokio::pin!(watchdog);
+        self.stale.store(false, Ordering::Relaxed);
+
+        let silence_period = {
+            let mut silence = self.silence.lock().unwrap();
+            silence.restart(Instant::now());
+            silence.check_period().unwrap_or(Duration::from_secs(1))
+        };
+        let mut silence_timer = tokio::time::interval_at(tokio::time::Instant::now() + silence_period, silence_period);
+        let activity_period = self
+            .config
+            .activity_report
+            .map_or(Duration::from_secs(3600), |activity| activity.interval.max(Duration::from_millis(1)));
+        let mut activity_timer =
+            tokio::time::interval_at(tokio::time::Instant::now() + activity_period, activity_period);
+
+        let reason = loop {
+            tokio::select! {
+                frame = transport.receive() => match frame {
+                    Some(Ok(Frame::Binary(data))) => {
+                        self.mark_fresh(watchdog.as_mut());
+                        let received_us = self.record(FrameKind::Binary, &data);
+                        if !is_control_frame(&data) && !self.admit_frame() {
                             continue;
                         }
-
-                        let msg_type = data[0];
-                        let payload = &data[1..];
-
-                        match decode_message(msg_type, payload) {
-                            Ok(Some(decoded)) => {
-                                match decoded {
-                                    DecodedMessage::PoolUpdate(update) => {
-                                        if let Some(cb) = on_pool_update.read().await.as_ref() {
-                                            cb(update);
-                                        }
-                                    }
-                                    DecodedMessage::PoolUpdateBatch(updates) => {
-                                        if let Some(cb) = on_pool_update.read().await.as_ref() {
-                                            for update in updates {
-                                                cb(update);
-                                            }
-                                        }
-                                    }
-                                    DecodedMessage::FeeMarket(fees) => {
-                                        if let Some(cb) = on_fee_market.read().await.as_ref() {
-                                            cb(fees);
-                                        }
-                                    }
-                                    DecodedMessage::Blockhash(bh) => {
-                                        if let Some(cb) = on_blockhash.read().await.as_ref() {
-                                            cb(bh);
-                                        }
-                                    }
-                                    DecodedMessage::Quote(quote) => {
-                                        if let Some(cb) = on_quote.read().await.as_ref() {
-                                            cb(quote);
-                                        }
-                                    }
-                                    DecodedMessage::Heartbeat(hb) => {
-                                        if let Some(cb) = on_heartbeat.read().await.as_ref() {
-                                            cb(hb);
-                                        }
-                                    }
-                                    DecodedMessage::PriceUpdate(entry) => {
-                                        if let Some(cb) = on_price_update.read().await.as_ref() {
-                                            cb(entry);
-                                        }
-                                    }
-                                    DecodedMessage::PriceBatch(entries) => {
-                                        if let Some(cb) = on_price_batch.read().await.as_ref() {
-                                            cb(entries.clone());
-                                        }
-                                        if let Some(cb) = on_price_update.read().await.as_ref() {
-                                            for entry in entries {
-                                                cb(entry);
-                                            }
-                                        }
-                                    }
-                                    DecodedMessage::PriceSnapshot(entries) => {
-                                        if let Some(cb) = on_price_snapshot.read().await.as_ref() {
-                                            cb(entries.clone());
-                                        }
-                                        if let Some(cb) = on_price_update.read().await.as_ref() {
-                                            for entry in entries {
-                                                cb(entry);
-                                            }
-                                        }
-                                    }
-                                    DecodedMessage::Error(err) => {
-                                        error!("Server error: {}", err);
-                                        if let Some(cb) = on_error.read().await.as_ref() {
-                                            cb(err);
-                                        }
-                                    }
-                                    DecodedMessage::Subscribed { channels } => {
-                                        info!("Subscribed to channels: {:?}", channels);
-                                    }
+                        match &self.decode_stage {
+                            Some(stage) if !is_control_frame(&data) => {
+                                if stage.tx.send(InboundFrame { data, received_us }).await.is_err() {
+                                    break "decode task stopped".to_string();
                                 }
                             }
-                            Ok(None) => {
-                                debug!("Unhandled message type: {}", msg_type);
-                            }
-                            Err(e) => {
-                                error!("Error decoding message: {}", e);
-                            }
+                            _ => self.receive_binary(&data, received_us).await,
                         }
                     }
-                    Ok(Message::Text(text)) => {
-                        // Parse JSON text messages for Heartbeat and other JSON responses
-                        if let Ok(json) = serde_json::from_str::<serde_json::Value>(&text) {
-                            if let Some(msg_type) = json.get("type").and_then(|t| t.as_str()) {
-                                match msg_type {
-                                    "heartbeat" => {
-                                        if let Some(cb) = on_heartbeat.read().await.as_ref() {
-                                            let hb = Heartbeat {
-                                                timestamp_ms: json.get("timestamp_ms")
-                                                    .and_then(|v| v.as_u64()).unwrap_or(0),
-                                                uptime_seconds: json.get("uptime_seconds")
-                                                    .and_then(|v| v.as_u64()).unwrap_or(0),
-                                                messages_received: json.get("messages_received")
-                                                    .and_then(|v| v.as_u64()).unwrap_or(0),
-                                                messages_sent: json.get("messages_sent")
-                                                    .and_then(|v| v.as_u64()).unwrap_or(0),
-                                                subscriptions: json.get("subscriptions")
-                                                    .and_then(|v| v.as_u64()).unwrap_or(0) as u32,
-                                            };
-                                            cb(hb);
-                                        }
-                                    }
-                                    "subscribed" => {
-                                        if let Some(channels) = json.get("channels").and_then(|c| c.as_array()) {
-                                            let channel_names: Vec<String> = channels
-                                                .iter()
-                                                .filter_map(|c| c.as_str().map(String::from))
-                                                .collect();
-                                            info!("Subscribed to channels: {:?}", channel_names);
-                                        }
-                                    }
-                                    "error" => {
-                                        let err_msg = json.get("message")
-                                            .and_then(|m| m.as_str())
-                                            .unwrap_or("Unknown error")
-                                            .to_string();
-                                        error!("Server error: {}", err_msg);
-                                        if let Some(cb) = on_error.read().await.as_ref() {
-                                            cb(err_msg);
-                                        }
-                                    }
-                                    _ => {
-                                        debug!("Unhandled text message type: {}", msg_type);
-                                    }
-                                }
+                    Some(Ok(Frame::Text(text))) => {
+                        self.mark_fresh(watchdog.as_mut());
+                        let received_us = self.record(FrameKind::Text, text.as_bytes());
+                        let span = self.message_span();
+                        let decoded =
+                            spans::decode(self.config.trace_spans, &span).in_scope(|| self.decode_text(&text));
+                        if let Some(decoded) = decoded {
+                            spans::record(&span, &decoded);
+                            self.observe_latency(&decoded, received_us);
+                            self.observe_feed(&decoded);
+                            if let Some(decoded) = self.answer_quote_request(decoded, &text) {
+                                self.deliver(decoded).instrument(span).await;
                             }
-                        } else {
-                            debug!("Received non-JSON text message: {}", text);
                         }
                     }
-                    Ok(Message::Close(_)) => {
+                    Some(Ok(Frame::Pong(_))) => self.record_pong(),
+                    Some(Ok(Frame::Close)) | None => {
                         warn!("WebSocket closed");
-                        break;
+                        break "closed by server".to_string();
                     }
-                    Err(e) => {
+                    Some(Ok(_)) => {}
+                    Some(Err(e)) => {
                         error!("WebSocket error: {}", e);
-                        break;
+                        break e.to_string();
+                    }
+                },
+                _ = closed(&mut closing) => break CLOSED_BY_CLIENT.to_string(),
+                Some(outgoing) = outbound.recv() => {
+                    if let Err(e) = transport.send(outgoing.frame).await {
+                        error!("Failed to send message: {}", e);
+                        let reason = format!("failed to send message: {}", e);
+                        if let Some(written) = outgoing.written {
+                            let _ = written.send(Err(reason.clone()));
+                        }
+                        break reason;
+                    }
+                    if let Some(written) = outgoing.written {
+                        let _ = written.send(Ok(()));
+                    }
+                }
+                _ = &mut watchdog, if self.config.stale_after.is_some() && !self.stale.load(Ordering::Relaxed) => {
+                    let stale_after = self.config.stale_after.unwrap_or_default();
+                    warn!("No data for {:?}, connection is stale", stale_after);
+                    self.stale.store(true, Ordering::Relaxed);
+                    self.set_state(ConnectionState::Degraded);
+                    self.on_stale.emit(stale_after);
+                    if self.config.reconnect_on_stale {
+                        break format!("no data for {:?}", stale_after);
+                    }
+                }
+                _ = silence_timer.tick() => self.check_silence(),
+                _ = activity_timer.tick(), if self.activity.is_some() => self.report_activity(),
+                _ = ping_timer.tick(), if ping_enabled => {
+                    if !self.record_ping() {
+                        warn!(
+                            "No pong for {} consecutive pings, dropping connection",
+                            self.config.max_missed_pings
+                        );
+                        break format!("no pong for {} consecutive pings", self.config.max_missed_pings);
+                    }
+                    let ping = Frame::Binary(encode_message(&ClientMessage::Ping));
+                    if let Err(e) = transport.send(ping).await {
+                        error!("Failed to send ping: {}", e);
+                        break format!("failed to send ping: {}", e);
                     }
-                    _ => {}
                 }
             }
+        };
+        self.session_subscribed.store(false, Ordering::Relaxed);
+        reason
+    }
+
+    /// Rebuild the fee account and liquidity filters and the pools watched
+    /// for silence after the subscription changed.
+    fn sync_filters(&self, request: Option<&SubscribeRequest>) {
+        let pools: Vec<Pubkey> = request
+            .and_then(|r| r.pools.as_deref())
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|pool| pool.parse().ok())
+            .collect();
+        self.silence.lock().unwrap().watch(&pools, Instant::now());
+        let mut filter = self.fee_filter.lock().unwrap();
+        let previous = filter.take();
+        *filter = request
+            .and_then(|r| r.fee_accounts.as_deref())
+            .map(|accounts| FeeAccountFilter::new(accounts).carry_over(previous));
+        let mut liquidity = self.liquidity.lock().unwrap();
+        let previous = liquidity.take();
+        *liquidity = request
+            .and_then(|r| r.min_liquidity_usd)
+            .map(|min_usd| LiquidityFilter::new(min_usd).carry_over(previous));
+    }
+
+    /// Record the server details of a subscription confirmation, warning
+    /// once per change when the server speaks a newer protocol revision.
+    fn set_server_info(&self, server: &ServerInfo) {
+        let mut current = self.server_info.lock().unwrap();
+        if server.is_newer() && current.as_ref() != Some(server) {
+            warn!(
+                "Server speaks protocol revision {} (SDK decodes {}); unknown message types are skipped",
+                server.protocol_version.unwrap_or_default(),
+                PROTOCOL_VERSION
+            );
+        }
+        *current = Some(server.clone());
+    }
+
+    fn apply_forward_compat(&self, request: &mut SubscribeRequest) {
+        if self.config.forward_compat {
+            request.forward_compat = Some(true);
+        }
+    }
+
+    fn server_is_newer(&self) -> bool {
+        self.server_info.lock().unwrap().as_ref().is_some_and(ServerInfo::is_newer)
+    }
+
+    /// Update the lifecycle state, notifying the callback on change.
+    fn set_state(&self, state: ConnectionState) {
+        {
+            let mut current = self.state.lock().unwrap();
+            if *current == state {
+                return;
+            }
+            *current = state.clone();
+        }
+        debug!("Connection state: {}", state);
+        self.on_connection_state.emit(state);
+    }
+
+    /// [`ConnectionState::Connected`] with the peer of the live connection.
+    fn connected_state(&self) -> ConnectionState {
+        ConnectionState::Connected {
+            peer: self.endpoint.lock().unwrap().peer,
+        }
+    }
+
+    /// Leave [`ConnectionState::Degraded`] once the connection is healthy again.
+    fn recover_state(&self) {
+        let degraded = *self.state.lock().unwrap() == ConnectionState::Degraded;
+        if degraded && !self.stale.load(Ordering::Relaxed) && self.keepalive.lock().unwrap().missed == 0 {
+            self.set_state(if self.session_subscribed.load(Ordering::Relaxed) {
+                ConnectionState::Subscribed
+            } else {
+                self.connected_state()
+            });
+        }
+    }
+
+    /// Push the stale watchdog back after a data frame.
+    fn mark_fresh(&self, watchdog: Pin<&mut Sleep>) {
+        if let Some(stale_after) = self.config.stale_after {
+            watchdog.reset(tokio::time::Instant::now() + stale_after);
+            if self.stale.swap(false, Ordering::Relaxed) {
+                info!("Data flow resumed");
+                self.recover_state();
+            }
+        }
+    }
+
+    /// Pass an inbound frame to the raw-frame hook and the active recording.
+    ///
+    /// Returns the receive time in microseconds since the UNIX epoch.
+    fn record(&self, kind: FrameKind, data: &[u8]) -> u64 {
+        let timestamp_us = replay::now_us();
+        for (_, tap) in self.on_raw_frame.snapshot().iter() {
+            tap(&RawFrame { timestamp_us, kind, data });
+        }
+        let mut recorder = self.recorder.lock().unwrap();
+        if let Some(active) = recorder.as_mut() {
+            if let Err(e) = active.write_frame(timestamp_us, kind, data) {
+                error!("Recording failed, stopping: {}", e);
+                *recorder = None;
+            }
+        }
+        timestamp_us
+    }
+
+    fn observe_latency(&self, decoded: &DecodedMessage, received_us: u64) {
+        let (Some(window), Some(sent_ms)) = (&self.latency, decoded.timestamp_ms()) else {
+            return;
+        };
+        let latency = window.lock().unwrap().record(sent_ms.get(), received_us / 1_000);
+        metrics::message_latency(decoded.kind(), latency);
+    }
+
+    fn observe_feed(&self, decoded: &DecodedMessage) {
+        if let Some(stats) = &self.feed_stats {
+            stats.lock().unwrap().record(decoded);
+        }
+        if let Some(activity) = &self.activity {
+            activity.lock().unwrap().record(decoded);
+        }
+    }
+
+    /// Record an outgoing ping. Returns `false` once too many pings went unanswered.
+    fn record_ping(&self) -> bool {
+        let missed = {
+            let mut keepalive = self.keepalive.lock().unwrap();
+            if keepalive.ping_sent_at.is_some() {
+                keepalive.missed += 1;
+            }
+            keepalive.ping_sent_at = Some(Instant::now());
+            keepalive.missed
+        };
+        let max = self.config.max_missed_pings;
+        if max > 0 && missed >= max {
+            return false;
+        }
+        if missed > 0 {
+            self.set_state(ConnectionState::Degraded);
+        }
+        true
+    }
+
+    fn record_pong(&self) {
+        {
+            let mut keepalive = self.keepalive.lock().unwrap();
+            if let Some(sent_at) = keepalive.ping_sent_at.take() {
+                let rtt = sent_at.elapsed();
+                debug!("Pong received, rtt={:?}", rtt);
+                keepalive.latency = Some(rtt);
+                metrics::ping_rtt(rtt);
+            }
+            keepalive.missed = 0;
+        }
+        self.recover_state();
+    }
+
+    async fn receive_binary(&self, data: &[u8], received_us: u64) {
+        let span = self.message_span();
+        let decoded = spans::decode(self.config.trace_spans, &span).in_scope(|| self.decode_binary(data));
+        if let Some(decoded) = decoded {
+            spans::record(&span, &decoded);
+            self.observe_latency(&decoded, received_us);
+            self.observe_feed(&decoded);
+            self.deliver(decoded).instrument(span).await;
+        }
+    }
+
+    /// New `k256.message` span under the live connection's span.
+    fn message_span(&self) -> Span {
+        spans::message(self.config.trace_spans, &self.connection_span.lock().unwrap())
+    }
+
+    /// Spawn the decode task draining `decode_stage`, once.
+    fn start_decoder(&self) {
+        let Some(mut rx) = self.decode_stage.as_ref().and_then(|stage| stage.rx.lock().unwrap().take()) else {
+            return;
+        };
+        let this = self.this.clone();
+        tokio::spawn(async move {
+            while let Some(frame) = rx.recv().await {
+                let Some(inner) = this.upgrade() else {
+                    break;
+                };
+                inner.receive_binary(&frame.data, frame.received_us).await;
+            }
         });
+    }
+
+    fn decode_binary(&self, data: &[u8]) -> Option<DecodedMessage> {
+        metrics::bytes_received(data.len());
+        self.stats.frame_received(data.len());
+        let (&msg_type, payload) = data.split_first()?;
+
+        match self.decode_payload(msg_type, payload) {
+            Ok(Some(decoded)) => {
+                self.stats.message_decoded(&decoded);
+                Some(decoded)
+            }
+            Ok(None) => {
+                debug!("Unhandled message type: {}", msg_type);
+                None
+            }
+            Err(DecodeError::InvalidMessageType(msg_type)) if self.server_is_newer() => {
+                debug!("Skipping message type {} from a newer protocol revision", msg_type);
+                None
+            }
+            Err(e @ DecodeError::LimitExceeded { .. }) => self.oversized(msg_type, payload, e),
+            Err(e) => {
+                metrics::decode_error();
+                self.stats.decode_error();
+                error!("Error decoding message: {}", e);
+                None
+            }
+        }
+    }
+
+    /// Handle a frame over `Config::decode_limits` as `Config::limit_policy`
+    /// says, reporting it through `on_error`.
+    fn oversized(&self, msg_type: u8, payload: &[u8], error: DecodeError) -> Option<DecodedMessage> {
+        self.stats.oversized_frame();
+        let truncatable = matches!(error, DecodeError::LimitExceeded { field: "batch" | "fee accounts", .. });
+        let truncated = (self.config.limit_policy == LimitPolicy::Truncate && truncatable)
+            .then(|| self.decode_truncated(msg_type, payload))
+            .flatten();
+        match truncated {
+            Some(decoded) => {
+                self.stats.message_decoded(&decoded);
+                self.report_guardrail(format!("Truncated {} frame: {}", decoded.kind(), error));
+                Some(decoded)
+            }
+            None => {
+                metrics::decode_error();
+                self.stats.decode_error();
+                self.report_guardrail(format!("Dropped oversized frame: {}", error));
+                None
+            }
+        }
+    }
+
+    fn decode_truncated(&self, msg_type: u8, payload: &[u8]) -> Option<DecodedMessage> {
+        let body = if self.config.forward_compat {
+            compat_body(MessageType::try_from(msg_type).ok()?, payload).ok()?
+        } else {
+            payload
+        };
+        decode_message_truncated(msg_type, body, &self.config.decode_limits).ok().flatten()
+    }
+
+    /// Apply `Config::max_frames_per_sec` to a data frame.
+    fn admit_frame(&self) -> bool {
+        let Some(limit) = &self.frame_rate else {
+            return true;
+        };
+        let (admission, max) = {
+            let mut limit = limit.lock().unwrap();
+            (limit.admit(Instant::now()), limit.max())
+        };
+        if admission != Admission::Accept {
+            self.stats.rate_limited_frame();
+        }
+        if admission == Admission::FirstDrop {
+            self.report_guardrail(format!("Over {} data frames/s; dropping frames for the rest of the second", max));
+        }
+        admission == Admission::Accept
+    }
+
+    /// Log a guardrail violation and pass it to `on_error`.
+    fn report_guardrail(&self, message: String) {
+        warn!("{}", message);
+        self.on_error.emit(message);
+    }
+
+    fn decode_payload(&self, msg_type: u8, payload: &[u8]) -> Result<Option<DecodedMessage>, DecodeError> {
+        let limits = &self.config.decode_limits;
+        if self.config.forward_compat {
+            let Some(compat) = decode_message_compat(msg_type, payload, limits)? else {
+                return Ok(None);
+            };
+            if !compat.extra.is_empty() {
+                self.stats.unknown_fields();
+                if !self.unknown_fields_seen.swap(true, Ordering::Relaxed) {
+                    warn!(
+                        "Skipping {} unknown trailing bytes in {} messages from a newer protocol revision",
+                        compat.extra.len(),
+                        compat.message.kind()
+                    );
+                }
+                self.on_unknown_fields.emit(UnknownFields {
+                    kind: compat.message.kind(),
+                    extra: compat.extra,
+                });
+            }
+            return Ok(Some(compat.message));
+        }
+
+        #[cfg(feature = "parallel")]
+        if let Some(min_parallel) = self.config.parallel_decode_threshold {
+            return crate::ws::decoder::decode_message_parallel(msg_type, payload, min_parallel, limits);
+        }
+        decode_message_with_limits(msg_type, payload, limits)
+    }
+
+    fn decode_text(&self, text: &str) -> Option<DecodedMessage> {
+        metrics::bytes_received(text.len());
+        self.stats.frame_received(text.len());
+        match decode_json_message(text) {
+            Ok(Some(decoded)) => {
+                self.stats.message_decoded(&decoded);
+                Some(decoded)
+            }
+            Ok(None) => {
+                debug!("Unhandled text message: {}", text);
+                None
+            }
+            Err(e) => {
+                metrics::decode_error();
+                self.stats.decode_error();
+                debug!("Error decoding text message: {}", e);
+                None
+            }
+        }
+    }
+
+    /// Route a decoded message through ordering, coalescing and dispatch.
+    async fn deliver(&self, decoded: DecodedMessage) {
+        self.check_sequence(&decoded);
+        self.observe_pools(&decoded);
+        self.advance_resume_sequence(&decoded);
+        match &decoded {
+            DecodedMessage::Subscribed { channels, server } => {
+                *self.quota.lock().unwrap() = None;
+                self.set_server_info(server);
+                self.confirm_subscribe_waiter(channels);
+            }
+            DecodedMessage::Error(message) => {
+                if let Some(quota) = QuotaError::parse(message) {
+                    warn!("Server quota error: {}", quota);
+                    *self.quota.lock().unwrap() = Some(quota);
+                }
+                self.reject_subscribe_waiter(message);
+            }
+            _ => {}
+        }
+        if matches!(decoded, DecodedMessage::Subscribed { .. }) {
+            self.session_subscribed.store(true, Ordering::Relaxed);
+            if *self.state.lock().unwrap() == ConnectionState::Degraded {
+                self.recover_state();
+            } else {
+                self.set_state(ConnectionState::Subscribed);
+            }
+        }
+        if decoded.channel().is_some_and(|channel| self.paused.read().unwrap().contains(&channel)) {
+            return;
+        }
+        let decoded = match decoded {
+            DecodedMessage::FeeMarket(fees) => {
+                let filtered = match self.fee_filter.lock().unwrap().as_mut() {
+                    Some(filter) => filter.apply(fees),
+                    None => Some(fees),
+                };
+                match filtered {
+                    Some(fees) => DecodedMessage::FeeMarket(fees),
+                    None => return,
+                }
+            }
+            decoded => decoded,
+        };
+        let Some(decoded) = self.apply_liquidity_floor(decoded) else {
+            return;
+        };
+        let Some(decoded) = self.predicates.read().unwrap().apply(decoded) else {
+            return;
+        };
+        let decoded = match (&self.orderer, decoded) {
+            (Some(orderer), DecodedMessage::PoolUpdate(update)) => {
+                orderer.offer(update);
+                self.start_ordering();
+                return;
+            }
+            (Some(orderer), DecodedMessage::PoolUpdateBatch(updates)) => {
+                for update in updates {
+                    orderer.offer(update);
+                }
+                self.start_ordering();
+                return;
+            }
+            (_, decoded) => decoded,
+        };
+        self.coalesce_or_enqueue(decoded).await;
+    }
+
+    /// Drop pools below the subscription's `min_liquidity_usd`, unless the
+    /// server already filters them.
+    fn apply_liquidity_floor(&self, decoded: DecodedMessage) -> Option<DecodedMessage> {
+        let mut liquidity = self.liquidity.lock().unwrap();
+        let Some(filter) = liquidity.as_mut() else {
+            return Some(decoded);
+        };
+        let server_filters = self
+            .server_info
+            .lock()
+            .unwrap()
+            .as_ref()
+            .is_some_and(|server| server.supports(FEATURE_MIN_LIQUIDITY));
+        if server_filters {
+            return Some(decoded);
+        }
+        filter.apply(decoded)
+    }
+
+    /// Hold pool updates for coalescing, or hand the message to dispatch.
+    async fn coalesce_or_enqueue(&self, decoded: DecodedMessage) {
+        let decoded = match (&self.coalescer, decoded) {
+            (Some(coalescer), DecodedMessage::PoolUpdate(update)) => {
+                coalescer.offer(update);
+                self.start_coalescing();
+                return;
+            }
+            (Some(coalescer), DecodedMessage::PoolUpdateBatch(updates)) => {
+                for update in updates {
+                    coalescer.offer(update);
+                }
+                self.start_coalescing();
+                return;
+            }
+            (_, decoded) => decoded,
+        };
+        self.enqueue(decoded).await;
+    }
+
+    /// Resolve the `request_quote` call a quote or error answers, passing
+    /// other messages on.
+    fn answer_quote_request(&self, decoded: DecodedMessage, text: &str) -> Option<DecodedMessage> {
+        match decoded {
+            DecodedMessage::Quote(quote) => self.quote_requests.answer(quote).map(DecodedMessage::Quote),
+            DecodedMessage::Error(message) if self.quote_requests.reject(text, &message) => None,
+            decoded => Some(decoded),
+        }
+    }
+
+    /// Resolve the oldest pending `subscribe_and_wait` call that requested
+    /// any of the `confirmed` channels.
+    fn confirm_subscribe_waiter(&self, confirmed: &[String]) {
+        let mut waiters = self.subscribe_waiters.lock().unwrap();
+        waiters.retain(|waiter| !waiter.tx.is_closed());
+        let answered = waiters
+            .iter()
+            .position(|waiter| waiter.channels.iter().any(|channel| confirmed.contains(channel)));
+        if let Some(index) = answered {
+            let _ = waiters.remove(index).tx.send(Ok(confirmed.to_vec()));
+        }
+    }
+
+    /// Fail the oldest pending `subscribe_and_wait` call if `message` is a
+    /// subscription error; other errors, e.g. about quotes, leave it waiting.
+    fn reject_subscribe_waiter(&self, message: &str) {
+        if !is_subscription_error(message) {
+            return;
+        }
+        let mut waiters = self.subscribe_waiters.lock().unwrap();
+        waiters.retain(|waiter| !waiter.tx.is_closed());
+        if !waiters.is_empty() {
+            let _ = waiters.remove(0).tx.send(Err(message.to_string()));
+        }
+    }
+
+    /// Track pool update sequences, reporting gaps.
+    fn check_sequence(&self, decoded: &DecodedMessage) {
+        if !self.config.detect_sequence_gaps {
+            return;
+        }
+        let updates = match decoded {
+            DecodedMessage::PoolUpdate(update) => std::slice::from_ref(update),
+            DecodedMessage::PoolUpdateBatch(updates) => updates.as_slice(),
+            DecodedMessage::PoolSnapshot(_) => {
+                // The snapshot supersedes whatever was missed
+                self.sequence.lock().unwrap().reset();
+                self.resnapshot_pending.store(false, Ordering::Relaxed);
+                return;
+            }
+            _ => return,
+        };
+        let gaps: Vec<GapInfo> = {
+            let mut tracker = self.sequence.lock().unwrap();
+            updates.iter().filter_map(|update| tracker.observe(update.sequence)).collect()
+        };
+        if gaps.is_empty() {
+            return;
+        }
+        for gap in gaps {
+            warn!("Pool update sequence gap: expected {}, got {}", gap.expected, gap.got);
+            self.on_gap.emit(gap);
+        }
+        if self.config.resnapshot_on_gap {
+            self.request_snapshot();
+        }
+    }
+
+    /// Clear the silence of pools that updated.
+    fn observe_pools(&self, decoded: &DecodedMessage) {
+        let updates = match decoded {
+            DecodedMessage::PoolUpdate(update) => std::slice::from_ref(update),
+            DecodedMessage::PoolUpdateBatch(updates) | DecodedMessage::PoolSnapshot(updates) => updates.as_slice(),
+            _ => return,
+        };
+        let now = Instant::now();
+        let mut silence = self.silence.lock().unwrap();
+        for update in updates {
+            silence.observe(&update.pool_address, now);
+        }
+    }
+
+    /// Report subscribed pools that went silent.
+    fn check_silence(&self) {
+        let silent = self.silence.lock().unwrap().check(Instant::now());
+        for event in silent {
+            warn!("Pool {} silent for {:?}", event.pool, event.last_update_age);
+            self.on_pool_silent.emit(event);
+        }
+    }
+
+    /// Derive balance deltas for `on_pool_delta`; snapshots only rebase.
+    fn emit_pool_deltas(&self, updates: &[PoolUpdate], snapshot: bool) {
+        if self.on_pool_delta.snapshot().is_empty() {
+            return;
+        }
+        let deltas: Vec<PoolDelta> = {
+            let mut tracker = self.pool_deltas.lock().unwrap();
+            if snapshot {
+                updates.iter().for_each(|update| tracker.rebase(update));
+                return;
+            }
+            updates.iter().filter_map(|update| tracker.apply(update)).collect()
+        };
+        self.on_pool_delta.emit_each(deltas);
+    }
 
-        // Message sending task
-        let mut rx = {
-            let (_tx, rx) = mpsc::channel::<Message>(100);
-            // Note: In a real implementation, we'd store tx in self
-            // This is a simplified version
-            rx
+    /// Emit an activity report.
+    fn report_activity(&self) {
+        if let Some(activity) = &self.activity {
+            let report = activity.lock().unwrap().report();
+            self.on_activity.emit(report);
+        }
+    }
+
+    /// Remember the newest pool update sequence as the resume point.
+    fn advance_resume_sequence(&self, decoded: &DecodedMessage) {
+        if !self.config.resume {
+            return;
+        }
+        let newest = match decoded {
+            DecodedMessage::PoolUpdate(update) => Some(update.sequence),
+            DecodedMessage::PoolUpdateBatch(updates) | DecodedMessage::PoolSnapshot(updates) => {
+                updates.iter().map(|update| update.sequence).max()
+            }
+            _ => None,
         };
+        if let Some(newest) = newest {
+            let mut sequence = self.resume_sequence.lock().unwrap();
+            *sequence = Some(sequence.map_or(newest, |current| current.max(newest)));
+        }
+    }
 
-        let send_task = tokio::spawn(async move {
-            while let Some(msg) = rx.recv().await {
-                if let Err(e) = write.send(msg).await {
-                    error!("Failed to send message: {}", e);
+    /// Re-send the effective subscription with `snapshot` set, once per gap
+    /// until the snapshot arrives.
+    fn request_snapshot(&self) {
+        let Some(mut request) = self.subscription.lock().unwrap().clone() else {
+            return;
+        };
+        if self.resnapshot_pending.swap(true, Ordering::AcqRel) {
+            return;
+        }
+        request.snapshot = Some(true);
+        let msg = serde_json::to_string(&request).expect("subscribe request serializes");
+        info!("Requesting pool snapshot after sequence gap");
+        let outgoing = Outgoing {
+            frame: Frame::Text(msg),
+            written: None,
+        };
+        if let Err(e) = self.outbound_tx.try_send(outgoing) {
+            warn!("Failed to request pool snapshot: {}", e);
+            self.resnapshot_pending.store(false, Ordering::Relaxed);
+        }
+    }
+
+    /// Start the ordered delivery flush task, once.
+    fn start_ordering(&self) {
+        let Some(window) = self.config.ordered_delivery else {
+            return;
+        };
+        if self.ordering_started.swap(true, Ordering::AcqRel) {
+            return;
+        }
+        let this = self.this.clone();
+        tokio::spawn(async move {
+            let period = window.max(Duration::from_millis(1));
+            let mut timer = tokio::time::interval_at(tokio::time::Instant::now() + period, period);
+            timer.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+            loop {
+                timer.tick().await;
+                let Some(inner) = this.upgrade() else {
                     break;
+                };
+                let updates = inner.orderer.as_ref().map(PoolOrderer::drain).unwrap_or_default();
+                if !updates.is_empty() {
+                    let decoded = DecodedMessage::PoolUpdateBatch(updates);
+                    let span = inner.message_span();
+                    spans::record(&span, &decoded);
+                    inner.coalesce_or_enqueue(decoded).instrument(span).await;
                 }
             }
         });
+    }
 
-        // Wait for tasks
-        tokio::select! {
-            _ = recv_task => {}
-            _ = send_task => {}
+    /// Start the coalescing flush task, once.
+    fn start_coalescing(&self) {
+        let Some(interval) = self.config.pool_coalesce_interval else {
+            return;
+        };
+        if self.coalesce_started.swap(true, Ordering::AcqRel) {
+            return;
         }
+        let this = self.this.clone();
+        tokio::spawn(async move {
+            let period = interval.max(Duration::from_millis(1));
+            let mut timer = tokio::time::interval_at(tokio::time::Instant::now() + period, period);
+            timer.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+            loop {
+                timer.tick().await;
+                let Some(inner) = this.upgrade() else {
+                    break;
+                };
+                let updates = inner.coalescer.as_ref().map(PoolCoalescer::drain).unwrap_or_default();
+                if !updates.is_empty() {
+                    let decoded = DecodedMessage::PoolUpdateBatch(updates);
+                    let span = inner.message_span();
+                    spans::record(&span, &decoded);
+                    inner.enqueue(decoded).instrument(span).await;
+                }
+            }
+        });
+    }
 
-        Ok(())
+    /// Hand a message to its dispatch queue, or run callbacks inline.
+    ///
+    /// Runs in the message's `k256.message` span, which queued messages
+    /// carry to the dispatch thread.
+    async fn enqueue(&self, decoded: DecodedMessage) {
+        let queue = Lane::of(&decoded)
+            .and_then(|lane| self.queues.iter().find(|queue| queue.lane() == lane));
+        let message = Span::current();
+        let span = spans::dispatch(self.config.trace_spans, &message);
+        match queue {
+            Some(queue) => queue.push(decoded, message).instrument(span).await,
+            None => span.in_scope(|| self.dispatch(decoded, &message)),
+        }
     }
 
-    /// Subscribe to channels.
-    pub async fn subscribe(
-        &self,
-        request: SubscribeRequest,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        let msg = serde_json::to_string(&request)?;
-        self.tx.send(Message::Text(msg)).await?;
-        Ok(())
+    fn dispatch(&self, decoded: DecodedMessage, message: &Span) {
+        let _span = spans::callback(self.config.trace_spans, message).entered();
+        let kind = decoded.kind();
+        metrics::message_decoded(kind);
+        let _timer = metrics::CallbackTimer::start(kind);
+
+        self.on_message.emit_cloned(&decoded);
+        match decoded {
+            DecodedMessage::PoolUpdate(update) => {
+                self.emit_pool_deltas(std::slice::from_ref(&update), false);
+                self.on_pool_update.emit(update);
+            }
+            DecodedMessage::PoolUpdateBatch(updates) => {
+                self.emit_pool_deltas(&updates, false);
+                self.on_pool_update.emit_each(updates);
+            }
+            DecodedMessage::PoolSnapshot(updates) => {
+                self.emit_pool_deltas(&updates, true);
+                self.on_pool_snapshot.emit_cloned(&updates);
+                self.on_pool_update.emit_each(updates);
+            }
+            DecodedMessage::FeeMarket(fees) => {
+                self.on_fee_market.emit(fees);
+            }
+            DecodedMessage::Blockhash(bh) => {
+                self.on_blockhash.emit(bh);
+            }
+            DecodedMessage::Quote(quote) => {
+                self.on_quote.emit(quote);
+            }
+            DecodedMessage::Heartbeat(hb) => {
+                self.on_heartbeat.emit(hb);
+            }
+            DecodedMessage::PriceUpdate(entry) => {
+                self.on_price_update.emit(entry);
+            }
+            DecodedMessage::PriceBatch(entries) => {
+                self.on_price_batch.emit_cloned(&entries);
+                self.on_price_update.emit_each(entries);
+            }
+            DecodedMessage::PriceSnapshot(entries) => {
+                self.on_price_snapshot.emit_cloned(&entries);
+                self.on_price_update.emit_each(entries);
+            }
+            DecodedMessage::Error(err) => {
+                error!("Server error: {}", err);
+                let quota = QuotaError::parse(&err);
+                self.on_error.emit(err);
+                if let Some(quota) = quota {
+                    self.on_quota_warning.emit(quota);
+                }
+            }
+            DecodedMessage::Pong { .. } => self.record_pong(),
+            DecodedMessage::Subscribed { channels, .. } => {
+                info!("Subscribed to channels: {:?}", channels);
+                self.on_subscribed.emit(channels);
+            }
+            DecodedMessage::ReplayComplete(done) => {
+                info!("Replayed {} pool updates up to sequence {}", done.replayed, done.to_sequence);
+                if done.truncated {
+                    warn!("Replay truncated, {} pool updates missed", done.missed());
+                }
+                self.on_replay_complete.emit(done);
+            }
+        }
     }
+}
 
-    /// Unsubscribe from all channels.
-    pub async fn unsubscribe(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        let msg = r#"{"type":"unsubscribe"}"#;
-        self.tx.send(Message::Text(msg.to_string())).await?;
-        Ok(())
+impl Drop for Inner {
+    fn drop(&mut self) {
+        for queue in &self.queues {
+            queue.close();
+        }
+    }
+}
+
+/// Drain a dispatch queue on a dedicated thread until the client is dropped.
+fn spawn_dispatcher(queue: Arc<DispatchQueue>, inner: Weak<Inner>) {
+    let name = format!("k256-dispatch-{:?}", queue.lane()).to_lowercase();
+    let spawned = std::thread::Builder::new().name(name).spawn(move || {
+        while let Some((msg, span)) = queue.pop() {
+            let Some(inner) = inner.upgrade() else {
+                break;
+            };
+            // Callbacks such as `async_callback` spawn onto the client's runtime
+            let _runtime = inner.runtime.get().map(Handle::enter);
+            inner.dispatch(msg, &span);
+        }
+    });
+    if let Err(e) = spawned {
+        error!("Failed to spawn dispatch thread: {}", e);
     }
 }
+
+/// Whether a server error answers a subscription request: a rate-limit or
+/// quota error, or one about subscriptions or channels.
+fn is_subscription_error(message: &str) -> bool {
+    let lower = message.to_ascii_lowercase();
+    QuotaError::parse(message).is_some() || lower.contains("subscri") || lower.contains("channel")
+}
+
+/// Wait until the client is closed.
+async fn closed(closing: &mut tokio::sync::watch::Receiver<bool>) {
+    let _ = closing.wait_for(|closing| *closing).await;
+}
+
+/// Whether a binary frame carries a control message, which is decoded on
+/// the connection task even with a decode queue.
+fn is_control_frame(data: &[u8]) -> bool {
+    matches!(
+        data.first().copied().map(MessageType::try_from),
+        Some(Ok(MessageType::Pong | MessageType::Subscribed | MessageType::Error))
+    )
+}
+
+fn add_filters<T>(filter: &mut Option<Vec<T>>, values: &[&str])
+where
+    T: for<'a> From<&'a str> + for<'a> PartialEq<&'a str>,
+{
+    let filter = filter.get_or_insert_with(Vec::new);
+    for value in values {
+        if !filter.iter().any(|v| v == value) {
+            filter.push(T::from(value));
+        }
+    }
+}
+
+fn remove_filters<T>(filter: &mut Option<Vec<T>>, values: &[&str])
+where
+    T: for<'a> PartialEq<&'a str>,
+{
+    if let Some(current) = filter {
+        current.retain(|v| !values.iter().any(|value| v == value));
+        if current.is_empty() {
+            *filter = None;
+        }
+    }
+}
+
+/// Endpoint indices ordered by TCP connect latency; unreachable endpoints last.
+///
+/// Probes resolve and connect like the connection itself, so with a proxy
+/// the time to open the tunnel to each endpoint is measured.
+async fn rank_by_latency(endpoints: &[&String], proxy: Option<&ProxyConfig>, dns: &DnsConfig) -> Vec<usize> {
+    const PROBE_TIMEOUT: Duration = Duration::from_secs(2);
+
+    let probes = endpoints.iter().map(|endpoint| async move {
+        let start = Instant::now();
+        tokio::time::timeout(PROBE_TIMEOUT, connector::probe(endpoint, proxy, dns))
+            .await
+            .ok()?
+            .ok()?;
+        Some(start.elapsed())
+    });
+    let latencies = futures_util::future::join_all(probes).await;
+
+    let mut order: Vec<usize> = (0..endpoints.len()).collect();
+    order.sort_by_key(|&i| latencies[i].unwrap_or(Duration::MAX));
+    debug!("Endpoint latencies: {:?}", latencies);
+    order
+}
+
+/// Random 0-500ms reconnect jitter.
+fn jitter() -> Duration {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.subsec_nanos())
+        .unwrap_or(0);
+    Duration::from_millis(u64::from(nanos % 500))
+}
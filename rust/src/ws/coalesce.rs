@@ -0,0 +1,111 @@
+//! Per-pool coalescing of pool updates.
+//!
+//! With [`Config::pool_coalesce_interval`] set, pool updates are held back
+//! and only the newest update per pool (by slot, then write version) is
+//! delivered once per interval, as a single `PoolUpdateBatch`.
+//!
+//! [`Config::pool_coalesce_interval`]: crate::ws::Config::pool_coalesce_interval
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+use crate::types::{PoolUpdate, Pubkey};
+
+/// Latest pending update per pool address.
+#[derive(Default)]
+pub(crate) struct PoolCoalescer {
+    pending: Mutex<HashMap<Pubkey, PoolUpdate>>,
+    coalesced: AtomicU64,
+}
+
+impl PoolCoalescer {
+    /// Hold an update, replacing an older pending update for the same pool.
+    pub(crate) fn offer(&self, update: PoolUpdate) {
+        let mut pending = self.pending.lock().unwrap();
+        match pending.get_mut(&update.pool_address) {
+            Some(existing) => {
+                if (update.slot, update.write_version) >= (existing.slot, existing.write_version) {
+                    *existing = update;
+                }
+                self.coalesced.fetch_add(1, Ordering::Relaxed);
+            }
+            None => {
+                pending.insert(update.pool_address, update);
+            }
+        }
+    }
+
+    /// Take all pending updates.
+    pub(crate) fn drain(&self) -> Vec<PoolUpdate> {
+        let mut pending = self.pending.lock().unwrap();
+        pending.drain().map(|(_, update)| update).collect()
+    }
+
+    /// Updates superseded by a newer update for the same pool so far.
+    pub(crate) fn coalesced(&self) -> u64 {
+        self.coalesced.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Slot;
+
+    fn update(pool: u8, slot: u64, write_version: u64) -> PoolUpdate {
+        PoolUpdate {
+            pool_address: Pubkey::new([pool; 32]),
+            slot: Slot(slot),
+            write_version,
+            ..PoolUpdate::default()
+        }
+    }
+
+    #[test]
+    fn test_keeps_newest_per_pool() {
+        let coalescer = PoolCoalescer::default();
+        coalescer.offer(update(1, 10, 1));
+        coalescer.offer(update(1, 10, 3));
+        coalescer.offer(update(1, 9, 7));
+        coalescer.offer(update(2, 5, 0));
+
+        let mut drained = coalescer.drain();
+        drained.sort_by_key(|u| u.pool_address);
+        assert_eq!(drained.len(), 2);
+        assert_eq!((drained[0].slot.get(), drained[0].write_version), (10, 3));
+        assert_eq!(coalescer.coalesced(), 2);
+        assert!(coalescer.drain().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_client_delivers_newest_once_per_interval() {
+        use crate::replay::FrameKind;
+        use crate::ws::{Config, K256WebSocketClient};
+        use std::time::Duration;
+
+        let client = K256WebSocketClient::new(Config {
+            pool_coalesce_interval: Some(Duration::from_millis(20)),
+            ..Config::default()
+        });
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let _updates = client.on_pool_update(move |update| {
+            tx.send(update.slot.get()).unwrap();
+        });
+
+        for slot in [1, 3, 2] {
+            let text = format!(
+                r#"{{"type":"pool_update","sequence":0,"slot":{},"writeVersion":0,"protocol":"p",
+                "poolAddress":"11111111111111111111111111111111","tokenMints":[],"tokenBalances":[],"tokenDecimals":[]}}"#,
+                slot
+            );
+            client.handle_frame(FrameKind::Text, text.as_bytes()).await;
+        }
+
+        let slot = tokio::time::timeout(Duration::from_secs(1), rx.recv()).await.unwrap();
+        assert_eq!(slot, Some(3));
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(rx.try_recv().is_err());
+        assert_eq!(client.coalesced_updates(), 2);
+    }
+}
@@ -1,16 +1,33 @@
 //! Binary message decoder for K256 WebSocket protocol.
+//!
+//! Wire layouts are parsed by [`k256_proto`]; this module turns its borrowed
+//! views into the SDK's owned types.
 
+use k256_proto::{self as proto, check_limit, read_u64};
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
 use thiserror::Error;
 
-use crate::types::{AccountFee, Blockhash, FeeMarket, MessageType, NetworkState, OrderLevel, PoolUpdate, PriceEntry};
-use crate::ws::client::DecodedMessage;
+use crate::types::{
+    AccountFee, Blockhash, FeeMarket, MessageType, NetworkState, PriceEntry, Pubkey, ServerInfo, Slot, TimestampMs,
+};
+use crate::utils::encode_32;
+use crate::ws::borrowed::{PoolUpdateBatchRef, PoolUpdateRef};
+use crate::ws::message::DecodedMessage;
+use crate::ws::quota::QUOTA_KEYS;
+use crate::ws::encoder::ClientMessage;
 
 /// Decoder error types.
 #[derive(Debug, Error)]
 pub enum DecodeError {
     /// Payload is too short
     #[error("Payload too short: expected {expected}, got {actual}")]
-    PayloadTooShort { expected: usize, actual: usize },
+    PayloadTooShort {
+        /// Minimum number of bytes required
+        expected: usize,
+        /// Number of bytes available
+        actual: usize,
+    },
 
     /// Invalid UTF-8 string
     #[error("Invalid UTF-8: {0}")]
@@ -23,6 +40,47 @@ pub enum DecodeError {
     /// Invalid network state
     #[error("Invalid network state: {0}")]
     InvalidNetworkState(u8),
+
+    /// Invalid JSON payload
+    #[error("Invalid JSON: {0}")]
+    InvalidJson(#[from] serde_json::Error),
+
+    /// A length or count exceeds the configured [`DecodeLimits`]
+    #[error("{field} exceeds limit: {actual} > {limit}")]
+    LimitExceeded {
+        /// Field or prefix that was too large
+        field: &'static str,
+        /// Configured limit
+        limit: usize,
+        /// Value found in the frame
+        actual: usize,
+    },
+}
+
+impl From<proto::Error> for DecodeError {
+    fn from(err: proto::Error) -> Self {
+        match err {
+            proto::Error::PayloadTooShort { expected, actual } => Self::PayloadTooShort { expected, actual },
+            proto::Error::LimitExceeded { field, limit, actual } => Self::LimitExceeded { field, limit, actual },
+        }
+    }
+}
+
+pub use k256_proto::DecodeLimits;
+
+/// What a client does with a frame exceeding its [`DecodeLimits`].
+///
+/// Either way the violation is reported through
+/// [`K256WebSocketClient::on_error`](crate::ws::K256WebSocketClient::on_error).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LimitPolicy {
+    /// Drop the frame
+    #[default]
+    Reject,
+    /// Keep the first `max_batch_len` updates of a batch or snapshot and the
+    /// first `max_fee_accounts` accounts of a fee market (see
+    /// [`decode_message_truncated`]); other oversized frames are dropped
+    Truncate,
 }
 
 /// Decode a binary WebSocket message.
@@ -36,367 +94,644 @@ pub enum DecodeError {
 ///
 /// Decoded message, or None for unhandled types
 pub fn decode_message(msg_type: u8, payload: &[u8]) -> Result<Option<DecodedMessage>, DecodeError> {
+    decode_message_with_limits(msg_type, payload, &DecodeLimits::default())
+}
+
+/// Decode a binary WebSocket message, enforcing `limits`.
+///
+/// # Arguments
+///
+/// * `msg_type` - Message type byte
+/// * `payload` - Message payload (without type byte)
+/// * `limits` - Size limits for length prefixes and counts
+///
+/// # Returns
+///
+/// Decoded message, or None for unhandled types
+pub fn decode_message_with_limits(
+    msg_type: u8,
+    payload: &[u8],
+    limits: &DecodeLimits,
+) -> Result<Option<DecodedMessage>, DecodeError> {
     let msg_type = MessageType::try_from(msg_type).map_err(DecodeError::InvalidMessageType)?;
+    check_limit("payload", payload.len(), limits.max_payload_len)?;
 
     match msg_type {
         MessageType::PoolUpdate => {
-            let update = decode_pool_update(payload)?;
+            let update = PoolUpdateRef::decode_with_limits(payload, limits)?.to_owned();
             Ok(Some(DecodedMessage::PoolUpdate(update)))
         }
         MessageType::PoolUpdateBatch => {
-            let updates = decode_pool_update_batch(payload)?;
+            let updates = PoolUpdateBatchRef::decode_with_limits(payload, limits)?.to_owned()?;
             Ok(Some(DecodedMessage::PoolUpdateBatch(updates)))
         }
+        MessageType::PoolSnapshot => {
+            let updates = PoolUpdateBatchRef::decode_with_limits(payload, limits)?.to_owned()?;
+            Ok(Some(DecodedMessage::PoolSnapshot(updates)))
+        }
         MessageType::PriorityFees => {
-            let fees = decode_fee_market(payload)?;
+            let fees = decode_fee_market(payload, limits)?;
             Ok(Some(DecodedMessage::FeeMarket(fees)))
         }
         MessageType::Blockhash => {
             let bh = decode_blockhash(payload)?;
             Ok(Some(DecodedMessage::Blockhash(bh)))
         }
-        MessageType::PriceUpdate => decode_price_update(payload),
-        MessageType::PriceBatch => decode_price_batch(payload),
-        MessageType::PriceSnapshot => decode_price_snapshot(payload),
+        MessageType::PriceUpdate => {
+            let entry = price_entry(proto::PriceEntry::parse(payload)?);
+            Ok(Some(DecodedMessage::PriceUpdate(entry)))
+        }
+        MessageType::PriceBatch => Ok(Some(DecodedMessage::PriceBatch(decode_price_entries(payload)?))),
+        MessageType::PriceSnapshot => Ok(Some(DecodedMessage::PriceSnapshot(decode_price_entries(payload)?))),
         MessageType::Error => {
             let msg = String::from_utf8(payload.to_vec())?;
             Ok(Some(DecodedMessage::Error(msg)))
         }
-        MessageType::Pong => Ok(None),
+        MessageType::Pong => {
+            let timestamp_ms = (payload.len() >= 8).then(|| read_u64(payload, &mut 0)).transpose()?.map(TimestampMs);
+            Ok(Some(DecodedMessage::Pong { timestamp_ms }))
+        }
         _ => Ok(None),
     }
 }
 
-fn decode_pool_update(data: &[u8]) -> Result<PoolUpdate, DecodeError> {
-    let mut offset = 0;
-
-    // serialized_state: Bytes (u64 len + bytes)
-    let state_len = read_u64(data, &mut offset)?;
-    if offset + state_len as usize > data.len() {
-        return Err(DecodeError::PayloadTooShort {
-            expected: offset + state_len as usize,
-            actual: data.len(),
-        });
+/// Like [`decode_message_with_limits`], but truncates pool update batches,
+/// snapshots and fee markets to `max_batch_len` updates and
+/// `max_fee_accounts` accounts instead of failing.
+///
+/// Only the kept entries are decoded and allocated; every other limit is
+/// still enforced.
+pub fn decode_message_truncated(
+    msg_type: u8,
+    payload: &[u8],
+    limits: &DecodeLimits,
+) -> Result<Option<DecodedMessage>, DecodeError> {
+    let snapshot = match MessageType::try_from(msg_type) {
+        Ok(MessageType::PoolUpdateBatch) => false,
+        Ok(MessageType::PoolSnapshot) => true,
+        Ok(MessageType::PriorityFees) => {
+            check_limit("payload", payload.len(), limits.max_payload_len)?;
+            let fees = decode_fee_market_capped(payload, limits, Vec::new(), true)?;
+            return Ok(Some(DecodedMessage::FeeMarket(fees)));
+        }
+        _ => return decode_message_with_limits(msg_type, payload, limits),
+    };
+    check_limit("payload", payload.len(), limits.max_payload_len)?;
+    let unbounded = DecodeLimits {
+        max_batch_len: usize::MAX,
+        ..*limits
+    };
+    let batch = PoolUpdateBatchRef::decode_with_limits(payload, &unbounded)?;
+    let mut updates = Vec::with_capacity(batch.capacity().min(limits.max_batch_len));
+    for update in batch.iter().take(limits.max_batch_len) {
+        updates.push(update?.to_owned());
     }
-    let serialized_state = data[offset..offset + state_len as usize].to_vec();
-    offset += state_len as usize;
-
-    // sequence: u64
-    let sequence = read_u64(data, &mut offset)?;
-
-    // slot: u64
-    let slot = read_u64(data, &mut offset)?;
+    Ok(Some(if snapshot {
+        DecodedMessage::PoolSnapshot(updates)
+    } else {
+        DecodedMessage::PoolUpdateBatch(updates)
+    }))
+}
 
-    // write_version: u64
-    let write_version = read_u64(data, &mut offset)?;
+/// A binary message decoded in forward-compatibility mode.
+#[derive(Debug, Clone)]
+pub struct CompatMessage {
+    /// The fields this SDK knows
+    pub message: DecodedMessage,
+    /// Trailing bytes of the message body after the known fields, appended
+    /// by a newer protocol revision
+    pub extra: Vec<u8>,
+}
 
-    // protocol_name: String (u64 len + UTF-8)
-    let name_len = read_u64(data, &mut offset)?;
-    if offset + name_len as usize > data.len() {
-        return Err(DecodeError::PayloadTooShort {
-            expected: offset + name_len as usize,
-            actual: data.len(),
-        });
-    }
-    let protocol_name = String::from_utf8(data[offset..offset + name_len as usize].to_vec())?;
-    offset += name_len as usize;
-
-    // pool_address: [u8; 32]
-    if offset + 32 > data.len() {
-        return Err(DecodeError::PayloadTooShort {
-            expected: offset + 32,
-            actual: data.len(),
-        });
-    }
-    let pool_address = bs58::encode(&data[offset..offset + 32]).into_string();
-    offset += 32;
-
-    // all_token_mints: Vec<[u8; 32]>
-    let num_mints = read_u64(data, &mut offset)?;
-    if offset + (num_mints as usize) * 32 > data.len() {
-        return Err(DecodeError::PayloadTooShort {
-            expected: offset + (num_mints as usize) * 32,
-            actual: data.len(),
-        });
-    }
-    let mut token_mints = Vec::with_capacity(num_mints as usize);
-    for _ in 0..num_mints {
-        token_mints.push(bs58::encode(&data[offset..offset + 32]).into_string());
-        offset += 32;
-    }
+/// Trailing fields of a message from a newer protocol revision, as passed to
+/// [`K256WebSocketClient::on_unknown_fields`](crate::ws::K256WebSocketClient::on_unknown_fields).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownFields {
+    /// Message type, e.g. `"blockhash"`
+    pub kind: &'static str,
+    /// Trailing bytes after the fields this SDK knows
+    pub extra: Vec<u8>,
+}
 
-    // all_token_balances: Vec<u64>
-    let num_balances = read_u64(data, &mut offset)?;
-    let mut token_balances = Vec::with_capacity(num_balances as usize);
-    for _ in 0..num_balances {
-        token_balances.push(read_u64(data, &mut offset)?);
-    }
+/// Decode a binary message sent in forward-compatibility mode.
+///
+/// With `forward_compat` set on the subscription, the server prefixes the
+/// body of every data message (pool updates, fee markets, blockhashes and
+/// prices) with its length as a little-endian `u32`; control messages are
+/// sent unchanged. Known fields are decoded from the front of the body and
+/// the rest is kept in [`CompatMessage::extra`], so fields appended by a
+/// newer server do not break decoding.
+///
+/// # Returns
+///
+/// Decoded message, or None for unhandled types
+pub fn decode_message_compat(
+    msg_type: u8,
+    payload: &[u8],
+    limits: &DecodeLimits,
+) -> Result<Option<CompatMessage>, DecodeError> {
+    let kind = MessageType::try_from(msg_type).map_err(DecodeError::InvalidMessageType)?;
+    let body = compat_body(kind, payload)?;
+    let Some(message) = decode_message_with_limits(msg_type, body, limits)? else {
+        return Ok(None);
+    };
+    let known = known_len(kind, body, limits)?.min(body.len());
+    Ok(Some(CompatMessage {
+        message,
+        extra: body[known..].to_vec(),
+    }))
+}
 
-    // all_token_decimals: Vec<i32>
-    let num_decimals = read_u64(data, &mut offset)?;
-    let mut token_decimals = Vec::with_capacity(num_decimals as usize);
-    for _ in 0..num_decimals {
-        token_decimals.push(read_i32(data, &mut offset)?);
-    }
+/// Message body of a forward-compatibility mode payload: data messages
+/// carry a length prefix, control messages are unchanged.
+pub(crate) fn compat_body(kind: MessageType, payload: &[u8]) -> Result<&[u8], DecodeError> {
+    Ok(proto::compat_body(kind, payload)?)
+}
 
-    // best_bid: Option<OrderLevel>
-    let best_bid = decode_optional_order_level(data, &mut offset)?;
-
-    // best_ask: Option<OrderLevel>
-    let best_ask = decode_optional_order_level(data, &mut offset)?;
-
-    Ok(PoolUpdate {
-        sequence,
-        slot,
-        write_version,
-        protocol_name,
-        pool_address,
-        token_mints,
-        token_balances,
-        token_decimals,
-        best_bid,
-        best_ask,
-        serialized_state,
+/// Bytes at the front of a successfully decoded `body` covered by the fields
+/// this SDK knows.
+fn known_len(kind: MessageType, body: &[u8], limits: &DecodeLimits) -> Result<usize, DecodeError> {
+    Ok(match kind {
+        MessageType::PoolUpdate => proto::PoolUpdate::parse_prefix(body, limits)?.1,
+        MessageType::PoolUpdateBatch | MessageType::PoolSnapshot => {
+            proto::PoolUpdateBatch::parse(body, limits)?.encoded_len()?
+        }
+        MessageType::PriorityFees => proto::FeeMarket::parse(body, limits)?.encoded_len(),
+        MessageType::Blockhash => proto::Blockhash::parse(body)?.encoded_len(),
+        MessageType::PriceUpdate => proto::PriceEntry::parse(body)?.encoded_len(),
+        MessageType::PriceBatch | MessageType::PriceSnapshot => proto::price_entries(body)?.encoded_len(),
+        MessageType::Pong if body.len() >= 8 => 8,
+        MessageType::Pong => 0,
+        _ => body.len(),
     })
 }
 
-fn decode_optional_order_level(data: &[u8], offset: &mut usize) -> Result<Option<OrderLevel>, DecodeError> {
-    if *offset >= data.len() {
-        return Err(DecodeError::PayloadTooShort {
-            expected: *offset + 1,
-            actual: data.len(),
-        });
+/// Decode a binary client → server frame (including the type byte).
+///
+/// Counterpart of [`encode_message`](crate::ws::encode_message), useful for
+/// mock servers and for inspecting captured client traffic.
+pub fn decode_client_message(data: &[u8]) -> Result<ClientMessage, DecodeError> {
+    let (&msg_type, payload) = data.split_first().ok_or(DecodeError::PayloadTooShort {
+        expected: 1,
+        actual: 0,
+    })?;
+    let msg_type = MessageType::try_from(msg_type).map_err(DecodeError::InvalidMessageType)?;
+
+    match msg_type {
+        MessageType::Subscribe => Ok(ClientMessage::Subscribe(serde_json::from_slice(payload)?)),
+        MessageType::Unsubscribe => Ok(ClientMessage::Unsubscribe),
+        MessageType::SubscribeQuote => Ok(ClientMessage::SubscribeQuote(serde_json::from_slice(payload)?)),
+        MessageType::UnsubscribeQuote => Ok(ClientMessage::UnsubscribeQuote {
+            topic_id: String::from_utf8(payload.to_vec())?,
+        }),
+        MessageType::Ping => Ok(ClientMessage::Ping),
+        MessageType::SubscribePrice => Ok(ClientMessage::SubscribePrice(serde_json::from_slice(payload)?)),
+        MessageType::UnsubscribePrice => Ok(ClientMessage::UnsubscribePrice),
+        other => Err(DecodeError::InvalidMessageType(other as u8)),
     }
-    if data[*offset] == 0 {
-        *offset += 1;
+}
+
+/// Decode a JSON-mode text WebSocket message.
+///
+/// Accepts both the `{"type": ..., "data": {...}}` envelope and flat
+/// objects, with camelCase or snake_case field names.
+///
+/// # Returns
+///
+/// Decoded message, or None for unhandled types
+pub fn decode_json_message(text: &str) -> Result<Option<DecodedMessage>, DecodeError> {
+    let Value::Object(mut object) = serde_json::from_str::<Value>(text)? else {
         return Ok(None);
-    }
+    };
+    let Some(Value::String(msg_type)) = object.remove("type") else {
+        return Ok(None);
+    };
+    let data = normalize_keys(object.remove("data").unwrap_or(Value::Object(object)));
+
+    let decoded = match msg_type.as_str() {
+        "pool_update" => DecodedMessage::PoolUpdate(from_json(data)?),
+        "pool_update_batch" => DecodedMessage::PoolUpdateBatch(from_json(unwrap_list(data, "updates"))?),
+        "pool_snapshot" => DecodedMessage::PoolSnapshot(from_json(unwrap_list(data, "updates"))?),
+        "fee_market" | "priority_fees" => DecodedMessage::FeeMarket(from_json(data)?),
+        "blockhash" => DecodedMessage::Blockhash(from_json(data)?),
+        "quote" => DecodedMessage::Quote(from_json(data)?),
+        "heartbeat" => DecodedMessage::Heartbeat(from_json(data)?),
+        "price_update" => DecodedMessage::PriceUpdate(from_json(data)?),
+        "price_batch" => DecodedMessage::PriceBatch(from_json(unwrap_list(data, "entries"))?),
+        "price_snapshot" => DecodedMessage::PriceSnapshot(from_json(unwrap_list(data, "entries"))?),
+        "subscribed" => {
+            let strings = |key: &str| -> Vec<String> {
+                data.get(key)
+                    .and_then(Value::as_array)
+                    .map(|values| values.iter().filter_map(|v| v.as_str().map(String::from)).collect())
+                    .unwrap_or_default()
+            };
+            let channels = strings("channels");
+            let server = ServerInfo {
+                protocol_version: data
+                    .get("protocol_version")
+                    .and_then(Value::as_u64)
+                    .and_then(|version| u32::try_from(version).ok()),
+                server_version: data
+                    .get("server_version")
+                    .or_else(|| data.get("version"))
+                    .and_then(Value::as_str)
+                    .map(String::from),
+                features: strings("features"),
+            };
+            DecodedMessage::Subscribed { channels, server }
+        }
+        "error" => DecodedMessage::Error(error_message(&data)),
+        "replay_complete" => DecodedMessage::ReplayComplete(from_json(data)?),
+        "pong" => DecodedMessage::Pong {
+            timestamp_ms: data.get("timestamp_ms").and_then(Value::as_u64).map(TimestampMs),
+        },
+        _ => return Ok(None),
+    };
+    Ok(Some(decoded))
+}
 
-    *offset += 1;
-    let price = read_u64(data, offset)?;
-    let size = read_u64(data, offset)?;
-    Ok(Some(OrderLevel { price, size }))
+/// Error message text, with any rate-limit details of an error object
+/// appended as `key=value` pairs for [`QuotaError::parse`].
+///
+/// [`QuotaError::parse`]: crate::ws::QuotaError::parse
+fn error_message(data: &Value) -> String {
+    let message = match data {
+        Value::String(message) => Some(message.as_str()),
+        other => other.get("message").and_then(Value::as_str),
+    };
+    let mut message = message.unwrap_or("Unknown error").to_string();
+    let details: Vec<String> = QUOTA_KEYS
+        .iter()
+        .filter_map(|key| Some(format!("{}={}", key, data.get(key)?.as_u64()?)))
+        .collect();
+    if !details.is_empty() {
+        message = format!("{} ({})", message, details.join(", "));
+    }
+    message
 }
 
-fn decode_pool_update_batch(data: &[u8]) -> Result<Vec<PoolUpdate>, DecodeError> {
-    let mut offset = 0;
+fn from_json<T: DeserializeOwned>(value: Value) -> Result<T, DecodeError> {
+    Ok(serde_json::from_value(value)?)
+}
 
-    // count: u16 LE
-    let count = read_u16(data, &mut offset)?;
+/// Batches may arrive as a bare array or wrapped in an object field.
+fn unwrap_list(value: Value, field: &str) -> Value {
+    match value {
+        Value::Object(mut object) => object.remove(field).unwrap_or(Value::Object(object)),
+        other => other,
+    }
+}
 
-    let mut updates = Vec::with_capacity(count as usize);
-    for _ in 0..count {
-        // length: u32 LE
-        let length = read_u32(data, &mut offset)?;
+/// Convert camelCase object keys to snake_case.
+fn normalize_keys(value: Value) -> Value {
+    match value {
+        Value::Object(object) => {
+            let normalized: Map<String, Value> = object
+                .into_iter()
+                .map(|(key, value)| (to_snake_case(&key), normalize_keys(value)))
+                .collect();
+            Value::Object(normalized)
+        }
+        Value::Array(values) => Value::Array(values.into_iter().map(normalize_keys).collect()),
+        other => other,
+    }
+}
 
-        // payload (without type byte)
-        if offset + length as usize > data.len() {
-            return Err(DecodeError::PayloadTooShort {
-                expected: offset + length as usize,
-                actual: data.len(),
-            });
+fn to_snake_case(key: &str) -> String {
+    let mut out = String::with_capacity(key.len() + 4);
+    for c in key.chars() {
+        if c.is_ascii_uppercase() {
+            out.push('_');
+            out.push(c.to_ascii_lowercase());
+        } else {
+            out.push(c);
         }
-        let update = decode_pool_update(&data[offset..offset + length as usize])?;
-        updates.push(update);
-        offset += length as usize;
     }
+    out
+}
 
-    Ok(updates)
+/// Like [`decode_message_with_limits`], but decodes pool update batches
+/// with at least `min_parallel` updates on the rayon thread pool.
+#[cfg(feature = "parallel")]
+#[cfg_attr(docsrs, doc(cfg(feature = "parallel")))]
+pub fn decode_message_parallel(
+    msg_type: u8,
+    payload: &[u8],
+    min_parallel: usize,
+    limits: &DecodeLimits,
+) -> Result<Option<DecodedMessage>, DecodeError> {
+    use rayon::prelude::*;
+
+    let snapshot = match MessageType::try_from(msg_type) {
+        Ok(MessageType::PoolUpdateBatch) => false,
+        Ok(MessageType::PoolSnapshot) => true,
+        _ => return decode_message_with_limits(msg_type, payload, limits),
+    };
+    check_limit("payload", payload.len(), limits.max_payload_len)?;
+    let batch = PoolUpdateBatchRef::decode_with_limits(payload, limits)?;
+    let updates = if batch.len() < min_parallel {
+        batch.to_owned()?
+    } else {
+        batch
+            .raw_entries()?
+            .par_iter()
+            .map(|entry| PoolUpdateRef::decode_with_limits(entry, limits).map(|update| update.to_owned()))
+            .collect::<Result<Vec<_>, _>>()?
+    };
+    Ok(Some(if snapshot {
+        DecodedMessage::PoolSnapshot(updates)
+    } else {
+        DecodedMessage::PoolUpdateBatch(updates)
+    }))
 }
 
-fn decode_fee_market(data: &[u8]) -> Result<FeeMarket, DecodeError> {
-    if data.len() < 42 {
-        return Err(DecodeError::PayloadTooShort {
-            expected: 42,
-            actual: data.len(),
-        });
-    }
+fn decode_fee_market(data: &[u8], limits: &DecodeLimits) -> Result<FeeMarket, DecodeError> {
+    decode_fee_market_into(data, limits, Vec::new())
+}
 
-    let mut offset = 0;
-
-    let slot = read_u64(data, &mut offset)?;
-    let timestamp_ms = read_u64(data, &mut offset)?;
-    let recommended = read_u64(data, &mut offset)?;
-    let state = NetworkState::try_from(data[offset]).map_err(DecodeError::InvalidNetworkState)?;
-    offset += 1;
-    let is_stale = data[offset] != 0;
-    offset += 1;
-    let block_utilization_pct = f32::from_le_bytes(data[offset..offset + 4].try_into().unwrap());
-    offset += 4;
-    let blocks_in_window = read_u32(data, &mut offset)?;
-    let account_count = read_u64(data, &mut offset)?;
-
-    let mut accounts = Vec::with_capacity(account_count as usize);
-    for _ in 0..account_count {
-        if offset + 92 > data.len() {
-            return Err(DecodeError::PayloadTooShort {
-                expected: offset + 92,
-                actual: data.len(),
-            });
-        }
-        let pubkey = bs58::encode(&data[offset..offset + 32]).into_string();
-        offset += 32;
-        let total_txs = read_u32(data, &mut offset)?;
-        let active_slots = read_u32(data, &mut offset)?;
-        let cu_consumed = read_u64(data, &mut offset)?;
-        let utilization_pct = f32::from_le_bytes(data[offset..offset + 4].try_into().unwrap());
-        offset += 4;
-        let p25 = read_u64(data, &mut offset)?;
-        let p50 = read_u64(data, &mut offset)?;
-        let p75 = read_u64(data, &mut offset)?;
-        let p90 = read_u64(data, &mut offset)?;
-        let min_nonzero_price = read_u64(data, &mut offset)?;
-
-        accounts.push(AccountFee {
-            pubkey,
-            total_txs,
-            active_slots,
-            cu_consumed,
-            utilization_pct,
-            p25, p50, p75, p90,
-            min_nonzero_price,
-        });
-    }
+/// Decode a fee market, collecting its accounts into `accounts` (cleared
+/// first) so its capacity can be reused.
+pub(super) fn decode_fee_market_into(
+    data: &[u8],
+    limits: &DecodeLimits,
+    accounts: Vec<AccountFee>,
+) -> Result<FeeMarket, DecodeError> {
+    decode_fee_market_capped(data, limits, accounts, false)
+}
+
+/// Decode a fee market, keeping only the first `max_fee_accounts` accounts
+/// when `truncate` is set instead of failing.
+fn decode_fee_market_capped(
+    data: &[u8],
+    limits: &DecodeLimits,
+    mut accounts: Vec<AccountFee>,
+    truncate: bool,
+) -> Result<FeeMarket, DecodeError> {
+    let fees = if truncate {
+        proto::FeeMarket::parse_truncated(data, limits)?
+    } else {
+        proto::FeeMarket::parse(data, limits)?
+    };
+    let state = NetworkState::try_from(fees.state).map_err(DecodeError::InvalidNetworkState)?;
+
+    accounts.clear();
+    accounts.extend(fees.accounts().map(|account| AccountFee {
+        pubkey: Pubkey::new(*account.pubkey),
+        total_txs: account.total_txs,
+        active_slots: account.active_slots,
+        cu_consumed: account.cu_consumed,
+        utilization_pct: account.utilization_pct,
+        p25: account.p25,
+        p50: account.p50,
+        p75: account.p75,
+        p90: account.p90,
+        min_nonzero_price: account.min_nonzero_price,
+    }));
 
     Ok(FeeMarket {
-        slot,
-        timestamp_ms,
-        recommended,
+        slot: Slot(fees.slot),
+        timestamp_ms: TimestampMs(fees.timestamp_ms),
+        recommended: fees.recommended,
         state,
-        is_stale,
-        block_utilization_pct,
-        blocks_in_window,
+        is_stale: fees.is_stale,
+        block_utilization_pct: fees.block_utilization_pct,
+        blocks_in_window: fees.blocks_in_window,
         accounts,
     })
 }
 
 fn decode_blockhash(data: &[u8]) -> Result<Blockhash, DecodeError> {
-    if data.len() < 65 {
-        return Err(DecodeError::PayloadTooShort {
-            expected: 65,
-            actual: data.len(),
-        });
-    }
-
-    let mut offset = 0;
-
-    let slot = read_u64(data, &mut offset)?;
-    let timestamp_ms = read_u64(data, &mut offset)?;
-    let blockhash = bs58::encode(&data[offset..offset + 32]).into_string();
-    offset += 32;
-    let block_height = read_u64(data, &mut offset)?;
-    let last_valid_block_height = read_u64(data, &mut offset)?;
-    let is_stale = data[offset] != 0;
-
+    let bh = proto::Blockhash::parse(data)?;
     Ok(Blockhash {
-        slot,
-        timestamp_ms,
-        blockhash,
-        block_height,
-        last_valid_block_height,
-        is_stale,
+        slot: Slot(bh.slot),
+        timestamp_ms: TimestampMs(bh.timestamp_ms),
+        blockhash: encode_32(bh.blockhash).to_string(),
+        block_height: bh.block_height,
+        last_valid_block_height: bh.last_valid_block_height,
+        is_stale: bh.is_stale,
     })
 }
 
 fn decode_price_entries(data: &[u8]) -> Result<Vec<PriceEntry>, DecodeError> {
-    let mut offset = 0;
-    let count = read_u16(data, &mut offset)?;
-    let mut entries = Vec::with_capacity(count as usize);
-    for _ in 0..count {
-        if offset + 56 > data.len() {
-            break;
-        }
-        let mint = bs58::encode(&data[offset..offset + 32]).into_string();
-        offset += 32;
-        let usd_price_raw = read_u64(data, &mut offset)?;
-        let slot = read_u64(data, &mut offset)?;
-        let timestamp_ms = read_u64(data, &mut offset)?;
-        entries.push(PriceEntry {
-            mint,
-            usd_price: usd_price_raw as f64 / 1e12,
-            slot,
-            timestamp_ms,
-        });
-    }
-    Ok(entries)
+    Ok(proto::price_entries(data)?.map(price_entry).collect())
 }
 
-fn decode_price_update(payload: &[u8]) -> Result<Option<DecodedMessage>, DecodeError> {
-    if payload.len() < 56 {
-        return Err(DecodeError::PayloadTooShort {
-            expected: 56,
-            actual: payload.len(),
-        });
+fn price_entry(entry: proto::PriceEntry<'_>) -> PriceEntry {
+    PriceEntry {
+        mint: Pubkey::new(*entry.mint),
+        usd_price: entry.usd_price(),
+        slot: Slot(entry.slot),
+        timestamp_ms: TimestampMs(entry.timestamp_ms),
     }
-    let mut offset = 0;
-    let mint = bs58::encode(&payload[0..32]).into_string();
-    offset += 32;
-    let usd_price_raw = read_u64(payload, &mut offset)?;
-    let slot = read_u64(payload, &mut offset)?;
-    let timestamp_ms = read_u64(payload, &mut offset)?;
-    Ok(Some(DecodedMessage::PriceUpdate(PriceEntry {
-        mint,
-        usd_price: usd_price_raw as f64 / 1e12,
-        slot,
-        timestamp_ms,
-    })))
 }
 
-fn decode_price_batch(payload: &[u8]) -> Result<Option<DecodedMessage>, DecodeError> {
-    let entries = decode_price_entries(payload)?;
-    Ok(Some(DecodedMessage::PriceBatch(entries)))
-}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::OrderLevel;
+
+    #[test]
+    fn test_decode_json_envelope_camel_case() {
+        let text = r#"{"type":"blockhash","data":{"slot":1,"timestampMs":2,"blockhash":"abc",
+            "blockHeight":3,"lastValidBlockHeight":153,"isStale":false}}"#;
+        let Some(DecodedMessage::Blockhash(bh)) = decode_json_message(text).unwrap() else {
+            panic!("expected blockhash");
+        };
+        assert_eq!(bh.last_valid_block_height, 153);
+        assert_eq!(bh.timestamp_ms, 2);
+    }
 
-fn decode_price_snapshot(payload: &[u8]) -> Result<Option<DecodedMessage>, DecodeError> {
-    let entries = decode_price_entries(payload)?;
-    Ok(Some(DecodedMessage::PriceSnapshot(entries)))
-}
+    #[test]
+    fn test_decode_json_error_keeps_quota_details() {
+        let text = r#"{"type":"error","data":{"message":"Rate limit exceeded","limit":10,"remaining":0,"resetAt":5}}"#;
+        let Some(DecodedMessage::Error(message)) = decode_json_message(text).unwrap() else {
+            panic!("expected error");
+        };
+        assert_eq!(message, "Rate limit exceeded (limit=10, remaining=0, reset_at=5)");
+
+        let text = r#"{"type":"error","message":"bad request"}"#;
+        let Some(DecodedMessage::Error(message)) = decode_json_message(text).unwrap() else {
+            panic!("expected error");
+        };
+        assert_eq!(message, "bad request");
+    }
 
-// Helper functions for reading little-endian integers
-fn read_u64(data: &[u8], offset: &mut usize) -> Result<u64, DecodeError> {
-    if *offset + 8 > data.len() {
-        return Err(DecodeError::PayloadTooShort {
-            expected: *offset + 8,
-            actual: data.len(),
-        });
+    #[test]
+    fn test_decode_json_flat_messages() {
+        let text = r#"{"type":"fee_market","slot":1,"timestamp_ms":2,"recommended":1000,"state":2,
+            "is_stale":false,"block_utilization_pct":50.0,"blocks_in_window":10,"accounts":[]}"#;
+        let Some(DecodedMessage::FeeMarket(fees)) = decode_json_message(text).unwrap() else {
+            panic!("expected fee market");
+        };
+        assert_eq!(fees.state, NetworkState::High);
+
+        let text = r#"{"type":"heartbeat","timestamp_ms":5,"uptime_seconds":60}"#;
+        let Some(DecodedMessage::Heartbeat(hb)) = decode_json_message(text).unwrap() else {
+            panic!("expected heartbeat");
+        };
+        assert_eq!(hb.uptime_seconds, 60);
+        assert_eq!(hb.subscriptions, 0);
+
+        let text = r#"{"type":"price_batch","data":[{"mint":"So11111111111111111111111111111111111111112","usdPrice":1.5,"slot":1,"timestampMs":2}]}"#;
+        let Some(DecodedMessage::PriceBatch(entries)) = decode_json_message(text).unwrap() else {
+            panic!("expected price batch");
+        };
+        assert_eq!(entries[0].usd_price, 1.5);
     }
-    let value = u64::from_le_bytes(data[*offset..*offset + 8].try_into().unwrap());
-    *offset += 8;
-    Ok(value)
-}
 
-fn read_u32(data: &[u8], offset: &mut usize) -> Result<u32, DecodeError> {
-    if *offset + 4 > data.len() {
-        return Err(DecodeError::PayloadTooShort {
-            expected: *offset + 4,
-            actual: data.len(),
-        });
+    #[test]
+    fn test_fee_market_account_count_checked_before_allocating() {
+        let mut payload = vec![0u8; 42];
+        payload[34..42].copy_from_slice(&u64::MAX.to_le_bytes());
+        let result = decode_message(MessageType::PriorityFees as u8, &payload);
+        assert!(matches!(result, Err(DecodeError::LimitExceeded { field: "fee accounts", .. })));
+
+        payload[34..42].copy_from_slice(&1000u64.to_le_bytes());
+        let result = decode_message(MessageType::PriorityFees as u8, &payload);
+        assert!(matches!(result, Err(DecodeError::PayloadTooShort { expected: 92_042, .. })));
+    }
+
+    #[test]
+    fn test_decode_truncated() {
+        use crate::types::PoolUpdate;
+        use crate::ws::{encode_fee_market, encode_pool_update};
+
+        let limits = DecodeLimits {
+            max_batch_len: 2,
+            max_fee_accounts: 1,
+            ..DecodeLimits::default()
+        };
+        let mut batch = 3u16.to_le_bytes().to_vec();
+        for sequence in 1..=3 {
+            let update = encode_pool_update(&PoolUpdate {
+                sequence,
+                ..PoolUpdate::default()
+            });
+            batch.extend_from_slice(&(update.len() as u32 - 1).to_le_bytes());
+            batch.extend_from_slice(&update[1..]);
+        }
+        let msg_type = MessageType::PoolUpdateBatch as u8;
+        let result = decode_message_with_limits(msg_type, &batch, &limits);
+        assert!(matches!(result, Err(DecodeError::LimitExceeded { field: "batch", actual: 3, .. })));
+        let truncated = decode_message_truncated(msg_type, &batch, &limits).unwrap();
+        let Some(DecodedMessage::PoolUpdateBatch(updates)) = truncated else {
+            panic!("expected batch");
+        };
+        assert_eq!(updates.iter().map(|u| u.sequence).collect::<Vec<_>>(), [1, 2]);
+
+        let fees = FeeMarket {
+            slot: Slot(1),
+            timestamp_ms: TimestampMs(0),
+            recommended: 1000,
+            state: NetworkState::Normal,
+            is_stale: false,
+            block_utilization_pct: 50.0,
+            blocks_in_window: 10,
+            accounts: vec![
+                AccountFee {
+                    pubkey: Pubkey::new([1; 32]),
+                    total_txs: 1,
+                    active_slots: 1,
+                    cu_consumed: 0,
+                    utilization_pct: 0.0,
+                    p25: 0,
+                    p50: 0,
+                    p75: 0,
+                    p90: 0,
+                    min_nonzero_price: 0,
+                };
+                2
+            ],
+        };
+        let frame = encode_fee_market(&fees);
+        assert!(decode_message_with_limits(frame[0], &frame[1..], &limits).is_err());
+        let truncated = decode_message_truncated(frame[0], &frame[1..], &limits).unwrap();
+        let Some(DecodedMessage::FeeMarket(fees)) = truncated else {
+            panic!("expected fee market");
+        };
+        assert_eq!(fees.accounts.len(), 1);
+
+        // Oversized payloads are never truncated
+        let limits = DecodeLimits {
+            max_payload_len: 8,
+            ..limits
+        };
+        let result = decode_message_truncated(msg_type, &batch, &limits);
+        assert!(matches!(result, Err(DecodeError::LimitExceeded { field: "payload", .. })));
     }
-    let value = u32::from_le_bytes(data[*offset..*offset + 4].try_into().unwrap());
-    *offset += 4;
-    Ok(value)
-}
 
-fn read_u16(data: &[u8], offset: &mut usize) -> Result<u16, DecodeError> {
-    if *offset + 2 > data.len() {
-        return Err(DecodeError::PayloadTooShort {
-            expected: *offset + 2,
-            actual: data.len(),
-        });
+    #[test]
+    fn test_decode_compat_keeps_unknown_trailing_fields() {
+        use crate::types::PoolUpdate;
+        use crate::ws::{encode_blockhash, encode_fee_market, encode_pool_update};
+
+        // Length-prefixed body with `extra` appended, as a newer server sends it
+        let wrap = |frame: Vec<u8>, extra: &[u8]| {
+            let mut payload = ((frame.len() - 1 + extra.len()) as u32).to_le_bytes().to_vec();
+            payload.extend_from_slice(&frame[1..]);
+            payload.extend_from_slice(extra);
+            (frame[0], payload)
+        };
+        let limits = DecodeLimits::default();
+
+        let blockhash = Blockhash {
+            slot: Slot(7),
+            blockhash: bs58::encode([1u8; 32]).into_string(),
+            ..Blockhash::default()
+        };
+        let (msg_type, payload) = wrap(encode_blockhash(&blockhash).unwrap(), &[9, 9, 9]);
+        let compat = decode_message_compat(msg_type, &payload, &limits).unwrap().unwrap();
+        assert!(matches!(compat.message, DecodedMessage::Blockhash(ref bh) if bh.slot == 7));
+        assert_eq!(compat.extra, [9, 9, 9]);
+
+        let update = PoolUpdate {
+            sequence: 3,
+            serialized_state: vec![1, 2],
+            ..PoolUpdate::default()
+        };
+        let (msg_type, payload) = wrap(encode_pool_update(&update), &[]);
+        let compat = decode_message_compat(msg_type, &payload, &limits).unwrap().unwrap();
+        assert!(matches!(compat.message, DecodedMessage::PoolUpdate(ref u) if u.sequence == 3));
+        assert!(compat.extra.is_empty());
+
+        let fees = FeeMarket {
+            slot: Slot(1),
+            timestamp_ms: TimestampMs(0),
+            recommended: 1000,
+            state: NetworkState::Normal,
+            is_stale: false,
+            block_utilization_pct: 50.0,
+            blocks_in_window: 10,
+            accounts: Vec::new(),
+        };
+        let (msg_type, payload) = wrap(encode_fee_market(&fees), &[0xAB; 16]);
+        let compat = decode_message_compat(msg_type, &payload, &limits).unwrap().unwrap();
+        assert_eq!(compat.extra.len(), 16);
+
+        // Control frames are not wrapped
+        let pong = decode_message_compat(MessageType::Pong as u8, &5u64.to_le_bytes(), &limits).unwrap().unwrap();
+        assert!(matches!(pong.message, DecodedMessage::Pong { timestamp_ms: Some(TimestampMs(5)) }));
+
+        // The length prefix must fit the payload
+        let result = decode_message_compat(msg_type, &payload[..payload.len() - 1], &limits);
+        assert!(matches!(result, Err(DecodeError::PayloadTooShort { .. })));
     }
-    let value = u16::from_le_bytes(data[*offset..*offset + 2].try_into().unwrap());
-    *offset += 2;
-    Ok(value)
-}
 
-fn read_i32(data: &[u8], offset: &mut usize) -> Result<i32, DecodeError> {
-    if *offset + 4 > data.len() {
-        return Err(DecodeError::PayloadTooShort {
-            expected: *offset + 4,
-            actual: data.len(),
-        });
+    #[test]
+    fn test_decode_json_pool_update() {
+        let text = r#"{"type":"pool_update","data":{"sequence":7,"slot":1,"writeVersion":2,
+            "protocol":"Whirlpool","poolAddress":"11111111111111111111111111111111",
+            "tokenMints":["So11111111111111111111111111111111111111112","EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v"],
+            "tokenBalances":[1,2],
+            "tokenDecimals":[9,6],"bestBid":null,"bestAsk":{"price":10,"size":1}}}"#;
+        let Some(DecodedMessage::PoolUpdate(update)) = decode_json_message(text).unwrap() else {
+            panic!("expected pool update");
+        };
+        assert_eq!(update.protocol_name, "Whirlpool");
+        assert_eq!(update.token_mints[1], "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v");
+        assert_eq!(update.best_ask, Some(OrderLevel { price: 10, size: 1 }));
+        assert!(update.serialized_state.is_empty());
+        assert!(decode_json_message(r#"{"type":"unknown"}"#).unwrap().is_none());
     }
-    let value = i32::from_le_bytes(data[*offset..*offset + 4].try_into().unwrap());
-    *offset += 4;
-    Ok(value)
 }
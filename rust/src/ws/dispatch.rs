@@ -0,0 +1,411 @@
+//! Bounded callback dispatch with drop policies.
+//!
+//! By default callbacks run inline on the connection task, so a slow
+//! callback delays reading from the socket. With [`Config::dispatch`] set,
+//! decoded messages are handed to one bounded queue per channel, each drained
+//! by its own thread, and the [`DropPolicy`] decides what happens when a
+//! queue is full.
+//!
+//! Control messages (pongs, subscription confirmations, server errors) are
+//! always handled inline so keepalive accounting is never delayed.
+//!
+//! Decoding still happens on the connection task unless
+//! [`Config::decode_queue`] is set too, which gives a three-stage pipeline:
+//! socket reads, decoding, and callbacks each run on their own task.
+//!
+//! [`Config::dispatch`]: crate::ws::Config::dispatch
+//! [`Config::decode_queue`]: crate::ws::Config::decode_queue
+
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+
+use tokio::sync::Notify;
+use tracing::Span;
+
+use crate::ws::message::DecodedMessage;
+use crate::ws::metrics;
+use crate::ws::subscription::Channel;
+
+/// What to do when a dispatch queue is full.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DropPolicy {
+    /// Wait for space, pausing socket reads (backpressure to the server)
+    Block,
+    /// Drop the oldest queued message to make room
+    DropOldest,
+    /// Drop the incoming message
+    DropNewest,
+    /// Replace any queued update for the same pool, keeping only the newest;
+    /// drop the oldest message when full. Behaves like `DropOldest` outside
+    /// the pools channel.
+    CoalesceByPool,
+}
+
+/// Queued dispatch configuration.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DispatchConfig {
+    /// Maximum queued messages per channel
+    pub capacity: usize,
+    /// Policy for channels without an override
+    pub policy: DropPolicy,
+    /// Per-channel policy overrides
+    pub channel_policies: Vec<(Channel, DropPolicy)>,
+}
+
+impl DispatchConfig {
+    /// Queue up to `capacity` messages per channel, applying `policy` when full.
+    pub fn new(capacity: usize, policy: DropPolicy) -> Self {
+        Self {
+            capacity,
+            policy,
+            channel_policies: Vec::new(),
+        }
+    }
+
+    /// Use a different policy for one channel.
+    pub fn with_channel(mut self, channel: Channel, policy: DropPolicy) -> Self {
+        self.channel_policies.retain(|(c, _)| *c != channel);
+        self.channel_policies.push((channel, policy));
+        self
+    }
+
+    fn policy_for(&self, lane: Lane) -> DropPolicy {
+        lane.channel()
+            .and_then(|channel| {
+                self.channel_policies
+                    .iter()
+                    .find(|(c, _)| *c == channel)
+                    .map(|(_, policy)| *policy)
+            })
+            .unwrap_or(self.policy)
+    }
+}
+
+impl Default for DispatchConfig {
+    fn default() -> Self {
+        Self::new(1024, DropPolicy::Block)
+    }
+}
+
+/// Messages dropped by the dispatch queues since the client was created.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DroppedMessages {
+    /// Pool updates and snapshots
+    pub pools: u64,
+    /// Fee market updates
+    pub priority_fees: u64,
+    /// Blockhash updates
+    pub blockhash: u64,
+    /// Quotes, prices and heartbeats
+    pub other: u64,
+}
+
+impl DroppedMessages {
+    /// Total dropped messages across all channels.
+    pub fn total(&self) -> u64 {
+        self.pools + self.priority_fees + self.blockhash + self.other
+    }
+}
+
+/// Dispatch queue a message is routed to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Lane {
+    Pools,
+    PriorityFees,
+    Blockhash,
+    Other,
+}
+
+impl Lane {
+    pub(crate) const ALL: [Lane; 4] = [Lane::Pools, Lane::PriorityFees, Lane::Blockhash, Lane::Other];
+
+    /// Lane for a message, or `None` for control messages handled inline.
+    pub(crate) fn of(msg: &DecodedMessage) -> Option<Self> {
+        match msg {
+            DecodedMessage::PoolUpdate(_)
+            | DecodedMessage::PoolUpdateBatch(_)
+            | DecodedMessage::PoolSnapshot(_) => Some(Self::Pools),
+            DecodedMessage::FeeMarket(_) => Some(Self::PriorityFees),
+            DecodedMessage::Blockhash(_) => Some(Self::Blockhash),
+            DecodedMessage::Quote(_)
+            | DecodedMessage::Heartbeat(_)
+            | DecodedMessage::PriceUpdate(_)
+            | DecodedMessage::PriceBatch(_)
+            | DecodedMessage::PriceSnapshot(_) => Some(Self::Other),
+            DecodedMessage::Error(_)
+            | DecodedMessage::Pong { .. }
+            | DecodedMessage::Subscribed { .. }
+            | DecodedMessage::ReplayComplete(_) => None,
+        }
+    }
+
+    fn channel(self) -> Option<Channel> {
+        match self {
+            Self::Pools => Some(Channel::Pools),
+            Self::PriorityFees => Some(Channel::PriorityFees),
+            Self::Blockhash => Some(Channel::Blockhash),
+            Self::Other => None,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Pools => "pools",
+            Self::PriorityFees => "priority_fees",
+            Self::Blockhash => "blockhash",
+            Self::Other => "other",
+        }
+    }
+}
+
+/// Bounded queue drained by a dedicated dispatch thread.
+pub(crate) struct DispatchQueue {
+    lane: Lane,
+    capacity: usize,
+    policy: DropPolicy,
+    /// Queued messages with their `k256.message` spans
+    items: Mutex<VecDeque<(DecodedMessage, Span)>>,
+    /// Signalled when an item is queued or the queue closes
+    available: Condvar,
+    /// Signalled when an item is taken, for `Block` producers
+    space: Notify,
+    closed: AtomicBool,
+    dropped: AtomicU64,
+}
+
+impl DispatchQueue {
+    pub(crate) fn new(lane: Lane, config: &DispatchConfig) -> Arc<Self> {
+        Arc::new(Self {
+            lane,
+            capacity: config.capacity.max(1),
+            policy: config.policy_for(lane),
+            items: Mutex::new(VecDeque::new()),
+            available: Condvar::new(),
+            space: Notify::new(),
+            closed: AtomicBool::new(false),
+            dropped: AtomicU64::new(0),
+        })
+    }
+
+    pub(crate) fn lane(&self) -> Lane {
+        self.lane
+    }
+
+    /// Queue a message, applying the drop policy if the queue is full.
+    pub(crate) async fn push(&self, msg: DecodedMessage, span: Span) {
+        match (self.policy, msg) {
+            (DropPolicy::CoalesceByPool, DecodedMessage::PoolUpdateBatch(updates)) => {
+                for update in updates {
+                    self.push_one((DecodedMessage::PoolUpdate(update), span.clone())).await;
+                }
+            }
+            (_, msg) => self.push_one((msg, span)).await,
+        }
+    }
+
+    async fn push_one(&self, msg: (DecodedMessage, Span)) {
+        let mut msg = Some(msg);
+        loop {
+            let space = self.space.notified();
+            {
+                let mut items = self.items.lock().unwrap();
+                if self.policy == DropPolicy::CoalesceByPool {
+                    if let Some(index) = coalesce_slot(&items, &msg.as_ref().unwrap().0) {
+                        items[index] = msg.take().unwrap();
+                        self.record_drop();
+                        return;
+                    }
+                }
+                if items.len() < self.capacity {
+                    items.push_back(msg.take().unwrap());
+                } else {
+                    match self.policy {
+                        DropPolicy::Block => {}
+                        DropPolicy::DropNewest => {
+                            self.record_drop();
+                            return;
+                        }
+                        DropPolicy::DropOldest | DropPolicy::CoalesceByPool => {
+                            items.pop_front();
+                            items.push_back(msg.take().unwrap());
+                            self.record_drop();
+                        }
+                    }
+                }
+            }
+            if msg.is_none() {
+                self.available.notify_one();
+                return;
+            }
+            if self.closed.load(Ordering::Acquire) {
+                return;
+            }
+            space.await;
+        }
+    }
+
+    /// Block until a message is available. Returns `None` once closed.
+    pub(crate) fn pop(&self) -> Option<(DecodedMessage, Span)> {
+        let mut items = self.items.lock().unwrap();
+        loop {
+            if self.closed.load(Ordering::Acquire) {
+                return None;
+            }
+            if let Some(msg) = items.pop_front() {
+                drop(items);
+                self.space.notify_one();
+                return Some(msg);
+            }
+            items = self.available.wait(items).unwrap();
+        }
+    }
+
+    pub(crate) fn close(&self) {
+        self.closed.store(true, Ordering::Release);
+        let _guard = self.items.lock().unwrap();
+        self.available.notify_all();
+        self.space.notify_waiters();
+    }
+
+    pub(crate) fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    fn record_drop(&self) {
+        self.dropped.fetch_add(1, Ordering::Relaxed);
+        metrics::message_dropped(self.lane.name());
+    }
+}
+
+/// Index of a queued update for the same pool as `incoming`.
+fn coalesce_slot(items: &VecDeque<(DecodedMessage, Span)>, incoming: &DecodedMessage) -> Option<usize> {
+    let DecodedMessage::PoolUpdate(update) = incoming else {
+        return None;
+    };
+    items.iter().position(|queued| {
+        matches!(queued, (DecodedMessage::PoolUpdate(q), _) if q.pool_address == update.pool_address)
+    })
+}
+
+/// Collect per-lane drop counters.
+pub(crate) fn dropped_messages(queues: &[Arc<DispatchQueue>]) -> DroppedMessages {
+    let mut dropped = DroppedMessages::default();
+    for queue in queues {
+        let count = queue.dropped();
+        match queue.lane() {
+            Lane::Pools => dropped.pools += count,
+            Lane::PriorityFees => dropped.priority_fees += count,
+            Lane::Blockhash => dropped.blockhash += count,
+            Lane::Other => dropped.other += count,
+        }
+    }
+    dropped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{Blockhash, PoolUpdate, Pubkey, Slot};
+
+    fn update(pool: u8, slot: u64) -> DecodedMessage {
+        DecodedMessage::PoolUpdate(PoolUpdate {
+            pool_address: Pubkey::new([pool; 32]),
+            slot: Slot(slot),
+            ..PoolUpdate::default()
+        })
+    }
+
+    fn slot_of((msg, _): (DecodedMessage, Span)) -> u64 {
+        match msg {
+            DecodedMessage::PoolUpdate(update) => update.slot.get(),
+            DecodedMessage::Blockhash(bh) => bh.slot.get(),
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    fn blockhash(slot: u64) -> DecodedMessage {
+        DecodedMessage::Blockhash(Blockhash {
+            slot: Slot(slot),
+            ..Blockhash::default()
+        })
+    }
+
+    #[tokio::test]
+    async fn test_drop_policies() {
+        let config = DispatchConfig::new(2, DropPolicy::DropNewest)
+            .with_channel(Channel::Blockhash, DropPolicy::DropOldest);
+
+        let newest = DispatchQueue::new(Lane::Other, &config);
+        for slot in 1..=3 {
+            newest.push(blockhash(slot), Span::none()).await;
+        }
+        assert_eq!(newest.dropped(), 1);
+        assert_eq!(slot_of(newest.pop().unwrap()), 1);
+
+        let oldest = DispatchQueue::new(Lane::Blockhash, &config);
+        for slot in 1..=3 {
+            oldest.push(blockhash(slot), Span::none()).await;
+        }
+        assert_eq!(oldest.dropped(), 1);
+        assert_eq!(slot_of(oldest.pop().unwrap()), 2);
+        assert_eq!(slot_of(oldest.pop().unwrap()), 3);
+    }
+
+    #[tokio::test]
+    async fn test_coalesce_by_pool() {
+        let config = DispatchConfig::new(2, DropPolicy::CoalesceByPool);
+        let queue = DispatchQueue::new(Lane::Pools, &config);
+        queue.push(update(1, 1), Span::none()).await;
+        queue.push(update(2, 1), Span::none()).await;
+        queue.push(update(1, 2), Span::none()).await;
+        assert_eq!(queue.dropped(), 1);
+        queue.push(update(3, 1), Span::none()).await;
+        assert_eq!(queue.dropped(), 2);
+        assert_eq!(slot_of(queue.pop().unwrap()), 1);
+        assert_eq!(slot_of(queue.pop().unwrap()), 1);
+        assert!(queue.items.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_block_waits_for_space() {
+        let queue = DispatchQueue::new(Lane::Pools, &DispatchConfig::new(1, DropPolicy::Block));
+        queue.push(update(1, 1), Span::none()).await;
+
+        let consumer = queue.clone();
+        let handle = std::thread::spawn(move || {
+            std::thread::sleep(std::time::Duration::from_millis(20));
+            consumer.pop().map(slot_of)
+        });
+        queue.push(update(1, 2), Span::none()).await;
+        assert_eq!(handle.join().unwrap(), Some(1));
+        assert_eq!(queue.dropped(), 0);
+        assert_eq!(slot_of(queue.pop().unwrap()), 2);
+
+        queue.close();
+        assert!(queue.pop().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_client_dispatches_on_queue_thread() {
+        use crate::replay::FrameKind;
+        use crate::ws::{Config, K256WebSocketClient};
+
+        let client = K256WebSocketClient::new(Config {
+            dispatch: Some(DispatchConfig::default()),
+            ..Config::default()
+        });
+        let (tx, rx) = std::sync::mpsc::channel();
+        let tx = Mutex::new(tx);
+        let _heartbeats = client.on_heartbeat(move |_| {
+            let name = std::thread::current().name().map(String::from);
+            tx.lock().unwrap().send(name).unwrap();
+        });
+
+        let text = br#"{"type":"heartbeat","timestamp_ms":1}"#;
+        client.handle_frame(FrameKind::Text, text).await;
+        let thread = rx.recv_timeout(std::time::Duration::from_secs(1)).unwrap();
+        assert_eq!(thread.as_deref(), Some("k256-dispatch-other"));
+        assert_eq!(client.dropped_messages().total(), 0);
+    }
+}
@@ -0,0 +1,340 @@
+//! Binary message encoders for K256 WebSocket messages.
+//!
+//! [`encode_message`] builds client → server requests. The `encode_*`
+//! server → client encoders are the inverse of
+//! [`decode_message`](crate::ws::decode_message), for building fixtures and
+//! mock servers from typed messages.
+
+use thiserror::Error;
+
+use crate::types::{AccountFee, Blockhash, FeeMarket, MessageType, OrderLevel, PoolUpdate};
+use crate::utils::decode_pubkey;
+use crate::ws::message::{SubscribePriceRequest, SubscribeQuoteRequest, SubscribeRequest};
+
+/// Encoder error types.
+#[derive(Debug, Error)]
+pub enum EncodeError {
+    /// Blockhash is not a base58-encoded 32-byte hash
+    #[error("Invalid blockhash: {0}")]
+    InvalidBlockhash(String),
+}
+
+/// Client → server message.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ClientMessage {
+    /// Subscribe to channels (JSON payload)
+    Subscribe(SubscribeRequest),
+    /// Unsubscribe from all channels
+    Unsubscribe,
+    /// Subscribe to a streaming quote (JSON payload)
+    SubscribeQuote(SubscribeQuoteRequest),
+    /// Unsubscribe from a streaming quote
+    UnsubscribeQuote {
+        /// Topic ID from the quote subscription confirmation
+        topic_id: String,
+    },
+    /// Keepalive ping
+    Ping,
+    /// Subscribe to the price feed (JSON payload)
+    SubscribePrice(SubscribePriceRequest),
+    /// Unsubscribe from the price feed
+    UnsubscribePrice,
+}
+
+impl ClientMessage {
+    /// Wire message type of this message.
+    pub fn message_type(&self) -> MessageType {
+        match self {
+            Self::Subscribe(_) => MessageType::Subscribe,
+            Self::Unsubscribe => MessageType::Unsubscribe,
+            Self::SubscribeQuote(_) => MessageType::SubscribeQuote,
+            Self::UnsubscribeQuote { .. } => MessageType::UnsubscribeQuote,
+            Self::Ping => MessageType::Ping,
+            Self::SubscribePrice(_) => MessageType::SubscribePrice,
+            Self::UnsubscribePrice => MessageType::UnsubscribePrice,
+        }
+    }
+}
+
+/// Encode a client message into a binary WebSocket frame.
+///
+/// Wire format: `[1 byte MessageType][N bytes payload]`. Subscription
+/// payloads are JSON, the quote topic ID is UTF-8, and `Ping`,
+/// `Unsubscribe` and `UnsubscribePrice` carry no payload.
+pub fn encode_message(msg: &ClientMessage) -> Vec<u8> {
+    let mut frame = vec![msg.message_type() as u8];
+    match msg {
+        ClientMessage::Subscribe(request) => write_json(&mut frame, request),
+        ClientMessage::SubscribeQuote(request) => write_json(&mut frame, request),
+        ClientMessage::SubscribePrice(request) => write_json(&mut frame, request),
+        ClientMessage::UnsubscribeQuote { topic_id } => frame.extend_from_slice(topic_id.as_bytes()),
+        ClientMessage::Unsubscribe | ClientMessage::Ping | ClientMessage::UnsubscribePrice => {}
+    }
+    frame
+}
+
+/// Encode a pool update into a binary `PoolUpdate` (`0x01`) frame.
+pub fn encode_pool_update(update: &PoolUpdate) -> Vec<u8> {
+    let mut frame = vec![MessageType::PoolUpdate as u8];
+    frame.extend_from_slice(&(update.serialized_state.len() as u64).to_le_bytes());
+    frame.extend_from_slice(&update.serialized_state);
+    frame.extend_from_slice(&update.sequence.to_le_bytes());
+    frame.extend_from_slice(&update.slot.get().to_le_bytes());
+    frame.extend_from_slice(&update.write_version.to_le_bytes());
+    let protocol_name = update.protocol_name.as_str();
+    frame.extend_from_slice(&(protocol_name.len() as u64).to_le_bytes());
+    frame.extend_from_slice(protocol_name.as_bytes());
+    frame.extend_from_slice(update.pool_address.as_bytes());
+    frame.extend_from_slice(&(update.token_mints.len() as u64).to_le_bytes());
+    for mint in &update.token_mints {
+        frame.extend_from_slice(mint.as_bytes());
+    }
+    frame.extend_from_slice(&(update.token_balances.len() as u64).to_le_bytes());
+    for balance in &update.token_balances {
+        frame.extend_from_slice(&balance.to_le_bytes());
+    }
+    frame.extend_from_slice(&(update.token_decimals.len() as u64).to_le_bytes());
+    for decimals in &update.token_decimals {
+        frame.extend_from_slice(&decimals.to_le_bytes());
+    }
+    write_order_level(&mut frame, update.best_bid.as_ref());
+    write_order_level(&mut frame, update.best_ask.as_ref());
+    frame
+}
+
+/// Encode a fee market update into a binary `PriorityFees` (`0x05`) frame.
+pub fn encode_fee_market(fees: &FeeMarket) -> Vec<u8> {
+    let mut frame = vec![MessageType::PriorityFees as u8];
+    frame.extend_from_slice(&fees.slot.get().to_le_bytes());
+    frame.extend_from_slice(&fees.timestamp_ms.get().to_le_bytes());
+    frame.extend_from_slice(&fees.recommended.to_le_bytes());
+    frame.push(fees.state as u8);
+    frame.push(fees.is_stale as u8);
+    frame.extend_from_slice(&fees.block_utilization_pct.to_le_bytes());
+    frame.extend_from_slice(&fees.blocks_in_window.to_le_bytes());
+    frame.extend_from_slice(&(fees.accounts.len() as u64).to_le_bytes());
+    for account in &fees.accounts {
+        write_account_fee(&mut frame, account);
+    }
+    frame
+}
+
+/// Encode a blockhash into a binary `Blockhash` (`0x06`) frame.
+///
+/// Fails unless `blockhash.blockhash` is a base58-encoded 32-byte hash.
+pub fn encode_blockhash(blockhash: &Blockhash) -> Result<Vec<u8>, EncodeError> {
+    let hash = decode_pubkey(&blockhash.blockhash)
+        .map_err(|_| EncodeError::InvalidBlockhash(blockhash.blockhash.clone()))?;
+
+    let mut frame = vec![MessageType::Blockhash as u8];
+    frame.extend_from_slice(&blockhash.slot.get().to_le_bytes());
+    frame.extend_from_slice(&blockhash.timestamp_ms.get().to_le_bytes());
+    frame.extend_from_slice(&hash);
+    frame.extend_from_slice(&blockhash.block_height.to_le_bytes());
+    frame.extend_from_slice(&blockhash.last_valid_block_height.to_le_bytes());
+    frame.push(blockhash.is_stale as u8);
+    Ok(frame)
+}
+
+fn write_order_level(out: &mut Vec<u8>, level: Option<&OrderLevel>) {
+    match level {
+        Some(level) => {
+            out.push(1);
+            out.extend_from_slice(&level.price.to_le_bytes());
+            out.extend_from_slice(&level.size.to_le_bytes());
+        }
+        None => out.push(0),
+    }
+}
+
+fn write_account_fee(out: &mut Vec<u8>, account: &AccountFee) {
+    out.extend_from_slice(account.pubkey.as_bytes());
+    out.extend_from_slice(&account.total_txs.to_le_bytes());
+    out.extend_from_slice(&account.active_slots.to_le_bytes());
+    out.extend_from_slice(&account.cu_consumed.to_le_bytes());
+    out.extend_from_slice(&account.utilization_pct.to_le_bytes());
+    for value in [account.p25, account.p50, account.p75, account.p90, account.min_nonzero_price] {
+        out.extend_from_slice(&value.to_le_bytes());
+    }
+}
+
+fn write_json<T: serde::Serialize>(frame: &mut Vec<u8>, value: &T) {
+    // Request structs only contain strings, integers and sequences, which always serialize
+    serde_json::to_writer(frame, value).expect("request serialization cannot fail");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{NetworkState, Pubkey, Slot, TimestampMs};
+    use crate::ws::{decode_client_message, decode_message, DecodedMessage};
+
+    fn roundtrip(msg: ClientMessage) {
+        let frame = encode_message(&msg);
+        assert_eq!(frame[0], msg.message_type() as u8);
+        assert_eq!(decode_client_message(&frame).unwrap(), msg);
+    }
+
+    #[test]
+    fn test_encode_control_frames() {
+        assert_eq!(encode_message(&ClientMessage::Ping), vec![0x0B]);
+        assert_eq!(encode_message(&ClientMessage::Unsubscribe), vec![0x04]);
+        assert_eq!(encode_message(&ClientMessage::UnsubscribePrice), vec![0x14]);
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        roundtrip(ClientMessage::Ping);
+        roundtrip(ClientMessage::Unsubscribe);
+        roundtrip(ClientMessage::UnsubscribePrice);
+        roundtrip(ClientMessage::Subscribe(SubscribeRequest::default()));
+        roundtrip(ClientMessage::UnsubscribeQuote {
+            topic_id: "topic-1".to_string(),
+        });
+        roundtrip(ClientMessage::SubscribeQuote(SubscribeQuoteRequest {
+            input_mint: "So11111111111111111111111111111111111111112".parse().unwrap(),
+            output_mint: "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v".parse().unwrap(),
+            amount: 1_000_000_000,
+            slippage_bps: 50,
+            refresh_interval_ms: Some(1000),
+        }));
+        roundtrip(ClientMessage::SubscribePrice(SubscribePriceRequest {
+            tokens: vec!["So11111111111111111111111111111111111111112".parse().unwrap()],
+            threshold_bps: None,
+        }));
+    }
+
+    #[test]
+    fn test_subscribe_price_payload_is_camel_case() {
+        let frame = encode_message(&ClientMessage::SubscribePrice(SubscribePriceRequest {
+            tokens: vec![Pubkey::new([1; 32])],
+            threshold_bps: Some(10),
+        }));
+        let expected = format!(r#"{{"tokens":["{}"],"thresholdBps":10}}"#, Pubkey::new([1; 32]));
+        assert_eq!(&frame[1..], expected.as_bytes());
+    }
+
+    /// Deterministic xorshift generator for property tests.
+    struct Rng(u64);
+
+    impl Rng {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+
+        fn below(&mut self, n: u64) -> usize {
+            (self.next() % n) as usize
+        }
+
+        fn bytes(&mut self, len: usize) -> Vec<u8> {
+            (0..len).map(|_| self.next() as u8).collect()
+        }
+
+        fn pubkey(&mut self) -> Pubkey {
+            Pubkey::new(self.bytes(32).try_into().unwrap())
+        }
+
+        fn order_level(&mut self) -> Option<OrderLevel> {
+            (self.next() % 2 == 0).then(|| OrderLevel {
+                price: self.next(),
+                size: self.next(),
+            })
+        }
+
+        fn pool_update(&mut self) -> PoolUpdate {
+            let mints = self.below(4);
+            let state_len = self.below(128);
+            PoolUpdate {
+                sequence: self.next(),
+                slot: Slot(self.next()),
+                write_version: self.next(),
+                protocol_name: (0..self.below(24))
+                    .map(|_| (b'a' + self.below(26) as u8) as char)
+                    .collect::<String>()
+                    .into(),
+                pool_address: self.pubkey(),
+                token_mints: (0..mints).map(|_| self.pubkey()).collect(),
+                token_balances: (0..mints).map(|_| self.next()).collect(),
+                token_decimals: (0..mints).map(|_| self.next() as i32).collect(),
+                best_bid: self.order_level(),
+                best_ask: self.order_level(),
+                serialized_state: self.bytes(state_len),
+            }
+        }
+
+        fn fee_market(&mut self) -> FeeMarket {
+            FeeMarket {
+                slot: Slot(self.next()),
+                timestamp_ms: TimestampMs(self.next()),
+                recommended: self.next(),
+                state: NetworkState::try_from(self.below(4) as u8).unwrap(),
+                is_stale: self.next() % 2 == 0,
+                block_utilization_pct: self.below(10_000) as f32 / 100.0,
+                blocks_in_window: self.next() as u32,
+                accounts: (0..self.below(5))
+                    .map(|_| AccountFee {
+                        pubkey: self.pubkey(),
+                        total_txs: self.next() as u32,
+                        active_slots: self.next() as u32,
+                        cu_consumed: self.next(),
+                        utilization_pct: self.below(10_000) as f32 / 100.0,
+                        p25: self.next(),
+                        p50: self.next(),
+                        p75: self.next(),
+                        p90: self.next(),
+                        min_nonzero_price: self.next(),
+                    })
+                    .collect(),
+            }
+        }
+
+        fn blockhash(&mut self) -> Blockhash {
+            Blockhash {
+                slot: Slot(self.next()),
+                timestamp_ms: TimestampMs(self.next()),
+                blockhash: self.pubkey().to_string(),
+                block_height: self.next(),
+                last_valid_block_height: self.next(),
+                is_stale: self.next() % 2 == 0,
+            }
+        }
+    }
+
+    fn decode(frame: &[u8]) -> DecodedMessage {
+        decode_message(frame[0], &frame[1..]).unwrap().unwrap()
+    }
+
+    #[test]
+    fn test_server_frames_roundtrip() {
+        let mut rng = Rng(0x9E37_79B9_7F4A_7C15);
+        for _ in 0..500 {
+            let update = rng.pool_update();
+            let frame = encode_pool_update(&update);
+            assert!(matches!(decode(&frame), DecodedMessage::PoolUpdate(decoded) if decoded == update));
+            // Every byte is consumed: a truncated frame no longer decodes
+            assert!(decode_message(frame[0], &frame[1..frame.len() - 1]).is_err());
+
+            let fees = rng.fee_market();
+            let frame = encode_fee_market(&fees);
+            assert!(matches!(decode(&frame), DecodedMessage::FeeMarket(decoded) if decoded == fees));
+
+            let blockhash = rng.blockhash();
+            let frame = encode_blockhash(&blockhash).unwrap();
+            assert!(matches!(decode(&frame), DecodedMessage::Blockhash(decoded) if decoded == blockhash));
+        }
+    }
+
+    #[test]
+    fn test_encode_blockhash_rejects_invalid_hash() {
+        for hash in ["", "not-base58!", "11111111"] {
+            let blockhash = Blockhash {
+                blockhash: hash.to_string(),
+                ..Blockhash::default()
+            };
+            assert!(matches!(encode_blockhash(&blockhash), Err(EncodeError::InvalidBlockhash(_))));
+        }
+    }
+}
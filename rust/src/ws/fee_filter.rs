@@ -0,0 +1,115 @@
+//! Client-side filtering of fee market updates by writable account.
+//!
+//! With [`SubscribeRequest::fee_accounts`] set, fee market updates are cut
+//! down to the watched accounts and only delivered when one of them changed
+//! since the last delivered update. This also covers servers that ignore the
+//! filter and send every account.
+//!
+//! [`SubscribeRequest::fee_accounts`]: crate::ws::SubscribeRequest::fee_accounts
+
+use std::collections::HashMap;
+
+use crate::types::{AccountFee, FeeMarket, Pubkey};
+
+/// Watched accounts and their last delivered fee data.
+#[derive(Debug, Default)]
+pub(crate) struct FeeAccountFilter {
+    last: HashMap<Pubkey, Option<AccountFee>>,
+}
+
+impl FeeAccountFilter {
+    /// Filter for the given base58 addresses. Invalid addresses are skipped.
+    pub(crate) fn new(accounts: &[String]) -> Self {
+        Self {
+            last: accounts.iter().filter_map(|a| a.parse().ok()).map(|key| (key, None)).collect(),
+        }
+    }
+
+    /// Keep the state of accounts that are still watched after a filter change.
+    pub(crate) fn carry_over(mut self, previous: Option<FeeAccountFilter>) -> Self {
+        if let Some(mut previous) = previous {
+            for (key, last) in &mut self.last {
+                if let Some(prev) = previous.last.remove(key) {
+                    *last = prev;
+                }
+            }
+        }
+        self
+    }
+
+    /// Restrict `fees` to the watched accounts.
+    ///
+    /// Returns `None` when no watched account changed since the last
+    /// delivered update.
+    pub(crate) fn apply(&mut self, mut fees: FeeMarket) -> Option<FeeMarket> {
+        fees.accounts.retain(|account| self.last.contains_key(&account.pubkey));
+        let mut changed = false;
+        for account in &fees.accounts {
+            let last = self.last.get_mut(&account.pubkey).expect("retained accounts are watched");
+            if last.as_ref() != Some(account) {
+                *last = Some(account.clone());
+                changed = true;
+            }
+        }
+        changed.then_some(fees)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{NetworkState, Slot, TimestampMs};
+
+    fn account(key: u8, p75: u64) -> AccountFee {
+        AccountFee {
+            pubkey: Pubkey::new([key; 32]),
+            total_txs: 1,
+            active_slots: 1,
+            cu_consumed: 0,
+            utilization_pct: 0.0,
+            p25: 0,
+            p50: 0,
+            p75,
+            p90: 0,
+            min_nonzero_price: 0,
+        }
+    }
+
+    fn fees(accounts: Vec<AccountFee>) -> FeeMarket {
+        FeeMarket {
+            slot: Slot(1),
+            timestamp_ms: TimestampMs(0),
+            recommended: 0,
+            state: NetworkState::Normal,
+            is_stale: false,
+            block_utilization_pct: 0.0,
+            blocks_in_window: 0,
+            accounts,
+        }
+    }
+
+    #[test]
+    fn test_fires_only_on_watched_changes() {
+        let mut filter = FeeAccountFilter::new(&[Pubkey::new([1; 32]).to_string(), "not a key".to_string()]);
+
+        let delivered = filter.apply(fees(vec![account(1, 100), account(2, 500)])).unwrap();
+        assert_eq!(delivered.accounts, vec![account(1, 100)]);
+
+        // Only an unwatched account changed
+        assert!(filter.apply(fees(vec![account(1, 100), account(2, 900)])).is_none());
+        assert!(filter.apply(fees(vec![account(2, 900)])).is_none());
+
+        assert!(filter.apply(fees(vec![account(1, 150)])).is_some());
+    }
+
+    #[test]
+    fn test_carry_over() {
+        let mut filter = FeeAccountFilter::new(&[Pubkey::new([1; 32]).to_string()]);
+        filter.apply(fees(vec![account(1, 100)]));
+
+        let accounts = [Pubkey::new([1; 32]).to_string(), Pubkey::new([2; 32]).to_string()];
+        let mut filter = FeeAccountFilter::new(&accounts).carry_over(Some(filter));
+        assert!(filter.apply(fees(vec![account(1, 100)])).is_none());
+        assert!(filter.apply(fees(vec![account(2, 1)])).is_some());
+    }
+}
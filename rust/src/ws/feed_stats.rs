@@ -0,0 +1,304 @@
+//! Pool update rates per protocol and pool over a sliding window.
+//!
+//! [`FeedStats`] counts pool updates into one-second buckets and reports,
+//! for the last `window`, updates per second overall, per protocol and per
+//! pool, plus the distribution of batch sizes. Snapshots serialize to JSON
+//! for export. Pool snapshots are state dumps rather than live traffic and
+//! are not counted.
+//!
+//! With [`Config::feed_stats_window`] set, the client keeps one for every
+//! decoded message (see [`K256WebSocketClient::feed_stats`]).
+//!
+//! [`Config::feed_stats_window`]: crate::ws::Config::feed_stats_window
+//! [`K256WebSocketClient::feed_stats`]: crate::ws::K256WebSocketClient::feed_stats
+
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+use crate::types::{PoolUpdate, Protocol, Pubkey};
+use crate::ws::message::DecodedMessage;
+
+/// Update count and rate of one protocol or pool.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct FeedRate {
+    /// Updates in the window
+    pub updates: u64,
+    /// Updates per second over the window
+    pub per_sec: f64,
+}
+
+/// Distribution of pool update batch sizes; single updates count as
+/// batches of one.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct BatchSizeStats {
+    /// Batches in the window
+    pub batches: u64,
+    /// Mean updates per batch
+    pub mean: f64,
+    /// Smallest batch
+    pub min: u32,
+    /// Median batch size
+    pub p50: u32,
+    /// 95th percentile batch size
+    pub p95: u32,
+    /// 99th percentile batch size
+    pub p99: u32,
+    /// Largest batch
+    pub max: u32,
+}
+
+/// Feed statistics over the window ending at the snapshot.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FeedStatsSnapshot {
+    /// Seconds covered, shorter than the configured window until that much
+    /// time has passed
+    pub window_secs: f64,
+    /// All pool updates
+    pub total: FeedRate,
+    /// Rates by protocol name
+    pub protocols: BTreeMap<String, FeedRate>,
+    /// Rates by pool address
+    pub pools: BTreeMap<Pubkey, FeedRate>,
+    /// Batch sizes, `None` without any batch in the window
+    pub batch_sizes: Option<BatchSizeStats>,
+}
+
+impl FeedStatsSnapshot {
+    /// The `n` busiest pools, busiest first.
+    pub fn top_pools(&self, n: usize) -> Vec<(Pubkey, FeedRate)> {
+        let mut pools: Vec<(Pubkey, FeedRate)> = self.pools.iter().map(|(pool, rate)| (*pool, *rate)).collect();
+        pools.sort_by(|a, b| b.1.updates.cmp(&a.1.updates).then(a.0.cmp(&b.0)));
+        pools.truncate(n);
+        pools
+    }
+}
+
+#[derive(Debug, Default)]
+struct Bucket {
+    /// Whole seconds since the aggregator was created
+    second: u64,
+    updates: u64,
+    protocols: HashMap<Protocol, u64>,
+    pools: HashMap<Pubkey, u64>,
+    batch_sizes: Vec<u32>,
+}
+
+/// Sliding-window pool update statistics.
+///
+/// ```rust
+/// use std::time::Duration;
+/// use k256_sdk::ws::FeedStats;
+/// use k256_sdk::{DecodedMessage, PoolUpdate};
+///
+/// let mut stats = FeedStats::new(Duration::from_secs(60));
+/// stats.record(&DecodedMessage::PoolUpdateBatch(vec![PoolUpdate::default(); 3]));
+///
+/// let snapshot = stats.snapshot();
+/// assert_eq!(snapshot.total.updates, 3);
+/// assert_eq!(snapshot.batch_sizes.unwrap().max, 3);
+/// println!("{}", serde_json::to_string(&snapshot).unwrap());
+/// ```
+#[derive(Debug)]
+pub struct FeedStats {
+    window_secs: u64,
+    origin: Instant,
+    buckets: VecDeque<Bucket>,
+    /// When each protocol last updated, regardless of the window
+    last_seen: HashMap<Protocol, Instant>,
+}
+
+impl FeedStats {
+    /// Statistics over the last `window`, rounded up to whole seconds.
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window_secs: window.as_secs_f64().ceil().max(1.0) as u64,
+            origin: Instant::now(),
+            buckets: VecDeque::new(),
+            last_seen: HashMap::new(),
+        }
+    }
+
+    /// Count the pool updates in `msg`, received now.
+    pub fn record(&mut self, msg: &DecodedMessage) {
+        self.record_at(msg, Instant::now());
+    }
+
+    /// Count the pool updates in `msg`, received at `at`.
+    ///
+    /// Other message types and pool snapshots are ignored.
+    pub fn record_at(&mut self, msg: &DecodedMessage, at: Instant) {
+        let updates = match msg {
+            DecodedMessage::PoolUpdate(update) => std::slice::from_ref(update),
+            DecodedMessage::PoolUpdateBatch(updates) if !updates.is_empty() => updates.as_slice(),
+            _ => return,
+        };
+        self.record_batch_at(updates, at);
+    }
+
+    /// Count a batch of pool updates received at `at`.
+    pub fn record_batch_at(&mut self, updates: &[PoolUpdate], at: Instant) {
+        let second = at.saturating_duration_since(self.origin).as_secs();
+        self.evict(second);
+        if self.buckets.back().map_or(true, |bucket| bucket.second < second) {
+            self.buckets.push_back(Bucket {
+                second,
+                ..Bucket::default()
+            });
+        }
+        let bucket = self.buckets.back_mut().expect("bucket was just ensured");
+        bucket.updates += updates.len() as u64;
+        bucket.batch_sizes.push(u32::try_from(updates.len()).unwrap_or(u32::MAX));
+        for update in updates {
+            *bucket.protocols.entry(update.protocol_name.clone()).or_insert(0) += 1;
+            *bucket.pools.entry(update.pool_address).or_insert(0) += 1;
+            let last_seen = self.last_seen.entry(update.protocol_name.clone()).or_insert(at);
+            *last_seen = (*last_seen).max(at);
+        }
+    }
+
+    /// Statistics for the window ending now.
+    pub fn snapshot(&mut self) -> FeedStatsSnapshot {
+        self.snapshot_at(Instant::now())
+    }
+
+    /// Statistics for the window ending at `at`.
+    pub fn snapshot_at(&mut self, at: Instant) -> FeedStatsSnapshot {
+        let elapsed = at.saturating_duration_since(self.origin);
+        self.evict(elapsed.as_secs());
+        let window_secs = elapsed.as_secs_f64().clamp(1.0, self.window_secs as f64);
+        let rate = |updates: u64| FeedRate {
+            updates,
+            per_sec: updates as f64 / window_secs,
+        };
+
+        let mut protocols: HashMap<&Protocol, u64> = HashMap::new();
+        let mut pools: HashMap<Pubkey, u64> = HashMap::new();
+        let mut batch_sizes: Vec<u32> = Vec::new();
+        for bucket in &self.buckets {
+            for (protocol, count) in &bucket.protocols {
+                *protocols.entry(protocol).or_insert(0) += count;
+            }
+            for (pool, count) in &bucket.pools {
+                *pools.entry(*pool).or_insert(0) += count;
+            }
+            batch_sizes.extend_from_slice(&bucket.batch_sizes);
+        }
+
+        FeedStatsSnapshot {
+            window_secs,
+            total: rate(self.buckets.iter().map(|bucket| bucket.updates).sum()),
+            protocols: protocols
+                .into_iter()
+                .map(|(protocol, count)| (protocol.as_str().to_string(), rate(count)))
+                .collect(),
+            pools: pools.into_iter().map(|(pool, count)| (pool, rate(count))).collect(),
+            batch_sizes: batch_size_stats(batch_sizes),
+        }
+    }
+
+    /// Protocols that updated before but not within `silence` of `at`,
+    /// with the time since their last update, quietest first.
+    pub fn quiet_protocols(&self, silence: Duration, at: Instant) -> Vec<(Protocol, Duration)> {
+        let mut quiet: Vec<(Protocol, Duration)> = self
+            .last_seen
+            .iter()
+            .map(|(protocol, seen)| (protocol.clone(), at.saturating_duration_since(*seen)))
+            .filter(|(_, age)| *age >= silence)
+            .collect();
+        quiet.sort_by_key(|(_, age)| std::cmp::Reverse(*age));
+        quiet
+    }
+
+    /// Drop buckets that fell out of the window ending in `second`.
+    fn evict(&mut self, second: u64) {
+        while self
+            .buckets
+            .front()
+            .is_some_and(|bucket| bucket.second + self.window_secs <= second)
+        {
+            self.buckets.pop_front();
+        }
+    }
+}
+
+fn batch_size_stats(mut sizes: Vec<u32>) -> Option<BatchSizeStats> {
+    if sizes.is_empty() {
+        return None;
+    }
+    sizes.sort_unstable();
+    // Nearest-rank percentile
+    let percentile = |p: usize| sizes[((sizes.len() * p).div_ceil(100)).saturating_sub(1)];
+    let total: u64 = sizes.iter().map(|&size| u64::from(size)).sum();
+    Some(BatchSizeStats {
+        batches: sizes.len() as u64,
+        mean: total as f64 / sizes.len() as f64,
+        min: sizes[0],
+        p50: percentile(50),
+        p95: percentile(95),
+        p99: percentile(99),
+        max: sizes[sizes.len() - 1],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn update(protocol: Protocol, pool: u8) -> PoolUpdate {
+        PoolUpdate {
+            protocol_name: protocol,
+            pool_address: Pubkey::new([pool; 32]),
+            ..PoolUpdate::default()
+        }
+    }
+
+    #[test]
+    fn test_rates_over_sliding_window() {
+        let mut stats = FeedStats::new(Duration::from_secs(10));
+        let start = stats.origin;
+        for second in 0..20 {
+            let at = start + Duration::from_secs(second);
+            stats.record_at(&DecodedMessage::PoolUpdate(update(Protocol::Whirlpool, 1)), at);
+            let batch = vec![update(Protocol::RaydiumClmm, 2), update(Protocol::RaydiumClmm, 3)];
+            stats.record_at(&DecodedMessage::PoolUpdateBatch(batch), at);
+        }
+        stats.record_at(&DecodedMessage::PoolSnapshot(vec![update(Protocol::Whirlpool, 1); 50]), start);
+
+        let snapshot = stats.snapshot_at(start + Duration::from_millis(19_500));
+        assert_eq!(snapshot.window_secs, 10.0);
+        assert_eq!(snapshot.total, FeedRate { updates: 30, per_sec: 3.0 });
+        assert_eq!(snapshot.protocols["Whirlpool"].per_sec, 1.0);
+        assert_eq!(snapshot.protocols["RaydiumClmm"].updates, 20);
+        assert_eq!(snapshot.pools.len(), 3);
+        assert_eq!(snapshot.top_pools(1)[0].1.updates, 10);
+
+        let batches = snapshot.batch_sizes.unwrap();
+        assert_eq!((batches.batches, batches.min, batches.p50, batches.max), (20, 1, 1, 2));
+        assert_eq!(batches.mean, 1.5);
+
+        let json = serde_json::to_value(&snapshot).unwrap();
+        assert_eq!(json["protocols"]["Whirlpool"]["updates"], 10);
+    }
+
+    #[test]
+    fn test_quiet_protocols() {
+        let mut stats = FeedStats::new(Duration::from_secs(60));
+        let start = stats.origin;
+        stats.record_at(&DecodedMessage::PoolUpdate(update(Protocol::Whirlpool, 1)), start);
+        stats.record_at(
+            &DecodedMessage::PoolUpdate(update(Protocol::RaydiumClmm, 2)),
+            start + Duration::from_secs(25),
+        );
+
+        let quiet = stats.quiet_protocols(Duration::from_secs(10), start + Duration::from_secs(30));
+        assert_eq!(quiet, [(Protocol::Whirlpool, Duration::from_secs(30))]);
+
+        // Short windows report rates over the time actually covered
+        let snapshot = stats.snapshot_at(start + Duration::from_secs(30));
+        assert_eq!(snapshot.window_secs, 30.0);
+        assert!(snapshot.batch_sizes.is_some());
+        assert!(stats.snapshot_at(start + Duration::from_secs(200)).batch_sizes.is_none());
+    }
+}
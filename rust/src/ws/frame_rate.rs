@@ -0,0 +1,73 @@
+//! Cap on data frames accepted per second.
+
+use std::time::{Duration, Instant};
+
+const WINDOW: Duration = Duration::from_secs(1);
+
+/// Outcome of [`FrameRateLimit::admit`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Admission {
+    /// Within the limit
+    Accept,
+    /// Over the limit
+    Drop,
+    /// Over the limit, for the first time in this window
+    FirstDrop,
+}
+
+/// Accepts at most `max` data frames per one-second window.
+#[derive(Debug)]
+pub(crate) struct FrameRateLimit {
+    max: u32,
+    window_start: Instant,
+    frames: u32,
+}
+
+impl FrameRateLimit {
+    pub(crate) fn new(max: u32, now: Instant) -> Self {
+        Self {
+            max,
+            window_start: now,
+            frames: 0,
+        }
+    }
+
+    /// Count a frame received at `now`.
+    pub(crate) fn admit(&mut self, now: Instant) -> Admission {
+        if now.saturating_duration_since(self.window_start) >= WINDOW {
+            self.window_start = now;
+            self.frames = 0;
+        }
+        self.frames = self.frames.saturating_add(1);
+        match self.frames.checked_sub(self.max) {
+            None | Some(0) => Admission::Accept,
+            Some(1) => Admission::FirstDrop,
+            Some(_) => Admission::Drop,
+        }
+    }
+
+    /// Frames per second allowed.
+    pub(crate) fn max(&self) -> u32 {
+        self.max
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_admit_per_window() {
+        let start = Instant::now();
+        let mut limit = FrameRateLimit::new(2, start);
+        assert_eq!(limit.admit(start), Admission::Accept);
+        assert_eq!(limit.admit(start), Admission::Accept);
+        assert_eq!(limit.admit(start + Duration::from_millis(10)), Admission::FirstDrop);
+        assert_eq!(limit.admit(start + Duration::from_millis(20)), Admission::Drop);
+
+        let next = start + WINDOW;
+        assert_eq!(limit.admit(next), Admission::Accept);
+        assert_eq!(limit.admit(next), Admission::Accept);
+        assert_eq!(limit.admit(next), Admission::FirstDrop);
+    }
+}
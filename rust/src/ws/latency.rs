@@ -0,0 +1,108 @@
+//! End-to-end message latency over a sliding window.
+//!
+//! With [`Config::latency_window`] set, every received message carrying a
+//! server `timestamp_ms` (fee market, blockhash, quotes, heartbeats, price
+//! updates) is compared against the local receive time. Latencies include
+//! any clock offset between server and client; negative values (client clock
+//! behind) count as zero.
+//!
+//! [`Config::latency_window`]: crate::ws::Config::latency_window
+
+use std::collections::VecDeque;
+use std::time::Duration;
+
+/// Latency percentiles over the most recent messages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LatencyStats {
+    /// Messages in the window
+    pub samples: usize,
+    /// Latency of the most recent message
+    pub last: Duration,
+    /// Smallest latency in the window
+    pub min: Duration,
+    /// Median latency
+    pub p50: Duration,
+    /// 95th percentile latency
+    pub p95: Duration,
+    /// 99th percentile latency
+    pub p99: Duration,
+    /// Largest latency in the window
+    pub max: Duration,
+}
+
+/// Ring buffer of the most recent latencies.
+#[derive(Debug)]
+#[cfg_attr(target_arch = "wasm32", allow(dead_code))]
+pub(crate) struct LatencyWindow {
+    capacity: usize,
+    samples: VecDeque<Duration>,
+}
+
+#[cfg_attr(target_arch = "wasm32", allow(dead_code))]
+impl LatencyWindow {
+    pub(crate) fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        Self {
+            capacity,
+            samples: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    /// Record a message sent at `sent_ms` and received at `received_ms`
+    /// (both Unix milliseconds).
+    pub(crate) fn record(&mut self, sent_ms: u64, received_ms: u64) -> Duration {
+        let latency = Duration::from_millis(received_ms.saturating_sub(sent_ms));
+        if self.samples.len() == self.capacity {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(latency);
+        latency
+    }
+
+    pub(crate) fn stats(&self) -> Option<LatencyStats> {
+        let last = *self.samples.back()?;
+        let mut sorted: Vec<Duration> = self.samples.iter().copied().collect();
+        sorted.sort_unstable();
+        // Nearest-rank percentile
+        let percentile = |p: usize| sorted[((sorted.len() * p).div_ceil(100)).saturating_sub(1)];
+        Some(LatencyStats {
+            samples: sorted.len(),
+            last,
+            min: sorted[0],
+            p50: percentile(50),
+            p95: percentile(95),
+            p99: percentile(99),
+            max: sorted[sorted.len() - 1],
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_percentiles_over_window() {
+        let mut window = LatencyWindow::new(100);
+        assert!(window.stats().is_none());
+        for ms in 1..=200 {
+            window.record(1_000, 1_000 + ms);
+        }
+
+        let stats = window.stats().unwrap();
+        assert_eq!(stats.samples, 100);
+        assert_eq!(stats.min, Duration::from_millis(101));
+        assert_eq!(stats.p50, Duration::from_millis(150));
+        assert_eq!(stats.p95, Duration::from_millis(195));
+        assert_eq!(stats.p99, Duration::from_millis(199));
+        assert_eq!(stats.max, Duration::from_millis(200));
+        assert_eq!(stats.last, Duration::from_millis(200));
+    }
+
+    #[test]
+    fn test_clock_skew_counts_as_zero() {
+        let mut window = LatencyWindow::new(1);
+        assert_eq!(window.record(2_000, 1_000), Duration::ZERO);
+        assert_eq!(window.stats().unwrap().p99, Duration::ZERO);
+    }
+}
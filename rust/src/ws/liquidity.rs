@@ -0,0 +1,156 @@
+//! Client-side minimum liquidity filter for pool updates.
+//!
+//! With [`SubscribeRequest::min_liquidity_usd`] set, gateways that report the
+//! [`FEATURE_MIN_LIQUIDITY`] feature filter pools themselves. For other
+//! gateways the client drops pool updates whose USD value is below the floor,
+//! valuing balances with the latest price feed prices. Mints without a feed
+//! price are valued through the pool's own spot price against a priced mint;
+//! pools that cannot be valued at all are delivered.
+//!
+//! [`SubscribeRequest::min_liquidity_usd`]: crate::ws::SubscribeRequest::min_liquidity_usd
+
+use std::collections::HashMap;
+
+use crate::types::{PoolUpdate, PriceEntry, Pubkey};
+use crate::ws::message::DecodedMessage;
+
+/// Server feature name of server-side `min_liquidity_usd` filtering.
+pub const FEATURE_MIN_LIQUIDITY: &str = "min_liquidity_usd";
+
+/// Liquidity floor and the USD prices to value pools with.
+#[derive(Debug, Default)]
+pub(crate) struct LiquidityFilter {
+    min_usd: f64,
+    prices: HashMap<Pubkey, f64>,
+}
+
+impl LiquidityFilter {
+    /// Filter dropping pools worth less than `min_usd`.
+    pub(crate) fn new(min_usd: u64) -> Self {
+        Self {
+            min_usd: min_usd as f64,
+            prices: HashMap::new(),
+        }
+    }
+
+    /// Keep the prices learned before a filter change.
+    pub(crate) fn carry_over(mut self, previous: Option<LiquidityFilter>) -> Self {
+        if let Some(previous) = previous {
+            self.prices = previous.prices;
+        }
+        self
+    }
+
+    /// Learn prices from price messages and drop pools below the floor.
+    ///
+    /// # Returns
+    ///
+    /// The message with only the pools that pass, or `None` if a pool update
+    /// or batch has none left. Snapshots are kept even when emptied.
+    pub(crate) fn apply(&mut self, decoded: DecodedMessage) -> Option<DecodedMessage> {
+        match decoded {
+            DecodedMessage::PriceUpdate(ref entry) => self.observe_prices(std::slice::from_ref(entry)),
+            DecodedMessage::PriceBatch(ref entries) | DecodedMessage::PriceSnapshot(ref entries) => {
+                self.observe_prices(entries)
+            }
+            DecodedMessage::PoolUpdate(update) => {
+                return self.allows(&update).then_some(DecodedMessage::PoolUpdate(update));
+            }
+            DecodedMessage::PoolUpdateBatch(mut updates) => {
+                updates.retain(|update| self.allows(update));
+                return (!updates.is_empty()).then_some(DecodedMessage::PoolUpdateBatch(updates));
+            }
+            DecodedMessage::PoolSnapshot(mut updates) => {
+                updates.retain(|update| self.allows(update));
+                return Some(DecodedMessage::PoolSnapshot(updates));
+            }
+            _ => {}
+        }
+        Some(decoded)
+    }
+
+    /// Remember the USD prices of price feed entries.
+    fn observe_prices(&mut self, entries: &[PriceEntry]) {
+        for entry in entries {
+            if entry.usd_price > 0.0 {
+                self.prices.insert(entry.mint, entry.usd_price);
+            }
+        }
+    }
+
+    /// Pool value in USD, if any of its mints has a known price.
+    fn liquidity_usd(&self, update: &PoolUpdate) -> Option<f64> {
+        let (quote, quote_usd) = update
+            .token_mints
+            .iter()
+            .find_map(|mint| Some((mint, *self.prices.get(mint)?)))?;
+        let tvl = update.tvl_in(quote, |mint| Some(self.prices.get(mint)? / quote_usd))?;
+        Some(tvl * quote_usd)
+    }
+
+    /// Whether `update` is at or above the floor, or cannot be valued.
+    fn allows(&self, update: &PoolUpdate) -> bool {
+        self.liquidity_usd(update).map_or(true, |usd| usd >= self.min_usd)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{Protocol, Slot, TimestampMs};
+
+    fn price(mint: u8, usd_price: f64) -> PriceEntry {
+        PriceEntry {
+            mint: Pubkey::new([mint; 32]),
+            usd_price,
+            slot: Slot(1),
+            timestamp_ms: TimestampMs(0),
+        }
+    }
+
+    fn pool(balances: [u64; 2]) -> PoolUpdate {
+        PoolUpdate {
+            protocol_name: Protocol::RaydiumAmm,
+            token_mints: vec![Pubkey::new([1; 32]), Pubkey::new([2; 32])],
+            token_balances: balances.to_vec(),
+            token_decimals: vec![9, 6],
+            ..PoolUpdate::default()
+        }
+    }
+
+    #[test]
+    fn test_values_pools_in_usd() {
+        let mut filter = LiquidityFilter::new(1_000);
+        // Unknown prices pass
+        assert!(filter.allows(&pool([1, 1])));
+
+        // 2 SOL at $150 and 300 USDC at $1
+        filter.apply(DecodedMessage::PriceBatch(vec![price(1, 150.0), price(2, 1.0)]));
+        let small = pool([2_000_000_000, 300_000_000]);
+        assert_eq!(filter.liquidity_usd(&small), Some(600.0));
+        assert!(!filter.allows(&small));
+        assert!(filter.allows(&pool([10_000_000_000, 1_500_000_000])));
+
+        // Without a USDC price the pool's own spot price values it
+        let mut filter = LiquidityFilter::new(500).carry_over(Some(filter));
+        filter.prices.remove(&Pubkey::new([2; 32]));
+        assert_eq!(filter.liquidity_usd(&small), Some(600.0));
+        assert!(filter.allows(&small));
+    }
+
+    #[test]
+    fn test_apply_drops_small_pools() {
+        let mut filter = LiquidityFilter::new(1_000);
+        filter.apply(DecodedMessage::PriceUpdate(price(1, 150.0)));
+        let small = pool([2_000_000_000, 300_000_000]);
+        let large = pool([10_000_000_000, 1_500_000_000]);
+        assert!(filter.apply(DecodedMessage::PoolUpdate(small.clone())).is_none());
+        assert!(filter.apply(DecodedMessage::PoolUpdate(large.clone())).is_some());
+
+        let batch = filter.apply(DecodedMessage::PoolUpdateBatch(vec![small.clone(), large]));
+        assert!(matches!(batch, Some(DecodedMessage::PoolUpdateBatch(updates)) if updates.len() == 1));
+        assert!(filter.apply(DecodedMessage::PoolUpdateBatch(vec![small.clone()])).is_none());
+        let snapshot = filter.apply(DecodedMessage::PoolSnapshot(vec![small]));
+        assert!(matches!(snapshot, Some(DecodedMessage::PoolSnapshot(updates)) if updates.is_empty()));
+    }
+}
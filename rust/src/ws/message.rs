@@ -0,0 +1,353 @@
+//! Subscription requests and decoded server messages.
+//!
+//! These are independent of the socket layer, so they are available on
+//! every target, including `wasm32`.
+
+use serde::{Deserialize, Serialize};
+
+use crate::types::{
+    Blockhash, FeeMarket, Heartbeat, PoolUpdate, PriceEntry, Protocol, Pubkey, Quote, ReplayComplete, ServerInfo,
+    TimestampMs, PROTOCOL_VERSION,
+};
+use crate::ws::subscription::Channel;
+
+/// WebSocket subscription request.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SubscribeRequest {
+    /// Request type (always "subscribe")
+    #[serde(rename = "type")]
+    pub request_type: String,
+    /// List of channels to subscribe to
+    pub channels: Vec<String>,
+    /// Message format ("binary" or "json")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub format: Option<String>,
+    /// Optional list of DEX protocols to filter
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub protocols: Option<Vec<Protocol>>,
+    /// Optional list of pool addresses to filter
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pools: Option<Vec<String>>,
+    /// Optional list of token pairs to filter
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token_pairs: Option<Vec<(String, String)>>,
+    /// Request a snapshot of current pool states right after subscribing
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub snapshot: Option<bool>,
+    /// Optional list of writable accounts to filter fee market data to.
+    ///
+    /// Also applied client-side: fee market callbacks only fire when one of
+    /// these accounts changed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fee_accounts: Option<Vec<String>>,
+    /// Only deliver pools worth at least this many US dollars.
+    ///
+    /// Also applied client-side, using price feed prices, when the server
+    /// does not report the `min_liquidity_usd` feature.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_liquidity_usd: Option<u64>,
+    /// Replay pool updates after this sequence before going live.
+    ///
+    /// Set by the client when restoring a subscription with
+    /// `Config::resume`; the server answers with `replay_complete`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resume_from: Option<u64>,
+    /// Binary protocol revision the client decodes
+    /// ([`PROTOCOL_VERSION`] by default)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub protocol_version: Option<u32>,
+    /// Ask the server to length-prefix data message bodies, so fields added
+    /// by newer protocol revisions can be skipped (see
+    /// [`decode_message_compat`](crate::ws::decode_message_compat)).
+    ///
+    /// Set by the client with `Config::forward_compat`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub forward_compat: Option<bool>,
+}
+
+impl Default for SubscribeRequest {
+    fn default() -> Self {
+        Self {
+            request_type: "subscribe".to_string(),
+            channels: vec![
+                "pools".to_string(),
+                "priority_fees".to_string(),
+                "blockhash".to_string(),
+            ],
+            format: None,
+            protocols: None,
+            pools: None,
+            token_pairs: None,
+            snapshot: None,
+            fee_accounts: None,
+            min_liquidity_usd: None,
+            resume_from: None,
+            protocol_version: Some(PROTOCOL_VERSION),
+            forward_compat: None,
+        }
+    }
+}
+
+/// Streaming quote subscription request (`SubscribeQuote`, 0x09).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubscribeQuoteRequest {
+    /// Input token mint address
+    pub input_mint: Pubkey,
+    /// Output token mint address
+    pub output_mint: Pubkey,
+    /// Amount in base units
+    pub amount: u64,
+    /// Slippage tolerance in basis points
+    pub slippage_bps: u32,
+    /// How often to refresh the quote, in milliseconds
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub refresh_interval_ms: Option<u64>,
+}
+
+/// One-off quote request, answered by a single `quote` message.
+///
+/// Sent with [`K256WebSocketClient::request_quote`], which sets the
+/// `requestId` the answer is matched by.
+///
+/// [`K256WebSocketClient::request_quote`]: crate::ws::K256WebSocketClient::request_quote
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuoteRequest {
+    /// Input token mint address
+    pub input_mint: Pubkey,
+    /// Output token mint address
+    pub output_mint: Pubkey,
+    /// Amount in base units
+    pub amount: u64,
+    /// Slippage tolerance in basis points
+    pub slippage_bps: u32,
+    /// "ExactIn" (default) or "ExactOut"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub swap_mode: Option<String>,
+}
+
+/// Price feed subscription request (`SubscribePrice`, 0x10).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubscribePriceRequest {
+    /// Token mint addresses to track
+    pub tokens: Vec<Pubkey>,
+    /// Minimum change in basis points before an update is sent (server default 10)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub threshold_bps: Option<u32>,
+}
+
+/// Decoded WebSocket message.
+///
+/// Serializes as `{"type": ..., "data": ...}`, with the snake_case message
+/// kind as `type` (`"pool_update"`, `"fee_market"`, ...), so decoded
+/// streams can be archived as NDJSON and read back:
+///
+/// ```rust
+/// use k256_sdk::{DecodedMessage, Heartbeat, TimestampMs};
+///
+/// let msg = DecodedMessage::Heartbeat(Heartbeat { timestamp_ms: TimestampMs(1), ..Heartbeat::default() });
+/// let line = serde_json::to_string(&msg).unwrap();
+/// assert!(line.starts_with(r#"{"type":"heartbeat","data":{"#));
+/// let read: DecodedMessage = serde_json::from_str(&line).unwrap();
+/// assert!(matches!(read, DecodedMessage::Heartbeat(hb) if hb.timestamp_ms == 1));
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", content = "data", rename_all = "snake_case")]
+pub enum DecodedMessage {
+    /// Pool update
+    PoolUpdate(PoolUpdate),
+    /// Batch of pool updates
+    PoolUpdateBatch(Vec<PoolUpdate>),
+    /// Current state of every matching pool, sent once after subscribing
+    /// with `snapshot` (and again after each reconnect)
+    PoolSnapshot(Vec<PoolUpdate>),
+    /// Fee market update (per-writable-account)
+    FeeMarket(FeeMarket),
+    /// Blockhash
+    Blockhash(Blockhash),
+    /// Quote
+    Quote(Quote),
+    /// Heartbeat
+    Heartbeat(Heartbeat),
+    /// Single price update
+    PriceUpdate(PriceEntry),
+    /// Batch of price updates
+    PriceBatch(Vec<PriceEntry>),
+    /// Full price snapshot
+    PriceSnapshot(Vec<PriceEntry>),
+    /// Error message
+    Error(String),
+    /// Pong response to a keepalive ping
+    Pong {
+        /// Server timestamp in milliseconds, if included
+        timestamp_ms: Option<TimestampMs>,
+    },
+    /// Subscription confirmed
+    Subscribed {
+        /// Confirmed channels
+        channels: Vec<String>,
+        /// Protocol revision and version reported by the server
+        #[serde(default)]
+        server: ServerInfo,
+    },
+    /// Missed pool updates were replayed after a resumed subscription
+    ReplayComplete(ReplayComplete),
+}
+
+impl DecodedMessage {
+    /// Server send time in Unix milliseconds, for message types that carry one.
+    ///
+    /// Batches report their newest entry. Snapshots and pool updates are not
+    /// timestamped.
+    pub fn timestamp_ms(&self) -> Option<TimestampMs> {
+        match self {
+            Self::FeeMarket(fees) => Some(fees.timestamp_ms),
+            Self::Blockhash(bh) => Some(bh.timestamp_ms),
+            Self::Quote(quote) => Some(quote.timestamp_ms),
+            Self::Heartbeat(hb) => Some(hb.timestamp_ms),
+            Self::PriceUpdate(entry) => Some(entry.timestamp_ms),
+            Self::PriceBatch(entries) => entries.iter().map(|e| e.timestamp_ms).max(),
+            _ => None,
+        }
+    }
+
+    /// Subscription channel carrying this message, `None` for messages
+    /// outside the channels (quotes, prices, control messages).
+    pub fn channel(&self) -> Option<Channel> {
+        match self {
+            Self::PoolUpdate(_) | Self::PoolUpdateBatch(_) | Self::PoolSnapshot(_) => Some(Channel::Pools),
+            Self::FeeMarket(_) => Some(Channel::PriorityFees),
+            Self::Blockhash(_) => Some(Channel::Blockhash),
+            _ => None,
+        }
+    }
+
+    /// Short snake_case name of the message kind, used as a metrics label.
+    #[cfg_attr(target_arch = "wasm32", allow(dead_code))]
+    pub(crate) fn kind(&self) -> &'static str {
+        match self {
+            Self::PoolUpdate(_) => "pool_update",
+            Self::PoolUpdateBatch(_) => "pool_update_batch",
+            Self::PoolSnapshot(_) => "pool_snapshot",
+            Self::FeeMarket(_) => "fee_market",
+            Self::Blockhash(_) => "blockhash",
+            Self::Quote(_) => "quote",
+            Self::Heartbeat(_) => "heartbeat",
+            Self::PriceUpdate(_) => "price_update",
+            Self::PriceBatch(_) => "price_batch",
+            Self::PriceSnapshot(_) => "price_snapshot",
+            Self::Error(_) => "error",
+            Self::Pong { .. } => "pong",
+            Self::Subscribed { .. } => "subscribed",
+            Self::ReplayComplete(_) => "replay_complete",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{AccountFee, NetworkState, OrderLevel, Pubkey, ReplayComplete, RoutePlanStep, Slot};
+
+    fn samples() -> Vec<DecodedMessage> {
+        let update = PoolUpdate {
+            sequence: 3,
+            slot: Slot(7),
+            write_version: 1,
+            protocol_name: "Whirlpool".into(),
+            pool_address: Pubkey::new([1; 32]),
+            token_mints: vec![Pubkey::new([2; 32])],
+            token_balances: vec![100],
+            token_decimals: vec![6],
+            best_bid: Some(OrderLevel { price: 9, size: 1 }),
+            best_ask: None,
+            serialized_state: vec![0xAB, 0xCD],
+        };
+        let fees = FeeMarket {
+            slot: Slot(7),
+            timestamp_ms: TimestampMs(1),
+            recommended: 1000,
+            state: NetworkState::High,
+            is_stale: false,
+            block_utilization_pct: 50.0,
+            blocks_in_window: 10,
+            accounts: vec![AccountFee {
+                pubkey: Pubkey::new([3; 32]),
+                total_txs: 4,
+                active_slots: 2,
+                cu_consumed: 1_000_000,
+                utilization_pct: 8.5,
+                p25: 1,
+                p50: 2,
+                p75: 3,
+                p90: 4,
+                min_nonzero_price: 1,
+            }],
+        };
+        let price = PriceEntry {
+            mint: Pubkey::new([4; 32]),
+            usd_price: 1.5,
+            slot: Slot(7),
+            timestamp_ms: TimestampMs(2),
+        };
+        let quote = Quote {
+            input_mint: Pubkey::new([2; 32]),
+            output_mint: Pubkey::new([4; 32]),
+            in_amount: 10,
+            out_amount: 9,
+            price_impact_pct: 0.1,
+            slot: Slot(7),
+            timestamp_ms: TimestampMs(3),
+            route_plan: vec![RoutePlanStep {
+                venue: Protocol::Whirlpool,
+                pool: Pubkey::new([1; 32]),
+                in_mint: Pubkey::new([2; 32]),
+                out_mint: Pubkey::new([4; 32]),
+                in_amount: 10,
+                out_amount: 9,
+                fee_amount: 1,
+                fee_mint: None,
+                fee_bps: Some(30),
+                percent: Some(100),
+            }],
+            other_amount_threshold: Some(8),
+            swap_mode: "ExactIn".to_string(),
+            request_id: None,
+        };
+        vec![
+            DecodedMessage::PoolUpdate(update.clone()),
+            DecodedMessage::PoolUpdateBatch(vec![update.clone()]),
+            DecodedMessage::PoolSnapshot(vec![update]),
+            DecodedMessage::FeeMarket(fees),
+            DecodedMessage::Blockhash(Blockhash::default()),
+            DecodedMessage::Quote(quote),
+            DecodedMessage::Heartbeat(Heartbeat::default()),
+            DecodedMessage::PriceUpdate(price.clone()),
+            DecodedMessage::PriceBatch(vec![price.clone()]),
+            DecodedMessage::PriceSnapshot(vec![price]),
+            DecodedMessage::Error("boom".to_string()),
+            DecodedMessage::Pong { timestamp_ms: Some(TimestampMs(5)) },
+            DecodedMessage::Subscribed {
+                channels: vec!["pools".to_string()],
+                server: ServerInfo {
+                    protocol_version: Some(PROTOCOL_VERSION),
+                    server_version: Some("1.4.0".to_string()),
+                    features: vec!["min_liquidity_usd".to_string()],
+                },
+            },
+            DecodedMessage::ReplayComplete(ReplayComplete::default()),
+        ]
+    }
+
+    #[test]
+    fn test_decoded_message_serde_round_trip() {
+        for msg in samples() {
+            let json = serde_json::to_value(&msg).unwrap();
+            assert_eq!(json["type"], msg.kind(), "{}", json);
+            let read: DecodedMessage = serde_json::from_value(json.clone()).unwrap();
+            assert_eq!(serde_json::to_value(&read).unwrap(), json);
+        }
+    }
+}
@@ -0,0 +1,118 @@
+//! Client instrumentation via the [`metrics`](https://docs.rs/metrics) facade.
+//!
+//! With the `metrics` feature enabled the client records the metrics below
+//! to whatever recorder the application installs (e.g.
+//! `metrics-exporter-prometheus`). Without it every call is a no-op.
+//!
+//! | Metric | Kind | Labels |
+//! |--------|------|--------|
+//! | `k256_messages_decoded_total` | counter | `type` |
+//! | `k256_decode_errors_total` | counter | |
+//! | `k256_bytes_received_total` | counter | |
+//! | `k256_reconnects_total` | counter | |
+//! | `k256_messages_dropped_total` | counter | `channel` |
+//! | `k256_callback_duration_seconds` | histogram | `type` |
+//! | `k256_ping_rtt_seconds` | histogram | |
+//! | `k256_message_latency_seconds` | histogram | `type` |
+
+use std::time::Duration;
+
+/// Messages decoded, by message type.
+pub const MESSAGES_DECODED: &str = "k256_messages_decoded_total";
+/// Frames that failed to decode.
+pub const DECODE_ERRORS: &str = "k256_decode_errors_total";
+/// Raw WebSocket payload bytes received.
+pub const BYTES_RECEIVED: &str = "k256_bytes_received_total";
+/// Successful reconnects.
+pub const RECONNECTS: &str = "k256_reconnects_total";
+/// Messages dropped by full dispatch queues, by channel.
+pub const MESSAGES_DROPPED: &str = "k256_messages_dropped_total";
+/// Time spent in user callbacks per message, by message type.
+pub const CALLBACK_DURATION: &str = "k256_callback_duration_seconds";
+/// Keepalive ping round-trip time.
+pub const PING_RTT: &str = "k256_ping_rtt_seconds";
+/// Server-to-client latency of timestamped messages, by message type
+/// (with `Config::latency_window` set).
+pub const MESSAGE_LATENCY: &str = "k256_message_latency_seconds";
+
+#[cfg(feature = "metrics")]
+#[cfg_attr(target_arch = "wasm32", allow(dead_code))]
+mod imp {
+    use super::*;
+    use std::time::Instant;
+
+    pub(crate) fn message_decoded(kind: &'static str) {
+        metrics::counter!(MESSAGES_DECODED, "type" => kind).increment(1);
+    }
+
+    pub(crate) fn decode_error() {
+        metrics::counter!(DECODE_ERRORS).increment(1);
+    }
+
+    pub(crate) fn bytes_received(len: usize) {
+        metrics::counter!(BYTES_RECEIVED).increment(len as u64);
+    }
+
+    pub(crate) fn reconnect() {
+        metrics::counter!(RECONNECTS).increment(1);
+    }
+
+    pub(crate) fn ping_rtt(rtt: Duration) {
+        metrics::histogram!(PING_RTT).record(rtt.as_secs_f64());
+    }
+
+    pub(crate) fn message_dropped(channel: &'static str) {
+        metrics::counter!(MESSAGES_DROPPED, "channel" => channel).increment(1);
+    }
+
+    pub(crate) fn message_latency(kind: &'static str, latency: Duration) {
+        metrics::histogram!(MESSAGE_LATENCY, "type" => kind).record(latency.as_secs_f64());
+    }
+
+    /// Records callback duration when dropped.
+    pub(crate) struct CallbackTimer {
+        kind: &'static str,
+        start: Instant,
+    }
+
+    impl CallbackTimer {
+        pub(crate) fn start(kind: &'static str) -> Self {
+            Self {
+                kind,
+                start: Instant::now(),
+            }
+        }
+    }
+
+    impl Drop for CallbackTimer {
+        fn drop(&mut self) {
+            metrics::histogram!(CALLBACK_DURATION, "type" => self.kind)
+                .record(self.start.elapsed().as_secs_f64());
+        }
+    }
+}
+
+#[cfg(not(feature = "metrics"))]
+#[cfg_attr(target_arch = "wasm32", allow(dead_code))]
+mod imp {
+    use super::*;
+
+    pub(crate) fn message_decoded(_kind: &'static str) {}
+    pub(crate) fn decode_error() {}
+    pub(crate) fn bytes_received(_len: usize) {}
+    pub(crate) fn reconnect() {}
+    pub(crate) fn ping_rtt(_rtt: Duration) {}
+    pub(crate) fn message_dropped(_channel: &'static str) {}
+    pub(crate) fn message_latency(_kind: &'static str, _latency: Duration) {}
+
+    pub(crate) struct CallbackTimer;
+
+    impl CallbackTimer {
+        pub(crate) fn start(_kind: &'static str) -> Self {
+            Self
+        }
+    }
+}
+
+#[cfg_attr(target_arch = "wasm32", allow(unused_imports))]
+pub(crate) use imp::*;
@@ -1,7 +1,102 @@
-//! WebSocket client and binary decoder.
+//! WebSocket client, binary decoder and message encoders.
+//!
+//! Messages, the decoders and the stateful helpers build for every target.
+//! The socket client needs tokio and is native-only; on `wasm32` the `wasm`
+//! feature provides [`K256WasmClient`] on the browser's WebSocket instead.
 
+#[cfg(not(target_arch = "wasm32"))]
+mod activity;
+#[cfg(not(target_arch = "wasm32"))]
+mod blockhash;
+mod borrowed;
+#[cfg(not(target_arch = "wasm32"))]
+mod callbacks;
+mod candles;
+#[cfg(not(target_arch = "wasm32"))]
 mod client;
+#[cfg(not(target_arch = "wasm32"))]
+mod coalesce;
 mod decoder;
+#[cfg(not(target_arch = "wasm32"))]
+mod dispatch;
+mod encoder;
+#[cfg(not(target_arch = "wasm32"))]
+mod fee_filter;
+#[cfg(not(target_arch = "wasm32"))]
+mod feed_stats;
+#[cfg(not(target_arch = "wasm32"))]
+mod frame_rate;
+mod latency;
+#[cfg(not(target_arch = "wasm32"))]
+mod liquidity;
+mod message;
+pub mod metrics;
+#[cfg(not(target_arch = "wasm32"))]
+mod ordering;
+mod pair_book;
+mod pool_delta;
+#[cfg(not(target_arch = "wasm32"))]
+mod predicates;
+#[cfg(not(target_arch = "wasm32"))]
+mod profile;
+mod quota;
+mod scratch;
+mod sequence;
+#[cfg(not(target_arch = "wasm32"))]
+mod quote_request;
+#[cfg(not(target_arch = "wasm32"))]
+mod silence;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod spans;
+#[cfg(not(target_arch = "wasm32"))]
+mod stats;
+mod subscription;
+mod vault_index;
+#[cfg(all(target_arch = "wasm32", feature = "wasm"))]
+mod wasm;
 
-pub use client::{Config, K256WebSocketClient, SubscribeRequest};
-pub use decoder::decode_message;
+#[cfg(not(target_arch = "wasm32"))]
+pub use activity::{ActivityConfig, ActivityRank, ActivityReport, ActivityTracker, PoolActivity, TokenActivity};
+#[cfg(not(target_arch = "wasm32"))]
+pub use blockhash::{BlockhashProvider, RecentBlockhash};
+pub use borrowed::{
+    decode_message_borrowed, decode_message_borrowed_with_limits, DecodedMessageRef, PoolUpdateBatchIter,
+    PoolUpdateBatchRef, PoolUpdateRef,
+};
+#[cfg(not(target_arch = "wasm32"))]
+pub use callbacks::{async_callback, CallbackHandle};
+pub use candles::{Candle, CandleAggregator};
+#[cfg(not(target_arch = "wasm32"))]
+pub use client::{Config, EndpointSelection, K256WebSocketClient};
+pub use decoder::{
+    decode_client_message, decode_json_message, decode_message, decode_message_compat, decode_message_truncated,
+    decode_message_with_limits, CompatMessage, DecodeError, DecodeLimits, LimitPolicy, UnknownFields,
+};
+#[cfg(feature = "parallel")]
+pub use decoder::decode_message_parallel;
+#[cfg(not(target_arch = "wasm32"))]
+pub use dispatch::{DispatchConfig, DropPolicy, DroppedMessages};
+pub use encoder::{encode_blockhash, encode_fee_market, encode_message, encode_pool_update, ClientMessage, EncodeError};
+#[cfg(not(target_arch = "wasm32"))]
+pub use feed_stats::{BatchSizeStats, FeedRate, FeedStats, FeedStatsSnapshot};
+pub use latency::LatencyStats;
+#[cfg(not(target_arch = "wasm32"))]
+pub use liquidity::FEATURE_MIN_LIQUIDITY;
+pub use message::{DecodedMessage, QuoteRequest, SubscribePriceRequest, SubscribeQuoteRequest, SubscribeRequest};
+#[cfg(not(target_arch = "wasm32"))]
+pub use ordering::OrderingStats;
+#[cfg(not(target_arch = "wasm32"))]
+pub use profile::{ConfigError, ProfileFile, SubscriptionProfile};
+pub use quota::QuotaError;
+pub use pair_book::{BestPriceChanged, PairBook, Side, VenuePrice};
+pub use pool_delta::{PoolDelta, PoolDeltaTracker};
+pub use scratch::Decoder;
+pub use sequence::GapInfo;
+#[cfg(not(target_arch = "wasm32"))]
+pub use silence::PoolSilent;
+#[cfg(not(target_arch = "wasm32"))]
+pub use stats::ClientStats;
+pub use subscription::{Channel, SubscribeRequestBuilder};
+pub use vault_index::{VaultIndex, VaultOwner};
+#[cfg(all(target_arch = "wasm32", feature = "wasm"))]
+pub use wasm::{K256WasmClient, WasmConfig, WasmError};
@@ -0,0 +1,166 @@
+//! Per-pool ordered delivery of pool updates.
+//!
+//! Updates for one pool can arrive out of `(slot, write_version)` order,
+//! within a batch or across a reconnect. With [`Config::ordered_delivery`]
+//! set, pool updates are held back for the configured window, then
+//! delivered sorted by `(slot, write_version)` as a single
+//! `PoolUpdateBatch`. An update that is not newer than one already
+//! delivered for its pool is dropped as stale.
+//!
+//! Snapshots are delivered immediately and do not take part in ordering.
+//!
+//! [`Config::ordered_delivery`]: crate::ws::Config::ordered_delivery
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+use crate::types::{PoolUpdate, Pubkey, Slot};
+
+/// Counters of the ordering layer.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct OrderingStats {
+    /// Updates that arrived after a newer update for the same pool and were
+    /// moved ahead of it
+    pub reordered: u64,
+    /// Updates dropped because a newer or identical update for the same
+    /// pool was already delivered
+    pub stale_dropped: u64,
+}
+
+/// Ordering position of an update within its pool.
+fn key(update: &PoolUpdate) -> (Slot, u64) {
+    (update.slot, update.write_version)
+}
+
+#[derive(Default)]
+struct OrderState {
+    /// Held updates, in arrival order
+    pending: Vec<PoolUpdate>,
+    /// Newest delivered position per pool
+    delivered: HashMap<Pubkey, (Slot, u64)>,
+}
+
+/// Pool updates held for ordered delivery.
+#[derive(Default)]
+pub(crate) struct PoolOrderer {
+    state: Mutex<OrderState>,
+    reordered: AtomicU64,
+    stale_dropped: AtomicU64,
+}
+
+impl PoolOrderer {
+    /// Hold an update until the next drain.
+    pub(crate) fn offer(&self, update: PoolUpdate) {
+        self.state.lock().unwrap().pending.push(update);
+    }
+
+    /// Take the held updates, sorted by `(slot, write_version)`, without
+    /// updates older than what was already delivered for their pool.
+    pub(crate) fn drain(&self) -> Vec<PoolUpdate> {
+        let mut state = self.state.lock().unwrap();
+        let mut updates = std::mem::take(&mut state.pending);
+
+        let mut newest: HashMap<Pubkey, (Slot, u64)> = HashMap::new();
+        let mut reordered = 0;
+        for update in &updates {
+            let seen = newest.entry(update.pool_address).or_insert(key(update));
+            if key(update) < *seen {
+                reordered += 1;
+            } else {
+                *seen = key(update);
+            }
+        }
+
+        // Stable, so identical positions keep their arrival order
+        updates.sort_by_key(key);
+        let before = updates.len();
+        updates.retain(|update| match state.delivered.get(&update.pool_address) {
+            Some(&delivered) if key(update) <= delivered => false,
+            _ => {
+                state.delivered.insert(update.pool_address, key(update));
+                true
+            }
+        });
+
+        self.reordered.fetch_add(reordered, Ordering::Relaxed);
+        self.stale_dropped.fetch_add((before - updates.len()) as u64, Ordering::Relaxed);
+        updates
+    }
+
+    pub(crate) fn stats(&self) -> OrderingStats {
+        OrderingStats {
+            reordered: self.reordered.load(Ordering::Relaxed),
+            stale_dropped: self.stale_dropped.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn update(pool: u8, slot: u64, write_version: u64) -> PoolUpdate {
+        PoolUpdate {
+            pool_address: Pubkey::new([pool; 32]),
+            slot: Slot(slot),
+            write_version,
+            ..PoolUpdate::default()
+        }
+    }
+
+    fn positions(updates: &[PoolUpdate]) -> Vec<(u8, u64, u64)> {
+        updates
+            .iter()
+            .map(|u| (u.pool_address.as_bytes()[0], u.slot.get(), u.write_version))
+            .collect()
+    }
+
+    #[test]
+    fn test_orders_per_pool_and_drops_stale() {
+        let orderer = PoolOrderer::default();
+        for u in [update(1, 10, 2), update(2, 9, 0), update(1, 10, 1), update(1, 11, 0)] {
+            orderer.offer(u);
+        }
+        assert_eq!(positions(&orderer.drain()), [(2, 9, 0), (1, 10, 1), (1, 10, 2), (1, 11, 0)]);
+        assert_eq!(orderer.stats().reordered, 1);
+
+        // Regressions and duplicates arriving after delivery are stale
+        for u in [update(1, 10, 5), update(1, 11, 0), update(2, 9, 1), update(1, 12, 0)] {
+            orderer.offer(u);
+        }
+        assert_eq!(positions(&orderer.drain()), [(2, 9, 1), (1, 12, 0)]);
+        assert_eq!(
+            orderer.stats(),
+            OrderingStats {
+                reordered: 1,
+                stale_dropped: 2,
+            }
+        );
+        assert!(orderer.drain().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_client_delivers_in_order() {
+        use crate::replay::FrameKind;
+        use crate::ws::{encode_pool_update, Config, K256WebSocketClient};
+        use std::time::Duration;
+
+        let client = K256WebSocketClient::new(Config {
+            ordered_delivery: Some(Duration::from_millis(20)),
+            ..Config::default()
+        });
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        client.on_pool_update(move |update| tx.send(update.slot).unwrap()).detach();
+
+        for slot in [3, 1, 2, 3] {
+            client.handle_frame(FrameKind::Binary, &encode_pool_update(&update(1, slot, 0))).await;
+        }
+        let mut slots = Vec::new();
+        while slots.len() < 3 {
+            slots.push(tokio::time::timeout(Duration::from_secs(1), rx.recv()).await.unwrap().unwrap());
+        }
+        assert_eq!(slots, [1, 2, 3]);
+        assert_eq!(client.ordering_stats(), OrderingStats { reordered: 2, stale_dropped: 1 });
+    }
+}
@@ -0,0 +1,245 @@
+//! Cross-venue top of book for a token pair.
+//!
+//! [`PairBook`] consumes pool updates for one base/quote pair from every
+//! venue and tracks the best bid (highest price) and best ask (lowest price)
+//! among the pools, reporting a [`BestPriceChanged`] whenever either moves
+//! or switches venue. Prices are spot prices of one whole base token in
+//! whole quote tokens (see [`PoolUpdate::spot_price`]); a best
+//! bid above the best ask is a cross-venue arbitrage.
+//!
+//! Combine it with a `token_pairs` subscription filter so only the pair's
+//! pools are streamed.
+
+use std::collections::HashMap;
+
+use crate::types::{PoolUpdate, Protocol, Pubkey, Slot};
+
+/// Spot price of one venue's pool.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VenuePrice {
+    /// Price of one whole base token in whole quote tokens
+    pub price: f64,
+    /// DEX protocol name of the pool
+    pub protocol_name: Protocol,
+    /// Pool address
+    pub pool_address: Pubkey,
+    /// Slot of the update the price comes from
+    pub slot: Slot,
+}
+
+/// Side of the book.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Side {
+    /// Highest price across venues
+    Bid,
+    /// Lowest price across venues
+    Ask,
+}
+
+/// The best price on one side of the book changed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BestPriceChanged {
+    /// Side that changed
+    pub side: Side,
+    /// Previous best price, if any
+    pub previous: Option<VenuePrice>,
+    /// New best price, `None` once no pool is priced
+    pub current: Option<VenuePrice>,
+}
+
+/// Best bid and ask for a token pair across venues.
+///
+/// ```rust,no_run
+/// use k256_sdk::ws::PairBook;
+/// # fn example(base: k256_sdk::Pubkey, quote: k256_sdk::Pubkey, update: &k256_sdk::PoolUpdate) {
+/// let mut book = PairBook::new(base, quote);
+/// for change in book.update(update) {
+///     if let Some(best) = change.current {
+///         println!("{:?} {} on {} ({})", change.side, best.price, best.protocol_name, best.pool_address);
+///     }
+/// }
+/// if book.spread_bps().is_some_and(|spread| spread < 0.0) {
+///     println!("crossed book");
+/// }
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct PairBook {
+    base: Pubkey,
+    quote: Pubkey,
+    /// Latest price per pool, with the update's (slot, write_version)
+    venues: HashMap<Pubkey, (VenuePrice, (Slot, u64))>,
+    best_bid: Option<VenuePrice>,
+    best_ask: Option<VenuePrice>,
+}
+
+impl PairBook {
+    /// Create an empty book for `base` priced in `quote`.
+    pub fn new(base: Pubkey, quote: Pubkey) -> Self {
+        Self {
+            base,
+            quote,
+            venues: HashMap::new(),
+            best_bid: None,
+            best_ask: None,
+        }
+    }
+
+    /// Whether `update` is for a pool trading this pair.
+    pub fn matches(&self, update: &PoolUpdate) -> bool {
+        update.token_mints.contains(&self.base) && update.token_mints.contains(&self.quote)
+    }
+
+    /// Apply a pool update, returning the sides whose best price changed.
+    ///
+    /// Updates for other pairs, and updates older than the last applied one
+    /// for the same pool, are ignored. A pool with an empty reserve is
+    /// dropped from the book.
+    pub fn update(&mut self, update: &PoolUpdate) -> Vec<BestPriceChanged> {
+        if !self.matches(update) {
+            return Vec::new();
+        }
+        let version = (update.slot, update.write_version);
+        if let Some((_, last)) = self.venues.get(&update.pool_address) {
+            if version < *last {
+                return Vec::new();
+            }
+        }
+
+        match update.spot_price(&self.base, &self.quote).filter(|p| p.is_finite() && *p > 0.0) {
+            Some(price) => {
+                let venue = VenuePrice {
+                    price,
+                    protocol_name: update.protocol_name.clone(),
+                    pool_address: update.pool_address,
+                    slot: update.slot,
+                };
+                self.venues.insert(update.pool_address, (venue, version));
+            }
+            None => {
+                self.venues.remove(&update.pool_address);
+            }
+        }
+        self.refresh()
+    }
+
+    /// Drop a pool from the book, returning the sides whose best price changed.
+    pub fn remove_pool(&mut self, pool_address: &Pubkey) -> Vec<BestPriceChanged> {
+        if self.venues.remove(pool_address).is_none() {
+            return Vec::new();
+        }
+        self.refresh()
+    }
+
+    /// Highest price across venues.
+    pub fn best_bid(&self) -> Option<&VenuePrice> {
+        self.best_bid.as_ref()
+    }
+
+    /// Lowest price across venues.
+    pub fn best_ask(&self) -> Option<&VenuePrice> {
+        self.best_ask.as_ref()
+    }
+
+    /// Best ask minus best bid, in basis points of the best ask.
+    ///
+    /// Negative when the book is crossed, i.e. buying on the ask venue and
+    /// selling on the bid venue is profitable before fees.
+    pub fn spread_bps(&self) -> Option<f64> {
+        let (bid, ask) = (self.best_bid.as_ref()?, self.best_ask.as_ref()?);
+        Some((ask.price - bid.price) / ask.price * 10_000.0)
+    }
+
+    /// Latest price of every pool in the book, in no particular order.
+    pub fn venues(&self) -> impl Iterator<Item = &VenuePrice> {
+        self.venues.values().map(|(venue, _)| venue)
+    }
+
+    /// Recompute both sides, reporting the ones that changed.
+    fn refresh(&mut self) -> Vec<BestPriceChanged> {
+        let best_by = |better: fn(f64, f64) -> bool| {
+            self.venues()
+                .fold(None::<&VenuePrice>, |best, venue| match best {
+                    Some(best) if !better(venue.price, best.price) => Some(best),
+                    _ => Some(venue),
+                })
+                .cloned()
+        };
+        let bid = best_by(|a, b| a > b);
+        let ask = best_by(|a, b| a < b);
+
+        let mut changes = Vec::new();
+        for (side, best, current) in [(Side::Bid, &mut self.best_bid, bid), (Side::Ask, &mut self.best_ask, ask)] {
+            if *best != current {
+                changes.push(BestPriceChanged {
+                    side,
+                    previous: std::mem::replace(best, current.clone()),
+                    current,
+                });
+            }
+        }
+        changes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BASE: Pubkey = Pubkey::new([1; 32]);
+    const QUOTE: Pubkey = Pubkey::new([2; 32]);
+
+    fn update(pool: u8, protocol: &str, base: u64, quote: u64, slot: u64) -> PoolUpdate {
+        PoolUpdate {
+            slot: Slot(slot),
+            protocol_name: protocol.into(),
+            pool_address: Pubkey::new([pool; 32]),
+            token_mints: vec![QUOTE, BASE],
+            token_balances: vec![quote, base],
+            token_decimals: vec![6, 9],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_tracks_best_across_venues() {
+        let mut book = PairBook::new(BASE, QUOTE);
+
+        // 100 SOL against 15_000 USDC: 150 per SOL
+        let changes = book.update(&update(10, "RaydiumAmm", 100_000_000_000, 15_000_000_000, 1));
+        assert_eq!(changes.len(), 2);
+        assert_eq!(book.best_bid().unwrap().price, 150.0);
+        assert_eq!(book.spread_bps(), Some(0.0));
+
+        let changes = book.update(&update(11, "RaydiumCpmm", 100_000_000_000, 15_300_000_000, 1));
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].side, Side::Bid);
+        assert_eq!(changes[0].previous.as_ref().unwrap().protocol_name, "RaydiumAmm");
+        assert_eq!(changes[0].current.as_ref().unwrap().pool_address, Pubkey::new([11; 32]));
+        assert_eq!(book.best_ask().unwrap().protocol_name, "RaydiumAmm");
+        assert!(book.spread_bps().unwrap() < 0.0);
+
+        // Stale update for the CPMM pool is ignored
+        assert!(book.update(&update(11, "RaydiumCpmm", 100_000_000_000, 14_000_000_000, 0)).is_empty());
+
+        let changes = book.remove_pool(&Pubkey::new([11; 32]));
+        assert_eq!(changes.len(), 1);
+        assert_eq!(book.best_bid().unwrap().price, 150.0);
+        assert_eq!(book.venues().count(), 1);
+    }
+
+    #[test]
+    fn test_ignores_other_pairs_and_empty_pools() {
+        let mut book = PairBook::new(BASE, QUOTE);
+        let mut other = update(10, "RaydiumAmm", 1, 1, 1);
+        other.token_mints[0] = Pubkey::new([3; 32]);
+        assert!(!book.matches(&other));
+        assert!(book.update(&other).is_empty());
+
+        book.update(&update(10, "RaydiumAmm", 100, 100, 1));
+        let changes = book.update(&update(10, "RaydiumAmm", 0, 100, 2));
+        assert_eq!(changes.len(), 2);
+        assert!(changes.iter().all(|change| change.current.is_none()));
+        assert!(book.best_ask().is_none());
+    }
+}
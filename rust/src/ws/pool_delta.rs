@@ -0,0 +1,237 @@
+//! Balance changes between consecutive updates of a pool.
+//!
+//! [`PoolDeltaTracker`] remembers the last balances of each pool and turns
+//! the next update into a [`PoolDelta`]: positive token deltas flowed into
+//! the pool, negative ones out, which approximates the direction and size of
+//! the swaps in between. Updates are compared by `(slot, write_version)`, so
+//! late or duplicate updates never produce a delta, and pool snapshots or a
+//! [`reset`](PoolDeltaTracker::reset) after a reconnect start over from
+//! fresh balances instead of reporting the gap as one large trade.
+//!
+//! # Example
+//! ```rust
+//! use k256_sdk::ws::PoolDeltaTracker;
+//! use k256_sdk::{PoolUpdate, Pubkey, Slot};
+//!
+//! let update = |slot, balances: [u64; 2]| PoolUpdate {
+//!     slot: Slot(slot),
+//!     token_mints: vec![Pubkey::new([1; 32]), Pubkey::new([2; 32])],
+//!     token_balances: balances.to_vec(),
+//!     token_decimals: vec![9, 6],
+//!     ..PoolUpdate::default()
+//! };
+//! let mut deltas = PoolDeltaTracker::new();
+//! assert!(deltas.apply(&update(1, [1_000_000_000, 150_000_000])).is_none());
+//! let delta = deltas.apply(&update(2, [2_000_000_000, 0])).unwrap();
+//! assert_eq!(delta.token_deltas, [1_000_000_000, -150_000_000]);
+//! assert_eq!(delta.ui_deltas(), [1.0, -150.0]);
+//! ```
+
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use crate::types::{PoolUpdate, Protocol, Pubkey, Slot};
+
+/// Balance change of a pool since its previous update.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PoolDelta {
+    /// Pool address
+    pub pool: Pubkey,
+    /// DEX protocol of the pool
+    pub protocol: Protocol,
+    /// Slot of the update the delta ends at
+    pub slot: Slot,
+    /// Slot of the update the delta starts from
+    pub previous_slot: Slot,
+    /// Token mints, in the order of the deltas
+    pub token_mints: Vec<Pubkey>,
+    /// Balance changes in base units; positive when tokens entered the pool
+    pub token_deltas: Vec<i128>,
+    /// Token decimals, in the order of the deltas
+    pub token_decimals: Vec<i32>,
+}
+
+impl PoolDelta {
+    /// Balance changes in whole tokens.
+    pub fn ui_deltas(&self) -> Vec<f64> {
+        self.token_deltas
+            .iter()
+            .enumerate()
+            .map(|(i, delta)| {
+                let decimals = self.token_decimals.get(i).copied().unwrap_or(0);
+                *delta as f64 / 10f64.powi(decimals)
+            })
+            .collect()
+    }
+
+    /// Mint of the token that entered the pool the most (the swap input),
+    /// if any did.
+    pub fn input_mint(&self) -> Option<&Pubkey> {
+        self.extreme_mint(|delta| delta > 0, |a, b| a > b)
+    }
+
+    /// Mint of the token that left the pool the most (the swap output),
+    /// if any did.
+    pub fn output_mint(&self) -> Option<&Pubkey> {
+        self.extreme_mint(|delta| delta < 0, |a, b| a < b)
+    }
+
+    fn extreme_mint(&self, keep: impl Fn(i128) -> bool, better: impl Fn(i128, i128) -> bool) -> Option<&Pubkey> {
+        let mut best: Option<(usize, i128)> = None;
+        for (i, &delta) in self.token_deltas.iter().enumerate() {
+            if keep(delta) && best.map_or(true, |(_, current)| better(delta, current)) {
+                best = Some((i, delta));
+            }
+        }
+        self.token_mints.get(best?.0)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Baseline {
+    slot: Slot,
+    write_version: u64,
+    token_mints: Vec<Pubkey>,
+    token_balances: Vec<u64>,
+}
+
+impl Baseline {
+    fn of(update: &PoolUpdate) -> Self {
+        Self {
+            slot: update.slot,
+            write_version: update.write_version,
+            token_mints: update.token_mints.clone(),
+            token_balances: update.token_balances.clone(),
+        }
+    }
+}
+
+/// Derives [`PoolDelta`]s from consecutive pool updates.
+#[derive(Debug, Clone, Default)]
+pub struct PoolDeltaTracker {
+    pools: HashMap<Pubkey, Baseline>,
+}
+
+impl PoolDeltaTracker {
+    /// Tracker without any pool balances.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Compare `update` with the previous update of its pool.
+    ///
+    /// # Returns
+    ///
+    /// The balance change, or `None` for the first update of a pool, an
+    /// update not newer than the previous one, a changed token layout, or
+    /// unchanged balances
+    pub fn apply(&mut self, update: &PoolUpdate) -> Option<PoolDelta> {
+        let Some(previous) = self.pools.get_mut(&update.pool_address) else {
+            self.pools.insert(update.pool_address, Baseline::of(update));
+            return None;
+        };
+        if (update.slot, update.write_version) <= (previous.slot, previous.write_version) {
+            return None;
+        }
+        let previous = std::mem::replace(previous, Baseline::of(update));
+        if previous.token_mints != update.token_mints || previous.token_balances.len() != update.token_balances.len() {
+            return None;
+        }
+        let token_deltas: Vec<i128> = update
+            .token_balances
+            .iter()
+            .zip(&previous.token_balances)
+            .map(|(&now, &before)| i128::from(now) - i128::from(before))
+            .collect();
+        if token_deltas.iter().all(|&delta| delta == 0) {
+            return None;
+        }
+        Some(PoolDelta {
+            pool: update.pool_address,
+            protocol: update.protocol_name.clone(),
+            slot: update.slot,
+            previous_slot: previous.slot,
+            token_mints: update.token_mints.clone(),
+            token_deltas,
+            token_decimals: update.token_decimals.clone(),
+        })
+    }
+
+    /// Take `update` as the new balances of its pool without reporting a
+    /// delta, e.g. for pool snapshots.
+    pub fn rebase(&mut self, update: &PoolUpdate) {
+        self.pools.insert(update.pool_address, Baseline::of(update));
+    }
+
+    /// Forget all balances, e.g. after a reconnect.
+    pub fn reset(&mut self) {
+        self.pools.clear();
+    }
+
+    /// Forget the balances of `pool`.
+    pub fn remove(&mut self, pool: &Pubkey) {
+        self.pools.remove(pool);
+    }
+
+    /// Number of pools with known balances.
+    pub fn len(&self) -> usize {
+        self.pools.len()
+    }
+
+    /// Whether no pool balances are known.
+    pub fn is_empty(&self) -> bool {
+        self.pools.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn update(slot: u64, write_version: u64, balances: &[u64]) -> PoolUpdate {
+        PoolUpdate {
+            slot: Slot(slot),
+            write_version,
+            pool_address: Pubkey::new([9; 32]),
+            token_mints: (1..=balances.len() as u8).map(|n| Pubkey::new([n; 32])).collect(),
+            token_balances: balances.to_vec(),
+            token_decimals: vec![0; balances.len()],
+            ..PoolUpdate::default()
+        }
+    }
+
+    #[test]
+    fn test_deltas_follow_update_order() {
+        let mut deltas = PoolDeltaTracker::new();
+        assert!(deltas.apply(&update(10, 0, &[100, 100])).is_none());
+        let delta = deltas.apply(&update(11, 0, &[150, 70])).unwrap();
+        assert_eq!((delta.previous_slot, delta.slot), (Slot(10), Slot(11)));
+        assert_eq!(delta.token_deltas, [50, -30]);
+        assert_eq!(delta.input_mint(), Some(&Pubkey::new([1; 32])));
+        assert_eq!(delta.output_mint(), Some(&Pubkey::new([2; 32])));
+
+        // Late, duplicate and unchanged updates
+        assert!(deltas.apply(&update(10, 5, &[0, 0])).is_none());
+        assert!(deltas.apply(&update(11, 0, &[150, 70])).is_none());
+        assert!(deltas.apply(&update(11, 1, &[150, 70])).is_none());
+        assert_eq!(deltas.apply(&update(12, 0, &[u64::MAX, 0])).unwrap().token_deltas[0], u64::MAX as i128 - 150);
+    }
+
+    #[test]
+    fn test_rebase_and_reset() {
+        let mut deltas = PoolDeltaTracker::new();
+        deltas.apply(&update(1, 0, &[100, 100]));
+        deltas.rebase(&update(5, 0, &[500, 20]));
+        assert_eq!(deltas.apply(&update(6, 0, &[510, 19])).unwrap().token_deltas, [10, -1]);
+
+        // A different token layout starts over
+        assert!(deltas.apply(&update(7, 0, &[510, 19, 3])).is_none());
+        assert_eq!(deltas.apply(&update(8, 0, &[510, 19, 4])).unwrap().token_deltas, [0, 0, 1]);
+
+        deltas.reset();
+        assert!(deltas.is_empty());
+        assert!(deltas.apply(&update(9, 0, &[1, 1, 1])).is_none());
+        assert_eq!(deltas.len(), 1);
+    }
+}
@@ -0,0 +1,139 @@
+//! Client-side message predicates.
+//!
+//! Set through [`K256WebSocketClient::set_pool_filter`] and friends, for
+//! conditions the server-side subscription filters cannot express. Rejected
+//! messages never reach the callbacks; batches and snapshots keep only the
+//! accepted entries.
+//!
+//! [`K256WebSocketClient::set_pool_filter`]: crate::ws::K256WebSocketClient::set_pool_filter
+
+use crate::types::{FeeMarket, PoolUpdate, PriceEntry};
+use crate::ws::message::DecodedMessage;
+
+pub(crate) type Predicate<T> = Box<dyn Fn(&T) -> bool + Send + Sync + 'static>;
+
+/// Predicates evaluated before dispatch; `None` accepts everything.
+#[derive(Default)]
+pub(crate) struct MessagePredicates {
+    pub(crate) pool: Option<Predicate<PoolUpdate>>,
+    pub(crate) fee_market: Option<Predicate<FeeMarket>>,
+    pub(crate) price: Option<Predicate<PriceEntry>>,
+}
+
+impl MessagePredicates {
+    /// Drop `decoded`, or the entries of a batch, that a predicate rejects.
+    ///
+    /// Returns `None` when nothing is left to deliver. Snapshots are kept
+    /// even when empty, since they still replace the previous state.
+    pub(crate) fn apply(&self, decoded: DecodedMessage) -> Option<DecodedMessage> {
+        match decoded {
+            DecodedMessage::PoolUpdate(update) => {
+                accepts(&self.pool, &update).then_some(DecodedMessage::PoolUpdate(update))
+            }
+            DecodedMessage::PoolUpdateBatch(updates) => {
+                non_empty(retain(&self.pool, updates)).map(DecodedMessage::PoolUpdateBatch)
+            }
+            DecodedMessage::PoolSnapshot(updates) => Some(DecodedMessage::PoolSnapshot(retain(&self.pool, updates))),
+            DecodedMessage::FeeMarket(fees) => {
+                accepts(&self.fee_market, &fees).then_some(DecodedMessage::FeeMarket(fees))
+            }
+            DecodedMessage::PriceUpdate(entry) => {
+                accepts(&self.price, &entry).then_some(DecodedMessage::PriceUpdate(entry))
+            }
+            DecodedMessage::PriceBatch(entries) => {
+                non_empty(retain(&self.price, entries)).map(DecodedMessage::PriceBatch)
+            }
+            DecodedMessage::PriceSnapshot(entries) => {
+                Some(DecodedMessage::PriceSnapshot(retain(&self.price, entries)))
+            }
+            other => Some(other),
+        }
+    }
+}
+
+fn accepts<T>(predicate: &Option<Predicate<T>>, value: &T) -> bool {
+    predicate.as_ref().map_or(true, |predicate| predicate(value))
+}
+
+fn retain<T>(predicate: &Option<Predicate<T>>, mut values: Vec<T>) -> Vec<T> {
+    if let Some(predicate) = predicate {
+        values.retain(|value| predicate(value));
+    }
+    values
+}
+
+fn non_empty<T>(values: Vec<T>) -> Option<Vec<T>> {
+    (!values.is_empty()).then_some(values)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{Pubkey, Slot, TimestampMs};
+
+    fn update(slot: u64) -> PoolUpdate {
+        PoolUpdate {
+            slot: Slot(slot),
+            ..PoolUpdate::default()
+        }
+    }
+
+    fn price(usd_price: f64) -> PriceEntry {
+        PriceEntry {
+            mint: Pubkey::new([1; 32]),
+            usd_price,
+            slot: Slot(1),
+            timestamp_ms: TimestampMs(0),
+        }
+    }
+
+    #[test]
+    fn test_predicates_filter_messages_and_batches() {
+        let predicates = MessagePredicates {
+            pool: Some(Box::new(|update| update.slot.get() % 2 == 0)),
+            price: Some(Box::new(|entry| entry.usd_price > 1.0)),
+            ..MessagePredicates::default()
+        };
+
+        assert!(predicates.apply(DecodedMessage::PoolUpdate(update(1))).is_none());
+        assert!(predicates.apply(DecodedMessage::PoolUpdate(update(2))).is_some());
+        let Some(DecodedMessage::PoolUpdateBatch(batch)) =
+            predicates.apply(DecodedMessage::PoolUpdateBatch(vec![update(1), update(2), update(4)]))
+        else {
+            panic!("batch dropped");
+        };
+        assert_eq!(batch.iter().map(|u| u.slot).collect::<Vec<_>>(), [2, 4]);
+        assert!(predicates.apply(DecodedMessage::PoolUpdateBatch(vec![update(3)])).is_none());
+        assert!(matches!(
+            predicates.apply(DecodedMessage::PoolSnapshot(vec![update(3)])),
+            Some(DecodedMessage::PoolSnapshot(updates)) if updates.is_empty()
+        ));
+
+        assert!(predicates.apply(DecodedMessage::PriceUpdate(price(0.5))).is_none());
+        assert!(matches!(
+            predicates.apply(DecodedMessage::PriceBatch(vec![price(0.5), price(2.0)])),
+            Some(DecodedMessage::PriceBatch(entries)) if entries.len() == 1
+        ));
+        assert!(predicates.apply(DecodedMessage::Error("boom".to_string())).is_some());
+    }
+
+    #[tokio::test]
+    async fn test_client_skips_rejected_updates() {
+        use crate::replay::FrameKind;
+        use crate::ws::{encode_pool_update, Config, K256WebSocketClient};
+
+        let client = K256WebSocketClient::new(Config::default());
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        client.on_pool_update(move |update| tx.send(update.slot.get()).unwrap()).detach();
+        client.set_pool_filter(|update| update.slot >= 10);
+
+        for slot in [5, 10, 7, 12] {
+            client.handle_frame(FrameKind::Binary, &encode_pool_update(&update(slot))).await;
+        }
+        client.clear_filters();
+        client.handle_frame(FrameKind::Binary, &encode_pool_update(&update(1))).await;
+
+        let delivered: Vec<u64> = std::iter::from_fn(|| rx.try_recv().ok()).collect();
+        assert_eq!(delivered, [10, 12, 1]);
+    }
+}
@@ -0,0 +1,341 @@
+//! Client settings and named subscription profiles from a config file.
+//!
+//! A [`ProfileFile`] holds connection settings plus any number of named
+//! subscription profiles, so deployments can change what is subscribed
+//! without recompiling. Files are JSON, or TOML with the `toml` feature,
+//! chosen by extension:
+//!
+//! ```toml
+//! endpoint = "wss://gateway.k256.xyz/v1/ws"
+//! reconnect_delay_max_ms = 30000
+//!
+//! [profiles.arb]
+//! channels = ["pools"]
+//! protocols = ["RaydiumClmm", "Whirlpool"]
+//! snapshot = true
+//! ```
+//!
+//! `K256_API_KEY` and `K256_ENDPOINT` override the file, so secrets can
+//! stay out of it.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::error::K256Error;
+use crate::ws::client::Config;
+use crate::ws::message::SubscribeRequest;
+use crate::ws::subscription::Channel;
+
+/// Environment variable overriding [`Config::api_key`].
+pub const ENV_API_KEY: &str = "K256_API_KEY";
+/// Environment variable overriding [`Config::endpoint`].
+pub const ENV_ENDPOINT: &str = "K256_ENDPOINT";
+/// Environment variable naming the file [`SubscribeRequest::from_profile`] reads.
+pub const ENV_CONFIG: &str = "K256_CONFIG";
+/// File [`SubscribeRequest::from_profile`] reads when `K256_CONFIG` is unset.
+pub const DEFAULT_CONFIG_FILE: &str = "k256.toml";
+
+/// Config file error types.
+#[derive(Debug, Error)]
+pub enum ConfigError {
+    /// The file could not be read
+    #[error("Failed to read {}: {source}", path.display())]
+    Io {
+        /// File path
+        path: PathBuf,
+        /// Underlying error
+        source: std::io::Error,
+    },
+
+    /// The file is not valid JSON for a [`ProfileFile`]
+    #[error("Invalid JSON config: {0}")]
+    Json(#[from] serde_json::Error),
+
+    /// The file is not valid TOML for a [`ProfileFile`]
+    #[cfg(feature = "toml")]
+    #[error("Invalid TOML config: {0}")]
+    Toml(#[from] toml::de::Error),
+
+    /// The file extension is not a supported format
+    #[error("Unsupported config format: {0}")]
+    UnsupportedFormat(String),
+
+    /// No profile with the given name
+    #[error("Unknown subscription profile: {0}")]
+    UnknownProfile(String),
+
+    /// A profile names a channel the SDK does not know
+    #[error("Unknown channel in profile {profile}: {channel}")]
+    UnknownChannel {
+        /// Profile name
+        profile: String,
+        /// Channel name
+        channel: String,
+    },
+
+    /// A profile is not a valid subscription
+    #[error("Invalid subscription profile {profile}: {source}")]
+    InvalidProfile {
+        /// Profile name
+        profile: String,
+        /// Validation error
+        source: K256Error,
+    },
+}
+
+/// Named subscription settings.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SubscriptionProfile {
+    /// Channels (`"pools"`, `"priority_fees"`, `"blockhash"`)
+    pub channels: Vec<String>,
+    /// Message format (`"binary"` or `"json"`)
+    pub format: Option<String>,
+    /// DEX protocol filter
+    pub protocols: Vec<String>,
+    /// Pool address filter
+    pub pools: Vec<String>,
+    /// Token pair filter
+    pub token_pairs: Vec<(String, String)>,
+    /// Fee market writable account filter
+    pub fee_accounts: Vec<String>,
+    /// Request a pool snapshot after subscribing
+    pub snapshot: bool,
+}
+
+/// Contents of a config file: client settings and subscription profiles.
+///
+/// Settings left out keep their [`Config::default`] values.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ProfileFile {
+    /// K256 API key
+    pub api_key: Option<String>,
+    /// WebSocket endpoint URL
+    pub endpoint: Option<String>,
+    /// Additional endpoints used when `endpoint` is unreachable
+    pub fallback_endpoints: Vec<String>,
+    /// Whether to automatically reconnect
+    pub reconnect: Option<bool>,
+    /// Initial reconnect delay in milliseconds
+    pub reconnect_delay_initial_ms: Option<u64>,
+    /// Maximum reconnect delay in milliseconds
+    pub reconnect_delay_max_ms: Option<u64>,
+    /// Ping interval in milliseconds (0 to disable)
+    pub ping_interval_ms: Option<u64>,
+    /// Consecutive unanswered pings before reconnecting
+    pub max_missed_pings: Option<u32>,
+    /// Connection handshake timeout in milliseconds (0 to wait indefinitely)
+    pub connect_timeout_ms: Option<u64>,
+    /// Request timeout in milliseconds (0 to wait indefinitely)
+    pub request_timeout_ms: Option<u64>,
+    /// Subscription profiles by name
+    pub profiles: HashMap<String, SubscriptionProfile>,
+}
+
+impl ProfileFile {
+    /// Read a `.json` or `.toml` file.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ConfigError> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path).map_err(|source| ConfigError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("json") => Self::from_json(&contents),
+            #[cfg(feature = "toml")]
+            Some("toml") => Self::from_toml(&contents),
+            #[cfg(not(feature = "toml"))]
+            Some("toml") => Err(ConfigError::UnsupportedFormat(
+                "toml (enable the `toml` feature)".to_string(),
+            )),
+            other => Err(ConfigError::UnsupportedFormat(other.unwrap_or_default().to_string())),
+        }
+    }
+
+    /// Parse JSON config.
+    pub fn from_json(contents: &str) -> Result<Self, ConfigError> {
+        Ok(serde_json::from_str(contents)?)
+    }
+
+    /// Parse TOML config.
+    #[cfg(feature = "toml")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "toml")))]
+    pub fn from_toml(contents: &str) -> Result<Self, ConfigError> {
+        Ok(toml::from_str(contents)?)
+    }
+
+    /// Client configuration from the file, with environment overrides applied.
+    pub fn config(&self) -> Config {
+        let defaults = Config::default();
+        let ms_or = |ms: Option<u64>, default: Duration| ms.map_or(default, Duration::from_millis);
+        let timeout_ms_or = |ms: Option<u64>, default: Option<Duration>| match ms {
+            Some(0) => None,
+            Some(ms) => Some(Duration::from_millis(ms)),
+            None => default,
+        };
+        let config = Config {
+            api_key: self.api_key.clone().unwrap_or(defaults.api_key.clone()),
+            endpoint: self.endpoint.clone().unwrap_or(defaults.endpoint.clone()),
+            fallback_endpoints: self.fallback_endpoints.clone(),
+            reconnect: self.reconnect.unwrap_or(defaults.reconnect),
+            reconnect_delay_initial: ms_or(self.reconnect_delay_initial_ms, defaults.reconnect_delay_initial),
+            reconnect_delay_max: ms_or(self.reconnect_delay_max_ms, defaults.reconnect_delay_max),
+            ping_interval: ms_or(self.ping_interval_ms, defaults.ping_interval),
+            max_missed_pings: self.max_missed_pings.unwrap_or(defaults.max_missed_pings),
+            connect_timeout: timeout_ms_or(self.connect_timeout_ms, defaults.connect_timeout),
+            request_timeout: timeout_ms_or(self.request_timeout_ms, defaults.request_timeout),
+            ..defaults
+        };
+        config.with_env()
+    }
+
+    /// Subscription request for the named profile.
+    pub fn subscription(&self, name: &str) -> Result<SubscribeRequest, ConfigError> {
+        let profile = self
+            .profiles
+            .get(name)
+            .ok_or_else(|| ConfigError::UnknownProfile(name.to_string()))?;
+
+        let mut builder = SubscribeRequest::builder()
+            .protocols(profile.protocols.iter().cloned())
+            .pool_addresses(profile.pools.iter().cloned())
+            .fee_accounts(profile.fee_accounts.iter().cloned())
+            .snapshot(profile.snapshot);
+        for channel in &profile.channels {
+            let channel = channel.parse::<Channel>().map_err(|channel| ConfigError::UnknownChannel {
+                profile: name.to_string(),
+                channel,
+            })?;
+            builder = builder.channel(channel);
+        }
+        for (mint_a, mint_b) in &profile.token_pairs {
+            builder = builder.token_pair(mint_a.clone(), mint_b.clone());
+        }
+        builder = match profile.format.as_deref() {
+            Some("json") => builder.json(),
+            Some("binary") => builder.binary(),
+            _ => builder,
+        };
+        builder.build().map_err(|source| ConfigError::InvalidProfile {
+            profile: name.to_string(),
+            source,
+        })
+    }
+}
+
+impl Config {
+    /// Load client settings from a config file (see [`ProfileFile`]).
+    ///
+    /// `K256_API_KEY` and `K256_ENDPOINT` take precedence over the file.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, ConfigError> {
+        Ok(ProfileFile::load(path)?.config())
+    }
+
+    /// Default settings with `K256_API_KEY` and `K256_ENDPOINT` applied.
+    pub fn from_env() -> Self {
+        Self::default().with_env()
+    }
+
+    fn with_env(mut self) -> Self {
+        if let Ok(api_key) = std::env::var(ENV_API_KEY) {
+            self.api_key = api_key;
+        }
+        if let Ok(endpoint) = std::env::var(ENV_ENDPOINT) {
+            self.endpoint = endpoint;
+        }
+        self
+    }
+}
+
+impl SubscribeRequest {
+    /// Build the named profile from the file in `K256_CONFIG`, or
+    /// `k256.toml` in the working directory.
+    pub fn from_profile(name: &str) -> Result<Self, ConfigError> {
+        let path = std::env::var_os(ENV_CONFIG).map_or_else(|| PathBuf::from(DEFAULT_CONFIG_FILE), PathBuf::from);
+        ProfileFile::load(path)?.subscription(name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Protocol;
+
+    const JSON: &str = r#"{
+        "endpoint": "wss://eu.gateway.k256.xyz/v1/ws",
+        "reconnect_delay_max_ms": 5000,
+        "connect_timeout_ms": 0,
+        "profiles": {
+            "arb": {
+                "channels": ["pools", "priority_fees"],
+                "protocols": ["Whirlpool"],
+                "token_pairs": [[
+                    "So11111111111111111111111111111111111111112",
+                    "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v"
+                ]],
+                "snapshot": true
+            },
+            "typo": { "channels": ["poools"] },
+            "bad_pool": { "channels": ["pools"], "pools": ["not-a-pubkey"] }
+        }
+    }"#;
+
+    #[test]
+    fn test_config_from_json() {
+        let file = ProfileFile::from_json(JSON).unwrap();
+        let config = file.config();
+        assert_eq!(config.reconnect_delay_max, Duration::from_secs(5));
+        assert_eq!(config.reconnect_delay_initial, Config::default().reconnect_delay_initial);
+        assert_eq!((config.connect_timeout, config.request_timeout), (None, Config::default().request_timeout));
+        if std::env::var_os(ENV_ENDPOINT).is_none() {
+            assert_eq!(config.endpoint, "wss://eu.gateway.k256.xyz/v1/ws");
+        }
+
+        assert!(matches!(
+            ProfileFile::from_json(r#"{ "endpont": "x" }"#),
+            Err(ConfigError::Json(_))
+        ));
+    }
+
+    #[test]
+    fn test_subscription_profiles() {
+        let file = ProfileFile::from_json(JSON).unwrap();
+        let request = file.subscription("arb").unwrap();
+        assert_eq!(request.channels, vec!["pools", "priority_fees"]);
+        assert_eq!(request.protocols, Some(vec![Protocol::Whirlpool]));
+        assert_eq!(request.token_pairs.as_ref().map(Vec::len), Some(1));
+        assert_eq!(request.snapshot, Some(true));
+
+        assert!(matches!(file.subscription("missing"), Err(ConfigError::UnknownProfile(_))));
+        assert!(matches!(
+            file.subscription("typo"),
+            Err(ConfigError::UnknownChannel { channel, .. }) if channel == "poools"
+        ));
+        assert!(matches!(
+            file.subscription("bad_pool"),
+            Err(ConfigError::InvalidProfile { source: K256Error::InvalidPubkey(_), .. })
+        ));
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn test_toml() {
+        let file = ProfileFile::from_toml(
+            r#"
+            ping_interval_ms = 0
+
+            [profiles.fees]
+            channels = ["priority_fees"]
+            format = "json"
+            "#,
+        )
+        .unwrap();
+        assert!(file.config().ping_interval.is_zero());
+        assert_eq!(file.subscription("fees").unwrap().format.as_deref(), Some("json"));
+    }
+}
@@ -0,0 +1,123 @@
+//! Rate-limit and quota errors from the gateway.
+//!
+//! The gateway reports exceeded limits as ordinary error messages. Their
+//! text names the limit and carries `key=value` details, e.g.
+//! `rate limit exceeded (limit=100, remaining=0, reset_at=1700000000000)`;
+//! JSON error objects with `limit`, `remaining` and `resetAt` fields are
+//! folded into the same form by the decoder. [`QuotaError::parse`] turns
+//! such a message into a [`QuotaError`].
+
+use std::fmt;
+use std::time::Duration;
+
+use crate::types::TimestampMs;
+
+/// Phrases marking an error message as a rate-limit or quota error.
+const QUOTA_MARKERS: [&str; 5] = ["rate limit", "ratelimit", "rate_limit", "quota", "too many requests"];
+
+/// Detail keys of JSON error objects, after key normalization.
+pub(crate) const QUOTA_KEYS: [&str; 4] = ["limit", "remaining", "reset_at", "retry_after"];
+
+/// A rate-limit or quota error reported by the gateway.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuotaError {
+    /// Allowed requests or subscriptions per window, if reported
+    pub limit: Option<u64>,
+    /// Requests or subscriptions left in the window, if reported
+    pub remaining: Option<u64>,
+    /// When the window resets, if reported
+    pub reset_at: Option<TimestampMs>,
+    /// Server error message
+    pub message: String,
+}
+
+impl QuotaError {
+    /// Parse a server error message; `None` if it is not about a rate limit
+    /// or quota.
+    ///
+    /// `reset_at` is read as Unix milliseconds (seconds if below 10^12);
+    /// `retry_after` as seconds from now.
+    pub fn parse(message: &str) -> Option<Self> {
+        let lower = message.to_ascii_lowercase();
+        let mut quota = Self {
+            limit: None,
+            remaining: None,
+            reset_at: None,
+            message: message.to_string(),
+        };
+        let mut has_details = false;
+        for token in message.split(|c: char| c.is_whitespace() || matches!(c, ',' | ';' | '(' | ')' | '{' | '}')) {
+            let Some((key, value)) = token.split_once(['=', ':']) else {
+                continue;
+            };
+            let key = key.trim_matches('"').replace(['_', '-'], "").to_ascii_lowercase();
+            let Ok(value) = value.trim_matches('"').trim_end_matches("ms").trim_end_matches('s').parse::<u64>() else {
+                continue;
+            };
+            match key.as_str() {
+                "limit" => quota.limit = Some(value),
+                "remaining" => quota.remaining = Some(value),
+                "resetat" | "reset" => {
+                    quota.reset_at = Some(TimestampMs(if value < 1_000_000_000_000 { value * 1000 } else { value }))
+                }
+                "retryafter" => quota.reset_at = Some(TimestampMs::now() + Duration::from_secs(value)),
+                _ => continue,
+            }
+            has_details = true;
+        }
+        let marked = QUOTA_MARKERS.iter().any(|marker| lower.contains(marker));
+        (marked || has_details && quota.remaining.is_some()).then_some(quota)
+    }
+
+    /// Whether the window is used up and has not reset yet.
+    ///
+    /// Only errors with a future `reset_at` count, so a missing reset time
+    /// never blocks requests indefinitely.
+    pub fn is_exhausted(&self) -> bool {
+        self.remaining.unwrap_or(0) == 0 && self.reset_at.is_some_and(|reset_at| reset_at > TimestampMs::now())
+    }
+
+    /// Time until the window resets, zero if it already did.
+    pub fn retry_in(&self) -> Option<Duration> {
+        Some(self.reset_at?.duration_since(TimestampMs::now()))
+    }
+}
+
+impl fmt::Display for QuotaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for QuotaError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_details() {
+        let quota = QuotaError::parse("Rate limit exceeded (limit=100, remaining=0, reset_at=1700000000000)").unwrap();
+        assert_eq!(quota.limit, Some(100));
+        assert_eq!(quota.remaining, Some(0));
+        assert_eq!(quota.reset_at, Some(TimestampMs(1_700_000_000_000)));
+        assert!(!quota.is_exhausted(), "reset time is in the past");
+
+        let quota = QuotaError::parse("Subscription quota reached, retry_after=30s").unwrap();
+        assert_eq!(quota.limit, None);
+        assert!(quota.is_exhausted());
+        assert!(quota.retry_in().unwrap() > Duration::from_secs(25));
+
+        // Seconds-based reset times
+        let quota = QuotaError::parse("quota exceeded: reset=1700000000").unwrap();
+        assert_eq!(quota.reset_at, Some(TimestampMs(1_700_000_000_000)));
+    }
+
+    #[test]
+    fn test_parse_ignores_other_errors() {
+        assert!(QuotaError::parse("Invalid pool address").is_none());
+        assert!(QuotaError::parse("limit=5").is_none());
+        assert!(QuotaError::parse("too many requests").is_some_and(|quota| !quota.is_exhausted()));
+        assert!(QuotaError::parse("remaining=0 limit=5").is_some());
+    }
+}
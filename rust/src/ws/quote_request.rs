@@ -0,0 +1,174 @@
+//! Correlation of quote requests with their answers.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+use serde::Serialize;
+use serde_json::Value;
+use tokio::sync::oneshot;
+
+use crate::types::Quote;
+use crate::ws::message::QuoteRequest;
+
+/// The quote, or the server's error message.
+type QuoteAnswer = Result<Quote, String>;
+
+/// Wire form of a quote request.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct QuoteRequestFrame<'a> {
+    #[serde(rename = "type")]
+    request_type: &'static str,
+    request_id: &'a str,
+    #[serde(flatten)]
+    request: &'a QuoteRequest,
+}
+
+/// A registered quote request.
+pub(crate) struct QuoteTicket {
+    pub(crate) request_id: String,
+    /// JSON frame to send
+    pub(crate) frame: String,
+    pub(crate) answer: oneshot::Receiver<QuoteAnswer>,
+}
+
+/// Quote requests awaiting an answer, by request ID.
+#[derive(Default)]
+pub(crate) struct PendingQuotes {
+    next_id: AtomicU64,
+    waiters: Mutex<HashMap<String, oneshot::Sender<QuoteAnswer>>>,
+}
+
+impl PendingQuotes {
+    /// Register a request under a new request ID.
+    pub(crate) fn register(&self, request: &QuoteRequest) -> Result<QuoteTicket, serde_json::Error> {
+        let request_id = format!("q{}", self.next_id.fetch_add(1, Ordering::Relaxed) + 1);
+        let frame = serde_json::to_string(&QuoteRequestFrame {
+            request_type: "quote_request",
+            request_id: &request_id,
+            request,
+        })?;
+        let (tx, rx) = oneshot::channel();
+        self.waiters.lock().unwrap().insert(request_id.clone(), tx);
+        Ok(QuoteTicket {
+            request_id,
+            frame,
+            answer: rx,
+        })
+    }
+
+    /// Forget a request that timed out or could not be sent.
+    pub(crate) fn cancel(&self, request_id: &str) {
+        self.waiters.lock().unwrap().remove(request_id);
+    }
+
+    /// Hand a quote to the request it answers.
+    ///
+    /// # Returns
+    ///
+    /// The quote back if it answers no pending request
+    pub(crate) fn answer(&self, quote: Quote) -> Option<Quote> {
+        let Some(request_id) = quote.request_id.as_deref() else {
+            return Some(quote);
+        };
+        let Some(waiter) = self.waiters.lock().unwrap().remove(request_id) else {
+            return Some(quote);
+        };
+        let _ = waiter.send(Ok(quote));
+        None
+    }
+
+    /// Fail the request named by the `requestId` of an error text frame.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the error answered a pending request
+    pub(crate) fn reject(&self, text: &str, message: &str) -> bool {
+        if self.waiters.lock().unwrap().is_empty() {
+            return false;
+        }
+        let Some(request_id) = error_request_id(text) else {
+            return false;
+        };
+        let Some(waiter) = self.waiters.lock().unwrap().remove(&request_id) else {
+            return false;
+        };
+        let _ = waiter.send(Err(message.to_string()));
+        true
+    }
+}
+
+/// `requestId` of an error message, at the top level or inside `data`.
+fn error_request_id(text: &str) -> Option<String> {
+    let value: Value = serde_json::from_str(text).ok()?;
+    let find = |value: &Value| {
+        ["requestId", "request_id"]
+            .iter()
+            .find_map(|key| value.get(key)?.as_str().map(String::from))
+    };
+    find(&value).or_else(|| find(value.get("data")?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{Pubkey, Slot, TimestampMs};
+
+    fn request() -> QuoteRequest {
+        QuoteRequest {
+            input_mint: Pubkey::new([1; 32]),
+            output_mint: Pubkey::new([2; 32]),
+            amount: 1_000,
+            slippage_bps: 50,
+            swap_mode: None,
+        }
+    }
+
+    fn quote(request_id: Option<&str>) -> Quote {
+        Quote {
+            input_mint: Pubkey::new([1; 32]),
+            output_mint: Pubkey::new([2; 32]),
+            in_amount: 1_000,
+            out_amount: 990,
+            price_impact_pct: 0.0,
+            slot: Slot(1),
+            timestamp_ms: TimestampMs(0),
+            route_plan: Vec::new(),
+            other_amount_threshold: None,
+            swap_mode: "ExactIn".to_string(),
+            request_id: request_id.map(String::from),
+        }
+    }
+
+    #[test]
+    fn test_answers_by_request_id() {
+        let pending = PendingQuotes::default();
+        let mut first = pending.register(&request()).unwrap();
+        let mut second = pending.register(&request()).unwrap();
+        assert_ne!(first.request_id, second.request_id);
+        let frame: Value = serde_json::from_str(&first.frame).unwrap();
+        assert_eq!(frame["type"], "quote_request");
+        assert_eq!(frame["requestId"], first.request_id.as_str());
+        assert_eq!(frame["slippageBps"], 50);
+
+        assert!(pending.answer(quote(None)).is_some());
+        assert!(pending.answer(quote(Some("unknown"))).is_some());
+        assert!(pending.answer(quote(Some(&second.request_id))).is_none());
+        assert_eq!(second.answer.try_recv().unwrap().unwrap().out_amount, 990);
+
+        let error = format!(r#"{{"type":"error","data":{{"message":"no route","requestId":"{}"}}}}"#, first.request_id);
+        assert!(pending.reject(&error, "no route"));
+        assert_eq!(first.answer.try_recv().unwrap().unwrap_err(), "no route");
+        assert!(!pending.reject(&error, "no route"));
+    }
+
+    #[test]
+    fn test_cancel() {
+        let pending = PendingQuotes::default();
+        let mut ticket = pending.register(&request()).unwrap();
+        pending.cancel(&ticket.request_id);
+        assert!(ticket.answer.try_recv().is_err());
+        assert!(pending.answer(quote(Some(&ticket.request_id))).is_some());
+    }
+}
@@ -0,0 +1,264 @@
+//! Binary decoder that reuses allocations across messages.
+//!
+//! [`decode_message`](crate::ws::decode_message) allocates fresh vectors for
+//! every pool update and fee market. A long-running consumer that is done
+//! with a message can hand it back to a [`Decoder`], which keeps its vectors
+//! and fills them again for later messages instead of allocating.
+//!
+//! ```rust
+//! use k256_sdk::ws::{Decoder, DecodedMessage};
+//!
+//! fn run(frames: impl Iterator<Item = Vec<u8>>) {
+//!     let mut decoder = Decoder::new();
+//!     let mut message = None;
+//!     for frame in frames {
+//!         let Some((&msg_type, payload)) = frame.split_first() else { continue };
+//!         if decoder.decode_into(msg_type, payload, &mut message).is_err() {
+//!             continue;
+//!         }
+//!         if let Some(DecodedMessage::PoolUpdateBatch(updates)) = &message {
+//!             println!("{} updates", updates.len());
+//!         }
+//!     }
+//! }
+//! ```
+
+use k256_proto::check_limit;
+
+use crate::types::{AccountFee, MessageType, PoolUpdate};
+use crate::ws::borrowed::{PoolUpdateBatchRef, PoolUpdateRef};
+use crate::ws::decoder::{decode_fee_market_into, decode_message_with_limits, DecodeError, DecodeLimits};
+use crate::ws::message::DecodedMessage;
+
+/// Default number of spare pool updates a [`Decoder`] keeps.
+const DEFAULT_MAX_SPARE_UPDATES: usize = 4096;
+
+/// Spare batch and account vectors kept, beyond which recycled ones are freed.
+const MAX_SPARE_VECS: usize = 8;
+
+/// Binary decoder holding buffers recycled from earlier messages.
+///
+/// Decodes exactly like [`decode_message_with_limits`]; only where the
+/// memory comes from differs. Recycled pool updates keep the capacity of
+/// their `serialized_state`, so the retained memory grows with the largest
+/// states seen; cap it with [`max_spare_updates`](Self::max_spare_updates).
+#[derive(Debug)]
+pub struct Decoder {
+    limits: DecodeLimits,
+    max_spare_updates: usize,
+    updates: Vec<PoolUpdate>,
+    batches: Vec<Vec<PoolUpdate>>,
+    accounts: Vec<Vec<AccountFee>>,
+}
+
+impl Default for Decoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Decoder {
+    /// Create a decoder with the default [`DecodeLimits`].
+    pub fn new() -> Self {
+        Self::with_limits(DecodeLimits::default())
+    }
+
+    /// Create a decoder enforcing `limits`.
+    pub fn with_limits(limits: DecodeLimits) -> Self {
+        Self {
+            limits,
+            max_spare_updates: DEFAULT_MAX_SPARE_UPDATES,
+            updates: Vec::new(),
+            batches: Vec::new(),
+            accounts: Vec::new(),
+        }
+    }
+
+    /// Keep at most `max` recycled pool updates (default 4096).
+    pub fn max_spare_updates(mut self, max: usize) -> Self {
+        self.max_spare_updates = max;
+        self.updates.truncate(max);
+        self
+    }
+
+    /// Decode a binary message, filling recycled buffers where available.
+    ///
+    /// # Arguments
+    ///
+    /// * `msg_type` - Message type byte
+    /// * `payload` - Message payload (without type byte)
+    ///
+    /// # Returns
+    ///
+    /// Decoded message, or None for unhandled types
+    pub fn decode(&mut self, msg_type: u8, payload: &[u8]) -> Result<Option<DecodedMessage>, DecodeError> {
+        check_limit("payload", payload.len(), self.limits.max_payload_len)?;
+        let snapshot = match MessageType::try_from(msg_type) {
+            Ok(MessageType::PoolUpdate) => {
+                let update = PoolUpdateRef::decode_with_limits(payload, &self.limits)?;
+                let mut owned = self.updates.pop().unwrap_or_default();
+                update.write_to(&mut owned);
+                return Ok(Some(DecodedMessage::PoolUpdate(owned)));
+            }
+            Ok(MessageType::PriorityFees) => {
+                let accounts = self.accounts.pop().unwrap_or_default();
+                let fees = decode_fee_market_into(payload, &self.limits, accounts)?;
+                return Ok(Some(DecodedMessage::FeeMarket(fees)));
+            }
+            Ok(MessageType::PoolUpdateBatch) => false,
+            Ok(MessageType::PoolSnapshot) => true,
+            _ => return decode_message_with_limits(msg_type, payload, &self.limits),
+        };
+
+        let batch = PoolUpdateBatchRef::decode_with_limits(payload, &self.limits)?;
+        let mut updates = self.batches.pop().unwrap_or_default();
+        updates.reserve(batch.capacity());
+        for update in batch.iter() {
+            match update {
+                Ok(update) => {
+                    let mut owned = self.updates.pop().unwrap_or_default();
+                    update.write_to(&mut owned);
+                    updates.push(owned);
+                }
+                Err(e) => {
+                    self.recycle_batch(updates);
+                    return Err(e);
+                }
+            }
+        }
+        Ok(Some(if snapshot {
+            DecodedMessage::PoolSnapshot(updates)
+        } else {
+            DecodedMessage::PoolUpdateBatch(updates)
+        }))
+    }
+
+    /// Decode a binary message into `out`, recycling the message it held.
+    ///
+    /// `out` is set to `None` for unhandled types and left untouched on error.
+    pub fn decode_into(
+        &mut self,
+        msg_type: u8,
+        payload: &[u8],
+        out: &mut Option<DecodedMessage>,
+    ) -> Result<(), DecodeError> {
+        let message = self.decode(msg_type, payload)?;
+        if let Some(previous) = std::mem::replace(out, message) {
+            self.recycle(previous);
+        }
+        Ok(())
+    }
+
+    /// Hand a message back so its buffers are reused by later decodes.
+    pub fn recycle(&mut self, message: DecodedMessage) {
+        match message {
+            DecodedMessage::PoolUpdate(update) => self.recycle_update(update),
+            DecodedMessage::PoolUpdateBatch(updates) | DecodedMessage::PoolSnapshot(updates) => {
+                self.recycle_batch(updates)
+            }
+            DecodedMessage::FeeMarket(fees) if self.accounts.len() < MAX_SPARE_VECS => {
+                self.accounts.push(fees.accounts)
+            }
+            _ => {}
+        }
+    }
+
+    fn recycle_update(&mut self, update: PoolUpdate) {
+        if self.updates.len() < self.max_spare_updates {
+            self.updates.push(update);
+        }
+    }
+
+    fn recycle_batch(&mut self, mut updates: Vec<PoolUpdate>) {
+        for update in updates.drain(..) {
+            self.recycle_update(update);
+        }
+        if self.batches.len() < MAX_SPARE_VECS {
+            self.batches.push(updates);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{FeeMarket, NetworkState, Pubkey, Slot, TimestampMs};
+    use crate::ws::{decode_message, encode_fee_market, encode_pool_update};
+
+    fn update(slot: u64, state_len: usize) -> PoolUpdate {
+        PoolUpdate {
+            slot: Slot(slot),
+            protocol_name: "Whirlpool".into(),
+            pool_address: Pubkey::new([slot as u8; 32]),
+            token_mints: vec![Pubkey::new([1; 32]), Pubkey::new([2; 32])],
+            token_balances: vec![10, 20],
+            token_decimals: vec![9, 6],
+            serialized_state: vec![0xAB; state_len],
+            ..PoolUpdate::default()
+        }
+    }
+
+    fn json(message: &Option<DecodedMessage>) -> serde_json::Value {
+        serde_json::to_value(message).unwrap()
+    }
+
+    fn batch_payload(updates: &[PoolUpdate]) -> Vec<u8> {
+        let mut payload = (updates.len() as u16).to_le_bytes().to_vec();
+        for update in updates {
+            let entry = &encode_pool_update(update)[1..];
+            payload.extend_from_slice(&(entry.len() as u32).to_le_bytes());
+            payload.extend_from_slice(entry);
+        }
+        payload
+    }
+
+    #[test]
+    fn test_reuses_batch_buffers() {
+        let msg_type = MessageType::PoolUpdateBatch as u8;
+        let first = batch_payload(&[update(1, 64), update(2, 64)]);
+        let second = batch_payload(&[update(3, 16)]);
+
+        let mut decoder = Decoder::new();
+        let mut message = None;
+        decoder.decode_into(msg_type, &first, &mut message).unwrap();
+        let Some(DecodedMessage::PoolUpdateBatch(updates)) = &message else {
+            panic!("expected batch");
+        };
+        let state_ptr = updates[1].serialized_state.as_ptr();
+
+        decoder.decode_into(msg_type, &second, &mut message).unwrap();
+        decoder.decode_into(msg_type, &first, &mut message).unwrap();
+        let Some(DecodedMessage::PoolUpdateBatch(updates)) = &message else {
+            panic!("expected batch");
+        };
+        assert!(updates.iter().any(|u| u.serialized_state.as_ptr() == state_ptr));
+        assert_eq!(json(&message), json(&decode_message(msg_type, &first).unwrap()));
+    }
+
+    #[test]
+    fn test_matches_stateless_decoder() {
+        let fees = FeeMarket {
+            slot: Slot(5),
+            timestamp_ms: TimestampMs(6),
+            recommended: 7,
+            state: NetworkState::High,
+            is_stale: false,
+            block_utilization_pct: 50.0,
+            blocks_in_window: 10,
+            accounts: Vec::new(),
+        };
+        let mut decoder = Decoder::new().max_spare_updates(1);
+        let mut message = None;
+        for frame in [encode_pool_update(&update(1, 8)), encode_fee_market(&fees), vec![MessageType::Pong as u8]] {
+            let (&msg_type, payload) = frame.split_first().unwrap();
+            decoder.decode_into(msg_type, payload, &mut message).unwrap();
+            assert_eq!(json(&message), json(&decode_message(msg_type, payload).unwrap()));
+        }
+
+        // Errors leave the previous message in place
+        let truncated = batch_payload(&[update(1, 8)]);
+        let result = decoder.decode_into(MessageType::PoolSnapshot as u8, &truncated[..20], &mut message);
+        assert!(result.is_err());
+        assert!(matches!(message, Some(DecodedMessage::Pong { .. })));
+    }
+}
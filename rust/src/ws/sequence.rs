@@ -0,0 +1,88 @@
+//! Pool update sequence gap detection.
+//!
+//! Every pool update carries the server's global `sequence` number. With
+//! [`Config::detect_sequence_gaps`] set, the client tracks the last sequence
+//! it saw (across reconnects) and reports a [`GapInfo`] when the next update
+//! skips ahead, so downstream state can be rebuilt from a fresh snapshot.
+//!
+//! Sequences are global, so gaps are only meaningful for subscriptions
+//! without pool or protocol filters; filtered subscriptions skip the
+//! sequences of pools they do not match.
+//!
+//! [`Config::detect_sequence_gaps`]: crate::ws::Config::detect_sequence_gaps
+
+/// A jump in pool update sequence numbers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GapInfo {
+    /// Sequence that should have arrived next
+    pub expected: u64,
+    /// Sequence that arrived instead
+    pub got: u64,
+}
+
+impl GapInfo {
+    /// Number of updates missed.
+    pub fn missed(&self) -> u64 {
+        self.got - self.expected
+    }
+}
+
+/// Last seen pool update sequence.
+#[derive(Debug, Default)]
+#[cfg_attr(target_arch = "wasm32", allow(dead_code))]
+pub(crate) struct SequenceTracker {
+    last: Option<u64>,
+}
+
+#[cfg_attr(target_arch = "wasm32", allow(dead_code))]
+impl SequenceTracker {
+    /// Track `sequence`, returning the gap if it skipped ahead.
+    ///
+    /// Duplicate and out-of-order sequences are ignored.
+    pub(crate) fn observe(&mut self, sequence: u64) -> Option<GapInfo> {
+        let gap = match self.last {
+            Some(last) if sequence <= last => return None,
+            Some(last) if sequence > last + 1 => Some(GapInfo {
+                expected: last + 1,
+                got: sequence,
+            }),
+            _ => None,
+        };
+        self.last = Some(sequence);
+        gap
+    }
+
+    /// Forget the last sequence; the next update starts tracking afresh.
+    pub(crate) fn reset(&mut self) {
+        self.last = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detects_gaps() {
+        let mut tracker = SequenceTracker::default();
+        assert_eq!(tracker.observe(10), None);
+        assert_eq!(tracker.observe(11), None);
+        // Duplicates and stragglers are not gaps
+        assert_eq!(tracker.observe(11), None);
+        assert_eq!(tracker.observe(5), None);
+
+        let gap = tracker.observe(15).unwrap();
+        assert_eq!(gap, GapInfo { expected: 12, got: 15 });
+        assert_eq!(gap.missed(), 3);
+        assert_eq!(tracker.observe(16), None);
+    }
+
+    #[test]
+    fn test_reset() {
+        let mut tracker = SequenceTracker::default();
+        tracker.observe(1);
+        tracker.reset();
+        assert_eq!(tracker.observe(100), None);
+        assert_eq!(tracker.observe(102), Some(GapInfo { expected: 101, got: 102 }));
+    }
+}
@@ -0,0 +1,176 @@
+//! Per-pool silence detection.
+//!
+//! Pools named in the subscription's pool filter are expected to keep
+//! updating. With [`Config::pool_silence_after`] set (or a per-pool timeout
+//! from [`K256WebSocketClient::set_pool_silence_after`]), the client reports
+//! a [`PoolSilent`] when such a pool goes that long without an update, e.g.
+//! because the upstream indexer stopped tracking it. Each silence is
+//! reported once; the next update for the pool clears it.
+//!
+//! [`Config::pool_silence_after`]: crate::ws::Config::pool_silence_after
+//! [`K256WebSocketClient::set_pool_silence_after`]: crate::ws::K256WebSocketClient::set_pool_silence_after
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use crate::types::Pubkey;
+
+/// Shortest and longest interval between silence checks.
+const MIN_CHECK_PERIOD: Duration = Duration::from_millis(10);
+const MAX_CHECK_PERIOD: Duration = Duration::from_secs(1);
+
+/// A subscribed pool that stopped updating.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PoolSilent {
+    /// Pool address
+    pub pool: Pubkey,
+    /// Time since the pool's last update, or since it was subscribed if it
+    /// never updated
+    pub last_update_age: Duration,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct PoolTimer {
+    last_update: Instant,
+    silent: bool,
+}
+
+/// Inactivity timers of the subscribed pools.
+#[derive(Debug)]
+pub(crate) struct SilenceTracker {
+    /// Timeout for pools without their own
+    default: Option<Duration>,
+    /// Per-pool timeouts
+    timeouts: HashMap<Pubkey, Duration>,
+    /// Subscribed pools
+    pools: HashMap<Pubkey, PoolTimer>,
+}
+
+impl SilenceTracker {
+    pub(crate) fn new(default: Option<Duration>) -> Self {
+        Self {
+            default,
+            timeouts: HashMap::new(),
+            pools: HashMap::new(),
+        }
+    }
+
+    /// Set or clear the timeout of `pool`; `None` falls back to the default.
+    pub(crate) fn set_timeout(&mut self, pool: Pubkey, timeout: Option<Duration>) {
+        match timeout {
+            Some(timeout) => self.timeouts.insert(pool, timeout),
+            None => self.timeouts.remove(&pool),
+        };
+    }
+
+    /// Watch exactly `pools`, timing newly added ones from `now`.
+    pub(crate) fn watch(&mut self, pools: &[Pubkey], now: Instant) {
+        self.pools.retain(|pool, _| pools.contains(pool));
+        for pool in pools {
+            self.pools.entry(*pool).or_insert(PoolTimer {
+                last_update: now,
+                silent: false,
+            });
+        }
+    }
+
+    /// Restart every timer from `now`, e.g. on a new connection.
+    pub(crate) fn restart(&mut self, now: Instant) {
+        for timer in self.pools.values_mut() {
+            *timer = PoolTimer {
+                last_update: now,
+                silent: false,
+            };
+        }
+    }
+
+    /// Record an update for `pool`, clearing its silence.
+    pub(crate) fn observe(&mut self, pool: &Pubkey, now: Instant) {
+        if let Some(timer) = self.pools.get_mut(pool) {
+            *timer = PoolTimer {
+                last_update: now,
+                silent: false,
+            };
+        }
+    }
+
+    /// Pools that went silent since the last check.
+    pub(crate) fn check(&mut self, now: Instant) -> Vec<PoolSilent> {
+        let mut silent = Vec::new();
+        for (pool, timer) in &mut self.pools {
+            let Some(timeout) = self.timeouts.get(pool).copied().or(self.default) else {
+                continue;
+            };
+            let age = now.saturating_duration_since(timer.last_update);
+            if !timer.silent && age >= timeout {
+                timer.silent = true;
+                silent.push(PoolSilent {
+                    pool: *pool,
+                    last_update_age: age,
+                });
+            }
+        }
+        silent
+    }
+
+    /// How often to check: a quarter of the shortest timeout, clamped to
+    /// 10ms..1s. `None` while no timeout is configured.
+    pub(crate) fn check_period(&self) -> Option<Duration> {
+        let shortest = self.timeouts.values().copied().chain(self.default).min()?;
+        Some((shortest / 4).clamp(MIN_CHECK_PERIOD, MAX_CHECK_PERIOD))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reports_silence_once_until_next_update() {
+        let (a, b) = (Pubkey::new([1; 32]), Pubkey::new([2; 32]));
+        let start = Instant::now();
+        let mut tracker = SilenceTracker::new(Some(Duration::from_secs(10)));
+        tracker.set_timeout(b, Some(Duration::from_secs(60)));
+        tracker.watch(&[a, b], start);
+        assert_eq!(tracker.check_period(), Some(MAX_CHECK_PERIOD));
+
+        tracker.observe(&a, start + Duration::from_secs(5));
+        assert!(tracker.check(start + Duration::from_secs(14)).is_empty());
+        let silent = tracker.check(start + Duration::from_secs(15));
+        assert_eq!(
+            silent,
+            [PoolSilent {
+                pool: a,
+                last_update_age: Duration::from_secs(10)
+            }]
+        );
+        assert!(tracker.check(start + Duration::from_secs(30)).is_empty());
+
+        // The next update clears the silence, so it can be reported again
+        tracker.observe(&a, start + Duration::from_secs(31));
+        assert_eq!(tracker.check(start + Duration::from_secs(41)).len(), 1);
+        assert_eq!(tracker.check(start + Duration::from_secs(61))[0].pool, b);
+    }
+
+    #[test]
+    fn test_watch_follows_subscription() {
+        let (a, b) = (Pubkey::new([1; 32]), Pubkey::new([2; 32]));
+        let start = Instant::now();
+        let mut tracker = SilenceTracker::new(None);
+        assert_eq!(tracker.check_period(), None);
+        tracker.watch(&[a, b], start);
+        // No timeout configured, nothing is reported
+        assert!(tracker.check(start + Duration::from_secs(3600)).is_empty());
+
+        tracker.set_timeout(a, Some(Duration::from_millis(20)));
+        assert_eq!(tracker.check_period(), Some(MIN_CHECK_PERIOD));
+        tracker.watch(&[b], start);
+        tracker.observe(&a, start);
+        assert!(tracker.check(start + Duration::from_secs(1)).is_empty());
+
+        tracker.watch(&[a, b], start + Duration::from_secs(1));
+        tracker.restart(start + Duration::from_secs(2));
+        assert!(tracker.check(start + Duration::from_millis(2010)).is_empty());
+        assert_eq!(tracker.check(start + Duration::from_millis(2020)).len(), 1);
+    }
+}
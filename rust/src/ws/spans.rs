@@ -0,0 +1,127 @@
+//! `tracing` spans following a connection and each message through the client.
+//!
+//! With [`Config::trace_spans`] set (the default, at `DEBUG`) the client
+//! opens these spans, so events logged while a message is handled, including
+//! those of user callbacks, can be correlated in a tracing backend:
+//!
+//! | Span | Parent | Fields |
+//! |------|--------|--------|
+//! | `k256.connection` | | `endpoint`, `peer` |
+//! | `k256.message` | `k256.connection` | `msg_type`, `slot`, `pool`, `count` |
+//! | `k256.decode` | `k256.message` | |
+//! | `k256.dispatch` | `k256.message` | |
+//! | `k256.callback` | `k256.message` | |
+//!
+//! `k256.dispatch` covers the hand-off to a dispatch queue, including any
+//! [`DropPolicy::Block`] wait; `k256.callback` covers running the callbacks,
+//! on the dispatch thread when [`Config::dispatch`] is set. Updates merged by
+//! ordered delivery or coalescing are delivered under a new `k256.message`
+//! span. With `trace_spans` set to `None` no span is created at all.
+//!
+//! [`Config::trace_spans`]: crate::ws::Config::trace_spans
+//! [`Config::dispatch`]: crate::ws::Config::dispatch
+//! [`DropPolicy::Block`]: crate::ws::DropPolicy::Block
+
+use std::net::SocketAddr;
+
+use tracing::field::{display, Empty};
+use tracing::{Level, Span};
+
+use crate::ws::message::DecodedMessage;
+
+/// Span of one connection.
+pub const CONNECTION: &str = "k256.connection";
+/// Span of one received message.
+pub const MESSAGE: &str = "k256.message";
+/// Span of decoding a message.
+pub const DECODE: &str = "k256.decode";
+/// Span of handing a message to its dispatch queue or inline callbacks.
+pub const DISPATCH: &str = "k256.dispatch";
+/// Span of running the callbacks for a message.
+pub const CALLBACK: &str = "k256.callback";
+
+/// `tracing::span!` at a level only known at runtime.
+macro_rules! span_at {
+    ($level:expr, $parent:expr, $($rest:tt)+) => {
+        match $level {
+            Some(Level::ERROR) => tracing::span!(parent: $parent, Level::ERROR, $($rest)+),
+            Some(Level::WARN) => tracing::span!(parent: $parent, Level::WARN, $($rest)+),
+            Some(Level::INFO) => tracing::span!(parent: $parent, Level::INFO, $($rest)+),
+            Some(Level::DEBUG) => tracing::span!(parent: $parent, Level::DEBUG, $($rest)+),
+            Some(_) => tracing::span!(parent: $parent, Level::TRACE, $($rest)+),
+            None => Span::none(),
+        }
+    };
+}
+
+/// Span of one connection, open until the session ends.
+pub(crate) fn connection(level: Option<Level>, endpoint: &str, peer: Option<SocketAddr>) -> Span {
+    let span = span_at!(level, None, CONNECTION, endpoint = %endpoint, peer = Empty);
+    if let Some(peer) = peer {
+        span.record("peer", display(peer));
+    }
+    span
+}
+
+/// Span of one received message; its fields are filled in by [`record`]
+/// once the message is decoded.
+pub(crate) fn message(level: Option<Level>, connection: &Span) -> Span {
+    span_at!(
+        level,
+        connection,
+        MESSAGE,
+        msg_type = Empty,
+        slot = Empty,
+        pool = Empty,
+        count = Empty
+    )
+}
+
+/// Span of decoding a message.
+pub(crate) fn decode(level: Option<Level>, message: &Span) -> Span {
+    span_at!(level, message, DECODE)
+}
+
+/// Span of handing a message to its dispatch queue, or to inline callbacks.
+pub(crate) fn dispatch(level: Option<Level>, message: &Span) -> Span {
+    span_at!(level, message, DISPATCH)
+}
+
+/// Span of running the callbacks for a message.
+pub(crate) fn callback(level: Option<Level>, message: &Span) -> Span {
+    span_at!(level, message, CALLBACK)
+}
+
+/// Fill in the fields of a `k256.message` span from the decoded message.
+///
+/// `slot` is the newest slot of a batch, and `pool` is only set for single
+/// pool updates.
+pub(crate) fn record(span: &Span, decoded: &DecodedMessage) {
+    if span.is_disabled() {
+        return;
+    }
+    span.record("msg_type", decoded.kind());
+    let (slot, count) = match decoded {
+        DecodedMessage::PoolUpdate(update) => {
+            span.record("pool", display(&update.pool_address));
+            (Some(update.slot), None)
+        }
+        DecodedMessage::PoolUpdateBatch(updates) | DecodedMessage::PoolSnapshot(updates) => {
+            (updates.iter().map(|update| update.slot).max(), Some(updates.len()))
+        }
+        DecodedMessage::FeeMarket(fees) => (Some(fees.slot), None),
+        DecodedMessage::Blockhash(bh) => (Some(bh.slot), None),
+        DecodedMessage::Quote(quote) => (Some(quote.slot), None),
+        DecodedMessage::PriceUpdate(entry) => (Some(entry.slot), None),
+        DecodedMessage::PriceBatch(entries) | DecodedMessage::PriceSnapshot(entries) => {
+            (entries.iter().map(|entry| entry.slot).max(), Some(entries.len()))
+        }
+        _ => (None, None),
+    };
+    if let Some(slot) = slot {
+        span.record("slot", slot.get());
+    }
+    if let Some(count) = count {
+        span.record("count", count);
+    }
+}
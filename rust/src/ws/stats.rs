@@ -0,0 +1,156 @@
+//! Client-side connection statistics.
+//!
+//! [`K256WebSocketClient::stats`] snapshots counters the client keeps for
+//! itself, the client-side counterpart of the server's [`Heartbeat`], so
+//! health can be exported without instrumenting callbacks or installing a
+//! `metrics` recorder.
+//!
+//! [`K256WebSocketClient::stats`]: crate::ws::K256WebSocketClient::stats
+
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::types::Heartbeat;
+use crate::ws::dispatch::DroppedMessages;
+use crate::ws::message::DecodedMessage;
+
+/// Snapshot of client health counters since the client was created.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ClientStats {
+    /// Payload bytes of received data frames
+    pub bytes_received: u64,
+    /// Received data frames, decodable or not
+    pub frames_received: u64,
+    /// Decoded messages by type (`"pool_update"`, `"fee_market"`, ...), before
+    /// client-side filters
+    pub messages: BTreeMap<&'static str, u64>,
+    /// Frames that failed to decode
+    pub decode_errors: u64,
+    /// Frames over `Config::decode_limits`, dropped or truncated
+    pub oversized_frames: u64,
+    /// Data frames dropped by `Config::max_frames_per_sec`
+    pub rate_limited_frames: u64,
+    /// Messages with trailing fields unknown to this SDK, skipped in
+    /// `Config::forward_compat` mode
+    pub unknown_field_messages: u64,
+    /// Latest heartbeat from the server
+    pub last_heartbeat: Option<Heartbeat>,
+    /// Time since `last_heartbeat` arrived
+    pub since_last_heartbeat: Option<Duration>,
+    /// Successful reconnects
+    pub reconnects: u64,
+    /// Delay before the next reconnect attempt, while reconnecting
+    pub reconnect_backoff: Option<Duration>,
+    /// Messages dropped by full dispatch queues
+    pub dropped: DroppedMessages,
+}
+
+impl ClientStats {
+    /// Decoded messages of one type, e.g. `"pool_update"`.
+    pub fn messages_of(&self, kind: &str) -> u64 {
+        self.messages.get(kind).copied().unwrap_or(0)
+    }
+}
+
+/// Counters behind [`ClientStats`].
+#[derive(Debug, Default)]
+pub(crate) struct StatsCounters {
+    bytes_received: AtomicU64,
+    frames_received: AtomicU64,
+    decode_errors: AtomicU64,
+    oversized_frames: AtomicU64,
+    rate_limited_frames: AtomicU64,
+    unknown_field_messages: AtomicU64,
+    reconnects: AtomicU64,
+    messages: Mutex<BTreeMap<&'static str, u64>>,
+    last_heartbeat: Mutex<Option<(Heartbeat, Instant)>>,
+    reconnect_backoff: Mutex<Option<Duration>>,
+}
+
+impl StatsCounters {
+    pub(crate) fn frame_received(&self, len: usize) {
+        self.frames_received.fetch_add(1, Ordering::Relaxed);
+        self.bytes_received.fetch_add(len as u64, Ordering::Relaxed);
+    }
+
+    pub(crate) fn message_decoded(&self, decoded: &DecodedMessage) {
+        *self.messages.lock().unwrap().entry(decoded.kind()).or_insert(0) += 1;
+        if let DecodedMessage::Heartbeat(heartbeat) = decoded {
+            *self.last_heartbeat.lock().unwrap() = Some((*heartbeat, Instant::now()));
+        }
+    }
+
+    pub(crate) fn decode_error(&self) {
+        self.decode_errors.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn oversized_frame(&self) {
+        self.oversized_frames.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn rate_limited_frame(&self) {
+        self.rate_limited_frames.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn unknown_fields(&self) {
+        self.unknown_field_messages.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Waiting `delay` before the next reconnect attempt.
+    pub(crate) fn backing_off(&self, delay: Duration) {
+        *self.reconnect_backoff.lock().unwrap() = Some(delay);
+    }
+
+    pub(crate) fn reconnected(&self) {
+        self.reconnects.fetch_add(1, Ordering::Relaxed);
+        *self.reconnect_backoff.lock().unwrap() = None;
+    }
+
+    pub(crate) fn snapshot(&self, dropped: DroppedMessages) -> ClientStats {
+        let last_heartbeat = *self.last_heartbeat.lock().unwrap();
+        ClientStats {
+            bytes_received: self.bytes_received.load(Ordering::Relaxed),
+            frames_received: self.frames_received.load(Ordering::Relaxed),
+            messages: self.messages.lock().unwrap().clone(),
+            decode_errors: self.decode_errors.load(Ordering::Relaxed),
+            oversized_frames: self.oversized_frames.load(Ordering::Relaxed),
+            rate_limited_frames: self.rate_limited_frames.load(Ordering::Relaxed),
+            unknown_field_messages: self.unknown_field_messages.load(Ordering::Relaxed),
+            last_heartbeat: last_heartbeat.map(|(heartbeat, _)| heartbeat),
+            since_last_heartbeat: last_heartbeat.map(|(_, at)| at.elapsed()),
+            reconnects: self.reconnects.load(Ordering::Relaxed),
+            reconnect_backoff: *self.reconnect_backoff.lock().unwrap(),
+            dropped,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::replay::FrameKind;
+    use crate::types::PoolUpdate;
+    use crate::ws::{encode_pool_update, Config, K256WebSocketClient};
+
+    #[tokio::test]
+    async fn test_client_counts_frames_and_messages() {
+        let client = K256WebSocketClient::new(Config::default());
+        let update = encode_pool_update(&PoolUpdate::default());
+        client.handle_frame(FrameKind::Binary, &update).await;
+        client.handle_frame(FrameKind::Binary, &update).await;
+        client.handle_frame(FrameKind::Binary, &[0x01, 0x02]).await;
+        let heartbeat = br#"{"type":"heartbeat","timestamp_ms":5,"uptime_seconds":60}"#;
+        client.handle_frame(FrameKind::Text, heartbeat).await;
+
+        let stats = client.stats();
+        assert_eq!(stats.frames_received, 4);
+        assert_eq!(stats.bytes_received, 2 * update.len() as u64 + 2 + heartbeat.len() as u64);
+        assert_eq!(stats.messages_of("pool_update"), 2);
+        assert_eq!(stats.messages_of("heartbeat"), 1);
+        assert_eq!(stats.decode_errors, 1);
+        assert_eq!(stats.last_heartbeat.map(|hb| hb.uptime_seconds), Some(60));
+        assert!(stats.since_last_heartbeat.is_some());
+        assert_eq!((stats.reconnects, stats.reconnect_backoff), (0, None));
+    }
+}
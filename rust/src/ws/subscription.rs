@@ -0,0 +1,276 @@
+//! Typed channels and subscription request builder.
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::error::K256Error;
+use crate::types::Protocol;
+use crate::utils::is_valid_pubkey;
+use crate::ws::message::SubscribeRequest;
+
+/// WebSocket subscription channel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Channel {
+    /// Pool state updates (`"pools"`)
+    Pools,
+    /// Fee market updates (`"priority_fees"`)
+    PriorityFees,
+    /// Recent blockhash (`"blockhash"`)
+    Blockhash,
+}
+
+impl Channel {
+    /// All channels.
+    pub const ALL: [Channel; 3] = [Channel::Pools, Channel::PriorityFees, Channel::Blockhash];
+
+    /// Wire name of the channel.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Pools => "pools",
+            Self::PriorityFees => "priority_fees",
+            Self::Blockhash => "blockhash",
+        }
+    }
+}
+
+impl fmt::Display for Channel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Channel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|c| c.as_str() == s)
+            .ok_or_else(|| s.to_string())
+    }
+}
+
+/// Builder for [`SubscribeRequest`].
+///
+/// ```rust
+/// use k256_sdk::SubscribeRequest;
+///
+/// let request = SubscribeRequest::builder()
+///     .pools()
+///     .priority_fees()
+///     .protocols(["RaydiumClmm", "Whirlpool"])
+///     .token_pair(
+///         "So11111111111111111111111111111111111111112",
+///         "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v",
+///     )
+///     .binary()
+///     .build()
+///     .unwrap();
+/// assert_eq!(request.channels, vec!["pools", "priority_fees"]);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct SubscribeRequestBuilder {
+    channels: Vec<Channel>,
+    format: Option<String>,
+    protocols: Vec<Protocol>,
+    pools: Vec<String>,
+    token_pairs: Vec<(String, String)>,
+    fee_accounts: Vec<String>,
+    min_liquidity_usd: Option<u64>,
+    snapshot: bool,
+}
+
+impl SubscribeRequest {
+    /// Start building a subscription request with no channels selected.
+    pub fn builder() -> SubscribeRequestBuilder {
+        SubscribeRequestBuilder::default()
+    }
+}
+
+impl SubscribeRequestBuilder {
+    /// Add a channel.
+    pub fn channel(mut self, channel: Channel) -> Self {
+        if !self.channels.contains(&channel) {
+            self.channels.push(channel);
+        }
+        self
+    }
+
+    /// Add the `pools` channel.
+    pub fn pools(self) -> Self {
+        self.channel(Channel::Pools)
+    }
+
+    /// Add the `priority_fees` channel.
+    pub fn priority_fees(self) -> Self {
+        self.channel(Channel::PriorityFees)
+    }
+
+    /// Add the `blockhash` channel.
+    pub fn blockhash(self) -> Self {
+        self.channel(Channel::Blockhash)
+    }
+
+    /// Filter pool updates to the given DEX protocols.
+    ///
+    /// Accepts [`Protocol`]s or their wire names.
+    pub fn protocols<I, S>(mut self, protocols: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<Protocol>,
+    {
+        self.protocols.extend(protocols.into_iter().map(Into::into));
+        self
+    }
+
+    /// Filter pool updates to the given pool addresses.
+    pub fn pool_addresses<I, S>(mut self, pools: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.pools.extend(pools.into_iter().map(Into::into));
+        self
+    }
+
+    /// Filter pool updates to pools trading the given token pair.
+    pub fn token_pair(mut self, mint_a: impl Into<String>, mint_b: impl Into<String>) -> Self {
+        self.token_pairs.push((mint_a.into(), mint_b.into()));
+        self
+    }
+
+    /// Filter fee market data to the given writable accounts.
+    pub fn fee_accounts<I, S>(mut self, accounts: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.fee_accounts.extend(accounts.into_iter().map(Into::into));
+        self
+    }
+
+    /// Only deliver pools worth at least `usd` US dollars.
+    ///
+    /// Filtered server-side where supported, else by the client using the
+    /// price feed (see [`SubscribeRequest::min_liquidity_usd`]).
+    pub fn min_liquidity_usd(mut self, usd: u64) -> Self {
+        self.min_liquidity_usd = Some(usd);
+        self
+    }
+
+    /// Request a snapshot of current pool states right after subscribing.
+    pub fn snapshot(mut self, snapshot: bool) -> Self {
+        self.snapshot = snapshot;
+        self
+    }
+
+    /// Receive binary messages (the default).
+    pub fn binary(mut self) -> Self {
+        self.format = Some("binary".to_string());
+        self
+    }
+
+    /// Receive JSON messages.
+    pub fn json(mut self) -> Self {
+        self.format = Some("json".to_string());
+        self
+    }
+
+    /// Validate the filters and build the request.
+    ///
+    /// Fails if no channel was selected or a pool address, token mint or fee
+    /// account is not a valid pubkey.
+    pub fn build(self) -> Result<SubscribeRequest, K256Error> {
+        if self.channels.is_empty() {
+            return Err(K256Error::NoChannels);
+        }
+
+        let addresses = self
+            .pools
+            .iter()
+            .chain(self.token_pairs.iter().flat_map(|(a, b)| [a, b]))
+            .chain(&self.fee_accounts);
+        if let Some(invalid) = addresses.into_iter().find(|a| !is_valid_pubkey(a)) {
+            return Err(K256Error::InvalidPubkey(invalid.clone()));
+        }
+
+        Ok(SubscribeRequest {
+            channels: self.channels.iter().map(|c| c.as_str().to_string()).collect(),
+            format: self.format,
+            protocols: (!self.protocols.is_empty()).then_some(self.protocols),
+            pools: (!self.pools.is_empty()).then_some(self.pools),
+            token_pairs: (!self.token_pairs.is_empty()).then_some(self.token_pairs),
+            fee_accounts: (!self.fee_accounts.is_empty()).then_some(self.fee_accounts),
+            min_liquidity_usd: self.min_liquidity_usd,
+            snapshot: self.snapshot.then_some(true),
+            ..SubscribeRequest::default()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOL: &str = "So11111111111111111111111111111111111111112";
+    const USDC: &str = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+
+    #[test]
+    fn test_build_request() {
+        let request = SubscribeRequest::builder()
+            .pools()
+            .blockhash()
+            .pools()
+            .pool_addresses([SOL])
+            .token_pair(SOL, USDC)
+            .json()
+            .build()
+            .unwrap();
+        assert_eq!(request.request_type, "subscribe");
+        assert_eq!(request.channels, vec!["pools", "blockhash"]);
+        assert_eq!(request.format.as_deref(), Some("json"));
+        assert_eq!(request.protocols, None);
+        assert_eq!(request.pools, Some(vec![SOL.to_string()]));
+        assert_eq!(request.token_pairs, Some(vec![(SOL.to_string(), USDC.to_string())]));
+        assert_eq!(request.snapshot, None);
+        assert_eq!(request.fee_accounts, None);
+        assert_eq!(request.min_liquidity_usd, None);
+
+        let request = SubscribeRequest::builder()
+            .pools()
+            .snapshot(true)
+            .fee_accounts([USDC])
+            .min_liquidity_usd(10_000)
+            .build()
+            .unwrap();
+        let json = serde_json::to_value(&request).unwrap();
+        assert_eq!(json["snapshot"], true);
+        assert_eq!(json["min_liquidity_usd"], 10_000);
+        assert_eq!(json["fee_accounts"][0], USDC);
+    }
+
+    #[test]
+    fn test_build_rejects_invalid_input() {
+        assert!(matches!(SubscribeRequest::builder().build(), Err(K256Error::NoChannels)));
+        assert!(matches!(
+            SubscribeRequest::builder().pools().pool_addresses(["pool"]).build(),
+            Err(K256Error::InvalidPubkey(addr)) if addr == "pool"
+        ));
+        assert!(matches!(
+            SubscribeRequest::builder().pools().token_pair(SOL, "USDC").build(),
+            Err(K256Error::InvalidPubkey(_))
+        ));
+        assert!(matches!(
+            SubscribeRequest::builder().priority_fees().fee_accounts(["acct"]).build(),
+            Err(K256Error::InvalidPubkey(addr)) if addr == "acct"
+        ));
+    }
+
+    #[test]
+    fn test_channel_names() {
+        for channel in Channel::ALL {
+            assert_eq!(channel.as_str().parse::<Channel>(), Ok(channel));
+        }
+        assert!("pool".parse::<Channel>().is_err());
+    }
+}
@@ -0,0 +1,216 @@
+//! Reverse index from token vaults to their pools.
+//!
+//! Writable accounts in fee market updates and raw account writes are often
+//! a pool's token vaults rather than the pool itself. [`VaultIndex`] maps
+//! each vault from [`Pool`] metadata (see `api::ApiClient::list_pools`)
+//! back to its pool and venue.
+
+use std::collections::HashMap;
+
+use crate::types::{AccountFee, FeeMarket, Pool, Protocol, Pubkey};
+
+/// Pool owning a token vault.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VaultOwner {
+    /// Pool address
+    pub pool: Pubkey,
+    /// DEX protocol of the pool
+    pub protocol: Protocol,
+    /// Mint held by the vault
+    pub mint: Pubkey,
+}
+
+/// Token vault address → owning pool.
+///
+/// ```rust
+/// use k256_sdk::ws::VaultIndex;
+/// # fn example(pools: Vec<k256_sdk::Pool>, fees: &k256_sdk::FeeMarket) {
+/// let index: VaultIndex = pools.into_iter().collect();
+/// for (account, owner) in index.fee_accounts(fees) {
+///     println!("{} p75={} on {} vault of {}", owner.protocol, account.p75, owner.mint, owner.pool);
+/// }
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct VaultIndex {
+    vaults: HashMap<Pubkey, VaultOwner>,
+    /// Vaults per pool, for removal
+    pools: HashMap<Pubkey, [Pubkey; 2]>,
+}
+
+impl VaultIndex {
+    /// Create an empty index.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Index both vaults of `pool`, replacing any previous entry for it.
+    pub fn insert(&mut self, pool: &Pool) {
+        self.remove_pool(&pool.address);
+        for (vault, mint) in [
+            (pool.token_a_vault, pool.token_a_mint),
+            (pool.token_b_vault, pool.token_b_mint),
+        ] {
+            let owner = VaultOwner {
+                pool: pool.address,
+                protocol: pool.protocol.clone(),
+                mint,
+            };
+            self.vaults.insert(vault, owner);
+        }
+        self.pools.insert(pool.address, [pool.token_a_vault, pool.token_b_vault]);
+    }
+
+    /// Drop the vaults of the pool at `pool_address`.
+    ///
+    /// Returns whether the pool was indexed.
+    pub fn remove_pool(&mut self, pool_address: &Pubkey) -> bool {
+        let Some(vaults) = self.pools.remove(pool_address) else {
+            return false;
+        };
+        for vault in vaults {
+            // A vault re-indexed under another pool keeps that owner
+            if self.vaults.get(&vault).is_some_and(|owner| owner.pool == *pool_address) {
+                self.vaults.remove(&vault);
+            }
+        }
+        true
+    }
+
+    /// Pool owning `vault`.
+    pub fn get(&self, vault: &Pubkey) -> Option<&VaultOwner> {
+        self.vaults.get(vault)
+    }
+
+    /// Address of the pool owning `vault`.
+    pub fn pool_of(&self, vault: &Pubkey) -> Option<&Pubkey> {
+        self.get(vault).map(|owner| &owner.pool)
+    }
+
+    /// Accounts of a fee market update that are indexed vaults, with their owners.
+    pub fn fee_accounts<'a>(
+        &'a self,
+        fees: &'a FeeMarket,
+    ) -> impl Iterator<Item = (&'a AccountFee, &'a VaultOwner)> + 'a {
+        fees.accounts
+            .iter()
+            .filter_map(|account| Some((account, self.get(&account.pubkey)?)))
+    }
+
+    /// Number of indexed vaults.
+    pub fn len(&self) -> usize {
+        self.vaults.len()
+    }
+
+    /// Whether no vault is indexed.
+    pub fn is_empty(&self) -> bool {
+        self.vaults.is_empty()
+    }
+}
+
+impl FromIterator<Pool> for VaultIndex {
+    fn from_iter<I: IntoIterator<Item = Pool>>(pools: I) -> Self {
+        let mut index = Self::new();
+        index.extend(pools);
+        index
+    }
+}
+
+impl Extend<Pool> for VaultIndex {
+    fn extend<I: IntoIterator<Item = Pool>>(&mut self, pools: I) {
+        for pool in pools {
+            self.insert(&pool);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{NetworkState, Slot, TimestampMs};
+
+    fn pool(key: u8, protocol: Protocol) -> Pool {
+        Pool {
+            address: Pubkey::new([key; 32]),
+            protocol,
+            token_a_mint: Pubkey::new([1; 32]),
+            token_b_mint: Pubkey::new([2; 32]),
+            token_a_vault: Pubkey::new([key + 10; 32]),
+            token_b_vault: Pubkey::new([key + 20; 32]),
+            fee_rate: 25,
+        }
+    }
+
+    fn account_fee(key: u8) -> AccountFee {
+        AccountFee {
+            pubkey: Pubkey::new([key; 32]),
+            total_txs: 1,
+            active_slots: 1,
+            cu_consumed: 0,
+            utilization_pct: 0.0,
+            p25: 0,
+            p50: 0,
+            p75: 100,
+            p90: 0,
+            min_nonzero_price: 0,
+        }
+    }
+
+    #[test]
+    fn test_vaults_map_to_pools() {
+        let mut index: VaultIndex = [pool(3, Protocol::Whirlpool), pool(4, Protocol::RaydiumClmm)]
+            .into_iter()
+            .collect();
+        assert_eq!(index.len(), 4);
+
+        let owner = index.get(&Pubkey::new([23; 32])).unwrap();
+        assert_eq!(owner.pool, Pubkey::new([3; 32]));
+        assert_eq!(owner.protocol, Protocol::Whirlpool);
+        assert_eq!(owner.mint, Pubkey::new([2; 32]));
+        assert_eq!(index.pool_of(&Pubkey::new([14; 32])), Some(&Pubkey::new([4; 32])));
+        assert_eq!(index.pool_of(&Pubkey::new([3; 32])), None);
+
+        assert!(index.remove_pool(&Pubkey::new([3; 32])));
+        assert!(!index.remove_pool(&Pubkey::new([3; 32])));
+        assert_eq!(index.len(), 2);
+        assert!(index.get(&Pubkey::new([13; 32])).is_none());
+    }
+
+    #[test]
+    fn test_reinsert_replaces_vaults() {
+        let mut index = VaultIndex::new();
+        let mut moved = pool(3, Protocol::Whirlpool);
+        index.insert(&moved);
+        moved.token_a_vault = Pubkey::new([99; 32]);
+        index.insert(&moved);
+        assert_eq!(index.len(), 2);
+        assert!(index.get(&Pubkey::new([13; 32])).is_none());
+        assert_eq!(index.pool_of(&Pubkey::new([99; 32])), Some(&Pubkey::new([3; 32])));
+    }
+
+    #[test]
+    fn test_fee_accounts_attribution() {
+        let index: VaultIndex = [pool(3, Protocol::Whirlpool)].into_iter().collect();
+        let fees = FeeMarket {
+            slot: Slot(1),
+            timestamp_ms: TimestampMs(0),
+            recommended: 100,
+            state: NetworkState::Normal,
+            is_stale: false,
+            block_utilization_pct: 0.0,
+            blocks_in_window: 1,
+            accounts: vec![account_fee(13), account_fee(50), account_fee(23)],
+        };
+        let attributed: Vec<_> = index
+            .fee_accounts(&fees)
+            .map(|(account, owner)| (account.pubkey, owner.mint))
+            .collect();
+        assert_eq!(
+            attributed,
+            [
+                (Pubkey::new([13; 32]), Pubkey::new([1; 32])),
+                (Pubkey::new([23; 32]), Pubkey::new([2; 32])),
+            ]
+        );
+    }
+}
@@ -0,0 +1,283 @@
+//! Browser WebSocket client for `wasm32` targets (feature `wasm`).
+//!
+//! [`K256WasmClient`] runs on the browser's WebSocket through `gloo-net`
+//! and feeds frames through the same decoders and [`DecodedMessage`] types
+//! as the native client. Browsers cannot set handshake headers, so the API
+//! key is always sent as the `apiKey` query parameter. There is no automatic
+//! reconnect; reconnect from [`on_close`](K256WasmClient::on_close).
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use futures_util::{SinkExt, StreamExt};
+use gloo_net::websocket::futures::WebSocket;
+use gloo_net::websocket::Message;
+use thiserror::Error;
+use tokio::sync::mpsc;
+
+use crate::types::{Blockhash, FeeMarket, PoolUpdate, PriceEntry, Quote};
+use crate::ws::decoder::{decode_json_message, decode_message_with_limits, DecodeLimits};
+use crate::ws::encoder::{encode_message, ClientMessage};
+use crate::ws::message::{DecodedMessage, SubscribeRequest};
+use crate::ws::metrics;
+
+/// Browser client error types.
+#[derive(Debug, Error)]
+pub enum WasmError {
+    /// The browser refused to open the socket
+    #[error("Failed to open WebSocket: {0}")]
+    Open(String),
+
+    /// [`K256WasmClient::connect`] has not succeeded, or the socket closed
+    #[error("Not connected")]
+    NotConnected,
+
+    /// A request could not be serialized
+    #[error("Failed to encode request: {0}")]
+    Encode(#[from] serde_json::Error),
+}
+
+/// Configuration for [`K256WasmClient`].
+#[derive(Debug, Clone)]
+pub struct WasmConfig {
+    /// K256 API key
+    pub api_key: String,
+    /// WebSocket endpoint URL
+    pub endpoint: String,
+    /// Size limits applied to incoming binary frames
+    pub decode_limits: DecodeLimits,
+}
+
+impl Default for WasmConfig {
+    fn default() -> Self {
+        Self {
+            api_key: String::new(),
+            endpoint: "wss://gateway.k256.xyz/v1/ws".to_string(),
+            decode_limits: DecodeLimits::default(),
+        }
+    }
+}
+
+type Callback<T> = RefCell<Option<Box<dyn Fn(T)>>>;
+
+#[derive(Default)]
+struct Callbacks {
+    on_message: Callback<DecodedMessage>,
+    on_pool_update: Callback<PoolUpdate>,
+    on_fee_market: Callback<FeeMarket>,
+    on_blockhash: Callback<Blockhash>,
+    on_quote: Callback<Quote>,
+    on_price_update: Callback<PriceEntry>,
+    on_error: Callback<String>,
+    on_close: Callback<String>,
+}
+
+/// K256 WebSocket client for browsers.
+///
+/// ```rust,ignore
+/// use k256_sdk::ws::{K256WasmClient, WasmConfig};
+/// use k256_sdk::SubscribeRequest;
+///
+/// let client = K256WasmClient::new(WasmConfig {
+///     api_key: "your-api-key".to_string(),
+///     ..Default::default()
+/// });
+/// client.on_pool_update(|update| web_sys::console::log_1(&update.protocol_name.as_str().into()));
+/// client.connect()?;
+/// client.subscribe(&SubscribeRequest::default())?;
+/// ```
+pub struct K256WasmClient {
+    config: WasmConfig,
+    callbacks: Rc<Callbacks>,
+    outbound: RefCell<Option<mpsc::UnboundedSender<Message>>>,
+}
+
+impl K256WasmClient {
+    /// Create a client; call [`connect`](Self::connect) to open the socket.
+    pub fn new(config: WasmConfig) -> Self {
+        Self {
+            config,
+            callbacks: Rc::new(Callbacks::default()),
+            outbound: RefCell::new(None),
+        }
+    }
+
+    /// Register a callback receiving every decoded message.
+    pub fn on_message(&self, callback: impl Fn(DecodedMessage) + 'static) {
+        *self.callbacks.on_message.borrow_mut() = Some(Box::new(callback));
+    }
+
+    /// Register a callback for pool updates, including batch and snapshot entries.
+    pub fn on_pool_update(&self, callback: impl Fn(PoolUpdate) + 'static) {
+        *self.callbacks.on_pool_update.borrow_mut() = Some(Box::new(callback));
+    }
+
+    /// Register a callback for fee market updates.
+    pub fn on_fee_market(&self, callback: impl Fn(FeeMarket) + 'static) {
+        *self.callbacks.on_fee_market.borrow_mut() = Some(Box::new(callback));
+    }
+
+    /// Register a callback for blockhash updates.
+    pub fn on_blockhash(&self, callback: impl Fn(Blockhash) + 'static) {
+        *self.callbacks.on_blockhash.borrow_mut() = Some(Box::new(callback));
+    }
+
+    /// Register a callback for quote updates.
+    pub fn on_quote(&self, callback: impl Fn(Quote) + 'static) {
+        *self.callbacks.on_quote.borrow_mut() = Some(Box::new(callback));
+    }
+
+    /// Register a callback for price updates, including batch and snapshot entries.
+    pub fn on_price_update(&self, callback: impl Fn(PriceEntry) + 'static) {
+        *self.callbacks.on_price_update.borrow_mut() = Some(Box::new(callback));
+    }
+
+    /// Register a callback for server and decode errors.
+    pub fn on_error(&self, callback: impl Fn(String) + 'static) {
+        *self.callbacks.on_error.borrow_mut() = Some(Box::new(callback));
+    }
+
+    /// Register a callback fired once when the socket closes.
+    pub fn on_close(&self, callback: impl Fn(String) + 'static) {
+        *self.callbacks.on_close.borrow_mut() = Some(Box::new(callback));
+    }
+
+    /// Open the socket and start delivering messages.
+    ///
+    /// Returns once the browser accepted the socket; messages sent before the
+    /// handshake completes are queued.
+    pub fn connect(&self) -> Result<(), WasmError> {
+        let url = format!(
+            "{}?apiKey={}",
+            self.config.endpoint,
+            urlencoding::encode(&self.config.api_key)
+        );
+        let socket = WebSocket::open(&url).map_err(|e| WasmError::Open(e.to_string()))?;
+        let (mut write, mut read) = socket.split();
+
+        let (tx, mut rx) = mpsc::unbounded_channel::<Message>();
+        *self.outbound.borrow_mut() = Some(tx);
+        wasm_bindgen_futures::spawn_local(async move {
+            while let Some(msg) = rx.recv().await {
+                if write.send(msg).await.is_err() {
+                    break;
+                }
+            }
+        });
+
+        let callbacks = self.callbacks.clone();
+        let limits = self.config.decode_limits;
+        wasm_bindgen_futures::spawn_local(async move {
+            let reason = loop {
+                let decoded = match read.next().await {
+                    Some(Ok(Message::Bytes(data))) => {
+                        metrics::bytes_received(data.len());
+                        match data.split_first() {
+                            Some((&msg_type, payload)) => decode_message_with_limits(msg_type, payload, &limits)
+                                .map_err(|e| e.to_string()),
+                            None => Ok(None),
+                        }
+                    }
+                    Some(Ok(Message::Text(text))) => {
+                        metrics::bytes_received(text.len());
+                        decode_json_message(&text).map_err(|e| e.to_string())
+                    }
+                    Some(Err(e)) => break e.to_string(),
+                    None => break "closed by server".to_string(),
+                };
+                match decoded {
+                    Ok(Some(decoded)) => {
+                        metrics::message_decoded(decoded.kind());
+                        callbacks.dispatch(decoded)
+                    }
+                    Ok(None) => {}
+                    Err(e) => {
+                        metrics::decode_error();
+                        callbacks.error(e)
+                    }
+                }
+            };
+            if let Some(cb) = callbacks.on_close.borrow().as_ref() {
+                cb(reason);
+            }
+        });
+        Ok(())
+    }
+
+    /// Subscribe to channels.
+    pub fn subscribe(&self, request: &SubscribeRequest) -> Result<(), WasmError> {
+        self.send(Message::Text(serde_json::to_string(request)?))
+    }
+
+    /// Unsubscribe from all channels.
+    pub fn unsubscribe(&self) -> Result<(), WasmError> {
+        self.send(Message::Text(r#"{"type":"unsubscribe"}"#.to_string()))
+    }
+
+    /// Send a binary client message (e.g. a quote or price subscription).
+    pub fn send_message(&self, msg: &ClientMessage) -> Result<(), WasmError> {
+        self.send(Message::Bytes(encode_message(msg)))
+    }
+
+    fn send(&self, msg: Message) -> Result<(), WasmError> {
+        let outbound = self.outbound.borrow();
+        let tx = outbound.as_ref().ok_or(WasmError::NotConnected)?;
+        tx.send(msg).map_err(|_| WasmError::NotConnected)
+    }
+}
+
+impl Callbacks {
+    fn dispatch(&self, decoded: DecodedMessage) {
+        if let Some(cb) = self.on_message.borrow().as_ref() {
+            cb(decoded.clone());
+        }
+        match decoded {
+            DecodedMessage::PoolUpdate(update) => self.pool_updates(vec![update]),
+            DecodedMessage::PoolUpdateBatch(updates) | DecodedMessage::PoolSnapshot(updates) => {
+                self.pool_updates(updates)
+            }
+            DecodedMessage::FeeMarket(fees) => {
+                if let Some(cb) = self.on_fee_market.borrow().as_ref() {
+                    cb(fees);
+                }
+            }
+            DecodedMessage::Blockhash(bh) => {
+                if let Some(cb) = self.on_blockhash.borrow().as_ref() {
+                    cb(bh);
+                }
+            }
+            DecodedMessage::Quote(quote) => {
+                if let Some(cb) = self.on_quote.borrow().as_ref() {
+                    cb(quote);
+                }
+            }
+            DecodedMessage::PriceUpdate(entry) => self.price_updates(vec![entry]),
+            DecodedMessage::PriceBatch(entries) | DecodedMessage::PriceSnapshot(entries) => {
+                self.price_updates(entries)
+            }
+            DecodedMessage::Error(err) => self.error(err),
+            DecodedMessage::Heartbeat(_)
+            | DecodedMessage::Pong { .. }
+            | DecodedMessage::Subscribed { .. }
+            | DecodedMessage::ReplayComplete(_) => {}
+        }
+    }
+
+    fn pool_updates(&self, updates: Vec<PoolUpdate>) {
+        if let Some(cb) = self.on_pool_update.borrow().as_ref() {
+            updates.into_iter().for_each(cb);
+        }
+    }
+
+    fn price_updates(&self, entries: Vec<PriceEntry>) {
+        if let Some(cb) = self.on_price_update.borrow().as_ref() {
+            entries.into_iter().for_each(cb);
+        }
+    }
+
+    fn error(&self, err: String) {
+        if let Some(cb) = self.on_error.borrow().as_ref() {
+            cb(err);
+        }
+    }
+}
@@ -0,0 +1,749 @@
+//! Client behaviour against the in-process [`MockGateway`].
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use k256_sdk::testing::{frames, MockGateway, MockSession};
+use k256_sdk::transport::{BoxFuture, Frame, Transport};
+use k256_sdk::ws::{
+    async_callback, ActivityConfig, Channel, ClientMessage, DecodeLimits, DispatchConfig, DropPolicy, LimitPolicy,
+    QuoteRequest, UnknownFields, FEATURE_MIN_LIQUIDITY,
+};
+use k256_sdk::{
+    Blockhash, Config, ConnectionState, FeeMarket, K256Error, K256WebSocketClient, NetworkState, PoolUpdate,
+    PriceEntry, Protocol, Pubkey, ReplayComplete, Slot, SubscribeRequest, TimestampMs, PROTOCOL_VERSION,
+};
+use tokio::sync::mpsc;
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+fn config() -> Config {
+    Config {
+        endpoint: "mock://gateway".to_string(),
+        reconnect_delay_initial: Duration::from_millis(1),
+        ping_interval: Duration::ZERO,
+        ..Config::default()
+    }
+}
+
+/// Client connected to a fresh gateway, with the session it opened.
+async fn connected_client(config: Config) -> (MockGateway, K256WebSocketClient, MockSession) {
+    let gateway = MockGateway::new();
+    let client = K256WebSocketClient::with_transport(config, gateway.transport());
+    client.connect().await.unwrap();
+    let session = gateway.accept().await;
+    (gateway, client, session)
+}
+
+/// [`connected_client`] after the default subscription reached the gateway.
+async fn subscribed_client(config: Config) -> (MockGateway, K256WebSocketClient, MockSession) {
+    let (gateway, client, mut session) = connected_client(config).await;
+    client.subscribe(SubscribeRequest::default()).await.unwrap();
+    session.next_subscription().await.unwrap();
+    (gateway, client, session)
+}
+
+/// Blockhash frame for `slot`.
+fn blockhash(slot: u64) -> Vec<u8> {
+    frames::blockhash(&Blockhash {
+        slot: Slot(slot),
+        blockhash: bs58::encode([1u8; 32]).into_string(),
+        ..Blockhash::default()
+    })
+}
+
+/// Fee market frame for `slot`.
+fn fee_market(slot: u64) -> Vec<u8> {
+    frames::fee_market(&FeeMarket {
+        slot: Slot(slot),
+        timestamp_ms: TimestampMs(0),
+        recommended: 1000,
+        state: NetworkState::Normal,
+        is_stale: false,
+        block_utilization_pct: 50.0,
+        blocks_in_window: 10,
+        accounts: Vec::new(),
+    })
+}
+
+/// Two-token AMM pool update with the given balances.
+fn amm_update(slot: u64, balances: [u64; 2]) -> PoolUpdate {
+    PoolUpdate {
+        slot: Slot(slot),
+        protocol_name: Protocol::RaydiumAmm,
+        token_mints: vec![Pubkey::new([1; 32]), Pubkey::new([2; 32])],
+        token_balances: balances.to_vec(),
+        token_decimals: vec![9, 6],
+        ..PoolUpdate::default()
+    }
+}
+
+#[tokio::test]
+async fn test_scripted_frames_reach_callbacks() {
+    let (_gateway, client, mut session) = connected_client(config()).await;
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let fees_tx = tx.clone();
+    client.on_blockhash(move |bh| tx.send(bh.slot.get()).unwrap()).detach();
+    client.on_fee_market(move |fees| fees_tx.send(fees.slot.get()).unwrap()).detach();
+
+    assert_eq!(session.endpoint(), "mock://gateway");
+    let confirmed = client
+        .subscribe_and_wait(SubscribeRequest::default(), Duration::from_secs(1))
+        .await
+        .unwrap();
+    assert_eq!(confirmed, SubscribeRequest::default().channels);
+    assert_eq!(session.next_subscription().await, Some(SubscribeRequest::default()));
+    assert_eq!(session.subscription(), Some(SubscribeRequest::default()));
+
+    session.send(blockhash(7));
+    session.send(fee_market(8));
+    assert_eq!(rx.recv().await, Some(7));
+    assert_eq!(rx.recv().await, Some(8));
+}
+
+#[tokio::test]
+async fn test_async_callback_on_dispatch_threads() {
+    let config = Config {
+        dispatch: Some(DispatchConfig::new(16, DropPolicy::Block)),
+        ..config()
+    };
+    let (_gateway, client, session) = connected_client(config).await;
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let handler = async_callback(1, move |bh: Blockhash| {
+        let tx = tx.clone();
+        async move { tx.send(bh.slot.get()).unwrap() }
+    });
+    client.on_blockhash(handler).detach();
+
+    session.send(blockhash(1));
+    session.send(blockhash(2));
+    assert_eq!(rx.recv().await, Some(1));
+    assert_eq!(rx.recv().await, Some(2));
+}
+
+#[tokio::test]
+async fn test_manual_confirmation_and_rejection() {
+    let (gateway, client, session) = connected_client(config()).await;
+    gateway.set_auto_confirm(false);
+
+    let wait = client.subscribe_and_wait(SubscribeRequest::default(), Duration::from_secs(1));
+    let reject = async {
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        session.send(frames::error("channel not allowed"));
+    };
+    let (result, ()) = tokio::join!(wait, reject);
+    assert!(result.unwrap_err().to_string().contains("channel not allowed"));
+}
+
+#[tokio::test]
+async fn test_subscribe_answers_are_correlated() {
+    let (gateway, client, session) = connected_client(config()).await;
+    gateway.set_auto_confirm(false);
+
+    let request = |channel: &str| SubscribeRequest {
+        channels: vec![channel.to_string()],
+        ..SubscribeRequest::default()
+    };
+    let pools = client.subscribe_and_wait(request("pools"), Duration::from_secs(1));
+    let blockhash = client.subscribe_and_wait(request("blockhash"), Duration::from_secs(1));
+    let answer = async {
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        // Errors about other requests leave the waiters alone
+        session.send(frames::error("invalid quote request"));
+        session.send_text(r#"{"type":"subscribed","channels":["blockhash"]}"#);
+        session.send_text(r#"{"type":"subscribed","channels":["pools"]}"#);
+    };
+    let (pools, blockhash, ()) = tokio::join!(pools, blockhash, answer);
+    assert_eq!(pools.unwrap(), ["pools"]);
+    assert_eq!(blockhash.unwrap(), ["blockhash"]);
+}
+
+#[tokio::test]
+async fn test_request_quote() {
+    let (_gateway, client, session) = connected_client(config()).await;
+    let (tx, mut streamed) = mpsc::unbounded_channel();
+    client.on_quote(move |quote| tx.send(quote.out_amount).unwrap()).detach();
+
+    let (input_mint, output_mint) = (Pubkey::new([1; 32]), Pubkey::new([2; 32]));
+    let request = QuoteRequest {
+        input_mint,
+        output_mint,
+        amount: 1_000,
+        slippage_bps: 50,
+        swap_mode: None,
+    };
+    let quote = |out_amount: u64, request_id: Option<&str>| {
+        serde_json::json!({
+            "type": "quote",
+            "data": {
+                "inputMint": input_mint, "outputMint": output_mint, "inAmount": 1_000, "outAmount": out_amount,
+                "priceImpactPct": 0.1, "slot": 1, "timestampMs": 0, "routePlan": [], "requestId": request_id,
+            },
+        })
+        .to_string()
+    };
+    let answer = async {
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        session.send_text(quote(1, None));
+        session.send_text(quote(990, Some("q1")));
+    };
+    let (result, ()) = tokio::join!(client.request_quote(request.clone(), Duration::from_secs(1)), answer);
+    let answered = result.unwrap();
+    assert_eq!((answered.out_amount, answered.request_id.as_deref()), (990, Some("q1")));
+    // Only the streamed quote reaches the callbacks
+    assert_eq!(streamed.recv().await, Some(1));
+
+    let reject = async {
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        session.send_text(r#"{"type":"error","data":{"message":"no route","requestId":"q2"}}"#);
+    };
+    let (result, ()) = tokio::join!(client.request_quote(request.clone(), Duration::from_secs(1)), reject);
+    let error = result.unwrap_err();
+    assert!(matches!(error.downcast_ref::<K256Error>(), Some(K256Error::QuoteRejected(m)) if m == "no route"));
+
+    let error = client.request_quote(request, Duration::from_millis(20)).await.unwrap_err();
+    assert!(matches!(error.downcast_ref::<K256Error>(), Some(K256Error::Timeout(_))));
+    assert!(streamed.try_recv().is_err());
+}
+
+#[tokio::test]
+async fn test_pause_and_resume_channel() {
+    let (_gateway, client, session) = subscribed_client(config()).await;
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let fees_tx = tx.clone();
+    client.on_blockhash(move |bh| tx.send(bh.slot.get()).unwrap()).detach();
+    client.on_fee_market(move |fees| fees_tx.send(fees.slot.get()).unwrap()).detach();
+
+    client.pause_channel(Channel::Blockhash);
+    assert!(client.is_paused(Channel::Blockhash));
+    session.send(blockhash(1));
+    session.send(fee_market(2));
+    assert_eq!(rx.recv().await, Some(2));
+
+    client.resume_channel(Channel::Blockhash);
+    session.send(blockhash(3));
+    assert_eq!(rx.recv().await, Some(3));
+    assert_eq!(client.subscription(), Some(SubscribeRequest::default()));
+}
+
+#[tokio::test]
+async fn test_unsubscribe_channels_and_pools() {
+    let (_gateway, client, mut session) = connected_client(config()).await;
+    let request = SubscribeRequest {
+        channels: vec!["pools".to_string(), "blockhash".to_string()],
+        pools: Some(vec!["pool-a".to_string(), "pool-b".to_string()]),
+        ..SubscribeRequest::default()
+    };
+    client.subscribe(request).await.unwrap();
+    session.next_subscription().await.unwrap();
+
+    client.unsubscribe_pools(&["pool-a"]).await.unwrap();
+    let narrowed = session.next_subscription().await.unwrap();
+    assert_eq!(narrowed.pools, Some(vec!["pool-b".to_string()]));
+    assert_eq!(narrowed.channels, ["pools", "blockhash"]);
+
+    // The last pool takes the channel with it rather than widening the filter
+    client.unsubscribe_pools(&["pool-b"]).await.unwrap();
+    let narrowed = session.next_subscription().await.unwrap();
+    assert_eq!(narrowed.channels, ["blockhash"]);
+    assert_eq!(narrowed.pools, None);
+    assert_eq!(client.subscription(), Some(narrowed));
+    assert!(client.unsubscribe_pools(&["pool-b"]).await.is_err());
+
+    client.unsubscribe_channels(&[Channel::Blockhash]).await.unwrap();
+    assert!(matches!(session.recv().await, Some(ClientMessage::Unsubscribe)));
+    assert_eq!((client.subscription(), session.subscription()), (None, None));
+}
+
+#[tokio::test]
+async fn test_connect_and_request_timeouts() {
+    /// Transport whose handshake never completes.
+    struct Stalled;
+
+    impl Transport for Stalled {
+        fn connect<'a>(&'a mut self, _endpoint: &'a str) -> BoxFuture<'a, Result<(), BoxError>> {
+            Box::pin(std::future::pending())
+        }
+
+        fn send(&mut self, _frame: Frame) -> BoxFuture<'_, Result<(), BoxError>> {
+            Box::pin(async { Ok(()) })
+        }
+
+        fn receive(&mut self) -> BoxFuture<'_, Option<Result<Frame, BoxError>>> {
+            Box::pin(async { None })
+        }
+
+        fn close(&mut self) -> BoxFuture<'_, Result<(), BoxError>> {
+            Box::pin(async { Ok(()) })
+        }
+    }
+
+    let config = Config {
+        connect_timeout: Some(Duration::from_millis(20)),
+        request_timeout: Some(Duration::from_millis(20)),
+        ..config()
+    };
+    let client = K256WebSocketClient::with_transport(config, Stalled);
+    let err = client.connect().await.unwrap_err();
+    assert!(matches!(err.downcast_ref(), Some(K256Error::Timeout("connection handshake"))));
+    assert!(matches!(client.connection_state(), ConnectionState::Closed { .. }));
+
+    // Nothing is written without a connection
+    let err = client.subscribe(SubscribeRequest::default()).await.unwrap_err();
+    assert!(matches!(err.downcast_ref(), Some(K256Error::Timeout("subscribe request"))));
+}
+
+#[tokio::test]
+async fn test_request_timeout_while_reconnecting() {
+    let config = Config {
+        request_timeout: Some(Duration::from_millis(50)),
+        ..config()
+    };
+    let (gateway, client, session) = connected_client(config).await;
+    let (tx, mut states) = mpsc::unbounded_channel();
+    client.on_connection_state(move |state| tx.send(state).unwrap()).detach();
+
+    // The gateway stops taking connections after dropping this one
+    gateway.refuse_connections(usize::MAX);
+    session.close();
+    while !matches!(states.recv().await, Some(ConnectionState::Reconnecting { .. })) {}
+    let err = client.subscribe(SubscribeRequest::default()).await.unwrap_err();
+    assert!(matches!(err.downcast_ref(), Some(K256Error::Timeout("subscribe request"))));
+
+    // The subscription is restored once the gateway is back
+    gateway.refuse_connections(0);
+    let mut session = gateway.accept().await;
+    assert_eq!(session.next_subscription().await, Some(SubscribeRequest::default()));
+}
+
+#[tokio::test]
+async fn test_server_info() {
+    let (gateway, client, mut session) = connected_client(config()).await;
+    client.subscribe_and_wait(SubscribeRequest::default(), Duration::from_secs(5)).await.unwrap();
+    let request = session.next_subscription().await.unwrap();
+    assert_eq!(request.protocol_version, Some(PROTOCOL_VERSION));
+    assert!(client.server_info().is_some_and(|server| !server.is_newer()));
+
+    gateway.set_auto_confirm(false);
+    let (tx, mut confirmed) = mpsc::unbounded_channel();
+    client.on_subscribed(move |channels| tx.send(channels).unwrap()).detach();
+    client.subscribe(SubscribeRequest::default()).await.unwrap();
+    session.send_text(
+        serde_json::json!({
+            "type": "subscribed",
+            "channels": ["pools"],
+            "protocolVersion": PROTOCOL_VERSION + 1,
+            "serverVersion": "2.0.0",
+        })
+        .to_string(),
+    );
+    confirmed.recv().await.unwrap();
+    let server = client.server_info().unwrap();
+    assert!(server.is_newer());
+    assert_eq!(server.server_version.as_deref(), Some("2.0.0"));
+
+    // Message types added by the newer revision are skipped, not counted as errors
+    let (tx, mut blockhashes) = mpsc::unbounded_channel();
+    client.on_blockhash(move |bh| tx.send(bh.slot).unwrap()).detach();
+    session.send(vec![0xEE, 1, 2, 3]);
+    session.send(blockhash(9));
+    assert_eq!(blockhashes.recv().await, Some(Slot(9)));
+    assert_eq!(client.stats().decode_errors, 0);
+}
+
+#[tokio::test]
+async fn test_forward_compat_mode() {
+    let config = Config {
+        forward_compat: true,
+        ..config()
+    };
+    let (_gateway, client, mut session) = connected_client(config).await;
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    client.on_blockhash(move |bh| tx.send(bh.slot).unwrap()).detach();
+    let (tx, mut unknown) = mpsc::unbounded_channel();
+    client.on_unknown_fields(move |fields| tx.send(fields).unwrap()).detach();
+    client.subscribe(SubscribeRequest::default()).await.unwrap();
+    assert_eq!(session.next_subscription().await.unwrap().forward_compat, Some(true));
+
+    session.send(frames::forward_compat(&blockhash(1), &[]));
+    session.send(frames::forward_compat(&blockhash(2), &[0xAB; 8]));
+    assert_eq!(rx.recv().await, Some(Slot(1)));
+    assert_eq!(rx.recv().await, Some(Slot(2)));
+    let fields = UnknownFields {
+        kind: "blockhash",
+        extra: vec![0xAB; 8],
+    };
+    assert_eq!(unknown.recv().await, Some(fields));
+    assert!(unknown.try_recv().is_err());
+
+    let stats = client.stats();
+    assert_eq!((stats.unknown_field_messages, stats.decode_errors), (1, 0));
+}
+
+#[tokio::test]
+async fn test_feed_stats() {
+    let config = Config {
+        feed_stats_window: Some(Duration::from_secs(60)),
+        ..config()
+    };
+    let (_gateway, client, session) = subscribed_client(config).await;
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    client.on_pool_update(move |update| tx.send(update.sequence).unwrap()).detach();
+
+    let update = |sequence, protocol_name| PoolUpdate {
+        sequence,
+        protocol_name,
+        ..PoolUpdate::default()
+    };
+    session.send(frames::pool_update(&update(1, Protocol::Whirlpool)));
+    session.send(frames::pool_update_batch(&[update(2, Protocol::RaydiumClmm), update(3, Protocol::RaydiumClmm)]));
+    for sequence in 1..=3 {
+        assert_eq!(rx.recv().await, Some(sequence));
+    }
+
+    let stats = client.feed_stats().unwrap();
+    assert_eq!(stats.total.updates, 3);
+    assert_eq!(stats.protocols["RaydiumClmm"].updates, 2);
+    assert_eq!(stats.batch_sizes.unwrap().batches, 2);
+}
+
+#[tokio::test]
+async fn test_activity_reports() {
+    let config = Config {
+        activity_report: Some(ActivityConfig::new(Duration::from_secs(60), Duration::from_millis(20))),
+        ..config()
+    };
+    let (_gateway, client, session) = subscribed_client(config).await;
+    let (tx, mut reports) = mpsc::unbounded_channel();
+    client.on_activity(move |report| tx.send(report).unwrap()).detach();
+
+    let update = |pool| PoolUpdate {
+        pool_address: Pubkey::new([pool; 32]),
+        ..PoolUpdate::default()
+    };
+    session.send(frames::pool_update_batch(&[update(1), update(2), update(2)]));
+    let report = loop {
+        let report = reports.recv().await.unwrap();
+        if report.total_updates == 3 {
+            break report;
+        }
+    };
+    assert_eq!((report.pools[0].pool, report.pools[0].updates), (Pubkey::new([2; 32]), 2));
+}
+
+#[tokio::test]
+async fn test_pool_deltas() {
+    let (_gateway, client, session) = subscribed_client(config()).await;
+    let (tx, mut deltas) = mpsc::unbounded_channel();
+    client.on_pool_delta(move |delta| tx.send(delta.token_deltas).unwrap()).detach();
+
+    session.send(frames::pool_update(&amm_update(1, [100, 100])));
+    session.send(frames::pool_update_batch(&[amm_update(2, [120, 80]), amm_update(2, [0, 0])]));
+    // A snapshot rebases instead of reporting the jump
+    session.send(frames::pool_snapshot(&[amm_update(3, [1_000, 1_000])]));
+    session.send(frames::pool_update(&amm_update(4, [990, 1_010])));
+    assert_eq!(deltas.recv().await, Some(vec![20, -20]));
+    assert_eq!(deltas.recv().await, Some(vec![-10, 10]));
+}
+
+#[tokio::test]
+async fn test_min_liquidity() {
+    let (gateway, client, mut session) = connected_client(config()).await;
+    let (tx, mut pools) = mpsc::unbounded_channel();
+    client.on_pool_update(move |update| tx.send(update.slot).unwrap()).detach();
+    let request = SubscribeRequest::builder().pools().min_liquidity_usd(1_000).build().unwrap();
+    client.subscribe(request).await.unwrap();
+    assert_eq!(session.next_subscription().await.unwrap().min_liquidity_usd, Some(1_000));
+
+    // Unpriced pools pass until the price feed values them
+    session.send(frames::pool_update(&amm_update(1, [1, 1])));
+    assert_eq!(pools.recv().await, Some(Slot(1)));
+    session.send(frames::price_update(&PriceEntry {
+        mint: Pubkey::new([1; 32]),
+        usd_price: 150.0,
+        slot: Slot(1),
+        timestamp_ms: TimestampMs(0),
+    }));
+    session.send(frames::pool_update(&amm_update(2, [2_000_000_000, 300_000_000])));
+    session.send(frames::pool_update(&amm_update(3, [10_000_000_000, 1_500_000_000])));
+    assert_eq!(pools.recv().await, Some(Slot(3)));
+
+    // Servers reporting the feature filter on their own
+    gateway.set_auto_confirm(false);
+    client.subscribe(SubscribeRequest::builder().pools().min_liquidity_usd(1_000).build().unwrap()).await.unwrap();
+    session.next_subscription().await.unwrap();
+    session.send_text(
+        serde_json::json!({"type": "subscribed", "channels": ["pools"], "features": ["min_liquidity_usd"]})
+            .to_string(),
+    );
+    session.send(frames::pool_update(&amm_update(4, [2_000_000_000, 300_000_000])));
+    assert_eq!(pools.recv().await, Some(Slot(4)));
+    assert!(client.server_info().unwrap().supports(FEATURE_MIN_LIQUIDITY));
+}
+
+#[tokio::test]
+async fn test_frame_guardrails() {
+    let truncating = Config {
+        decode_limits: DecodeLimits {
+            max_batch_len: 2,
+            ..DecodeLimits::default()
+        },
+        limit_policy: LimitPolicy::Truncate,
+        ..config()
+    };
+    let (_gateway, client, session) = subscribed_client(truncating).await;
+    let (tx, mut updates) = mpsc::unbounded_channel();
+    client.on_pool_update(move |update| tx.send(update.sequence).unwrap()).detach();
+    let (tx, mut errors) = mpsc::unbounded_channel();
+    client.on_error(move |error| tx.send(error).unwrap()).detach();
+
+    let batch: Vec<PoolUpdate> = (1..=3)
+        .map(|sequence| PoolUpdate {
+            sequence,
+            ..PoolUpdate::default()
+        })
+        .collect();
+    session.send(frames::pool_update_batch(&batch));
+    assert_eq!(updates.recv().await, Some(1));
+    assert_eq!(updates.recv().await, Some(2));
+    assert_eq!(errors.recv().await.unwrap(), "Truncated pool_update_batch frame: batch exceeds limit: 3 > 2");
+    assert_eq!(client.stats().oversized_frames, 1);
+
+    // At most two data frames per second
+    let limited = Config {
+        max_frames_per_sec: Some(2),
+        ..config()
+    };
+    let (_gateway, client, session) = subscribed_client(limited).await;
+    let (tx, mut updates) = mpsc::unbounded_channel();
+    client.on_pool_update(move |update| tx.send(update.sequence).unwrap()).detach();
+    let (tx, mut errors) = mpsc::unbounded_channel();
+    client.on_error(move |error| tx.send(error).unwrap()).detach();
+
+    for sequence in 1..=4 {
+        session.send(frames::pool_update(&PoolUpdate {
+            sequence,
+            ..PoolUpdate::default()
+        }));
+    }
+    session.send(frames::error("still delivered"));
+    assert_eq!(updates.recv().await, Some(1));
+    assert_eq!(updates.recv().await, Some(2));
+    assert!(errors.recv().await.unwrap().starts_with("Over 2 data frames/s"));
+    assert_eq!(errors.recv().await.unwrap(), "still delivered");
+    assert!(updates.try_recv().is_err());
+    assert_eq!(client.stats().rate_limited_frames, 2);
+}
+
+#[tokio::test]
+async fn test_pool_silence() {
+    let config = Config {
+        pool_silence_after: Some(Duration::from_millis(50)),
+        ..config()
+    };
+    let (_gateway, client, mut session) = connected_client(config).await;
+    let (tx, mut silent) = mpsc::unbounded_channel();
+    client.on_pool_silent(move |event| tx.send(event).unwrap()).detach();
+    let (quiet, busy) = (Pubkey::new([1; 32]), Pubkey::new([2; 32]));
+    client.set_pool_silence_after(busy, Some(Duration::from_secs(60)));
+    let request = SubscribeRequest {
+        channels: vec!["pools".to_string()],
+        pools: Some(vec![quiet.to_string(), busy.to_string()]),
+        ..SubscribeRequest::default()
+    };
+    client.subscribe(request).await.unwrap();
+    session.next_subscription().await.unwrap();
+
+    let event = silent.recv().await.unwrap();
+    assert_eq!(event.pool, quiet);
+    assert!(event.last_update_age >= Duration::from_millis(50));
+
+    // Reported once until the pool updates again
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert!(silent.try_recv().is_err());
+    session.send(frames::pool_update(&PoolUpdate {
+        pool_address: quiet,
+        ..PoolUpdate::default()
+    }));
+    assert_eq!(silent.recv().await.unwrap().pool, quiet);
+}
+
+#[tokio::test]
+async fn test_quota_errors() {
+    let (gateway, client, session) = connected_client(config()).await;
+    gateway.set_auto_confirm(false);
+    let (tx, mut warnings) = mpsc::unbounded_channel();
+    client.on_quota_warning(move |quota| tx.send(quota).unwrap()).detach();
+
+    let wait = client.subscribe_and_wait(SubscribeRequest::default(), Duration::from_secs(1));
+    let reject = async {
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        session.send(frames::error("Subscription quota exceeded (limit=5, remaining=0, retry_after=60)"));
+    };
+    let (result, ()) = tokio::join!(wait, reject);
+    let err = result.unwrap_err().downcast::<K256Error>().unwrap();
+    assert!(matches!(*err, K256Error::QuotaExceeded(ref quota) if quota.limit == Some(5)));
+    assert_eq!(warnings.recv().await.unwrap().remaining, Some(0));
+
+    // Over quota until the window resets: fail without sending
+    assert!(client.quota().unwrap().is_exhausted());
+    let err = client.subscribe(SubscribeRequest::default()).await.unwrap_err();
+    assert!(matches!(err.downcast_ref::<K256Error>(), Some(K256Error::QuotaExceeded(_))));
+}
+
+#[tokio::test]
+async fn test_reconnect_after_close() {
+    let (gateway, client, first) = subscribed_client(config()).await;
+
+    // Refused attempts are retried with backoff
+    gateway.refuse_connections(1);
+    first.close();
+    let mut second = gateway.accept().await;
+    assert_eq!(second.next_subscription().await, Some(SubscribeRequest::default()));
+    assert_eq!(gateway.connections(), 2);
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    assert_eq!(client.connection_state(), ConnectionState::Subscribed);
+}
+
+#[tokio::test]
+async fn test_disconnect() {
+    let (gateway, client, _session) = connected_client(config()).await;
+    let (tx, mut states) = mpsc::unbounded_channel();
+    client.on_connection_state(move |state| tx.send(state).unwrap()).detach();
+
+    client.disconnect();
+    let closed = ConnectionState::Closed {
+        reason: "closed by client".to_string(),
+    };
+    while states.recv().await != Some(closed.clone()) {}
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    assert_eq!((gateway.connections(), client.connection_state()), (1, closed));
+    let error = client.connect().await.unwrap_err();
+    assert!(matches!(error.downcast_ref(), Some(K256Error::Closed)));
+}
+
+#[tokio::test]
+async fn test_resume_after_reconnect() {
+    let config = Config { resume: true, ..config() };
+    let (gateway, client, mut first) = connected_client(config).await;
+    let (tx, mut sequences) = mpsc::unbounded_channel();
+    let (done_tx, mut done) = mpsc::unbounded_channel();
+    client.on_pool_update(move |update| tx.send(update.sequence).unwrap()).detach();
+    client.on_replay_complete(move |replay| done_tx.send(replay).unwrap()).detach();
+    client.subscribe(SubscribeRequest::default()).await.unwrap();
+
+    let update = |sequence| PoolUpdate { sequence, ..PoolUpdate::default() };
+    assert_eq!(first.next_subscription().await.unwrap().resume_from, None);
+    first.send(frames::pool_update_batch(&[update(5), update(6)]));
+    assert_eq!((sequences.recv().await, sequences.recv().await), (Some(5), Some(6)));
+    assert_eq!(client.last_sequence(), Some(6));
+    first.close();
+
+    let mut second = gateway.accept().await;
+    assert_eq!(second.next_subscription().await.unwrap().resume_from, Some(6));
+    second.send(frames::pool_update(&update(7)));
+    let replayed = r#"{"type":"replay_complete","data":{"resumeFrom":6,"fromSequence":7,"toSequence":7,"replayed":1}}"#;
+    second.send_text(replayed);
+    assert_eq!(sequences.recv().await, Some(7));
+    let replay = done.recv().await.unwrap();
+    assert_eq!(
+        replay,
+        ReplayComplete {
+            resume_from: 6,
+            from_sequence: 7,
+            to_sequence: 7,
+            replayed: 1,
+            truncated: false,
+        }
+    );
+    assert_eq!(replay.missed(), 0);
+}
+
+/// Fields recorded on a span, as ` name=value` pairs.
+struct SpanFields(String);
+
+impl tracing::field::Visit for SpanFields {
+    fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn std::fmt::Debug) {
+        self.0 += &format!(" {}={:?}", field.name(), value);
+    }
+}
+
+/// Logs every closed span as `name < parent: fields`.
+#[derive(Clone, Default)]
+struct SpanLog(Arc<Mutex<Vec<String>>>);
+
+impl<S> tracing_subscriber::Layer<S> for SpanLog
+where
+    S: tracing::Subscriber + for<'a> tracing_subscriber::registry::LookupSpan<'a>,
+{
+    fn on_new_span(
+        &self,
+        attrs: &tracing::span::Attributes<'_>,
+        id: &tracing::span::Id,
+        ctx: tracing_subscriber::layer::Context<'_, S>,
+    ) {
+        let mut fields = SpanFields(String::new());
+        attrs.record(&mut fields);
+        ctx.span(id).unwrap().extensions_mut().insert(fields);
+    }
+
+    fn on_record(
+        &self,
+        id: &tracing::span::Id,
+        values: &tracing::span::Record<'_>,
+        ctx: tracing_subscriber::layer::Context<'_, S>,
+    ) {
+        let span = ctx.span(id).unwrap();
+        values.record(span.extensions_mut().get_mut::<SpanFields>().unwrap());
+    }
+
+    fn on_close(&self, id: tracing::span::Id, ctx: tracing_subscriber::layer::Context<'_, S>) {
+        let span = ctx.span(&id).unwrap();
+        let parent = span.parent().map_or("-", |parent| parent.name());
+        let fields = span.extensions().get::<SpanFields>().unwrap().0.clone();
+        self.0.lock().unwrap().push(format!("{} < {}:{}", span.name(), parent, fields));
+    }
+}
+
+#[tokio::test]
+async fn test_message_spans() {
+    use tracing_subscriber::layer::SubscriberExt;
+
+    let log = SpanLog::default();
+    let _guard = tracing::subscriber::set_default(tracing_subscriber::registry().with(log.clone()));
+    let update = PoolUpdate {
+        slot: Slot(9),
+        pool_address: Pubkey::new([3; 32]),
+        ..PoolUpdate::default()
+    };
+
+    for trace_spans in [None, Some(tracing::Level::DEBUG)] {
+        let config = Config { trace_spans, ..config() };
+        let (_gateway, client, session) = connected_client(config).await;
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        client
+            .on_pool_update(move |_| tx.send(tracing::Span::current().metadata().map(|m| m.name())).unwrap())
+            .detach();
+        session.send(frames::pool_update(&update));
+        let current = rx.recv().await.unwrap();
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        let log = log.0.lock().unwrap().clone();
+
+        if trace_spans.is_none() {
+            assert_eq!(current, None);
+            assert!(log.is_empty(), "{:?}", log);
+            continue;
+        }
+        assert_eq!(current, Some("k256.callback"));
+        let message = format!(
+            "k256.message < k256.connection: msg_type=\"pool_update\" pool={} slot=9",
+            update.pool_address
+        );
+        let expected = [
+            "k256.decode < k256.message:",
+            "k256.callback < k256.message:",
+            "k256.dispatch < k256.message:",
+        ];
+        assert_eq!(log, [&expected[..], &[message.as_str()]].concat());
+    }
+}